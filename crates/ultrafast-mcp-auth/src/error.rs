@@ -1,4 +1,5 @@
 use thiserror::Error;
+use ultrafast_mcp_core::error::{AuthenticationError, MCPError, TransportError};
 
 /// Authentication and authorization errors
 #[derive(Error, Debug)]
@@ -85,6 +86,50 @@ pub enum AuthError {
     },
 }
 
+/// Convert authentication failures into the matching [`MCPError`] category,
+/// keeping the original error reachable through `source()`
+impl From<AuthError> for MCPError {
+    fn from(err: AuthError) -> Self {
+        let oauth = |error: &str, description: String| {
+            MCPError::Authentication(AuthenticationError::OAuthError {
+                error: error.to_string(),
+                description,
+            })
+        };
+
+        match err {
+            AuthError::InvalidCredentials => {
+                MCPError::Authentication(AuthenticationError::InvalidCredentials)
+            }
+            AuthError::ExpiredToken | AuthError::TokenExpired => {
+                MCPError::Authentication(AuthenticationError::TokenExpired)
+            }
+            AuthError::AuthorizationServerError { error } => oauth(&error, String::new()),
+            AuthError::InvalidClient(description) => oauth("invalid_client", description),
+            AuthError::InvalidGrant(description) => oauth("invalid_grant", description),
+            AuthError::InvalidScope(description) => oauth("invalid_scope", description),
+            AuthError::UnsupportedGrantType(description) => {
+                oauth("unsupported_grant_type", description)
+            }
+            AuthError::InvalidRequest(description) => oauth("invalid_request", description),
+            AuthError::UnauthorizedClient => oauth("unauthorized_client", String::new()),
+            AuthError::SerializationError { source } => MCPError::Serialization(source),
+            AuthError::NetworkError(message) => MCPError::Transport(TransportError::Network {
+                message,
+                source: None,
+            }),
+            AuthError::ReqwestError { source } => MCPError::Transport(TransportError::Network {
+                message: source.to_string(),
+                source: Some(Box::new(source)),
+            }),
+            err => MCPError::Authentication(AuthenticationError::Failed {
+                message: err.to_string(),
+                source: Some(Box::new(err)),
+            }),
+        }
+    }
+}
+
 impl PartialEq for AuthError {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -143,3 +188,48 @@ impl PartialEq for AuthError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_auth_error_maps_to_mcp_category() {
+        assert!(matches!(
+            MCPError::from(AuthError::TokenExpired),
+            MCPError::Authentication(AuthenticationError::TokenExpired)
+        ));
+
+        match MCPError::from(AuthError::InvalidGrant("code reused".to_string())) {
+            MCPError::Authentication(AuthenticationError::OAuthError { error, description }) => {
+                assert_eq!(error, "invalid_grant");
+                assert_eq!(description, "code reused");
+            }
+            other => panic!("unexpected mapping: {other:?}"),
+        }
+
+        let serde_err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert!(matches!(
+            MCPError::from(AuthError::from(serde_err)),
+            MCPError::Serialization(_)
+        ));
+    }
+
+    #[test]
+    fn test_auth_error_source_is_preserved() {
+        let err = MCPError::from(AuthError::MissingScope {
+            scope: "tools:call".to_string(),
+        });
+        let MCPError::Authentication(auth) = &err else {
+            panic!("unexpected mapping: {err:?}");
+        };
+        let source = auth.source().expect("source kept");
+        assert_eq!(
+            source.downcast_ref::<AuthError>(),
+            Some(&AuthError::MissingScope {
+                scope: "tools:call".to_string()
+            })
+        );
+    }
+}
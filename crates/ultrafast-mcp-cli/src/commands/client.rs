@@ -136,6 +136,7 @@ async fn add_client(args: AddClientArgs, config: Option<Config>) -> Result<()> {
                 backoff_multiplier: 2.0,
             }),
         },
+        signing: None,
     };
 
     config.clients.insert(args.name.clone(), client_config);
@@ -171,6 +172,9 @@ async fn show_client(args: ShowClientArgs, config: Option<Config>) -> Result<()>
             println!("   Version: {}", client.version);
             println!("   Server: {}", client.server.endpoint);
             println!("   Transport: {}", client.server.transport.transport_type);
+            if let Some(signing) = &client.signing {
+                println!("   Signing key: {}", signing.key_id);
+            }
             // TODO: Show more details
         } else {
             println!("Client '{}' not found.", args.name);
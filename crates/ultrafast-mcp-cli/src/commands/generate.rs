@@ -1,7 +1,10 @@
 use crate::config::Config;
+use crate::typegen::{self, ServerCatalog, TypeLang};
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
 
 /// Generate project scaffolding
 #[derive(Debug, Args)]
@@ -18,9 +21,18 @@ pub struct GenerateArgs {
     #[arg(short, long)]
     pub template: Option<String>,
 
-    /// Output directory
+    /// Output directory, or the output file for `types`
     #[arg(short, long)]
     pub output: Option<std::path::PathBuf>,
+
+    /// Language of `types`: `ts` for a .d.ts file, `zod` for zod schemas
+    #[arg(long, default_value = "ts")]
+    pub lang: String,
+
+    /// Running HTTP server to generate `types` for, e.g.
+    /// http://localhost:8080
+    #[arg(long, value_name = "URL")]
+    pub server: Option<String>,
 }
 
 pub async fn execute(args: GenerateArgs, _config: Option<Config>) -> Result<()> {
@@ -31,6 +43,7 @@ pub async fn execute(args: GenerateArgs, _config: Option<Config>) -> Result<()>
         "resource" => generate_resource(&args).await,
         "client" => generate_client(&args).await,
         "server" => generate_server(&args).await,
+        "types" => generate_types(&args).await,
         _ => {
             anyhow::bail!("Unknown generation type: {}", args.generate_type);
         }
@@ -228,7 +241,7 @@ mod tests {{
     println!("✅ Generated tool template at {file_path}");
     println!("📝 Updated {mod_file_path}");
     println!("\n🔧 To register this tool in your server, add:");
-    println!("   .tool(\"{tool_name}\", tools::{snake_case_name}::{snake_case_name});");
+    println!("   .tool(\"{tool_name}\", tools::{snake_case_name}::{snake_case_name})?");
 
     Ok(())
 }
@@ -1105,3 +1118,277 @@ This project is licensed under the MIT License.
 
     Ok(())
 }
+
+async fn generate_types(args: &GenerateArgs) -> Result<()> {
+    let server = args
+        .server
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("--server is required for types generation"))?;
+    let lang = TypeLang::parse(&args.lang)?;
+
+    println!("🔷 Generating types for: {server}");
+    let catalog = fetch_catalog(server, "mcp-generate-types").await?;
+    let path = match &args.output {
+        Some(output) if output.is_dir() => output.join(lang.default_file_name()),
+        Some(output) => output.clone(),
+        None => lang.default_file_name().into(),
+    };
+    std::fs::write(&path, typegen::render(&catalog, lang))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    println!(
+        "✅ Generated types for {} tools, {} resources and {} resource templates at {}",
+        catalog.tools.len(),
+        catalog.resources.len(),
+        catalog.resource_templates.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// A connection to a server that the catalog requests are sent over
+trait CatalogSession {
+    /// Result of the request, failing on an error response
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value>;
+
+    async fn notify(&mut self, method: &str) -> Result<()>;
+}
+
+/// A Streamable HTTP session for the catalog requests
+struct HttpSession {
+    client: reqwest::Client,
+    url: String,
+    session_id: Option<String>,
+    next_id: u64,
+}
+
+impl CatalogSession for HttpSession {
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let response = self
+            .post(json!({
+                "jsonrpc": "2.0",
+                "id": self.next_id,
+                "method": method,
+                "params": params
+            }))
+            .await
+            .with_context(|| format!("Failed to send {method} request"))?;
+        if let Some(session_id) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|value| value.to_str().ok())
+        {
+            self.session_id = Some(session_id.to_string());
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("{method} failed with status: {}", response.status());
+        }
+        let response: Value = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {method} response"))?;
+        response_result(method, response)
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<()> {
+        self.post(json!({ "jsonrpc": "2.0", "method": method }))
+            .await
+            .with_context(|| format!("Failed to send {method} notification"))?;
+        Ok(())
+    }
+}
+
+impl HttpSession {
+    async fn post(&self, message: Value) -> reqwest::Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .header("MCP-Protocol-Version", "2025-06-18")
+            .json(&message);
+        if let Some(session_id) = &self.session_id {
+            request = request.header("mcp-session-id", session_id);
+        }
+        request.send().await
+    }
+}
+
+/// A server started as a child process, spoken to over its stdin/stdout
+struct StdioSession {
+    child: tokio::process::Child,
+    stdin: tokio::process::ChildStdin,
+    stdout: tokio::io::Lines<tokio::io::BufReader<tokio::process::ChildStdout>>,
+    next_id: u64,
+}
+
+impl StdioSession {
+    fn spawn(command: &str, args: &[String]) -> Result<Self> {
+        let mut child = tokio::process::Command::new(command)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {command}"))?;
+        let stdin = child.stdin.take().context("No stdin for server process")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("No stdout from server process")?;
+        Ok(Self {
+            child,
+            stdin,
+            stdout: tokio::io::AsyncBufReadExt::lines(tokio::io::BufReader::new(stdout)),
+            next_id: 0,
+        })
+    }
+
+    async fn send(&mut self, message: Value) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut line = serde_json::to_vec(&message)?;
+        line.push(b'\n');
+        self.stdin
+            .write_all(&line)
+            .await
+            .context("Failed to write to server stdin")
+    }
+}
+
+impl CatalogSession for StdioSession {
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        // Skip notifications and server requests until the response arrives
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await
+                .context("Failed to read server response")?
+                .ok_or_else(|| anyhow::anyhow!("Server closed its stdout"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: Value = serde_json::from_str(&line)
+                .with_context(|| format!("Failed to parse {method} response"))?;
+            if message.get("id") == Some(&json!(id)) && message.get("method").is_none() {
+                return response_result(method, message);
+            }
+        }
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method }))
+            .await
+    }
+}
+
+/// The `result` of a JSON-RPC response to `method`
+fn response_result(method: &str, mut response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error") {
+        anyhow::bail!("{method} failed: {error}");
+    }
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| anyhow::anyhow!("Invalid {method} response: missing result"))
+}
+
+/// Every item of a paginated `*/list` method
+async fn list_all<T: DeserializeOwned>(
+    session: &mut impl CatalogSession,
+    method: &str,
+    field: &str,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let mut page = session.request(method, params).await?;
+        let page_items = page.get_mut(field).map(Value::take).unwrap_or_default();
+        items.extend(
+            serde_json::from_value::<Vec<T>>(page_items)
+                .with_context(|| format!("Invalid {method} response"))?,
+        );
+        cursor = page
+            .get("nextCursor")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if cursor.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+/// Tools, resources and prompts advertised by the HTTP server at `server`
+pub(crate) async fn fetch_catalog(server: &str, client_name: &str) -> Result<ServerCatalog> {
+    let mut session = HttpSession {
+        client: reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?,
+        url: format!("{}/mcp", server.trim_end_matches('/')),
+        session_id: None,
+        next_id: 0,
+    };
+    fetch_session_catalog(&mut session, client_name).await
+}
+
+/// Tools, resources and prompts advertised by the server `command` starts
+pub(crate) async fn fetch_stdio_catalog(
+    command: &str,
+    args: &[String],
+    client_name: &str,
+) -> Result<ServerCatalog> {
+    let mut session = StdioSession::spawn(command, args)?;
+    let catalog = fetch_session_catalog(&mut session, client_name).await;
+    let _ = session.child.kill().await;
+    catalog
+}
+
+async fn fetch_session_catalog(
+    session: &mut impl CatalogSession,
+    client_name: &str,
+) -> Result<ServerCatalog> {
+    let initialized = session
+        .request(
+            "initialize",
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": { "name": client_name, "version": env!("CARGO_PKG_VERSION") }
+            }),
+        )
+        .await?;
+    session.notify("notifications/initialized").await?;
+
+    let capabilities = &initialized["capabilities"];
+    let mut catalog = ServerCatalog {
+        server: initialized["serverInfo"]["name"].as_str().map(|name| {
+            match initialized["serverInfo"]["version"].as_str() {
+                Some(version) => format!("{name} {version}"),
+                None => name.to_string(),
+            }
+        }),
+        ..Default::default()
+    };
+    if capabilities.get("tools").is_some() {
+        catalog.tools = list_all(session, "tools/list", "tools").await?;
+    }
+    if capabilities.get("resources").is_some() {
+        catalog.resources = list_all(session, "resources/list", "resources").await?;
+        catalog.resource_templates =
+            list_all(session, "resources/templates/list", "resourceTemplates").await?;
+    }
+    if capabilities.get("prompts").is_some() {
+        catalog.prompts = list_all(session, "prompts/list", "prompts").await?;
+    }
+    Ok(catalog)
+}
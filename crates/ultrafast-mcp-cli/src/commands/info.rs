@@ -1,7 +1,8 @@
 use crate::config::Config;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
+use ultrafast_mcp_server::BuildInfo;
 
 /// Show project information
 #[derive(Debug, Args)]
@@ -13,9 +14,18 @@ pub struct InfoArgs {
     /// Output format
     #[arg(long, default_value = "human")]
     pub format: String,
+
+    /// Show the build of a running HTTP server instead, e.g.
+    /// http://localhost:8080
+    #[arg(long, value_name = "URL")]
+    pub server: Option<String>,
 }
 
 pub async fn execute(args: InfoArgs, config: Option<Config>) -> Result<()> {
+    if let Some(server) = &args.server {
+        return show_server_build(server, &args.format).await;
+    }
+
     println!("{}", "MCP Project Information".green().bold());
     println!();
 
@@ -79,3 +89,48 @@ pub async fn execute(args: InfoArgs, config: Option<Config>) -> Result<()> {
 
     Ok(())
 }
+
+/// Fetch and print `GET /x-ultrafast/info` of a running server
+async fn show_server_build(server: &str, format: &str) -> Result<()> {
+    let base = server.trim_end_matches('/').trim_end_matches("/mcp");
+    let url = format!("{base}/x-ultrafast/info");
+    let response = reqwest::Client::new()
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to connect to {url}"))?;
+    if !response.status().is_success() {
+        anyhow::bail!("{url} responded with status {}", response.status());
+    }
+    let info: BuildInfo = serde_json::from_str(&response.text().await?)
+        .context("Server returned an invalid build info document")?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("{}", "MCP Server Build".green().bold());
+    println!();
+    println!("📡 {} {}", info.server_name, info.server_version);
+    println!("   Crate version: {}", info.crate_version);
+    println!(
+        "   Git revision: {}",
+        info.git_sha.as_deref().unwrap_or("unknown")
+    );
+    println!(
+        "   Features: {}",
+        if info.features.is_empty() {
+            "none".to_string()
+        } else {
+            info.features.join(", ")
+        }
+    );
+    println!(
+        "   Protocol versions: {}",
+        info.protocol_versions.join(", ")
+    );
+    println!("   Uptime: {}s", info.uptime_secs);
+
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use clap::Args;
+use colored::*;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Upgrade a project to the current ultrafast-mcp APIs and configuration
+#[derive(Debug, Args)]
+pub struct MigrateArgs {
+    /// Project directory to migrate
+    #[arg(value_name = "PATH")]
+    pub path: Option<PathBuf>,
+
+    /// Report what would change without writing any files
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Which files a rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Rust,
+    Manifest,
+    Config,
+}
+
+impl FileKind {
+    fn of(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "Cargo.toml" => Some(Self::Manifest),
+            name if name.ends_with(".rs") => Some(Self::Rust),
+            name if name.ends_with(".toml") => Some(Self::Config),
+            _ => None,
+        }
+    }
+}
+
+/// A mechanical rewrite, applied with [`Regex::replace_all`]
+struct Rewrite {
+    kind: FileKind,
+    pattern: &'static str,
+    replacement: &'static str,
+    description: &'static str,
+}
+
+/// A pattern that needs a person to update it
+struct ManualCheck {
+    kind: FileKind,
+    pattern: &'static str,
+    instructions: &'static str,
+}
+
+const REWRITES: &[Rewrite] = &[
+    Rewrite {
+        kind: FileKind::Rust,
+        pattern: r"\bultrafast_mcp_client::Client\b",
+        replacement: "ultrafast_mcp_client::UltraFastClient",
+        description: "`ultrafast_mcp_client::Client` is now `UltraFastClient`",
+    },
+    Rewrite {
+        kind: FileKind::Rust,
+        pattern: r#""\$/cancelRequest""#,
+        replacement: r#""notifications/cancelled""#,
+        description: "cancellations are sent as `notifications/cancelled`",
+    },
+    Rewrite {
+        kind: FileKind::Config,
+        pattern: r#"(transport_type\s*=\s*)"sse""#,
+        replacement: r#"${1}"http""#,
+        description: "SSE transports are configured as Streamable HTTP (`http`)",
+    },
+];
+
+const MANUAL_CHECKS: &[ManualCheck] = &[
+    ManualCheck {
+        kind: FileKind::Rust,
+        pattern: r"\bClientBuilder\b",
+        instructions: "Replace `ClientBuilder` with `UltraFastClient::new(client_info, capabilities)`",
+    },
+    ManualCheck {
+        kind: FileKind::Rust,
+        pattern: r"\b(SseTransport|connect_sse|run_sse)\b",
+        instructions: "The SSE transport was replaced by Streamable HTTP; use `connect_streamable_http` on clients and `run_streamable_http` on servers",
+    },
+    ManualCheck {
+        kind: FileKind::Manifest,
+        pattern: r#"ultrafast-mcp-server\s*=.*"oauth""#,
+        instructions: "`ultrafast-mcp-server` no longer has an `oauth` feature; enable `oauth` on `ultrafast-mcp` instead",
+    },
+];
+
+/// Result of migrating one file's contents
+#[derive(Debug, Default, PartialEq)]
+struct FileMigration {
+    content: String,
+    changes: Vec<String>,
+    manual_steps: Vec<(usize, &'static str)>,
+}
+
+pub async fn execute(args: MigrateArgs, _config: Option<Config>) -> Result<()> {
+    let root = match args.path {
+        Some(path) => path,
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    println!("{}", "Migrating MCP project...".green().bold());
+    println!("📁 Project: {}", root.display());
+    if args.dry_run {
+        println!("   (dry run, no files will be written)");
+    }
+    println!();
+
+    let mut files = Vec::new();
+    collect_files(&root, &mut files)?;
+
+    let mut changed_files = 0;
+    let mut manual_steps = 0;
+    for path in files {
+        let Some(kind) = FileKind::of(&path) else {
+            continue;
+        };
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let migration = migrate_content(kind, &content);
+        let display = path.strip_prefix(&root).unwrap_or(&path).display();
+
+        if !migration.changes.is_empty() {
+            changed_files += 1;
+            println!("✏️  {display}");
+            for change in &migration.changes {
+                println!("   {change}");
+            }
+            if !args.dry_run {
+                fs::write(&path, &migration.content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+            }
+        }
+        for (line, instructions) in &migration.manual_steps {
+            manual_steps += 1;
+            println!("⚠️  {display}:{line}: {instructions}");
+        }
+    }
+
+    println!();
+    let verb = if args.dry_run {
+        "would be updated"
+    } else {
+        "updated"
+    };
+    println!("📊 {changed_files} file(s) {verb}, {manual_steps} manual step(s)");
+    if manual_steps > 0 {
+        println!(
+            "{}",
+            "Review the manual steps above, then run `cargo check`.".yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Files under `dir`, skipping build output and hidden directories
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') {
+                collect_files(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(())
+}
+
+fn migrate_content(kind: FileKind, content: &str) -> FileMigration {
+    let mut migration = FileMigration {
+        content: content.to_string(),
+        ..Default::default()
+    };
+
+    for rewrite in REWRITES.iter().filter(|rule| rule.kind == kind) {
+        let pattern = Regex::new(rewrite.pattern).expect("valid migration pattern");
+        let count = pattern.find_iter(&migration.content).count();
+        if count > 0 {
+            migration.content = pattern
+                .replace_all(&migration.content, rewrite.replacement)
+                .into_owned();
+            migration
+                .changes
+                .push(format!("{} ({count} occurrence(s))", rewrite.description));
+        }
+    }
+
+    if kind == FileKind::Manifest {
+        let version = Regex::new(
+            r#"(ultrafast-mcp[a-z-]*\s*=\s*(?:\{[^}\n]*?version\s*=\s*)?")=?([0-9][^"]*)""#,
+        )
+        .expect("valid version pattern");
+        let current = env!("CARGO_PKG_VERSION");
+        let outdated = version
+            .captures_iter(&migration.content)
+            .filter(|captures| &captures[2] != current)
+            .count();
+        if outdated > 0 {
+            migration.content = version
+                .replace_all(&migration.content, |captures: &regex::Captures| {
+                    format!("{}{current}\"", &captures[1])
+                })
+                .into_owned();
+            migration.changes.push(format!(
+                "ultrafast-mcp dependencies set to version {current} ({outdated} occurrence(s))"
+            ));
+        }
+    }
+
+    for check in MANUAL_CHECKS.iter().filter(|check| check.kind == kind) {
+        let pattern = Regex::new(check.pattern).expect("valid migration pattern");
+        for (index, line) in migration.content.lines().enumerate() {
+            if pattern.is_match(line) {
+                migration.manual_steps.push((index + 1, check.instructions));
+            }
+        }
+    }
+    migration.manual_steps.sort();
+    migration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_rewrites_and_reports() {
+        let source = r#"use ultrafast_mcp_client::Client;
+use ultrafast_mcp_client::ClientBuilder;
+
+async fn cancel(client: &ultrafast_mcp_client::Client) {
+    client.send_notification("$/cancelRequest", None).await.unwrap();
+}
+"#;
+        let migration = migrate_content(FileKind::Rust, source);
+        assert!(
+            migration
+                .content
+                .contains("client: &ultrafast_mcp_client::UltraFastClient")
+        );
+        assert!(migration.content.contains("\"notifications/cancelled\""));
+        assert_eq!(migration.changes.len(), 2);
+        assert_eq!(migration.manual_steps.len(), 1);
+        assert_eq!(migration.manual_steps[0].0, 2);
+
+        let manifest = r#"[dependencies]
+ultrafast-mcp = { version = "0.1.0", features = ["http"] }
+ultrafast-mcp-server = { version = "=0.1.0", features = ["oauth"] }
+serde = "1.0"
+"#;
+        let migration = migrate_content(FileKind::Manifest, manifest);
+        let current = env!("CARGO_PKG_VERSION");
+        assert!(
+            migration
+                .content
+                .contains(&format!("ultrafast-mcp = {{ version = \"{current}\""))
+        );
+        assert!(migration.content.contains("serde = \"1.0\""));
+        assert_eq!(migration.manual_steps.len(), 1);
+
+        let unchanged = migrate_content(FileKind::Manifest, &migration.content);
+        assert!(unchanged.changes.is_empty());
+    }
+}
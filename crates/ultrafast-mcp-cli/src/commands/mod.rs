@@ -5,6 +5,7 @@ pub mod dev;
 pub mod generate;
 pub mod info;
 pub mod init;
+pub mod migrate;
 pub mod server;
 pub mod test;
 pub mod validate;
@@ -17,6 +18,7 @@ pub use dev::DevArgs;
 pub use generate::GenerateArgs;
 pub use info::InfoArgs;
 pub use init::InitArgs;
+pub use migrate::MigrateArgs;
 pub use server::ServerArgs;
 pub use test::TestArgs;
 pub use validate::ValidateArgs;
@@ -139,6 +139,7 @@ async fn add_server(args: AddServerArgs, config: Option<Config>) -> Result<()> {
         },
         tools: Vec::new(),
         resources: Vec::new(),
+        signing: None,
     };
 
     // Add to configuration
@@ -184,6 +185,9 @@ async fn show_server(args: ShowServerArgs, config: Option<Config>) -> Result<()>
             println!("   Name: {}", server.name);
             println!("   Version: {}", server.version);
             println!("   Transport: {}", server.transport.transport_type);
+            if let Some(signing) = &server.signing {
+                println!("   Signing key: {}", signing.key_id);
+            }
             // TODO: Show more details
         } else {
             println!("Server '{}' not found.", args.name);
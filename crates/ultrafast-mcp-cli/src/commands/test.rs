@@ -1,4 +1,6 @@
+use crate::commands::generate::{fetch_catalog, fetch_stdio_catalog};
 use crate::config::Config;
+use crate::testgen::{self, SmokeTarget};
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
@@ -28,9 +30,22 @@ pub struct TestArgs {
     /// Verbose output
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Write a smoke test file covering every tool, resource and prompt of
+    /// the server instead of running the tests
+    #[arg(long)]
+    pub generate: bool,
+
+    /// File the `--generate`d tests are written to
+    #[arg(short, long, default_value = "tests/mcp_smoke.rs")]
+    pub output: std::path::PathBuf,
 }
 
 pub async fn execute(args: TestArgs, config: Option<Config>) -> Result<()> {
+    if args.generate {
+        return generate_tests(&args).await;
+    }
+
     println!("{}", "Testing MCP connections...".green().bold());
 
     let timeout_duration = Duration::from_secs(args.timeout);
@@ -52,6 +67,58 @@ pub async fn execute(args: TestArgs, config: Option<Config>) -> Result<()> {
     }
 }
 
+async fn generate_tests(args: &TestArgs) -> Result<()> {
+    let server = args
+        .server
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("--server is required to generate tests"))?;
+    let target = SmokeTarget::parse(server, &args.transport)?;
+
+    println!("🧪 Generating smoke tests for: {server}");
+    let fetch = async {
+        match &target {
+            SmokeTarget::Stdio { command, args } => {
+                fetch_stdio_catalog(command, args, "mcp-test-generate").await
+            }
+            SmokeTarget::Http { url } => fetch_catalog(url, "mcp-test-generate").await,
+        }
+    };
+    let catalog = timeout(Duration::from_secs(args.timeout), fetch)
+        .await
+        .with_context(|| format!("Timed out after {} seconds", args.timeout))??;
+
+    if let Some(parent) = args
+        .output
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&args.output, testgen::render(&catalog, &target))
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    println!(
+        "✅ Generated smoke tests for {} tools, {} resources and {} prompts at {}",
+        catalog.tools.len(),
+        catalog.resources.len(),
+        catalog.prompts.len(),
+        args.output.display()
+    );
+    println!(
+        "   Run them with: cargo test --test {}",
+        test_target(&args.output)
+    );
+    Ok(())
+}
+
+/// Name of the integration test target the file at `path` becomes
+fn test_target(path: &std::path::Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
 async fn run_tests(args: &TestArgs, config: Option<Config>) -> Result<()> {
     if let Some(test_name) = &args.test {
         run_specific_test(test_name, args, config).await
@@ -264,24 +331,13 @@ async fn test_stdio_connection(args: &TestArgs) -> Result<()> {
         }
 
         // Read response with timeout
-        let response = tokio::time::timeout(Duration::from_secs(5), async {
-            if let Some(stdout) = server_process.stdout.as_mut() {
-                let mut buffer = String::new();
-                let mut reader = std::io::BufReader::new(stdout);
-                std::io::BufRead::read_line(&mut reader, &mut buffer)
-                    .context("Failed to read server response")?;
-                Ok(buffer)
-            } else {
-                anyhow::bail!("No stdout from server process")
-            }
-        })
-        .await
-        .context("Timeout waiting for server response")?
-        .context("Failed to read server response")?;
-
-        // Parse and validate response
-        let response_json: serde_json::Value =
-            serde_json::from_str(&response).context("Failed to parse server response as JSON")?;
+        let mut reader = std::io::BufReader::new(
+            server_process
+                .stdout
+                .take()
+                .context("No stdout from server process")?,
+        );
+        let response_json = read_stdio_response(&mut reader, 1).await?;
 
         if let Some(result) = response_json.get("result") {
             if let Some(protocol_version) = result.get("protocolVersion") {
@@ -294,10 +350,33 @@ async fn test_stdio_connection(args: &TestArgs) -> Result<()> {
             anyhow::bail!("Invalid initialization response: missing result");
         }
 
+        // List the tools along with their behavior hints
+        if let Some(stdin) = server_process.stdin.as_mut() {
+            for message in [
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/initialized"
+                }),
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "tools/list",
+                    "params": {}
+                }),
+            ] {
+                let message_str = serde_json::to_string(&message)?;
+                stdin
+                    .write_all((message_str + "\n").as_bytes())
+                    .context("Failed to write to server stdin")?;
+            }
+        }
+        let tools_response = read_stdio_response(&mut reader, 2).await?;
+        print_tools(&tools_response);
+
         // Send shutdown request
         let shutdown_request = serde_json::json!({
             "jsonrpc": "2.0",
-            "id": 2,
+            "id": 3,
             "method": "shutdown",
             "params": {}
         });
@@ -318,6 +397,58 @@ async fn test_stdio_connection(args: &TestArgs) -> Result<()> {
     Ok(())
 }
 
+/// Read lines from the server until the response to request `id` arrives,
+/// skipping notifications and server requests in between
+async fn read_stdio_response(
+    reader: &mut std::io::BufReader<std::process::ChildStdout>,
+    id: u64,
+) -> Result<serde_json::Value> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let mut buffer = String::new();
+            let read = std::io::BufRead::read_line(reader, &mut buffer)
+                .context("Failed to read server response")?;
+            if read == 0 {
+                anyhow::bail!("Server closed its stdout");
+            }
+            let message: serde_json::Value =
+                serde_json::from_str(&buffer).context("Failed to parse server response as JSON")?;
+            if message.get("id") == Some(&serde_json::json!(id)) && message.get("method").is_none()
+            {
+                return Ok(message);
+            }
+        }
+    })
+    .await
+    .context("Timeout waiting for server response")?
+}
+
+/// Print the tools of a `tools/list` response with their annotation hints
+fn print_tools(response: &serde_json::Value) {
+    if let Some(error) = response.get("error") {
+        println!("   ⚠️  tools/list failed: {error}");
+        return;
+    }
+    let tools: Vec<ultrafast_mcp_core::types::tools::Tool> = response
+        .get("result")
+        .and_then(|result| result.get("tools"))
+        .and_then(|tools| serde_json::from_value(tools.clone()).ok())
+        .unwrap_or_default();
+    println!("   🔧 Tools: {}", tools.len());
+    for tool in &tools {
+        let hints = tool
+            .annotations
+            .as_ref()
+            .map(|annotations| annotations.hint_labels())
+            .unwrap_or_default();
+        if hints.is_empty() {
+            println!("      - {}", tool.name);
+        } else {
+            println!("      - {} [{}]", tool.name, hints.join(", ").dimmed());
+        }
+    }
+}
+
 async fn test_http_connection(args: &TestArgs) -> Result<()> {
     if let Some(server) = &args.server {
         println!("   Testing connection to: {server}");
@@ -375,6 +506,11 @@ async fn test_http_connection(args: &TestArgs) -> Result<()> {
             .send()
             .await
             .context("Failed to send MCP initialization request")?;
+        let session_id = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
 
         if !response.status().is_success() {
             anyhow::bail!(
@@ -398,6 +534,38 @@ async fn test_http_connection(args: &TestArgs) -> Result<()> {
         } else {
             anyhow::bail!("Invalid MCP response: missing result");
         }
+
+        // List the tools along with their behavior hints
+        let post = |message: serde_json::Value| {
+            let mut request = client
+                .post(&mcp_url)
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json, text/event-stream")
+                .header("MCP-Protocol-Version", "2025-06-18")
+                .json(&message);
+            if let Some(session_id) = &session_id {
+                request = request.header("mcp-session-id", session_id);
+            }
+            request.send()
+        };
+        post(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        }))
+        .await
+        .context("Failed to send initialized notification")?;
+        let tools_response: serde_json::Value = post(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/list",
+            "params": {}
+        }))
+        .await
+        .context("Failed to send tools/list request")?
+        .json()
+        .await
+        .context("Failed to parse tools/list response")?;
+        print_tools(&tools_response);
     } else {
         anyhow::bail!("Server URL required for HTTP test");
     }
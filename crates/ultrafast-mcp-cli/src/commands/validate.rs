@@ -1,10 +1,15 @@
+use crate::commands::generate::{fetch_catalog, fetch_stdio_catalog};
 use crate::config::Config;
+use crate::testgen::SmokeTarget;
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
+use ultrafast_mcp_core::schema::{SchemaLintConfig, SchemaLintReport, SchemaLintRule};
+use ultrafast_mcp_core::types::tools::Tool;
+use ultrafast_mcp_transport::signing::MessageSigner;
 
 /// Validate MCP schemas and configurations
 #[derive(Debug, Args)]
@@ -28,6 +33,19 @@ pub struct ValidateArgs {
     /// Fix issues automatically where possible
     #[arg(long)]
     pub fix: bool,
+
+    /// Lint the tool schemas of a server instead of validating a project:
+    /// the command starting it, or its URL with `--transport http`
+    #[arg(long)]
+    pub server: Option<String>,
+
+    /// Transport of `--server`
+    #[arg(long, default_value = "stdio")]
+    pub transport: String,
+
+    /// Largest size of a tool schema in bytes when linting `--server`
+    #[arg(long)]
+    pub max_schema_size: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -55,6 +73,10 @@ enum ValidationLevel {
 }
 
 pub async fn execute(args: ValidateArgs, config: Option<Config>) -> Result<()> {
+    if let Some(server) = &args.server {
+        return lint_server(server, &args).await;
+    }
+
     println!("{}", "Validating MCP project...".green().bold());
 
     let path = match args.path {
@@ -95,6 +117,76 @@ pub async fn execute(args: ValidateArgs, config: Option<Config>) -> Result<()> {
     Ok(())
 }
 
+/// Lint the tool schemas of `server`, failing on denied issues, or on any
+/// issue in strict mode
+async fn lint_server(server: &str, args: &ValidateArgs) -> Result<()> {
+    println!("{}", "Linting tool schemas...".green().bold());
+    println!("🔌 Server: {server}");
+
+    let catalog = match SmokeTarget::parse(server, &args.transport)? {
+        SmokeTarget::Stdio { command, args } => {
+            fetch_stdio_catalog(&command, &args, "mcp-validate").await?
+        }
+        SmokeTarget::Http { url } => fetch_catalog(&url, "mcp-validate").await?,
+    };
+    let mut lint = SchemaLintConfig::default();
+    if let Some(max_schema_size) = args.max_schema_size {
+        lint = lint.with_max_schema_size(max_schema_size);
+    }
+    let report = lint.lint_tools(&catalog.tools);
+
+    let result = lint_result(&catalog.tools, &report, args.strict);
+    output_results(&result, args)?;
+    if result.errors > 0 {
+        anyhow::bail!("Schema linting failed with {} error(s)", result.errors);
+    }
+    Ok(())
+}
+
+/// The issues of a lint `report` on `tools`, with the tools without any
+/// counted as passed
+fn lint_result(tools: &[Tool], report: &SchemaLintReport, strict: bool) -> ValidationResult {
+    let mut result = ValidationResult {
+        passed: tools
+            .iter()
+            .filter(|tool| report.issues.iter().all(|issue| issue.tool != tool.name))
+            .count(),
+        warnings: 0,
+        errors: 0,
+        issues: Vec::new(),
+    };
+    for issue in &report.issues {
+        let level = if issue.is_error() || strict {
+            result.errors += 1;
+            ValidationLevel::Error
+        } else {
+            result.warnings += 1;
+            ValidationLevel::Warning
+        };
+        let location = if issue.path.is_empty() {
+            format!("tool '{}'", issue.tool)
+        } else {
+            format!("tool '{}' at {}", issue.tool, issue.path)
+        };
+        let suggestion = match issue.rule {
+            SchemaLintRule::SchemaTooLarge => "Split the tool, or move rarely used options out",
+            SchemaLintRule::UnsupportedKeyword => {
+                "Use keywords every host supports, and check the rest in the tool"
+            }
+            SchemaLintRule::MissingDescription => {
+                "Add a description, such as a doc comment on the field"
+            }
+        };
+        result.issues.push(ValidationIssue {
+            level,
+            file: None,
+            message: format!("{location}: {} [{}]", issue.message, issue.rule.as_str()),
+            suggestion: Some(suggestion.to_string()),
+        });
+    }
+    result
+}
+
 async fn validate_project_structure(
     path: &Path,
     _args: &ValidateArgs,
@@ -474,12 +566,45 @@ fn validate_loaded_config(config: &Config, result: &mut ValidationResult) -> Res
         }
     }
 
+    let signing_issues = signing_issues(config);
+    result.errors += signing_issues.len();
+    result.issues.extend(signing_issues);
+
     result.passed += 1;
     println!("   ✅ Loaded configuration is valid");
 
     Ok(())
 }
 
+/// Signing keys of servers and clients that fail to load, including private
+/// keys read from the environment
+fn signing_issues(config: &Config) -> Vec<ValidationIssue> {
+    let servers = config
+        .servers
+        .iter()
+        .map(|(name, server)| (format!("server '{name}'"), &server.signing));
+    let clients = config
+        .clients
+        .iter()
+        .map(|(name, client)| (format!("client '{name}'"), &client.signing));
+    servers
+        .chain(clients)
+        .filter_map(|(owner, signing)| {
+            let error = MessageSigner::from_config(signing.as_ref()?).err()?;
+            Some(ValidationIssue {
+                level: ValidationLevel::Error,
+                file: None,
+                message: format!("Signing keys of {owner} do not load: {error}"),
+                suggestion: Some(
+                    "Use base64 encoded 32-byte Ed25519 keys, and set the variable named by \
+                     private_key_env"
+                        .to_string(),
+                ),
+            })
+        })
+        .collect()
+}
+
 fn output_results(result: &ValidationResult, args: &ValidateArgs) -> Result<()> {
     println!("\n📊 Validation Results:");
 
@@ -682,3 +807,88 @@ fn fix_wildcard_versions(content: &str) -> Result<String> {
 
     Ok(fixed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lint_issues_become_validation_issues() {
+        let tools = [
+            Tool::new(
+                "search".to_string(),
+                "Search the docs".to_string(),
+                json!({"type": "object", "properties": {"query": {"type": "string"}}}),
+            ),
+            Tool::new(
+                "ping".to_string(),
+                "Ping".to_string(),
+                json!({"type": "object"}),
+            ),
+        ];
+        let report = SchemaLintConfig::default().lint_tools(&tools);
+
+        let result = lint_result(&tools, &report, false);
+        assert_eq!((result.passed, result.warnings, result.errors), (1, 1, 0));
+        assert_eq!(
+            result.issues[0].message,
+            "tool 'search' at /inputSchema/properties/query: property 'query' has no \
+             description [missing_description]"
+        );
+
+        let strict = lint_result(&tools, &report, true);
+        assert_eq!((strict.warnings, strict.errors), (0, 1));
+    }
+
+    #[test]
+    fn test_signing_keys_that_do_not_load_are_errors() {
+        use crate::config::{ServerCapabilities, ServerConfig, TransportConfig};
+        use ultrafast_mcp_transport::signing::{SigningConfig, SigningKeypair};
+
+        let server = |signing| ServerConfig {
+            name: "server".to_string(),
+            version: "1.0.0".to_string(),
+            capabilities: ServerCapabilities {
+                experimental: Default::default(),
+                logging: None,
+                prompts: None,
+                resources: None,
+                tools: None,
+            },
+            transport: TransportConfig {
+                transport_type: "stdio".to_string(),
+                config: Default::default(),
+            },
+            tools: Vec::new(),
+            resources: Vec::new(),
+            signing: Some(signing),
+        };
+        let mut config = Config::default();
+        config.servers.insert(
+            "signed".to_string(),
+            server(
+                SigningConfig::new("signed")
+                    .with_private_key(SigningKeypair::generate().private_key),
+            ),
+        );
+        config.servers.insert(
+            "unset".to_string(),
+            server(
+                SigningConfig::new("unset")
+                    .with_private_key_env("ULTRAFAST_MCP_TEST_UNSET_SIGNING_KEY"),
+            ),
+        );
+        config.servers.insert(
+            "malformed".to_string(),
+            server(SigningConfig::new("malformed").with_private_key("not a key")),
+        );
+
+        let mut owners: Vec<_> = signing_issues(&config)
+            .into_iter()
+            .map(|issue| issue.message.split('\'').nth(1).unwrap().to_string())
+            .collect();
+        owners.sort();
+        assert_eq!(owners, ["malformed", "unset"]);
+    }
+}
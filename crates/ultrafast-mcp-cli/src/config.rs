@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use ultrafast_mcp_transport::signing::SigningConfig;
 
 /// CLI configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +51,9 @@ pub struct ServerConfig {
     pub tools: Vec<ToolConfig>,
     /// Resource configurations
     pub resources: Vec<ResourceConfig>,
+    /// Message signing keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
 }
 
 /// Server capabilities configuration
@@ -154,6 +158,9 @@ pub struct ClientConfig {
     pub capabilities: ClientCapabilities,
     /// Server connection settings
     pub server: ServerConnectionConfig,
+    /// Message signing keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
 }
 
 /// Client capabilities configuration
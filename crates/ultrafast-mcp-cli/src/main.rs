@@ -159,6 +159,8 @@
 //!   --client <CONFIG>        Client configuration file
 //!   --timeout <SECONDS>      Test timeout in seconds
 //!   --verbose                Verbose output
+//!   --generate               Write smoke tests for every tool, resource and prompt
+//!   --output <PATH>          File of the generated tests [default: tests/mcp_smoke.rs]
 //! ```
 //!
 //! #### `mcp validate` - Validate Schemas
@@ -184,6 +186,18 @@
 //! Options:
 //!   --format <FORMAT>        Output format (text, json, yaml)
 //!   --detailed               Show detailed information
+//!   --server <URL>           Show the build of a running HTTP server
+//! ```
+//!
+//! #### `mcp migrate` - Upgrade a Project
+//! Rewrites renamed APIs, dependency versions and configuration for the
+//! current release, and lists the changes that need a manual edit.
+//!
+//! ```bash
+//! mcp migrate [PATH] [OPTIONS]
+//!
+//! Options:
+//!   --dry-run                Report changes without writing files
 //! ```
 //!
 //! #### `mcp generate` - Generate Code
@@ -198,6 +212,12 @@
 //!   tool                     Generate tool implementation
 //!   resource                 Generate resource handler
 //!   prompt                   Generate prompt template
+//!   types                    Generate TypeScript types for a running server
+//!
+//! Options for types:
+//!   --server <URL>           HTTP server to read tools and resources from
+//!   --lang <LANG>            ts (.d.ts declarations) or zod (zod schemas)
+//!   --output <FILE>          Output file (default: mcp-types.d.ts / mcp-types.ts)
 //! ```
 //!
 //! ### Configuration Management
@@ -418,6 +438,8 @@ use tracing::{debug, info};
 mod commands;
 mod config;
 mod templates;
+mod testgen;
+mod typegen;
 mod utils;
 
 use commands::*;
@@ -461,6 +483,8 @@ enum Commands {
     Validate(ValidateArgs),
     /// Show project information
     Info(InfoArgs),
+    /// Migrate a project to the current APIs and configuration
+    Migrate(MigrateArgs),
     /// Manage server configurations
     Server(ServerArgs),
     /// Manage client configurations
@@ -506,6 +530,7 @@ async fn main() -> Result<()> {
         Commands::Test(args) => test::execute(args, config).await,
         Commands::Validate(args) => validate::execute(args, config).await,
         Commands::Info(args) => info::execute(args, config).await,
+        Commands::Migrate(args) => migrate::execute(args, config).await,
         Commands::Server(args) => server::execute(args, config).await,
         Commands::Client(args) => client::execute(args, config).await,
         Commands::Completions(args) => completions::execute(args, config).await,
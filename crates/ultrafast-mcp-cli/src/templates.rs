@@ -395,6 +395,8 @@ impl ToolHandler for EchoHandler {
         Ok(ToolResult {
             content: vec![ToolContent::text(response_text)],
             is_error: None,
+            structured_content: None,
+            progress_summary: None,
         })
     }
 
@@ -471,6 +473,8 @@ impl ToolHandler for InfoHandler {
         Ok(ToolResult {
             content: vec![ToolContent::text(response_text)],
             is_error: None,
+            structured_content: None,
+            progress_summary: None,
         })
     }
 
@@ -550,6 +554,8 @@ impl ToolHandler for CalculatorHandler {
         Ok(ToolResult {
             content: vec![ToolContent::text(response_text)],
             is_error: None,
+            structured_content: None,
+            progress_summary: None,
         })
     }
 
@@ -1050,9 +1056,7 @@ async fn main() -> Result<()> {
     
     // Example 4: Read status resource
     println!("\\n4. Reading status resource:");
-    let status_request = ReadResourceRequest {
-        uri: "status://server".to_string(),
-    };
+    let status_request = ReadResourceRequest::new("status://server");
     
     let status_result = client.read_resource(status_request).await?;
     println!("Status result: {:?}", status_result);
@@ -0,0 +1,508 @@
+//! Smoke test generation from what a server registers
+//!
+//! `mcp test --generate` lists a server's tools, resources and prompts and
+//! writes a Rust integration test file with one test per item: each tool is
+//! called with example arguments derived from its input schema, each
+//! resource is read and each prompt is fetched with its required arguments
+//! filled in. A test passes when the server answers without an error, which
+//! gives a server author smoke coverage of the whole surface in one command.
+//!
+//! Resource templates are listed in the file but not tested, as no URI can
+//! be derived for them.
+
+use std::collections::HashSet;
+
+use serde_json::{Map, Value, json};
+use ultrafast_mcp_core::types::prompts::Prompt;
+use ultrafast_mcp_core::types::tools::Tool;
+
+use crate::typegen::ServerCatalog;
+
+/// Nesting past which example values are cut off, so recursive schemas end
+const MAX_DEPTH: usize = 8;
+
+/// Value of a required prompt argument
+const EXAMPLE_TEXT: &str = "example";
+
+/// How the generated tests reach the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmokeTarget {
+    /// A command speaking MCP on its standard input/output
+    Stdio { command: String, args: Vec<String> },
+    /// A Streamable HTTP server, e.g. `http://localhost:8080`
+    Http { url: String },
+}
+
+impl SmokeTarget {
+    /// The target of `mcp test --server <server> --transport <transport>`
+    pub fn parse(server: &str, transport: &str) -> anyhow::Result<Self> {
+        match transport {
+            "stdio" => {
+                let mut parts = server.split_whitespace().map(str::to_string);
+                let command = parts
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Invalid server command"))?;
+                Ok(Self::Stdio {
+                    command,
+                    args: parts.collect(),
+                })
+            }
+            "http" => Ok(Self::Http {
+                url: server.trim_end_matches('/').to_string(),
+            }),
+            other => anyhow::bail!("Unsupported transport: {other}"),
+        }
+    }
+}
+
+/// Arguments to call `tool` with: an example of its input schema
+pub fn tool_arguments(tool: &Tool) -> Value {
+    match example_value(&tool.input_schema, &tool.input_schema, 0) {
+        Value::Object(arguments) => Value::Object(arguments),
+        _ => json!({}),
+    }
+}
+
+/// Arguments to get `prompt` with: its required arguments, set to a
+/// placeholder text
+pub fn prompt_arguments(prompt: &Prompt) -> Option<Value> {
+    let required: Map<String, Value> = prompt
+        .arguments
+        .iter()
+        .flatten()
+        .filter(|argument| argument.required == Some(true))
+        .map(|argument| (argument.name.clone(), json!(EXAMPLE_TEXT)))
+        .collect();
+    (!required.is_empty()).then_some(Value::Object(required))
+}
+
+/// A value valid against `schema`, preferring what the schema itself
+/// offers (`const`, `default`, `examples`, `enum`) over a made-up one
+///
+/// Objects get their required properties only. `root` is the schema local
+/// `$ref`s are resolved against.
+pub fn example_value(schema: &Value, root: &Value, depth: usize) -> Value {
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+    if let Some(target) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| resolve_ref(root, reference))
+    {
+        return example_value(target, root, depth + 1);
+    }
+    for offered in ["const", "default"] {
+        if let Some(value) = schema.get(offered) {
+            return value.clone();
+        }
+    }
+    for offered in ["examples", "enum"] {
+        if let Some(value) = schema
+            .get(offered)
+            .and_then(Value::as_array)
+            .and_then(|values| values.first())
+        {
+            return value.clone();
+        }
+    }
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        let mut merged = Map::new();
+        for part in parts {
+            if let Value::Object(object) = example_value(part, root, depth + 1) {
+                merged.extend(object);
+            }
+        }
+        return Value::Object(merged);
+    }
+    for alternatives in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema.get(alternatives).and_then(Value::as_array) {
+            let variant = variants
+                .iter()
+                .find(|variant| schema_type(variant) != Some("null"))
+                .or(variants.first());
+            return variant.map_or(Value::Null, |variant| {
+                example_value(variant, root, depth + 1)
+            });
+        }
+    }
+
+    match schema_type(schema) {
+        Some("string") => json!(example_string(schema)),
+        Some("integer") => json!(example_number(schema).ceil() as i64),
+        Some("number") => json!(example_number(schema)),
+        Some("boolean") => json!(true),
+        Some("array") => {
+            let item = schema
+                .get("items")
+                .map_or(Value::Null, |items| example_value(items, root, depth + 1));
+            let count = schema.get("minItems").and_then(Value::as_u64).unwrap_or(1);
+            let count = match schema.get("maxItems").and_then(Value::as_u64) {
+                Some(max_items) => count.min(max_items),
+                None => count,
+            };
+            Value::Array(vec![item; count as usize])
+        }
+        Some("object") => {
+            let properties = schema.get("properties");
+            let object = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(|name| {
+                    let value = properties
+                        .and_then(|properties| properties.get(name))
+                        .map_or(json!(EXAMPLE_TEXT), |property| {
+                            example_value(property, root, depth + 1)
+                        });
+                    (name.to_string(), value)
+                })
+                .collect();
+            Value::Object(object)
+        }
+        _ => Value::Null,
+    }
+}
+
+/// The type an example is made for: the first non-null one of a type list,
+/// or `object` for a schema with properties and no type
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(name)) => Some(name),
+        Some(Value::Array(names)) => {
+            let mut names = names.iter().filter_map(Value::as_str);
+            let first = names.clone().next();
+            names.find(|name| *name != "null").or(first)
+        }
+        _ if schema.get("properties").is_some() => Some("object"),
+        _ => None,
+    }
+}
+
+/// The subschema a local `$ref` points at
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    root.pointer(pointer)
+}
+
+fn example_string(schema: &Value) -> String {
+    let text = match schema.get("format").and_then(Value::as_str) {
+        Some("date-time") => "2025-01-01T00:00:00Z",
+        Some("date") => "2025-01-01",
+        Some("time") => "00:00:00Z",
+        Some("email") => "user@example.com",
+        Some("uri" | "url" | "iri") => "https://example.com",
+        Some("uuid") => "00000000-0000-0000-0000-000000000000",
+        Some("ipv4") => "127.0.0.1",
+        Some("ipv6") => "::1",
+        _ => EXAMPLE_TEXT,
+    };
+    let min_length = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let max_length = schema.get("maxLength").and_then(Value::as_u64);
+    let mut text = text.to_string();
+    while text.len() < min_length {
+        text.push('x');
+    }
+    if let Some(max_length) = max_length {
+        text.truncate(max_length as usize);
+    }
+    text
+}
+
+fn example_number(schema: &Value) -> f64 {
+    let bound = |name: &str| schema.get(name).and_then(Value::as_f64);
+    if let Some(minimum) = bound("minimum") {
+        minimum
+    } else if let Some(minimum) = bound("exclusiveMinimum") {
+        minimum + 1.0
+    } else if let Some(maximum) = bound("maximum").filter(|maximum| *maximum < 1.0) {
+        maximum
+    } else if let Some(maximum) = bound("exclusiveMaximum").filter(|maximum| *maximum <= 1.0) {
+        maximum - 1.0
+    } else {
+        1.0
+    }
+}
+
+/// Render the smoke test file for `catalog`, reaching the server through
+/// `target`
+pub fn render(catalog: &ServerCatalog, target: &SmokeTarget) -> String {
+    let mut out = String::new();
+    let source = catalog
+        .server
+        .as_deref()
+        .map(|server| format!(" from {server}"))
+        .unwrap_or_default();
+    out.push_str(&format!(
+        "//! Smoke tests generated by `mcp test --generate`{source}\n\
+         //!\n\
+         //! Each test calls one tool with example arguments derived from its\n\
+         //! input schema, reads one resource or gets one prompt, and passes when\n\
+         //! the server answers without an error. Adjust the arguments where the\n\
+         //! examples are not meaningful for the server.\n"
+    ));
+    if !catalog.resource_templates.is_empty() {
+        out.push_str("//!\n//! Resource templates, not tested:\n");
+        for template in &catalog.resource_templates {
+            out.push_str(&format!("//! - `{}`\n", template.uri_template));
+        }
+    }
+    out.push('\n');
+
+    let mut imports = vec!["ClientCapabilities", "ClientInfo"];
+    if !catalog.prompts.is_empty() {
+        imports.push("GetPromptRequest");
+    }
+    if !catalog.resources.is_empty() {
+        imports.push("ReadResourceRequest");
+    }
+    if !catalog.tools.is_empty() {
+        imports.push("ToolCall");
+    }
+    imports.push("UltraFastClient");
+    if !catalog.tools.is_empty()
+        || catalog
+            .prompts
+            .iter()
+            .any(|prompt| prompt_arguments(prompt).is_some())
+    {
+        out.push_str("use serde_json::json;\n");
+    }
+    out.push_str(&format!(
+        "use ultrafast_mcp::{{{}}};\n\n",
+        imports.join(", ")
+    ));
+    out.push_str(&render_connect(target));
+
+    let mut names = HashSet::new();
+    for tool in &catalog.tools {
+        let arguments = serde_json::to_string(&tool_arguments(tool)).unwrap_or_default();
+        out.push_str(&format!(
+            "\n#[tokio::test]\n\
+             async fn {test}() {{\n    \
+             let (client, _server) = connect().await;\n    \
+             let result = client\n        \
+             .call_tool(ToolCall {{\n            \
+             name: {name:?}.to_string(),\n            \
+             arguments: Some(json!({arguments})),\n        \
+             }})\n        \
+             .await\n        \
+             .expect(\"tools/call {name} failed\");\n    \
+             assert_ne!(result.is_error, Some(true), \"{name} returned an error: {{result:?}}\");\n\
+             }}\n",
+            test = test_name("tool", &tool.name, &mut names),
+            name = tool.name,
+        ));
+    }
+    for resource in &catalog.resources {
+        out.push_str(&format!(
+            "\n#[tokio::test]\n\
+             async fn {test}() {{\n    \
+             let (client, _server) = connect().await;\n    \
+             client\n        \
+             .read_resource(ReadResourceRequest::new({uri:?}))\n        \
+             .await\n        \
+             .expect(\"resources/read {uri} failed\");\n\
+             }}\n",
+            test = test_name("resource", &resource.name, &mut names),
+            uri = resource.uri,
+        ));
+    }
+    for prompt in &catalog.prompts {
+        let arguments = match prompt_arguments(prompt) {
+            Some(arguments) => format!("Some(json!({arguments}))"),
+            None => "None".to_string(),
+        };
+        out.push_str(&format!(
+            "\n#[tokio::test]\n\
+             async fn {test}() {{\n    \
+             let (client, _server) = connect().await;\n    \
+             client\n        \
+             .get_prompt(GetPromptRequest {{\n            \
+             name: {name:?}.to_string(),\n            \
+             arguments: {arguments},\n        \
+             }})\n        \
+             .await\n        \
+             .expect(\"prompts/get {name} failed\");\n\
+             }}\n",
+            test = test_name("prompt", &prompt.name, &mut names),
+            name = prompt.name,
+        ));
+    }
+    out
+}
+
+/// The `connect` function every test starts with, returning the client and
+/// whatever must be kept alive with it
+fn render_connect(target: &SmokeTarget) -> String {
+    let client = "    let client = UltraFastClient::new(\n        \
+                  ClientInfo::new(\"mcp-smoke-tests\".to_string(), \"1.0.0\".to_string()),\n        \
+                  ClientCapabilities::default(),\n    );\n";
+    match target {
+        SmokeTarget::Stdio { command, args } => format!(
+            "/// Start the server and connect to it; the server is killed when the\n\
+             /// returned child is dropped\n\
+             async fn connect() -> (UltraFastClient, tokio::process::Child) {{\n    \
+             let mut server = tokio::process::Command::new({command:?})\n        \
+             .args({args:?})\n        \
+             .stdin(std::process::Stdio::piped())\n        \
+             .stdout(std::process::Stdio::piped())\n        \
+             .kill_on_drop(true)\n        \
+             .spawn()\n        \
+             .expect(\"failed to start the server\");\n    \
+             let transport = ultrafast_mcp::ChildProcessTransport::child(&mut server)\n        \
+             .expect(\"failed to attach to the server\");\n\
+             {client}    \
+             client\n        \
+             .connect(Box::new(transport))\n        \
+             .await\n        \
+             .expect(\"failed to initialize the server\");\n    \
+             (client, server)\n\
+             }}\n"
+        ),
+        SmokeTarget::Http { url } => format!(
+            "/// Connect to the running server\n\
+             async fn connect() -> (UltraFastClient, ()) {{\n\
+             {client}    \
+             client\n        \
+             .connect_streamable_http({url:?})\n        \
+             .await\n        \
+             .expect(\"failed to connect to the server\");\n    \
+             (client, ())\n\
+             }}\n"
+        ),
+    }
+}
+
+/// A test function name for the item called `name`, unique among `taken`
+fn test_name(kind: &str, name: &str, taken: &mut HashSet<String>) -> String {
+    let mut base = format!("{kind}_");
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            base.push(c.to_ascii_lowercase());
+        } else if !base.ends_with('_') {
+            base.push('_');
+        }
+    }
+    let base = base.trim_end_matches('_').to_string();
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{base}_{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::schema::validate_tool_input;
+    use ultrafast_mcp_core::types::prompts::PromptArgument;
+    use ultrafast_mcp_core::types::resources::{Resource, ResourceTemplate};
+
+    fn forecast_tool() -> Tool {
+        Tool {
+            name: "get_forecast".to_string(),
+            description: "Forecast for a city".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string", "minLength": 10},
+                    "days": {"type": ["integer", "null"], "minimum": 1, "maximum": 14},
+                    "unit": {"$ref": "#/$defs/Unit"},
+                    "contact": {"type": "string", "format": "email"},
+                    "stations": {"type": "array", "items": {"type": "string"}, "minItems": 2},
+                    "verbose": {"type": "boolean"}
+                },
+                "required": ["city", "days", "unit", "contact", "stations"],
+                "$defs": {
+                    "Unit": {"type": "string", "enum": ["celsius", "fahrenheit"]}
+                }
+            }),
+            output_schema: None,
+            annotations: None,
+        }
+    }
+
+    #[test]
+    fn test_tool_arguments_satisfy_the_schema() {
+        let tool = forecast_tool();
+        let arguments = tool_arguments(&tool);
+
+        assert_eq!(
+            arguments,
+            json!({
+                "city": "examplexxx",
+                "days": 1,
+                "unit": "celsius",
+                "contact": "user@example.com",
+                "stations": ["example", "example"]
+            })
+        );
+        validate_tool_input(&arguments, &tool.input_schema).unwrap();
+        assert_eq!(
+            tool_arguments(&Tool {
+                input_schema: json!({"type": "object"}),
+                ..tool
+            }),
+            json!({})
+        );
+    }
+
+    #[test]
+    fn test_rendered_file() {
+        let catalog = ServerCatalog {
+            server: Some("weather 1.0.0".to_string()),
+            tools: vec![forecast_tool()],
+            resources: vec![Resource {
+                uri: "weather://stations".to_string(),
+                name: "Stations".to_string(),
+                description: None,
+                mime_type: None,
+            }],
+            resource_templates: vec![ResourceTemplate {
+                uri_template: "weather://city/{name}/today".to_string(),
+                name: "Today".to_string(),
+                description: None,
+                mime_type: None,
+            }],
+            prompts: vec![Prompt {
+                name: "summarize-week".to_string(),
+                description: None,
+                arguments: Some(vec![PromptArgument {
+                    name: "city".to_string(),
+                    description: None,
+                    required: Some(true),
+                }]),
+            }],
+        };
+        let target = SmokeTarget::parse("cargo run --quiet", "stdio").unwrap();
+        let out = render(&catalog, &target);
+
+        assert!(out.starts_with("//! Smoke tests generated by `mcp test --generate` from weather"));
+        assert!(out.contains("//! - `weather://city/{name}/today`"));
+        assert!(out.contains("Command::new(\"cargo\")\n        .args([\"run\", \"--quiet\"])"));
+        assert!(out.contains("async fn tool_get_forecast() {"));
+        assert!(out.contains("name: \"get_forecast\".to_string(),"));
+        assert!(out.contains("async fn resource_stations() {"));
+        assert!(out.contains("async fn prompt_summarize_week() {"));
+        assert!(out.contains("arguments: Some(json!({\"city\":\"example\"})),"));
+    }
+
+    #[test]
+    fn test_test_names_are_unique() {
+        let mut taken = HashSet::new();
+        assert_eq!(
+            test_name("tool", "Get-Forecast", &mut taken),
+            "tool_get_forecast"
+        );
+        assert_eq!(
+            test_name("tool", "get forecast", &mut taken),
+            "tool_get_forecast_2"
+        );
+        assert_eq!(test_name("tool", "ping!", &mut taken), "tool_ping");
+    }
+}
@@ -0,0 +1,696 @@
+//! TypeScript type generation for a server's tools and resources
+//!
+//! Turns the JSON Schemas a server advertises in `tools/list`, and the URIs
+//! from `resources/list` and `resources/templates/list`, into either a
+//! `.d.ts` file of plain type declarations or a TypeScript module of
+//! [zod](https://zod.dev) schemas with their inferred types.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+use ultrafast_mcp_core::types::prompts::Prompt;
+use ultrafast_mcp_core::types::resources::{Resource, ResourceTemplate};
+use ultrafast_mcp_core::types::tools::Tool;
+
+/// Flavour of the generated file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeLang {
+    /// A `.d.ts` file of type declarations
+    TypeScript,
+    /// A `.ts` module of zod schemas and the types inferred from them
+    Zod,
+}
+
+impl TypeLang {
+    pub fn parse(lang: &str) -> anyhow::Result<Self> {
+        match lang {
+            "ts" | "typescript" => Ok(Self::TypeScript),
+            "zod" => Ok(Self::Zod),
+            other => anyhow::bail!("Unsupported language '{other}', expected 'ts' or 'zod'"),
+        }
+    }
+
+    /// File written when no output path is given
+    pub fn default_file_name(self) -> &'static str {
+        match self {
+            Self::TypeScript => "mcp-types.d.ts",
+            Self::Zod => "mcp-types.ts",
+        }
+    }
+}
+
+/// What a server advertises, as types and smoke tests are generated from it
+#[derive(Debug, Default)]
+pub struct ServerCatalog {
+    /// `name version` of the server, for the file header
+    pub server: Option<String>,
+    pub tools: Vec<Tool>,
+    pub resources: Vec<Resource>,
+    pub resource_templates: Vec<ResourceTemplate>,
+    /// Not part of the generated types
+    pub prompts: Vec<Prompt>,
+}
+
+/// Render the type definitions for `catalog`
+pub fn render(catalog: &ServerCatalog, lang: TypeLang) -> String {
+    let mut out = String::new();
+    let source = catalog
+        .server
+        .as_deref()
+        .map(|server| format!(" from {server}"))
+        .unwrap_or_default();
+    out.push_str(&format!(
+        "// Generated by `mcp generate types`{source}. Do not edit.\n\n"
+    ));
+    if lang == TypeLang::Zod {
+        out.push_str("import { z } from \"zod\";\n\n");
+    }
+
+    let mut names = HashSet::new();
+    let mut entries = Vec::new();
+    for tool in &catalog.tools {
+        let base = unique_name(&pascal_case(&tool.name), &mut names);
+        let input = format!("{base}Input");
+        let output = tool.output_schema.as_ref().map(|_| format!("{base}Output"));
+
+        for (def_name, def) in schema_defs(&tool.input_schema)
+            .into_iter()
+            .chain(tool.output_schema.iter().flat_map(schema_defs))
+        {
+            let name = format!("{base}{}", pascal_case(def_name));
+            emit_declaration(&mut out, lang, &name, def, &base, None);
+        }
+        emit_declaration(
+            &mut out,
+            lang,
+            &input,
+            &tool.input_schema,
+            &base,
+            Some(&format!("Arguments of `{}`", tool.name)),
+        );
+        if let (Some(output), Some(schema)) = (&output, &tool.output_schema) {
+            emit_declaration(
+                &mut out,
+                lang,
+                output,
+                schema,
+                &base,
+                Some(&format!("Structured result of `{}`", tool.name)),
+            );
+        }
+        entries.push((tool, input, output));
+    }
+
+    match lang {
+        TypeLang::TypeScript => {
+            out.push_str("/** Every tool, with its arguments and structured result */\n");
+            out.push_str("export interface Tools {\n");
+            for (tool, input, output) in &entries {
+                push_doc(&mut out, 1, Some(&tool.description));
+                out.push_str(&format!(
+                    "  {}: {{ input: {input}; output: {} }};\n",
+                    property_key(&tool.name),
+                    output.as_deref().unwrap_or("unknown")
+                ));
+            }
+            out.push_str("}\n\nexport type ToolName = keyof Tools;\n");
+        }
+        TypeLang::Zod => {
+            out.push_str(
+                "/** Every tool, with the schemas of its arguments and structured result */\n",
+            );
+            out.push_str("export const toolSchemas = {\n");
+            for (tool, input, output) in &entries {
+                let output = output.as_ref().map_or("z.unknown()".to_string(), |output| {
+                    format!("{output}Schema")
+                });
+                out.push_str(&format!(
+                    "  {}: {{ input: {input}Schema, output: {output} }},\n",
+                    property_key(&tool.name)
+                ));
+            }
+            out.push_str("} as const;\n\nexport type ToolName = keyof typeof toolSchemas;\n");
+        }
+    }
+
+    let uris: Vec<String> = catalog
+        .resources
+        .iter()
+        .map(|resource| string_literal(&resource.uri))
+        .collect();
+    match lang {
+        TypeLang::TypeScript => {
+            out.push_str(&format!(
+                "\nexport type ResourceUri = {};\n",
+                union_or(&uris, "never")
+            ));
+        }
+        TypeLang::Zod => {
+            out.push_str(&format!(
+                "\nexport const resourceUris = [{}] as const;\n\
+                 export type ResourceUri = (typeof resourceUris)[number];\n",
+                uris.join(", ")
+            ));
+        }
+    }
+    let templates: Vec<String> = catalog
+        .resource_templates
+        .iter()
+        .map(|template| template_literal(&template.uri_template))
+        .collect();
+    out.push_str(&format!(
+        "export type ResourceTemplateUri = {};\n",
+        union_or(&templates, "never")
+    ));
+    out
+}
+
+fn emit_declaration(
+    out: &mut String,
+    lang: TypeLang,
+    name: &str,
+    schema: &Value,
+    defs_prefix: &str,
+    fallback_doc: Option<&str>,
+) {
+    let doc = schema
+        .get("description")
+        .and_then(Value::as_str)
+        .or(fallback_doc);
+    push_doc(out, 0, doc);
+    match lang {
+        TypeLang::TypeScript => {
+            out.push_str(&format!(
+                "export type {name} = {};\n\n",
+                ts_type(schema, defs_prefix, 0)
+            ));
+        }
+        TypeLang::Zod => {
+            out.push_str(&format!(
+                "export const {name}Schema = {};\n\
+                 export type {name} = z.infer<typeof {name}Schema>;\n\n",
+                zod_type(schema, defs_prefix, 0)
+            ));
+        }
+    }
+}
+
+/// Named subschemas of `schema`, from `$defs` or the older `definitions`
+fn schema_defs(schema: &Value) -> Vec<(&str, &Value)> {
+    ["$defs", "definitions"]
+        .iter()
+        .filter_map(|key| schema.get(*key).and_then(Value::as_object))
+        .flatten()
+        .map(|(name, def)| (name.as_str(), def))
+        .collect()
+}
+
+/// Name of the type a local `$ref` points at
+fn ref_name(reference: &str, defs_prefix: &str) -> Option<String> {
+    let name = reference
+        .strip_prefix("#/$defs/")
+        .or_else(|| reference.strip_prefix("#/definitions/"))?;
+    Some(format!("{defs_prefix}{}", pascal_case(name)))
+}
+
+/// The `type` of a schema, as a list
+fn schema_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(kind)) => vec![kind.as_str()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ if schema.get("properties").is_some() => vec!["object"],
+        _ => Vec::new(),
+    }
+}
+
+fn subschemas<'a>(schema: &'a Value, key: &str) -> Option<&'a Vec<Value>> {
+    schema
+        .get(key)
+        .and_then(Value::as_array)
+        .filter(|schemas| !schemas.is_empty())
+}
+
+fn ts_type(schema: &Value, defs_prefix: &str, indent: usize) -> String {
+    if let Some(name) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| ref_name(reference, defs_prefix))
+    {
+        return name;
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        let literals: Vec<String> = values.iter().map(Value::to_string).collect();
+        return union_or(&literals, "never");
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(variants) = subschemas(schema, key) {
+            let types: Vec<String> = variants
+                .iter()
+                .map(|variant| ts_type(variant, defs_prefix, indent))
+                .collect();
+            return union_or(&types, "never");
+        }
+    }
+    if let Some(parts) = subschemas(schema, "allOf") {
+        let types: Vec<String> = parts
+            .iter()
+            .map(|part| ts_type(part, defs_prefix, indent))
+            .collect();
+        return types.join(" & ");
+    }
+
+    let types = schema_types(schema);
+    if types.is_empty() {
+        return "unknown".to_string();
+    }
+    let types: Vec<String> = types
+        .into_iter()
+        .map(|kind| match kind {
+            "string" => "string".to_string(),
+            "number" | "integer" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => {
+                let items = schema.get("items").map_or("unknown".to_string(), |items| {
+                    ts_type(items, defs_prefix, indent)
+                });
+                format!("Array<{items}>")
+            }
+            "object" => ts_object(schema, defs_prefix, indent),
+            _ => "unknown".to_string(),
+        })
+        .collect();
+    union_or(&types, "unknown")
+}
+
+fn ts_object(schema: &Value, defs_prefix: &str, indent: usize) -> String {
+    let extra = match schema.get("additionalProperties") {
+        Some(Value::Bool(false)) => None,
+        Some(Value::Object(_)) => schema
+            .get("additionalProperties")
+            .map(|extra| ts_type(extra, defs_prefix, indent)),
+        _ => Some("unknown".to_string()),
+    };
+    let Some(properties) = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .filter(|properties| !properties.is_empty())
+    else {
+        return format!("Record<string, {}>", extra.as_deref().unwrap_or("never"));
+    };
+
+    let required = required_properties(schema);
+    let pad = "  ".repeat(indent + 1);
+    let mut out = "{\n".to_string();
+    for (name, property) in properties {
+        push_doc(
+            &mut out,
+            indent + 1,
+            property.get("description").and_then(Value::as_str),
+        );
+        let optional = if required.contains(name.as_str()) {
+            ""
+        } else {
+            "?"
+        };
+        out.push_str(&format!(
+            "{pad}{}{optional}: {};\n",
+            property_key(name),
+            ts_type(property, defs_prefix, indent + 1)
+        ));
+    }
+    if schema
+        .get("additionalProperties")
+        .is_some_and(Value::is_object)
+    {
+        // The index signature must admit the declared properties too
+        out.push_str(&format!("{pad}[key: string]: unknown;\n"));
+    }
+    out.push_str(&"  ".repeat(indent));
+    out.push('}');
+    out
+}
+
+fn zod_type(schema: &Value, defs_prefix: &str, indent: usize) -> String {
+    if let Some(name) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| ref_name(reference, defs_prefix))
+    {
+        // Lazy, so definitions may refer to each other in any order
+        return format!("z.lazy(() => {name}Schema)");
+    }
+    if let Some(value) = schema.get("const") {
+        return format!("z.literal({value})");
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        if !values.is_empty() && values.iter().all(Value::is_string) {
+            let values: Vec<String> = values.iter().map(Value::to_string).collect();
+            return format!("z.enum([{}])", values.join(", "));
+        }
+        let literals: Vec<String> = values
+            .iter()
+            .map(|value| format!("z.literal({value})"))
+            .collect();
+        return zod_union(literals);
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(variants) = subschemas(schema, key) {
+            return zod_union(
+                variants
+                    .iter()
+                    .map(|variant| zod_type(variant, defs_prefix, indent))
+                    .collect(),
+            );
+        }
+    }
+    if let Some(parts) = subschemas(schema, "allOf") {
+        let mut parts = parts.iter().map(|part| zod_type(part, defs_prefix, indent));
+        let first = parts.next().unwrap_or_default();
+        return parts.fold(first, |all, part| format!("{all}.and({part})"));
+    }
+
+    let types = schema_types(schema);
+    if types.is_empty() {
+        return "z.unknown()".to_string();
+    }
+    zod_union(
+        types
+            .into_iter()
+            .map(|kind| match kind {
+                "string" => "z.string()".to_string(),
+                "number" => "z.number()".to_string(),
+                "integer" => "z.number().int()".to_string(),
+                "boolean" => "z.boolean()".to_string(),
+                "null" => "z.null()".to_string(),
+                "array" => {
+                    let items = schema
+                        .get("items")
+                        .map_or("z.unknown()".to_string(), |items| {
+                            zod_type(items, defs_prefix, indent)
+                        });
+                    format!("z.array({items})")
+                }
+                "object" => zod_object(schema, defs_prefix, indent),
+                _ => "z.unknown()".to_string(),
+            })
+            .collect(),
+    )
+}
+
+fn zod_object(schema: &Value, defs_prefix: &str, indent: usize) -> String {
+    let extra = match schema.get("additionalProperties") {
+        Some(Value::Bool(false)) => None,
+        Some(extra @ Value::Object(_)) => Some(zod_type(extra, defs_prefix, indent)),
+        _ => Some("z.unknown()".to_string()),
+    };
+    let Some(properties) = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .filter(|properties| !properties.is_empty())
+    else {
+        return match extra {
+            Some(extra) => format!("z.record(z.string(), {extra})"),
+            None => "z.object({}).strict()".to_string(),
+        };
+    };
+
+    let required = required_properties(schema);
+    let pad = "  ".repeat(indent + 1);
+    let mut out = "z.object({\n".to_string();
+    for (name, property) in properties {
+        push_doc(
+            &mut out,
+            indent + 1,
+            property.get("description").and_then(Value::as_str),
+        );
+        let optional = if required.contains(name.as_str()) {
+            ""
+        } else {
+            ".optional()"
+        };
+        out.push_str(&format!(
+            "{pad}{}: {}{optional},\n",
+            property_key(name),
+            zod_type(property, defs_prefix, indent + 1)
+        ));
+    }
+    out.push_str(&"  ".repeat(indent));
+    out.push_str("})");
+    match schema.get("additionalProperties") {
+        Some(Value::Bool(false)) => out.push_str(".strict()"),
+        Some(Value::Object(_)) => {
+            out.push_str(&format!(".catchall({})", extra.unwrap_or_default()))
+        }
+        _ => out.push_str(".passthrough()"),
+    }
+    out
+}
+
+fn zod_union(mut variants: Vec<String>) -> String {
+    match variants.len() {
+        0 => "z.never()".to_string(),
+        1 => variants.remove(0),
+        _ => format!("z.union([{}])", variants.join(", ")),
+    }
+}
+
+fn required_properties(schema: &Value) -> HashSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn union_or(types: &[String], empty: &str) -> String {
+    if types.is_empty() {
+        return empty.to_string();
+    }
+    let mut unique: Vec<&str> = Vec::new();
+    for kind in types {
+        if !unique.contains(&kind.as_str()) {
+            unique.push(kind);
+        }
+    }
+    unique.join(" | ")
+}
+
+fn push_doc(out: &mut String, indent: usize, doc: Option<&str>) {
+    let Some(doc) = doc.map(str::trim).filter(|doc| !doc.is_empty()) else {
+        return;
+    };
+    let pad = "  ".repeat(indent);
+    let doc = doc.replace("*/", "*\\/");
+    let mut lines = doc.lines();
+    match (lines.next(), lines.next()) {
+        (Some(line), None) => out.push_str(&format!("{pad}/** {line} */\n")),
+        _ => {
+            out.push_str(&format!("{pad}/**\n"));
+            for line in doc.lines() {
+                out.push_str(&format!("{pad} * {line}\n").replace(" * \n", " *\n"));
+            }
+            out.push_str(&format!("{pad} */\n"));
+        }
+    }
+}
+
+/// An object key, quoted unless it is a plain identifier
+fn property_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        name.to_string()
+    } else {
+        string_literal(name)
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    Value::String(value.to_string()).to_string()
+}
+
+/// A template literal type matching the URIs of an RFC 6570 template
+fn template_literal(template: &str) -> String {
+    let mut out = "`".to_string();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&escape_template(&rest[..start]));
+        match rest[start..].find('}') {
+            Some(end) => {
+                out.push_str("${string}");
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                out.push_str(&escape_template(&rest[start..]));
+                rest = "";
+            }
+        }
+    }
+    out.push_str(&escape_template(rest));
+    out.push('`');
+    out
+}
+
+fn escape_template(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+/// `get_weather`, `get-weather` and `getWeather` all become `GetWeather`
+fn pascal_case(name: &str) -> String {
+    let name: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    match name.chars().next() {
+        None => "Tool".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("T{name}"),
+        Some(_) => name,
+    }
+}
+
+fn unique_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{name}{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn weather_catalog() -> ServerCatalog {
+        ServerCatalog {
+            server: Some("weather 1.0.0".to_string()),
+            tools: vec![
+                Tool {
+                    name: "get_forecast".to_string(),
+                    description: "Forecast for a city".to_string(),
+                    input_schema: json!({
+                        "type": "object",
+                        "properties": {
+                            "city": {"type": "string", "description": "City name"},
+                            "days": {"type": ["integer", "null"]},
+                            "unit": {"$ref": "#/$defs/Unit"}
+                        },
+                        "required": ["city"],
+                        "$defs": {
+                            "Unit": {"type": "string", "enum": ["celsius", "fahrenheit"]}
+                        }
+                    }),
+                    output_schema: Some(json!({
+                        "type": "object",
+                        "properties": {
+                            "temperatures": {"type": "array", "items": {"type": "number"}}
+                        },
+                        "required": ["temperatures"],
+                        "additionalProperties": false
+                    })),
+                    annotations: None,
+                },
+                Tool {
+                    name: "ping-all".to_string(),
+                    description: "Ping every station".to_string(),
+                    input_schema: json!({"type": "object"}),
+                    output_schema: None,
+                    annotations: None,
+                },
+            ],
+            resources: vec![Resource {
+                uri: "weather://stations".to_string(),
+                name: "Stations".to_string(),
+                description: None,
+                mime_type: None,
+            }],
+            resource_templates: vec![ResourceTemplate {
+                uri_template: "weather://city/{name}/today".to_string(),
+                name: "Today".to_string(),
+                description: None,
+                mime_type: None,
+            }],
+            prompts: vec![],
+        }
+    }
+
+    #[test]
+    fn test_typescript_declarations() {
+        let out = render(&weather_catalog(), TypeLang::TypeScript);
+
+        assert!(out.contains("export type GetForecastUnit = \"celsius\" | \"fahrenheit\";"));
+        assert!(out.contains(
+            "/** Arguments of `get_forecast` */\n\
+             export type GetForecastInput = {\n  \
+             /** City name */\n  \
+             city: string;\n  \
+             days?: number | null;\n  \
+             unit?: GetForecastUnit;\n};"
+        ));
+        assert!(out.contains("temperatures: Array<number>;"));
+        assert!(out.contains("export type PingAllInput = Record<string, unknown>;"));
+        assert!(
+            out.contains("  get_forecast: { input: GetForecastInput; output: GetForecastOutput };")
+        );
+        assert!(out.contains("  \"ping-all\": { input: PingAllInput; output: unknown };"));
+        assert!(out.contains("export type ResourceUri = \"weather://stations\";"));
+        assert!(
+            out.contains("export type ResourceTemplateUri = `weather://city/${string}/today`;")
+        );
+    }
+
+    #[test]
+    fn test_zod_schemas() {
+        let out = render(&weather_catalog(), TypeLang::Zod);
+
+        assert!(out.starts_with("// Generated by `mcp generate types` from weather 1.0.0."));
+        assert!(out.contains("import { z } from \"zod\";"));
+        assert!(out.contains(
+            "export const GetForecastUnitSchema = z.enum([\"celsius\", \"fahrenheit\"]);"
+        ));
+        assert!(out.contains("  days: z.union([z.number().int(), z.null()]).optional(),"));
+        assert!(out.contains("  unit: z.lazy(() => GetForecastUnitSchema).optional(),"));
+        assert!(out.contains("}).strict();\nexport type GetForecastOutput"));
+        assert!(
+            out.contains("export type GetForecastInput = z.infer<typeof GetForecastInputSchema>;")
+        );
+        assert!(
+            out.contains("  \"ping-all\": { input: PingAllInputSchema, output: z.unknown() },")
+        );
+        assert!(out.contains("export const resourceUris = [\"weather://stations\"] as const;"));
+    }
+
+    #[test]
+    fn test_names_and_literals() {
+        assert_eq!(pascal_case("get_weather"), "GetWeather");
+        assert_eq!(pascal_case("getWeather"), "GetWeather");
+        assert_eq!(pascal_case("3d-render"), "T3dRender");
+        let mut taken = HashSet::new();
+        assert_eq!(unique_name("GetWeather", &mut taken), "GetWeather");
+        assert_eq!(unique_name("GetWeather", &mut taken), "GetWeather2");
+
+        assert_eq!(property_key("city"), "city");
+        assert_eq!(property_key("max-days"), "\"max-days\"");
+        assert_eq!(template_literal("file:///{+path}"), "`file:///${string}`");
+        assert_eq!(template_literal("a`b{x}"), "`a\\`b${string}`");
+        assert!(TypeLang::parse("python").is_err());
+    }
+}
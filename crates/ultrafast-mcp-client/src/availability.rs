@@ -0,0 +1,215 @@
+//! Waiting for tools and resources a server registers after startup
+//!
+//! A server may register some of its tools lazily, once a model is loaded or
+//! a backend connected, so they are missing from the first `tools/list`.
+//! [`UltraFastClient::wait_for_tool`] and
+//! [`UltraFastClient::wait_for_resource`] list what the server has and, until
+//! the item shows up, list again on every `notifications/tools/listChanged`
+//! or `notifications/resources/listChanged`. [`UltraFastClient::watch_tool`]
+//! keeps following whether a tool is available.
+//!
+//! Waiting stops at the timeout given, or when the future is dropped.
+//!
+//! [`UltraFastClient::wait_for_tool`]: crate::UltraFastClient::wait_for_tool
+//! [`UltraFastClient::wait_for_resource`]: crate::UltraFastClient::wait_for_resource
+//! [`UltraFastClient::watch_tool`]: crate::UltraFastClient::watch_tool
+
+use tokio::sync::watch;
+use ultrafast_mcp_core::error::{MCPError, MCPResult};
+use ultrafast_mcp_core::types::tools::Tool;
+
+use crate::UltraFastClient;
+
+/// Counts of the list changed notifications received, per list
+#[derive(Debug)]
+pub(crate) struct ListChanges {
+    tools: watch::Sender<u64>,
+    resources: watch::Sender<u64>,
+}
+
+impl Default for ListChanges {
+    fn default() -> Self {
+        Self {
+            tools: watch::Sender::new(0),
+            resources: watch::Sender::new(0),
+        }
+    }
+}
+
+impl ListChanges {
+    /// Count a list changed notification, if `method` is one
+    pub(crate) fn observe(&self, method: &str) {
+        let list = match method {
+            "notifications/tools/listChanged" => &self.tools,
+            "notifications/resources/listChanged" => &self.resources,
+            _ => return,
+        };
+        list.send_modify(|changes| *changes += 1);
+    }
+
+    pub(crate) fn tools(&self) -> watch::Receiver<u64> {
+        self.tools.subscribe()
+    }
+
+    pub(crate) fn resources(&self) -> watch::Receiver<u64> {
+        self.resources.subscribe()
+    }
+}
+
+/// Wait for the next change notified on `changes`
+pub(crate) async fn next_change(changes: &mut watch::Receiver<u64>) -> MCPResult<()> {
+    changes
+        .changed()
+        .await
+        .map_err(|_| MCPError::internal_error("Client was dropped".to_string()))
+}
+
+/// Whether a tool is available, as last listed
+///
+/// Returned by [`UltraFastClient::watch_tool`].
+pub struct ToolAvailability<'a> {
+    client: &'a UltraFastClient,
+    name: String,
+    changes: watch::Receiver<u64>,
+    tool: Option<Tool>,
+}
+
+impl<'a> ToolAvailability<'a> {
+    pub(crate) async fn new(client: &'a UltraFastClient, name: String) -> MCPResult<Self> {
+        // Subscribed before listing, so a change in between is not missed
+        let changes = client.state_manager.read().await.list_changes.tools();
+        let tool = client.find_tool(&name).await?;
+        Ok(Self {
+            client,
+            name,
+            changes,
+            tool,
+        })
+    }
+
+    /// The tool, if it was listed
+    pub fn tool(&self) -> Option<&Tool> {
+        self.tool.as_ref()
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.tool.is_some()
+    }
+
+    /// Wait until the tool appears or disappears, returning it if it
+    /// appeared
+    ///
+    /// Waits for as long as the server sends no list changed notification
+    /// making a difference; wrap it in a timeout to bound the wait.
+    pub async fn changed(&mut self) -> MCPResult<Option<&Tool>> {
+        loop {
+            next_change(&mut self.changes).await?;
+            let tool = self.client.find_tool(&self.name).await?;
+            let flipped = tool.is_some() != self.tool.is_some();
+            self.tool = tool;
+            if flipped {
+                return Ok(self.tool.as_ref());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use ultrafast_mcp_core::protocol::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse};
+    use ultrafast_mcp_core::types::client::{ClientCapabilities, ClientInfo};
+    use ultrafast_mcp_transport::{
+        StreamTransport, Transport, TransportDescription, TransportKind,
+    };
+
+    fn tool(name: &str) -> Value {
+        json!({"name": name, "description": name, "inputSchema": {"type": "object"}})
+    }
+
+    /// A server listing `tools`, notifying a change whenever `changed` fires
+    async fn serve_tools(
+        mut transport: impl Transport,
+        tools: Arc<Mutex<Vec<Value>>>,
+        mut changed: mpsc::UnboundedReceiver<()>,
+    ) {
+        loop {
+            let message = tokio::select! {
+                message = transport.receive_message() => match message {
+                    Ok(JsonRpcMessage::Request(request)) if request.id.is_some() => {
+                        let result = match request.method.as_str() {
+                            "initialize" => json!({
+                                "protocolVersion": "2025-06-18",
+                                "capabilities": {"tools": {"listChanged": true}},
+                                "serverInfo": {"name": "lazy", "version": "1.0.0"}
+                            }),
+                            "tools/list" => json!({"tools": *tools.lock().unwrap()}),
+                            _ => json!({}),
+                        };
+                        JsonRpcMessage::Response(JsonRpcResponse::success(result, request.id))
+                    }
+                    Ok(_) => continue,
+                    Err(_) => return,
+                },
+                Some(()) = changed.recv() => JsonRpcMessage::Notification(
+                    JsonRpcRequest::notification(
+                        "notifications/tools/listChanged".to_string(),
+                        None,
+                    ),
+                ),
+            };
+            if transport.send_message(message).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_registered_later_are_awaited_and_watched() {
+        let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+        let description = || TransportDescription::new(TransportKind::Custom("test".to_string()));
+        let tools = Arc::new(Mutex::new(vec![tool("early")]));
+        let (change, changed) = mpsc::unbounded_channel();
+        tokio::spawn(serve_tools(
+            StreamTransport::new(server_end, description()),
+            tools.clone(),
+            changed,
+        ));
+        let client = UltraFastClient::new(
+            ClientInfo::new("test-client".to_string(), "1.0.0".to_string()),
+            ClientCapabilities::default(),
+        );
+        client
+            .connect(Box::new(StreamTransport::new(client_end, description())))
+            .await
+            .unwrap();
+
+        let register = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            tools.lock().unwrap().push(tool("late"));
+            change.send(()).unwrap();
+        };
+        let (late, ()) = tokio::join!(
+            client.wait_for_tool("late", Duration::from_secs(5)),
+            register
+        );
+        assert_eq!(late.unwrap().name, "late");
+
+        let mut availability = client.watch_tool("late").await.unwrap();
+        assert!(availability.is_available());
+        tools.lock().unwrap().pop();
+        change.send(()).unwrap();
+        assert!(availability.changed().await.unwrap().is_none());
+        assert!(!availability.is_available());
+
+        let error = client
+            .wait_for_tool("never", Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("'never' was not listed"));
+    }
+}
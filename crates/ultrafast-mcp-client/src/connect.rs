@@ -0,0 +1,213 @@
+//! Options for a single connection
+//!
+//! [`UltraFastClient::connect_with`] takes a [`ConnectOptions`] naming the
+//! transport and whatever applies to that connection only, such as the
+//! credentials sent to an HTTP server. Options are plain values: nothing is
+//! locked, spawned or opened until the client connects, so they can be built
+//! anywhere, including inside a tokio runtime.
+//!
+//! ```rust,no_run
+//! # #[cfg(all(feature = "http", feature = "oauth"))]
+//! # async fn example(client: ultrafast_mcp_client::UltraFastClient) -> ultrafast_mcp_core::MCPResult<()> {
+//! use std::time::Duration;
+//! use ultrafast_mcp_client::ConnectOptions;
+//!
+//! let options = ConnectOptions::streamable_http("http://127.0.0.1:8080/mcp")
+//!     .with_auth(ultrafast_mcp_auth::AuthMethod::bearer("token".to_string()))
+//!     .with_timeout(Duration::from_secs(10));
+//! client.connect_with(options).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`UltraFastClient::connect_with`]: crate::UltraFastClient::connect_with
+
+use std::time::Duration;
+
+use ultrafast_mcp_core::error::MCPResult;
+use ultrafast_mcp_transport::Transport;
+#[cfg(feature = "http")]
+use ultrafast_mcp_transport::streamable_http::client::{
+    StreamableHttpClient, StreamableHttpClientConfig,
+};
+
+use crate::UltraFastClient;
+
+/// What to connect to
+enum ConnectTarget {
+    Transport(Box<dyn Transport>),
+    Stdio,
+    #[cfg(feature = "http")]
+    StreamableHttp(Box<StreamableHttpClientConfig>),
+}
+
+/// How to connect, passed to [`UltraFastClient::connect_with`]
+///
+/// The timeout, retries and credentials apply to HTTP connections; other
+/// transports ignore them.
+///
+/// [`UltraFastClient::connect_with`]: crate::UltraFastClient::connect_with
+pub struct ConnectOptions {
+    target: ConnectTarget,
+    timeout: Option<Duration>,
+    max_retries: Option<u32>,
+    auth_token: Option<String>,
+    #[cfg(feature = "oauth")]
+    auth: Option<ultrafast_mcp_auth::AuthMethod>,
+}
+
+impl std::fmt::Debug for ConnectOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = match &self.target {
+            ConnectTarget::Transport(_) => "transport".to_string(),
+            ConnectTarget::Stdio => "stdio".to_string(),
+            #[cfg(feature = "http")]
+            ConnectTarget::StreamableHttp(config) => config.base_url.clone(),
+        };
+        f.debug_struct("ConnectOptions")
+            .field("target", &target)
+            .field("timeout", &self.timeout)
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ConnectOptions {
+    fn new(target: ConnectTarget) -> Self {
+        Self {
+            target,
+            timeout: None,
+            max_retries: None,
+            auth_token: None,
+            #[cfg(feature = "oauth")]
+            auth: None,
+        }
+    }
+
+    /// Connect over a transport that is already open
+    pub fn transport(transport: Box<dyn Transport>) -> Self {
+        Self::new(ConnectTarget::Transport(transport))
+    }
+
+    /// Connect over the process's standard input and output
+    pub fn stdio() -> Self {
+        Self::new(ConnectTarget::Stdio)
+    }
+
+    /// Connect to a Streamable HTTP server at `url`
+    ///
+    /// Without credentials of its own, the connection uses the client's,
+    /// set with [`UltraFastClient::with_auth`](crate::UltraFastClient::with_auth).
+    #[cfg(feature = "http")]
+    pub fn streamable_http(url: impl Into<String>) -> Self {
+        Self::new(ConnectTarget::StreamableHttp(Box::new(
+            StreamableHttpClientConfig {
+                base_url: url.into(),
+                ..Default::default()
+            },
+        )))
+    }
+
+    /// Connect to a Streamable HTTP server configured in full
+    ///
+    /// The config's own timeout and credentials are kept unless overridden
+    /// with the methods below.
+    #[cfg(feature = "http")]
+    pub fn streamable_http_config(config: StreamableHttpClientConfig) -> Self {
+        let mut options = Self::new(ConnectTarget::StreamableHttp(Box::new(config)));
+        if let ConnectTarget::StreamableHttp(config) = &options.target {
+            options.timeout = Some(config.timeout);
+        }
+        options
+    }
+
+    /// Timeout of each HTTP request; defaults to the client's request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Send `token` as a bearer token, without the auth feature
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Authenticate this connection with `auth`, instead of the client's
+    /// credentials
+    #[cfg(feature = "oauth")]
+    pub fn with_auth(mut self, auth: ultrafast_mcp_auth::AuthMethod) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Open the transport, with the client's timeout and credentials where
+    /// the options set none
+    #[cfg_attr(not(feature = "http"), allow(unused_variables))]
+    pub(crate) async fn open(self, client: &UltraFastClient) -> MCPResult<Box<dyn Transport>> {
+        match self.target {
+            ConnectTarget::Transport(transport) => Ok(transport),
+            ConnectTarget::Stdio => Ok(Box::new(
+                ultrafast_mcp_transport::stdio::StdioTransport::new().await?,
+            )),
+            #[cfg(feature = "http")]
+            ConnectTarget::StreamableHttp(mut config) => {
+                config.timeout = self.timeout.unwrap_or(client.request_timeout);
+                if let Some(max_retries) = self.max_retries {
+                    config.max_retries = max_retries;
+                }
+                if self.auth_token.is_some() {
+                    config.auth_token = self.auth_token;
+                }
+                #[cfg(feature = "oauth")]
+                let auth = match self.auth {
+                    Some(auth) => Some(auth),
+                    None if config.auth_method.is_none() && config.auth_token.is_none() => client
+                        .auth_middleware
+                        .read()
+                        .await
+                        .as_ref()
+                        .map(|auth| auth.get_auth_method().clone()),
+                    None => None,
+                };
+                #[cfg(feature = "oauth")]
+                if let Some(auth) = auth {
+                    if let ultrafast_mcp_auth::AuthMethod::OAuth(oauth_config) = &auth {
+                        config.oauth_config = Some(oauth_config.clone());
+                    }
+                    config.auth_method = Some(auth);
+                }
+
+                let mut http_transport = StreamableHttpClient::new(*config)?;
+                http_transport.connect().await?;
+                Ok(Box::new(http_transport))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http", feature = "oauth"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_override_the_config() {
+        let config = StreamableHttpClientConfig {
+            base_url: "http://127.0.0.1:1/mcp".to_string(),
+            timeout: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let options = ConnectOptions::streamable_http_config(config)
+            .with_max_retries(0)
+            .with_auth(ultrafast_mcp_auth::AuthMethod::bearer("token".to_string()));
+        assert_eq!(options.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(options.max_retries, Some(0));
+        assert!(options.auth.is_some());
+        assert!(format!("{options:?}").contains("http://127.0.0.1:1/mcp"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Round-trip latency estimation and adaptive timeouts
+//!
+//! Every `ping` the client sends yields a round-trip time sample. The samples
+//! are smoothed the way TCP smooths them (RFC 6298) into a
+//! [`LatencyEstimate`] per connection. With [`AdaptiveTimeouts`] enabled, the
+//! client stretches each operation's configured timeout by a multiple of the
+//! estimated round-trip timeout, within a minimum and maximum, so the same
+//! configuration suits a local stdio server and a distant HTTP one.
+
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// Smoothed round-trip time of a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyEstimate {
+    /// Exponentially smoothed round-trip time
+    pub smoothed_rtt: Duration,
+    /// Smoothed deviation of the samples from `smoothed_rtt`
+    pub rtt_variance: Duration,
+    /// Most recent sample
+    pub last_rtt: Duration,
+    /// Number of samples taken
+    pub samples: u64,
+}
+
+impl LatencyEstimate {
+    fn first(rtt: Duration) -> Self {
+        Self {
+            smoothed_rtt: rtt,
+            rtt_variance: rtt / 2,
+            last_rtt: rtt,
+            samples: 1,
+        }
+    }
+
+    fn update(&mut self, rtt: Duration) {
+        let deviation = self.smoothed_rtt.abs_diff(rtt);
+        self.rtt_variance = (self.rtt_variance * 3 + deviation) / 4;
+        self.smoothed_rtt = (self.smoothed_rtt * 7 + rtt) / 8;
+        self.last_rtt = rtt;
+        self.samples += 1;
+    }
+
+    /// How long a round trip may take before it is considered lost
+    pub fn round_trip_timeout(&self) -> Duration {
+        self.smoothed_rtt + self.rtt_variance * 4
+    }
+}
+
+/// How operation timeouts follow the measured latency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveTimeouts {
+    /// Round-trip timeouts added to each operation's configured timeout
+    pub rtt_multiplier: u32,
+    /// Lower bound of adapted timeouts
+    pub min_timeout: Duration,
+    /// Upper bound of adapted timeouts
+    pub max_timeout: Duration,
+}
+
+impl Default for AdaptiveTimeouts {
+    fn default() -> Self {
+        Self {
+            rtt_multiplier: 4,
+            min_timeout: Duration::from_secs(1),
+            max_timeout: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl AdaptiveTimeouts {
+    /// Adapt the configured timeout `base` to the latency estimate
+    ///
+    /// Without an estimate `base` is only bounded.
+    pub fn apply(&self, base: Duration, estimate: Option<&LatencyEstimate>) -> Duration {
+        let allowance = estimate
+            .map(|estimate| estimate.round_trip_timeout() * self.rtt_multiplier)
+            .unwrap_or_default();
+        (base + allowance).clamp(self.min_timeout, self.max_timeout.max(self.min_timeout))
+    }
+}
+
+/// Latency estimate updated from round-trip samples
+#[derive(Debug, Default)]
+pub(crate) struct LatencyEstimator {
+    estimate: Mutex<Option<LatencyEstimate>>,
+}
+
+impl LatencyEstimator {
+    pub(crate) fn record(&self, rtt: Duration) {
+        let mut estimate = self.lock();
+        match estimate.as_mut() {
+            Some(estimate) => estimate.update(rtt),
+            None => *estimate = Some(LatencyEstimate::first(rtt)),
+        }
+    }
+
+    pub(crate) fn estimate(&self) -> Option<LatencyEstimate> {
+        *self.lock()
+    }
+
+    pub(crate) fn reset(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<LatencyEstimate>> {
+        self.estimate.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_converges_on_stable_latency() {
+        let estimator = LatencyEstimator::default();
+        for _ in 0..50 {
+            estimator.record(Duration::from_millis(100));
+        }
+        let estimate = estimator.estimate().unwrap();
+        assert_eq!(estimate.samples, 50);
+        assert_eq!(estimate.smoothed_rtt, Duration::from_millis(100));
+        assert!(estimate.rtt_variance < Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_adapted_timeouts_are_bounded() {
+        let adaptive = AdaptiveTimeouts {
+            rtt_multiplier: 4,
+            min_timeout: Duration::from_secs(2),
+            max_timeout: Duration::from_secs(10),
+        };
+        let base = Duration::from_secs(5);
+        assert_eq!(adaptive.apply(base, None), base);
+        assert_eq!(
+            adaptive.apply(Duration::from_millis(10), None),
+            Duration::from_secs(2)
+        );
+
+        // 200ms round trips with 100ms variance give a 600ms round-trip
+        // timeout, four of which are added
+        let estimate = LatencyEstimate::first(Duration::from_millis(200));
+        assert_eq!(
+            adaptive.apply(base, Some(&estimate)),
+            Duration::from_millis(7400)
+        );
+
+        let distant = LatencyEstimate::first(Duration::from_secs(5));
+        assert_eq!(
+            adaptive.apply(base, Some(&distant)),
+            Duration::from_secs(10)
+        );
+    }
+}
@@ -2,32 +2,87 @@
 //!
 //! A high-performance client implementation for the Model Context Protocol (MCP).
 
+use futures::{Stream, TryStreamExt};
+use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{RwLock, oneshot};
-use tracing::{error, info, warn};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::{Notify, RwLock, RwLockWriteGuard, broadcast, mpsc, oneshot};
+use tracing::{Instrument, debug, error, info, warn};
+#[cfg(feature = "content-encoding")]
+use ultrafast_mcp_core::content_encoding;
 use ultrafast_mcp_core::{
     config::TimeoutConfig,
-    error::{MCPError, MCPResult, ProtocolError, TransportError},
+    error::{MCPError, MCPResult, ProtocolError, ToolError, TransportError},
+    i18n::set_locale_meta,
     protocol::{
         InitializeRequest, InitializeResponse, InitializedNotification, ShutdownRequest,
-        jsonrpc::{JsonRpcMessage, JsonRpcRequest},
+        capabilities::RootsCapability,
+        jsonrpc::{JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse},
+        metadata::ResponseMeta,
+        trace_context,
     },
     types::{
         client::{ClientCapabilities, ClientInfo},
         completion::{CompleteRequest, CompleteResponse},
         elicitation::{ElicitationRequest, ElicitationResponse},
-        prompts::{GetPromptRequest, GetPromptResponse, ListPromptsRequest, ListPromptsResponse},
+        notifications::{
+            CancelledNotification, LoggingMessageNotification, NOTIFICATION_ACK_METHOD,
+            NotificationAck, ProgressNotification, RESOURCE_CHUNK_METHOD, ResourceChunk,
+            TOOL_CONTENT_CHUNK_METHOD, ToolContentChunk, notification_ack_id,
+            notification_sequence,
+        },
+        prompts::{
+            GetPromptRequest, GetPromptResponse, ListPromptsRequest, ListPromptsResponse, Prompt,
+        },
         resources::{
-            ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
+            ListResourcesRequest, ListResourcesResponse, READ_RESOURCE_STREAM_METHOD,
+            ReadResourceRequest, ReadResourceResponse, ReadResourceStreamRequest,
+            ReadResourceStreamResponse, Resource, ResourceUpdatedNotification,
         },
+        roots::{ListRootsResponse, Root},
         sampling::{CreateMessageRequest, CreateMessageResponse},
         server::{ServerCapabilities, ServerInfo},
-        tools::{ListToolsRequest, ListToolsResponse, ToolCall, ToolResult},
+        tools::{ListToolsRequest, ListToolsResponse, Tool, ToolCall, ToolContent, ToolResult},
     },
+    utils::{CloseTracker, PingEvent, PingPolicy, PingTracker},
+};
+use ultrafast_mcp_transport::{Transport, TransportHealth};
+
+pub mod availability;
+pub mod connect;
+pub mod latency;
+pub mod manager;
+pub mod model_policy;
+pub mod notification_order;
+pub mod notifications;
+pub mod retry;
+pub mod streaming;
+pub mod transform;
+
+use availability::ListChanges;
+pub use availability::ToolAvailability;
+pub use connect::ConnectOptions;
+use latency::LatencyEstimator;
+pub use latency::{AdaptiveTimeouts, LatencyEstimate};
+pub use manager::{
+    ManagedServerConfig, ManagedServerState, ManagedServerStatus, RestartPolicy, ServerManager,
+    ServerManagerHealth,
+};
+pub use model_policy::{ModelDecision, ModelPolicy, RejectedModel, SelectionReason};
+use notification_order::NotificationSequenceTracker;
+pub use notification_order::{
+    ClientNotificationOrderHandler, NotificationOrderMetrics, SequenceAnomaly,
 };
-use ultrafast_mcp_transport::Transport;
+use notifications::NOTIFICATION_BUFFER;
+pub use notifications::{NotificationSubscription, ServerNotification};
+pub use retry::RetryPolicy;
+pub use streaming::ToolCallStream;
+use transform::ResultTransforms;
+pub use transform::{DecodeImages, NormalizeMarkdown, ToolResultTransform, TruncateText};
 
 /// Client-side elicitation handler trait
 #[async_trait::async_trait]
@@ -40,6 +95,95 @@ pub trait ClientElicitationHandler: Send + Sync {
     ) -> MCPResult<ElicitationResponse>;
 }
 
+/// Client-side sampling handler trait
+#[async_trait::async_trait]
+pub trait ClientSamplingHandler: Send + Sync {
+    /// Handle a `sampling/createMessage` request from the server
+    /// This method should run the request against the host's model, usually
+    /// after the user has approved it, and return the generated message
+    async fn handle_sampling_request(
+        &self,
+        request: CreateMessageRequest,
+    ) -> MCPResult<CreateMessageResponse>;
+}
+
+/// A typed result together with the execution metadata the server reported
+/// for it, see the `*_with_meta` methods of [`UltraFastClient`]
+#[derive(Debug, Clone)]
+pub struct WithMeta<T> {
+    result: T,
+    meta: Option<ResponseMeta>,
+}
+
+impl<T> WithMeta<T> {
+    /// The typed result
+    pub fn result(&self) -> &T {
+        &self.result
+    }
+
+    /// How the server executed the request, if it reported it
+    pub fn meta(&self) -> Option<&ResponseMeta> {
+        self.meta.as_ref()
+    }
+
+    pub fn into_result(self) -> T {
+        self.result
+    }
+}
+
+/// Why a response matched no pending request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmatchedResponseKind {
+    /// The request had already timed out
+    Late,
+    /// The request had already received a response
+    Duplicate,
+}
+
+/// A response that arrived after its request was settled
+#[derive(Debug, Clone)]
+pub struct LateResponse {
+    pub kind: UnmatchedResponseKind,
+    /// Method of the original request
+    pub method: String,
+    pub response: JsonRpcResponse,
+}
+
+/// Client-side handler for late and duplicate responses
+///
+/// Without a handler such responses are logged and counted in
+/// [`RequestMetrics`], then dropped.
+#[async_trait::async_trait]
+pub trait ClientLateResponseHandler: Send + Sync {
+    async fn handle_late_response(&self, response: LateResponse);
+}
+
+/// Client-side handler for `notifications/resources/updated`
+///
+/// Called for updates to resources subscribed with
+/// [`UltraFastClient::subscribe_resource`]; updates for other URIs are
+/// logged and dropped.
+#[async_trait::async_trait]
+pub trait ResourceChangeHandler: Send + Sync {
+    async fn handle_resource_updated(&self, uri: String);
+}
+
+/// What the client does when a tool result does not match the tool's
+/// output schema
+///
+/// Output schemas are learned from [`UltraFastClient::list_tools`]; results
+/// of tools without a known schema, and error results, are not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputValidation {
+    /// Results are not checked
+    #[default]
+    Off,
+    /// Mismatches are logged and the result is returned as is
+    Warn,
+    /// Mismatches fail the call with [`ToolError::SchemaValidation`]
+    Fail,
+}
+
 /// MCP Client state
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ClientState {
@@ -70,12 +214,71 @@ impl ClientState {
     }
 }
 
+/// How often expired pending requests are swept
+const PENDING_REQUEST_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How many settled requests are remembered to classify late and duplicate
+/// responses
+const SETTLED_REQUEST_HISTORY: usize = 1024;
+
+/// How many acknowledged notifications are remembered to recognise resends
+const ACKED_NOTIFICATION_HISTORY: usize = 1024;
+
+/// Resource chunks received ahead of the writer before reading pauses
+const RESOURCE_CHUNK_BUFFER: usize = 8;
+
 /// Pending request information
 #[derive(Debug)]
 struct PendingRequest {
-    response_sender: oneshot::Sender<JsonRpcMessage>,
-    #[allow(dead_code)]
-    timeout: tokio::time::Instant,
+    method: String,
+    response_sender: oneshot::Sender<MCPResult<JsonRpcMessage>>,
+    deadline: tokio::time::Instant,
+    /// The operation timeout, by which progress on the request moves
+    /// `deadline`
+    timeout: std::time::Duration,
+    /// The `_meta.progressToken` the request was sent with
+    progress_token: Option<String>,
+}
+
+/// Counters for request/response correlation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestMetrics {
+    /// Requests currently awaiting a response
+    pub pending_requests: usize,
+    /// Requests removed by the sweeper after their deadline passed
+    pub expired_requests: u64,
+    /// Responses to requests that had already timed out
+    pub late_responses: u64,
+    /// Responses to requests that had already been answered
+    pub duplicate_responses: u64,
+    /// Responses whose ID matches no known request
+    pub orphaned_responses: u64,
+}
+
+/// Sizes of the client's connection bookkeeping
+///
+/// `pending_requests` returns to zero once every request is answered or
+/// expires; the remembered settled requests and acknowledged notifications
+/// are bounded histories.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// Requests currently awaiting a response
+    pub pending_requests: usize,
+    /// Settled requests remembered to classify late responses
+    pub settled_requests: usize,
+    /// Acknowledged notifications remembered to recognise resends
+    pub acked_notifications: usize,
+    /// Tool input schemas cached from `tools/list`
+    pub cached_tool_schemas: usize,
+    /// Resources subscribed with `subscribe_resource`
+    pub resource_subscriptions: usize,
+}
+
+/// A request that is no longer pending
+#[derive(Debug)]
+struct SettledRequest {
+    method: String,
+    timed_out: bool,
 }
 
 /// Client state management
@@ -86,7 +289,32 @@ struct ClientStateManager {
     negotiated_version: Option<String>,
     request_id_counter: u64,
     pending_requests: HashMap<u64, PendingRequest>,
-    elicitation_handler: Option<Arc<dyn ClientElicitationHandler>>,
+    settled_requests: HashMap<u64, SettledRequest>,
+    settled_order: VecDeque<u64>,
+    expired_requests: u64,
+    late_responses: u64,
+    duplicate_responses: u64,
+    orphaned_responses: u64,
+    notification_sequence: NotificationSequenceTracker,
+    acked_notifications: HashSet<String>,
+    acked_order: VecDeque<String>,
+    /// Input schemas from `tools/list`, by tool name
+    tool_input_schemas: HashMap<String, Value>,
+    /// Output schemas from `tools/list`, by tool name
+    tool_output_schemas: HashMap<String, Value>,
+    /// Tools annotated idempotent or read-only in `tools/list`, whose calls
+    /// may be retried
+    retry_safe_tools: HashSet<String>,
+    /// URIs and patterns passed to `subscribe_resource`
+    resource_subscriptions: HashSet<String>,
+    /// List changed notifications, for the waits of [`availability`]
+    list_changes: ListChanges,
+    /// Where the chunks of `call_tool_streaming` calls go, by progress token
+    content_streams: HashMap<String, mpsc::UnboundedSender<ToolContent>>,
+    /// Where the chunks of `read_resource_stream` reads go, by progress token
+    resource_streams: HashMap<String, mpsc::Sender<ResourceChunk>>,
+    /// Decoded notifications, for [`UltraFastClient::subscribe_notifications`]
+    notifications: broadcast::Sender<ServerNotification>,
 }
 
 impl ClientStateManager {
@@ -98,7 +326,23 @@ impl ClientStateManager {
             negotiated_version: None,
             request_id_counter: 1,
             pending_requests: HashMap::new(),
-            elicitation_handler: None,
+            settled_requests: HashMap::new(),
+            settled_order: VecDeque::new(),
+            expired_requests: 0,
+            late_responses: 0,
+            duplicate_responses: 0,
+            orphaned_responses: 0,
+            notification_sequence: NotificationSequenceTracker::default(),
+            acked_notifications: HashSet::new(),
+            acked_order: VecDeque::new(),
+            tool_input_schemas: HashMap::new(),
+            tool_output_schemas: HashMap::new(),
+            retry_safe_tools: HashSet::new(),
+            resource_subscriptions: HashSet::new(),
+            list_changes: ListChanges::default(),
+            content_streams: HashMap::new(),
+            resource_streams: HashMap::new(),
+            notifications: broadcast::channel(NOTIFICATION_BUFFER).0,
         }
     }
 
@@ -118,10 +362,6 @@ impl ClientStateManager {
         self.negotiated_version = Some(version);
     }
 
-    fn set_elicitation_handler(&mut self, handler: Option<Arc<dyn ClientElicitationHandler>>) {
-        self.elicitation_handler = handler;
-    }
-
     fn next_request_id(&mut self) -> u64 {
         let id = self.request_id_counter;
         self.request_id_counter += 1;
@@ -135,6 +375,131 @@ impl ClientStateManager {
     fn remove_pending_request(&mut self, id: &u64) -> Option<PendingRequest> {
         self.pending_requests.remove(id)
     }
+
+    /// Give the request sent with `progress_token` its whole timeout again
+    fn extend_deadline(&mut self, progress_token: &str) {
+        let now = tokio::time::Instant::now();
+        for request in self.pending_requests.values_mut() {
+            if request.progress_token.as_deref() == Some(progress_token) {
+                request.deadline = request.deadline.max(now + request.timeout);
+            }
+        }
+    }
+
+    /// Fail every pending request whose deadline has passed with a timeout
+    /// error, returning how many were expired
+    fn expire_pending_requests(&mut self, now: tokio::time::Instant) -> Vec<u64> {
+        let expired: Vec<u64> = self
+            .pending_requests
+            .iter()
+            .filter(|(_, request)| request.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in &expired {
+            if let Some(request) = self.pending_requests.remove(id) {
+                warn!(
+                    "Request {} ({}) expired without a response",
+                    id, request.method
+                );
+                let _ = request
+                    .response_sender
+                    .send(Err(MCPError::Protocol(ProtocolError::RequestTimeout)));
+                self.settle_request(*id, request.method, true);
+            }
+        }
+
+        self.expired_requests += expired.len() as u64;
+        expired
+    }
+
+    /// Fail every pending request because the connection is gone
+    fn fail_pending_requests(&mut self, reason: &str) {
+        let failed: Vec<(u64, PendingRequest)> = self.pending_requests.drain().collect();
+        for (id, request) in failed {
+            warn!("Request {} ({}) failed: {}", id, request.method, reason);
+            let _ = request.response_sender.send(Err(MCPError::Transport(
+                TransportError::ConnectionFailed(reason.to_string()),
+            )));
+            self.settle_request(id, request.method, false);
+        }
+    }
+
+    /// Remember a request that received a response or timed out
+    fn settle_request(&mut self, id: u64, method: String, timed_out: bool) {
+        if self
+            .settled_requests
+            .insert(id, SettledRequest { method, timed_out })
+            .is_none()
+        {
+            self.settled_order.push_back(id);
+        }
+        while self.settled_order.len() > SETTLED_REQUEST_HISTORY {
+            if let Some(oldest) = self.settled_order.pop_front() {
+                self.settled_requests.remove(&oldest);
+            }
+        }
+    }
+
+    /// Remember an acknowledged notification
+    ///
+    /// Returns `false` if it was already acknowledged, i.e. this is a resend.
+    fn remember_acked_notification(&mut self, ack_id: &str) -> bool {
+        if !self.acked_notifications.insert(ack_id.to_string()) {
+            return false;
+        }
+        self.acked_order.push_back(ack_id.to_string());
+        while self.acked_order.len() > ACKED_NOTIFICATION_HISTORY {
+            if let Some(oldest) = self.acked_order.pop_front() {
+                self.acked_notifications.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Classify and count a response that matched no pending request
+    ///
+    /// Returns the kind and original method for late and duplicate
+    /// responses, `None` for responses to unknown requests.
+    fn classify_unmatched_response(
+        &mut self,
+        id: Option<u64>,
+    ) -> Option<(UnmatchedResponseKind, String)> {
+        let Some(settled) = id.and_then(|id| self.settled_requests.get_mut(&id)) else {
+            self.orphaned_responses += 1;
+            return None;
+        };
+
+        if settled.timed_out {
+            // Any further response to the same request is a duplicate
+            settled.timed_out = false;
+            self.late_responses += 1;
+            Some((UnmatchedResponseKind::Late, settled.method.clone()))
+        } else {
+            self.duplicate_responses += 1;
+            Some((UnmatchedResponseKind::Duplicate, settled.method.clone()))
+        }
+    }
+
+    fn stats(&self) -> ClientStats {
+        ClientStats {
+            pending_requests: self.pending_requests.len(),
+            settled_requests: self.settled_requests.len(),
+            acked_notifications: self.acked_notifications.len(),
+            cached_tool_schemas: self.tool_input_schemas.len(),
+            resource_subscriptions: self.resource_subscriptions.len(),
+        }
+    }
+
+    fn request_metrics(&self) -> RequestMetrics {
+        RequestMetrics {
+            pending_requests: self.pending_requests.len(),
+            expired_requests: self.expired_requests,
+            late_responses: self.late_responses,
+            duplicate_responses: self.duplicate_responses,
+            orphaned_responses: self.orphaned_responses,
+        }
+    }
 }
 
 /// UltraFast MCP Client
@@ -143,32 +508,119 @@ pub struct UltraFastClient {
     capabilities: ClientCapabilities,
     state_manager: Arc<RwLock<ClientStateManager>>,
     transport: Arc<RwLock<Option<Box<dyn Transport>>>>,
+    // Asks the message receiver task to release the transport lock
+    transport_wanted: Arc<TransportWanted>,
     message_receiver: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    pending_sweeper: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    ping_monitor: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    ping_policy: Option<PingPolicy>,
+    // Outcomes of the pings sent by the current ping monitor
+    ping_tracker: RwLock<Option<Arc<PingTracker>>>,
+    ping_events: broadcast::Sender<PingEvent>,
+    // Same channel as the state manager's, to subscribe without locking it
+    notifications: broadcast::Sender<ServerNotification>,
+    elicitation_handler: Option<Arc<dyn ClientElicitationHandler>>,
+    sampling_handler: Option<Arc<dyn ClientSamplingHandler>>,
+    // Answer to `roots/list` from the server, if the client exposes roots
+    roots: Arc<RwLock<Option<Vec<Root>>>>,
+    late_response_handler: Option<Arc<dyn ClientLateResponseHandler>>,
+    notification_order_handler: Option<Arc<dyn ClientNotificationOrderHandler>>,
+    resource_change_handler: Option<Arc<dyn ResourceChangeHandler>>,
+    model_policy: Option<Arc<ModelPolicy>>,
     request_timeout: std::time::Duration,
     // Timeout configuration (MCP 2025-06-18 compliance)
     timeout_config: Arc<TimeoutConfig>,
+    // Round-trip times measured by pings, see `with_adaptive_timeouts`
+    latency: Arc<LatencyEstimator>,
+    adaptive_timeouts: Option<AdaptiveTimeouts>,
+    output_validation: OutputValidation,
+    result_transforms: ResultTransforms,
+    retry_policy: Option<RetryPolicy>,
+    locale: Option<String>,
     // Authentication middleware
     #[cfg(feature = "oauth")]
     auth_middleware: Arc<RwLock<Option<ultrafast_mcp_auth::ClientAuthMiddleware>>>,
-    #[cfg(not(feature = "oauth"))]
-    auth_middleware: Arc<RwLock<Option<()>>>,
+    // Open from `connect` until `disconnect`
+    close_tracker: CloseTracker,
+    close_on_drop: bool,
+}
+
+impl Drop for UltraFastClient {
+    fn drop(&mut self) {
+        // Nothing else would stop the monitor, which pings through the
+        // transport
+        if let Some(handle) = self
+            .ping_monitor
+            .try_write()
+            .ok()
+            .and_then(|mut task| task.take())
+        {
+            handle.abort();
+        }
+        if !self.close_on_drop || !self.close_tracker.is_open() {
+            return;
+        }
+        // Mirrors `disconnect`, with the transport closed on a spawned task
+        for task in [&self.message_receiver, &self.pending_sweeper] {
+            if let Some(handle) = task.try_write().ok().and_then(|mut task| task.take()) {
+                handle.abort();
+            }
+        }
+        let transport = self
+            .transport
+            .try_write()
+            .ok()
+            .and_then(|mut transport| transport.take());
+        if let (Some(mut transport), Ok(runtime)) =
+            (transport, tokio::runtime::Handle::try_current())
+        {
+            runtime.spawn(async move {
+                if let Err(e) = transport.close().await {
+                    warn!("Failed to close transport of dropped client: {}", e);
+                }
+            });
+        }
+        self.close_tracker.closed();
+    }
 }
 
 impl UltraFastClient {
     /// Create a new MCP client
     pub fn new(info: ClientInfo, capabilities: ClientCapabilities) -> Self {
+        let state_manager = ClientStateManager::new();
+        let notifications = state_manager.notifications.clone();
         Self {
             info,
             capabilities,
-            state_manager: Arc::new(RwLock::new(ClientStateManager::new())),
+            state_manager: Arc::new(RwLock::new(state_manager)),
             transport: Arc::new(RwLock::new(None)),
+            transport_wanted: Arc::new(TransportWanted::default()),
             message_receiver: Arc::new(RwLock::new(None)),
+            pending_sweeper: Arc::new(RwLock::new(None)),
+            ping_monitor: Arc::new(RwLock::new(None)),
+            ping_policy: None,
+            ping_tracker: RwLock::new(None),
+            ping_events: broadcast::channel(16).0,
+            notifications,
+            elicitation_handler: None,
+            sampling_handler: None,
+            roots: Arc::new(RwLock::new(None)),
+            late_response_handler: None,
+            notification_order_handler: None,
+            resource_change_handler: None,
+            model_policy: None,
             request_timeout: std::time::Duration::from_secs(30),
             timeout_config: Arc::new(TimeoutConfig::default()),
+            latency: Arc::new(LatencyEstimator::default()),
+            adaptive_timeouts: None,
+            output_validation: OutputValidation::Off,
+            result_transforms: ResultTransforms::default(),
+            retry_policy: None,
+            locale: None,
             #[cfg(feature = "oauth")]
             auth_middleware: Arc::new(RwLock::new(None)),
-            #[cfg(not(feature = "oauth"))]
-            auth_middleware: Arc::new(RwLock::new(None)),
+            close_tracker: CloseTracker::new("UltraFastClient"),
+            close_on_drop: false,
         }
     }
 
@@ -178,18 +630,40 @@ impl UltraFastClient {
         capabilities: ClientCapabilities,
         timeout: std::time::Duration,
     ) -> Self {
+        let state_manager = ClientStateManager::new();
+        let notifications = state_manager.notifications.clone();
         Self {
             info,
             capabilities,
-            state_manager: Arc::new(RwLock::new(ClientStateManager::new())),
+            state_manager: Arc::new(RwLock::new(state_manager)),
             transport: Arc::new(RwLock::new(None)),
+            transport_wanted: Arc::new(TransportWanted::default()),
             message_receiver: Arc::new(RwLock::new(None)),
+            pending_sweeper: Arc::new(RwLock::new(None)),
+            ping_monitor: Arc::new(RwLock::new(None)),
+            ping_policy: None,
+            ping_tracker: RwLock::new(None),
+            ping_events: broadcast::channel(16).0,
+            notifications,
+            elicitation_handler: None,
+            sampling_handler: None,
+            roots: Arc::new(RwLock::new(None)),
+            late_response_handler: None,
+            notification_order_handler: None,
+            resource_change_handler: None,
+            model_policy: None,
             request_timeout: timeout,
             timeout_config: Arc::new(TimeoutConfig::default()),
+            latency: Arc::new(LatencyEstimator::default()),
+            adaptive_timeouts: None,
+            output_validation: OutputValidation::Off,
+            result_transforms: ResultTransforms::default(),
+            retry_policy: None,
+            locale: None,
             #[cfg(feature = "oauth")]
             auth_middleware: Arc::new(RwLock::new(None)),
-            #[cfg(not(feature = "oauth"))]
-            auth_middleware: Arc::new(RwLock::new(None)),
+            close_tracker: CloseTracker::new("UltraFastClient"),
+            close_on_drop: false,
         }
     }
 
@@ -222,32 +696,123 @@ impl UltraFastClient {
         self
     }
 
+    /// Scale operation timeouts with the latency measured by pings
+    ///
+    /// Each operation's configured timeout is extended by a multiple of the
+    /// estimated round-trip timeout and bounded as `config` says. Latency is
+    /// measured by [`ping`](Self::ping), periodically once
+    /// [`start_ping_monitoring`](Self::start_ping_monitoring) runs; until the
+    /// first ping the configured timeouts are only bounded.
+    pub fn with_adaptive_timeouts(mut self, config: AdaptiveTimeouts) -> Self {
+        self.adaptive_timeouts = Some(config);
+        self
+    }
+
+    /// Watch the connection with pings, as `policy` says
+    ///
+    /// Monitoring starts once connected. After `policy.max_missed`
+    /// unanswered pings the server is reported unhealthy through
+    /// [`subscribe_ping_events`](Self::subscribe_ping_events) and
+    /// [`health`](Self::health), and with `policy.disconnect` the transport
+    /// is closed, failing pending requests. See
+    /// [`ultrafast_mcp_core::utils::ping_policy`].
+    pub fn with_ping_policy(mut self, policy: PingPolicy) -> Self {
+        self.ping_policy = Some(policy);
+        self
+    }
+
+    /// Check tool results against the output schemas advertised in
+    /// `tools/list`
+    ///
+    /// Applies to [`call_tool`](Self::call_tool) and the calls built on it.
+    /// Catches servers drifting from their declared contract before the
+    /// result reaches application code.
+    pub fn with_output_validation(mut self, policy: OutputValidation) -> Self {
+        self.output_validation = policy;
+        self
+    }
+
+    /// Run `transform` on the result of every tool call, see [`transform`]
+    pub fn with_result_transform(mut self, transform: impl ToolResultTransform + 'static) -> Self {
+        self.result_transforms.push(None, Arc::new(transform));
+        self
+    }
+
+    /// Run `transform` on the results of calls to the tool called `tool`
+    pub fn with_tool_result_transform(
+        mut self,
+        tool: impl Into<String>,
+        transform: impl ToolResultTransform + 'static,
+    ) -> Self {
+        self.result_transforms
+            .push(Some(tool.into()), Arc::new(transform));
+        self
+    }
+
+    /// Send requests that are safe to repeat again when they fail with a
+    /// transient error, see [`retry`]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Ask servers for user-facing messages in `locale`, such as `de-CH`
+    ///
+    /// Sent with every request as `_meta["ultrafast/locale"]`; servers with
+    /// a message catalog render their errors in it. See
+    /// [`ultrafast_mcp_core::i18n`].
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Accept large content items gzip-compressed
+    ///
+    /// Declares the experimental `ultrafast/contentEncoding` capability, so
+    /// servers that support it send big tool outputs and resources in a
+    /// fraction of the bytes. Results are decoded before they are returned,
+    /// see [`ultrafast_mcp_core::content_encoding`].
+    #[cfg(feature = "content-encoding")]
+    pub fn with_content_compression(mut self) -> Self {
+        content_encoding::advertise(&mut self.capabilities);
+        self
+    }
+
+    /// Round-trip latency estimated from pings on the current connection
+    pub fn latency_estimate(&self) -> Option<LatencyEstimate> {
+        self.latency.estimate()
+    }
+
     /// Get operation-specific timeout
+    ///
+    /// Adapted to the measured latency when
+    /// [`with_adaptive_timeouts`](Self::with_adaptive_timeouts) is set.
     pub fn get_operation_timeout(&self, operation: &str) -> std::time::Duration {
-        self.timeout_config.get_timeout_for_operation(operation)
+        operation_timeout(
+            &self.timeout_config,
+            self.adaptive_timeouts.as_ref(),
+            &self.latency,
+            operation,
+        )
     }
 
     /// Set authentication method
+    ///
+    /// Builder methods run before the client is shared, so the middleware is
+    /// replaced outright rather than written through the lock; this is safe to
+    /// call from within an async runtime.
     #[cfg(feature = "oauth")]
-    pub fn with_auth(self, auth_method: ultrafast_mcp_auth::AuthMethod) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
+    pub fn with_auth(mut self, auth_method: ultrafast_mcp_auth::AuthMethod) -> Self {
+        self.auth_middleware = Arc::new(RwLock::new(Some(
+            ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method),
+        )));
         self
     }
 
     /// Set Bearer token authentication
     #[cfg(feature = "oauth")]
     pub fn with_bearer_auth(self, token: String) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let auth_method = ultrafast_mcp_auth::AuthMethod::bearer(token);
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::bearer(token))
     }
 
     /// Set Bearer token authentication with auto-refresh
@@ -259,77 +824,40 @@ impl UltraFastClient {
             + Send
             + 'static,
     {
-        #[cfg(feature = "oauth")]
-        {
-            let bearer_auth =
-                ultrafast_mcp_auth::BearerAuth::new(token).with_auto_refresh(refresh_fn);
-            let auth_method = ultrafast_mcp_auth::AuthMethod::Bearer(bearer_auth);
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        let bearer_auth = ultrafast_mcp_auth::BearerAuth::new(token).with_auto_refresh(refresh_fn);
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::Bearer(bearer_auth))
     }
 
     /// Set OAuth authentication
     #[cfg(feature = "oauth")]
     pub fn with_oauth_auth(self, config: ultrafast_mcp_auth::OAuthConfig) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let auth_method = ultrafast_mcp_auth::AuthMethod::oauth(config);
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::oauth(config))
     }
 
     /// Set API key authentication
     #[cfg(feature = "oauth")]
     pub fn with_api_key_auth(self, api_key: String) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let auth_method = ultrafast_mcp_auth::AuthMethod::api_key(api_key);
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::api_key(api_key))
     }
 
     /// Set API key authentication with custom header name
     #[cfg(feature = "oauth")]
     pub fn with_api_key_auth_custom(self, api_key: String, header_name: String) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let api_key_auth =
-                ultrafast_mcp_auth::ApiKeyAuth::new(api_key).with_header_name(header_name);
-            let auth_method = ultrafast_mcp_auth::AuthMethod::ApiKey(api_key_auth);
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        let api_key_auth =
+            ultrafast_mcp_auth::ApiKeyAuth::new(api_key).with_header_name(header_name);
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::ApiKey(api_key_auth))
     }
 
     /// Set Basic authentication
     #[cfg(feature = "oauth")]
     pub fn with_basic_auth(self, username: String, password: String) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let auth_method = ultrafast_mcp_auth::AuthMethod::basic(username, password);
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::basic(username, password))
     }
 
     /// Set custom header authentication
     #[cfg(feature = "oauth")]
     pub fn with_custom_auth(self) -> Self {
-        #[cfg(feature = "oauth")]
-        {
-            let auth_method = ultrafast_mcp_auth::AuthMethod::custom();
-            let mut auth = self.auth_middleware.blocking_write();
-            *auth = Some(ultrafast_mcp_auth::ClientAuthMiddleware::new(auth_method));
-        }
-        self
+        self.with_auth(ultrafast_mcp_auth::AuthMethod::custom())
     }
 
     /// Get authentication headers for requests
@@ -345,151 +873,262 @@ impl UltraFastClient {
     }
 
     /// Set elicitation handler for handling server-initiated elicitation requests
-    pub fn with_elicitation_handler(self, handler: Arc<dyn ClientElicitationHandler>) -> Self {
-        let state_manager = self.state_manager.clone();
-        tokio::spawn(async move {
-            let mut state = state_manager.write().await;
-            state.set_elicitation_handler(Some(handler));
-        });
+    ///
+    /// The handler is in place before `connect`, so no elicitation request
+    /// can arrive ahead of it.
+    pub fn with_elicitation_handler(mut self, handler: Arc<dyn ClientElicitationHandler>) -> Self {
+        self.elicitation_handler = Some(handler);
+        self
+    }
+
+    /// Set sampling handler for handling server-initiated sampling requests
+    ///
+    /// Servers only send sampling requests to clients declaring the
+    /// `sampling` capability, which must be set in the client's capabilities
+    /// alongside this handler.
+    pub fn with_sampling_handler(mut self, handler: Arc<dyn ClientSamplingHandler>) -> Self {
+        self.sampling_handler = Some(handler);
+        self
+    }
+
+    /// Expose filesystem roots to the server
+    ///
+    /// The server receives them in answer to `roots/list`. Declares the
+    /// `roots` capability if the client's capabilities do not already.
+    pub fn with_roots(mut self, roots: Vec<Root>) -> Self {
+        self.roots = Arc::new(RwLock::new(Some(roots)));
+        if self.capabilities.roots.is_none() {
+            self.capabilities.roots = Some(RootsCapability {
+                list_changed: Some(true),
+            });
+        }
+        self
+    }
+
+    /// Set a handler that receives responses arriving after their request
+    /// timed out or was already answered
+    pub fn with_late_response_handler(
+        mut self,
+        handler: Arc<dyn ClientLateResponseHandler>,
+    ) -> Self {
+        self.late_response_handler = Some(handler);
+        self
+    }
+
+    /// Set a handler that is told about notifications arriving out of
+    /// sequence
+    pub fn with_notification_order_handler(
+        mut self,
+        handler: Arc<dyn ClientNotificationOrderHandler>,
+    ) -> Self {
+        self.notification_order_handler = Some(handler);
+        self
+    }
+
+    /// Set a handler that is told when a subscribed resource changes
+    pub fn with_resource_change_handler(mut self, handler: Arc<dyn ResourceChangeHandler>) -> Self {
+        self.resource_change_handler = Some(handler);
+        self
+    }
+
+    /// Disconnect when the client is dropped while still connected
+    ///
+    /// Without this, a client dropped without [`disconnect`](Self::disconnect)
+    /// leaves its transport open, and debug builds log a warning. The
+    /// transport is closed on a spawned task, so this requires a tokio
+    /// runtime at drop time; the server is not sent a `shutdown` request.
+    pub fn close_on_drop(mut self) -> Self {
+        self.close_on_drop = true;
+        self
+    }
+
+    /// Set the policy that maps server model preferences onto the host's
+    /// models for sampling requests
+    pub fn with_model_policy(mut self, policy: Arc<ModelPolicy>) -> Self {
+        self.model_policy = Some(policy);
         self
     }
 
+    /// Get the configured model policy
+    pub fn model_policy(&self) -> Option<&Arc<ModelPolicy>> {
+        self.model_policy.as_ref()
+    }
+
     /// Connect to a server using the provided transport
     pub async fn connect(&self, transport: Box<dyn Transport>) -> MCPResult<()> {
         info!("Connecting to MCP server");
 
         {
-            let mut transport_guard = self.transport.write().await;
+            let mut transport_guard = self.lock_transport().await;
             *transport_guard = Some(transport);
         }
 
         // Start message receiver task
         self.start_message_receiver().await?;
+        self.start_pending_sweeper().await;
+
+        self.close_tracker.opened();
 
         // Initialize the connection
         self.initialize().await?;
+        if let Some(policy) = &self.ping_policy {
+            self.start_ping_monitoring(policy.interval).await?;
+        }
 
         info!("Successfully connected to MCP server");
         Ok(())
     }
 
+    /// Connect to a server as described by `options`
+    ///
+    /// The transport is opened here, with the configuration set on the
+    /// client and in `options` applied only now; see [`connect`](mod@connect).
+    pub async fn connect_with(&self, options: ConnectOptions) -> MCPResult<()> {
+        let transport = options.open(self).await?;
+        self.connect(transport).await
+    }
+
+    /// Start the task that expires pending requests past their deadline
+    ///
+    /// Expired requests are failed with `RequestTimeout` and cancelled on
+    /// the server. The task only holds weak references to the client state
+    /// and exits once the client is dropped.
+    async fn start_pending_sweeper(&self) {
+        let state_manager = Arc::downgrade(&self.state_manager);
+        let transport = Arc::downgrade(&self.transport);
+        let handle = tokio::spawn(Self::sweep_pending_requests(
+            state_manager,
+            transport,
+            self.transport_wanted.clone(),
+        ));
+
+        if let Some(previous) = self.pending_sweeper.write().await.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    async fn sweep_pending_requests(
+        state_manager: Weak<RwLock<ClientStateManager>>,
+        transport: Weak<RwLock<Option<Box<dyn Transport>>>>,
+        transport_wanted: Arc<TransportWanted>,
+    ) {
+        let mut interval = tokio::time::interval(PENDING_REQUEST_SWEEP_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let (Some(state_manager), Some(transport)) =
+                (state_manager.upgrade(), transport.upgrade())
+            else {
+                break;
+            };
+            Self::sweep_pending_requests_once(&state_manager, &transport, &transport_wanted).await;
+        }
+    }
+
+    async fn sweep_pending_requests_once(
+        state_manager: &RwLock<ClientStateManager>,
+        transport: &RwLock<Option<Box<dyn Transport>>>,
+        transport_wanted: &TransportWanted,
+    ) {
+        let expired = state_manager
+            .write()
+            .await
+            .expire_pending_requests(tokio::time::Instant::now());
+        if !expired.is_empty() {
+            cancel_timed_out_requests(transport, transport_wanted, &expired).await;
+        }
+    }
+
     /// Start the message receiver task for handling responses
     async fn start_message_receiver(&self) -> MCPResult<()> {
-        let transport = self.transport.clone();
+        let transport_lock = self.transport.clone();
+        let transport_wanted = self.transport_wanted.clone();
         let state_manager = self.state_manager.clone();
+        let elicitation_handler = self.elicitation_handler.clone();
+        let sampling_handler = self.sampling_handler.clone();
+        let roots = self.roots.clone();
+        let late_response_handler = self.late_response_handler.clone();
+        let notification_order_handler = self.notification_order_handler.clone();
+        let resource_change_handler = self.resource_change_handler.clone();
 
         let handle = tokio::spawn(async move {
-            let mut transport_guard = transport.write().await;
-            let transport = transport_guard
-                .as_mut()
-                .expect("Transport should be available");
-
             loop {
-                match transport.receive_message().await {
+                let mut transport_guard = transport_lock.write().await;
+                // A sender that asked for the transport before this task
+                // took the lock goes first
+                if transport_wanted.is_wanted() {
+                    drop(transport_guard);
+                    tokio::task::yield_now().await;
+                    continue;
+                }
+                let transport = transport_guard
+                    .as_mut()
+                    .expect("Transport should be available");
+
+                // Receiving is cancel-safe, so the lock can be handed to a
+                // sender while waiting
+                let received = tokio::select! {
+                    received = transport.receive_message() => received,
+                    _ = transport_wanted.notified() => {
+                        drop(transport_guard);
+                        tokio::task::yield_now().await;
+                        continue;
+                    }
+                };
+                match received {
                     Ok(message) => {
                         match &message {
                             JsonRpcMessage::Response(response) => {
-                                if let Some(id) = &response.id {
-                                    if let Ok(id_num) = serde_json::from_value::<u64>(
-                                        serde_json::to_value(id).unwrap_or_default(),
-                                    ) {
-                                        let mut state = state_manager.write().await;
-                                        if let Some(pending_req) =
-                                            state.remove_pending_request(&id_num)
-                                        {
-                                            // Send response to waiting request
-                                            let _ = pending_req.response_sender.send(message);
-                                        }
-                                    }
-                                }
+                                Self::deliver_response(
+                                    response.clone(),
+                                    &state_manager,
+                                    late_response_handler.as_deref(),
+                                )
+                                .await;
                             }
                             JsonRpcMessage::Request(request) if request.id.is_none() => {
                                 // This is a notification, handle it
-                                Self::handle_notification_static(request.clone()).await;
+                                Self::receive_notification(
+                                    request,
+                                    transport.as_mut(),
+                                    &state_manager,
+                                    notification_order_handler.as_deref(),
+                                    resource_change_handler.as_deref(),
+                                )
+                                .await;
                             }
                             JsonRpcMessage::Request(request) => {
-                                // This is a request without ID, handle as elicitation
-                                if request.method == "elicitation/create" {
-                                    info!("Processing elicitation request from server");
-
-                                    // Get the elicitation handler from state manager
-                                    let elicitation_handler = {
-                                        let state = state_manager.read().await;
-                                        state.elicitation_handler.clone()
-                                    };
-
-                                    if let Some(handler) = elicitation_handler {
-                                        // Parse the elicitation request
-                                        if let Ok(elicitation_request) =
-                                            serde_json::from_value::<ElicitationRequest>(
-                                                request.params.clone().unwrap_or_default(),
-                                            )
-                                        {
-                                            // Handle the elicitation request
-                                            match handler
-                                                .handle_elicitation_request(elicitation_request)
-                                                .await
-                                            {
-                                                Ok(response) => {
-                                                    // Send the response back to the server
-                                                    let response_params = match serde_json::to_value(response) {
-                                                        Ok(params) => Some(params),
-                                                        Err(e) => {
-                                                            error!("Failed to serialize elicitation response: {}", e);
-                                                            continue;
-                                                        }
-                                                    };
-                                                    
-                                                    let response_message = JsonRpcMessage::Request(
-                                                        JsonRpcRequest::new(
-                                                            "elicitation/respond".to_string(),
-                                                            response_params,
-                                                            None, // No ID for elicitation response
-                                                        ),
-                                                    );
-
-                                                    if let Err(e) = transport
-                                                        .send_message(response_message)
-                                                        .await
-                                                    {
-                                                        error!(
-                                                            "Failed to send elicitation response: {}",
-                                                            e
-                                                        );
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    error!(
-                                                        "Failed to handle elicitation request: {}",
-                                                        e
-                                                    );
-                                                }
-                                            }
-                                        } else {
-                                            error!("Failed to parse elicitation request");
-                                        }
-                                    } else {
-                                        warn!(
-                                            "No elicitation handler configured, ignoring elicitation request"
-                                        );
-                                    }
-                                } else {
-                                    warn!(
-                                        "Received unexpected request without ID: {}",
-                                        request.method
-                                    );
-                                }
+                                Self::answer_server_request(
+                                    request,
+                                    transport.as_mut(),
+                                    elicitation_handler.as_deref(),
+                                    sampling_handler.as_deref(),
+                                    &roots,
+                                )
+                                .await;
                             }
                             JsonRpcMessage::Notification(notification) => {
                                 // Handle notification
-                                Self::handle_notification_static(notification.clone()).await;
+                                Self::receive_notification(
+                                    notification,
+                                    transport.as_mut(),
+                                    &state_manager,
+                                    notification_order_handler.as_deref(),
+                                    resource_change_handler.as_deref(),
+                                )
+                                .await;
                             }
                         }
                     }
                     Err(e) => {
                         // Only log as error if it's not a normal connection closure
-                        if !e.to_string().contains("Connection closed") {
+                        if !matches!(e, ultrafast_mcp_transport::TransportError::ConnectionClosed) {
                             error!("Transport error in message receiver: {}", e);
+                            // Nothing will answer the requests still waiting
+                            state_manager
+                                .write()
+                                .await
+                                .fail_pending_requests(&e.to_string());
                         } else {
                             info!("Transport connection closed (normal shutdown)");
                         }
@@ -507,305 +1146,498 @@ impl UltraFastClient {
         Ok(())
     }
 
-    async fn handle_notification_static(notification: JsonRpcRequest) {
-        match notification.method.as_str() {
-            "initialized" => {
-                info!("Received initialized notification");
-            }
-            "notifications/tools/listChanged" => {
-                info!("Received tools list changed notification");
-            }
-            "notifications/resources/listChanged" => {
-                info!("Received resources list changed notification");
-            }
-            "notifications/prompts/listChanged" => {
-                info!("Received prompts list changed notification");
+    /// Hand a response to the request waiting for it
+    async fn deliver_response(
+        response: JsonRpcResponse,
+        state_manager: &RwLock<ClientStateManager>,
+        late_response_handler: Option<&dyn ClientLateResponseHandler>,
+    ) {
+        let id = response_id(&response);
+        let mut state = state_manager.write().await;
+        match id.and_then(|id| state.remove_pending_request(&id).map(|req| (id, req))) {
+            Some((id, pending)) => {
+                state.settle_request(id, pending.method, false);
+                let _ = pending
+                    .response_sender
+                    .send(Ok(JsonRpcMessage::Response(response)));
             }
-            "notifications/roots/listChanged" => {
-                info!("Received roots list changed notification");
-            }
-            "elicitation/create" => {
-                info!("Received elicitation request from server");
-                // Note: This should be handled by the client's elicitation handler
-                // The actual handling is done in the message receiver loop
-            }
-            _ => {
-                warn!("Unknown notification method: {}", notification.method);
+            None => {
+                let unmatched = state.classify_unmatched_response(id);
+                drop(state);
+                Self::handle_unmatched_response(unmatched, response, late_response_handler).await;
             }
         }
     }
 
-    /// Connect to a server using STDIO transport
-    pub async fn connect_stdio(&self) -> MCPResult<()> {
-        let stdio_transport = ultrafast_mcp_transport::stdio::StdioTransport::new()
+    /// Answer a request initiated by the server with a response carrying
+    /// its ID
+    async fn answer_server_request(
+        request: &JsonRpcRequest,
+        transport: &mut dyn Transport,
+        elicitation_handler: Option<&dyn ClientElicitationHandler>,
+        sampling_handler: Option<&dyn ClientSamplingHandler>,
+        roots: &RwLock<Option<Vec<Root>>>,
+    ) {
+        let roots = roots.read().await.clone();
+        let response = Self::handle_server_request(
+            request,
+            elicitation_handler,
+            sampling_handler,
+            roots.as_deref(),
+        )
+        .await;
+        if let Err(e) = transport
+            .send_message(JsonRpcMessage::Response(response))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-        self.connect(Box::new(stdio_transport)).await
+        {
+            error!(
+                "Failed to send response to server request {}: {}",
+                request.method, e
+            );
+        }
     }
 
-    /// Connect to a server using Streamable HTTP transport
-    /// This method will automatically use any client-level authentication configured
-    #[cfg(feature = "http")]
-    pub async fn connect_streamable_http(&self, url: &str) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
+    async fn handle_unmatched_response(
+        unmatched: Option<(UnmatchedResponseKind, String)>,
+        response: JsonRpcResponse,
+        handler: Option<&dyn ClientLateResponseHandler>,
+    ) {
+        let Some((kind, method)) = unmatched else {
+            warn!(
+                "Received response {:?} matching no known request",
+                response.id
+            );
+            return;
         };
 
-        let mut config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: None,
-            oauth_config: None,
-            auth_method: None,
-        };
+        match kind {
+            UnmatchedResponseKind::Late => warn!(
+                "Late response for request {:?} ({}) arrived after it timed out",
+                response.id, method
+            ),
+            UnmatchedResponseKind::Duplicate => warn!(
+                "Duplicate response for request {:?} ({})",
+                response.id, method
+            ),
+        }
 
-        // Integrate with client-level auth middleware if available
-        #[cfg(feature = "oauth")]
-        {
-            if let Some(auth) = self.auth_middleware.read().await.as_ref() {
-                config.auth_method = Some(auth.get_auth_method().clone());
-            }
+        if let Some(handler) = handler {
+            handler
+                .handle_late_response(LateResponse {
+                    kind,
+                    method,
+                    response,
+                })
+                .await;
         }
+    }
 
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
+    /// Acknowledge, sequence-check and handle a notification from the server
+    async fn receive_notification(
+        notification: &JsonRpcRequest,
+        transport: &mut dyn Transport,
+        state_manager: &RwLock<ClientStateManager>,
+        order_handler: Option<&dyn ClientNotificationOrderHandler>,
+        resource_change_handler: Option<&dyn ResourceChangeHandler>,
+    ) {
+        if let Some(ack_id) = notification_ack_id(notification.params.as_ref()) {
+            let ack = NotificationAck {
+                ack_id: ack_id.to_string(),
+            };
+            let ack = JsonRpcRequest::notification(
+                NOTIFICATION_ACK_METHOD.to_string(),
+                serde_json::to_value(ack).ok(),
+            );
+            if let Err(e) = transport
+                .send_message(JsonRpcMessage::Notification(ack))
+                .await
+            {
+                error!("Failed to acknowledge notification {}: {}", ack_id, e);
+            }
+            // A resend means our earlier ack was lost; it was handled already
+            if !state_manager
+                .write()
+                .await
+                .remember_acked_notification(ack_id)
+            {
+                info!(
+                    "Ignoring resent notification {} ({})",
+                    notification.method, ack_id
+                );
+                return;
+            }
+        }
 
-        // Connect the transport first
-        http_transport
-            .connect()
+        Self::check_notification_sequence(notification, state_manager, order_handler).await;
+        if notification.method == "notifications/tools/listChanged" {
+            let mut state = state_manager.write().await;
+            state.tool_input_schemas.clear();
+            state.tool_output_schemas.clear();
+            state.retry_safe_tools.clear();
+        }
+        state_manager
+            .read()
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        self.connect(Box::new(http_transport)).await
+            .list_changes
+            .observe(&notification.method);
+        if notification.method == TOOL_CONTENT_CHUNK_METHOD {
+            Self::deliver_tool_content(notification, state_manager).await;
+            return;
+        }
+        if notification.method == RESOURCE_CHUNK_METHOD {
+            Self::deliver_resource_chunk(notification, state_manager).await;
+            return;
+        }
+        Self::publish_notification(notification, state_manager).await;
+        if notification.method == "notifications/resources/updated" {
+            Self::deliver_resource_update(notification, state_manager, resource_change_handler)
+                .await;
+        }
     }
 
-    /// Connect to a server using HTTP transport with authentication
-    #[cfg(feature = "http")]
-    pub async fn connect_http_with_auth(&self, url: &str, auth_token: String) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
+    /// Pass an update for a subscribed resource to the change handler
+    async fn deliver_resource_update(
+        notification: &JsonRpcRequest,
+        state_manager: &RwLock<ClientStateManager>,
+        handler: Option<&dyn ResourceChangeHandler>,
+    ) {
+        let update = match serde_json::from_value::<ResourceUpdatedNotification>(
+            notification.params.clone().unwrap_or_default(),
+        ) {
+            Ok(update) => update,
+            Err(e) => {
+                warn!("Invalid resource updated notification: {}", e);
+                return;
+            }
         };
+        let subscribed = state_manager
+            .read()
+            .await
+            .resource_subscriptions
+            .iter()
+            .any(|subscription| subscription_covers(subscription, &update.uri));
+        if !subscribed {
+            warn!("Update for unsubscribed resource {}", update.uri);
+            return;
+        }
+        if let Some(handler) = handler {
+            handler.handle_resource_updated(update.uri).await;
+        }
+    }
 
-        let config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: Some(auth_token),
-            oauth_config: None,
-            auth_method: None,
+    /// Pass a streamed chunk of a tool result to its `call_tool_streaming`
+    async fn deliver_tool_content(
+        notification: &JsonRpcRequest,
+        state_manager: &RwLock<ClientStateManager>,
+    ) {
+        let chunk = match serde_json::from_value::<ToolContentChunk>(
+            notification.params.clone().unwrap_or_default(),
+        ) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                warn!("Invalid tool content notification: {}", e);
+                return;
+            }
+        };
+        let Some(token) = chunk.progress_token.as_str() else {
+            warn!("Tool content for unknown call {}", chunk.progress_token);
+            return;
+        };
+        let mut state = state_manager.write().await;
+        let Some(chunks) = state.content_streams.get(token) else {
+            warn!("Tool content for unknown call {}", token);
+            return;
         };
+        // The stream was dropped before its call completed
+        if chunks.send(chunk.content).is_err() {
+            state.content_streams.remove(token);
+        }
+    }
 
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
+    async fn deliver_resource_chunk(
+        notification: &JsonRpcRequest,
+        state_manager: &RwLock<ClientStateManager>,
+    ) {
+        let chunk = match serde_json::from_value::<ResourceChunk>(
+            notification.params.clone().unwrap_or_default(),
+        ) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                warn!("Invalid resource chunk notification: {}", e);
+                return;
+            }
+        };
+        let Some(token) = chunk.progress_token.as_str().map(str::to_string) else {
+            warn!("Resource chunk for unknown read {}", chunk.progress_token);
+            return;
+        };
+        let Some(chunks) = state_manager
+            .read()
+            .await
+            .resource_streams
+            .get(&token)
+            .cloned()
+        else {
+            warn!("Resource chunk for unknown read {}", token);
+            return;
+        };
+        // Waits for the writer, so reading does not outpace it; each chunk
+        // shows the read is progressing, so it does not time out
+        let sent = chunks.send(chunk).await;
+        let mut state = state_manager.write().await;
+        match sent {
+            Ok(()) => state.extend_deadline(&token),
+            // The read failed writing an earlier chunk
+            Err(_) => {
+                state.resource_streams.remove(&token);
+            }
+        }
+    }
 
-        // Connect the transport first
-        http_transport
-            .connect()
+    async fn check_notification_sequence(
+        notification: &JsonRpcRequest,
+        state_manager: &RwLock<ClientStateManager>,
+        handler: Option<&dyn ClientNotificationOrderHandler>,
+    ) {
+        let Some(sequence) = notification_sequence(notification.params.as_ref()) else {
+            return;
+        };
+        let anomaly = state_manager
+            .write()
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
+            .notification_sequence
+            .observe(&notification.method, sequence);
+        let Some(anomaly) = anomaly else {
+            return;
+        };
 
-        self.connect(Box::new(http_transport)).await
+        warn!("Notification out of sequence: {:?}", anomaly);
+        if let Some(handler) = handler {
+            handler.handle_sequence_anomaly(anomaly).await;
+        }
     }
 
-    /// Connect to a server using Streamable HTTP transport with Bearer token authentication
-    #[cfg(all(feature = "http", feature = "oauth"))]
-    pub async fn connect_streamable_http_with_bearer(
-        &self,
-        url: &str,
-        token: String,
-    ) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
+    /// Answer a request initiated by the server
+    async fn handle_server_request(
+        request: &JsonRpcRequest,
+        elicitation_handler: Option<&dyn ClientElicitationHandler>,
+        sampling_handler: Option<&dyn ClientSamplingHandler>,
+        roots: Option<&[Root]>,
+    ) -> JsonRpcResponse {
+        let result = match request.method.as_str() {
+            "ping" => Ok(serde_json::json!({})),
+            "roots/list" => match roots {
+                Some(roots) => serde_json::to_value(ListRootsResponse {
+                    roots: roots.to_vec(),
+                })
+                .map_err(|e| JsonRpcError::internal_error(Some(e.to_string()))),
+                None => {
+                    warn!("No roots configured, rejecting roots request");
+                    Err(JsonRpcError::capability_not_supported("roots".to_string()))
+                }
+            },
+            "elicitation/create" => {
+                info!("Processing elicitation request from server");
+                match elicitation_handler {
+                    Some(handler) => Self::handle_elicitation(request, handler).await,
+                    None => {
+                        warn!("No elicitation handler configured, declining elicitation request");
+                        Err(JsonRpcError::capability_not_supported(
+                            "elicitation".to_string(),
+                        ))
+                    }
+                }
+            }
+            "sampling/createMessage" => {
+                info!("Processing sampling request from server");
+                match sampling_handler {
+                    Some(handler) => Self::handle_sampling(request, handler).await,
+                    None => {
+                        warn!("No sampling handler configured, rejecting sampling request");
+                        Err(JsonRpcError::capability_not_supported(
+                            "sampling".to_string(),
+                        ))
+                    }
+                }
+            }
+            method => {
+                warn!("Received unsupported request from server: {}", method);
+                Err(JsonRpcError::method_not_found(method.to_string()))
+            }
         };
 
-        let config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: None,
-            oauth_config: None,
-            auth_method: Some(ultrafast_mcp_auth::AuthMethod::bearer(token)),
-        };
+        match result {
+            Ok(value) => JsonRpcResponse::success(value, request.id.clone()),
+            Err(error) => JsonRpcResponse::error(error, request.id.clone()),
+        }
+    }
+
+    async fn handle_elicitation(
+        request: &JsonRpcRequest,
+        handler: &dyn ClientElicitationHandler,
+    ) -> Result<Value, JsonRpcError> {
+        let elicitation_request = serde_json::from_value::<ElicitationRequest>(
+            request.params.clone().unwrap_or_default(),
+        )
+        .map_err(|e| JsonRpcError::invalid_params(Some(e.to_string())))?;
+
+        let response = handler
+            .handle_elicitation_request(elicitation_request)
+            .await
+            .map_err(|e| {
+                error!("Failed to handle elicitation request: {}", e);
+                JsonRpcError::internal_error(Some(e.to_string()))
+            })?;
+
+        serde_json::to_value(response)
+            .map_err(|e| JsonRpcError::internal_error(Some(e.to_string())))
+    }
+
+    async fn handle_sampling(
+        request: &JsonRpcRequest,
+        handler: &dyn ClientSamplingHandler,
+    ) -> Result<Value, JsonRpcError> {
+        let sampling_request = serde_json::from_value::<CreateMessageRequest>(
+            request.params.clone().unwrap_or_default(),
+        )
+        .map_err(|e| JsonRpcError::invalid_params(Some(e.to_string())))?;
+
+        let response = handler
+            .handle_sampling_request(sampling_request)
+            .await
+            .map_err(|e| {
+                error!("Failed to handle sampling request: {}", e);
+                JsonRpcError::internal_error(Some(e.to_string()))
+            })?;
+
+        serde_json::to_value(response)
+            .map_err(|e| JsonRpcError::internal_error(Some(e.to_string())))
+    }
+
+    /// Decode a notification for the subscribers of `subscribe_notifications`
+    async fn publish_notification(
+        notification: &JsonRpcRequest,
+        state_manager: &RwLock<ClientStateManager>,
+    ) {
+        let notification = ServerNotification::from_notification(notification);
+        if let ServerNotification::Other { method, .. } = &notification {
+            debug!("Received notification {}", method);
+        }
+        // Nobody may be subscribed
+        let _ = state_manager.read().await.notifications.send(notification);
+    }
+
+    /// Connect to a server using STDIO transport
+    pub async fn connect_stdio(&self) -> MCPResult<()> {
+        self.connect_with(ConnectOptions::stdio()).await
+    }
 
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
+    /// Connect to a server using Streamable HTTP transport
+    /// This method will automatically use any client-level authentication configured
+    #[cfg(feature = "http")]
+    pub async fn connect_streamable_http(&self, url: &str) -> MCPResult<()> {
+        self.connect_with(ConnectOptions::streamable_http(url))
+            .await
+    }
 
-        // Connect the transport first
-        http_transport
-            .connect()
+    /// Connect to a server using HTTP transport with authentication
+    #[cfg(feature = "http")]
+    #[deprecated(
+        note = "use `connect_with(ConnectOptions::streamable_http(url).with_auth_token(token))`"
+    )]
+    pub async fn connect_http_with_auth(&self, url: &str, auth_token: String) -> MCPResult<()> {
+        self.connect_with(ConnectOptions::streamable_http(url).with_auth_token(auth_token))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
+    }
 
-        self.connect(Box::new(http_transport)).await
+    /// Connect to a server using Streamable HTTP transport with Bearer token authentication
+    #[cfg(all(feature = "http", feature = "oauth"))]
+    #[deprecated(
+        note = "use `connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::bearer(token)))`"
+    )]
+    pub async fn connect_streamable_http_with_bearer(
+        &self,
+        url: &str,
+        token: String,
+    ) -> MCPResult<()> {
+        let auth = ultrafast_mcp_auth::AuthMethod::bearer(token);
+        self.connect_with(ConnectOptions::streamable_http(url).with_auth(auth))
+            .await
     }
 
     /// Connect to a server using Streamable HTTP transport with OAuth 2.1 authentication
     #[cfg(all(feature = "http", feature = "oauth"))]
+    #[deprecated(
+        note = "use `connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::oauth(config)))`"
+    )]
     pub async fn connect_streamable_http_with_oauth(
         &self,
         url: &str,
         oauth_config: ultrafast_mcp_auth::OAuthConfig,
     ) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
-        };
-
-        let config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: None,
-            oauth_config: Some(oauth_config.clone()),
-            auth_method: Some(ultrafast_mcp_auth::AuthMethod::oauth(oauth_config)),
-        };
-
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        // Connect the transport first
-        http_transport
-            .connect()
+        let auth = ultrafast_mcp_auth::AuthMethod::oauth(oauth_config);
+        self.connect_with(ConnectOptions::streamable_http(url).with_auth(auth))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        self.connect(Box::new(http_transport)).await
     }
 
     /// Connect to a server using Streamable HTTP transport with API key authentication
     #[cfg(all(feature = "http", feature = "oauth"))]
+    #[deprecated(
+        note = "use `connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::api_key(key)))`"
+    )]
     pub async fn connect_streamable_http_with_api_key(
         &self,
         url: &str,
         api_key: String,
     ) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
-        };
-
-        let config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: None,
-            oauth_config: None,
-            auth_method: Some(ultrafast_mcp_auth::AuthMethod::api_key(api_key)),
-        };
-
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        // Connect the transport first
-        http_transport
-            .connect()
+        let auth = ultrafast_mcp_auth::AuthMethod::api_key(api_key);
+        self.connect_with(ConnectOptions::streamable_http(url).with_auth(auth))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        self.connect(Box::new(http_transport)).await
     }
 
     /// Connect to a server using Streamable HTTP transport with API key authentication (custom header)
     #[cfg(all(feature = "http", feature = "oauth"))]
+    #[deprecated(
+        note = "use `connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::ApiKey(..)))`"
+    )]
     pub async fn connect_streamable_http_with_api_key_custom(
         &self,
         url: &str,
         api_key: String,
         header_name: String,
     ) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
-        };
-
         let api_key_auth =
             ultrafast_mcp_auth::ApiKeyAuth::new(api_key).with_header_name(header_name);
-        let auth_method = ultrafast_mcp_auth::AuthMethod::ApiKey(api_key_auth);
-
-        let config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: None,
-            oauth_config: None,
-            auth_method: Some(auth_method),
-        };
-
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        // Connect the transport first
-        http_transport
-            .connect()
+        let auth = ultrafast_mcp_auth::AuthMethod::ApiKey(api_key_auth);
+        self.connect_with(ConnectOptions::streamable_http(url).with_auth(auth))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        self.connect(Box::new(http_transport)).await
     }
 
     /// Connect to a server using Streamable HTTP transport with Basic authentication
     #[cfg(all(feature = "http", feature = "oauth"))]
+    #[deprecated(
+        note = "use `connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::basic(username, password)))`"
+    )]
     pub async fn connect_streamable_http_with_basic(
         &self,
         url: &str,
         username: String,
         password: String,
     ) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::{
-            StreamableHttpClient, StreamableHttpClientConfig,
-        };
-
-        let config = StreamableHttpClientConfig {
-            base_url: url.to_string(),
-            session_id: None,
-            protocol_version: "2025-06-18".to_string(),
-            timeout: self.request_timeout,
-            max_retries: 3,
-            auth_token: None,
-            oauth_config: None,
-            auth_method: Some(ultrafast_mcp_auth::AuthMethod::basic(username, password)),
-        };
-
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        // Connect the transport first
-        http_transport
-            .connect()
+        let auth = ultrafast_mcp_auth::AuthMethod::basic(username, password);
+        self.connect_with(ConnectOptions::streamable_http(url).with_auth(auth))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        self.connect(Box::new(http_transport)).await
     }
 
     /// Connect to a server using Streamable HTTP transport with custom configuration
     #[cfg(feature = "http")]
+    #[deprecated(note = "use `connect_with(ConnectOptions::streamable_http_config(config))`")]
     pub async fn connect_streamable_http_with_config(
         &self,
         config: ultrafast_mcp_transport::streamable_http::client::StreamableHttpClientConfig,
     ) -> MCPResult<()> {
-        use ultrafast_mcp_transport::streamable_http::client::StreamableHttpClient;
-
-        let mut http_transport = StreamableHttpClient::new(config)
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        // Connect the transport first
-        http_transport
-            .connect()
+        self.connect_with(ConnectOptions::streamable_http_config(config))
             .await
-            .map_err(|e| MCPError::Transport(TransportError::ConnectionFailed(e.to_string())))?;
-
-        self.connect(Box::new(http_transport)).await
     }
 
     /// Initialize the connection with the server
@@ -916,27 +1748,150 @@ impl UltraFastClient {
 
     /// Disconnect from the server
     pub async fn disconnect(&self) -> MCPResult<()> {
-        // Stop message receiver
+        // Stop message receiver and pending request sweeper
         if let Some(handle) = self.message_receiver.write().await.take() {
             handle.abort();
         }
+        if let Some(handle) = self.pending_sweeper.write().await.take() {
+            handle.abort();
+        }
+        self.stop_ping_monitoring().await?;
 
         // Close transport
         if let Some(mut transport) = self.transport.write().await.take() {
-            transport.close().await.map_err(|e| {
-                MCPError::Transport(TransportError::ConnectionFailed(e.to_string()))
-            })?;
+            transport.close().await?;
         }
 
         {
             let mut state = self.state_manager.write().await;
             state.set_state(ClientState::Uninitialized);
         }
+        self.latency.reset();
+        self.close_tracker.closed();
 
         info!("Client disconnected");
         Ok(())
     }
 
+    /// Health of the connection, with the counts of the ping monitor
+    ///
+    /// `None` while not connected.
+    pub async fn health(&self) -> Option<TransportHealth> {
+        let mut health = self.lock_transport().await.as_ref()?.get_health();
+        health.pings = self
+            .ping_tracker
+            .read()
+            .await
+            .as_ref()
+            .map(|tracker| tracker.stats());
+        Some(health)
+    }
+
+    /// Changes in how the server answers the ping monitor's pings
+    pub fn subscribe_ping_events(&self) -> broadcast::Receiver<PingEvent> {
+        self.ping_events.subscribe()
+    }
+
+    /// Notifications from the server, decoded
+    ///
+    /// A receiver that falls behind by more than 256 notifications misses
+    /// the oldest ones.
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Call `handler` whenever the server's tool list changes
+    pub fn on_tools_changed(
+        &self,
+        handler: impl Fn() + Send + 'static,
+    ) -> NotificationSubscription {
+        self.on_notification(move |notification| {
+            if let ServerNotification::ToolsListChanged = notification {
+                handler();
+            }
+        })
+    }
+
+    /// Call `handler` whenever the server's resource list changes
+    pub fn on_resources_changed(
+        &self,
+        handler: impl Fn() + Send + 'static,
+    ) -> NotificationSubscription {
+        self.on_notification(move |notification| {
+            if let ServerNotification::ResourcesListChanged = notification {
+                handler();
+            }
+        })
+    }
+
+    /// Call `handler` whenever the server's prompt list changes
+    pub fn on_prompts_changed(
+        &self,
+        handler: impl Fn() + Send + 'static,
+    ) -> NotificationSubscription {
+        self.on_notification(move |notification| {
+            if let ServerNotification::PromptsListChanged = notification {
+                handler();
+            }
+        })
+    }
+
+    /// Call `handler` with every log message the server sends
+    ///
+    /// Which levels are sent is up to the server; see `logging/setLevel`.
+    pub fn on_log_message(
+        &self,
+        handler: impl Fn(LoggingMessageNotification) + Send + 'static,
+    ) -> NotificationSubscription {
+        self.on_notification(move |notification| {
+            if let ServerNotification::LogMessage(message) = notification {
+                handler(message);
+            }
+        })
+    }
+
+    /// Call `handler` with the progress the server reports for `token`
+    pub fn on_progress(
+        &self,
+        token: impl Into<Value>,
+        handler: impl Fn(ProgressNotification) + Send + 'static,
+    ) -> NotificationSubscription {
+        let token = token.into();
+        self.on_notification(move |notification| match notification {
+            ServerNotification::Progress(progress) if progress.progress_token == token => {
+                handler(progress)
+            }
+            _ => {}
+        })
+    }
+
+    /// Call `handler` with every notification from the server
+    pub fn on_notification(
+        &self,
+        handler: impl FnMut(ServerNotification) + Send + 'static,
+    ) -> NotificationSubscription {
+        NotificationSubscription::spawn(self.subscribe_notifications(), handler)
+    }
+
+    /// Get request/response correlation counters
+    pub async fn request_metrics(&self) -> RequestMetrics {
+        self.state_manager.read().await.request_metrics()
+    }
+
+    /// Get the sizes of the client's connection bookkeeping
+    pub async fn stats(&self) -> ClientStats {
+        self.state_manager.read().await.stats()
+    }
+
+    /// Get notification sequence checking counters
+    pub async fn notification_order_metrics(&self) -> NotificationOrderMetrics {
+        self.state_manager
+            .read()
+            .await
+            .notification_sequence
+            .metrics()
+    }
+
     /// Get current client state
     pub async fn get_state(&self) -> ClientState {
         self.state_manager.read().await.state.clone()
@@ -1000,8 +1955,35 @@ impl UltraFastClient {
 
     /// List available tools
     pub async fn list_tools(&self, request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
-        self.send_request("tools/list", Some(serde_json::to_value(request)?))
-            .await
+        let response: ListToolsResponse = self
+            .send_request("tools/list", Some(serde_json::to_value(request)?))
+            .await?;
+        let mut state = self.state_manager.write().await;
+        for tool in &response.tools {
+            state
+                .tool_input_schemas
+                .insert(tool.name.clone(), tool.input_schema.clone());
+            match &tool.output_schema {
+                Some(schema) => {
+                    state
+                        .tool_output_schemas
+                        .insert(tool.name.clone(), schema.clone());
+                }
+                None => {
+                    state.tool_output_schemas.remove(&tool.name);
+                }
+            }
+            let retry_safe = tool.annotations.as_ref().is_some_and(|annotations| {
+                annotations.idempotent_hint == Some(true)
+                    || annotations.read_only_hint == Some(true)
+            });
+            if retry_safe {
+                state.retry_safe_tools.insert(tool.name.clone());
+            } else {
+                state.retry_safe_tools.remove(&tool.name);
+            }
+        }
+        Ok(response)
     }
 
     /// List tools with default parameters
@@ -1009,10 +1991,184 @@ impl UltraFastClient {
         self.list_tools(ListToolsRequest::default()).await
     }
 
+    /// List every tool, following `next_cursor` across pages
+    pub async fn list_tools_all(&self) -> MCPResult<Vec<Tool>> {
+        collect_pages(|cursor| async move {
+            let response = self.list_tools(ListToolsRequest { cursor }).await?;
+            Ok((response.tools, response.next_cursor))
+        })
+        .await
+    }
+
+    /// The tool called `name`, if the server lists it
+    async fn find_tool(&self, name: &str) -> MCPResult<Option<Tool>> {
+        let tools = self.list_tools_all().await?;
+        Ok(tools.into_iter().find(|tool| tool.name == name))
+    }
+
+    /// Wait until the server lists the tool called `name`
+    ///
+    /// The tools are listed now and again on every tools list changed
+    /// notification. Fails with [`MCPError::not_found`] when the tool is not
+    /// listed within `timeout`; see [`availability`].
+    pub async fn wait_for_tool(&self, name: &str, timeout: std::time::Duration) -> MCPResult<Tool> {
+        let wait = async {
+            let mut availability = self.watch_tool(name).await?;
+            loop {
+                if let Some(tool) = availability.tool() {
+                    return Ok(tool.clone());
+                }
+                availability.changed().await?;
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(MCPError::not_found(format!(
+                    "Tool '{name}' was not listed within {timeout:?}"
+                )))
+            })
+    }
+
+    /// Follow whether the server lists the tool called `name`
+    pub async fn watch_tool(&self, name: &str) -> MCPResult<ToolAvailability<'_>> {
+        ToolAvailability::new(self, name.to_string()).await
+    }
+
     /// Call a tool
+    ///
+    /// The result is checked against the tool's output schema as set by
+    /// [`with_output_validation`](Self::with_output_validation), then passed
+    /// through the client's result transforms.
     pub async fn call_tool(&self, tool_call: ToolCall) -> MCPResult<ToolResult> {
-        self.send_request("tools/call", Some(serde_json::to_value(tool_call)?))
+        let name = tool_call.name.clone();
+        let result = self
+            .send_request("tools/call", Some(serde_json::to_value(tool_call)?))
+            .await?;
+        self.finish_tool_result(&name, result).await
+    }
+
+    /// Call a tool, keeping the execution metadata reported by the server
+    ///
+    /// The metadata is only present if the server enables it.
+    pub async fn call_tool_with_meta(
+        &self,
+        tool_call: ToolCall,
+    ) -> MCPResult<WithMeta<ToolResult>> {
+        let name = tool_call.name.clone();
+        let response: WithMeta<ToolResult> = self
+            .send_request_with_meta("tools/call", Some(serde_json::to_value(tool_call)?))
+            .await?;
+        Ok(WithMeta {
+            result: self.finish_tool_result(&name, response.result).await?,
+            meta: response.meta,
+        })
+    }
+
+    /// Call a tool, asking the server to report its progress under `token`
+    ///
+    /// The updates arrive as [`ServerNotification::Progress`]; register
+    /// [`on_progress`](Self::on_progress) for `token` before calling.
+    pub async fn call_tool_with_progress(
+        &self,
+        tool_call: ToolCall,
+        token: impl Into<Value>,
+    ) -> MCPResult<ToolResult> {
+        let name = tool_call.name.clone();
+        let mut params = serde_json::to_value(tool_call)?;
+        params["_meta"] = serde_json::json!({ "progressToken": token.into() });
+        let result = self.send_request("tools/call", Some(params)).await?;
+        self.finish_tool_result(&name, result).await
+    }
+
+    /// Call a tool, receiving the content it streams while it runs
+    ///
+    /// The returned stream yields the chunks as they arrive, then ends when
+    /// the call completes; see [`streaming`]. Chunks of a tool that does
+    /// not stream are only in the final result.
+    pub fn call_tool_streaming(&self, tool_call: ToolCall) -> ToolCallStream<'_> {
+        let token = format!("stream-{}", uuid::Uuid::new_v4());
+        let (sender, chunks) = mpsc::unbounded_channel();
+        let call = async move {
+            let name = tool_call.name.clone();
+            let mut params = serde_json::to_value(tool_call)?;
+            params["_meta"] = serde_json::json!({ "progressToken": token });
+            self.state_manager
+                .write()
+                .await
+                .content_streams
+                .insert(token.clone(), sender);
+            let result = self
+                .send_request::<ToolResult>("tools/call", Some(params))
+                .await;
+            self.state_manager
+                .write()
+                .await
+                .content_streams
+                .remove(&token);
+            self.finish_tool_result(&name, result?).await
+        };
+        ToolCallStream::new(chunks, Box::pin(call))
+    }
+
+    /// Validate a tool result, then run the result transforms on it
+    async fn finish_tool_result(&self, name: &str, result: ToolResult) -> MCPResult<ToolResult> {
+        self.validate_tool_output(name, &result).await?;
+        self.result_transforms.apply(name, result)
+    }
+
+    async fn validate_tool_output(&self, name: &str, result: &ToolResult) -> MCPResult<()> {
+        if self.output_validation == OutputValidation::Off {
+            return Ok(());
+        }
+        let schema = self
+            .state_manager
+            .read()
             .await
+            .tool_output_schemas
+            .get(name)
+            .cloned();
+        match schema {
+            Some(schema) => check_tool_output(name, result, &schema, self.output_validation),
+            None => Ok(()),
+        }
+    }
+
+    /// Call a tool with typed input and output
+    ///
+    /// `input` is serialized as the tool arguments and, if the tool's input
+    /// schema is known from an earlier [`list_tools`](Self::list_tools) call,
+    /// validated against it before the request is sent. The result's
+    /// structured content, or else its first text content parsed as JSON, is
+    /// deserialized into `O`; a result flagged `isError` is returned as an
+    /// error.
+    pub async fn call_tool_typed<I: Serialize, O: DeserializeOwned>(
+        &self,
+        name: &str,
+        input: I,
+    ) -> MCPResult<O> {
+        let arguments = serde_json::to_value(input)?;
+        let schema = self
+            .state_manager
+            .read()
+            .await
+            .tool_input_schemas
+            .get(name)
+            .cloned();
+        if let Some(schema) = schema {
+            ultrafast_mcp_core::schema::validation::validate_tool_input(&arguments, &schema)
+                .map_err(|e| {
+                    ToolError::SchemaValidation(format!("Input for tool '{name}': {e}"))
+                })?;
+        }
+
+        let result = self
+            .call_tool(ToolCall {
+                name: name.to_string(),
+                arguments: Some(arguments),
+            })
+            .await?;
+        typed_tool_output(name, result)
     }
 
     /// List available resources
@@ -1024,7 +2180,57 @@ impl UltraFastClient {
             .await
     }
 
+    /// List every resource, following `next_cursor` across pages
+    pub async fn list_resources_all(&self) -> MCPResult<Vec<Resource>> {
+        collect_pages(|cursor| self.fetch_resources_page(cursor)).await
+    }
+
+    /// Stream every resource, fetching each page as the previous one is
+    /// consumed
+    ///
+    /// Unlike [`list_resources_all`](Self::list_resources_all), only one
+    /// page is held in memory at a time. The stream ends after the first
+    /// error.
+    pub fn list_resources_stream(&self) -> impl Stream<Item = MCPResult<Resource>> + '_ {
+        page_stream(|cursor| self.fetch_resources_page(cursor))
+    }
+
+    async fn fetch_resources_page(&self, cursor: Option<String>) -> MCPResult<Page<Resource>> {
+        let response = self.list_resources(ListResourcesRequest { cursor }).await?;
+        Ok((response.resources, response.next_cursor))
+    }
+
     /// Read a resource
+    /// Wait until the server lists the resource at `uri`
+    ///
+    /// The resources are listed now and again on every resources list
+    /// changed notification. Fails with [`MCPError::not_found`] when the
+    /// resource is not listed within `timeout`; see [`availability`].
+    pub async fn wait_for_resource(
+        &self,
+        uri: &str,
+        timeout: std::time::Duration,
+    ) -> MCPResult<Resource> {
+        let wait = async {
+            // Subscribed before listing, so a change in between is not missed
+            let mut changes = self.state_manager.read().await.list_changes.resources();
+            loop {
+                let resources = self.list_resources_all().await?;
+                if let Some(resource) = resources.into_iter().find(|resource| resource.uri == uri) {
+                    return Ok(resource);
+                }
+                availability::next_change(&mut changes).await?;
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(MCPError::not_found(format!(
+                    "Resource '{uri}' was not listed within {timeout:?}"
+                )))
+            })
+    }
+
     pub async fn read_resource(
         &self,
         request: ReadResourceRequest,
@@ -1033,13 +2239,175 @@ impl UltraFastClient {
             .await
     }
 
+    /// Read a resource, keeping the execution metadata reported by the server
+    pub async fn read_resource_with_meta(
+        &self,
+        request: ReadResourceRequest,
+    ) -> MCPResult<WithMeta<ReadResourceResponse>> {
+        self.send_request_with_meta("resources/read", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    /// Read a resource's bytes into `writer`
+    ///
+    /// The server sends them in chunks with the `resources/readStream`
+    /// extension, so a large blob is neither base64 encoded into one
+    /// message nor held in memory whole. Servers without the extension are
+    /// read with `resources/read` instead.
+    pub async fn read_resource_to_writer<W>(
+        &self,
+        uri: &str,
+        writer: &mut W,
+    ) -> MCPResult<ReadResourceStreamResponse>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let request = ReadResourceStreamRequest {
+            uri: uri.to_string(),
+            ..Default::default()
+        };
+        self.read_resource_stream(request, writer).await
+    }
+
+    /// Read the bytes of a resource in `request.range` into `writer`, in
+    /// chunks of at most `request.chunk_size`
+    ///
+    /// The `resources/readStream` timeout applies between chunks rather than
+    /// to the whole read, and chunks are only read as fast as `writer`
+    /// takes them. See [`read_resource_to_writer`](Self::read_resource_to_writer).
+    pub async fn read_resource_stream<W>(
+        &self,
+        request: ReadResourceStreamRequest,
+        writer: &mut W,
+    ) -> MCPResult<ReadResourceStreamResponse>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let token = format!("read-{}", uuid::Uuid::new_v4());
+        let (sender, mut chunks) = mpsc::channel::<ResourceChunk>(RESOURCE_CHUNK_BUFFER);
+        let mut params = serde_json::to_value(&request)?;
+        params["_meta"] = serde_json::json!({ "progressToken": token });
+        self.state_manager
+            .write()
+            .await
+            .resource_streams
+            .insert(token.clone(), sender);
+
+        let read = async {
+            let response = self
+                .send_request::<ReadResourceStreamResponse>(
+                    READ_RESOURCE_STREAM_METHOD,
+                    Some(params),
+                )
+                .await;
+            // Ends the chunks once those already sent are written
+            self.state_manager
+                .write()
+                .await
+                .resource_streams
+                .remove(&token);
+            response
+        };
+        let write = async {
+            let mut written = 0;
+            while let Some(chunk) = chunks.recv().await {
+                let expected = request.range.map_or(0, |range| range.offset) + written;
+                if chunk.offset != expected {
+                    return Err(MCPError::invalid_response(format!(
+                        "Resource chunk at offset {} where {expected} was expected",
+                        chunk.offset
+                    )));
+                }
+                let bytes = chunk.bytes()?;
+                writer.write_all(&bytes).await.map_err(write_error)?;
+                written += bytes.len() as u64;
+            }
+            Ok(written)
+        };
+        let (response, written) = tokio::join!(read, write);
+        let response = match response {
+            Err(MCPError::Protocol(ProtocolError::MethodNotFound(_))) => {
+                return self.read_resource_into(request, writer).await;
+            }
+            response => response?,
+        };
+        let written = written?;
+        if written != response.length {
+            return Err(MCPError::invalid_response(format!(
+                "Resource stream sent {written} of {} bytes",
+                response.length
+            )));
+        }
+        writer.flush().await.map_err(write_error)?;
+        Ok(response)
+    }
+
+    /// Read a resource with `resources/read`, for servers that cannot
+    /// stream it, and write the range requested
+    async fn read_resource_into<W>(
+        &self,
+        request: ReadResourceStreamRequest,
+        writer: &mut W,
+    ) -> MCPResult<ReadResourceStreamResponse>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let response = self
+            .read_resource(ReadResourceRequest::new(request.uri.clone()))
+            .await?;
+        let content = response.contents.first().ok_or_else(|| {
+            MCPError::invalid_response(format!("Resource '{}' has no contents", request.uri))
+        })?;
+        let bytes = content.to_bytes()?;
+        let range = request.range.unwrap_or_default();
+        let slice = range.slice(&bytes);
+        writer.write_all(slice).await.map_err(write_error)?;
+        writer.flush().await.map_err(write_error)?;
+        Ok(ReadResourceStreamResponse {
+            uri: request.uri,
+            mime_type: content.mime_type().map(str::to_string),
+            offset: range.offset,
+            length: slice.len() as u64,
+            size: Some(bytes.len() as u64),
+        })
+    }
+
     /// Subscribe to resource changes
+    ///
+    /// Updates are passed to the handler set with
+    /// [`with_resource_change_handler`](Self::with_resource_change_handler).
+    /// Servers that support pattern subscriptions also accept a glob such as
+    /// `file:///project/**` in place of a URI.
     pub async fn subscribe_resource(&self, uri: String) -> MCPResult<()> {
         let request = serde_json::json!({
             "uri": uri
         });
-        self.send_notification("resources/subscribe", Some(request))
+        let _: Value = self
+            .send_request("resources/subscribe", Some(request))
+            .await?;
+        self.state_manager
+            .write()
+            .await
+            .resource_subscriptions
+            .insert(uri);
+        Ok(())
+    }
+
+    /// Stop receiving updates for a resource subscribed with
+    /// [`subscribe_resource`](Self::subscribe_resource)
+    pub async fn unsubscribe_resource(&self, uri: String) -> MCPResult<()> {
+        let request = serde_json::json!({
+            "uri": uri
+        });
+        let _: Value = self
+            .send_request("resources/unsubscribe", Some(request))
+            .await?;
+        self.state_manager
+            .write()
             .await
+            .resource_subscriptions
+            .remove(&uri);
+        Ok(())
     }
 
     /// List available prompts
@@ -1051,12 +2419,30 @@ impl UltraFastClient {
             .await
     }
 
+    /// List every prompt, following `next_cursor` across pages
+    pub async fn list_prompts_all(&self) -> MCPResult<Vec<Prompt>> {
+        collect_pages(|cursor| async move {
+            let response = self.list_prompts(ListPromptsRequest { cursor }).await?;
+            Ok((response.prompts, response.next_cursor))
+        })
+        .await
+    }
+
     /// Get a specific prompt
     pub async fn get_prompt(&self, request: GetPromptRequest) -> MCPResult<GetPromptResponse> {
         self.send_request("prompts/get", Some(serde_json::to_value(request)?))
             .await
     }
 
+    /// Get a prompt, keeping the execution metadata reported by the server
+    pub async fn get_prompt_with_meta(
+        &self,
+        request: GetPromptRequest,
+    ) -> MCPResult<WithMeta<GetPromptResponse>> {
+        self.send_request_with_meta("prompts/get", Some(serde_json::to_value(request)?))
+            .await
+    }
+
     /// Create a message using sampling
     pub async fn create_message(
         &self,
@@ -1075,10 +2461,19 @@ impl UltraFastClient {
             .await
     }
 
-    /// Respond to elicitation request (called by client-side elicitation handler)
-    pub async fn respond_to_elicitation(&self, response: ElicitationResponse) -> MCPResult<()> {
-        self.send_request("elicitation/respond", Some(serde_json::to_value(response)?))
-            .await
+    /// Respond to elicitation request
+    ///
+    /// Answers to `elicitation/create` are sent as the JSON-RPC response to
+    /// the server's request, with whatever the
+    /// [`ClientElicitationHandler`] returns; there is no separate method to
+    /// send them with, so this always fails.
+    #[deprecated(
+        note = "return the answer from `ClientElicitationHandler::handle_elicitation_request`"
+    )]
+    pub async fn respond_to_elicitation(&self, _response: ElicitationResponse) -> MCPResult<()> {
+        Err(MCPError::invalid_request(
+            "Elicitation answers are returned by the ClientElicitationHandler".to_string(),
+        ))
     }
 
     /// List filesystem roots
@@ -1086,7 +2481,21 @@ impl UltraFastClient {
         self.send_request("roots/list", None).await
     }
 
-    /// Set log level
+    /// Replace the roots exposed to the server
+    ///
+    /// A connected server is sent `notifications/roots/list_changed`, so it
+    /// asks for the new roots.
+    pub async fn set_roots(&self, roots: Vec<Root>) -> MCPResult<()> {
+        *self.roots.write().await = Some(roots);
+        if self.can_operate().await {
+            self.send_notification("notifications/roots/list_changed", None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Set the least severe level of the log messages the server sends
+    /// this client
     pub async fn set_log_level(
         &self,
         level: ultrafast_mcp_core::types::notifications::LogLevel,
@@ -1094,56 +2503,113 @@ impl UltraFastClient {
         let request = serde_json::json!({
             "level": level
         });
-        self.send_request("logging/setLevel", Some(request)).await
+        let _: ultrafast_mcp_core::types::notifications::LogLevelSetResponse =
+            self.send_request("logging/setLevel", Some(request)).await?;
+        Ok(())
     }
 
     /// Send ping
+    ///
+    /// The round-trip time is recorded in the
+    /// [`latency_estimate`](Self::latency_estimate).
     pub async fn ping(
         &self,
         data: Option<serde_json::Value>,
     ) -> MCPResult<ultrafast_mcp_core::types::notifications::PingResponse> {
-        self.send_request("ping", data).await
+        let result = self.requester().ping(data).await?;
+        serde_json::from_value(result).map_err(MCPError::Serialization)
     }
 
     /// Start periodic ping monitoring (optional, for connection health)
+    ///
+    /// Pings the server every `ping_interval` while connected to keep the
+    /// [`latency_estimate`](Self::latency_estimate) current. Unanswered
+    /// pings are handled as the [ping policy](Self::with_ping_policy) says,
+    /// by default only logged; monitoring stops on
+    /// [`disconnect`](Self::disconnect) or
+    /// [`stop_ping_monitoring`](Self::stop_ping_monitoring).
     pub async fn start_ping_monitoring(&self, ping_interval: std::time::Duration) -> MCPResult<()> {
         info!(
             "Starting periodic ping monitoring with interval: {:?}",
             ping_interval
         );
 
-        // Note: This is a placeholder for future implementation
-        // The actual ping monitoring would need to be integrated with the transport layer
-        // For now, we log that ping monitoring is enabled
-        info!("Ping monitoring enabled (interval: {:?})", ping_interval);
-
-        // Future implementation would spawn a background task that:
-        // 1. Clones the client's transport
-        // 2. Sends periodic ping requests
-        // 3. Handles timeouts and reconnection logic
-
+        let policy = PingPolicy {
+            interval: ping_interval,
+            ..self.ping_policy.clone().unwrap_or_default()
+        };
+        let tracker = Arc::new(PingTracker::new(policy));
+        *self.ping_tracker.write().await = Some(tracker.clone());
+        let requester = self.requester();
+        let events = self.ping_events.clone();
+        let handle = tokio::spawn(async move {
+            let policy = tracker.policy().clone();
+            let mut interval = tokio::time::interval(policy.interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                // Nothing to measure until connected
+                if requester.ensure_operational().await.is_err() {
+                    continue;
+                }
+                let answered =
+                    match tokio::time::timeout(policy.timeout, requester.ping(None)).await {
+                        Ok(Ok(_)) => true,
+                        Ok(Err(e)) => {
+                            warn!("Ping monitoring failed to reach server: {}", e);
+                            false
+                        }
+                        Err(_) => {
+                            warn!("Server did not answer ping within {:?}", policy.timeout);
+                            false
+                        }
+                    };
+                if let Some(event) = tracker.record(answered) {
+                    let _ = events.send(event);
+                }
+                if tracker.should_disconnect() {
+                    warn!(
+                        "Closing connection after {} unanswered pings",
+                        tracker.stats().consecutive_missed
+                    );
+                    requester
+                        .close_transport("Server stopped answering pings")
+                        .await;
+                    let _ = events.send(PingEvent::Disconnected);
+                    break;
+                }
+            }
+        });
+        if let Some(previous) = self.ping_monitor.write().await.replace(handle) {
+            previous.abort();
+        }
         Ok(())
     }
 
     /// Stop ping monitoring
     pub async fn stop_ping_monitoring(&self) -> MCPResult<()> {
         info!("Stopping periodic ping monitoring");
-        // The ping monitoring task will naturally stop when the client is disconnected
+        if let Some(handle) = self.ping_monitor.write().await.take() {
+            handle.abort();
+        }
         Ok(())
     }
 
     /// Notify cancellation
+    ///
+    /// The server marks the request cancelled in its handler's `Context`, so
+    /// a tool checking `is_cancelled` can stop early.
     pub async fn notify_cancelled(
         &self,
         request_id: serde_json::Value,
         reason: Option<String>,
     ) -> MCPResult<()> {
-        let request = serde_json::json!({
-            "requestId": request_id,
-            "reason": reason
-        });
-        self.send_notification("$/cancelRequest", Some(request))
-            .await
+        let notification = CancelledNotification { request_id, reason };
+        self.send_notification(
+            "notifications/cancelled",
+            Some(serde_json::to_value(notification)?),
+        )
+        .await
     }
 
     /// Notify progress
@@ -1177,7 +2643,282 @@ impl UltraFastClient {
     }
 
     async fn ensure_operational(&self) -> MCPResult<()> {
-        let state = self.get_state().await;
+        self.requester().ensure_operational().await
+    }
+
+    /// Lock the transport, taking it from the message receiver task
+    async fn lock_transport(&self) -> RwLockWriteGuard<'_, Option<Box<dyn Transport>>> {
+        self.transport_wanted.lock(&self.transport).await
+    }
+
+    async fn send_request<T>(&self, method: &str, params: Option<Value>) -> MCPResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let result = self.send_request_value(method, params).await?;
+        serde_json::from_value(result).map_err(MCPError::Serialization)
+    }
+
+    /// Send a request, returning its result along with the execution
+    /// metadata the server attached to it
+    async fn send_request_with_meta<T>(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> MCPResult<WithMeta<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let result = self.send_request_value(method, params).await?;
+        let meta = ResponseMeta::from_result(&result);
+        Ok(WithMeta {
+            result: serde_json::from_value(result).map_err(MCPError::Serialization)?,
+            meta,
+        })
+    }
+
+    /// Send a request in an `mcp_client_request` span, whose trace context
+    /// goes along in `_meta` so the server's handler span joins its trace
+    async fn send_request_value(
+        &self,
+        method: &str,
+        mut params: Option<Value>,
+    ) -> MCPResult<Value> {
+        if let Some(locale) = &self.locale {
+            set_locale_meta(&mut params, locale);
+        }
+        let span = tracing::info_span!("mcp_client_request", method = %method);
+        span.in_scope(|| trace_context::inject_current(&mut params));
+        let retry_policy = match &self.retry_policy {
+            Some(policy) if self.is_retry_safe(method, params.as_ref()).await => policy,
+            _ => {
+                let mut result = self
+                    .requester()
+                    .send(method, params)
+                    .instrument(span)
+                    .await?;
+                decode_content(&mut result)?;
+                return Ok(result);
+            }
+        };
+
+        let mut attempt = 1;
+        let mut result = loop {
+            match self
+                .requester()
+                .send(method, params.clone())
+                .instrument(span.clone())
+                .await
+            {
+                Err(e) if retry_policy.should_retry(&e, attempt) => {
+                    let delay = retry_policy.retry_delay(attempt - 1, &e);
+                    warn!(
+                        "{} failed on attempt {} ({}), retrying in {:?}",
+                        method, attempt, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        };
+        decode_content(&mut result)?;
+        Ok(result)
+    }
+
+    /// Whether sending the request again cannot repeat an effect on the
+    /// server, see [`retry`]
+    async fn is_retry_safe(&self, method: &str, params: Option<&Value>) -> bool {
+        if method != "tools/call" {
+            return retry::IDEMPOTENT_METHODS.contains(&method);
+        }
+        let Some(name) = params.and_then(|params| params.get("name")?.as_str()) else {
+            return false;
+        };
+        self.state_manager
+            .read()
+            .await
+            .retry_safe_tools
+            .contains(name)
+    }
+
+    /// What sending a request needs, detached from the client for
+    /// background tasks
+    fn requester(&self) -> Requester {
+        Requester {
+            state_manager: self.state_manager.clone(),
+            transport: self.transport.clone(),
+            transport_wanted: self.transport_wanted.clone(),
+            timeout_config: self.timeout_config.clone(),
+            latency: self.latency.clone(),
+            adaptive_timeouts: self.adaptive_timeouts,
+            notification_order_handler: self.notification_order_handler.clone(),
+            resource_change_handler: self.resource_change_handler.clone(),
+            late_response_handler: self.late_response_handler.clone(),
+            elicitation_handler: self.elicitation_handler.clone(),
+            sampling_handler: self.sampling_handler.clone(),
+            roots: self.roots.clone(),
+        }
+    }
+
+    async fn send_notification(&self, method: &str, params: Option<Value>) -> MCPResult<()> {
+        // Allow initialized notification even when not operational
+        if method != "initialized" {
+            self.ensure_operational().await?;
+        }
+
+        let notification = JsonRpcRequest::notification(method.to_string(), params);
+
+        let mut transport_guard = self.lock_transport().await;
+        let transport = transport_guard.as_mut().ok_or_else(|| {
+            MCPError::Transport(TransportError::ConnectionFailed(
+                "Transport not available".to_string(),
+            ))
+        })?;
+
+        transport
+            .send_message(JsonRpcMessage::Notification(notification))
+            .await
+            .map_err(MCPError::from)
+    }
+}
+
+/// Hands the transport lock from the message receiver task to senders
+///
+/// The receiver holds the lock while it waits for a message. A sender
+/// registers before locking, so the receiver steps aside whether it is
+/// already waiting, and is woken, or only about to take the lock.
+#[derive(Default)]
+struct TransportWanted {
+    senders: AtomicUsize,
+    notify: Notify,
+}
+
+impl TransportWanted {
+    /// Lock `transport`, taking it from the message receiver task
+    async fn lock<'a>(
+        &self,
+        transport: &'a RwLock<Option<Box<dyn Transport>>>,
+    ) -> RwLockWriteGuard<'a, Option<Box<dyn Transport>>> {
+        struct Registered<'a>(&'a AtomicUsize);
+        impl Drop for Registered<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        self.senders.fetch_add(1, Ordering::SeqCst);
+        let _registered = Registered(&self.senders);
+        self.notify.notify_one();
+        transport.write().await
+    }
+
+    /// Whether a sender is waiting for the lock
+    fn is_wanted(&self) -> bool {
+        self.senders.load(Ordering::SeqCst) > 0
+    }
+
+    /// Resolves once a sender asks for the lock
+    async fn notified(&self) {
+        self.notify.notified().await
+    }
+}
+
+/// Tell the server to stop working on requests the client gave up on
+async fn cancel_timed_out_requests(
+    transport: &RwLock<Option<Box<dyn Transport>>>,
+    transport_wanted: &TransportWanted,
+    request_ids: &[u64],
+) {
+    let mut transport_guard = transport_wanted.lock(transport).await;
+    let Some(transport) = transport_guard.as_mut() else {
+        return;
+    };
+    for id in request_ids {
+        let cancelled = CancelledNotification {
+            request_id: Value::from(*id),
+            reason: Some("Request timed out".to_string()),
+        };
+        let notification = JsonRpcRequest::notification(
+            "notifications/cancelled".to_string(),
+            serde_json::to_value(cancelled).ok(),
+        );
+        if let Err(e) = transport
+            .send_message(JsonRpcMessage::Notification(notification))
+            .await
+        {
+            warn!("Failed to cancel timed out request {}: {}", id, e);
+        }
+    }
+}
+
+/// Restore the content items the server sent compressed, see
+/// [`UltraFastClient::with_content_compression`]
+#[cfg(feature = "content-encoding")]
+fn decode_content(result: &mut Value) -> MCPResult<()> {
+    content_encoding::decode_content(result).map(|_| ())
+}
+
+#[cfg(not(feature = "content-encoding"))]
+fn decode_content(_result: &mut Value) -> MCPResult<()> {
+    Ok(())
+}
+
+/// Numeric ID of a response, as the client numbers its requests
+fn response_id(response: &JsonRpcResponse) -> Option<u64> {
+    response.id.as_ref().and_then(|id| {
+        serde_json::from_value::<u64>(serde_json::to_value(id).unwrap_or_default()).ok()
+    })
+}
+
+/// Sends requests and correlates their responses
+///
+/// Holds the client's shared state, so tasks such as the ping monitor can
+/// send requests without borrowing the client.
+#[derive(Clone)]
+struct Requester {
+    state_manager: Arc<RwLock<ClientStateManager>>,
+    transport: Arc<RwLock<Option<Box<dyn Transport>>>>,
+    transport_wanted: Arc<TransportWanted>,
+    timeout_config: Arc<TimeoutConfig>,
+    latency: Arc<LatencyEstimator>,
+    adaptive_timeouts: Option<AdaptiveTimeouts>,
+    notification_order_handler: Option<Arc<dyn ClientNotificationOrderHandler>>,
+    resource_change_handler: Option<Arc<dyn ResourceChangeHandler>>,
+    late_response_handler: Option<Arc<dyn ClientLateResponseHandler>>,
+    elicitation_handler: Option<Arc<dyn ClientElicitationHandler>>,
+    sampling_handler: Option<Arc<dyn ClientSamplingHandler>>,
+    roots: Arc<RwLock<Option<Vec<Root>>>>,
+}
+
+impl Requester {
+    /// Handle a notification received while waiting for a response
+    async fn receive_immediate_notification(
+        &self,
+        notification: &JsonRpcRequest,
+        transport: &mut dyn Transport,
+    ) {
+        UltraFastClient::receive_notification(
+            notification,
+            transport,
+            &self.state_manager,
+            self.notification_order_handler.as_deref(),
+            self.resource_change_handler.as_deref(),
+        )
+        .await;
+    }
+
+    fn operation_timeout(&self, operation: &str) -> std::time::Duration {
+        operation_timeout(
+            &self.timeout_config,
+            self.adaptive_timeouts.as_ref(),
+            &self.latency,
+            operation,
+        )
+    }
+
+    async fn ensure_operational(&self) -> MCPResult<()> {
+        let state = self.state_manager.read().await.state.clone();
         if !state.can_operate() {
             return Err(MCPError::Protocol(ProtocolError::InternalError(format!(
                 "Client is not in operating state (current state: {state:?})"
@@ -1186,21 +2927,78 @@ impl UltraFastClient {
         Ok(())
     }
 
-    async fn generate_request_id(&self) -> u64 {
+    /// Lock the transport, taking it from the message receiver task
+    async fn lock_transport(&self) -> RwLockWriteGuard<'_, Option<Box<dyn Transport>>> {
+        self.transport_wanted.lock(&self.transport).await
+    }
+
+    /// Close the transport, ending the message receiver, and fail the
+    /// requests still waiting; the client has to connect again
+    async fn close_transport(&self, reason: &str) {
+        if let Some(transport) = self.lock_transport().await.as_mut() {
+            if let Err(e) = transport.close().await {
+                warn!("Failed to close transport: {}", e);
+            }
+        }
         let mut state = self.state_manager.write().await;
-        state.next_request_id()
+        state.fail_pending_requests(reason);
+        state.set_state(ClientState::Uninitialized);
+    }
+
+    /// Run `future` until the deadline of a pending request, which progress
+    /// on the request may extend meanwhile; `None` once it passed
+    async fn within_deadline<F: Future>(&self, request_id: u64, future: F) -> Option<F::Output> {
+        tokio::pin!(future);
+        loop {
+            let deadline = self
+                .state_manager
+                .read()
+                .await
+                .pending_requests
+                .get(&request_id)
+                .map(|request| request.deadline);
+            // Requests no longer pending were answered, failed or expired,
+            // which the future is about to see
+            let Some(deadline) = deadline else {
+                return Some(future.await);
+            };
+            tokio::select! {
+                biased;
+                output = &mut future => return Some(output),
+                _ = tokio::time::sleep_until(deadline) => {}
+            }
+            let extended = self
+                .state_manager
+                .read()
+                .await
+                .pending_requests
+                .get(&request_id)
+                .is_none_or(|request| request.deadline > tokio::time::Instant::now());
+            if !extended {
+                return None;
+            }
+        }
     }
 
-    async fn send_request<T>(&self, method: &str, params: Option<Value>) -> MCPResult<T>
-    where
-        T: serde::de::DeserializeOwned,
-    {
+    /// Send a ping, recording its round-trip time in the latency estimate
+    async fn ping(&self, data: Option<Value>) -> MCPResult<Value> {
+        let started = std::time::Instant::now();
+        let result = self.send("ping", data).await?;
+        self.latency.record(started.elapsed());
+        Ok(result)
+    }
+
+    async fn send(&self, method: &str, params: Option<Value>) -> MCPResult<Value> {
         // Allow initialize request even when not operational
         if method != "initialize" {
             self.ensure_operational().await?;
         }
 
-        let request_id = self.generate_request_id().await;
+        let request_id = self.state_manager.write().await.next_request_id();
+        let progress_token = params
+            .as_ref()
+            .and_then(|params| params.get("_meta")?.get("progressToken")?.as_str())
+            .map(str::to_string);
         let request = JsonRpcRequest::new(
             method.to_string(),
             params,
@@ -1210,7 +3008,7 @@ impl UltraFastClient {
         );
 
         // Get operation-specific timeout
-        let operation_timeout = self.get_operation_timeout(method);
+        let operation_timeout = self.operation_timeout(method);
 
         // Create response channel
         let (response_sender, response_receiver) = oneshot::channel();
@@ -1221,15 +3019,18 @@ impl UltraFastClient {
             state.add_pending_request(
                 request_id,
                 PendingRequest {
+                    method: method.to_string(),
                     response_sender,
-                    timeout: tokio::time::Instant::now() + operation_timeout,
+                    deadline: tokio::time::Instant::now() + operation_timeout,
+                    timeout: operation_timeout,
+                    progress_token,
                 },
             );
         }
 
         // Send request
-        {
-            let mut transport_guard = self.transport.write().await;
+        let sent = {
+            let mut transport_guard = self.lock_transport().await;
             let transport = transport_guard.as_mut().ok_or_else(|| {
                 MCPError::Transport(TransportError::ConnectionFailed(
                     "Transport not available".to_string(),
@@ -1238,40 +3039,113 @@ impl UltraFastClient {
             transport
                 .send_message(JsonRpcMessage::Request(request))
                 .await
-                .map_err(|e| MCPError::Transport(TransportError::SendFailed(e.to_string())))?;
-        }
-
-        // Try to get immediate response from transport (for HTTP transport)
-        let immediate_response = {
-            let mut transport_guard = self.transport.write().await;
-            let transport = transport_guard.as_mut().ok_or_else(|| {
-                MCPError::Transport(TransportError::ConnectionFailed(
-                    "Transport not available".to_string(),
-                ))
-            })?;
-            transport.receive_message().await.ok()
         };
-
-        let response = if let Some(immediate) = immediate_response {
-            // Got immediate response from transport
-            immediate
-        } else {
-            // Wait for response through message receiver task
-            tokio::time::timeout(operation_timeout, response_receiver)
+        if let Err(e) = sent {
+            // Nothing will answer it, and a retry is sent under a new ID
+            self.state_manager
+                .write()
                 .await
-                .map_err(|_| MCPError::Protocol(ProtocolError::RequestTimeout))?
-                .map_err(|_| {
-                    MCPError::Protocol(ProtocolError::InternalError(
-                        "Response channel closed".to_string(),
+                .remove_pending_request(&request_id);
+            return Err(e.into());
+        }
+
+        // Read the response straight from the transport (the HTTP transport
+        // returns it to the sender), unless it is delivered to the pending
+        // request first
+        let mut response_receiver = response_receiver;
+        let immediate_response = self
+            .within_deadline(request_id, async {
+                let mut transport_guard = self.lock_transport().await;
+                let transport = transport_guard.as_mut().ok_or_else(|| {
+                    MCPError::Transport(TransportError::ConnectionFailed(
+                        "Transport not available".to_string(),
                     ))
-                })?
+                })?;
+                loop {
+                    let received = tokio::select! {
+                        delivered = &mut response_receiver => return Ok(Some(delivered)),
+                        received = transport.receive_message() => received,
+                    };
+                    match received {
+                        // Notifications sent while the request is handled, such
+                        // as streamed tool content, arrive ahead of its response
+                        Ok(JsonRpcMessage::Notification(notification)) => {
+                            self.receive_immediate_notification(&notification, transport.as_mut())
+                                .await;
+                        }
+                        Ok(JsonRpcMessage::Request(request)) if request.id.is_none() => {
+                            self.receive_immediate_notification(&request, transport.as_mut())
+                                .await;
+                        }
+                        // So do requests the server needs answered to finish it,
+                        // such as elicitations
+                        Ok(JsonRpcMessage::Request(request)) => {
+                            UltraFastClient::answer_server_request(
+                                &request,
+                                transport.as_mut(),
+                                self.elicitation_handler.as_deref(),
+                                self.sampling_handler.as_deref(),
+                                &self.roots,
+                            )
+                            .await;
+                        }
+                        Ok(JsonRpcMessage::Response(response))
+                            if response_id(&response) == Some(request_id) =>
+                        {
+                            return Ok(Some(Ok(Ok(JsonRpcMessage::Response(response)))));
+                        }
+                        // Responses to other requests sent meanwhile
+                        Ok(JsonRpcMessage::Response(response)) => {
+                            UltraFastClient::deliver_response(
+                                response,
+                                &self.state_manager,
+                                self.late_response_handler.as_deref(),
+                            )
+                            .await;
+                        }
+                        Err(_) => return Ok(None),
+                    }
+                }
+            })
+            .await;
+
+        let delivered = match immediate_response {
+            Some(Ok(Some(delivered))) => Some(delivered),
+            Some(Ok(None)) => {
+                // Wait for response through message receiver task
+                self.within_deadline(request_id, response_receiver).await
+            }
+            Some(Err(e)) => return Err(e),
+            None => None,
+        };
+        let response = match delivered {
+            Some(Ok(response)) => response,
+            Some(Err(_)) => Err(MCPError::Protocol(ProtocolError::InternalError(
+                "Response channel closed".to_string(),
+            ))),
+            None => Err(MCPError::Protocol(ProtocolError::RequestTimeout)),
         };
 
-        // Remove from pending requests
-        {
+        // Remove from pending requests, including on timeout
+        let timed_out = matches!(
+            response,
+            Err(MCPError::Protocol(ProtocolError::RequestTimeout))
+        );
+        let was_pending = {
             let mut state = self.state_manager.write().await;
-            state.remove_pending_request(&request_id);
+            match state.remove_pending_request(&request_id) {
+                Some(pending) => {
+                    state.settle_request(request_id, pending.method, timed_out);
+                    true
+                }
+                None => false,
+            }
+        };
+        // The sweeper may have expired and cancelled it already
+        if timed_out && was_pending {
+            cancel_timed_out_requests(&self.transport, &self.transport_wanted, &[request_id]).await;
         }
+        let response = response?;
 
         match response {
             JsonRpcMessage::Response(response) => {
@@ -1280,7 +3154,7 @@ impl UltraFastClient {
                 }
 
                 if let Some(result) = response.result {
-                    serde_json::from_value(result).map_err(MCPError::Serialization)
+                    Ok(result)
                 } else {
                     Err(MCPError::Protocol(ProtocolError::InvalidResponse(
                         "Response has no result or error".to_string(),
@@ -1292,26 +3166,23 @@ impl UltraFastClient {
             ))),
         }
     }
+}
 
-    async fn send_notification(&self, method: &str, params: Option<Value>) -> MCPResult<()> {
-        // Allow initialized notification even when not operational
-        if method != "initialized" {
-            self.ensure_operational().await?;
-        }
-
-        let notification = JsonRpcRequest::notification(method.to_string(), params);
-
-        let mut transport_guard = self.transport.write().await;
-        let transport = transport_guard.as_mut().ok_or_else(|| {
-            MCPError::Transport(TransportError::ConnectionFailed(
-                "Transport not available".to_string(),
-            ))
-        })?;
+fn write_error(error: std::io::Error) -> MCPError {
+    MCPError::internal_error(format!("Failed to write resource: {error}"))
+}
 
-        transport
-            .send_message(JsonRpcMessage::Notification(notification))
-            .await
-            .map_err(|e| MCPError::Transport(TransportError::SendFailed(e.to_string())))
+/// Timeout for `operation`, adapted to the measured latency if enabled
+fn operation_timeout(
+    timeout_config: &TimeoutConfig,
+    adaptive_timeouts: Option<&AdaptiveTimeouts>,
+    latency: &LatencyEstimator,
+    operation: &str,
+) -> std::time::Duration {
+    let base = timeout_config.get_timeout_for_operation(operation);
+    match adaptive_timeouts {
+        Some(adaptive) => adaptive.apply(base, latency.estimate().as_ref()),
+        None => base,
     }
 }
 
@@ -1327,6 +3198,151 @@ impl std::fmt::Debug for UltraFastClient {
     }
 }
 
+/// Whether a subscription covers `uri`
+///
+/// Exact URIs and `prefix**` patterns are checked here. Other globs are
+/// matched by the server, which only sends updates for URIs they match, so
+/// they are taken to cover the update.
+fn subscription_covers(subscription: &str, uri: &str) -> bool {
+    let has_wildcards = |pattern: &str| pattern.contains(['*', '?']);
+    match subscription.strip_suffix("**") {
+        _ if subscription == uri => true,
+        Some(prefix) if !has_wildcards(prefix) => uri.starts_with(prefix),
+        _ => has_wildcards(subscription),
+    }
+}
+
+/// A page of list results and the cursor of the next page
+type Page<T> = (Vec<T>, Option<String>);
+
+/// Error for a server handing out a cursor it already returned, which would
+/// otherwise page forever
+fn repeated_cursor(cursor: &str) -> MCPError {
+    MCPError::Protocol(ProtocolError::InvalidResponse(format!(
+        "Server repeated pagination cursor '{cursor}'"
+    )))
+}
+
+/// Fetch pages, starting without a cursor, until one has no next cursor
+async fn collect_pages<T, F, Fut>(mut fetch_page: F) -> MCPResult<Vec<T>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = MCPResult<Page<T>>>,
+{
+    let mut items = Vec::new();
+    let mut cursor = None;
+    let mut seen = HashSet::new();
+    loop {
+        let (page, next_cursor) = fetch_page(cursor).await?;
+        items.extend(page);
+        match next_cursor {
+            Some(next) if !seen.insert(next.clone()) => return Err(repeated_cursor(&next)),
+            Some(next) => cursor = Some(next),
+            None => return Ok(items),
+        }
+    }
+}
+
+/// Lazy [`collect_pages`]: the next page is fetched once the stream has
+/// yielded every item of the current one
+fn page_stream<'a, T, F, Fut>(fetch_page: F) -> impl Stream<Item = MCPResult<T>> + 'a
+where
+    T: 'a,
+    F: FnMut(Option<String>) -> Fut + 'a,
+    Fut: Future<Output = MCPResult<Page<T>>> + 'a,
+{
+    struct Paging<F> {
+        fetch_page: F,
+        cursor: Option<String>,
+        seen: HashSet<String>,
+    }
+
+    let paging = Paging {
+        fetch_page,
+        cursor: None,
+        seen: HashSet::new(),
+    };
+    futures::stream::try_unfold(Some(paging), |paging| async move {
+        let Some(mut paging) = paging else {
+            return Ok(None);
+        };
+        let (page, next_cursor) = (paging.fetch_page)(paging.cursor.take()).await?;
+        let paging = match next_cursor {
+            Some(next) if !paging.seen.insert(next.clone()) => {
+                return Err(repeated_cursor(&next));
+            }
+            Some(next) => {
+                paging.cursor = Some(next);
+                Some(paging)
+            }
+            None => None,
+        };
+        Ok(Some((
+            futures::stream::iter(page.into_iter().map(Ok)),
+            paging,
+        )))
+    })
+    .try_flatten()
+}
+
+/// Parse the result of a typed tool call into its output type
+fn typed_tool_output<O: DeserializeOwned>(name: &str, result: ToolResult) -> MCPResult<O> {
+    let text = result.content.iter().find_map(|content| match content {
+        ToolContent::Text { text } => Some(text.as_str()),
+        _ => None,
+    });
+    if result.is_error == Some(true) {
+        return Err(ToolError::ExecutionFailed(format!(
+            "Tool '{name}': {}",
+            text.unwrap_or("no details")
+        ))
+        .into());
+    }
+    if let Some(output) = result.structured_content_as() {
+        return output.map_err(|e| {
+            MCPError::serialization_error(format!("Unexpected output from tool '{name}': {e}"))
+        });
+    }
+    let text = text.ok_or_else(|| {
+        MCPError::serialization_error(format!("Tool '{name}' returned no text content"))
+    })?;
+    // Plain text output is accepted for string-like output types
+    serde_json::from_str(text)
+        .or_else(|_| serde_json::from_value(Value::String(text.to_string())))
+        .map_err(|e| {
+            MCPError::serialization_error(format!("Unexpected output from tool '{name}': {e}"))
+        })
+}
+
+/// Check `result` against the output schema of tool `name` under `policy`
+fn check_tool_output(
+    name: &str,
+    result: &ToolResult,
+    schema: &Value,
+    policy: OutputValidation,
+) -> MCPResult<()> {
+    if policy == OutputValidation::Off || result.is_error == Some(true) {
+        return Ok(());
+    }
+    let outcome = match &result.structured_content {
+        Some(content) => {
+            ultrafast_mcp_core::schema::validation::validate_tool_output(content, schema)
+                .map_err(|e| e.to_string())
+        }
+        None => Err("no structured content".to_string()),
+    };
+    match (outcome, policy) {
+        (Ok(()), _) => Ok(()),
+        (Err(e), OutputValidation::Fail) => {
+            Err(ToolError::SchemaValidation(format!("Output of tool '{name}': {e}")).into())
+        }
+        (Err(e), _) => {
+            warn!("Output of tool '{}' does not match its schema: {}", name, e);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1390,4 +3406,680 @@ mod tests {
         }
         assert_eq!(client.get_state().await, ClientState::Initializing);
     }
+
+    #[test]
+    fn test_typed_tool_output_parsing() {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        struct Sum {
+            sum: i64,
+        }
+
+        let result = |text: &str, is_error| ToolResult {
+            content: vec![ToolContent::text(text.to_string())],
+            is_error,
+            structured_content: None,
+            progress_summary: None,
+        };
+        assert_eq!(
+            typed_tool_output::<Sum>("add", result(r#"{"sum":5}"#, None)).unwrap(),
+            Sum { sum: 5 }
+        );
+        assert_eq!(
+            typed_tool_output::<String>("echo", result("hello", None)).unwrap(),
+            "hello"
+        );
+        assert!(matches!(
+            typed_tool_output::<Sum>("add", result("overflow", Some(true))),
+            Err(MCPError::ToolExecution(ToolError::ExecutionFailed(_)))
+        ));
+        assert!(typed_tool_output::<Sum>("add", result(r#"{"total":5}"#, None)).is_err());
+
+        // Structured content takes precedence over the text
+        let structured = ToolResult {
+            structured_content: Some(serde_json::json!({"sum": 7})),
+            ..result("the sum is 7", None)
+        };
+        assert_eq!(
+            typed_tool_output::<Sum>("add", structured).unwrap(),
+            Sum { sum: 7 }
+        );
+    }
+
+    #[test]
+    fn test_tool_output_checked_against_schema() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "sum": { "type": "integer" } },
+            "required": ["sum"]
+        });
+        let result = |structured_content, is_error| ToolResult {
+            content: vec![ToolContent::text("result".to_string())],
+            is_error,
+            structured_content,
+            progress_summary: None,
+        };
+        let valid = result(Some(serde_json::json!({"sum": 5})), None);
+        let drifted = result(Some(serde_json::json!({"total": 5})), None);
+        let unstructured = result(None, None);
+
+        let check = |result: &ToolResult, policy| check_tool_output("add", result, &schema, policy);
+        assert!(check(&valid, OutputValidation::Fail).is_ok());
+        assert!(matches!(
+            check(&drifted, OutputValidation::Fail),
+            Err(MCPError::ToolExecution(ToolError::SchemaValidation(_)))
+        ));
+        assert!(check(&unstructured, OutputValidation::Fail).is_err());
+        assert!(check(&drifted, OutputValidation::Warn).is_ok());
+        assert!(check(&drifted, OutputValidation::Off).is_ok());
+
+        // Error results carry no structured output to check
+        let failed = result(None, Some(true));
+        assert!(check(&failed, OutputValidation::Fail).is_ok());
+    }
+
+    /// Pages of `page_size` numbers up to `total`, with the page's start as
+    /// its cursor
+    async fn numbers_page(cursor: Option<String>, total: u32) -> MCPResult<Page<u32>> {
+        let start: u32 = cursor.map_or(0, |cursor| cursor.parse().unwrap());
+        let end = (start + 3).min(total);
+        let next_cursor = (end < total).then(|| end.to_string());
+        Ok(((start..end).collect(), next_cursor))
+    }
+
+    #[tokio::test]
+    async fn test_pages_are_followed_until_exhausted() {
+        let all = collect_pages(|cursor| numbers_page(cursor, 10))
+            .await
+            .unwrap();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+
+        let streamed: Vec<u32> = page_stream(|cursor| numbers_page(cursor, 10))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed, all);
+
+        let empty = collect_pages(|cursor| numbers_page(cursor, 0))
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_cursor_stops_paging() {
+        let looping = |_cursor: Option<String>| async { Ok((vec![1], Some("again".to_string()))) };
+        assert!(matches!(
+            collect_pages(looping).await,
+            Err(MCPError::Protocol(ProtocolError::InvalidResponse(_)))
+        ));
+
+        let streamed: Vec<MCPResult<u32>> = futures::StreamExt::collect(page_stream(looping)).await;
+        assert_eq!(streamed.len(), 2);
+        assert!(streamed[0].is_ok());
+        assert!(streamed[1].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_pending_requests_receive_timeout() {
+        let mut state = ClientStateManager::new();
+        let now = tokio::time::Instant::now();
+
+        let (expired_sender, expired_receiver) = oneshot::channel();
+        state.add_pending_request(
+            1,
+            PendingRequest {
+                method: "tools/call".to_string(),
+                response_sender: expired_sender,
+                deadline: now - std::time::Duration::from_millis(1),
+                timeout: std::time::Duration::from_secs(60),
+                progress_token: None,
+            },
+        );
+        let (live_sender, _live_receiver) = oneshot::channel();
+        state.add_pending_request(
+            2,
+            PendingRequest {
+                method: "tools/list".to_string(),
+                response_sender: live_sender,
+                deadline: now + std::time::Duration::from_secs(60),
+                timeout: std::time::Duration::from_secs(60),
+                progress_token: None,
+            },
+        );
+
+        assert_eq!(state.expire_pending_requests(now), [1]);
+        assert!(matches!(
+            expired_receiver.await.unwrap(),
+            Err(MCPError::Protocol(ProtocolError::RequestTimeout))
+        ));
+
+        // A response to the expired request is late, a second one a duplicate
+        assert_eq!(
+            state.classify_unmatched_response(Some(1)),
+            Some((UnmatchedResponseKind::Late, "tools/call".to_string()))
+        );
+        assert_eq!(
+            state.classify_unmatched_response(Some(1)),
+            Some((UnmatchedResponseKind::Duplicate, "tools/call".to_string()))
+        );
+        assert_eq!(state.classify_unmatched_response(Some(99)), None);
+        assert_eq!(
+            state.request_metrics(),
+            RequestMetrics {
+                pending_requests: 1,
+                expired_requests: 1,
+                late_responses: 1,
+                duplicate_responses: 1,
+                orphaned_responses: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_settled_request_history_is_bounded() {
+        let mut state = ClientStateManager::new();
+        for id in 0..(SETTLED_REQUEST_HISTORY as u64 + 10) {
+            state.settle_request(id, "ping".to_string(), false);
+        }
+
+        assert_eq!(state.settled_requests.len(), SETTLED_REQUEST_HISTORY);
+        assert_eq!(state.classify_unmatched_response(Some(0)), None);
+        assert!(state.classify_unmatched_response(Some(10)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stats_return_to_baseline_after_churn() {
+        let mut state = ClientStateManager::new();
+        assert_eq!(state.stats(), ClientStats::default());
+
+        let now = tokio::time::Instant::now();
+        for round in 0..10_u64 {
+            let mut receivers = Vec::new();
+            for _ in 0..500 {
+                let id = state.next_request_id();
+                let (response_sender, response_receiver) = oneshot::channel();
+                state.add_pending_request(
+                    id,
+                    PendingRequest {
+                        method: "tools/call".to_string(),
+                        response_sender,
+                        // Every other request is never answered and expires
+                        deadline: now + std::time::Duration::from_secs(id % 2),
+                        timeout: std::time::Duration::from_secs(60),
+                        progress_token: None,
+                    },
+                );
+                receivers.push(response_receiver);
+                state.remember_acked_notification(&format!("{round}-{id}"));
+            }
+            for id in (round * 500 + 1..=(round + 1) * 500).filter(|id| id % 2 == 1) {
+                let request = state.remove_pending_request(&id).unwrap();
+                state.settle_request(id, request.method, false);
+            }
+            state.expire_pending_requests(now);
+        }
+
+        let stats = state.stats();
+        assert_eq!(stats.pending_requests, 0);
+        assert_eq!(stats.settled_requests, SETTLED_REQUEST_HISTORY);
+        assert_eq!(stats.acked_notifications, ACKED_NOTIFICATION_HISTORY);
+    }
+
+    /// Server answering `tools/list` and `tools/call` in place, after
+    /// failing the next `failures` sends
+    #[derive(Default)]
+    struct FlakyServer {
+        failures: usize,
+        sent: Vec<String>,
+        notifications: Vec<JsonRpcRequest>,
+        responses: VecDeque<JsonRpcMessage>,
+    }
+
+    struct FlakyTransport(Arc<std::sync::Mutex<FlakyServer>>);
+
+    #[async_trait::async_trait]
+    impl Transport for FlakyTransport {
+        async fn send_message(
+            &mut self,
+            message: JsonRpcMessage,
+        ) -> ultrafast_mcp_transport::Result<()> {
+            let mut server = self.0.lock().unwrap();
+            let request = match message {
+                JsonRpcMessage::Request(request) => request,
+                JsonRpcMessage::Notification(notification) => {
+                    server.notifications.push(notification);
+                    return Ok(());
+                }
+                _ => return Ok(()),
+            };
+            server.sent.push(request.method.clone());
+            if server.failures > 0 {
+                server.failures -= 1;
+                return Err(ultrafast_mcp_transport::TransportError::NetworkError {
+                    message: "connection reset".to_string(),
+                    source: None,
+                });
+            }
+            let result = match request.method.as_str() {
+                "tools/list" => serde_json::json!({"tools": [
+                    {"name": "lookup", "description": "Look up", "inputSchema": {"type": "object"},
+                     "annotations": {"idempotentHint": true}},
+                    {"name": "charge", "description": "Charge", "inputSchema": {"type": "object"}},
+                ]}),
+                _ => serde_json::json!({"content": [{"type": "text", "text": "ok"}]}),
+            };
+            server
+                .responses
+                .push_back(JsonRpcMessage::Response(JsonRpcResponse::success(
+                    result, request.id,
+                )));
+            Ok(())
+        }
+
+        async fn receive_message(&mut self) -> ultrafast_mcp_transport::Result<JsonRpcMessage> {
+            self.0
+                .lock()
+                .unwrap()
+                .responses
+                .pop_front()
+                .ok_or(ultrafast_mcp_transport::TransportError::ConnectionClosed)
+        }
+
+        async fn close(&mut self) -> ultrafast_mcp_transport::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_idempotent_requests_are_retried() {
+        let server = Arc::new(std::sync::Mutex::new(FlakyServer::default()));
+        let client = UltraFastClient::new(
+            ClientInfo {
+                name: "test-client".to_string(),
+                version: "1.0.0".to_string(),
+                description: None,
+                authors: None,
+                homepage: None,
+                license: None,
+                repository: None,
+            },
+            ClientCapabilities::default(),
+        )
+        .with_retry_policy(RetryPolicy::new().with_backoff(
+            std::time::Duration::from_millis(1),
+            std::time::Duration::from_millis(1),
+            1.0,
+        ));
+        *client.transport.write().await = Some(Box::new(FlakyTransport(server.clone())));
+        client
+            .state_manager
+            .write()
+            .await
+            .set_state(ClientState::Operating);
+        let call = |name: &str| ToolCall {
+            name: name.to_string(),
+            arguments: None,
+        };
+
+        server.lock().unwrap().failures = 2;
+        assert_eq!(client.list_tools_default().await.unwrap().tools.len(), 2);
+        // Tool calls are only retried for tools annotated idempotent
+        server.lock().unwrap().failures = 1;
+        assert!(client.call_tool(call("lookup")).await.is_ok());
+        server.lock().unwrap().failures = 1;
+        assert!(client.call_tool(call("charge")).await.is_err());
+
+        assert_eq!(
+            server.lock().unwrap().sent,
+            [
+                "tools/list",
+                "tools/list",
+                "tools/list",
+                "tools/call",
+                "tools/call",
+                "tools/call"
+            ]
+        );
+        assert_eq!(client.request_metrics().await.pending_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_expired_requests_are_cancelled_on_the_server() {
+        let server = Arc::new(std::sync::Mutex::new(FlakyServer::default()));
+        let transport: RwLock<Option<Box<dyn Transport>>> =
+            RwLock::new(Some(Box::new(FlakyTransport(server.clone()))));
+        let state_manager = RwLock::new(ClientStateManager::new());
+        let (sender, receiver) = oneshot::channel();
+        state_manager.write().await.add_pending_request(
+            7,
+            PendingRequest {
+                method: "tools/call".to_string(),
+                response_sender: sender,
+                deadline: tokio::time::Instant::now(),
+                timeout: std::time::Duration::from_secs(60),
+                progress_token: None,
+            },
+        );
+
+        UltraFastClient::sweep_pending_requests_once(
+            &state_manager,
+            &transport,
+            &TransportWanted::default(),
+        )
+        .await;
+
+        assert!(matches!(
+            receiver.await.unwrap(),
+            Err(MCPError::Protocol(ProtocolError::RequestTimeout))
+        ));
+        assert_eq!(state_manager.read().await.stats().pending_requests, 0);
+        let notifications = &server.lock().unwrap().notifications;
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].method, "notifications/cancelled");
+        assert_eq!(
+            notifications[0].params.as_ref().unwrap()["requestId"],
+            serde_json::json!(7)
+        );
+    }
+
+    /// Transport that records what the client sends
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Vec<JsonRpcMessage>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for RecordingTransport {
+        async fn send_message(
+            &mut self,
+            message: JsonRpcMessage,
+        ) -> ultrafast_mcp_transport::Result<()> {
+            self.sent.push(message);
+            Ok(())
+        }
+
+        async fn receive_message(&mut self) -> ultrafast_mcp_transport::Result<JsonRpcMessage> {
+            Err(ultrafast_mcp_transport::TransportError::ConnectionClosed)
+        }
+
+        async fn close(&mut self) -> ultrafast_mcp_transport::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_pings_close_the_connection() {
+        let client = UltraFastClient::new(
+            ClientInfo {
+                name: "test-client".to_string(),
+                version: "1.0.0".to_string(),
+                description: None,
+                authors: None,
+                homepage: None,
+                license: None,
+                repository: None,
+            },
+            ClientCapabilities::default(),
+        )
+        .with_ping_policy(
+            PingPolicy::new(std::time::Duration::from_millis(10))
+                .with_timeout(std::time::Duration::from_millis(10))
+                .with_max_missed(2)
+                .with_disconnect(true),
+        );
+        *client.transport.write().await = Some(Box::new(RecordingTransport::default()));
+        client
+            .state_manager
+            .write()
+            .await
+            .set_state(ClientState::Operating);
+        let mut events = client.subscribe_ping_events();
+
+        client
+            .start_ping_monitoring(std::time::Duration::from_millis(10))
+            .await
+            .unwrap();
+        let mut received = Vec::new();
+        while received.last() != Some(&PingEvent::Disconnected) {
+            let event = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv())
+                .await
+                .expect("the policy should close the connection")
+                .unwrap();
+            received.push(event);
+        }
+
+        assert_eq!(
+            received,
+            [
+                PingEvent::Missed { consecutive: 1 },
+                PingEvent::Unhealthy { consecutive: 2 },
+                PingEvent::Disconnected
+            ]
+        );
+        assert!(!client.can_operate().await);
+        let pings = client.health().await.unwrap().pings.unwrap();
+        assert_eq!((pings.sent, pings.missed), (2, 2));
+        assert!(pings.unhealthy);
+    }
+
+    #[tokio::test]
+    async fn test_resent_notifications_are_acked_but_handled_once() {
+        let state_manager = RwLock::new(ClientStateManager::new());
+        let mut transport = RecordingTransport::default();
+        let notification = JsonRpcRequest::notification(
+            "notifications/resources/updated".to_string(),
+            Some(serde_json::json!({
+                "uri": "file:///a",
+                "_meta": {"ultrafast/ackId": "7", "ultrafast/seq": 1}
+            })),
+        );
+
+        for _ in 0..2 {
+            UltraFastClient::receive_notification(
+                &notification,
+                &mut transport,
+                &state_manager,
+                None,
+                None,
+            )
+            .await;
+        }
+
+        assert_eq!(transport.sent.len(), 2);
+        let JsonRpcMessage::Notification(ack) = &transport.sent[0] else {
+            panic!("expected an ack notification");
+        };
+        assert_eq!(ack.method, NOTIFICATION_ACK_METHOD);
+        assert_eq!(ack.params, Some(serde_json::json!({"ackId": "7"})));
+        // The resend is not mistaken for a reordered notification
+        assert_eq!(
+            state_manager
+                .read()
+                .await
+                .notification_sequence
+                .metrics()
+                .sequenced_notifications,
+            1
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingResourceChanges {
+        uris: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ResourceChangeHandler for RecordingResourceChanges {
+        async fn handle_resource_updated(&self, uri: String) {
+            self.uris.lock().unwrap().push(uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resource_updates_reach_handler_for_subscribed_uris() {
+        let state_manager = RwLock::new(ClientStateManager::new());
+        {
+            let mut state = state_manager.write().await;
+            state.resource_subscriptions.insert("file:///a".to_string());
+            state
+                .resource_subscriptions
+                .insert("file:///dir/**".to_string());
+        }
+        let handler = RecordingResourceChanges::default();
+        let mut transport = RecordingTransport::default();
+
+        for uri in ["file:///a", "file:///b", "file:///dir/c"] {
+            let notification = JsonRpcRequest::notification(
+                "notifications/resources/updated".to_string(),
+                Some(serde_json::json!({ "uri": uri })),
+            );
+            UltraFastClient::receive_notification(
+                &notification,
+                &mut transport,
+                &state_manager,
+                None,
+                Some(&handler),
+            )
+            .await;
+        }
+
+        assert_eq!(
+            *handler.uris.lock().unwrap(),
+            vec!["file:///a".to_string(), "file:///dir/c".to_string()]
+        );
+    }
+
+    struct DeclineElicitation;
+
+    #[async_trait::async_trait]
+    impl ClientElicitationHandler for DeclineElicitation {
+        async fn handle_elicitation_request(
+            &self,
+            _request: ElicitationRequest,
+        ) -> MCPResult<ElicitationResponse> {
+            Ok(ElicitationResponse {
+                action: ultrafast_mcp_core::types::elicitation::ElicitationAction::Decline,
+                content: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_handlers_are_set_before_connect() {
+        let client = UltraFastClient::new(
+            ClientInfo::new("test-client".to_string(), "1.0.0".to_string()),
+            ClientCapabilities::default(),
+        )
+        .with_elicitation_handler(Arc::new(DeclineElicitation));
+
+        assert!(client.elicitation_handler.is_some());
+    }
+
+    struct EchoSampling;
+
+    #[async_trait::async_trait]
+    impl ClientSamplingHandler for EchoSampling {
+        async fn handle_sampling_request(
+            &self,
+            request: CreateMessageRequest,
+        ) -> MCPResult<CreateMessageResponse> {
+            Ok(CreateMessageResponse {
+                role: ultrafast_mcp_core::types::sampling::SamplingRole::Assistant,
+                content: request.messages[0].content.clone(),
+                model: Some("echo".to_string()),
+                stop_reason: None,
+                approval_status: None,
+                request_id: None,
+                processing_time_ms: None,
+                cost_info: None,
+                included_context: None,
+                human_feedback: None,
+                warnings: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_requests_are_answered_by_handler() {
+        let request = JsonRpcRequest::new(
+            "sampling/createMessage".to_string(),
+            Some(serde_json::json!({
+                "messages": [{"role": "user", "content": {"type": "text", "text": "hi"}}],
+                "maxTokens": 10
+            })),
+            Some(ultrafast_mcp_core::protocol::RequestId::number(1)),
+        );
+
+        let response =
+            UltraFastClient::handle_server_request(&request, None, Some(&EchoSampling), None).await;
+        let result = response.result.expect("sampling should succeed");
+        assert_eq!(result["model"], "echo");
+        assert_eq!(result["content"]["text"], "hi");
+
+        let response = UltraFastClient::handle_server_request(&request, None, None, None).await;
+        assert_eq!(response.error.expect("no handler").code, -32001);
+    }
+
+    #[test]
+    fn test_operation_timeouts_follow_measured_latency() {
+        let client = UltraFastClient::new(
+            ClientInfo::new("test-client".to_string(), "1.0.0".to_string()),
+            ClientCapabilities::default(),
+        )
+        .with_adaptive_timeouts(AdaptiveTimeouts::default());
+        let configured = client
+            .get_timeout_config()
+            .get_timeout_for_operation("request");
+        assert_eq!(client.get_operation_timeout("request"), configured);
+
+        client.latency.record(std::time::Duration::from_secs(1));
+        assert!(client.get_operation_timeout("request") > configured);
+        assert_eq!(client.latency_estimate().unwrap().samples, 1);
+    }
+
+    #[tokio::test]
+    async fn test_roots_list_is_answered_from_configured_roots() {
+        let client = UltraFastClient::new(
+            ClientInfo::new("test-client".to_string(), "1.0.0".to_string()),
+            ClientCapabilities::default(),
+        )
+        .with_roots(vec![Root {
+            uri: "file:///workspace".to_string(),
+            name: Some("workspace".to_string()),
+            security: None,
+        }]);
+        assert!(client.capabilities.roots.is_some());
+
+        let request = JsonRpcRequest::new(
+            "roots/list".to_string(),
+            None,
+            Some(ultrafast_mcp_core::protocol::RequestId::number(1)),
+        );
+        let roots = client.roots.read().await.clone();
+        let response =
+            UltraFastClient::handle_server_request(&request, None, None, roots.as_deref()).await;
+        assert_eq!(
+            response.result.unwrap()["roots"][0]["uri"],
+            "file:///workspace"
+        );
+
+        let response = UltraFastClient::handle_server_request(&request, None, None, None).await;
+        assert_eq!(response.error.expect("no roots").code, -32001);
+    }
+
+    #[cfg(feature = "oauth")]
+    #[tokio::test]
+    async fn test_with_bearer_auth_inside_runtime() {
+        let client = UltraFastClient::new(
+            ClientInfo::new("test-client".to_string(), "1.0.0".to_string()),
+            ClientCapabilities::default(),
+        )
+        .with_bearer_auth("token".to_string());
+
+        let headers = client.get_auth_headers().await.unwrap();
+        assert_eq!(
+            headers.get("Authorization").map(String::as_str),
+            Some("Bearer token")
+        );
+    }
 }
@@ -0,0 +1,711 @@
+//! Supervising several stdio servers from one host process
+//!
+//! A host that runs the MCP servers a user has installed needs to start each
+//! of them as a child process, notice when one dies and bring it back, and
+//! keep what it printed for troubleshooting. [`ServerManager`] does this for
+//! any number of [`ManagedServerConfig`]s:
+//!
+//! - each server is spawned with piped stdin/stdout and connected through an
+//!   [`UltraFastClient`], which [`ServerManager::client`] hands out while the
+//!   server runs
+//! - a server that exits or fails to initialize is restarted after an
+//!   exponential backoff, until its [`RestartPolicy`] gives up
+//! - what a server writes to stderr is kept, up to a number of lines, in
+//!   [`ServerManager::logs`]
+//! - [`ServerManager::health`] reports the state of all servers at once
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, ChildStderr, Command};
+use tokio::sync::{RwLock, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult, TransportError},
+    types::{
+        client::{ClientCapabilities, ClientInfo},
+        tools::{ToolCall, ToolResult},
+    },
+};
+use ultrafast_mcp_transport::ChildProcessTransport;
+
+use crate::UltraFastClient;
+
+/// Lines of stderr kept per server by default
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+
+/// How a server that exited is brought back
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartPolicy {
+    /// Restarts in a row before the server is given up on; `None` restarts
+    /// forever
+    pub max_restarts: Option<u32>,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub backoff_multiplier: f64,
+    /// A server that ran this long before exiting starts over with the
+    /// initial backoff and a fresh restart count
+    pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: Some(5),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            backoff_multiplier: 2.0,
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Never restart; a server that exits is marked failed
+    pub fn never() -> Self {
+        Self {
+            max_restarts: Some(0),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_restarts(mut self, max_restarts: Option<u32>) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Wait `initial_backoff` before the first restart, growing by
+    /// `backoff_multiplier` per restart in a row up to `max_backoff`
+    pub fn with_backoff(
+        mut self,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+        backoff_multiplier: f64,
+    ) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self.backoff_multiplier = backoff_multiplier;
+        self
+    }
+
+    pub fn with_stable_after(mut self, stable_after: Duration) -> Self {
+        self.stable_after = stable_after;
+        self
+    }
+
+    /// Delay before restart number `restart` in a row, counting from one
+    pub fn backoff(&self, restart: u32) -> Duration {
+        let exponent = restart.saturating_sub(1) as i32;
+        self.initial_backoff
+            .mul_f64(self.backoff_multiplier.max(1.0).powi(exponent))
+            .min(self.max_backoff)
+    }
+}
+
+/// A stdio server run by a [`ServerManager`]
+#[derive(Debug, Clone)]
+pub struct ManagedServerConfig {
+    /// Name the server is addressed by
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// Variables added to the environment the host runs in
+    pub env: HashMap<String, String>,
+    pub working_dir: Option<PathBuf>,
+    pub restart: RestartPolicy,
+    /// How long a stopping server gets to exit after its stdin is closed
+    /// before it is killed
+    pub shutdown_timeout: Duration,
+}
+
+impl ManagedServerConfig {
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            working_dir: None,
+            restart: RestartPolicy::default(),
+            shutdown_timeout: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(working_dir.into());
+        self
+    }
+
+    pub fn with_restart_policy(mut self, restart: RestartPolicy) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    pub fn with_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+}
+
+/// Where a managed server is in its lifecycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManagedServerState {
+    /// Spawned, not yet initialized
+    Starting,
+    /// Initialized and serving requests
+    Running,
+    /// Exited; waiting out the backoff before restart number `attempt` in a
+    /// row
+    Restarting { attempt: u32 },
+    /// Stopped through the manager
+    Stopped,
+    /// Given up on after too many restarts
+    Failed(String),
+}
+
+/// Point-in-time view of a managed server
+#[derive(Debug, Clone)]
+pub struct ManagedServerStatus {
+    pub name: String,
+    pub state: ManagedServerState,
+    /// Process ID of the running child
+    pub pid: Option<u32>,
+    /// Restarts since the server was added
+    pub restarts: u32,
+    /// When the running child was spawned
+    pub started_at: Option<SystemTime>,
+    /// Why the previous child ended
+    pub last_exit: Option<String>,
+}
+
+/// State of every server a [`ServerManager`] runs
+#[derive(Debug, Clone)]
+pub struct ServerManagerHealth {
+    /// Servers ordered by name
+    pub servers: Vec<ManagedServerStatus>,
+    pub running: usize,
+    pub failed: usize,
+}
+
+impl ServerManagerHealth {
+    /// Whether every server is running
+    pub fn is_healthy(&self) -> bool {
+        self.running == self.servers.len()
+    }
+}
+
+/// Spawns, monitors and restarts stdio servers and routes requests to them
+pub struct ServerManager {
+    client_info: ClientInfo,
+    capabilities: ClientCapabilities,
+    log_capacity: usize,
+    servers: RwLock<HashMap<String, ManagedServer>>,
+}
+
+struct ManagedServer {
+    shared: Arc<Shared>,
+    stop: watch::Sender<bool>,
+    supervisor: JoinHandle<()>,
+}
+
+/// What the supervisor task and the manager both see
+struct Shared {
+    status: Mutex<ManagedServerStatus>,
+    client: std::sync::RwLock<Option<Arc<UltraFastClient>>>,
+    logs: Mutex<VecDeque<String>>,
+    log_capacity: usize,
+}
+
+impl Shared {
+    fn status(&self) -> MutexGuard<'_, ManagedServerStatus> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_state(&self, state: ManagedServerState) {
+        self.status().state = state;
+    }
+
+    fn client(&self) -> Option<Arc<UltraFastClient>> {
+        self.client
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn set_client(&self, client: Option<Arc<UltraFastClient>>) {
+        *self.client.write().unwrap_or_else(|e| e.into_inner()) = client;
+    }
+
+    fn push_log(&self, line: String) {
+        let mut logs = self.logs.lock().unwrap_or_else(|e| e.into_inner());
+        if logs.len() == self.log_capacity {
+            logs.pop_front();
+        }
+        logs.push_back(line);
+    }
+}
+
+/// How one run of a server ended
+enum RunOutcome {
+    Stopped,
+    Exited(String),
+}
+
+impl ServerManager {
+    /// Manage servers, identifying to each of them as `client_info`
+    pub fn new(client_info: ClientInfo, capabilities: ClientCapabilities) -> Self {
+        Self {
+            client_info,
+            capabilities,
+            log_capacity: DEFAULT_LOG_CAPACITY,
+            servers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Keep the last `lines` lines of each server's stderr
+    pub fn with_log_capacity(mut self, lines: usize) -> Self {
+        self.log_capacity = lines.max(1);
+        self
+    }
+
+    /// Start supervising a server
+    ///
+    /// Returns once the supervisor runs; the server itself starts in the
+    /// background, see [`status`](Self::status).
+    pub async fn start(&self, config: ManagedServerConfig) -> MCPResult<()> {
+        let mut servers = self.servers.write().await;
+        if servers.contains_key(&config.name) {
+            return Err(MCPError::invalid_params(format!(
+                "Server {} is already managed",
+                config.name
+            )));
+        }
+
+        let shared = Arc::new(Shared {
+            status: Mutex::new(ManagedServerStatus {
+                name: config.name.clone(),
+                state: ManagedServerState::Starting,
+                pid: None,
+                restarts: 0,
+                started_at: None,
+                last_exit: None,
+            }),
+            client: std::sync::RwLock::new(None),
+            logs: Mutex::new(VecDeque::new()),
+            log_capacity: self.log_capacity,
+        });
+        let (stop, stopped) = watch::channel(false);
+        let supervisor = tokio::spawn(supervise(
+            config.clone(),
+            self.client_info.clone(),
+            self.capabilities.clone(),
+            shared.clone(),
+            stopped,
+        ));
+        servers.insert(
+            config.name,
+            ManagedServer {
+                shared,
+                stop,
+                supervisor,
+            },
+        );
+        Ok(())
+    }
+
+    /// Stop a server and forget about it
+    pub async fn stop(&self, name: &str) -> MCPResult<()> {
+        let server = self
+            .servers
+            .write()
+            .await
+            .remove(name)
+            .ok_or_else(|| unknown_server(name))?;
+        let _ = server.stop.send(true);
+        server
+            .supervisor
+            .await
+            .map_err(|e| MCPError::internal_error(format!("Supervisor of {name} failed: {e}")))
+    }
+
+    /// Stop every server
+    pub async fn shutdown(&self) {
+        let servers: Vec<_> = self.servers.write().await.drain().collect();
+        for (_, server) in &servers {
+            let _ = server.stop.send(true);
+        }
+        for (name, server) in servers {
+            if let Err(e) = server.supervisor.await {
+                warn!("Supervisor of {} failed: {}", name, e);
+            }
+        }
+    }
+
+    /// Names of the managed servers
+    pub async fn server_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.servers.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Client connected to `name`, while it runs
+    pub async fn client(&self, name: &str) -> Option<Arc<UltraFastClient>> {
+        self.servers.read().await.get(name)?.shared.client()
+    }
+
+    /// Call a tool on server `name`
+    pub async fn call_tool(&self, name: &str, tool_call: ToolCall) -> MCPResult<ToolResult> {
+        self.running_client(name).await?.call_tool(tool_call).await
+    }
+
+    async fn running_client(&self, name: &str) -> MCPResult<Arc<UltraFastClient>> {
+        let servers = self.servers.read().await;
+        let server = servers.get(name).ok_or_else(|| unknown_server(name))?;
+        server.shared.client().ok_or_else(|| {
+            MCPError::Transport(TransportError::ConnectionFailed(format!(
+                "Server {name} is not running"
+            )))
+        })
+    }
+
+    pub async fn status(&self, name: &str) -> Option<ManagedServerStatus> {
+        let servers = self.servers.read().await;
+        Some(servers.get(name)?.shared.status().clone())
+    }
+
+    /// State of all servers
+    pub async fn health(&self) -> ServerManagerHealth {
+        let mut servers: Vec<_> = self
+            .servers
+            .read()
+            .await
+            .values()
+            .map(|server| server.shared.status().clone())
+            .collect();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+        let count = |matches: fn(&ManagedServerState) -> bool| {
+            servers.iter().filter(|s| matches(&s.state)).count()
+        };
+        ServerManagerHealth {
+            running: count(|state| *state == ManagedServerState::Running),
+            failed: count(|state| matches!(state, ManagedServerState::Failed(_))),
+            servers,
+        }
+    }
+
+    /// The most recent lines server `name` wrote to stderr, oldest first,
+    /// across restarts
+    pub async fn logs(&self, name: &str) -> Option<Vec<String>> {
+        let servers = self.servers.read().await;
+        let logs = servers
+            .get(name)?
+            .shared
+            .logs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect();
+        Some(logs)
+    }
+}
+
+fn unknown_server(name: &str) -> MCPError {
+    MCPError::not_found(format!("No managed server named {name}"))
+}
+
+/// Run a server until it is stopped or given up on
+async fn supervise(
+    config: ManagedServerConfig,
+    client_info: ClientInfo,
+    capabilities: ClientCapabilities,
+    shared: Arc<Shared>,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut restarts_in_row = 0;
+    loop {
+        shared.set_state(ManagedServerState::Starting);
+        let started = Instant::now();
+        let reason = match run_once(&config, &client_info, &capabilities, &shared, &mut stop).await
+        {
+            RunOutcome::Stopped => break,
+            RunOutcome::Exited(reason) => reason,
+        };
+        warn!("Managed server {} exited: {}", config.name, reason);
+        {
+            let mut status = shared.status();
+            status.pid = None;
+            status.started_at = None;
+            status.last_exit = Some(reason.clone());
+        }
+
+        if started.elapsed() >= config.restart.stable_after {
+            restarts_in_row = 0;
+        }
+        restarts_in_row += 1;
+        if config
+            .restart
+            .max_restarts
+            .is_some_and(|max| restarts_in_row > max)
+        {
+            shared.set_state(ManagedServerState::Failed(reason));
+            return;
+        }
+
+        shared.set_state(ManagedServerState::Restarting {
+            attempt: restarts_in_row,
+        });
+        tokio::select! {
+            _ = tokio::time::sleep(config.restart.backoff(restarts_in_row)) => {}
+            _ = stop.changed() => break,
+        }
+        shared.status().restarts += 1;
+    }
+    shared.set_state(ManagedServerState::Stopped);
+}
+
+/// Spawn the server once and serve it until it exits or is stopped
+async fn run_once(
+    config: &ManagedServerConfig,
+    client_info: &ClientInfo,
+    capabilities: &ClientCapabilities,
+    shared: &Arc<Shared>,
+    stop: &mut watch::Receiver<bool>,
+) -> RunOutcome {
+    if *stop.borrow() {
+        return RunOutcome::Stopped;
+    }
+
+    let mut command = Command::new(&config.command);
+    command
+        .args(&config.args)
+        .envs(&config.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(working_dir) = &config.working_dir {
+        command.current_dir(working_dir);
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return RunOutcome::Exited(format!("failed to spawn: {e}")),
+    };
+    {
+        let mut status = shared.status();
+        status.pid = child.id();
+        status.started_at = Some(SystemTime::now());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(collect_logs(config.name.clone(), stderr, shared.clone()));
+    }
+
+    let transport = match ChildProcessTransport::child(&mut child) {
+        Ok(transport) => transport,
+        Err(e) => return RunOutcome::Exited(e.to_string()),
+    };
+    let client = Arc::new(UltraFastClient::new(
+        client_info.clone(),
+        capabilities.clone(),
+    ));
+
+    let connected = tokio::select! {
+        connected = client.connect(Box::new(transport)) => connected,
+        exited = child.wait() => {
+            stop_child(config, &client, &mut child).await;
+            return RunOutcome::Exited(exit_reason(exited));
+        }
+        _ = stop.changed() => {
+            stop_child(config, &client, &mut child).await;
+            return RunOutcome::Stopped;
+        }
+    };
+    if let Err(e) = connected {
+        stop_child(config, &client, &mut child).await;
+        // A child that dies while starting closes its stdout first, so the
+        // exit status says more than the connection error
+        return RunOutcome::Exited(match child.try_wait() {
+            Ok(Some(status)) => status.to_string(),
+            _ => format!("failed to initialize: {e}"),
+        });
+    }
+
+    info!("Managed server {} is running", config.name);
+    shared.set_client(Some(client.clone()));
+    shared.set_state(ManagedServerState::Running);
+
+    let outcome = tokio::select! {
+        exited = child.wait() => RunOutcome::Exited(exit_reason(exited)),
+        _ = stop.changed() => RunOutcome::Stopped,
+    };
+    shared.set_client(None);
+    stop_child(config, &client, &mut child).await;
+    outcome
+}
+
+/// Close the connection, giving the child its shutdown timeout to exit
+/// before killing it
+async fn stop_child(config: &ManagedServerConfig, client: &UltraFastClient, child: &mut Child) {
+    if let Err(e) = client.disconnect().await {
+        debug!("Disconnecting from {} failed: {}", config.name, e);
+    }
+    if tokio::time::timeout(config.shutdown_timeout, child.wait())
+        .await
+        .is_err()
+    {
+        warn!(
+            "Managed server {} did not exit within {:?}, killing it",
+            config.name, config.shutdown_timeout
+        );
+        let _ = child.kill().await;
+    }
+}
+
+fn exit_reason(exited: std::io::Result<std::process::ExitStatus>) -> String {
+    match exited {
+        Ok(status) => status.to_string(),
+        Err(e) => format!("failed to wait for the process: {e}"),
+    }
+}
+
+async fn collect_logs(name: String, stderr: ChildStderr, shared: Arc<Shared>) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!(server = %name, "{}", line);
+        shared.push_log(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_up_to_the_limit() {
+        let policy = RestartPolicy::default().with_backoff(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            2.0,
+        );
+        let delays: Vec<_> = (1..=3).map(|restart| policy.backoff(restart)).collect();
+        assert_eq!(delays, [100, 200, 300].map(Duration::from_millis).to_vec());
+    }
+
+    #[cfg(unix)]
+    fn manager() -> ServerManager {
+        ServerManager::new(ClientInfo::default(), ClientCapabilities::default())
+    }
+
+    #[cfg(unix)]
+    async fn wait_for_state(
+        manager: &ServerManager,
+        name: &str,
+        done: impl Fn(&ManagedServerState) -> bool,
+    ) -> ManagedServerStatus {
+        for _ in 0..200 {
+            let status = manager.status(name).await.unwrap();
+            if done(&status.state) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("server {name} never reached the expected state");
+    }
+
+    /// Answers `initialize` and `tools/call` with canned responses
+    #[cfg(unix)]
+    const ECHO_SERVER: &str = r#"
+echo "echo server started" >&2
+while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9][0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  case "$line" in
+    *'"initialize"'*) result='{"protocolVersion":"2025-06-18","capabilities":{},"serverInfo":{"name":"echo","version":"1.0.0"}}' ;;
+    *'"tools/call"'*) result='{"content":[{"type":"text","text":"echoed"}]}' ;;
+    *) result='{}' ;;
+  esac
+  printf '{"jsonrpc":"2.0","id":%s,"result":%s}\n' "$id" "$result"
+done
+"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_running_server_is_routed_to_and_stopped() {
+        let manager = manager();
+        manager
+            .start(ManagedServerConfig::new("echo", "sh").with_args(["-c", ECHO_SERVER]))
+            .await
+            .unwrap();
+
+        let status = wait_for_state(&manager, "echo", |state| {
+            *state == ManagedServerState::Running
+        })
+        .await;
+        assert!(status.pid.is_some());
+        assert!(manager.health().await.is_healthy());
+
+        let call = ToolCall {
+            name: "echo".to_string(),
+            arguments: None,
+        };
+        let result = manager.call_tool("echo", call.clone()).await.unwrap();
+        assert_eq!(result.content.len(), 1);
+        assert_eq!(manager.logs("echo").await.unwrap(), ["echo server started"]);
+
+        manager.stop("echo").await.unwrap();
+        assert!(manager.status("echo").await.is_none());
+        assert!(manager.call_tool("echo", call).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_crashing_server_is_restarted_until_given_up() {
+        let manager = manager();
+        let restart = RestartPolicy::default()
+            .with_max_restarts(Some(2))
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(10), 1.0);
+        manager
+            .start(
+                ManagedServerConfig::new("crash", "sh")
+                    .with_args(["-c", "echo crashing >&2; exit 3"])
+                    .with_restart_policy(restart),
+            )
+            .await
+            .unwrap();
+
+        let status = wait_for_state(&manager, "crash", |state| {
+            matches!(state, ManagedServerState::Failed(_))
+        })
+        .await;
+        assert_eq!(status.restarts, 2);
+        assert!(status.last_exit.unwrap().contains('3'));
+        assert_eq!(manager.health().await.failed, 1);
+        assert!(
+            manager
+                .logs("crash")
+                .await
+                .unwrap()
+                .contains(&"crashing".to_string())
+        );
+        manager.shutdown().await;
+    }
+}
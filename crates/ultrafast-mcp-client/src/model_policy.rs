@@ -0,0 +1,389 @@
+//! Model selection policy for sampling requests
+//!
+//! Servers only express *preferences* for the model used to answer a
+//! `sampling/createMessage` request. A [`ModelPolicy`] maps those preferences
+//! and hints onto the models the host actually has, enforcing an allowlist
+//! and a per-request cost ceiling and falling back through a configured chain
+//! when nothing else fits. Every decision, including why each rejected model
+//! was ruled out, is kept in an audit log.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult},
+    types::sampling::{
+        CreateMessageRequest, Modality, ModelCapability, ModelPreferences, ModelSelectionContext,
+        ModelSelector, RequestContext,
+    },
+};
+
+/// Decisions kept in the audit log by default
+const DEFAULT_AUDIT_CAPACITY: usize = 256;
+
+/// Output tokens assumed for requests without `maxTokens`
+const DEFAULT_OUTPUT_TOKENS: u32 = 1000;
+
+/// Why a model was chosen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SelectionReason {
+    /// The model matched one of the server's hints
+    HintMatched { hint: String },
+    /// The model scored best against the server's priorities
+    Preferences { score: f64 },
+    /// Nothing else qualified; the model is next in the fallback chain
+    Fallback { position: usize },
+}
+
+/// A model ruled out by the policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedModel {
+    pub model_id: String,
+    pub reason: String,
+}
+
+/// Audit record of one model selection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDecision {
+    pub model_id: String,
+    pub reason: SelectionReason,
+    pub estimated_cost_cents: f64,
+    pub rejected: Vec<RejectedModel>,
+    pub decided_at: SystemTime,
+}
+
+/// Maps server model preferences onto the host's available models
+#[derive(Debug)]
+pub struct ModelPolicy {
+    models: Vec<ModelCapability>,
+    allowlist: Option<HashSet<String>>,
+    max_cost_cents: Option<f64>,
+    fallback_chain: Vec<String>,
+    selector: ModelSelector,
+    audit_capacity: usize,
+    audit: Mutex<VecDeque<ModelDecision>>,
+}
+
+impl ModelPolicy {
+    /// Create a policy choosing among `models`
+    pub fn new(models: Vec<ModelCapability>) -> Self {
+        Self {
+            models,
+            allowlist: None,
+            max_cost_cents: None,
+            fallback_chain: Vec::new(),
+            selector: ModelSelector::default(),
+            audit_capacity: DEFAULT_AUDIT_CAPACITY,
+            audit: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Only ever choose models with these IDs
+    pub fn with_allowlist<I, S>(mut self, model_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowlist = Some(model_ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Reject models whose estimated cost for a request exceeds `cents`
+    pub fn with_max_cost_cents(mut self, cents: f64) -> Self {
+        self.max_cost_cents = Some(cents);
+        self
+    }
+
+    /// Models to try, in order, when neither hints nor preferences select one
+    ///
+    /// Fallback models are still subject to the allowlist and cost ceiling.
+    pub fn with_fallback_chain<I, S>(mut self, model_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fallback_chain = model_ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Use a custom scorer for preference-based selection
+    pub fn with_selector(mut self, selector: ModelSelector) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Number of decisions kept in the audit log
+    pub fn with_audit_capacity(mut self, capacity: usize) -> Self {
+        self.audit_capacity = capacity;
+        self
+    }
+
+    /// Choose the model to answer `request` with
+    ///
+    /// Hints are honoured first, in the order the server gave them, then the
+    /// server's cost/speed/intelligence priorities, then the fallback chain.
+    pub fn choose(&self, request: &CreateMessageRequest) -> MCPResult<ModelDecision> {
+        let request_context = Self::request_context(request);
+        let mut rejected = Vec::new();
+        let mut candidates = Vec::new();
+        for model in &self.models {
+            match self.rejection(model, &request_context) {
+                Some(reason) => rejected.push(RejectedModel {
+                    model_id: model.model_id.clone(),
+                    reason,
+                }),
+                None => candidates.push(model),
+            }
+        }
+
+        let preferences = request
+            .model_preferences
+            .clone()
+            .unwrap_or_else(ModelPreferences::balanced);
+        let chosen = Self::match_hint(&candidates, &preferences)
+            .or_else(|| self.match_preferences(&candidates, &request_context, &preferences))
+            .or_else(|| self.match_fallback(&candidates));
+
+        let Some((model, reason)) = chosen else {
+            return Err(MCPError::invalid_request(format!(
+                "No available model satisfies the sampling policy ({} rejected)",
+                rejected.len()
+            )));
+        };
+
+        let decision = ModelDecision {
+            model_id: model.model_id.clone(),
+            reason,
+            estimated_cost_cents: Self::estimated_cost(model, &request_context),
+            rejected,
+            decided_at: SystemTime::now(),
+        };
+        info!(
+            "Sampling policy chose model {} ({:?})",
+            decision.model_id, decision.reason
+        );
+        self.record(decision.clone());
+        Ok(decision)
+    }
+
+    /// Most recent decisions, oldest first
+    pub fn audit_log(&self) -> Vec<ModelDecision> {
+        self.lock_audit().iter().cloned().collect()
+    }
+
+    fn rejection(&self, model: &ModelCapability, context: &RequestContext) -> Option<String> {
+        if let Some(allowlist) = &self.allowlist {
+            if !allowlist.contains(&model.model_id) {
+                return Some("not in allowlist".to_string());
+            }
+        }
+        if let Some(ceiling) = self.max_cost_cents {
+            let cost = Self::estimated_cost(model, context);
+            if cost > ceiling {
+                return Some(format!(
+                    "estimated cost {cost:.4} cents exceeds ceiling {ceiling:.4} cents"
+                ));
+            }
+        }
+        if !context
+            .required_modalities
+            .iter()
+            .all(|modality| model.modalities.contains(modality))
+        {
+            return Some("missing a required modality".to_string());
+        }
+        None
+    }
+
+    fn match_hint<'a>(
+        candidates: &[&'a ModelCapability],
+        preferences: &ModelPreferences,
+    ) -> Option<(&'a ModelCapability, SelectionReason)> {
+        preferences.hints.iter().flatten().find_map(|hint| {
+            let name = hint.name.as_deref();
+            let provider = hint.provider.as_deref();
+            if name.is_none() && provider.is_none() {
+                return None;
+            }
+            candidates
+                .iter()
+                .find(|model| {
+                    name.is_none_or(|name| model.model_id.contains(name))
+                        && provider.is_none_or(|provider| model.provider == provider)
+                })
+                .map(|model| {
+                    let hint = name.or(provider).unwrap_or_default().to_string();
+                    (*model, SelectionReason::HintMatched { hint })
+                })
+        })
+    }
+
+    fn match_preferences<'a>(
+        &self,
+        candidates: &[&'a ModelCapability],
+        request_context: &RequestContext,
+        preferences: &ModelPreferences,
+    ) -> Option<(&'a ModelCapability, SelectionReason)> {
+        let context = ModelSelectionContext {
+            available_models: candidates.iter().map(|model| (*model).clone()).collect(),
+            request_context: request_context.clone(),
+            preferences: preferences.clone(),
+            performance_history: None,
+        };
+        let result = self.selector.select_model(&context).ok()?;
+        let score = result.selection_reasoning.priority_scores.composite_score;
+        candidates
+            .iter()
+            .find(|model| model.model_id == result.selected_model.model_id)
+            .map(|model| (*model, SelectionReason::Preferences { score }))
+    }
+
+    fn match_fallback<'a>(
+        &self,
+        candidates: &[&'a ModelCapability],
+    ) -> Option<(&'a ModelCapability, SelectionReason)> {
+        self.fallback_chain
+            .iter()
+            .enumerate()
+            .find_map(|(position, model_id)| {
+                candidates
+                    .iter()
+                    .find(|model| &model.model_id == model_id)
+                    .map(|model| (*model, SelectionReason::Fallback { position }))
+            })
+    }
+
+    fn request_context(request: &CreateMessageRequest) -> RequestContext {
+        let mut required_modalities = vec![Modality::Text];
+        if request.requires_image_modality() {
+            required_modalities.push(Modality::Image);
+        }
+        RequestContext {
+            estimated_input_tokens: request.estimate_input_tokens().unwrap_or(0),
+            estimated_output_tokens: request.max_tokens.unwrap_or(DEFAULT_OUTPUT_TOKENS),
+            required_modalities,
+            requires_function_calling: false,
+            prefers_streaming: false,
+            complexity_level: 0.5,
+            time_sensitivity: 0.5,
+            quality_requirements: 0.5,
+        }
+    }
+
+    fn estimated_cost(model: &ModelCapability, context: &RequestContext) -> f64 {
+        f64::from(context.estimated_input_tokens) / 1000.0 * model.cost_per_1k_input_tokens
+            + f64::from(context.estimated_output_tokens) / 1000.0 * model.cost_per_1k_output_tokens
+    }
+
+    fn record(&self, decision: ModelDecision) {
+        if self.audit_capacity == 0 {
+            return;
+        }
+        let mut audit = self.lock_audit();
+        while audit.len() >= self.audit_capacity {
+            audit.pop_front();
+        }
+        audit.push_back(decision);
+    }
+
+    fn lock_audit(&self) -> MutexGuard<'_, VecDeque<ModelDecision>> {
+        self.audit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::types::sampling::ModelHint;
+
+    fn model(id: &str, provider: &str, cost: f64, intelligence: f64) -> ModelCapability {
+        ModelCapability {
+            model_id: id.to_string(),
+            provider: provider.to_string(),
+            display_name: id.to_string(),
+            version: None,
+            cost_per_1k_input_tokens: cost,
+            cost_per_1k_output_tokens: cost,
+            speed_score: 5.0,
+            intelligence_score: intelligence,
+            max_context_length: 100_000,
+            modalities: vec![Modality::Text],
+            supports_function_calling: false,
+            supports_streaming: true,
+            metadata: None,
+        }
+    }
+
+    fn models() -> Vec<ModelCapability> {
+        vec![
+            model("claude-3-haiku", "anthropic", 0.1, 5.0),
+            model("claude-3-opus", "anthropic", 50.0, 9.0),
+            model("gpt-4o-mini", "openai", 0.2, 6.0),
+        ]
+    }
+
+    fn request_with_hint(name: &str) -> CreateMessageRequest {
+        CreateMessageRequest {
+            model_preferences: Some(ModelPreferences {
+                hints: Some(vec![ModelHint {
+                    name: Some(name.to_string()),
+                    provider: None,
+                }]),
+                ..ModelPreferences::balanced()
+            }),
+            max_tokens: Some(1000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_hint_is_honoured_within_policy_limits() {
+        let policy = ModelPolicy::new(models()).with_max_cost_cents(10.0);
+
+        let decision = policy.choose(&request_with_hint("haiku")).unwrap();
+        assert_eq!(decision.model_id, "claude-3-haiku");
+        assert!(matches!(
+            decision.reason,
+            SelectionReason::HintMatched { .. }
+        ));
+
+        // Opus matches the hint but is over the cost ceiling
+        let decision = policy.choose(&request_with_hint("opus")).unwrap();
+        assert_ne!(decision.model_id, "claude-3-opus");
+        assert!(
+            decision
+                .rejected
+                .iter()
+                .any(|rejected| rejected.model_id == "claude-3-opus")
+        );
+        assert_eq!(policy.audit_log().len(), 2);
+    }
+
+    #[test]
+    fn test_fallback_chain_and_allowlist() {
+        let policy = ModelPolicy::new(models())
+            .with_allowlist(["gpt-4o-mini"])
+            .with_selector(ModelSelector::new(
+                ultrafast_mcp_core::types::sampling::ScoringStrategy::WeightedSum,
+                1.1,
+                3,
+            ))
+            .with_fallback_chain(["claude-3-haiku", "gpt-4o-mini"]);
+
+        let decision = policy.choose(&request_with_hint("claude")).unwrap();
+        assert_eq!(decision.model_id, "gpt-4o-mini");
+        assert_eq!(decision.reason, SelectionReason::Fallback { position: 1 });
+        assert_eq!(decision.rejected.len(), 2);
+
+        let empty = ModelPolicy::new(models()).with_allowlist(Vec::<String>::new());
+        assert!(empty.choose(&CreateMessageRequest::default()).is_err());
+    }
+}
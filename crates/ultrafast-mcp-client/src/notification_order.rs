@@ -0,0 +1,127 @@
+//! Detection of lost and reordered server notifications
+//!
+//! Servers built with notification sequencing stamp every notification with
+//! a per-session sequence number in `_meta["ultrafast/seq"]`. The client
+//! checks each number against the next one it expects, counts anomalies in
+//! [`NotificationOrderMetrics`] and reports them to a
+//! [`ClientNotificationOrderHandler`]. Notifications without a sequence
+//! number are not tracked.
+
+/// A notification that did not arrive in sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceAnomaly {
+    /// Notifications `expected..received` were skipped
+    Gap {
+        method: String,
+        expected: u64,
+        received: u64,
+    },
+    /// A notification arrived after one with a higher sequence number, or
+    /// was delivered twice
+    OutOfOrder {
+        method: String,
+        received: u64,
+        latest: u64,
+    },
+}
+
+/// Counters for notification sequence checking
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationOrderMetrics {
+    /// Notifications that carried a sequence number
+    pub sequenced_notifications: u64,
+    /// Gaps detected in the sequence
+    pub gaps: u64,
+    /// Sequence numbers skipped over by gaps
+    pub missing_notifications: u64,
+    /// Notifications that arrived behind a later one
+    pub out_of_order: u64,
+}
+
+/// Client-side handler for notifications that arrive out of sequence
+///
+/// Without a handler anomalies are logged and counted in
+/// [`NotificationOrderMetrics`].
+#[async_trait::async_trait]
+pub trait ClientNotificationOrderHandler: Send + Sync {
+    async fn handle_sequence_anomaly(&self, anomaly: SequenceAnomaly);
+}
+
+/// Tracks the sequence numbers of one connection
+#[derive(Debug, Default)]
+pub(crate) struct NotificationSequenceTracker {
+    latest: u64,
+    metrics: NotificationOrderMetrics,
+}
+
+impl NotificationSequenceTracker {
+    /// Record a received sequence number, returning the anomaly if it was
+    /// not the next one expected
+    pub(crate) fn observe(&mut self, method: &str, sequence: u64) -> Option<SequenceAnomaly> {
+        self.metrics.sequenced_notifications += 1;
+        let expected = self.latest + 1;
+        if sequence == expected {
+            self.latest = sequence;
+            return None;
+        }
+        if sequence > expected {
+            self.metrics.gaps += 1;
+            self.metrics.missing_notifications += sequence - expected;
+            self.latest = sequence;
+            return Some(SequenceAnomaly::Gap {
+                method: method.to_string(),
+                expected,
+                received: sequence,
+            });
+        }
+        self.metrics.out_of_order += 1;
+        Some(SequenceAnomaly::OutOfOrder {
+            method: method.to_string(),
+            received: sequence,
+            latest: self.latest,
+        })
+    }
+
+    pub(crate) fn metrics(&self) -> NotificationOrderMetrics {
+        self.metrics.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaps_and_reordering_are_detected() {
+        let mut tracker = NotificationSequenceTracker::default();
+        assert_eq!(tracker.observe("a", 1), None);
+        assert_eq!(tracker.observe("a", 2), None);
+        assert_eq!(
+            tracker.observe("b", 5),
+            Some(SequenceAnomaly::Gap {
+                method: "b".to_string(),
+                expected: 3,
+                received: 5,
+            })
+        );
+        assert_eq!(
+            tracker.observe("c", 4),
+            Some(SequenceAnomaly::OutOfOrder {
+                method: "c".to_string(),
+                received: 4,
+                latest: 5,
+            })
+        );
+        assert_eq!(tracker.observe("d", 6), None);
+
+        assert_eq!(
+            tracker.metrics(),
+            NotificationOrderMetrics {
+                sequenced_notifications: 5,
+                gaps: 1,
+                missing_notifications: 2,
+                out_of_order: 1,
+            }
+        );
+    }
+}
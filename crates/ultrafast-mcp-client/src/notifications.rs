@@ -0,0 +1,181 @@
+//! Typed notifications from the server
+//!
+//! Every notification the client receives is decoded into a
+//! [`ServerNotification`] and published to the receivers of
+//! [`UltraFastClient::subscribe_notifications`]. The `on_*` methods of the
+//! client, such as [`UltraFastClient::on_tools_changed`], run a callback for
+//! one kind of notification instead, on a task of their own.
+//!
+//! Chunks of streamed tool results and resource reads go to their streams
+//! and are not published here.
+//!
+//! [`UltraFastClient::subscribe_notifications`]: crate::UltraFastClient::subscribe_notifications
+//! [`UltraFastClient::on_tools_changed`]: crate::UltraFastClient::on_tools_changed
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::warn;
+use ultrafast_mcp_core::{
+    protocol::jsonrpc::JsonRpcRequest,
+    types::{
+        notifications::{CancelledNotification, LoggingMessageNotification, ProgressNotification},
+        resources::ResourceUpdatedNotification,
+    },
+};
+
+/// Notifications a subscriber may fall behind by before missing the oldest
+pub(crate) const NOTIFICATION_BUFFER: usize = 256;
+
+/// A notification from the server
+#[derive(Debug, Clone)]
+pub enum ServerNotification {
+    /// `notifications/tools/listChanged`
+    ToolsListChanged,
+    /// `notifications/resources/listChanged`
+    ResourcesListChanged,
+    /// `notifications/prompts/listChanged`
+    PromptsListChanged,
+    /// `notifications/resources/updated`
+    ResourceUpdated(ResourceUpdatedNotification),
+    /// `notifications/message`
+    LogMessage(LoggingMessageNotification),
+    /// `notifications/progress`
+    Progress(ProgressNotification),
+    /// `notifications/cancelled`
+    Cancelled(CancelledNotification),
+    /// Any other notification, or one whose params did not decode
+    Other {
+        method: String,
+        params: Option<Value>,
+    },
+}
+
+impl ServerNotification {
+    /// Decode a notification received from the server
+    pub fn from_notification(notification: &JsonRpcRequest) -> Self {
+        let params = notification.params.as_ref();
+        let decoded = match notification.method.as_str() {
+            "notifications/tools/listChanged" => Some(Self::ToolsListChanged),
+            "notifications/resources/listChanged" => Some(Self::ResourcesListChanged),
+            "notifications/prompts/listChanged" => Some(Self::PromptsListChanged),
+            "notifications/resources/updated" => decode(params).map(Self::ResourceUpdated),
+            "notifications/message" => decode(params).map(Self::LogMessage),
+            "notifications/progress" => decode(params).map(Self::Progress),
+            "notifications/cancelled" => decode(params).map(Self::Cancelled),
+            _ => None,
+        };
+        decoded.unwrap_or_else(|| Self::Other {
+            method: notification.method.clone(),
+            params: notification.params.clone(),
+        })
+    }
+
+    /// The JSON-RPC method of the notification
+    pub fn method(&self) -> &str {
+        match self {
+            Self::ToolsListChanged => "notifications/tools/listChanged",
+            Self::ResourcesListChanged => "notifications/resources/listChanged",
+            Self::PromptsListChanged => "notifications/prompts/listChanged",
+            Self::ResourceUpdated(_) => "notifications/resources/updated",
+            Self::LogMessage(_) => "notifications/message",
+            Self::Progress(_) => "notifications/progress",
+            Self::Cancelled(_) => "notifications/cancelled",
+            Self::Other { method, .. } => method,
+        }
+    }
+}
+
+fn decode<T: DeserializeOwned>(params: Option<&Value>) -> Option<T> {
+    serde_json::from_value(params.cloned().unwrap_or_default()).ok()
+}
+
+/// A callback registered with one of the client's `on_*` methods
+///
+/// The callback runs until the client is dropped or the subscription is
+/// [`unsubscribe`](Self::unsubscribe)d; dropping the subscription leaves it
+/// running.
+#[derive(Debug)]
+pub struct NotificationSubscription {
+    task: JoinHandle<()>,
+}
+
+impl NotificationSubscription {
+    /// Run `handler` on every notification `receiver` gets
+    pub(crate) fn spawn(
+        mut receiver: broadcast::Receiver<ServerNotification>,
+        mut handler: impl FnMut(ServerNotification) + Send + 'static,
+    ) -> Self {
+        let task = tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(notification) => handler(notification),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Notification callback fell behind, {} missed", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        Self { task }
+    }
+
+    /// Stop running the callback
+    pub fn unsubscribe(self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(method: &str, params: Option<Value>) -> ServerNotification {
+        ServerNotification::from_notification(&JsonRpcRequest::notification(
+            method.to_string(),
+            params,
+        ))
+    }
+
+    #[test]
+    fn test_notifications_are_decoded_by_method() {
+        assert!(matches!(
+            notification("notifications/tools/listChanged", None),
+            ServerNotification::ToolsListChanged
+        ));
+        let ServerNotification::Progress(progress) = notification(
+            "notifications/progress",
+            Some(serde_json::json!({ "progressToken": "call-1", "progress": 0.5 })),
+        ) else {
+            panic!("expected a progress notification");
+        };
+        assert_eq!(progress.progress_token, "call-1");
+        assert_eq!(progress.progress, 0.5);
+
+        let log = notification(
+            "notifications/message",
+            Some(serde_json::json!({ "level": "warning", "data": "disk almost full" })),
+        );
+        assert_eq!(log.method(), "notifications/message");
+        assert!(matches!(log, ServerNotification::LogMessage(_)));
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_notifications_are_kept_as_other() {
+        let custom = notification("notifications/custom", Some(serde_json::json!({ "a": 1 })));
+        assert_eq!(custom.method(), "notifications/custom");
+        assert!(matches!(
+            custom,
+            ServerNotification::Other {
+                params: Some(_),
+                ..
+            }
+        ));
+
+        // A progress notification without its required fields
+        let malformed = notification("notifications/progress", None);
+        assert_eq!(malformed.method(), "notifications/progress");
+        assert!(matches!(malformed, ServerNotification::Other { .. }));
+    }
+}
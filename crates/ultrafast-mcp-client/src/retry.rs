@@ -0,0 +1,144 @@
+//! Retrying requests that are safe to repeat
+//!
+//! With a [`RetryPolicy`] set through
+//! [`UltraFastClient::with_retry_policy`](crate::UltraFastClient::with_retry_policy),
+//! a request that fails with an error of a retryable
+//! [`ErrorCategory`] — a dropped connection, a timeout, a rate limit — is sent
+//! again after a backoff instead of failing right away. Only requests that
+//! change nothing on the server are repeated: the methods in
+//! [`IDEMPOTENT_METHODS`], and `tools/call` for tools that `tools/list`
+//! annotated with `idempotentHint` or `readOnlyHint`. Any other tool call
+//! fails on the first error, since running it twice could repeat its effect.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use ultrafast_mcp_core::error::{ErrorCategory, MCPError};
+
+/// Methods that can be sent again without changing their outcome
+pub const IDEMPOTENT_METHODS: &[&str] = &[
+    "ping",
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "prompts/list",
+    "prompts/get",
+    "completion/complete",
+];
+
+/// When and how often a failed request is sent again
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub backoff_multiplier: f64,
+    /// Error categories worth another attempt
+    pub retry_on: HashSet<ErrorCategory>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            backoff_multiplier: 2.0,
+            retry_on: HashSet::from([
+                ErrorCategory::Transport,
+                ErrorCategory::Timeout,
+                ErrorCategory::RateLimited,
+            ]),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Wait `initial_delay` before the first retry, growing by
+    /// `backoff_multiplier` per retry up to `max_delay`
+    pub fn with_backoff(
+        mut self,
+        initial_delay: Duration,
+        max_delay: Duration,
+        backoff_multiplier: f64,
+    ) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay;
+        self.backoff_multiplier = backoff_multiplier;
+        self
+    }
+
+    /// Retry errors of exactly `categories`
+    pub fn retry_on(mut self, categories: impl IntoIterator<Item = ErrorCategory>) -> Self {
+        self.retry_on = categories.into_iter().collect();
+        self
+    }
+
+    /// Whether a request that failed with `error` on attempt number
+    /// `attempt`, counting from one, is sent again
+    pub fn should_retry(&self, error: &MCPError, attempt: u32) -> bool {
+        attempt < self.max_attempts && self.retry_on.contains(&error.category())
+    }
+
+    /// Delay before retry number `retry`, counting from zero; at least the
+    /// delay the error asks for
+    pub fn retry_delay(&self, retry: u32, error: &MCPError) -> Duration {
+        let backoff = self
+            .initial_delay
+            .mul_f64(self.backoff_multiplier.max(1.0).powi(retry as i32))
+            .min(self.max_delay);
+        error
+            .retry_after()
+            .map_or(backoff, |after| after.max(backoff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::error::RateLimitError;
+
+    #[test]
+    fn test_only_listed_categories_are_retried() {
+        let policy = RetryPolicy::new().with_max_attempts(2);
+        let dropped = MCPError::transport_error("connection reset".to_string());
+        assert!(policy.should_retry(&dropped, 1));
+        assert!(!policy.should_retry(&dropped, 2));
+        assert!(!policy.should_retry(&MCPError::invalid_params("bad".to_string()), 1));
+
+        let policy = policy.retry_on([ErrorCategory::Timeout]);
+        assert!(!policy.should_retry(&dropped, 1));
+        assert!(policy.should_retry(&MCPError::request_timeout(), 1));
+    }
+
+    #[test]
+    fn test_backoff_grows_up_to_the_limit() {
+        let policy = RetryPolicy::new().with_backoff(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            2.0,
+        );
+        let error = MCPError::request_timeout();
+        let delays: Vec<_> = (0..3)
+            .map(|retry| policy.retry_delay(retry, &error))
+            .collect();
+        assert_eq!(delays, [100, 200, 300].map(Duration::from_millis).to_vec());
+
+        let limited = MCPError::RateLimit(RateLimitError::TooManyRequests {
+            retry_after: 1000,
+            limit: Some(10),
+        });
+        assert_eq!(policy.retry_delay(0, &limited), Duration::from_secs(1));
+    }
+}
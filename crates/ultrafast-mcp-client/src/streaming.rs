@@ -0,0 +1,112 @@
+//! Tool results streamed in chunks while the tool runs
+//!
+//! A server tool calling `Context::stream_content` sends pieces of its
+//! result as [`TOOL_CONTENT_CHUNK_METHOD`] notifications, tied to the call by
+//! the progress token the client sent with it.
+//! [`UltraFastClient::call_tool_streaming`] sends such a token and yields the
+//! chunks as a [`Stream`] that ends once the call completes. The final
+//! [`ToolResult`], holding what the tool returned besides the chunks, is then
+//! available from [`ToolCallStream::result`].
+//!
+//! [`TOOL_CONTENT_CHUNK_METHOD`]: ultrafast_mcp_core::types::notifications::TOOL_CONTENT_CHUNK_METHOD
+//! [`UltraFastClient::call_tool_streaming`]: crate::UltraFastClient::call_tool_streaming
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+use ultrafast_mcp_core::error::{MCPError, MCPResult};
+use ultrafast_mcp_core::types::tools::{ToolContent, ToolResult};
+
+/// The chunks of a tool call in progress, followed by its result
+///
+/// Returned by [`UltraFastClient::call_tool_streaming`]. The call is only
+/// sent once the stream is polled or [`result`](Self::result) awaited.
+///
+/// [`UltraFastClient::call_tool_streaming`]: crate::UltraFastClient::call_tool_streaming
+pub struct ToolCallStream<'a> {
+    chunks: mpsc::UnboundedReceiver<ToolContent>,
+    call: Option<BoxFuture<'a, MCPResult<ToolResult>>>,
+    result: Option<MCPResult<ToolResult>>,
+}
+
+impl<'a> ToolCallStream<'a> {
+    pub(crate) fn new(
+        chunks: mpsc::UnboundedReceiver<ToolContent>,
+        call: BoxFuture<'a, MCPResult<ToolResult>>,
+    ) -> Self {
+        Self {
+            chunks,
+            call: Some(call),
+            result: None,
+        }
+    }
+
+    /// Wait for the call to complete, dropping the chunks not read yet
+    pub async fn result(mut self) -> MCPResult<ToolResult> {
+        match self.call.take() {
+            Some(call) => call.await,
+            None => self.result.take().unwrap_or_else(|| {
+                Err(MCPError::internal_error(
+                    "Tool call result was already taken".to_string(),
+                ))
+            }),
+        }
+    }
+}
+
+impl Stream for ToolCallStream<'_> {
+    type Item = ToolContent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ToolContent>> {
+        let this = self.get_mut();
+        if let Poll::Ready(Some(chunk)) = this.chunks.poll_recv(cx) {
+            return Poll::Ready(Some(chunk));
+        }
+        if let Some(call) = &mut this.call {
+            let result = std::task::ready!(call.as_mut().poll(cx));
+            this.call = None;
+            this.result = Some(result);
+        }
+        // Chunks are delivered before the response that completes the call,
+        // so any left are queued already
+        Poll::Ready(this.chunks.try_recv().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn text(text: &str) -> ToolContent {
+        ToolContent::text(text.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_chunks_then_result() {
+        let (sender, chunks) = mpsc::unbounded_channel();
+        let call = Box::pin(async move {
+            sender.send(text("a")).unwrap();
+            tokio::task::yield_now().await;
+            sender.send(text("b")).unwrap();
+            Ok(ToolResult {
+                content: vec![text("done")],
+                is_error: None,
+                structured_content: None,
+                progress_summary: None,
+            })
+        });
+        let mut stream = ToolCallStream::new(chunks, call);
+
+        let mut received = Vec::new();
+        while let Some(ToolContent::Text { text }) = stream.next().await {
+            received.push(text);
+        }
+        assert_eq!(received, ["a", "b"]);
+        let result = stream.result().await.unwrap();
+        assert!(matches!(&result.content[0], ToolContent::Text { text } if text == "done"));
+    }
+}
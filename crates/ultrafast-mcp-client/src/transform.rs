@@ -0,0 +1,294 @@
+//! Post-processing of tool results on the client
+//!
+//! Hosts tend to clean up every tool result the same way before using it:
+//! normalizing markdown, checking images, cutting long output down to fit a
+//! context window. A [`ToolResultTransform`] registered with
+//! [`UltraFastClient::with_result_transform`] does that once for every
+//! result; one registered with
+//! [`UltraFastClient::with_tool_result_transform`] only for the results of
+//! one tool. Transforms run in the order they were registered, after the
+//! output validation, on the results of [`UltraFastClient::call_tool`] and
+//! the calls built on it.
+//!
+//! [`NormalizeMarkdown`], [`DecodeImages`] and [`TruncateText`] cover the
+//! common cases; any `Fn(&str, ToolResult) -> MCPResult<ToolResult>` is a
+//! transform too.
+//!
+//! [`UltraFastClient::with_result_transform`]: crate::UltraFastClient::with_result_transform
+//! [`UltraFastClient::with_tool_result_transform`]: crate::UltraFastClient::with_tool_result_transform
+//! [`UltraFastClient::call_tool`]: crate::UltraFastClient::call_tool
+
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ultrafast_mcp_core::{
+    content_type::sniff_mime_type,
+    error::MCPResult,
+    types::tools::{ToolContent, ToolResult},
+};
+
+/// Rewrites the result of a tool call before it is returned to the caller
+pub trait ToolResultTransform: Send + Sync {
+    /// Transform the result of a call to `tool`
+    ///
+    /// An error fails the call with it.
+    fn transform(&self, tool: &str, result: ToolResult) -> MCPResult<ToolResult>;
+}
+
+impl<F> ToolResultTransform for F
+where
+    F: Fn(&str, ToolResult) -> MCPResult<ToolResult> + Send + Sync,
+{
+    fn transform(&self, tool: &str, result: ToolResult) -> MCPResult<ToolResult> {
+        self(tool, result)
+    }
+}
+
+/// The transforms of a client, with the tool each is limited to
+#[derive(Clone, Default)]
+pub(crate) struct ResultTransforms {
+    transforms: Vec<(Option<String>, Arc<dyn ToolResultTransform>)>,
+}
+
+impl ResultTransforms {
+    pub(crate) fn push(&mut self, tool: Option<String>, transform: Arc<dyn ToolResultTransform>) {
+        self.transforms.push((tool, transform));
+    }
+
+    pub(crate) fn apply(&self, tool: &str, result: ToolResult) -> MCPResult<ToolResult> {
+        self.transforms
+            .iter()
+            .filter(|(only, _)| only.as_deref().is_none_or(|only| only == tool))
+            .try_fold(result, |result, (_, transform)| {
+                transform.transform(tool, result)
+            })
+    }
+}
+
+/// Apply `rewrite` to every text content of `result`
+fn map_text(mut result: ToolResult, rewrite: impl Fn(&str) -> String) -> ToolResult {
+    for content in &mut result.content {
+        if let ToolContent::Text { text } = content {
+            *text = rewrite(text);
+        }
+    }
+    result
+}
+
+/// Normalizes the markdown of text content
+///
+/// Line endings become `\n`, trailing whitespace is removed from every line
+/// and runs of blank lines are collapsed into one, as are blank lines at
+/// the start and end.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeMarkdown;
+
+impl NormalizeMarkdown {
+    fn normalize(text: &str) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        let mut blank_lines = 0;
+        for line in text.lines().map(str::trim_end) {
+            if line.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            if !normalized.is_empty() {
+                normalized.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+            }
+            normalized.push_str(line);
+            blank_lines = 0;
+        }
+        normalized
+    }
+}
+
+impl ToolResultTransform for NormalizeMarkdown {
+    fn transform(&self, _tool: &str, result: ToolResult) -> MCPResult<ToolResult> {
+        Ok(map_text(result, Self::normalize))
+    }
+}
+
+/// The image type the bytes start with, if a known one
+fn sniff_image(bytes: &[u8]) -> Option<&'static str> {
+    sniff_mime_type(bytes).filter(|mime_type| mime_type.starts_with("image/"))
+}
+
+/// Checks that image content decodes, and labels it with its actual type
+///
+/// An image whose data is not valid base64 is replaced by a text note
+/// saying so. One whose bytes are a PNG, JPEG, GIF or WebP image gets the
+/// matching MIME type, whatever the server declared.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeImages;
+
+impl ToolResultTransform for DecodeImages {
+    fn transform(&self, tool: &str, mut result: ToolResult) -> MCPResult<ToolResult> {
+        for content in &mut result.content {
+            let ToolContent::Image { data, mime_type } = content else {
+                continue;
+            };
+            match STANDARD.decode(data.as_bytes()) {
+                Ok(bytes) => {
+                    if let Some(sniffed) = sniff_image(&bytes) {
+                        *mime_type = sniffed.to_string();
+                    }
+                }
+                Err(e) => {
+                    *content = ToolContent::text(format!(
+                        "[{mime_type} image from {tool} could not be decoded: {e}]"
+                    ));
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Cuts the text content of a result down to a number of characters
+///
+/// Text content is kept in order until `max_chars` characters are used up;
+/// the text that crosses the limit is cut and marked, and any text after it
+/// dropped. Other content is kept.
+#[derive(Debug, Clone, Copy)]
+pub struct TruncateText {
+    max_chars: usize,
+}
+
+impl TruncateText {
+    pub fn new(max_chars: usize) -> Self {
+        Self { max_chars }
+    }
+}
+
+impl ToolResultTransform for TruncateText {
+    fn transform(&self, _tool: &str, mut result: ToolResult) -> MCPResult<ToolResult> {
+        let mut remaining = self.max_chars;
+        let mut truncated = 0;
+        result.content.retain_mut(|content| {
+            let ToolContent::Text { text } = content else {
+                return true;
+            };
+            let length = text.chars().count();
+            if length <= remaining {
+                remaining -= length;
+                return true;
+            }
+            truncated += length - remaining;
+            if remaining == 0 {
+                return false;
+            }
+            let cut = text
+                .char_indices()
+                .nth(remaining)
+                .map_or(text.len(), |(index, _)| index);
+            text.truncate(cut);
+            remaining = 0;
+            true
+        });
+        if truncated > 0 {
+            result.content.push(ToolContent::text(format!(
+                "[truncated {truncated} characters]"
+            )));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(content: Vec<ToolContent>) -> ToolResult {
+        ToolResult {
+            content,
+            is_error: None,
+            structured_content: None,
+            progress_summary: None,
+        }
+    }
+
+    fn texts(result: &ToolResult) -> Vec<&str> {
+        result
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                ToolContent::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_markdown() {
+        let normalized = NormalizeMarkdown
+            .transform(
+                "docs",
+                result(vec![ToolContent::text(
+                    "\r\n# Title  \r\n\r\n\r\n\r\nBody\t\r\n- item\r\n\r\n".to_string(),
+                )]),
+            )
+            .unwrap();
+        assert_eq!(texts(&normalized), ["# Title\n\nBody\n- item"]);
+    }
+
+    #[test]
+    fn test_decode_images() {
+        let png = STANDARD.encode([0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+        let decoded = DecodeImages
+            .transform(
+                "camera",
+                result(vec![
+                    ToolContent::image(png.clone(), "image/jpeg".to_string()),
+                    ToolContent::image("not base64!".to_string(), "image/png".to_string()),
+                ]),
+            )
+            .unwrap();
+        assert!(matches!(
+            &decoded.content[0],
+            ToolContent::Image { data, mime_type } if *data == png && mime_type == "image/png"
+        ));
+        assert!(texts(&decoded)[0].starts_with("[image/png image from camera could not"));
+    }
+
+    #[test]
+    fn test_truncate_text_across_content() {
+        let truncated = TruncateText::new(5)
+            .transform(
+                "search",
+                result(vec![
+                    ToolContent::text("abc".to_string()),
+                    ToolContent::image("AAAA".to_string(), "image/png".to_string()),
+                    ToolContent::text("déjà vu".to_string()),
+                    ToolContent::text("dropped".to_string()),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            texts(&truncated),
+            ["abc", "dé", "[truncated 12 characters]"]
+        );
+        assert_eq!(truncated.content.len(), 4);
+
+        let untouched = TruncateText::new(3)
+            .transform("search", result(vec![ToolContent::text("abc".to_string())]))
+            .unwrap();
+        assert_eq!(texts(&untouched), ["abc"]);
+    }
+
+    #[test]
+    fn test_transforms_run_in_order_for_their_tools() {
+        let mut transforms = ResultTransforms::default();
+        transforms.push(None, Arc::new(NormalizeMarkdown));
+        transforms.push(
+            Some("shout".to_string()),
+            Arc::new(|_: &str, result: ToolResult| Ok(map_text(result, str::to_uppercase))),
+        );
+        transforms.push(None, Arc::new(TruncateText::new(4)));
+
+        let input = || result(vec![ToolContent::text("hi  \n\n\nthere".to_string())]);
+        let shouted = transforms.apply("shout", input()).unwrap();
+        assert_eq!(texts(&shouted), ["HI\n\n", "[truncated 5 characters]"]);
+        let other = transforms.apply("whisper", input()).unwrap();
+        assert_eq!(texts(&other), ["hi\n\n", "[truncated 5 characters]"]);
+    }
+}
@@ -142,7 +142,9 @@ impl TimeoutConfig {
             "request" => self.request_timeout,
             "response" => self.response_timeout,
             "tool_execution" | "tools/call" => self.tool_execution_timeout,
-            "resource_read" | "resources/read" => self.resource_read_timeout,
+            "resource_read" | "resources/read" | "resources/readStream" => {
+                self.resource_read_timeout
+            }
             "prompt_generation" | "prompts/get" => self.prompt_generation_timeout,
             "sampling" | "sampling/createMessage" => self.sampling_timeout,
             "completion" | "completion/complete" => self.completion_timeout,
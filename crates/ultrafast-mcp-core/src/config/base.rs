@@ -186,6 +186,14 @@ pub struct SecurityConfig {
     pub ca_cert_path: Option<String>,
     pub allow_insecure: bool,
     pub allowed_origins: Vec<String>,
+    /// Environment variable holding the secret that signs pagination
+    /// cursors, see `CursorCodec::from_config` (requires the
+    /// `cursor-signing` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor_key_env: Option<String>,
+    /// Also encrypt signed cursors
+    #[serde(default)]
+    pub encrypt_cursors: bool,
 }
 
 impl Default for SecurityConfig {
@@ -197,6 +205,8 @@ impl Default for SecurityConfig {
             ca_cert_path: None,
             allow_insecure: true, // Default to true for development
             allowed_origins: vec!["*".to_string()], // Default to allow all for development
+            cursor_key_env: None,
+            encrypt_cursors: false,
         }
     }
 }
@@ -219,6 +229,13 @@ impl BaseConfig for SecurityConfig {
             }
         }
 
+        if self.encrypt_cursors && self.cursor_key_env.is_none() {
+            return Err(ValidationError::RequiredField {
+                field: "cursor_key_env".to_string(),
+            }
+            .into());
+        }
+
         Ok(())
     }
 
@@ -328,6 +345,13 @@ mod tests {
         assert!(!config.enable_tls);
         assert!(config.allow_insecure);
         assert_eq!(config.allowed_origins, vec!["*"]);
+        assert!(config.validate().is_ok());
+
+        let config = SecurityConfig {
+            encrypt_cursors: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
     }
 
     #[test]
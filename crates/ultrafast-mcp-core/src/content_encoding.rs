@@ -0,0 +1,293 @@
+//! Compression of large content items
+//!
+//! Multi-megabyte resources and tool outputs are mostly text that compresses
+//! well, and stdio has no transport-level compression to fall back on. So a
+//! client can advertise the [`CONTENT_ENCODING_CAPABILITY`] experimental
+//! capability, and the server then sends the `text` or `blob` of large items
+//! in `tools/call` and `resources/read` results gzip-compressed and base64
+//! encoded, marking each one with `_meta["ultrafast/contentEncoding"]`:
+//!
+//! ```json
+//! {
+//!   "uri": "file:///var/log/app.log",
+//!   "text": "H4sIAAAAAAAA/+y9...",
+//!   "_meta": { "ultrafast/contentEncoding": "gzip+base64" }
+//! }
+//! ```
+//!
+//! The client restores such items with [`decode_content`] before anything
+//! else sees them. Items are only sent compressed when that makes them
+//! smaller, and clients that do not advertise the capability always get them
+//! as they are.
+//!
+//! Requires the `content-encoding` feature.
+//!
+//! ```rust
+//! use serde_json::json;
+//! use ultrafast_mcp_core::content_encoding::{decode_content, encode_content};
+//!
+//! let text = "the same line, over and over\n".repeat(1000);
+//! let mut result = json!({"content": [{"type": "text", "text": text}]});
+//! assert_eq!(encode_content(&mut result, 1024), 1);
+//! assert!(result["content"][0]["text"].as_str().unwrap().len() < text.len());
+//!
+//! decode_content(&mut result).unwrap();
+//! assert_eq!(result["content"][0]["text"], text);
+//! ```
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde_json::{Map, Value, json};
+
+use crate::error::{MCPError, MCPResult};
+use crate::protocol::capabilities::ClientCapabilities;
+
+/// Experimental capability a client advertises to receive compressed items
+pub const CONTENT_ENCODING_CAPABILITY: &str = "ultrafast/contentEncoding";
+
+/// `_meta` key marking an item whose `text` or `blob` is encoded
+pub const CONTENT_ENCODING_META_KEY: &str = "ultrafast/contentEncoding";
+
+/// gzip compression, then base64 encoding
+pub const GZIP_BASE64: &str = "gzip+base64";
+
+/// Size from which items are compressed, in bytes of `text` or `blob`
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 64 * 1024;
+
+/// Largest item [`decode_content`] restores, so a small compressed item
+/// cannot make the client allocate without bound
+pub const MAX_DECODED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Advertise that the client decodes [`GZIP_BASE64`] items
+pub fn advertise(capabilities: &mut ClientCapabilities) {
+    capabilities
+        .experimental
+        .get_or_insert_with(HashMap::new)
+        .insert(
+            CONTENT_ENCODING_CAPABILITY.to_string(),
+            json!({ "encodings": [GZIP_BASE64] }),
+        );
+}
+
+/// Whether a client advertised that it decodes [`GZIP_BASE64`] items
+pub fn accepts_gzip(capabilities: &ClientCapabilities) -> bool {
+    capabilities
+        .experimental
+        .as_ref()
+        .and_then(|experimental| experimental.get(CONTENT_ENCODING_CAPABILITY))
+        .and_then(|capability| capability.get("encodings"))
+        .and_then(Value::as_array)
+        .is_some_and(|encodings| encodings.iter().any(|e| e == GZIP_BASE64))
+}
+
+/// The content items of a `tools/call` or `resources/read` result, with the
+/// resources embedded in them
+fn items(result: &mut Value) -> impl Iterator<Item = &mut Map<String, Value>> {
+    let Some(result) = result.as_object_mut() else {
+        return Vec::new().into_iter();
+    };
+    let mut items = Vec::new();
+    for (key, value) in result.iter_mut() {
+        if key != "content" && key != "contents" {
+            continue;
+        }
+        let Some(list) = value.as_array_mut() else {
+            continue;
+        };
+        for item in list.iter_mut().filter_map(Value::as_object_mut) {
+            if item.get("resource").is_some_and(Value::is_object) {
+                if let Some(resource) = item.get_mut("resource").and_then(Value::as_object_mut) {
+                    items.push(resource);
+                }
+            } else {
+                items.push(item);
+            }
+        }
+    }
+    items.into_iter()
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|()| encoder.finish())
+        .expect("writing to a Vec cannot fail")
+}
+
+/// Decompress gzip `data`, failing if it is corrupt or inflates to more than
+/// `max_size` bytes
+fn decompress(data: &[u8], max_size: usize) -> MCPResult<Vec<u8>> {
+    let mut out = Vec::new();
+    GzDecoder::new(data)
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| MCPError::invalid_response(format!("Invalid gzip data: {e}")))?;
+    if out.len() > max_size {
+        return Err(MCPError::invalid_response(format!(
+            "Decompressed content exceeds {max_size} bytes"
+        )));
+    }
+    Ok(out)
+}
+
+fn encoding(item: &Map<String, Value>) -> Option<&str> {
+    item.get("_meta")?.get(CONTENT_ENCODING_META_KEY)?.as_str()
+}
+
+/// Compress the items of `result` whose `text` or `blob` is at least
+/// `threshold` bytes, returning how many were
+pub fn encode_content(result: &mut Value, threshold: usize) -> usize {
+    let mut encoded = 0;
+    for item in items(result) {
+        if encoding(item).is_some() || !item.get("_meta").is_none_or(Value::is_object) {
+            continue;
+        }
+        let (field, raw) = match (item.get("text"), item.get("blob")) {
+            (Some(Value::String(text)), _) if text.len() >= threshold => {
+                ("text", text.as_bytes().to_vec())
+            }
+            (_, Some(Value::String(blob))) if blob.len() >= threshold => {
+                match STANDARD.decode(blob) {
+                    Ok(raw) => ("blob", raw),
+                    Err(_) => continue,
+                }
+            }
+            _ => continue,
+        };
+        let compressed = STANDARD.encode(compress(&raw));
+        if item
+            .get(field)
+            .and_then(Value::as_str)
+            .is_some_and(|original| compressed.len() >= original.len())
+        {
+            continue;
+        }
+        item.insert(field.to_string(), Value::String(compressed));
+        item.entry("_meta")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("_meta checked to be an object")
+            .insert(CONTENT_ENCODING_META_KEY.to_string(), json!(GZIP_BASE64));
+        encoded += 1;
+    }
+    encoded
+}
+
+/// Restore the items of `result` that [`encode_content`] compressed,
+/// returning how many there were
+pub fn decode_content(result: &mut Value) -> MCPResult<usize> {
+    let mut decoded = 0;
+    for item in items(result) {
+        match encoding(item) {
+            None => continue,
+            Some(GZIP_BASE64) => {}
+            Some(other) => {
+                return Err(MCPError::invalid_response(format!(
+                    "Unsupported content encoding: {other}"
+                )));
+            }
+        }
+        let field = if item.contains_key("text") {
+            "text"
+        } else {
+            "blob"
+        };
+        let raw = item
+            .get(field)
+            .and_then(Value::as_str)
+            .ok_or_else(|| MCPError::invalid_response(format!("Encoded item has no {field}")))
+            .and_then(|encoded| {
+                STANDARD
+                    .decode(encoded)
+                    .map_err(|e| MCPError::invalid_response(format!("Invalid base64: {e}")))
+            })
+            .and_then(|compressed| decompress(&compressed, MAX_DECODED_SIZE))?;
+        let value = if field == "text" {
+            String::from_utf8(raw)
+                .map_err(|_| MCPError::invalid_response("Encoded text is not UTF-8".to_string()))?
+        } else {
+            STANDARD.encode(raw)
+        };
+        item.insert(field.to_string(), Value::String(value));
+
+        if let Some(meta) = item.get_mut("_meta").and_then(Value::as_object_mut) {
+            meta.remove(CONTENT_ENCODING_META_KEY);
+            if meta.is_empty() {
+                item.remove("_meta");
+            }
+        }
+        decoded += 1;
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_items_round_trip() {
+        let text = "2025-06-18T12:00:00Z INFO request handled\n".repeat(5000);
+        let blob = STANDARD.encode(vec![7u8; 200_000]);
+        let mut result = json!({
+            "contents": [
+                {"uri": "file:///app.log", "mimeType": "text/plain", "text": text},
+                {"uri": "file:///zeros.bin", "blob": blob, "_meta": {"owner": "ops"}},
+                {"uri": "file:///small.txt", "text": "small"},
+            ]
+        });
+        let original = result.clone();
+
+        assert_eq!(encode_content(&mut result, 1024), 2);
+        assert_eq!(
+            result["contents"][0]["_meta"][CONTENT_ENCODING_META_KEY],
+            GZIP_BASE64
+        );
+        assert_eq!(result["contents"][1]["_meta"]["owner"], "ops");
+        assert!(result["contents"][1]["blob"].as_str().unwrap().len() < 10_000);
+        assert_eq!(result["contents"][2], original["contents"][2]);
+        // Already encoded items are left alone
+        assert_eq!(encode_content(&mut result, 1024), 0);
+
+        assert_eq!(decode_content(&mut result).unwrap(), 2);
+        assert_eq!(result, original);
+    }
+
+    #[test]
+    fn test_incompressible_and_invalid_items() {
+        let mut state = 1u32;
+        let noise: String = (0..4000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                char::from(b'!' + (state >> 16) as u8 % 90)
+            })
+            .collect();
+        let mut result = json!({"content": [{"type": "text", "text": noise}]});
+        assert_eq!(encode_content(&mut result, 1024), 0);
+
+        let mut result = json!({"content": [{
+            "type": "text",
+            "text": "not base64!",
+            "_meta": {CONTENT_ENCODING_META_KEY: GZIP_BASE64},
+        }]});
+        assert!(decode_content(&mut result).is_err());
+
+        let mut capabilities = ClientCapabilities::default();
+        assert!(!accepts_gzip(&capabilities));
+        advertise(&mut capabilities);
+        assert!(accepts_gzip(&capabilities));
+        assert!(capabilities.supports_capability(CONTENT_ENCODING_CAPABILITY));
+    }
+
+    #[test]
+    fn test_decompress_limits() {
+        let compressed = compress(&[0u8; 4096]);
+        assert_eq!(decompress(&compressed, 4096).unwrap().len(), 4096);
+        assert!(decompress(&compressed, 4095).is_err());
+        assert!(decompress(&compressed[..compressed.len() / 2], 4096).is_err());
+    }
+}
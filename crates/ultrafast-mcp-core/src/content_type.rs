@@ -0,0 +1,281 @@
+//! Content type detection and charset handling for resources
+//!
+//! A resource's MIME type is best taken from its bytes when the format has a
+//! signature, since a `.txt` file holding a PNG image is still an image, and
+//! from the file extension otherwise. [`detect_mime_type`] does both, and
+//! tells text from binary data for files that match neither.
+//!
+//! Text travels as UTF-8 in MCP messages, but files on disk are often in
+//! legacy encodings. [`decode_text`] decodes bytes using a byte order mark,
+//! the declared charset or the bytes themselves, in that order, so text
+//! written in Windows-1252 or UTF-16 arrives readable instead of mojibake'd.
+//!
+//! ```rust
+//! use std::path::Path;
+//! use ultrafast_mcp_core::content_type::{decode_text, detect_mime_type};
+//!
+//! let png = b"\x89PNG\r\n\x1a\n....";
+//! assert_eq!(detect_mime_type(Some(Path::new("notes.txt")), png), "image/png");
+//!
+//! let latin1 = b"caf\xe9";
+//! assert_eq!(detect_mime_type(Some(Path::new("menu.md")), latin1), "text/markdown");
+//! assert_eq!(decode_text(latin1, None).text, "café");
+//! ```
+
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+/// MIME type of data of an unknown type
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// How many leading bytes are looked at to tell text from binary data
+const TEXT_SAMPLE_SIZE: usize = 8 * 1024;
+
+/// MIME type for a file extension, without the dot, if a known one
+pub fn mime_type_for_extension(extension: &str) -> Option<&'static str> {
+    let mime_type = match extension.to_ascii_lowercase().as_str() {
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "js" | "mjs" => "text/javascript",
+        "rs" => "text/x-rust",
+        "py" => "text/x-python",
+        "ts" | "tsx" => "text/x-typescript",
+        "go" => "text/x-go",
+        "c" | "h" => "text/x-c",
+        "sh" => "text/x-shellscript",
+        "toml" => "application/toml",
+        "yaml" | "yml" => "application/yaml",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "wasm" => "application/wasm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// MIME type of a file, guessed from its extension
+pub fn mime_type_for_path(path: &Path) -> Option<&'static str> {
+    mime_type_for_extension(&path.extension()?.to_string_lossy())
+}
+
+/// MIME type of a format whose signature `bytes` start with
+pub fn sniff_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    let riff = |form: &[u8]| bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(form);
+    let mime_type = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if bytes.starts_with(b"\xFF\xD8\xFF") {
+        "image/jpeg"
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if riff(b"WEBP") {
+        "image/webp"
+    } else if bytes.starts_with(b"%PDF-") {
+        "application/pdf"
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        "application/zip"
+    } else if bytes.starts_with(b"\x1F\x8B") {
+        "application/gzip"
+    } else if bytes.starts_with(b"\0asm") {
+        "application/wasm"
+    } else if bytes.starts_with(b"ID3") || bytes.starts_with(b"\xFF\xFB") {
+        "audio/mpeg"
+    } else if riff(b"WAVE") {
+        "audio/wav"
+    } else if bytes.get(4..8) == Some(b"ftyp") {
+        "video/mp4"
+    } else {
+        return None;
+    };
+    Some(mime_type)
+}
+
+/// MIME type of content, from its signature, else its path's extension,
+/// else whether it looks like text
+pub fn detect_mime_type(path: Option<&Path>, bytes: &[u8]) -> &'static str {
+    sniff_mime_type(bytes)
+        .or_else(|| path.and_then(mime_type_for_path))
+        .unwrap_or(if looks_like_text(bytes) {
+            "text/plain"
+        } else {
+            OCTET_STREAM
+        })
+}
+
+/// Whether contents of `mime_type` are text; parameters are ignored
+pub fn is_text_mime_type(mime_type: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence,
+            "application/json"
+                | "application/toml"
+                | "application/yaml"
+                | "application/xml"
+                | "application/javascript"
+        )
+}
+
+/// The `charset` parameter of a MIME type, such as `latin1` in
+/// `text/plain; charset="latin1"`
+pub fn charset_of(mime_type: &str) -> Option<&str> {
+    mime_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Whether `bytes` look like text rather than binary data
+///
+/// Text with a byte order mark always does; otherwise the first few
+/// kilobytes may hold no NUL bytes and only a few other control characters.
+pub fn looks_like_text(bytes: &[u8]) -> bool {
+    if Encoding::for_bom(bytes).is_some() {
+        return true;
+    }
+    let sample = &bytes[..bytes.len().min(TEXT_SAMPLE_SIZE)];
+    if sample.contains(&0) {
+        return false;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&byte| byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+        .count();
+    control * 10 <= sample.len()
+}
+
+/// Text decoded by [`decode_text`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// Name of the encoding the bytes were decoded from, such as `UTF-8`
+    pub charset: &'static str,
+}
+
+/// Decode text whose charset may be declared, or not
+///
+/// A byte order mark decides the encoding. Otherwise `declared`, a charset
+/// label such as `iso-8859-1`, is used if known and the bytes are valid in
+/// it; then UTF-8 if they are valid UTF-8. Anything else is decoded as
+/// Windows-1252, the usual encoding of legacy text files, in which every
+/// byte is a character.
+pub fn decode_text(bytes: &[u8], declared: Option<&str>) -> DecodedText {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return DecodedText {
+            text: text.into_owned(),
+            charset: encoding.name(),
+        };
+    }
+    let declared = declared.and_then(|label| Encoding::for_label(label.trim().as_bytes()));
+    for encoding in declared.into_iter().chain([UTF_8]) {
+        if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+            return DecodedText {
+                text: text.into_owned(),
+                charset: encoding.name(),
+            };
+        }
+    }
+    let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+    DecodedText {
+        text: text.into_owned(),
+        charset: WINDOWS_1252.name(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_win_over_extensions() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(
+            detect_mime_type(Some(Path::new("image.txt")), png),
+            "image/png"
+        );
+        assert_eq!(
+            detect_mime_type(Some(Path::new("song")), b"ID3\x04\0"),
+            "audio/mpeg"
+        );
+        assert_eq!(
+            detect_mime_type(Some(Path::new("clip.bin")), b"\0\0\0\x18ftypmp42"),
+            "video/mp4"
+        );
+        assert_eq!(
+            detect_mime_type(Some(Path::new("README.MD")), b"# Title"),
+            "text/markdown"
+        );
+    }
+
+    #[test]
+    fn test_unknown_files_are_text_or_binary_by_content() {
+        assert_eq!(detect_mime_type(None, b"plain words\n"), "text/plain");
+        assert_eq!(
+            detect_mime_type(Some(Path::new("Makefile")), b"all:\n\tcargo build\n"),
+            "text/plain"
+        );
+        assert_eq!(
+            detect_mime_type(Some(Path::new("data.bin")), b"\x01\x02\0\x03"),
+            OCTET_STREAM
+        );
+        // UTF-16 is full of NUL bytes, but has a byte order mark
+        assert!(looks_like_text(b"\xFF\xFEh\0i\0"));
+        assert!(!looks_like_text(b"\x01\x02\x03\x04\x05 text"));
+    }
+
+    #[test]
+    fn test_text_mime_types_and_charsets() {
+        assert!(is_text_mime_type("text/csv; charset=utf-8"));
+        assert!(is_text_mime_type("application/ld+json"));
+        assert!(!is_text_mime_type("image/png"));
+        assert_eq!(
+            charset_of("text/plain; format=flowed; Charset=\"ISO-8859-1\""),
+            Some("ISO-8859-1")
+        );
+        assert_eq!(charset_of("text/plain"), None);
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let utf8 = decode_text("naïve".as_bytes(), None);
+        assert_eq!((utf8.text.as_str(), utf8.charset), ("naïve", "UTF-8"));
+
+        // Not valid UTF-8, so Windows-1252
+        let legacy = decode_text(b"na\xefve \x80", None);
+        assert_eq!(
+            (legacy.text.as_str(), legacy.charset),
+            ("naïve €", "windows-1252")
+        );
+
+        let utf16 = decode_text(b"\xFE\xFF\0h\0\xe9", Some("utf-8"));
+        assert_eq!((utf16.text.as_str(), utf16.charset), ("hé", "UTF-16BE"));
+
+        let declared = decode_text(b"\xa4", Some("iso-8859-15"));
+        assert_eq!(declared.text, "€");
+
+        // A declared charset the bytes are invalid in is ignored, as is an
+        // unknown one
+        let odd = decode_text(b"abc", Some("utf-16le"));
+        assert_eq!((odd.text.as_str(), odd.charset), ("abc", "UTF-8"));
+        assert_eq!(decode_text(b"abc", Some("klingon")).charset, "UTF-8");
+    }
+}
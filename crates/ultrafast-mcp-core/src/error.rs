@@ -5,6 +5,10 @@ use thiserror::Error;
 /// MCPResult is the canonical result type for all MCP operations.
 pub type MCPResult<T> = Result<T, MCPError>;
 
+/// Boxed error kept as the `source()` of an MCP error when converting from
+/// another crate's error type
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
 /// Main error type for MCP operations
 #[derive(Debug, Error)]
 pub enum MCPError {
@@ -75,6 +79,129 @@ impl MCPError {
     pub fn internal_error(msg: String) -> Self {
         MCPError::Protocol(ProtocolError::InternalError(msg))
     }
+
+    /// Broad category of this error, see [`ErrorCategory`]
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            MCPError::Transport(TransportError::ConnectionTimeout) => ErrorCategory::Timeout,
+            MCPError::Protocol(ProtocolError::ConnectionClosed)
+            | MCPError::Protocol(ProtocolError::TransportError(_))
+            | MCPError::Transport(_) => ErrorCategory::Transport,
+            MCPError::Protocol(ProtocolError::RequestTimeout) => ErrorCategory::Timeout,
+            MCPError::Protocol(ProtocolError::InternalError(_)) | MCPError::Other(_) => {
+                ErrorCategory::Internal
+            }
+            MCPError::Protocol(ProtocolError::SerializationError(_))
+            | MCPError::Serialization(_) => ErrorCategory::Serialization,
+            MCPError::Protocol(ProtocolError::AuthenticationError(_))
+            | MCPError::Authentication(_) => ErrorCategory::Authentication,
+            MCPError::Protocol(ProtocolError::NotFound(_))
+            | MCPError::ToolExecution(ToolError::NotFound(_))
+            | MCPError::Resource(ResourceError::NotFound(_)) => ErrorCategory::NotFound,
+            MCPError::Protocol(ProtocolError::InvalidParams(_))
+            | MCPError::ToolExecution(ToolError::InvalidInput(_))
+            | MCPError::ToolExecution(ToolError::SchemaValidation(_))
+            | MCPError::Validation(_) => ErrorCategory::InvalidInput,
+            MCPError::Protocol(_) => ErrorCategory::Protocol,
+            MCPError::ToolExecution(_) => ErrorCategory::Execution,
+            MCPError::Resource(ResourceError::AccessDenied(_)) => ErrorCategory::Authentication,
+            MCPError::Resource(_) => ErrorCategory::InvalidInput,
+            MCPError::RateLimit(_) => ErrorCategory::RateLimited,
+            MCPError::Io(err) => match err.kind() {
+                std::io::ErrorKind::TimedOut => ErrorCategory::Timeout,
+                std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::ConnectionRefused
+                | std::io::ErrorKind::NotConnected
+                | std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::Interrupted
+                | std::io::ErrorKind::WouldBlock => ErrorCategory::Transport,
+                std::io::ErrorKind::NotFound => ErrorCategory::NotFound,
+                std::io::ErrorKind::PermissionDenied => ErrorCategory::Authentication,
+                _ => ErrorCategory::Internal,
+            },
+        }
+    }
+
+    /// Whether repeating the same request may succeed
+    ///
+    /// Driven by [`ErrorCategory::is_retryable`], except for a transport that
+    /// has given up; use [`MCPError::retry_after`] to honour a server-provided
+    /// delay.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, MCPError::Transport(TransportError::Exhausted(_)))
+            && self.category().is_retryable()
+    }
+
+    /// Minimum delay before retrying, when the error carries one
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            MCPError::RateLimit(RateLimitError::TooManyRequests { retry_after, .. })
+                if *retry_after > 0 =>
+            {
+                Some(std::time::Duration::from_millis(*retry_after))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_transport(&self) -> bool {
+        self.category() == ErrorCategory::Transport
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.category() == ErrorCategory::Timeout
+    }
+
+    pub fn is_authentication(&self) -> bool {
+        self.category() == ErrorCategory::Authentication
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.category() == ErrorCategory::RateLimited
+    }
+}
+
+/// Error taxonomy shared by [`MCPError`] and the transport layer
+///
+/// | Category         | Retryable | Typical cause                                     |
+/// |------------------|-----------|---------------------------------------------------|
+/// | `Transport`      | yes       | connection closed/refused/reset, network failure  |
+/// | `Timeout`        | yes       | request or connection timed out                   |
+/// | `RateLimited`    | yes       | peer asked to slow down (see `retry_after`)       |
+/// | `Protocol`       | no        | malformed JSON-RPC, unknown method, bad version   |
+/// | `InvalidInput`   | no        | invalid params, schema or validation failure      |
+/// | `NotFound`       | no        | unknown tool, resource or prompt                  |
+/// | `Authentication` | no        | bad or expired credentials, access denied         |
+/// | `Execution`      | no        | a tool ran and failed                             |
+/// | `Serialization`  | no        | payload could not be (de)serialized               |
+/// | `Internal`       | no        | bug or unexpected state on either side            |
+///
+/// Retrying never helps for non-retryable categories without changing the
+/// request or the credentials first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    Transport,
+    Timeout,
+    RateLimited,
+    Protocol,
+    InvalidInput,
+    NotFound,
+    Authentication,
+    Execution,
+    Serialization,
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Whether errors in this category are transient
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCategory::Transport | ErrorCategory::Timeout | ErrorCategory::RateLimited
+        )
+    }
 }
 
 /// Protocol-related errors
@@ -140,6 +267,24 @@ pub enum TransportError {
 
     #[error("Receive failed: {0}")]
     ReceiveFailed(String),
+
+    #[error("Connection timeout")]
+    ConnectionTimeout,
+
+    #[error("Network error: {message}")]
+    Network {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+
+    #[error("Transport not ready: {0}")]
+    NotReady(String),
+
+    /// The transport gave up recovering or shutting down, so retrying on it
+    /// cannot succeed
+    #[error("Transport gave up: {0}")]
+    Exhausted(String),
 }
 
 /// Tool execution errors
@@ -188,6 +333,13 @@ pub enum AuthenticationError {
 
     #[error("OAuth error: {error} - {description}")]
     OAuthError { error: String, description: String },
+
+    #[error("Authentication failed: {message}")]
+    Failed {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 }
 
 /// Validation errors
@@ -214,11 +366,21 @@ pub enum ValidationError {
 /// Rate limiting errors
 #[derive(Debug, Error)]
 pub enum RateLimitError {
-    #[error("Too many requests. Retry after {retry_after}ms. Limit: {limit}")]
-    TooManyRequests { retry_after: u64, limit: u32 },
+    /// `limit` is `None` when the peer did not say what its limit is
+    #[error(
+        "Too many requests. Retry after {retry_after}ms{}",
+        .limit.map(|limit| format!(". Limit: {limit}")).unwrap_or_default()
+    )]
+    TooManyRequests {
+        retry_after: u64,
+        limit: Option<u32>,
+    },
 
     #[error("Quota exceeded: {quota} requests per {period}")]
     QuotaExceeded { quota: u32, period: String },
+
+    #[error("Server busy: {limit} requests already in progress")]
+    ServerBusy { limit: u32 },
 }
 
 /// Standard JSON-RPC error codes
@@ -239,6 +401,7 @@ pub mod error_codes {
     pub const AUTHENTICATION_ERROR: i32 = -32006;
     pub const VALIDATION_ERROR: i32 = -32007;
     pub const RATE_LIMIT_ERROR: i32 = -32008;
+    pub const SERVER_BUSY: i32 = -32009;
 }
 
 impl From<crate::protocol::jsonrpc::JsonRpcError> for MCPError {
@@ -284,10 +447,23 @@ impl From<crate::protocol::jsonrpc::JsonRpcError> for MCPError {
                     details: err.message,
                 })
             }
-            error_codes::RATE_LIMIT_ERROR => MCPError::RateLimit(RateLimitError::TooManyRequests {
-                retry_after: 0,
-                limit: 0,
-            }),
+            error_codes::RATE_LIMIT_ERROR => {
+                let data = err.data.as_ref();
+                let field = |name: &str| data.and_then(|data| data.get(name)?.as_u64());
+                MCPError::RateLimit(RateLimitError::TooManyRequests {
+                    retry_after: field("retryAfterMs").unwrap_or(0),
+                    limit: field("limit").map(|limit| limit as u32),
+                })
+            }
+            error_codes::SERVER_BUSY => {
+                let limit = err
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("maxConcurrent")?.as_u64());
+                MCPError::RateLimit(RateLimitError::ServerBusy {
+                    limit: limit.unwrap_or(0) as u32,
+                })
+            }
             _ => MCPError::Protocol(ProtocolError::InternalError(err.message)),
         }
     }
@@ -320,4 +496,49 @@ mod tests {
             MCPError::Protocol(ProtocolError::InternalError(_))
         ));
     }
+
+    #[test]
+    fn test_error_retry_classification() {
+        assert!(MCPError::transport_error("refused".to_string()).is_retryable());
+        assert!(MCPError::request_timeout().is_retryable());
+        assert!(!MCPError::invalid_params("bad".to_string()).is_retryable());
+        assert!(!MCPError::Authentication(AuthenticationError::TokenExpired).is_retryable());
+
+        let io = MCPError::Io(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert_eq!(io.category(), ErrorCategory::Transport);
+        assert!(io.is_retryable());
+
+        assert_eq!(
+            MCPError::not_found("tool".to_string()).category(),
+            ErrorCategory::NotFound
+        );
+    }
+
+    #[test]
+    fn test_error_retry_after() {
+        let error = MCPError::RateLimit(RateLimitError::TooManyRequests {
+            retry_after: 1500,
+            limit: Some(10),
+        });
+        assert!(error.is_rate_limited());
+        assert!(error.is_retryable());
+        assert_eq!(
+            error.retry_after(),
+            Some(std::time::Duration::from_millis(1500))
+        );
+
+        assert_eq!(MCPError::request_timeout().retry_after(), None);
+
+        let error = MCPError::from(
+            crate::protocol::jsonrpc::JsonRpcError::new(
+                error_codes::RATE_LIMIT_ERROR,
+                "Too many requests".to_string(),
+            )
+            .with_data(serde_json::json!({"retryAfterMs": 250, "limit": 5})),
+        );
+        assert_eq!(
+            error.retry_after(),
+            Some(std::time::Duration::from_millis(250))
+        );
+    }
 }
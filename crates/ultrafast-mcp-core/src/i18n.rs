@@ -0,0 +1,525 @@
+//! Localized, parameterized user-facing messages
+//!
+//! User-facing error strings (authentication failures, validation errors,
+//! rate limits, elicitation re-prompts) are identified by a stable message
+//! key from [`keys`] plus named arguments, collected in a
+//! [`LocalizedMessage`]. A [`MessageCatalog`] renders them in a locale,
+//! substituting `{name}` placeholders in its templates with the arguments.
+//!
+//! Clients pick the locale per request with `_meta["ultrafast/locale"]`
+//! ([`LOCALE_META_KEY`]); otherwise the catalog's default locale is used.
+//! Locales fall back from the most to the least specific tag (`pt-BR`, then
+//! `pt`), then to the default locale and finally to the built-in English
+//! messages, so a catalog only needs the keys it translates.
+//!
+//! Errors rendered through a catalog keep the key and arguments in the
+//! JSON-RPC error's `data`, so hosts can render their own text without
+//! parsing the message:
+//!
+//! ```rust
+//! use ultrafast_mcp_core::error::{MCPError, ValidationError};
+//! use ultrafast_mcp_core::i18n::{MessageCatalog, keys};
+//!
+//! let catalog = MessageCatalog::new().with_messages(
+//!     "de",
+//!     [(keys::VALIDATION_REQUIRED_FIELD, "Das Feld '{field}' fehlt")],
+//! );
+//! let error = MCPError::Validation(ValidationError::RequiredField {
+//!     field: "city".to_string(),
+//! });
+//! let message = error.localized().unwrap();
+//! assert_eq!(catalog.render(Some("de-AT"), &message), "Das Feld 'city' fehlt");
+//! assert_eq!(catalog.render(None, &message), "Required field 'city' is missing");
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{
+    AuthenticationError, MCPError, RateLimitError, ResourceError, ToolError, ValidationError,
+};
+use crate::protocol::jsonrpc::JsonRpcError;
+
+/// `_meta` key of a request carrying the locale the client wants messages in
+pub const LOCALE_META_KEY: &str = "ultrafast/locale";
+
+/// Locale of the built-in messages
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Keys of the built-in messages
+pub mod keys {
+    pub const AUTH_INVALID_CREDENTIALS: &str = "auth.invalid_credentials";
+    pub const AUTH_TOKEN_EXPIRED: &str = "auth.token_expired";
+    pub const AUTH_INSUFFICIENT_PERMISSIONS: &str = "auth.insufficient_permissions";
+    pub const AUTH_OAUTH: &str = "auth.oauth";
+    pub const AUTH_FAILED: &str = "auth.failed";
+    pub const VALIDATION_SCHEMA: &str = "validation.schema";
+    pub const VALIDATION_REQUIRED_FIELD: &str = "validation.required_field";
+    pub const VALIDATION_INVALID_FORMAT: &str = "validation.invalid_format";
+    pub const VALIDATION_OUT_OF_RANGE: &str = "validation.out_of_range";
+    pub const RATE_LIMIT_TOO_MANY_REQUESTS: &str = "rate_limit.too_many_requests";
+    pub const RATE_LIMIT_RETRY_AFTER: &str = "rate_limit.retry_after";
+    pub const RATE_LIMIT_QUOTA_EXCEEDED: &str = "rate_limit.quota_exceeded";
+    pub const RATE_LIMIT_SERVER_BUSY: &str = "rate_limit.server_busy";
+    pub const TOOL_NOT_FOUND: &str = "tool.not_found";
+    pub const TOOL_INVALID_INPUT: &str = "tool.invalid_input";
+    pub const RESOURCE_NOT_FOUND: &str = "resource.not_found";
+    pub const RESOURCE_ACCESS_DENIED: &str = "resource.access_denied";
+    pub const ELICITATION_INVALID_ANSWER: &str = "elicitation.invalid_answer";
+}
+
+/// The English messages every catalog falls back to
+const BUILTIN_MESSAGES: &[(&str, &str)] = &[
+    (keys::AUTH_INVALID_CREDENTIALS, "Invalid credentials"),
+    (keys::AUTH_TOKEN_EXPIRED, "Token expired"),
+    (
+        keys::AUTH_INSUFFICIENT_PERMISSIONS,
+        "Insufficient permissions for resource: {resource}",
+    ),
+    (keys::AUTH_OAUTH, "OAuth error: {error} - {description}"),
+    (keys::AUTH_FAILED, "Authentication failed: {message}"),
+    (
+        keys::VALIDATION_SCHEMA,
+        "Schema validation failed for field '{field}': {details}",
+    ),
+    (
+        keys::VALIDATION_REQUIRED_FIELD,
+        "Required field '{field}' is missing",
+    ),
+    (
+        keys::VALIDATION_INVALID_FORMAT,
+        "Invalid format for field '{field}': expected {expected}",
+    ),
+    (
+        keys::VALIDATION_OUT_OF_RANGE,
+        "Value for field '{field}' is out of range: {actual} (expected {min}..{max})",
+    ),
+    (
+        keys::RATE_LIMIT_TOO_MANY_REQUESTS,
+        "Too many requests. Retry after {retry_after}ms. Limit: {limit}",
+    ),
+    (
+        keys::RATE_LIMIT_RETRY_AFTER,
+        "Too many requests. Retry after {retry_after}ms",
+    ),
+    (
+        keys::RATE_LIMIT_QUOTA_EXCEEDED,
+        "Quota exceeded: {quota} requests per {period}",
+    ),
+    (
+        keys::RATE_LIMIT_SERVER_BUSY,
+        "Server busy: {limit} requests already in progress",
+    ),
+    (keys::TOOL_NOT_FOUND, "Tool not found: {name}"),
+    (keys::TOOL_INVALID_INPUT, "Invalid input: {details}"),
+    (keys::RESOURCE_NOT_FOUND, "Resource not found: {uri}"),
+    (keys::RESOURCE_ACCESS_DENIED, "Access denied: {uri}"),
+    (keys::ELICITATION_INVALID_ANSWER, "{error}\n\n{prompt}"),
+];
+
+static BUILTIN_CATALOG: LazyLock<MessageCatalog> = LazyLock::new(MessageCatalog::new);
+
+/// A message key and the arguments for its placeholders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizedMessage {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, String>,
+}
+
+impl LocalizedMessage {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            args: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.args.insert(name.into(), value.to_string());
+        self
+    }
+}
+
+/// Message templates by locale
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    default_locale: String,
+    messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageCatalog {
+    /// A catalog with the built-in English messages
+    pub fn new() -> Self {
+        let builtin = BUILTIN_MESSAGES
+            .iter()
+            .map(|(key, template)| (key.to_string(), template.to_string()))
+            .collect();
+        Self {
+            default_locale: DEFAULT_LOCALE.to_string(),
+            messages: HashMap::from([(DEFAULT_LOCALE.to_string(), builtin)]),
+        }
+    }
+
+    /// The catalog of built-in English messages
+    pub fn builtin() -> &'static MessageCatalog {
+        &BUILTIN_CATALOG
+    }
+
+    /// Locale used when a request does not ask for one
+    pub fn with_default_locale(mut self, locale: impl AsRef<str>) -> Self {
+        self.default_locale = normalize_locale(locale.as_ref());
+        self
+    }
+
+    /// Add or replace the templates of `locale`
+    pub fn with_messages<K, T>(
+        mut self,
+        locale: impl AsRef<str>,
+        messages: impl IntoIterator<Item = (K, T)>,
+    ) -> Self
+    where
+        K: Into<String>,
+        T: Into<String>,
+    {
+        for (key, template) in messages {
+            self.insert(locale.as_ref(), key, template);
+        }
+        self
+    }
+
+    /// Add or replace a single template
+    pub fn insert(&mut self, locale: &str, key: impl Into<String>, template: impl Into<String>) {
+        self.messages
+            .entry(normalize_locale(locale))
+            .or_default()
+            .insert(key.into(), template.into());
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    /// Locales with at least one template
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<_> = self.messages.keys().map(String::as_str).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// Render `message` in `locale`, or the default locale
+    pub fn render(&self, locale: Option<&str>, message: &LocalizedMessage) -> String {
+        self.render_with_locale(locale, message).0
+    }
+
+    /// Render `message`, also returning the locale whose template was used
+    ///
+    /// A key no locale knows renders as the key itself, reported in the
+    /// default locale.
+    pub fn render_with_locale(
+        &self,
+        locale: Option<&str>,
+        message: &LocalizedMessage,
+    ) -> (String, String) {
+        for candidate in self.fallback_chain(locale) {
+            if let Some(template) = self
+                .messages
+                .get(&candidate)
+                .and_then(|messages| messages.get(&message.key))
+            {
+                return (format_template(template, &message.args), candidate);
+            }
+        }
+        (message.key.clone(), self.default_locale.clone())
+    }
+
+    /// Locales to look a key up in, most specific first
+    fn fallback_chain(&self, locale: Option<&str>) -> Vec<String> {
+        let mut chain = Vec::new();
+        if let Some(locale) = locale {
+            let mut tag = normalize_locale(locale);
+            while !tag.is_empty() {
+                chain.push(tag.clone());
+                tag.truncate(tag.rfind('-').unwrap_or(0));
+            }
+        }
+        for fallback in [self.default_locale.as_str(), DEFAULT_LOCALE] {
+            if !chain.iter().any(|tag| tag == fallback) {
+                chain.push(fallback.to_string());
+            }
+        }
+        chain
+    }
+
+    /// JSON-RPC error with `code` and `message` rendered in `locale`
+    ///
+    /// The error's `data` carries the `messageKey`, `messageArgs` and the
+    /// `locale` the message was rendered in.
+    pub fn error(
+        &self,
+        code: i32,
+        locale: Option<&str>,
+        message: &LocalizedMessage,
+    ) -> JsonRpcError {
+        let (text, locale) = self.render_with_locale(locale, message);
+        JsonRpcError::new(code, text).with_data(serde_json::json!({
+            "messageKey": message.key,
+            "messageArgs": message.args,
+            "locale": locale,
+        }))
+    }
+}
+
+/// Lower-case a locale tag, with `-` separating its subtags
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+/// Substitute `{name}` placeholders; unknown placeholders are kept as is
+fn format_template(template: &str, args: &BTreeMap<String, String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        match placeholder
+            .find('}')
+            .and_then(|end| args.get(&placeholder[1..end]).map(|value| (end, value)))
+        {
+            Some((end, value)) => {
+                output.push_str(value);
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The locale a request asks for in `_meta`, given the request's params
+pub fn locale_from_params(params: Option<&Value>) -> Option<&str> {
+    params?.get("_meta")?.get(LOCALE_META_KEY)?.as_str()
+}
+
+/// Ask for `locale` in the `_meta` of a request's params
+///
+/// Params that are not an object are left untouched.
+pub fn set_locale_meta(params: &mut Option<Value>, locale: &str) {
+    let params = params.get_or_insert_with(|| Value::Object(Default::default()));
+    if let Some(params) = params.as_object_mut() {
+        let meta = params
+            .entry("_meta")
+            .or_insert_with(|| Value::Object(Default::default()));
+        if let Some(meta) = meta.as_object_mut() {
+            meta.insert(LOCALE_META_KEY.to_string(), Value::from(locale));
+        }
+    }
+}
+
+impl MCPError {
+    /// The user-facing message of this error, for errors the built-in
+    /// catalog has a key for
+    pub fn localized(&self) -> Option<LocalizedMessage> {
+        let message = match self {
+            MCPError::Authentication(error) => match error {
+                AuthenticationError::InvalidCredentials => {
+                    LocalizedMessage::new(keys::AUTH_INVALID_CREDENTIALS)
+                }
+                AuthenticationError::TokenExpired => {
+                    LocalizedMessage::new(keys::AUTH_TOKEN_EXPIRED)
+                }
+                AuthenticationError::InsufficientPermissions { resource } => {
+                    LocalizedMessage::new(keys::AUTH_INSUFFICIENT_PERMISSIONS)
+                        .with_arg("resource", resource)
+                }
+                AuthenticationError::OAuthError { error, description } => {
+                    LocalizedMessage::new(keys::AUTH_OAUTH)
+                        .with_arg("error", error)
+                        .with_arg("description", description)
+                }
+                AuthenticationError::Failed { message, .. } => {
+                    LocalizedMessage::new(keys::AUTH_FAILED).with_arg("message", message)
+                }
+            },
+            MCPError::Validation(error) => match error {
+                ValidationError::SchemaValidation { field, details } => {
+                    LocalizedMessage::new(keys::VALIDATION_SCHEMA)
+                        .with_arg("field", field)
+                        .with_arg("details", details)
+                }
+                ValidationError::RequiredField { field } => {
+                    LocalizedMessage::new(keys::VALIDATION_REQUIRED_FIELD).with_arg("field", field)
+                }
+                ValidationError::InvalidFormat { field, expected } => {
+                    LocalizedMessage::new(keys::VALIDATION_INVALID_FORMAT)
+                        .with_arg("field", field)
+                        .with_arg("expected", expected)
+                }
+                ValidationError::ValueOutOfRange {
+                    field,
+                    min,
+                    max,
+                    actual,
+                } => LocalizedMessage::new(keys::VALIDATION_OUT_OF_RANGE)
+                    .with_arg("field", field)
+                    .with_arg("min", min)
+                    .with_arg("max", max)
+                    .with_arg("actual", actual),
+            },
+            MCPError::RateLimit(error) => match error {
+                RateLimitError::TooManyRequests {
+                    retry_after,
+                    limit: Some(limit),
+                } => LocalizedMessage::new(keys::RATE_LIMIT_TOO_MANY_REQUESTS)
+                    .with_arg("retry_after", retry_after)
+                    .with_arg("limit", limit),
+                RateLimitError::TooManyRequests {
+                    retry_after,
+                    limit: None,
+                } => LocalizedMessage::new(keys::RATE_LIMIT_RETRY_AFTER)
+                    .with_arg("retry_after", retry_after),
+                RateLimitError::QuotaExceeded { quota, period } => {
+                    LocalizedMessage::new(keys::RATE_LIMIT_QUOTA_EXCEEDED)
+                        .with_arg("quota", quota)
+                        .with_arg("period", period)
+                }
+                RateLimitError::ServerBusy { limit } => {
+                    LocalizedMessage::new(keys::RATE_LIMIT_SERVER_BUSY).with_arg("limit", limit)
+                }
+            },
+            MCPError::ToolExecution(ToolError::NotFound(name)) => {
+                LocalizedMessage::new(keys::TOOL_NOT_FOUND).with_arg("name", name)
+            }
+            MCPError::ToolExecution(ToolError::InvalidInput(details)) => {
+                LocalizedMessage::new(keys::TOOL_INVALID_INPUT).with_arg("details", details)
+            }
+            MCPError::Resource(ResourceError::NotFound(uri)) => {
+                LocalizedMessage::new(keys::RESOURCE_NOT_FOUND).with_arg("uri", uri)
+            }
+            MCPError::Resource(ResourceError::AccessDenied(uri)) => {
+                LocalizedMessage::new(keys::RESOURCE_ACCESS_DENIED).with_arg("uri", uri)
+            }
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_messages_match_error_display() {
+        let errors = [
+            MCPError::Authentication(AuthenticationError::InsufficientPermissions {
+                resource: "file:///etc".to_string(),
+            }),
+            MCPError::Validation(ValidationError::ValueOutOfRange {
+                field: "age".to_string(),
+                min: "0".to_string(),
+                max: "150".to_string(),
+                actual: "200".to_string(),
+            }),
+            MCPError::RateLimit(RateLimitError::TooManyRequests {
+                retry_after: 500,
+                limit: Some(10),
+            }),
+            MCPError::RateLimit(RateLimitError::TooManyRequests {
+                retry_after: 500,
+                limit: None,
+            }),
+            MCPError::Resource(ResourceError::AccessDenied("secret://x".to_string())),
+        ];
+        for error in errors {
+            let message = error.localized().unwrap();
+            let rendered = MessageCatalog::builtin().render(None, &message);
+            assert!(
+                error.to_string().ends_with(&rendered),
+                "{rendered:?} does not match {error}"
+            );
+        }
+        assert!(
+            MCPError::internal_error("x".to_string())
+                .localized()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_locales_fall_back_to_less_specific_tags() {
+        let catalog = MessageCatalog::new()
+            .with_messages("pt", [(keys::AUTH_TOKEN_EXPIRED, "Token expirado")])
+            .with_messages(
+                "pt_BR",
+                [(keys::AUTH_INVALID_CREDENTIALS, "Credenciais inválidas")],
+            )
+            .with_messages("fr", [(keys::AUTH_TOKEN_EXPIRED, "Jeton expiré")])
+            .with_default_locale("fr");
+
+        let expired = LocalizedMessage::new(keys::AUTH_TOKEN_EXPIRED);
+        let invalid = LocalizedMessage::new(keys::AUTH_INVALID_CREDENTIALS);
+        assert_eq!(
+            catalog.render_with_locale(Some("PT-br"), &expired),
+            ("Token expirado".to_string(), "pt".to_string())
+        );
+        assert_eq!(
+            catalog.render(Some("pt-BR"), &invalid),
+            "Credenciais inválidas"
+        );
+        assert_eq!(catalog.render(Some("ja"), &expired), "Jeton expiré");
+        assert_eq!(catalog.render(Some("ja"), &invalid), "Invalid credentials");
+        assert_eq!(
+            catalog.render(None, &LocalizedMessage::new("app.unknown")),
+            "app.unknown"
+        );
+    }
+
+    #[test]
+    fn test_templates_substitute_known_placeholders_once() {
+        let args = BTreeMap::from([
+            ("name".to_string(), "{other}".to_string()),
+            ("other".to_string(), "x".to_string()),
+        ]);
+        assert_eq!(
+            format_template("{name} and {missing} {other}{", &args),
+            "{other} and {missing} x{"
+        );
+    }
+
+    #[test]
+    fn test_locale_round_trips_through_meta() {
+        let mut params = Some(serde_json::json!({"name": "echo"}));
+        set_locale_meta(&mut params, "de-CH");
+        assert_eq!(locale_from_params(params.as_ref()), Some("de-CH"));
+        assert_eq!(params.as_ref().unwrap()["name"], "echo");
+
+        let mut empty = None;
+        set_locale_meta(&mut empty, "fr");
+        assert_eq!(locale_from_params(empty.as_ref()), Some("fr"));
+
+        let error = MessageCatalog::new().error(
+            -32602,
+            Some("fr"),
+            &LocalizedMessage::new(keys::TOOL_NOT_FOUND).with_arg("name", "echo"),
+        );
+        assert_eq!(error.message, "Tool not found: echo");
+        let data = error.data.unwrap();
+        assert_eq!(data["messageKey"], keys::TOOL_NOT_FOUND);
+        assert_eq!(data["messageArgs"]["name"], "echo");
+        assert_eq!(data["locale"], "en");
+    }
+}
@@ -6,7 +6,11 @@
 //! for building high-performance MCP-compliant servers and clients.
 
 pub mod config;
+#[cfg(feature = "content-encoding")]
+pub mod content_encoding;
+pub mod content_type;
 pub mod error;
+pub mod i18n;
 pub mod protocol;
 pub mod schema;
 pub mod traits;
@@ -14,13 +18,13 @@ pub mod types;
 pub mod utils;
 pub mod validation;
 
-pub use error::{MCPError, MCPResult};
+pub use error::{ErrorCategory, MCPError, MCPResult};
 
 // Re-export protocol items
 pub use protocol::{
     ImplementationMetadata, InitializeRequest, InitializeResponse, InitializedNotification,
     JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, LifecyclePhase, LogLevel,
-    LogMessage, Message, Notification, RequestId, RequestMetadata, ResponseMetadata,
+    LogMessage, Message, Notification, RequestId, RequestMetadata, ResponseMeta, ResponseMetadata,
     ShutdownRequest,
 };
 
@@ -48,6 +52,7 @@ pub use types::{
     ModelPreferences,
     PingRequest,
     PingResponse,
+    ProgressCheckpoint,
     ProgressNotification,
     Prompt,
     PromptArgument,
@@ -79,6 +84,7 @@ pub use types::{
     ToolCallRequest,
     ToolCallResponse,
     ToolContent,
+    ToolProgressSummary,
     // Notification types
     ToolsListChangedNotification,
     UnsubscribeRequest,
@@ -94,3 +100,5 @@ pub use schema::{
 pub use utils::{
     Cursor, PaginationInfo, PaginationParams, Progress, ProgressStatus, ProgressTracker, Uri,
 };
+#[cfg(feature = "cursor-signing")]
+pub use utils::{CursorCodec, CursorError};
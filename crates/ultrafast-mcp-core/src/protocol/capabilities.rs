@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Client capabilities that can be negotiated during initialization
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -14,6 +17,10 @@ pub struct ClientCapabilities {
     /// User input elicitation capability
     #[serde(skip_serializing_if = "Option::is_none")]
     pub elicitation: Option<ElicitationCapability>,
+
+    /// Non-standard capabilities, by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<HashMap<String, Value>>,
 }
 
 /// Server capabilities that can be advertised during initialization
@@ -148,7 +155,10 @@ impl ClientCapabilities {
             "roots" => self.roots.is_some(),
             "sampling" => self.sampling.is_some(),
             "elicitation" => self.elicitation.is_some(),
-            _ => false,
+            name => self
+                .experimental
+                .as_ref()
+                .is_some_and(|experimental| experimental.contains_key(name)),
         }
     }
 }
@@ -3,8 +3,10 @@ use crate::protocol::constants::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use serde_json::value::RawValue;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Standard JSON-RPC 2.0 error codes
 pub mod error_codes {
@@ -163,12 +165,25 @@ impl JsonRpcRequest {
 }
 
 /// JSON-RPC 2.0 Response with optimized memory usage
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcResponse {
     #[serde(rename = "jsonrpc")]
     pub jsonrpc: Cow<'static, str>,
+    /// Parsed result; `None` on a response built with
+    /// [`success_raw`](Self::success_raw), so check for a result with
+    /// [`has_result`](Self::has_result) and take it with
+    /// [`into_result`](Self::into_result)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<Value>,
+    /// Result serialized ahead of time and shared between responses,
+    /// written out as `result`; never set on a received response
+    #[serde(
+        rename = "result",
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_raw_result"
+    )]
+    pub(crate) raw_result: Option<Arc<RawValue>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<JsonRpcError>,
     pub id: Option<RequestId>,
@@ -181,6 +196,20 @@ impl JsonRpcResponse {
         Self {
             jsonrpc: Cow::Borrowed(JSONRPC_VERSION),
             result: Some(result),
+            raw_result: None,
+            error: None,
+            id,
+            meta: HashMap::new(),
+        }
+    }
+
+    /// A success response whose result is already serialized, so that it is
+    /// written out without being copied
+    pub fn success_raw(result: Arc<RawValue>, id: Option<RequestId>) -> Self {
+        Self {
+            jsonrpc: Cow::Borrowed(JSONRPC_VERSION),
+            result: None,
+            raw_result: Some(result),
             error: None,
             id,
             meta: HashMap::new(),
@@ -191,18 +220,57 @@ impl JsonRpcResponse {
         Self {
             jsonrpc: Cow::Borrowed(JSONRPC_VERSION),
             result: None,
+            raw_result: None,
             error: Some(error),
             id,
             meta: HashMap::new(),
         }
     }
 
+    /// Whether the response carries a result, parsed or pre-serialized
+    pub fn has_result(&self) -> bool {
+        self.result.is_some() || self.raw_result.is_some()
+    }
+
+    /// The pre-serialized result of a response built with
+    /// [`success_raw`](Self::success_raw)
+    pub fn raw_result(&self) -> Option<&Arc<RawValue>> {
+        self.raw_result.as_ref()
+    }
+
+    /// The result as a value, parsing a pre-serialized one
+    pub fn into_result(self) -> Option<Value> {
+        match self.raw_result {
+            Some(raw) => serde_json::from_str(raw.get()).ok(),
+            None => self.result,
+        }
+    }
+
     pub fn with_meta(mut self, key: String, value: Value) -> Self {
         self.meta.insert(key, value);
         self
     }
 }
 
+impl PartialEq for JsonRpcResponse {
+    fn eq(&self, other: &Self) -> bool {
+        self.jsonrpc == other.jsonrpc
+            && self.result == other.result
+            && self.raw_result.as_deref().map(RawValue::get)
+                == other.raw_result.as_deref().map(RawValue::get)
+            && self.error == other.error
+            && self.id == other.id
+            && self.meta == other.meta
+    }
+}
+
+fn serialize_raw_result<S: serde::Serializer>(
+    result: &Option<Arc<RawValue>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    result.as_deref().serialize(serializer)
+}
+
 /// JSON-RPC 2.0 Error with improved error handling and optimized memory usage
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct JsonRpcError {
@@ -358,13 +426,13 @@ pub fn validate_jsonrpc_message(
                 )));
             }
 
-            if response.result.is_some() && response.error.is_some() {
+            if response.has_result() && response.error.is_some() {
                 return Err(crate::error::ProtocolError::InvalidResponse(
                     "Response cannot have both result and error".to_string(),
                 ));
             }
 
-            if response.result.is_none() && response.error.is_none() {
+            if !response.has_result() && response.error.is_none() {
                 return Err(crate::error::ProtocolError::InvalidResponse(
                     "Response must have either result or error".to_string(),
                 ));
@@ -440,6 +508,24 @@ mod tests {
         assert_eq!(response.id, Some(RequestId::number(1)));
     }
 
+    #[test]
+    fn test_raw_response_success() {
+        let raw: Arc<RawValue> = serde_json::value::to_raw_value(&serde_json::json!({"tools": []}))
+            .unwrap()
+            .into();
+        let response = JsonRpcResponse::success_raw(raw, Some(RequestId::number(1)));
+
+        assert!(response.has_result());
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"jsonrpc":"2.0","result":{"tools":[]},"id":1}"#
+        );
+        assert_eq!(
+            response.into_result(),
+            Some(serde_json::json!({"tools": []}))
+        );
+    }
+
     #[test]
     fn test_response_error() {
         let error = JsonRpcError::method_not_found("unknown_method".to_string());
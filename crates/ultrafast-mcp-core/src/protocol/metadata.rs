@@ -56,3 +56,66 @@ pub struct ResponseMetadata {
     #[serde(flatten)]
     pub additional: HashMap<String, serde_json::Value>,
 }
+
+/// `_meta` key under which a server reports [`ResponseMeta`]
+pub const RESPONSE_META_KEY: &str = "ultrafast/execution";
+
+/// How the server executed a request, reported in the result's `_meta`
+///
+/// Servers only attach it when enabled, so clients must treat it as
+/// optional.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseMeta {
+    /// Time spent handling the request in milliseconds
+    pub duration_ms: u64,
+    /// Size of the serialized result in bytes
+    pub bytes_processed: u64,
+    /// Whether the result was served from a cache
+    #[serde(default)]
+    pub cache_hit: bool,
+}
+
+impl ResponseMeta {
+    /// Read the execution metadata from a result's `_meta`
+    pub fn from_result(result: &serde_json::Value) -> Option<Self> {
+        let meta = result.get("_meta")?.get(RESPONSE_META_KEY)?;
+        serde_json::from_value(meta.clone()).ok()
+    }
+
+    /// Stamp the execution metadata into a result's `_meta`
+    ///
+    /// Results that are not objects are left untouched.
+    pub fn attach(&self, result: &mut serde_json::Value) {
+        let Some(object) = result.as_object_mut() else {
+            return;
+        };
+        let meta = object
+            .entry("_meta")
+            .or_insert_with(|| serde_json::json!({}));
+        if let (Some(meta), Ok(value)) = (meta.as_object_mut(), serde_json::to_value(self)) {
+            meta.insert(RESPONSE_META_KEY.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_meta_round_trips_through_result() {
+        let meta = ResponseMeta {
+            duration_ms: 12,
+            bytes_processed: 340,
+            cache_hit: true,
+        };
+        let mut result = serde_json::json!({"tools": [], "_meta": {"other": 1}});
+        meta.attach(&mut result);
+
+        assert_eq!(result["_meta"]["other"], 1);
+        assert_eq!(result["_meta"][RESPONSE_META_KEY]["durationMs"], 12);
+        assert_eq!(ResponseMeta::from_result(&result), Some(meta));
+        assert_eq!(ResponseMeta::from_result(&serde_json::json!({})), None);
+    }
+}
@@ -188,6 +188,7 @@ pub mod jsonrpc;
 pub mod lifecycle;
 pub mod messages;
 pub mod metadata;
+pub mod trace_context;
 pub mod version;
 
 pub use capabilities::*;
@@ -0,0 +1,196 @@
+//! Distributed trace context carried in request `_meta`
+//!
+//! A request carries the [W3C Trace Context] of the span it was sent from
+//! in `_meta["traceparent"]` and, if there is one, `_meta["tracestate"]`.
+//! The receiving side makes its handler span a child of that span, so the
+//! client's request and the server's handling of it end up in one trace,
+//! whichever transport carried them.
+//!
+//! Linking `tracing` spans to trace IDs is up to the tracing backend, which
+//! registers a [`TracePropagator`] with [`set_propagator`];
+//! `ultrafast-mcp-monitoring` does so for OpenTelemetry. Without one,
+//! [`inject_current`] and [`set_parent_from_meta`] do nothing.
+//!
+//! [W3C Trace Context]: https://www.w3.org/TR/trace-context/
+
+use std::sync::{Arc, RwLock};
+
+use serde_json::Value;
+
+/// `_meta` key of the `traceparent` of the span a request was sent from
+pub const TRACEPARENT_META_KEY: &str = "traceparent";
+
+/// `_meta` key of the vendor-specific `tracestate` that goes with it
+pub const TRACESTATE_META_KEY: &str = "tracestate";
+
+/// A remote span, as identified by a W3C `traceparent` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    traceparent: String,
+    tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// `None` unless `traceparent` is `version-traceid-spanid-flags` with
+    /// the trace and span IDs not all zeros
+    pub fn new(traceparent: impl Into<String>, tracestate: Option<String>) -> Option<Self> {
+        let traceparent = traceparent.into();
+        let parts: Vec<&str> = traceparent.split('-').collect();
+        let valid = matches!(parts.as_slice(), [version, trace_id, span_id, flags, ..]
+            if is_hex(version, 2) && *version != "ff"
+                && is_hex(trace_id, 32) && trace_id.bytes().any(|b| b != b'0')
+                && is_hex(span_id, 16) && span_id.bytes().any(|b| b != b'0')
+                && is_hex(flags, 2)
+                && (parts.len() == 4 || *version != "00"));
+        valid.then_some(Self {
+            traceparent,
+            tracestate: tracestate.filter(|state| !state.is_empty()),
+        })
+    }
+
+    pub fn traceparent(&self) -> &str {
+        &self.traceparent
+    }
+
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    pub fn trace_id(&self) -> &str {
+        &self.traceparent[3..35]
+    }
+
+    pub fn parent_span_id(&self) -> &str {
+        &self.traceparent[36..52]
+    }
+
+    /// Whether the caller recorded its span
+    pub fn sampled(&self) -> bool {
+        u8::from_str_radix(&self.traceparent[53..55], 16).is_ok_and(|flags| flags & 1 == 1)
+    }
+
+    /// The trace context in the `_meta` of request params
+    pub fn from_meta(params: Option<&Value>) -> Option<Self> {
+        let meta = params?.get("_meta")?;
+        let traceparent = meta.get(TRACEPARENT_META_KEY)?.as_str()?;
+        let tracestate = meta
+            .get(TRACESTATE_META_KEY)
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        Self::new(traceparent, tracestate)
+    }
+
+    /// Put the trace context into the `_meta` of request params
+    pub fn inject(&self, params: &mut Option<Value>) {
+        let params = params.get_or_insert_with(|| Value::Object(Default::default()));
+        let Some(params) = params.as_object_mut() else {
+            return;
+        };
+        let meta = params
+            .entry("_meta")
+            .or_insert_with(|| Value::Object(Default::default()));
+        if let Some(meta) = meta.as_object_mut() {
+            meta.insert(
+                TRACEPARENT_META_KEY.to_string(),
+                Value::from(self.traceparent.as_str()),
+            );
+            if let Some(tracestate) = &self.tracestate {
+                meta.insert(
+                    TRACESTATE_META_KEY.to_string(),
+                    Value::from(tracestate.as_str()),
+                );
+            }
+        }
+    }
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Links `tracing` spans to distributed traces
+pub trait TracePropagator: Send + Sync {
+    /// The trace context of the current span, to send along with a request
+    fn current(&self) -> Option<TraceContext>;
+
+    /// Make `span` a child of the remote span `context` identifies
+    fn set_parent(&self, span: &tracing::Span, context: &TraceContext);
+}
+
+static PROPAGATOR: RwLock<Option<Arc<dyn TracePropagator>>> = RwLock::new(None);
+
+/// Use `propagator` for every request sent and handled in this process
+pub fn set_propagator(propagator: Arc<dyn TracePropagator>) {
+    *PROPAGATOR.write().unwrap_or_else(|e| e.into_inner()) = Some(propagator);
+}
+
+fn propagator() -> Option<Arc<dyn TracePropagator>> {
+    PROPAGATOR.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Put the trace context of the current span into the `_meta` of request
+/// params, if a propagator is set and the span belongs to a trace
+pub fn inject_current(params: &mut Option<Value>) {
+    if let Some(context) = propagator().and_then(|propagator| propagator.current()) {
+        context.inject(params);
+    }
+}
+
+/// Make `span` a child of the span a request was sent from, if its params
+/// carry a trace context and a propagator is set
+pub fn set_parent_from_meta(span: &tracing::Span, params: Option<&Value>) {
+    if let (Some(propagator), Some(context)) = (propagator(), TraceContext::from_meta(params)) {
+        propagator.set_parent(span, &context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_traceparent_is_validated() {
+        let context = TraceContext::new(TRACEPARENT, Some("vendor=1".to_string())).unwrap();
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.parent_span_id(), "00f067aa0ba902b7");
+        assert!(context.sampled());
+
+        for invalid in [
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert!(TraceContext::new(invalid, None).is_none(), "{invalid}");
+        }
+        // Later versions may append fields
+        assert!(
+            TraceContext::new(
+                "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra",
+                None
+            )
+            .is_some_and(|context| !context.sampled())
+        );
+    }
+
+    #[test]
+    fn test_context_round_trips_through_meta() {
+        let context = TraceContext::new(TRACEPARENT, Some("vendor=1".to_string())).unwrap();
+        let mut params = Some(json!({"name": "add", "_meta": {"progressToken": 1}}));
+        context.inject(&mut params);
+
+        let meta = &params.as_ref().unwrap()["_meta"];
+        assert_eq!(meta["progressToken"], 1);
+        assert_eq!(meta[TRACEPARENT_META_KEY], TRACEPARENT);
+        assert_eq!(TraceContext::from_meta(params.as_ref()), Some(context));
+        assert_eq!(TraceContext::from_meta(Some(&json!({"name": "add"}))), None);
+    }
+}
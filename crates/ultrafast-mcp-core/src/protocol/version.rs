@@ -9,6 +9,11 @@ pub const PROTOCOL_VERSION: &str = "2025-06-18";
 /// All supported protocol versions (latest first)
 pub const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
 
+/// Version an HTTP server assumes for requests without an
+/// `MCP-Protocol-Version` header when it has no other way to tell, as the
+/// specification requires
+pub const DEFAULT_HTTP_PROTOCOL_VERSION: &str = "2025-03-26";
+
 /// Protocol version representation for comparison
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ProtocolVersion {
@@ -0,0 +1,516 @@
+//! Linting of tool schemas for what breaks downstream hosts
+//!
+//! A schema can be valid JSON Schema and still fail in a host: some cut
+//! tool definitions off at a size, some reject keywords they do not
+//! implement, and models call tools poorly when their arguments are not
+//! described. [`SchemaLintConfig`] checks tools for these [rules], each at
+//! a [`LintLevel`]:
+//!
+//! - [`SchemaTooLarge`]: an input or output schema serializes to more than
+//!   the maximum size, [`DEFAULT_MAX_SCHEMA_SIZE`] unless configured; denied
+//!   by default
+//! - [`UnsupportedKeyword`]: a schema uses one of the unsupported keywords,
+//!   [`DEFAULT_UNSUPPORTED_KEYWORDS`] unless configured; warned about by
+//!   default
+//! - [`MissingDescription`]: the tool, or a property of its input, has no
+//!   description; warned about by default
+//!
+//! ```rust
+//! use serde_json::json;
+//! use ultrafast_mcp_core::schema::lint::{LintLevel, SchemaLintConfig, SchemaLintRule};
+//! use ultrafast_mcp_core::types::tools::Tool;
+//!
+//! let tool = Tool::new(
+//!     "search".to_string(),
+//!     "Search the docs".to_string(),
+//!     json!({"type": "object", "properties": {"query": {"type": "string"}}}),
+//! );
+//! let lint = SchemaLintConfig::default()
+//!     .with_level(SchemaLintRule::MissingDescription, LintLevel::Deny);
+//! let report = lint.lint_tool(&tool);
+//! assert!(report.has_errors());
+//! assert_eq!(report.issues[0].path, "/inputSchema/properties/query");
+//! ```
+//!
+//! [rules]: SchemaLintRule
+//! [`SchemaTooLarge`]: SchemaLintRule::SchemaTooLarge
+//! [`UnsupportedKeyword`]: SchemaLintRule::UnsupportedKeyword
+//! [`MissingDescription`]: SchemaLintRule::MissingDescription
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::tools::Tool;
+
+/// Largest serialized size of a schema, in bytes, unless configured
+pub const DEFAULT_MAX_SCHEMA_SIZE: usize = 64 * 1024;
+
+/// Keywords hosts commonly do not implement, unless configured
+pub const DEFAULT_UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "$dynamicRef",
+    "$dynamicAnchor",
+    "$recursiveRef",
+    "if",
+    "then",
+    "else",
+    "not",
+    "patternProperties",
+    "dependentSchemas",
+    "dependentRequired",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+];
+
+/// Keywords whose value is a map of subschemas
+const SCHEMA_MAP_KEYWORDS: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+];
+
+/// Keywords whose value is a subschema or, for some, a list of them
+const SUBSCHEMA_KEYWORDS: &[&str] = &[
+    "items",
+    "prefixItems",
+    "additionalItems",
+    "additionalProperties",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "propertyNames",
+    "contains",
+    "not",
+    "if",
+    "then",
+    "else",
+    "allOf",
+    "anyOf",
+    "oneOf",
+];
+
+/// What a lint checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaLintRule {
+    SchemaTooLarge,
+    UnsupportedKeyword,
+    MissingDescription,
+}
+
+impl SchemaLintRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SchemaTooLarge => "schema_too_large",
+            Self::UnsupportedKeyword => "unsupported_keyword",
+            Self::MissingDescription => "missing_description",
+        }
+    }
+}
+
+/// What a finding of a rule does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// Nothing; the rule is not checked
+    Allow,
+    /// Reported as a warning
+    Warn,
+    /// Reported as an error, rejecting the tool at registration
+    Deny,
+}
+
+/// Which rules tools are linted for, and how strictly
+#[derive(Debug, Clone)]
+pub struct SchemaLintConfig {
+    max_schema_size: usize,
+    unsupported_keywords: BTreeSet<String>,
+    levels: HashMap<SchemaLintRule, LintLevel>,
+}
+
+impl Default for SchemaLintConfig {
+    fn default() -> Self {
+        Self {
+            max_schema_size: DEFAULT_MAX_SCHEMA_SIZE,
+            unsupported_keywords: DEFAULT_UNSUPPORTED_KEYWORDS
+                .iter()
+                .map(|keyword| keyword.to_string())
+                .collect(),
+            levels: HashMap::from([
+                (SchemaLintRule::SchemaTooLarge, LintLevel::Deny),
+                (SchemaLintRule::UnsupportedKeyword, LintLevel::Warn),
+                (SchemaLintRule::MissingDescription, LintLevel::Warn),
+            ]),
+        }
+    }
+}
+
+impl SchemaLintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest serialized size of an input or output schema, in bytes
+    pub fn with_max_schema_size(mut self, bytes: usize) -> Self {
+        self.max_schema_size = bytes;
+        self
+    }
+
+    /// Also report schemas using `keyword`, such as `$ref` for hosts that
+    /// do not resolve references
+    pub fn with_unsupported_keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.unsupported_keywords.insert(keyword.into());
+        self
+    }
+
+    /// Stop reporting schemas using `keyword`
+    pub fn without_unsupported_keyword(mut self, keyword: &str) -> Self {
+        self.unsupported_keywords.remove(keyword);
+        self
+    }
+
+    /// Set what a finding of `rule` does
+    pub fn with_level(mut self, rule: SchemaLintRule, level: LintLevel) -> Self {
+        self.levels.insert(rule, level);
+        self
+    }
+
+    pub fn max_schema_size(&self) -> usize {
+        self.max_schema_size
+    }
+
+    pub fn level(&self, rule: SchemaLintRule) -> LintLevel {
+        self.levels.get(&rule).copied().unwrap_or(LintLevel::Allow)
+    }
+
+    /// Lint the schemas and descriptions of one tool
+    pub fn lint_tool(&self, tool: &Tool) -> SchemaLintReport {
+        let mut lint = ToolLint {
+            config: self,
+            tool: &tool.name,
+            issues: Vec::new(),
+        };
+        if tool.description.trim().is_empty() {
+            lint.report(
+                SchemaLintRule::MissingDescription,
+                String::new(),
+                "tool has no description".to_string(),
+            );
+        }
+        let schemas = [
+            ("/inputSchema", Some(&tool.input_schema)),
+            ("/outputSchema", tool.output_schema.as_ref()),
+        ];
+        for (path, schema) in schemas {
+            let Some(schema) = schema else {
+                continue;
+            };
+            lint.size(path, schema);
+            lint.keywords(path.to_string(), schema);
+        }
+        lint.descriptions("/inputSchema".to_string(), &tool.input_schema);
+        SchemaLintReport {
+            issues: lint.issues,
+        }
+    }
+
+    /// Lint several tools into one report
+    pub fn lint_tools<'a>(&self, tools: impl IntoIterator<Item = &'a Tool>) -> SchemaLintReport {
+        let mut report = SchemaLintReport::default();
+        for tool in tools {
+            report.issues.extend(self.lint_tool(tool).issues);
+        }
+        report
+    }
+}
+
+/// Issues found while linting one tool
+struct ToolLint<'a> {
+    config: &'a SchemaLintConfig,
+    tool: &'a str,
+    issues: Vec<SchemaLintIssue>,
+}
+
+impl ToolLint<'_> {
+    fn report(&mut self, rule: SchemaLintRule, path: String, message: String) {
+        let level = self.config.level(rule);
+        if level == LintLevel::Allow {
+            return;
+        }
+        self.issues.push(SchemaLintIssue {
+            tool: self.tool.to_string(),
+            rule,
+            level,
+            path,
+            message,
+        });
+    }
+
+    fn size(&mut self, path: &str, schema: &Value) {
+        let size = serde_json::to_vec(schema).map_or(0, |bytes| bytes.len());
+        if size > self.config.max_schema_size {
+            self.report(
+                SchemaLintRule::SchemaTooLarge,
+                path.to_string(),
+                format!(
+                    "schema is {size} bytes, more than the {} allowed",
+                    self.config.max_schema_size
+                ),
+            );
+        }
+    }
+
+    /// Report unsupported keywords of `schema` and its subschemas
+    fn keywords(&mut self, path: String, schema: &Value) {
+        let Some(object) = schema.as_object() else {
+            return;
+        };
+        for (keyword, value) in object {
+            if self.config.unsupported_keywords.contains(keyword) {
+                self.report(
+                    SchemaLintRule::UnsupportedKeyword,
+                    format!("{path}/{}", escape(keyword)),
+                    format!("keyword '{keyword}' is not supported by all hosts"),
+                );
+            }
+            for (subpath, subschema) in subschemas(keyword, value) {
+                self.keywords(format!("{path}/{subpath}"), subschema);
+            }
+        }
+    }
+
+    /// Report properties of `schema` and its subschemas without a
+    /// description
+    fn descriptions(&mut self, path: String, schema: &Value) {
+        let Some(object) = schema.as_object() else {
+            return;
+        };
+        if let Some(Value::Object(properties)) = object.get("properties") {
+            for (name, property) in properties {
+                if property.get("description").is_none() {
+                    self.report(
+                        SchemaLintRule::MissingDescription,
+                        format!("{path}/properties/{}", escape(name)),
+                        format!("property '{name}' has no description"),
+                    );
+                }
+            }
+        }
+        for (keyword, value) in object {
+            for (subpath, subschema) in subschemas(keyword, value) {
+                self.descriptions(format!("{path}/{subpath}"), subschema);
+            }
+        }
+    }
+}
+
+/// The subschemas held by `keyword`, with their JSON pointers relative to
+/// the schema holding it
+fn subschemas<'a>(keyword: &str, value: &'a Value) -> Vec<(String, &'a Value)> {
+    let keyword_path = escape(keyword);
+    if SCHEMA_MAP_KEYWORDS.contains(&keyword) {
+        let Some(map) = value.as_object() else {
+            return Vec::new();
+        };
+        return map
+            .iter()
+            .map(|(name, schema)| (format!("{keyword_path}/{}", escape(name)), schema))
+            .collect();
+    }
+    if !SUBSCHEMA_KEYWORDS.contains(&keyword) {
+        return Vec::new();
+    }
+    match value {
+        Value::Array(schemas) => schemas
+            .iter()
+            .enumerate()
+            .map(|(index, schema)| (format!("{keyword_path}/{index}"), schema))
+            .collect(),
+        schema => vec![(keyword_path, schema)],
+    }
+}
+
+/// Escape a JSON pointer segment
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// One finding of a lint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaLintIssue {
+    /// Name of the tool
+    pub tool: String,
+    pub rule: SchemaLintRule,
+    /// [`LintLevel::Warn`] or [`LintLevel::Deny`]
+    pub level: LintLevel,
+    /// JSON pointer into the tool definition, such as
+    /// `/inputSchema/properties/query`; empty for the tool itself
+    pub path: String,
+    pub message: String,
+}
+
+impl SchemaLintIssue {
+    pub fn is_error(&self) -> bool {
+        self.level == LintLevel::Deny
+    }
+}
+
+impl fmt::Display for SchemaLintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = if self.is_error() { "error" } else { "warning" };
+        write!(f, "{level}[{}]: tool '{}'", self.rule.as_str(), self.tool)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The issues found by linting tools
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaLintReport {
+    pub issues: Vec<SchemaLintIssue>,
+}
+
+impl SchemaLintReport {
+    /// Whether no issues were found
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Whether any issue is denied
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(SchemaLintIssue::is_error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &SchemaLintIssue> {
+        self.issues.iter().filter(|issue| issue.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &SchemaLintIssue> {
+        self.issues.iter().filter(|issue| !issue.is_error())
+    }
+}
+
+impl fmt::Display for SchemaLintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, issue) in self.issues.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(input_schema: Value) -> Tool {
+        Tool::new("search".to_string(), "Search".to_string(), input_schema)
+    }
+
+    fn rules(report: &SchemaLintReport) -> Vec<(SchemaLintRule, &str)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.rule, issue.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_described_schemas_are_clean() {
+        let report = SchemaLintConfig::default().lint_tool(&tool(json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "Words to find"},
+                "filters": {
+                    "type": "object",
+                    "description": "Narrow the results",
+                    "properties": {
+                        "if": {"type": "string", "description": "A property named like a keyword"}
+                    }
+                }
+            }
+        })));
+        assert!(report.is_clean(), "{report}");
+    }
+
+    #[test]
+    fn test_unsupported_keywords_and_missing_descriptions_are_found_in_subschemas() {
+        let report = SchemaLintConfig::default()
+            .with_unsupported_keyword("$ref")
+            .lint_tool(&tool(json!({
+                "type": "object",
+                "properties": {
+                    "page": {"$ref": "#/$defs/Page", "description": "Which page"},
+                    "sort": {"anyOf": [{"type": "string", "not": {"const": ""}}]}
+                },
+                "$defs": {
+                    "Page": {"type": "object", "properties": {"size": {"type": "integer"}}}
+                }
+            })));
+        let mut found = rules(&report);
+        found.sort_by_key(|(_, path)| *path);
+        assert_eq!(
+            found,
+            [
+                (
+                    SchemaLintRule::MissingDescription,
+                    "/inputSchema/$defs/Page/properties/size"
+                ),
+                (
+                    SchemaLintRule::UnsupportedKeyword,
+                    "/inputSchema/properties/page/$ref"
+                ),
+                (
+                    SchemaLintRule::MissingDescription,
+                    "/inputSchema/properties/sort"
+                ),
+                (
+                    SchemaLintRule::UnsupportedKeyword,
+                    "/inputSchema/properties/sort/anyOf/0/not"
+                ),
+            ]
+        );
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn test_levels_and_size_limits() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"query": {"type": "string", "description": "x".repeat(100)}}
+        });
+        let report = SchemaLintConfig::default()
+            .with_max_schema_size(64)
+            .lint_tool(&tool(schema.clone()));
+        assert_eq!(
+            rules(&report),
+            [(SchemaLintRule::SchemaTooLarge, "/inputSchema")]
+        );
+        assert!(report.has_errors());
+        assert!(
+            report
+                .to_string()
+                .starts_with("error[schema_too_large]: tool 'search' at /inputSchema: schema is")
+        );
+
+        let allowed = SchemaLintConfig::default()
+            .with_max_schema_size(64)
+            .with_level(SchemaLintRule::SchemaTooLarge, LintLevel::Allow)
+            .lint_tool(&tool(schema));
+        assert!(allowed.is_clean());
+
+        let undescribed = Tool::new("bare".to_string(), " ".to_string(), json!({}));
+        let report = SchemaLintConfig::default().lint_tools([&undescribed]);
+        assert_eq!(rules(&report), [(SchemaLintRule::MissingDescription, "")]);
+        assert_eq!(report.warnings().count(), 1);
+    }
+}
@@ -1592,15 +1592,15 @@ impl MCPMessageValidator {
         }
 
         // Validate that response has either result or error, but not both
-        match (&response.result, &response.error) {
-            (Some(_), Some(_)) => {
+        match (response.has_result(), &response.error) {
+            (true, Some(_)) => {
                 report.add_error(ValidationError::new(
                     "response".to_string(),
                     "Response cannot have both result and error".to_string(),
                     ErrorSeverity::High,
                 ));
             }
-            (None, None) => {
+            (false, None) => {
                 report.add_error(ValidationError::new(
                     "response".to_string(),
                     "Response must have either result or error".to_string(),
@@ -2881,6 +2881,7 @@ mod mcp_message_validator_tests {
         let response = JsonRpcResponse {
             jsonrpc: Cow::Borrowed("2.0"),
             result: Some(json!({"status": "ok"})),
+            raw_result: None,
             error: Some(JsonRpcError::new(-32600, "Invalid request".to_string())),
             id: Some(RequestId::String("1".to_string())),
             meta: std::collections::HashMap::new(),
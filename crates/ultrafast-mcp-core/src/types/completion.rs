@@ -10,6 +10,7 @@ pub struct CompletionReference {
     #[serde(rename = "type")]
     pub ref_type: String,
     /// Reference name (prompt name or resource URI)
+    #[serde(alias = "uri")]
     pub name: String,
 }
 
@@ -360,6 +361,18 @@ impl CompletionValue {
     }
 }
 
+impl From<&str> for CompletionValue {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for CompletionValue {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
 impl Completion {
     /// Create a new completion set
     pub fn new(values: Vec<CompletionValue>) -> Self {
@@ -1,9 +1,45 @@
 //! Elicitation types for MCP
 //!
 //! Server-initiated user input collection according to MCP specification 2025-06-18
+//!
+//! Requests are built with an [`ElicitationBuilder`], which writes the flat
+//! object schema the specification allows from one [`ElicitationField`] per
+//! answer. [`ElicitationResponse::parse`] checks an answer against that
+//! schema and reads it into a typed [`ElicitationResult`]:
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use serde_json::json;
+//! use ultrafast_mcp_core::types::elicitation::{
+//!     ElicitationBuilder, ElicitationField, ElicitationResponse, ElicitationResult,
+//! };
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct Confirmation {
+//!     confirm: bool,
+//!     reason: Option<String>,
+//! }
+//!
+//! let request = ElicitationBuilder::new("Confirm deletion")
+//!     .field("confirm", ElicitationField::boolean())
+//!     .field("reason", ElicitationField::string().optional())
+//!     .build();
+//!
+//! let response = ElicitationResponse::accept(json!({ "confirm": true }));
+//! assert_eq!(
+//!     response.parse::<Confirmation>(&request).unwrap(),
+//!     ElicitationResult::Accepted(Confirmation { confirm: true, reason: None })
+//! );
+//! ```
 
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    error::{MCPError, MCPResult},
+    schema::validation::validate_against_schema,
+};
 
 /// Server-initiated request for user input
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -30,6 +66,163 @@ impl Default for ElicitationRequest {
     }
 }
 
+/// One answer asked for by an [`ElicitationBuilder`]
+///
+/// Fields are required unless made [`optional`](Self::optional).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElicitationField {
+    schema: Map<String, Value>,
+    required: bool,
+}
+
+impl ElicitationField {
+    fn of_type(kind: &str) -> Self {
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), json!(kind));
+        Self {
+            schema,
+            required: true,
+        }
+    }
+
+    pub fn string() -> Self {
+        Self::of_type("string")
+    }
+
+    pub fn number() -> Self {
+        Self::of_type("number")
+    }
+
+    pub fn integer() -> Self {
+        Self::of_type("integer")
+    }
+
+    pub fn boolean() -> Self {
+        Self::of_type("boolean")
+    }
+
+    /// A string chosen from `values`
+    pub fn one_of<I>(values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let values: Vec<String> = values.into_iter().map(Into::into).collect();
+        Self::string().with("enum", json!(values))
+    }
+
+    /// Let the user leave the field out
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.with("title", json!(title.into()))
+    }
+
+    pub fn description(self, description: impl Into<String>) -> Self {
+        self.with("description", json!(description.into()))
+    }
+
+    /// Value the client shows before the user answers
+    pub fn default_value(self, value: impl Into<Value>) -> Self {
+        self.with("default", value.into())
+    }
+
+    /// Display names for the values of a [`one_of`](Self::one_of) field
+    pub fn value_names<I>(self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        self.with("enumNames", json!(names))
+    }
+
+    pub fn min_length(self, min_length: usize) -> Self {
+        self.with("minLength", json!(min_length))
+    }
+
+    pub fn max_length(self, max_length: usize) -> Self {
+        self.with("maxLength", json!(max_length))
+    }
+
+    /// String format: `email`, `uri`, `date` or `date-time`
+    pub fn format(self, format: impl Into<String>) -> Self {
+        self.with("format", json!(format.into()))
+    }
+
+    pub fn minimum(self, minimum: f64) -> Self {
+        self.with("minimum", json!(minimum))
+    }
+
+    pub fn maximum(self, maximum: f64) -> Self {
+        self.with("maximum", json!(maximum))
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// The field's JSON Schema
+    pub fn schema(&self) -> Value {
+        Value::Object(self.schema.clone())
+    }
+
+    fn with(mut self, key: &str, value: Value) -> Self {
+        self.schema.insert(key.to_string(), value);
+        self
+    }
+}
+
+/// Builds an [`ElicitationRequest`] field by field
+#[derive(Debug, Clone)]
+pub struct ElicitationBuilder {
+    message: String,
+    properties: Map<String, Value>,
+    required: Vec<String>,
+}
+
+impl ElicitationBuilder {
+    /// Start a request showing `message` to the user
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            properties: Map::new(),
+            required: Vec::new(),
+        }
+    }
+
+    /// Ask for `name`; a field given twice replaces the earlier one
+    pub fn field(mut self, name: impl Into<String>, field: ElicitationField) -> Self {
+        let name = name.into();
+        self.required.retain(|required| *required != name);
+        if field.required {
+            self.required.push(name.clone());
+        }
+        self.properties.insert(name, Value::Object(field.schema));
+        self
+    }
+
+    pub fn build(self) -> ElicitationRequest {
+        ElicitationRequest {
+            message: self.message,
+            requested_schema: json!({
+                "type": "object",
+                "properties": self.properties,
+                "required": self.required,
+            }),
+        }
+    }
+}
+
+impl From<ElicitationBuilder> for ElicitationRequest {
+    fn from(builder: ElicitationBuilder) -> Self {
+        builder.build()
+    }
+}
+
 /// User response to elicitation request
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ElicitationResponse {
@@ -62,6 +255,72 @@ impl Default for ElicitationResponse {
     }
 }
 
+impl ElicitationResponse {
+    /// The user accepted and answered with `content`
+    pub fn accept(content: Value) -> Self {
+        Self {
+            action: ElicitationAction::Accept,
+            content: Some(content),
+        }
+    }
+
+    pub fn decline() -> Self {
+        Self {
+            action: ElicitationAction::Decline,
+            content: None,
+        }
+    }
+
+    pub fn cancel() -> Self {
+        Self {
+            action: ElicitationAction::Cancel,
+            content: None,
+        }
+    }
+
+    /// Check an accepted answer against the schema of `request` and read it
+    /// as `T`
+    ///
+    /// Answers that do not match the schema or cannot be read as `T` are
+    /// invalid params errors.
+    pub fn parse<T: DeserializeOwned>(
+        self,
+        request: &ElicitationRequest,
+    ) -> MCPResult<ElicitationResult<T>> {
+        let content = match self.action {
+            ElicitationAction::Decline => return Ok(ElicitationResult::Declined),
+            ElicitationAction::Cancel => return Ok(ElicitationResult::Cancelled),
+            ElicitationAction::Accept => self.content.unwrap_or_else(|| json!({})),
+        };
+        validate_against_schema(&content, &request.requested_schema).map_err(|e| {
+            MCPError::invalid_params(format!(
+                "Elicitation answer does not match the requested schema: {e}"
+            ))
+        })?;
+        serde_json::from_value(content)
+            .map(ElicitationResult::Accepted)
+            .map_err(|e| MCPError::invalid_params(format!("Invalid elicitation answer: {e}")))
+    }
+}
+
+/// A user's answer read as `T`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElicitationResult<T> {
+    Accepted(T),
+    Declined,
+    Cancelled,
+}
+
+impl<T> ElicitationResult<T> {
+    /// The answer, if the user accepted
+    pub fn accepted(self) -> Option<T> {
+        match self {
+            ElicitationResult::Accepted(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +446,94 @@ mod tests {
         assert!(boolean_schema.is_object());
         assert!(selection_schema.is_object());
     }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Deletion {
+        confirm: bool,
+        reason: Option<String>,
+        retries: Option<u32>,
+    }
+
+    fn deletion_request() -> ElicitationRequest {
+        ElicitationBuilder::new("Confirm deletion")
+            .field("confirm", ElicitationField::boolean().title("Confirm"))
+            .field(
+                "reason",
+                ElicitationField::string().optional().max_length(20),
+            )
+            .field(
+                "retries",
+                ElicitationField::integer().optional().minimum(0.0),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_builder_writes_flat_object_schema() {
+        let request = deletion_request();
+        assert_eq!(request.message, "Confirm deletion");
+        assert_eq!(
+            request.requested_schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "confirm": { "type": "boolean", "title": "Confirm" },
+                    "reason": { "type": "string", "maxLength": 20 },
+                    "retries": { "type": "integer", "minimum": 0.0 }
+                },
+                "required": ["confirm"]
+            })
+        );
+
+        let request = ElicitationBuilder::new("Pick")
+            .field("env", ElicitationField::one_of(["dev", "prod"]).optional())
+            .field("env", ElicitationField::one_of(["dev", "prod"]))
+            .build();
+        assert_eq!(request.requested_schema["required"], json!(["env"]));
+        assert_eq!(
+            request.requested_schema["properties"]["env"]["enum"],
+            json!(["dev", "prod"])
+        );
+    }
+
+    #[test]
+    fn test_responses_are_validated_and_typed() {
+        let request = deletion_request();
+
+        let answer = ElicitationResponse::accept(json!({ "confirm": true, "reason": "stale" }))
+            .parse::<Deletion>(&request)
+            .unwrap();
+        assert_eq!(
+            answer,
+            ElicitationResult::Accepted(Deletion {
+                confirm: true,
+                reason: Some("stale".to_string()),
+                retries: None,
+            })
+        );
+
+        assert!(
+            ElicitationResponse::accept(json!({ "reason": "stale" }))
+                .parse::<Deletion>(&request)
+                .is_err()
+        );
+        assert!(
+            ElicitationResponse::accept(json!({ "confirm": "yes" }))
+                .parse::<Deletion>(&request)
+                .is_err()
+        );
+        assert_eq!(
+            ElicitationResponse::decline()
+                .parse::<Deletion>(&request)
+                .unwrap(),
+            ElicitationResult::Declined
+        );
+        assert_eq!(
+            ElicitationResponse::cancel()
+                .parse::<Deletion>(&request)
+                .unwrap()
+                .accepted(),
+            None
+        );
+    }
 }
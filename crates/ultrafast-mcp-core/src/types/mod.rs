@@ -92,6 +92,8 @@
 //! let tool_result = ToolResult {
 //!     content: vec![ToolContent::text("Hello there, Alice!".to_string())],
 //!     is_error: Some(false),
+//!     structured_content: None,
+//!     progress_summary: None,
 //! };
 //! ```
 //!
@@ -111,9 +113,7 @@
 //! };
 //!
 //! // Create a read request
-//! let read_request = ReadResourceRequest {
-//!     uri: "file:///path/to/document.txt".to_string(),
-//! };
+//! let read_request = ReadResourceRequest::new("file:///path/to/document.txt");
 //!
 //! // Create a read response
 //! let read_response = ReadResourceResponse {
@@ -1,5 +1,7 @@
 //! Notification types for MCP 2025-06-18 protocol
 
+use crate::error::{MCPError, MCPResult};
+use base64::{Engine, engine::general_purpose::STANDARD};
 use serde::{Deserialize, Serialize};
 
 /// Tools list changed notification
@@ -434,3 +436,129 @@ impl Default for RateLimitNotification {
         }
     }
 }
+
+/// `_meta` key holding a notification's per-session sequence number
+///
+/// Servers that number their notifications start at 1 for each session and
+/// increase by one per notification, letting clients detect notifications
+/// lost or reordered by a transport.
+pub const NOTIFICATION_SEQUENCE_META_KEY: &str = "ultrafast/seq";
+
+/// `_meta` key holding the id a client acknowledges a notification with
+///
+/// Notifications carrying it are resent until the client answers with a
+/// [`NOTIFICATION_ACK_METHOD`] notification or they expire.
+pub const NOTIFICATION_ACK_META_KEY: &str = "ultrafast/ackId";
+
+/// Vendor notification a client sends to acknowledge a notification
+pub const NOTIFICATION_ACK_METHOD: &str = "notifications/ultrafast/ack";
+
+/// Parameters of a [`NOTIFICATION_ACK_METHOD`] notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationAck {
+    pub ack_id: String,
+}
+
+/// Vendor notification carrying a piece of a tool's result, sent while the
+/// tool still runs
+pub const TOOL_CONTENT_CHUNK_METHOD: &str = "notifications/ultrafast/toolContent";
+
+/// Parameters of a [`TOOL_CONTENT_CHUNK_METHOD`] notification
+///
+/// The chunk belongs to the `tools/call` request the client sent
+/// `progress_token` with; `index` numbers the chunks of one call from 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolContentChunk {
+    pub progress_token: serde_json::Value,
+    pub index: u64,
+    pub content: super::tools::ToolContent,
+}
+
+/// Vendor notification carrying bytes of a resource read with
+/// [`READ_RESOURCE_STREAM_METHOD`](super::resources::READ_RESOURCE_STREAM_METHOD)
+pub const RESOURCE_CHUNK_METHOD: &str = "notifications/ultrafast/resourceChunk";
+
+/// Parameters of a [`RESOURCE_CHUNK_METHOD`] notification
+///
+/// The chunk belongs to the `resources/readStream` request the client sent
+/// `progress_token` with, and starts `offset` bytes into the resource.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceChunk {
+    pub progress_token: serde_json::Value,
+    pub offset: u64,
+    /// The bytes, base64 encoded
+    pub data: String,
+}
+
+impl ResourceChunk {
+    pub fn new(progress_token: serde_json::Value, offset: u64, bytes: &[u8]) -> Self {
+        Self {
+            progress_token,
+            offset,
+            data: STANDARD.encode(bytes),
+        }
+    }
+
+    pub fn bytes(&self) -> MCPResult<Vec<u8>> {
+        STANDARD
+            .decode(&self.data)
+            .map_err(|e| MCPError::invalid_response(format!("Invalid base64 chunk: {e}")))
+    }
+}
+
+/// Stamp `sequence` into the `_meta` object of notification `params`
+///
+/// Params that are not an object (or absent) are replaced by an object
+/// holding only `_meta`, except arrays which are left untouched.
+pub fn set_notification_sequence(params: &mut Option<serde_json::Value>, sequence: u64) {
+    insert_meta(
+        params,
+        NOTIFICATION_SEQUENCE_META_KEY,
+        serde_json::Value::from(sequence),
+    );
+}
+
+/// Read the sequence number stamped by [`set_notification_sequence`]
+pub fn notification_sequence(params: Option<&serde_json::Value>) -> Option<u64> {
+    params?
+        .get("_meta")?
+        .get(NOTIFICATION_SEQUENCE_META_KEY)?
+        .as_u64()
+}
+
+/// Stamp `ack_id` into the `_meta` object of notification `params`,
+/// asking the client to acknowledge it
+pub fn set_notification_ack_id(params: &mut Option<serde_json::Value>, ack_id: &str) {
+    insert_meta(
+        params,
+        NOTIFICATION_ACK_META_KEY,
+        serde_json::Value::from(ack_id),
+    );
+}
+
+/// Read the id stamped by [`set_notification_ack_id`]
+pub fn notification_ack_id(params: Option<&serde_json::Value>) -> Option<&str> {
+    params?
+        .get("_meta")?
+        .get(NOTIFICATION_ACK_META_KEY)?
+        .as_str()
+}
+
+fn insert_meta(params: &mut Option<serde_json::Value>, key: &str, value: serde_json::Value) {
+    let params = params.get_or_insert_with(|| serde_json::json!({}));
+    if params.is_null() {
+        *params = serde_json::json!({});
+    }
+    let Some(object) = params.as_object_mut() else {
+        return;
+    };
+    let meta = object
+        .entry("_meta")
+        .or_insert_with(|| serde_json::json!({}));
+    if let Some(meta) = meta.as_object_mut() {
+        meta.insert(key.to_string(), value);
+    }
+}
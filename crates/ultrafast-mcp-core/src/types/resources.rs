@@ -1,3 +1,6 @@
+use crate::content_type::{charset_of, decode_text};
+use crate::error::{MCPError, MCPResult};
+use base64::{Engine, engine::general_purpose::STANDARD};
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -204,6 +207,46 @@ impl ResourceTemplate {
         variables
     }
 
+    /// The variables of `uri`, if it is an expansion of this template
+    ///
+    /// A `{name}` variable matches within one path segment and a `{+name}`
+    /// variable across segments. Values are percent-decoded.
+    pub fn match_uri(&self, uri: &str) -> Option<HashMap<String, String>> {
+        let mut pattern = String::from("^");
+        let mut names = Vec::new();
+        let mut literal_start = 0;
+        for cap in TEMPLATE_VAR_PATTERN.captures_iter(&self.uri_template) {
+            let variable = cap.get(0)?;
+            pattern.push_str(&regex::escape(
+                &self.uri_template[literal_start..variable.start()],
+            ));
+            let name = cap[1].trim();
+            match name.strip_prefix('+') {
+                Some(name) => {
+                    names.push(name.to_string());
+                    pattern.push_str("(.+)");
+                }
+                None => {
+                    names.push(name.to_string());
+                    pattern.push_str("([^/?#]+)");
+                }
+            }
+            literal_start = variable.end();
+        }
+        pattern.push_str(&regex::escape(&self.uri_template[literal_start..]));
+        pattern.push('$');
+
+        let captures = Regex::new(&pattern).ok()?.captures(uri)?;
+        names
+            .into_iter()
+            .zip(captures.iter().skip(1))
+            .map(|(name, value)| {
+                let value = urlencoding::decode(value?.as_str()).ok()?.into_owned();
+                Some((name, value))
+            })
+            .collect()
+    }
+
     /// Expand the URI template with provided variables
     pub fn expand(&self, variables: &HashMap<String, String>) -> Result<String, TemplateError> {
         self.expand_with_options(variables, &TemplateExpansionOptions::default())
@@ -515,6 +558,60 @@ pub enum TemplateError {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ReadResourceRequest {
     pub uri: String,
+    /// Only read these bytes of the resource's blob contents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<ByteRange>,
+}
+
+impl ReadResourceRequest {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            range: None,
+        }
+    }
+
+    pub fn with_range(mut self, range: ByteRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+}
+
+/// A span of a resource's bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ByteRange {
+    pub offset: u64,
+    /// Bytes to read from `offset`; `None` reads to the end
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+}
+
+impl ByteRange {
+    pub fn new(offset: u64, length: u64) -> Self {
+        Self {
+            offset,
+            length: Some(length),
+        }
+    }
+
+    /// From `offset` to the end
+    pub fn from_offset(offset: u64) -> Self {
+        Self {
+            offset,
+            length: None,
+        }
+    }
+
+    /// The part of `bytes` in the range, empty if it starts past the end
+    pub fn slice<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        let start =
+            usize::try_from(self.offset).map_or(bytes.len(), |offset| offset.min(bytes.len()));
+        let rest = &bytes[start..];
+        match self.length.and_then(|length| usize::try_from(length).ok()) {
+            Some(length) => &rest[..length.min(rest.len())],
+            None => rest,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -534,6 +631,43 @@ pub struct ReadResourceResponse {
     pub contents: Vec<ResourceContent>,
 }
 
+/// Extension method reading a resource's bytes in chunks, so a large blob
+/// is not sent as one base64 message
+///
+/// The client sends a progress token in `_meta`; the bytes arrive as
+/// [`RESOURCE_CHUNK_METHOD`](super::notifications::RESOURCE_CHUNK_METHOD)
+/// notifications tied to it, followed by a [`ReadResourceStreamResponse`].
+pub const READ_RESOURCE_STREAM_METHOD: &str = "resources/readStream";
+
+/// Parameters of a [`READ_RESOURCE_STREAM_METHOD`] request
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadResourceStreamRequest {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<ByteRange>,
+    /// Largest chunk the client wants, in bytes before base64 encoding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<u32>,
+}
+
+/// Result of a [`READ_RESOURCE_STREAM_METHOD`] request, sent after the last
+/// chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadResourceStreamResponse {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Where the bytes sent start in the resource
+    pub offset: u64,
+    /// Bytes sent in chunks
+    pub length: u64,
+    /// Size of the whole resource, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
 /// Resource content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -664,6 +798,40 @@ impl ResourceContent {
             mime_type,
         }
     }
+
+    /// Blob content holding `bytes`, base64 encoded
+    pub fn blob_from_bytes(uri: String, bytes: &[u8], mime_type: String) -> Self {
+        Self::blob(uri, STANDARD.encode(bytes), mime_type)
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            Self::Text { mime_type, .. } => mime_type.as_deref(),
+            Self::Blob { mime_type, .. } => Some(mime_type),
+        }
+    }
+
+    /// The content's bytes: a blob decoded, or text as UTF-8
+    pub fn to_bytes(&self) -> MCPResult<Vec<u8>> {
+        match self {
+            Self::Text { text, .. } => Ok(text.as_bytes().to_vec()),
+            Self::Blob { blob, .. } => STANDARD
+                .decode(blob)
+                .map_err(|e| MCPError::invalid_response(format!("Invalid base64 blob: {e}"))),
+        }
+    }
+
+    /// The content as text: text as is, or a blob decoded in the charset of
+    /// its MIME type, else the one its bytes suggest
+    pub fn to_text(&self) -> MCPResult<String> {
+        match self {
+            Self::Text { text, .. } => Ok(text.clone()),
+            Self::Blob { mime_type, .. } => {
+                let bytes = self.to_bytes()?;
+                Ok(decode_text(&bytes, charset_of(mime_type)).text)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -725,6 +893,35 @@ mod tests {
         assert_eq!(result, "https://api.example.com/users/123/posts/456");
     }
 
+    #[test]
+    fn test_match_uri() {
+        let template = ResourceTemplate::new(
+            "https://api.example.com/users/{user_id}/posts/{post_id}".to_string(),
+            "user_post".to_string(),
+        );
+        let variables = template
+            .match_uri("https://api.example.com/users/12%203/posts/456")
+            .unwrap();
+        assert_eq!(variables["user_id"], "12 3");
+        assert_eq!(variables["post_id"], "456");
+        assert!(
+            template
+                .match_uri("https://api.example.com/users/1/2/posts/3")
+                .is_none()
+        );
+        assert!(
+            template
+                .match_uri("https://api.example.com/users/1")
+                .is_none()
+        );
+
+        let files = ResourceTemplate::new("file:///{+path}".to_string(), "files".to_string());
+        assert_eq!(
+            files.match_uri("file:///src/main.rs").unwrap()["path"],
+            "src/main.rs"
+        );
+    }
+
     #[test]
     fn test_expand_template_missing_variable() {
         let template = ResourceTemplate::new(
@@ -1092,4 +1289,47 @@ mod tests {
             _ => panic!("Expected Blob variant"),
         }
     }
+
+    #[test]
+    fn test_byte_range_slice() {
+        let bytes = b"0123456789";
+        assert_eq!(ByteRange::new(2, 3).slice(bytes), b"234");
+        assert_eq!(ByteRange::new(8, 5).slice(bytes), b"89");
+        assert_eq!(ByteRange::from_offset(4).slice(bytes), b"456789");
+        assert!(ByteRange::from_offset(20).slice(bytes).is_empty());
+
+        let request = ReadResourceRequest::new("file:///big.bin").with_range(ByteRange::new(2, 3));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({"uri": "file:///big.bin", "range": {"offset": 2, "length": 3}})
+        );
+    }
+
+    #[test]
+    fn test_blob_bytes_round_trip() {
+        let content = ResourceContent::blob_from_bytes(
+            "file:///big.bin".to_string(),
+            &[0, 159, 255],
+            "application/octet-stream".to_string(),
+        );
+        assert_eq!(content.to_bytes().unwrap(), [0, 159, 255]);
+        assert_eq!(content.mime_type(), Some("application/octet-stream"));
+    }
+
+    #[test]
+    fn test_blob_text_is_decoded_in_its_charset() {
+        let latin1 = ResourceContent::blob_from_bytes(
+            "file:///menu.txt".to_string(),
+            b"caf\xe9 \xa4",
+            "text/plain; charset=iso-8859-15".to_string(),
+        );
+        assert_eq!(latin1.to_text().unwrap(), "café €");
+
+        let undeclared = ResourceContent::blob_from_bytes(
+            "file:///menu.txt".to_string(),
+            b"caf\xe9",
+            "text/plain".to_string(),
+        );
+        assert_eq!(undeclared.to_text().unwrap(), "café");
+    }
 }
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 
 /// Type aliases for consistency
@@ -14,11 +14,11 @@ use serde_json::Value;
 ///
 /// // Preferred: Use the full type names
 /// let request = ToolCallRequest { name: "my_tool".to_string(), arguments: None };
-/// let response = ToolCallResponse { content: vec![], is_error: None };
+/// let response = ToolCallResponse { content: vec![], is_error: None, structured_content: None, progress_summary: None };
 ///
 /// // Legacy: Using aliases (still works but less clear)
 /// let request: ToolCall = ToolCallRequest { name: "my_tool".to_string(), arguments: None };
-/// let response: ToolResult = ToolCallResponse { content: vec![], is_error: None };
+/// let response: ToolResult = ToolCallResponse { content: vec![], is_error: None, structured_content: None, progress_summary: None };
 /// ```
 pub type ToolCall = ToolCallRequest;
 pub type ToolResult = ToolCallResponse;
@@ -121,6 +121,44 @@ impl ToolAnnotations {
         self.open_world_hint = Some(open_world);
         self
     }
+
+    /// Whether the tool leaves its environment unmodified (default: false)
+    pub fn is_read_only(&self) -> bool {
+        self.read_only_hint.unwrap_or(false)
+    }
+
+    /// Whether the tool may perform destructive updates
+    ///
+    /// Read-only tools are never destructive; otherwise the spec default is
+    /// true.
+    pub fn is_destructive(&self) -> bool {
+        !self.is_read_only() && self.destructive_hint.unwrap_or(true)
+    }
+
+    /// Whether repeated calls with the same arguments have no additional
+    /// effect (default: false, always true for read-only tools)
+    pub fn is_idempotent(&self) -> bool {
+        self.is_read_only() || self.idempotent_hint.unwrap_or(false)
+    }
+
+    /// Whether the tool interacts with external entities (default: true)
+    pub fn is_open_world(&self) -> bool {
+        self.open_world_hint.unwrap_or(true)
+    }
+
+    /// Short labels for the hints that are explicitly set, for display
+    pub fn hint_labels(&self) -> Vec<&'static str> {
+        let hints = [
+            (self.read_only_hint, "read-only", "writes"),
+            (self.destructive_hint, "destructive", "non-destructive"),
+            (self.idempotent_hint, "idempotent", "non-idempotent"),
+            (self.open_world_hint, "open-world", "closed-world"),
+        ];
+        hints
+            .into_iter()
+            .filter_map(|(hint, yes, no)| hint.map(|hint| if hint { yes } else { no }))
+            .collect()
+    }
 }
 
 /// Tool definition
@@ -165,6 +203,71 @@ pub struct ToolCallResponse {
     /// Whether the tool execution was cancelled
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+
+    /// Result as JSON, matching the tool's output schema if it has one
+    #[serde(
+        rename = "structuredContent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub structured_content: Option<Value>,
+
+    /// What the call did, recorded from the progress it reported
+    #[serde(
+        rename = "progressSummary",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub progress_summary: Option<ToolProgressSummary>,
+}
+
+impl ToolCallResponse {
+    /// A successful result carrying `value` as structured content
+    ///
+    /// The value is also serialized into a text content block for clients
+    /// that predate `structuredContent`.
+    pub fn structured(value: Value) -> Self {
+        Self {
+            content: vec![ToolContent::text(value.to_string())],
+            is_error: None,
+            structured_content: Some(value),
+            progress_summary: None,
+        }
+    }
+
+    /// Deserialize the structured content, if the result has any
+    pub fn structured_content_as<T: DeserializeOwned>(&self) -> Option<serde_json::Result<T>> {
+        self.structured_content
+            .as_ref()
+            .map(|value| T::deserialize(value))
+    }
+}
+
+/// Record of the progress a tool call reported, for clients that missed the
+/// live `notifications/progress`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolProgressSummary {
+    /// Duration of the call in milliseconds
+    pub duration_ms: u64,
+    /// Number of progress updates the handler reported
+    pub steps_completed: u64,
+    /// The reported updates, oldest first; only the most recent are kept
+    /// for long calls
+    pub checkpoints: Vec<ProgressCheckpoint>,
+}
+
+/// One progress update of a tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressCheckpoint {
+    /// Milliseconds since the call started
+    pub elapsed_ms: u64,
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 /// Tool content (result of tool execution)
@@ -256,6 +359,42 @@ impl Tool {
         self
     }
 
+    /// Mark the tool as read-only, keeping any other hints already set
+    pub fn read_only(self) -> Self {
+        self.with_hint(|annotations| annotations.with_read_only_hint(true))
+    }
+
+    /// Mark the tool as destructive, keeping any other hints already set
+    pub fn destructive(self) -> Self {
+        self.with_hint(|annotations| {
+            annotations
+                .with_read_only_hint(false)
+                .with_destructive_hint(true)
+        })
+    }
+
+    /// Mark the tool as idempotent, keeping any other hints already set
+    pub fn idempotent(self) -> Self {
+        self.with_hint(|annotations| annotations.with_idempotent_hint(true))
+    }
+
+    /// Mark the tool as interacting with an open world, keeping any other
+    /// hints already set
+    pub fn open_world(self) -> Self {
+        self.with_hint(|annotations| annotations.with_open_world_hint(true))
+    }
+
+    /// Mark the tool as only touching a closed set of entities, keeping any
+    /// other hints already set
+    pub fn closed_world(self) -> Self {
+        self.with_hint(|annotations| annotations.with_open_world_hint(false))
+    }
+
+    fn with_hint(mut self, set: impl FnOnce(ToolAnnotations) -> ToolAnnotations) -> Self {
+        self.annotations = Some(set(self.annotations.take().unwrap_or_default()));
+        self
+    }
+
     /// Validate the tool definition
     pub fn validate(&self) -> Result<(), crate::error::ToolError> {
         // Validate name
@@ -355,6 +494,25 @@ impl ToolContent {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_structured_result_round_trip() {
+        let result = ToolCallResponse::structured(serde_json::json!({"sum": 3}));
+        let wire = serde_json::to_value(&result).unwrap();
+        assert_eq!(wire["structuredContent"]["sum"], 3);
+        assert_eq!(wire["content"][0]["text"], r#"{"sum":3}"#);
+
+        let parsed: ToolCallResponse = serde_json::from_value(wire).unwrap();
+        let value: std::collections::HashMap<String, u32> =
+            parsed.structured_content_as().unwrap().unwrap();
+        assert_eq!(value["sum"], 3);
+
+        let plain: ToolCallResponse = serde_json::from_value(serde_json::json!({
+            "content": [{"type": "text", "text": "hi"}]
+        }))
+        .unwrap();
+        assert!(plain.structured_content_as::<Value>().is_none());
+    }
+
     #[test]
     fn test_tool_validation() {
         // Valid tool
@@ -466,6 +624,45 @@ mod tests {
         assert_eq!(annotations.read_only_hint, Some(true));
     }
 
+    #[test]
+    fn test_chained_hints_combine() {
+        let tool = Tool::new(
+            "delete_file".to_string(),
+            "Delete a file".to_string(),
+            serde_json::json!({"type": "object"}),
+        )
+        .destructive()
+        .idempotent()
+        .closed_world();
+
+        let annotations = tool.annotations.as_ref().unwrap();
+        assert!(annotations.is_destructive());
+        assert!(annotations.is_idempotent());
+        assert!(!annotations.is_open_world());
+        assert_eq!(
+            annotations.hint_labels(),
+            vec!["writes", "destructive", "idempotent", "closed-world"]
+        );
+
+        let json = serde_json::to_value(&tool).unwrap();
+        assert_eq!(
+            json["annotations"],
+            serde_json::json!({
+                "readOnlyHint": false,
+                "destructiveHint": true,
+                "idempotentHint": true,
+                "openWorldHint": false
+            })
+        );
+
+        // Unset hints take the spec defaults
+        let defaults = ToolAnnotations::default();
+        assert!(!defaults.is_read_only());
+        assert!(defaults.is_destructive());
+        assert!(defaults.is_open_world());
+        assert!(!ToolAnnotations::read_only().is_destructive());
+    }
+
     #[test]
     fn test_destructive_tool_annotations() {
         let tool = Tool::new(
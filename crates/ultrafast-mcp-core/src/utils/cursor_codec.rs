@@ -0,0 +1,258 @@
+//! Signed and encrypted pagination cursors
+//!
+//! Available with the `cursor-signing` feature. See [`CursorCodec`].
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chacha20poly1305::{
+    ChaCha20Poly1305, KeyInit, Nonce,
+    aead::{Aead, Payload},
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    config::base::SecurityConfig,
+    error::{MCPError, MCPResult, ValidationError},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIGNED_CURSOR: u8 = 1;
+const ENCRYPTED_CURSOR: u8 = 2;
+const TAG_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Why a cursor was rejected by [`CursorCodec::decode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum CursorError {
+    #[error("cursor is malformed")]
+    Malformed,
+    #[error("cursor was modified or issued with a different key")]
+    Tampered,
+}
+
+impl From<CursorError> for MCPError {
+    fn from(error: CursorError) -> Self {
+        MCPError::invalid_params(format!("Invalid cursor: {error}"))
+    }
+}
+
+/// Signs, and optionally encrypts, pagination cursors
+///
+/// A handler that puts an offset or a database key in its cursors trusts
+/// whatever the client sends back. Cursors encoded with a codec carry an
+/// HMAC-SHA256 tag, so modified or forged cursors fail to decode. With
+/// [`with_encryption`](Self::with_encryption) they are also encrypted with
+/// ChaCha20-Poly1305. Encoding is deterministic: the same cursor value and
+/// key always produce the same token.
+///
+/// A codec is built from a secret with [`new`](Self::new), or from a
+/// [`SecurityConfig`] with [`from_config`](Self::from_config), and handed to
+/// a server with `UltraFastServer::with_cursor_codec`.
+#[derive(Clone)]
+pub struct CursorCodec {
+    mac_key: [u8; 32],
+    cipher: Option<ChaCha20Poly1305>,
+    encryption_key: [u8; 32],
+}
+
+impl std::fmt::Debug for CursorCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CursorCodec")
+            .field("encrypted", &self.cipher.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CursorCodec {
+    /// Create a codec signing cursors with a key derived from `secret`
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        let secret = secret.as_ref();
+        Self {
+            mac_key: derive_key(secret, b"ultrafast-mcp cursor signing"),
+            cipher: None,
+            encryption_key: derive_key(secret, b"ultrafast-mcp cursor encryption"),
+        }
+    }
+
+    /// Codec configured by `cursor_key_env` and `encrypt_cursors`
+    ///
+    /// The secret is read from the environment variable named by
+    /// `cursor_key_env` rather than from the configuration itself. Returns
+    /// `None` when no variable is named, and an error when the variable is
+    /// not set or empty.
+    pub fn from_config(config: &SecurityConfig) -> MCPResult<Option<Self>> {
+        let Some(variable) = &config.cursor_key_env else {
+            return Ok(None);
+        };
+        let secret = std::env::var(variable)
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .ok_or_else(|| ValidationError::InvalidFormat {
+                field: "cursor_key_env".to_string(),
+                expected: format!("a set environment variable, {variable} is not set"),
+            })?;
+        let codec = Self::new(secret);
+        Ok(Some(if config.encrypt_cursors {
+            codec.with_encryption()
+        } else {
+            codec
+        }))
+    }
+
+    /// Encrypt cursors in addition to signing them
+    pub fn with_encryption(mut self) -> Self {
+        self.cipher = Some(ChaCha20Poly1305::new(&self.encryption_key.into()));
+        self
+    }
+
+    /// Turn a cursor value into the token handed to clients
+    pub fn encode(&self, value: &str) -> String {
+        let mut token = Vec::with_capacity(1 + NONCE_LEN + value.len() + TAG_LEN);
+        match &self.cipher {
+            Some(cipher) => {
+                token.push(ENCRYPTED_CURSOR);
+                let nonce = &self.tag(ENCRYPTED_CURSOR, value.as_bytes())[..NONCE_LEN];
+                let ciphertext = cipher
+                    .encrypt(
+                        Nonce::from_slice(nonce),
+                        Payload {
+                            msg: value.as_bytes(),
+                            aad: &[ENCRYPTED_CURSOR],
+                        },
+                    )
+                    .expect("ChaCha20-Poly1305 encryption cannot fail for cursor sizes");
+                token.extend_from_slice(nonce);
+                token.extend_from_slice(&ciphertext);
+            }
+            None => {
+                token.push(SIGNED_CURSOR);
+                token.extend_from_slice(value.as_bytes());
+                token.extend_from_slice(&self.tag(SIGNED_CURSOR, value.as_bytes()));
+            }
+        }
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// Recover the cursor value from a token produced by [`encode`](Self::encode)
+    pub fn decode(&self, token: &str) -> Result<String, CursorError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|_| CursorError::Malformed)?;
+        let value = match bytes.split_first() {
+            Some((&SIGNED_CURSOR, rest)) if rest.len() >= TAG_LEN => {
+                let (value, tag) = rest.split_at(rest.len() - TAG_LEN);
+                let mut mac = self.mac(SIGNED_CURSOR);
+                mac.update(value);
+                mac.verify_slice(tag).map_err(|_| CursorError::Tampered)?;
+                value.to_vec()
+            }
+            Some((&ENCRYPTED_CURSOR, rest)) if rest.len() >= NONCE_LEN => {
+                let cipher = self.cipher.as_ref().ok_or(CursorError::Malformed)?;
+                let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+                cipher
+                    .decrypt(
+                        Nonce::from_slice(nonce),
+                        Payload {
+                            msg: ciphertext,
+                            aad: &[ENCRYPTED_CURSOR],
+                        },
+                    )
+                    .map_err(|_| CursorError::Tampered)?
+            }
+            _ => return Err(CursorError::Malformed),
+        };
+        String::from_utf8(value).map_err(|_| CursorError::Malformed)
+    }
+
+    fn mac(&self, kind: u8) -> HmacSha256 {
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.mac_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&[kind]);
+        mac
+    }
+
+    fn tag(&self, kind: u8, value: &[u8]) -> [u8; TAG_LEN] {
+        let mut mac = self.mac(kind);
+        mac.update(value);
+        mac.finalize().into_bytes().into()
+    }
+}
+
+fn derive_key(secret: &[u8], purpose: &[u8]) -> [u8; 32] {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(purpose);
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::pagination::{Cursor, PaginationBuilder, PaginationParams};
+
+    #[test]
+    fn test_cursor_codec_detects_tampering() {
+        let codec = CursorCodec::new("secret");
+        let token = codec.encode("offset:20");
+        assert_eq!(token, codec.encode("offset:20"));
+        assert_eq!(codec.decode(&token).unwrap(), "offset:20");
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(&token).unwrap();
+        bytes[8] ^= 1;
+        let tampered = URL_SAFE_NO_PAD.encode(bytes);
+        assert_eq!(codec.decode(&tampered), Err(CursorError::Tampered));
+        assert_eq!(
+            CursorCodec::new("other").decode(&token),
+            Err(CursorError::Tampered)
+        );
+        assert_eq!(codec.decode("offset:20"), Err(CursorError::Malformed));
+    }
+
+    #[test]
+    fn test_encrypted_cursors_hide_their_value() {
+        let codec = CursorCodec::new("secret").with_encryption();
+        let token = codec.encode("offset:20");
+        assert_eq!(token, codec.encode("offset:20"));
+        assert!(
+            !String::from_utf8_lossy(&URL_SAFE_NO_PAD.decode(&token).unwrap()).contains("offset")
+        );
+        assert_eq!(codec.decode(&token).unwrap(), "offset:20");
+        assert_eq!(
+            CursorCodec::new("secret").decode(&token),
+            Err(CursorError::Malformed)
+        );
+
+        let list = PaginationBuilder::new(vec![1, 2, 3])
+            .with_limit(2)
+            .with_cursor_codec(codec.clone())
+            .build(|items| Some(Cursor::new(format!("offset:{}", items.len()))));
+        let params = PaginationParams::new().with_cursor(list.next_cursor().unwrap().clone());
+        assert_eq!(
+            params.decode_cursor(&codec).unwrap().as_deref(),
+            Some("offset:2")
+        );
+    }
+
+    #[test]
+    fn test_cursor_codec_from_config_reads_the_key_from_the_environment() {
+        assert!(
+            CursorCodec::from_config(&SecurityConfig::default())
+                .unwrap()
+                .is_none()
+        );
+
+        let mut config = SecurityConfig {
+            cursor_key_env: Some("ULTRAFAST_MCP_TEST_UNSET_CURSOR_KEY".to_string()),
+            encrypt_cursors: true,
+            ..Default::default()
+        };
+        assert!(CursorCodec::from_config(&config).is_err());
+
+        // PATH is set wherever the tests run
+        config.cursor_key_env = Some("PATH".to_string());
+        let codec = CursorCodec::from_config(&config).unwrap().unwrap();
+        let expected = CursorCodec::new(std::env::var("PATH").unwrap()).with_encryption();
+        assert_eq!(codec.encode("offset:20"), expected.encode("offset:20"));
+    }
+}
@@ -0,0 +1,77 @@
+//! Detection of connections dropped without being closed
+//!
+//! Closing a client, server or transport is asynchronous, so it cannot happen
+//! in `Drop`; forgetting it leaves sessions open on the other side and
+//! background tasks running. A [`CloseTracker`] embedded in the connection
+//! notices this: in debug builds, dropping it while still open logs a
+//! warning with the backtrace of where the connection was created (captured
+//! when `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` is set). Release builds do
+//! not capture or warn.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Open/closed flag that warns when dropped while open
+#[derive(Debug)]
+pub struct CloseTracker {
+    kind: &'static str,
+    open: AtomicBool,
+    #[cfg(debug_assertions)]
+    created_at: std::backtrace::Backtrace,
+}
+
+impl CloseTracker {
+    /// A closed tracker for a connection of the given kind, such as
+    /// `"UltraFastClient"`
+    pub fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            open: AtomicBool::new(false),
+            #[cfg(debug_assertions)]
+            created_at: std::backtrace::Backtrace::capture(),
+        }
+    }
+
+    /// Record that the connection is open and must be closed
+    pub fn opened(&self) {
+        self.open.store(true, Ordering::Release);
+    }
+
+    /// Record that the connection was closed
+    pub fn closed(&self) {
+        self.open.store(false, Ordering::Release);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for CloseTracker {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        if self.is_open() && !std::thread::panicking() {
+            use std::backtrace::BacktraceStatus;
+
+            let created_at = match self.created_at.status() {
+                BacktraceStatus::Captured => format!("created at:\n{}", self.created_at),
+                _ => "set RUST_BACKTRACE=1 to see where it was created".to_string(),
+            };
+            tracing::warn!("{} dropped without being closed; {}", self.kind, created_at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_tracker_state() {
+        let tracker = CloseTracker::new("test");
+        assert!(!tracker.is_open());
+        tracker.opened();
+        assert!(tracker.is_open());
+        tracker.closed();
+        assert!(!tracker.is_open());
+    }
+}
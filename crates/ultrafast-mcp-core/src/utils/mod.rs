@@ -51,6 +51,7 @@
 //! - **[`pagination`]**: Cursor-based pagination support and management
 //! - **[`progress`]**: Progress tracking and status reporting utilities
 //! - **[`cancellation`]**: Request cancellation and timeout management
+//! - **[`drop_check`]**: Warnings for connections dropped without being closed
 //!
 //! ## Usage Examples
 //!
@@ -231,7 +232,11 @@
 //! - **Notification Integration**: Progress and cancellation notifications
 
 pub mod cancellation;
+#[cfg(feature = "cursor-signing")]
+pub mod cursor_codec;
+pub mod drop_check;
 pub mod pagination;
+pub mod ping_policy;
 pub mod progress;
 pub mod uri;
 
@@ -239,7 +244,11 @@ pub mod uri;
 pub mod identifiers;
 
 pub use cancellation::*;
+#[cfg(feature = "cursor-signing")]
+pub use cursor_codec::{CursorCodec, CursorError};
+pub use drop_check::CloseTracker;
 pub use identifiers::*;
 pub use pagination::*;
+pub use ping_policy::{PingEvent, PingPolicy, PingStats, PingTracker};
 pub use progress::*;
 pub use uri::*;
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+#[cfg(feature = "cursor-signing")]
+use super::cursor_codec::{CursorCodec, CursorError};
+
 /// Cursor-based pagination for MCP list operations
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Cursor {
@@ -75,6 +78,15 @@ impl PaginationParams {
     pub fn effective_limit(&self, default: u32) -> u32 {
         self.limit.unwrap_or(default)
     }
+
+    /// Cursor value after checking it with `codec`
+    #[cfg(feature = "cursor-signing")]
+    pub fn decode_cursor(&self, codec: &CursorCodec) -> Result<Option<String>, CursorError> {
+        self.cursor
+            .as_ref()
+            .map(|cursor| codec.decode(cursor.value()))
+            .transpose()
+    }
 }
 
 /// Pagination information in responses
@@ -192,6 +204,8 @@ pub struct PaginationBuilder<T> {
     items: Vec<T>,
     limit: Option<u32>,
     total: Option<u64>,
+    #[cfg(feature = "cursor-signing")]
+    codec: Option<CursorCodec>,
 }
 
 impl<T> PaginationBuilder<T> {
@@ -201,6 +215,8 @@ impl<T> PaginationBuilder<T> {
             items,
             limit: None,
             total: None,
+            #[cfg(feature = "cursor-signing")]
+            codec: None,
         }
     }
 
@@ -216,6 +232,13 @@ impl<T> PaginationBuilder<T> {
         self
     }
 
+    /// Encode the next cursor with `codec`
+    #[cfg(feature = "cursor-signing")]
+    pub fn with_cursor_codec(mut self, codec: CursorCodec) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
     /// Build the paginated list
     pub fn build<F>(self, next_cursor_fn: F) -> PaginatedList<T>
     where
@@ -238,6 +261,14 @@ impl<T> PaginationBuilder<T> {
         } else {
             None
         };
+        #[cfg(feature = "cursor-signing")]
+        let next_cursor = match (next_cursor, &self.codec) {
+            (Some(cursor), Some(codec)) => Some(Cursor {
+                value: codec.encode(&cursor.value),
+                metadata: cursor.metadata,
+            }),
+            (next_cursor, _) => next_cursor,
+        };
 
         let pagination = if let Some(total) = self.total {
             PaginationInfo::with_total(total, next_cursor)
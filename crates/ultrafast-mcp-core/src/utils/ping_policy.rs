@@ -0,0 +1,198 @@
+//! Consequences of unanswered pings
+//!
+//! Either side of a connection may ping the other to check it is still
+//! there. A [`PingPolicy`] says how often to ping, how long to wait for the
+//! answer and what happens when answers stop coming: after
+//! [`max_missed`](PingPolicy::max_missed) unanswered pings in a row the peer
+//! is considered unhealthy and, if the policy says so, the connection is
+//! closed. A [`PingTracker`] applies the policy to the outcomes of the pings
+//! one side sends and keeps the counts reported as [`PingStats`].
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How a connection is watched with pings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingPolicy {
+    /// Time between pings
+    pub interval: Duration,
+    /// How long to wait for an answer before the ping counts as missed
+    pub timeout: Duration,
+    /// Unanswered pings in a row after which the peer is unhealthy
+    pub max_missed: u32,
+    /// Whether to close the connection once the peer is unhealthy
+    pub disconnect: bool,
+}
+
+impl Default for PingPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            max_missed: 3,
+            disconnect: false,
+        }
+    }
+}
+
+impl PingPolicy {
+    /// Ping every `interval`, otherwise as the default policy does
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Consider the peer unhealthy after `max_missed` unanswered pings in a
+    /// row; at least one
+    pub fn with_max_missed(mut self, max_missed: u32) -> Self {
+        self.max_missed = max_missed.max(1);
+        self
+    }
+
+    /// Close the connection once the peer is unhealthy
+    pub fn with_disconnect(mut self, disconnect: bool) -> Self {
+        self.disconnect = disconnect;
+        self
+    }
+}
+
+/// A change in how a peer answers pings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PingEvent {
+    /// A ping went unanswered, the `consecutive`-th in a row
+    Missed { consecutive: u32 },
+    /// [`PingPolicy::max_missed`] pings in a row went unanswered
+    Unhealthy { consecutive: u32 },
+    /// An unhealthy peer answered a ping again
+    Recovered,
+    /// The connection was closed because the peer stayed unhealthy
+    Disconnected,
+}
+
+/// Counts of the pings one side sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingStats {
+    pub sent: u64,
+    pub missed: u64,
+    /// Unanswered pings since the last answered one
+    pub consecutive_missed: u32,
+    /// Whether the peer missed [`PingPolicy::max_missed`] pings in a row
+    pub unhealthy: bool,
+}
+
+/// Applies a [`PingPolicy`] to the outcomes of pings
+#[derive(Debug)]
+pub struct PingTracker {
+    policy: PingPolicy,
+    stats: Mutex<PingStats>,
+}
+
+impl PingTracker {
+    pub fn new(policy: PingPolicy) -> Self {
+        Self {
+            policy,
+            stats: Mutex::new(PingStats::default()),
+        }
+    }
+
+    pub fn policy(&self) -> &PingPolicy {
+        &self.policy
+    }
+
+    pub fn stats(&self) -> PingStats {
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count a ping and whether it was answered in time
+    ///
+    /// Returns the event to report, if the outcome changes anything.
+    pub fn record(&self, answered: bool) -> Option<PingEvent> {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.sent += 1;
+        if answered {
+            stats.consecutive_missed = 0;
+            return std::mem::take(&mut stats.unhealthy).then_some(PingEvent::Recovered);
+        }
+
+        stats.missed += 1;
+        stats.consecutive_missed += 1;
+        let consecutive = stats.consecutive_missed;
+        if consecutive == self.policy.max_missed {
+            stats.unhealthy = true;
+            Some(PingEvent::Unhealthy { consecutive })
+        } else {
+            Some(PingEvent::Missed { consecutive })
+        }
+    }
+
+    /// Whether the policy says to close the connection now
+    pub fn should_disconnect(&self) -> bool {
+        self.policy.disconnect && self.stats().unhealthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missed_pings_make_the_peer_unhealthy() {
+        let tracker = PingTracker::new(
+            PingPolicy::new(Duration::from_secs(1))
+                .with_max_missed(2)
+                .with_disconnect(true),
+        );
+
+        assert_eq!(tracker.record(true), None);
+        assert_eq!(
+            tracker.record(false),
+            Some(PingEvent::Missed { consecutive: 1 })
+        );
+        assert!(!tracker.should_disconnect());
+        assert_eq!(
+            tracker.record(false),
+            Some(PingEvent::Unhealthy { consecutive: 2 })
+        );
+        assert!(tracker.should_disconnect());
+        assert_eq!(
+            tracker.stats(),
+            PingStats {
+                sent: 3,
+                missed: 2,
+                consecutive_missed: 2,
+                unhealthy: true,
+            }
+        );
+
+        // Pings keep being counted while unhealthy
+        assert_eq!(
+            tracker.record(false),
+            Some(PingEvent::Missed { consecutive: 3 })
+        );
+        assert_eq!(tracker.record(true), Some(PingEvent::Recovered));
+        assert_eq!(tracker.record(true), None);
+        assert!(!tracker.stats().unhealthy);
+    }
+
+    #[test]
+    fn test_unhealthy_peers_are_kept_without_disconnect() {
+        let tracker = PingTracker::new(PingPolicy::default().with_max_missed(0));
+        assert_eq!(tracker.policy().max_missed, 1);
+        assert_eq!(
+            tracker.record(false),
+            Some(PingEvent::Unhealthy { consecutive: 1 })
+        );
+        assert!(!tracker.should_disconnect());
+    }
+}
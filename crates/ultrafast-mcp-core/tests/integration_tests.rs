@@ -217,6 +217,8 @@ mod integration_tests {
                 text: "Hello, World!".to_string(),
             }],
             is_error: Some(false),
+            structured_content: None,
+            progress_summary: None,
         };
 
         assert_eq!(tool.name, call_request.name);
@@ -53,6 +53,13 @@ pub struct MetricsConfig {
     pub collection_interval: Duration,
     /// Enable system metrics
     pub system_metrics: bool,
+    /// Upper bounds in seconds of the request duration histogram buckets
+    #[serde(default = "default_latency_buckets")]
+    pub latency_buckets: Vec<f64>,
+}
+
+fn default_latency_buckets() -> Vec<f64> {
+    crate::metrics::DEFAULT_LATENCY_BUCKETS.to_vec()
 }
 
 /// Health check configuration
@@ -149,6 +156,7 @@ impl Default for MetricsConfig {
             otlp: None,
             collection_interval: Duration::from_secs(30),
             system_metrics: true,
+            latency_buckets: default_latency_buckets(),
         }
     }
 }
@@ -395,9 +395,14 @@ pub mod middleware;
 pub mod tracing;
 
 // Re-export types from metrics module
-pub use metrics::{Metrics, MetricsCollector, RequestMetrics, SystemMetrics, TransportMetrics};
+pub use metrics::{
+    DEFAULT_LATENCY_BUCKETS, LatencyHistogram, LatencyPercentiles, Metrics, MetricsCollector,
+    RECOVERY_BACKOFF_BUCKETS, RecoveryStats, RejectMetrics, RejectReason, RejectRecord,
+    RequestMetrics, SystemMetrics, TransportMetrics, TransportRequestMetrics,
+};
 
 pub use config::MonitoringConfig;
+pub use exporters::ExporterManager;
 pub use health::{HealthChecker, HealthStatus};
 
 /// The main monitoring system that orchestrates all monitoring components
@@ -406,15 +411,20 @@ pub struct MonitoringSystem {
     pub metrics_collector: Arc<MetricsCollector>,
     pub health_checker: Arc<HealthChecker>,
     pub config: MonitoringConfig,
+    /// Where the metrics are flushed on [`shutdown`](Self::shutdown)
+    pub exporters: ExporterManager,
 }
 
 impl MonitoringSystem {
     /// Create a new monitoring system with configuration (synchronous)
     pub fn new(config: MonitoringConfig) -> Self {
         let health_checker = Arc::new(HealthChecker::new());
-        let metrics_collector = Arc::new(MetricsCollector::new());
+        let metrics_collector = Arc::new(
+            MetricsCollector::new().with_latency_buckets(config.metrics.latency_buckets.clone()),
+        );
 
         Self {
+            exporters: ExporterManager::new(metrics_collector.clone()),
             metrics_collector,
             health_checker,
             config,
@@ -430,9 +440,12 @@ impl MonitoringSystem {
             .add_check(Box::new(health::SystemHealthCheck::new("system")))
             .await;
 
-        let metrics_collector = Arc::new(MetricsCollector::new());
+        let metrics_collector = Arc::new(
+            MetricsCollector::new().with_latency_buckets(config.metrics.latency_buckets.clone()),
+        );
 
         Ok(Self {
+            exporters: ExporterManager::new(metrics_collector.clone()),
             metrics_collector,
             health_checker,
             config,
@@ -508,9 +521,10 @@ impl MonitoringSystem {
         ))
     }
 
-    /// Shutdown the monitoring system
+    /// Shutdown the monitoring system, flushing the metrics to the
+    /// exporters one last time
     pub async fn shutdown(&self) -> Result<()> {
         println!("Shutting down monitoring system");
-        Ok(())
+        self.exporters.export_metrics().await
     }
 }
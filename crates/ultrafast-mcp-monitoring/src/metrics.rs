@@ -2,6 +2,17 @@
 //!
 //! This module provides comprehensive metrics collection for MCP servers and clients,
 //! including request metrics, transport metrics, and system metrics.
+//!
+//! Request durations are exported per method both as a Prometheus histogram,
+//! `mcp_request_duration_seconds`, over [`DEFAULT_LATENCY_BUCKETS`] or the
+//! buckets given to [`MetricsCollector::with_latency_buckets`], and as a
+//! summary, `mcp_request_duration_quantiles_seconds`, with the p50, p95 and
+//! p99 of the most recent requests.
+//!
+//! Connections that reconnect on failure report each recovery attempt, its
+//! backoff delay and outcome, health probes and circuit breaker changes under
+//! a label naming the connection, exported as `mcp_transport_recovery_*` and
+//! `mcp_transport_circuit_*` so flapping connections can be alerted on.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -9,12 +20,27 @@ use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+/// Upper bounds in seconds of the request duration histogram buckets,
+/// the Prometheus client defaults
+pub const DEFAULT_LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Upper bounds in seconds of the recovery backoff delay histogram buckets
+pub const RECOVERY_BACKOFF_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Quantiles exported in the request duration summary
+const SUMMARY_QUANTILES: [f64; 3] = [0.5, 0.95, 0.99];
+
 /// Core metrics structure containing all collected metrics
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct Metrics {
     pub request: RequestMetrics,
     pub transport: TransportMetrics,
     pub system: SystemMetrics,
+    pub rejects: RejectMetrics,
+    /// Reconnection behavior by connection label
+    pub recovery: HashMap<String, RecoveryStats>,
 }
 
 /// Request-related metrics
@@ -25,8 +51,162 @@ pub struct RequestMetrics {
     pub failed_requests: u64,
     pub average_response_time: f64,
     pub method_counts: HashMap<String, u64>,
+    /// The most recent response times of each method
     pub response_time_histogram: HashMap<String, Vec<Duration>>,
+    /// Response times of each method, counted into buckets
+    pub latency: HashMap<String, LatencyHistogram>,
     pub last_request_time: Option<SystemTime>,
+    /// Requests broken down by the kind of transport they arrived on
+    pub by_transport: HashMap<String, TransportRequestMetrics>,
+    /// Requests by the MCP protocol version they were made with
+    pub by_protocol_version: HashMap<String, u64>,
+}
+
+/// Request durations counted into buckets, as a Prometheus histogram
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct LatencyHistogram {
+    /// Upper bounds in seconds, ascending
+    pub bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative; the last entry counts those
+    /// above every bound
+    pub counts: Vec<u64>,
+    pub count: u64,
+    /// Sum of all observations in seconds
+    pub sum: f64,
+}
+
+impl LatencyHistogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    pub fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = self.bounds.partition_point(|bound| *bound < seconds);
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    /// Observations at or below each bound, then the total for `+Inf`
+    pub fn cumulative_counts(&self) -> Vec<u64> {
+        self.counts
+            .iter()
+            .scan(0, |total, count| {
+                *total += count;
+                Some(*total)
+            })
+            .collect()
+    }
+}
+
+/// p50, p95 and p99 of a method's recent request durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct LatencyPercentiles {
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+/// Nearest-rank `quantile` of ascending `samples`
+fn quantile(samples: &[Duration], quantile: f64) -> Duration {
+    let rank = (quantile * samples.len() as f64).ceil() as usize;
+    samples[rank.clamp(1, samples.len()) - 1]
+}
+
+/// Escape a Prometheus label value
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Request metrics for one kind of transport
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct TransportRequestMetrics {
+    pub total_requests: u64,
+    pub failed_requests: u64,
+    /// Average response time in milliseconds
+    pub average_response_time: f64,
+}
+
+/// Why a message was rejected before reaching a handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// The message was not valid JSON or not a JSON-RPC message
+    ParseError,
+    /// The message was JSON-RPC but not a valid request
+    InvalidRequest,
+    /// The parameters did not match the method's schema
+    InvalidParams,
+    /// The method is not known to the server
+    MethodNotFound,
+    /// The message exceeded the transport's size limit
+    OversizedMessage,
+    /// The message named a protocol version the server does not speak, or
+    /// not the one negotiated for its session
+    UnsupportedProtocolVersion,
+}
+
+impl RejectReason {
+    /// Label used for the reason in metrics
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectReason::ParseError => "parse_error",
+            RejectReason::InvalidRequest => "invalid_request",
+            RejectReason::InvalidParams => "invalid_params",
+            RejectReason::MethodNotFound => "method_not_found",
+            RejectReason::OversizedMessage => "oversized_message",
+            RejectReason::UnsupportedProtocolVersion => "unsupported_protocol_version",
+        }
+    }
+
+    /// The reason for a JSON-RPC error response, if its code is one of the
+    /// protocol-level codes rather than an application error
+    pub fn from_error_code(code: i32) -> Option<Self> {
+        match code {
+            -32700 => Some(RejectReason::ParseError),
+            -32600 => Some(RejectReason::InvalidRequest),
+            -32602 => Some(RejectReason::InvalidParams),
+            -32601 => Some(RejectReason::MethodNotFound),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One rejected message, kept so the offending client can be identified
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RejectRecord {
+    pub reason: RejectReason,
+    /// Method of the message, when it could be parsed far enough to tell
+    pub method: Option<String>,
+    pub session_id: Option<String>,
+    pub time: SystemTime,
+}
+
+/// Messages rejected at the protocol level, counted separately from
+/// requests that reached a handler
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RejectMetrics {
+    pub total_rejects: u64,
+    pub by_reason: HashMap<RejectReason, u64>,
+    pub by_method: HashMap<String, u64>,
+    pub by_session: HashMap<String, u64>,
+    /// The most recent rejects, oldest first
+    pub recent: Vec<RejectRecord>,
 }
 
 /// Transport-related metrics
@@ -41,6 +221,49 @@ pub struct TransportMetrics {
     pub last_activity: Option<SystemTime>,
 }
 
+/// Reconnection behavior of one connection
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RecoveryStats {
+    /// Recovery attempts started
+    pub attempts: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Times recovery gave up after its last retry
+    pub exhausted: u64,
+    /// Delays waited before each attempt
+    pub backoff: LatencyHistogram,
+    pub probes: u64,
+    pub probes_failed: u64,
+    /// Durations of the health probes run after reconnecting
+    pub probe_latency: LatencyHistogram,
+    /// Circuit breaker state, such as `closed`, `open` or `half-open`
+    pub circuit_state: String,
+    /// Circuit breaker transitions by the state entered
+    pub circuit_transitions: HashMap<String, u64>,
+    pub last_attempt: Option<SystemTime>,
+}
+
+impl Default for RecoveryStats {
+    fn default() -> Self {
+        Self {
+            attempts: 0,
+            successes: 0,
+            failures: 0,
+            exhausted: 0,
+            backoff: LatencyHistogram::new(RECOVERY_BACKOFF_BUCKETS),
+            probes: 0,
+            probes_failed: 0,
+            probe_latency: LatencyHistogram::new(DEFAULT_LATENCY_BUCKETS),
+            circuit_state: "closed".to_string(),
+            circuit_transitions: HashMap::new(),
+            last_attempt: None,
+        }
+    }
+}
+
+/// Name, help text and value of a counter exported per connection
+type RecoveryCounter = (&'static str, &'static str, fn(&RecoveryStats) -> u64);
+
 /// System-related metrics
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SystemMetrics {
@@ -57,6 +280,7 @@ pub struct MetricsCollector {
     metrics: Arc<RwLock<Metrics>>,
     collection_interval: Duration,
     max_histogram_size: usize,
+    latency_buckets: Vec<f64>,
 }
 
 impl MetricsCollector {
@@ -67,9 +291,12 @@ impl MetricsCollector {
                 request: RequestMetrics::default(),
                 transport: TransportMetrics::default(),
                 system: SystemMetrics::default(),
+                rejects: RejectMetrics::default(),
+                recovery: HashMap::new(),
             })),
             collection_interval: Duration::from_secs(30),
             max_histogram_size: 1000,
+            latency_buckets: DEFAULT_LATENCY_BUCKETS.to_vec(),
         }
     }
 
@@ -80,12 +307,25 @@ impl MetricsCollector {
                 request: RequestMetrics::default(),
                 transport: TransportMetrics::default(),
                 system: SystemMetrics::default(),
+                rejects: RejectMetrics::default(),
+                recovery: HashMap::new(),
             })),
             collection_interval,
             max_histogram_size,
+            latency_buckets: DEFAULT_LATENCY_BUCKETS.to_vec(),
         }
     }
 
+    /// Count request durations into buckets with these upper bounds, in
+    /// seconds, in place of [`DEFAULT_LATENCY_BUCKETS`]
+    pub fn with_latency_buckets(mut self, mut buckets: Vec<f64>) -> Self {
+        buckets.retain(|bound| bound.is_finite());
+        buckets.sort_by(f64::total_cmp);
+        buckets.dedup();
+        self.latency_buckets = buckets;
+        self
+    }
+
     /// Record a request with timing and success status
     pub async fn record_request(&self, method: &str, response_time: Duration, success: bool) {
         let mut metrics = self.metrics.write().await;
@@ -124,6 +364,13 @@ impl MetricsCollector {
         metrics.request.average_response_time =
             total_time.as_millis() as f64 / method_histogram.len() as f64;
 
+        metrics
+            .request
+            .latency
+            .entry(method.to_string())
+            .or_insert_with(|| LatencyHistogram::new(&self.latency_buckets))
+            .observe(response_time);
+
         // Update last request time
         metrics.request.last_request_time = Some(SystemTime::now());
 
@@ -133,6 +380,86 @@ impl MetricsCollector {
         );
     }
 
+    /// Record a request as [`record_request`](Self::record_request) does,
+    /// also counting it against the transport it arrived on
+    ///
+    /// `transport` is a short label such as `stdio`, `http` or `ws`.
+    pub async fn record_transport_request(
+        &self,
+        transport: &str,
+        method: &str,
+        response_time: Duration,
+        success: bool,
+    ) {
+        self.record_request(method, response_time, success).await;
+
+        let mut metrics = self.metrics.write().await;
+        let entry = metrics
+            .request
+            .by_transport
+            .entry(transport.to_string())
+            .or_default();
+        entry.total_requests += 1;
+        if !success {
+            entry.failed_requests += 1;
+        }
+        // Running mean, so no per-transport samples need to be kept
+        entry.average_response_time += (response_time.as_secs_f64() * 1000.0
+            - entry.average_response_time)
+            / entry.total_requests as f64;
+    }
+
+    /// Count a request against the MCP protocol version it was made with
+    pub async fn record_protocol_version(&self, version: &str) {
+        let mut metrics = self.metrics.write().await;
+        *metrics
+            .request
+            .by_protocol_version
+            .entry(version.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Record a message rejected before it reached a handler
+    ///
+    /// Rejects are not counted as requests; `method` and `session_id` name
+    /// the offending call and client where known.
+    pub async fn record_reject(
+        &self,
+        reason: RejectReason,
+        method: Option<&str>,
+        session_id: Option<&str>,
+    ) {
+        let mut metrics = self.metrics.write().await;
+        let rejects = &mut metrics.rejects;
+        rejects.total_rejects += 1;
+        *rejects.by_reason.entry(reason).or_insert(0) += 1;
+        if let Some(method) = method {
+            *rejects.by_method.entry(method.to_string()).or_insert(0) += 1;
+        }
+        if let Some(session_id) = session_id {
+            *rejects
+                .by_session
+                .entry(session_id.to_string())
+                .or_insert(0) += 1;
+        }
+        rejects.recent.push(RejectRecord {
+            reason,
+            method: method.map(str::to_string),
+            session_id: session_id.map(str::to_string),
+            time: SystemTime::now(),
+        });
+        if rejects.recent.len() > self.max_histogram_size {
+            rejects.recent.remove(0);
+        }
+
+        warn!(
+            "Rejected message: reason={}, method={}, session={}",
+            reason,
+            method.unwrap_or("-"),
+            session_id.unwrap_or("-")
+        );
+    }
+
     /// Record transport send operation
     pub async fn record_transport_send(&self, bytes: u64) {
         let mut metrics = self.metrics.write().await;
@@ -164,6 +491,79 @@ impl MetricsCollector {
         warn!("Recorded transport error: {}", error_type);
     }
 
+    /// Record that `connection` starts recovery attempt `attempt` after
+    /// waiting `delay`
+    pub async fn record_recovery_attempt(&self, connection: &str, attempt: u32, delay: Duration) {
+        let mut metrics = self.metrics.write().await;
+        let stats = metrics.recovery.entry(connection.to_string()).or_default();
+        stats.attempts += 1;
+        stats.backoff.observe(delay);
+        stats.last_attempt = Some(SystemTime::now());
+
+        warn!(
+            connection,
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            "Transport recovery attempt"
+        );
+    }
+
+    /// Record how a recovery attempt of `connection` ended
+    pub async fn record_recovery_outcome(&self, connection: &str, attempt: u32, success: bool) {
+        let mut metrics = self.metrics.write().await;
+        let stats = metrics.recovery.entry(connection.to_string()).or_default();
+        if success {
+            stats.successes += 1;
+            info!(connection, attempt, "Transport recovered");
+        } else {
+            stats.failures += 1;
+            warn!(connection, attempt, "Transport recovery attempt failed");
+        }
+    }
+
+    /// Record that `connection` gave up recovering after `attempts` attempts
+    pub async fn record_recovery_exhausted(&self, connection: &str, attempts: u32) {
+        let mut metrics = self.metrics.write().await;
+        metrics
+            .recovery
+            .entry(connection.to_string())
+            .or_default()
+            .exhausted += 1;
+
+        warn!(connection, attempts, "Transport recovery exhausted");
+    }
+
+    /// Record a health probe of `connection` after reconnecting
+    pub async fn record_health_probe(&self, connection: &str, success: bool, latency: Duration) {
+        let mut metrics = self.metrics.write().await;
+        let stats = metrics.recovery.entry(connection.to_string()).or_default();
+        stats.probes += 1;
+        if !success {
+            stats.probes_failed += 1;
+        }
+        stats.probe_latency.observe(latency);
+
+        debug!(
+            connection,
+            success,
+            latency_ms = latency.as_millis() as u64,
+            "Transport health probe"
+        );
+    }
+
+    /// Record that the circuit breaker of `connection` entered `state`
+    pub async fn record_circuit_state(&self, connection: &str, state: &str) {
+        let mut metrics = self.metrics.write().await;
+        let stats = metrics.recovery.entry(connection.to_string()).or_default();
+        let from = std::mem::replace(&mut stats.circuit_state, state.to_string());
+        *stats
+            .circuit_transitions
+            .entry(state.to_string())
+            .or_insert(0) += 1;
+
+        warn!(connection, from = %from, to = state, "Transport circuit state changed");
+    }
+
     /// Update connection count
     pub async fn update_connection_count(&self, count: u32) {
         let mut metrics = self.metrics.write().await;
@@ -202,6 +602,21 @@ impl MetricsCollector {
         self.metrics.read().await.clone()
     }
 
+    /// p50, p95 and p99 of the most recent durations of `method`
+    pub async fn latency_percentiles(&self, method: &str) -> Option<LatencyPercentiles> {
+        let metrics = self.metrics.read().await;
+        let mut samples = metrics.request.response_time_histogram.get(method)?.clone();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+        Some(LatencyPercentiles {
+            p50: quantile(&samples, 0.5),
+            p95: quantile(&samples, 0.95),
+            p99: quantile(&samples, 0.99),
+        })
+    }
+
     /// Export metrics as JSON
     pub async fn export_json(&self) -> serde_json::Result<String> {
         let metrics = self.get_metrics().await;
@@ -262,6 +677,142 @@ impl MetricsCollector {
             ));
         }
 
+        // Per-method durations
+        let mut methods: Vec<_> = metrics.request.latency.iter().collect();
+        methods.sort_by(|a, b| a.0.cmp(b.0));
+        if !methods.is_empty() {
+            prometheus_output.push_str(
+                "# HELP mcp_request_duration_seconds Request duration in seconds by method\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_request_duration_seconds histogram\n");
+            for (method, histogram) in &methods {
+                let method = label_value(method);
+                let cumulative = histogram.cumulative_counts();
+                for (bound, count) in histogram.bounds.iter().zip(&cumulative) {
+                    prometheus_output.push_str(&format!(
+                        "mcp_request_duration_seconds_bucket{{method=\"{method}\",le=\"{bound}\"}} {count}\n"
+                    ));
+                }
+                prometheus_output.push_str(&format!(
+                    "mcp_request_duration_seconds_bucket{{method=\"{method}\",le=\"+Inf\"}} {}\n",
+                    histogram.count
+                ));
+                prometheus_output.push_str(&format!(
+                    "mcp_request_duration_seconds_sum{{method=\"{method}\"}} {}\n",
+                    histogram.sum
+                ));
+                prometheus_output.push_str(&format!(
+                    "mcp_request_duration_seconds_count{{method=\"{method}\"}} {}\n",
+                    histogram.count
+                ));
+            }
+
+            prometheus_output.push_str(
+                "# HELP mcp_request_duration_quantiles_seconds Request duration quantiles in seconds over recent requests by method\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_request_duration_quantiles_seconds summary\n");
+            for (method, _) in &methods {
+                let Some(samples) = metrics.request.response_time_histogram.get(*method) else {
+                    continue;
+                };
+                let mut samples = samples.clone();
+                if samples.is_empty() {
+                    continue;
+                }
+                samples.sort();
+                let label = label_value(method);
+                for q in SUMMARY_QUANTILES {
+                    prometheus_output.push_str(&format!(
+                        "mcp_request_duration_quantiles_seconds{{method=\"{label}\",quantile=\"{q}\"}} {}\n",
+                        quantile(&samples, q).as_secs_f64()
+                    ));
+                }
+                let sum: Duration = samples.iter().sum();
+                prometheus_output.push_str(&format!(
+                    "mcp_request_duration_quantiles_seconds_sum{{method=\"{label}\"}} {}\n",
+                    sum.as_secs_f64()
+                ));
+                prometheus_output.push_str(&format!(
+                    "mcp_request_duration_quantiles_seconds_count{{method=\"{label}\"}} {}\n",
+                    samples.len()
+                ));
+            }
+        }
+
+        // Transport-specific metrics
+        if !metrics.request.by_transport.is_empty() {
+            prometheus_output.push_str(
+                "# HELP mcp_requests_by_transport_total Total requests by transport kind\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_requests_by_transport_total counter\n");
+            for (transport, transport_metrics) in &metrics.request.by_transport {
+                prometheus_output.push_str(&format!(
+                    "mcp_requests_by_transport_total{{transport=\"{transport}\"}} {}\n",
+                    transport_metrics.total_requests
+                ));
+            }
+            prometheus_output.push_str(
+                "# HELP mcp_requests_failed_by_transport_total Failed requests by transport kind\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_requests_failed_by_transport_total counter\n");
+            for (transport, transport_metrics) in &metrics.request.by_transport {
+                prometheus_output.push_str(&format!(
+                    "mcp_requests_failed_by_transport_total{{transport=\"{transport}\"}} {}\n",
+                    transport_metrics.failed_requests
+                ));
+            }
+            prometheus_output.push_str(
+                "# HELP mcp_request_duration_average_by_transport Average request duration in milliseconds by transport kind\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_request_duration_average_by_transport gauge\n");
+            for (transport, transport_metrics) in &metrics.request.by_transport {
+                prometheus_output.push_str(&format!(
+                    "mcp_request_duration_average_by_transport{{transport=\"{transport}\"}} {}\n",
+                    transport_metrics.average_response_time
+                ));
+            }
+        }
+        if !metrics.request.by_protocol_version.is_empty() {
+            prometheus_output.push_str(
+                "# HELP mcp_requests_by_protocol_version_total Total requests by MCP protocol version\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_requests_by_protocol_version_total counter\n");
+            for (version, count) in &metrics.request.by_protocol_version {
+                prometheus_output.push_str(&format!(
+                    "mcp_requests_by_protocol_version_total{{version=\"{version}\"}} {count}\n"
+                ));
+            }
+        }
+
+        // Protocol-level rejects
+        prometheus_output
+            .push_str("# HELP mcp_rejects_total Messages rejected before reaching a handler\n");
+        prometheus_output.push_str("# TYPE mcp_rejects_total counter\n");
+        prometheus_output.push_str(&format!(
+            "mcp_rejects_total {}\n",
+            metrics.rejects.total_rejects
+        ));
+        if !metrics.rejects.by_reason.is_empty() {
+            prometheus_output
+                .push_str("# HELP mcp_rejects_by_reason_total Rejected messages by reason\n");
+            prometheus_output.push_str("# TYPE mcp_rejects_by_reason_total counter\n");
+            for (reason, count) in &metrics.rejects.by_reason {
+                prometheus_output.push_str(&format!(
+                    "mcp_rejects_by_reason_total{{reason=\"{reason}\"}} {count}\n"
+                ));
+            }
+        }
+        if !metrics.rejects.by_method.is_empty() {
+            prometheus_output
+                .push_str("# HELP mcp_rejects_by_method_total Rejected messages by method\n");
+            prometheus_output.push_str("# TYPE mcp_rejects_by_method_total counter\n");
+            for (method, count) in &metrics.rejects.by_method {
+                prometheus_output.push_str(&format!(
+                    "mcp_rejects_by_method_total{{method=\"{method}\"}} {count}\n"
+                ));
+            }
+        }
+
         // Transport metrics
         prometheus_output.push_str("# HELP mcp_transport_bytes_sent Total bytes sent\n");
         prometheus_output.push_str("# TYPE mcp_transport_bytes_sent counter\n");
@@ -292,6 +843,117 @@ impl MetricsCollector {
             metrics.transport.error_count
         ));
 
+        // Connection recovery
+        let mut connections: Vec<_> = metrics.recovery.iter().collect();
+        connections.sort_by(|a, b| a.0.cmp(b.0));
+        if !connections.is_empty() {
+            let counters: [RecoveryCounter; 6] = [
+                (
+                    "mcp_transport_recovery_attempts_total",
+                    "Recovery attempts started",
+                    |stats| stats.attempts,
+                ),
+                (
+                    "mcp_transport_recovery_successes_total",
+                    "Recovery attempts that reconnected",
+                    |stats| stats.successes,
+                ),
+                (
+                    "mcp_transport_recovery_failures_total",
+                    "Recovery attempts that failed",
+                    |stats| stats.failures,
+                ),
+                (
+                    "mcp_transport_recovery_exhausted_total",
+                    "Times recovery gave up after its last retry",
+                    |stats| stats.exhausted,
+                ),
+                (
+                    "mcp_transport_health_probes_total",
+                    "Health probes run after reconnecting",
+                    |stats| stats.probes,
+                ),
+                (
+                    "mcp_transport_health_probes_failed_total",
+                    "Health probes that failed",
+                    |stats| stats.probes_failed,
+                ),
+            ];
+            for (name, help, value) in counters {
+                prometheus_output.push_str(&format!("# HELP {name} {help} by connection\n"));
+                prometheus_output.push_str(&format!("# TYPE {name} counter\n"));
+                for (connection, stats) in &connections {
+                    prometheus_output.push_str(&format!(
+                        "{name}{{connection=\"{}\"}} {}\n",
+                        label_value(connection),
+                        value(stats)
+                    ));
+                }
+            }
+
+            prometheus_output.push_str(
+                "# HELP mcp_transport_recovery_backoff_seconds Delay before each recovery attempt by connection\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_transport_recovery_backoff_seconds histogram\n");
+            for (connection, stats) in &connections {
+                let connection = label_value(connection);
+                let histogram = &stats.backoff;
+                let cumulative = histogram.cumulative_counts();
+                for (bound, count) in histogram.bounds.iter().zip(&cumulative) {
+                    prometheus_output.push_str(&format!(
+                        "mcp_transport_recovery_backoff_seconds_bucket{{connection=\"{connection}\",le=\"{bound}\"}} {count}\n"
+                    ));
+                }
+                prometheus_output.push_str(&format!(
+                    "mcp_transport_recovery_backoff_seconds_bucket{{connection=\"{connection}\",le=\"+Inf\"}} {}\n",
+                    histogram.count
+                ));
+                prometheus_output.push_str(&format!(
+                    "mcp_transport_recovery_backoff_seconds_sum{{connection=\"{connection}\"}} {}\n",
+                    histogram.sum
+                ));
+                prometheus_output.push_str(&format!(
+                    "mcp_transport_recovery_backoff_seconds_count{{connection=\"{connection}\"}} {}\n",
+                    histogram.count
+                ));
+            }
+
+            prometheus_output.push_str(
+                "# HELP mcp_transport_circuit_state Circuit breaker state by connection, 1 for the current state\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_transport_circuit_state gauge\n");
+            for (connection, stats) in &connections {
+                let connection = label_value(connection);
+                let mut states = vec!["closed", "open", "half-open"];
+                if !states.contains(&stats.circuit_state.as_str()) {
+                    states.push(&stats.circuit_state);
+                }
+                for state in states {
+                    prometheus_output.push_str(&format!(
+                        "mcp_transport_circuit_state{{connection=\"{connection}\",state=\"{}\"}} {}\n",
+                        label_value(state),
+                        u8::from(state == stats.circuit_state)
+                    ));
+                }
+            }
+
+            prometheus_output.push_str(
+                "# HELP mcp_transport_circuit_transitions_total Circuit breaker transitions by connection and state entered\n",
+            );
+            prometheus_output.push_str("# TYPE mcp_transport_circuit_transitions_total counter\n");
+            for (connection, stats) in &connections {
+                let connection = label_value(connection);
+                let mut transitions: Vec<_> = stats.circuit_transitions.iter().collect();
+                transitions.sort();
+                for (state, count) in transitions {
+                    prometheus_output.push_str(&format!(
+                        "mcp_transport_circuit_transitions_total{{connection=\"{connection}\",state=\"{}\"}} {count}\n",
+                        label_value(state)
+                    ));
+                }
+            }
+        }
+
         // System metrics
         prometheus_output
             .push_str("# HELP mcp_system_memory_usage_bytes Current memory usage in bytes\n");
@@ -326,6 +988,8 @@ impl MetricsCollector {
             request: RequestMetrics::default(),
             transport: TransportMetrics::default(),
             system: SystemMetrics::default(),
+            rejects: RejectMetrics::default(),
+            recovery: HashMap::new(),
         };
 
         info!("Metrics reset completed");
@@ -357,7 +1021,10 @@ impl Default for RequestMetrics {
             average_response_time: 0.0,
             method_counts: HashMap::new(),
             response_time_histogram: HashMap::new(),
+            latency: HashMap::new(),
             last_request_time: None,
+            by_transport: HashMap::new(),
+            by_protocol_version: HashMap::new(),
         }
     }
 }
@@ -379,6 +1046,7 @@ impl Default for SystemMetrics {
 pub struct RequestTimer {
     start: Instant,
     method: String,
+    transport: Option<String>,
     metrics: Arc<MetricsCollector>,
 }
 
@@ -388,16 +1056,32 @@ impl RequestTimer {
         Self {
             start: Instant::now(),
             method: method.into(),
+            transport: None,
             metrics,
         }
     }
 
+    /// Also count the request against `transport`, e.g. `stdio` or `http`
+    pub fn with_transport(mut self, transport: impl Into<String>) -> Self {
+        self.transport = Some(transport.into());
+        self
+    }
+
     /// Finish the timer and record the metrics
     pub async fn finish(self, success: bool) {
         let duration = self.start.elapsed();
-        self.metrics
-            .record_request(&self.method, duration, success)
-            .await;
+        match &self.transport {
+            Some(transport) => {
+                self.metrics
+                    .record_transport_request(transport, &self.method, duration, success)
+                    .await
+            }
+            None => {
+                self.metrics
+                    .record_request(&self.method, duration, success)
+                    .await
+            }
+        }
 
         debug!(
             "Request completed: method={}, duration={:?}, success={}",
@@ -482,6 +1166,196 @@ mod tests {
         assert!(prometheus_output.contains("mcp_request_duration_average"));
     }
 
+    #[tokio::test]
+    async fn test_latency_histograms_and_percentiles() {
+        let collector =
+            Arc::new(MetricsCollector::new().with_latency_buckets(vec![0.1, 0.01, 1.0]));
+        for millis in 1..=100 {
+            collector
+                .record_request("tools/call", Duration::from_millis(millis * 5), true)
+                .await;
+        }
+
+        let percentiles = collector.latency_percentiles("tools/call").await.unwrap();
+        assert_eq!(percentiles.p50, Duration::from_millis(250));
+        assert_eq!(percentiles.p95, Duration::from_millis(475));
+        assert_eq!(percentiles.p99, Duration::from_millis(495));
+        assert!(collector.latency_percentiles("tools/list").await.is_none());
+
+        let histogram = &collector.get_metrics().await.request.latency["tools/call"];
+        assert_eq!(histogram.bounds, [0.01, 0.1, 1.0]);
+        assert_eq!(histogram.cumulative_counts(), [2, 20, 100, 100]);
+
+        let prometheus_output = collector.export_prometheus().await;
+        assert!(prometheus_output.contains("# TYPE mcp_request_duration_seconds histogram"));
+        assert!(
+            prometheus_output.contains(
+                "mcp_request_duration_seconds_bucket{method=\"tools/call\",le=\"0.1\"} 20"
+            )
+        );
+        assert!(prometheus_output.contains(
+            "mcp_request_duration_seconds_bucket{method=\"tools/call\",le=\"+Inf\"} 100"
+        ));
+        assert!(
+            prometheus_output
+                .contains("mcp_request_duration_seconds_count{method=\"tools/call\"} 100")
+        );
+        assert!(prometheus_output.contains(
+            "mcp_request_duration_quantiles_seconds{method=\"tools/call\",quantile=\"0.95\"} 0.475"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_requests_broken_down_by_transport() {
+        let collector = Arc::new(MetricsCollector::new());
+
+        collector
+            .record_transport_request("stdio", "tools/call", Duration::from_millis(10), true)
+            .await;
+        collector
+            .record_transport_request("http", "tools/call", Duration::from_millis(20), true)
+            .await;
+        RequestTimer::start("tools/list", collector.clone())
+            .with_transport("http")
+            .finish(false)
+            .await;
+
+        let metrics = collector.get_metrics().await;
+        assert_eq!(metrics.request.total_requests, 3);
+        assert_eq!(metrics.request.by_transport["stdio"].total_requests, 1);
+        let http = &metrics.request.by_transport["http"];
+        assert_eq!((http.total_requests, http.failed_requests), (2, 1));
+
+        let prometheus_output = collector.export_prometheus().await;
+        assert!(
+            prometheus_output.contains("mcp_requests_by_transport_total{transport=\"http\"} 2")
+        );
+        assert!(
+            prometheus_output
+                .contains("mcp_requests_failed_by_transport_total{transport=\"http\"} 1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_broken_down_by_protocol_version() {
+        let collector = MetricsCollector::new();
+
+        collector.record_protocol_version("2025-06-18").await;
+        collector.record_protocol_version("2025-06-18").await;
+        collector.record_protocol_version("2025-03-26").await;
+
+        let metrics = collector.get_metrics().await;
+        assert_eq!(metrics.request.by_protocol_version["2025-06-18"], 2);
+        assert_eq!(metrics.request.by_protocol_version["2025-03-26"], 1);
+
+        let prometheus_output = collector.export_prometheus().await;
+        assert!(
+            prometheus_output
+                .contains("mcp_requests_by_protocol_version_total{version=\"2025-03-26\"} 1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_are_counted_apart_from_requests() {
+        let collector = Arc::new(MetricsCollector::new());
+
+        collector
+            .record_reject(RejectReason::ParseError, None, Some("session-1"))
+            .await;
+        collector
+            .record_reject(
+                RejectReason::MethodNotFound,
+                Some("tools/explode"),
+                Some("session-1"),
+            )
+            .await;
+        collector
+            .record_request("tools/list", Duration::from_millis(5), false)
+            .await;
+
+        let metrics = collector.get_metrics().await;
+        assert_eq!(metrics.request.total_requests, 1);
+        assert_eq!(metrics.rejects.total_rejects, 2);
+        assert_eq!(metrics.rejects.by_reason[&RejectReason::ParseError], 1);
+        assert_eq!(metrics.rejects.by_method["tools/explode"], 1);
+        assert_eq!(metrics.rejects.by_session["session-1"], 2);
+        let last = metrics.rejects.recent.last().unwrap();
+        assert_eq!(last.reason, RejectReason::MethodNotFound);
+        assert_eq!(last.method.as_deref(), Some("tools/explode"));
+
+        let prometheus_output = collector.export_prometheus().await;
+        assert!(prometheus_output.contains("mcp_rejects_total 2"));
+        assert!(
+            prometheus_output.contains("mcp_rejects_by_reason_total{reason=\"parse_error\"} 1")
+        );
+        assert!(
+            prometheus_output.contains("mcp_rejects_by_method_total{method=\"tools/explode\"} 1")
+        );
+        assert_eq!(
+            RejectReason::from_error_code(-32602),
+            Some(RejectReason::InvalidParams)
+        );
+        assert_eq!(RejectReason::from_error_code(-32000), None);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_is_tracked_per_connection() {
+        let collector = Arc::new(MetricsCollector::new());
+
+        collector
+            .record_recovery_attempt("upstream", 1, Duration::from_millis(200))
+            .await;
+        collector
+            .record_recovery_outcome("upstream", 1, false)
+            .await;
+        collector.record_circuit_state("upstream", "open").await;
+        collector
+            .record_recovery_attempt("upstream", 2, Duration::from_secs(2))
+            .await;
+        collector
+            .record_health_probe("upstream", true, Duration::from_millis(3))
+            .await;
+        collector.record_recovery_outcome("upstream", 2, true).await;
+        collector.record_circuit_state("upstream", "closed").await;
+        collector.record_recovery_exhausted("backup", 5).await;
+
+        let metrics = collector.get_metrics().await;
+        let upstream = &metrics.recovery["upstream"];
+        assert_eq!(upstream.attempts, 2);
+        assert_eq!(upstream.successes, 1);
+        assert_eq!(upstream.failures, 1);
+        assert_eq!(upstream.backoff.count, 2);
+        assert_eq!(upstream.probes, 1);
+        assert_eq!(upstream.circuit_state, "closed");
+        assert_eq!(upstream.circuit_transitions["open"], 1);
+        assert_eq!(metrics.recovery["backup"].exhausted, 1);
+
+        let prometheus_output = collector.export_prometheus().await;
+        assert!(
+            prometheus_output
+                .contains("mcp_transport_recovery_attempts_total{connection=\"upstream\"} 2")
+        );
+        assert!(
+            prometheus_output
+                .contains("mcp_transport_recovery_exhausted_total{connection=\"backup\"} 1")
+        );
+        assert!(prometheus_output.contains(
+            "mcp_transport_recovery_backoff_seconds_bucket{connection=\"upstream\",le=\"0.25\"} 1"
+        ));
+        assert!(
+            prometheus_output.contains(
+                "mcp_transport_circuit_state{connection=\"upstream\",state=\"closed\"} 1"
+            )
+        );
+        assert!(
+            prometheus_output
+                .contains("mcp_transport_circuit_state{connection=\"upstream\",state=\"open\"} 0")
+        );
+        assert!(prometheus_output.contains(
+            "mcp_transport_circuit_transitions_total{connection=\"upstream\",state=\"open\"} 1"
+        ));
+    }
+
     #[tokio::test]
     async fn test_metrics_reset() {
         let collector = Arc::new(MetricsCollector::new());
@@ -2,13 +2,48 @@
 //!
 //! This module provides comprehensive distributed tracing capabilities for MCP servers
 //! and clients, including OpenTelemetry integration, span management, and trace export.
-
+//!
+//! With the `otlp` feature and [`TracingConfig::enable_otlp`] set,
+//! [`TracingSystem::init`] exports spans over OTLP/gRPC to
+//! [`TracingConfig::otlp_endpoint`] (the exporter's default,
+//! `http://localhost:4317`, if unset) and registers an
+//! [`OpenTelemetryPropagator`]. Clients then send the trace context of their
+//! request spans in `_meta["traceparent"]`, and servers make the handler span
+//! of each request a child of it, so both sides of a call show up in one
+//! trace; see [`ultrafast_mcp_core::protocol::trace_context`]. The OTLP
+//! exporter needs to be initialized from within a Tokio runtime.
+
+#[cfg(feature = "otlp")]
+use std::collections::HashMap;
+#[cfg(feature = "otlp")]
+use std::sync::Arc;
+
+#[cfg(feature = "otlp")]
+use opentelemetry::{KeyValue, propagation::TextMapPropagator, trace::TracerProvider as _};
+#[cfg(feature = "otlp")]
+use opentelemetry_sdk::{
+    Resource,
+    propagation::TraceContextPropagator,
+    trace::{Sampler, SdkTracerProvider},
+};
 use tracing::{Level, debug, error, info, warn};
+#[cfg(feature = "otlp")]
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::{
-    EnvFilter,
-    fmt::{format::FmtSpan, time::UtcTime},
+    EnvFilter, Layer, Registry,
+    fmt::{self, format::FmtSpan, time::UtcTime},
+    layer::{Layered, SubscriberExt},
+    util::SubscriberInitExt,
+};
+#[cfg(feature = "otlp")]
+use ultrafast_mcp_core::protocol::trace_context::{
+    self, TRACEPARENT_META_KEY, TRACESTATE_META_KEY, TraceContext, TracePropagator,
 };
 
+/// What the output and export layers of the subscriber are stacked on
+type FilteredRegistry = Layered<EnvFilter, Registry>;
+type BoxedLayer = Box<dyn Layer<FilteredRegistry> + Send + Sync>;
+
 /// Configuration for tracing system
 #[derive(Debug, Clone)]
 pub struct TracingConfig {
@@ -48,7 +83,9 @@ impl Default for TracingConfig {
 /// Tracing system for managing distributed tracing
 pub struct TracingSystem {
     config: TracingConfig,
-    _guard: Option<()>,
+    /// Exports spans over OTLP until the system is dropped
+    #[cfg(feature = "otlp")]
+    tracer_provider: Option<SdkTracerProvider>,
 }
 
 impl TracingSystem {
@@ -56,7 +93,8 @@ impl TracingSystem {
     pub fn new(config: TracingConfig) -> Self {
         Self {
             config,
-            _guard: None,
+            #[cfg(feature = "otlp")]
+            tracer_provider: None,
         }
     }
 
@@ -66,30 +104,53 @@ impl TracingSystem {
             return Ok(Self::new(config));
         }
 
-        // Initialize the subscriber with console output
-        if config.enable_console {
-            tracing_subscriber::fmt()
+        let output: BoxedLayer = if config.enable_console {
+            fmt::layer()
                 .with_timer(UtcTime::rfc_3339())
                 .with_span_events(FmtSpan::CLOSE)
                 .with_target(false)
                 .with_thread_ids(false)
                 .with_thread_names(false)
-                .with_env_filter(EnvFilter::from_default_env())
-                .init();
+                .boxed()
         } else if config.enable_json {
-            tracing_subscriber::fmt()
+            fmt::layer()
                 .json()
                 .with_timer(UtcTime::rfc_3339())
                 .with_span_events(FmtSpan::CLOSE)
-                .with_env_filter(EnvFilter::from_default_env())
-                .init();
+                .boxed()
         } else {
-            tracing_subscriber::fmt()
-                .with_env_filter(EnvFilter::from_default_env())
-                .init();
-        }
+            fmt::layer().boxed()
+        };
 
-        let guard = ();
+        #[cfg(feature = "otlp")]
+        let tracer_provider = if config.enable_otlp {
+            Some(otlp_tracer_provider(&config)?)
+        } else {
+            None
+        };
+        #[cfg(feature = "otlp")]
+        let export: Option<BoxedLayer> = tracer_provider.as_ref().map(|provider| {
+            tracing_opentelemetry::layer()
+                .with_tracer(provider.tracer("ultrafast-mcp"))
+                .boxed()
+        });
+        #[cfg(not(feature = "otlp"))]
+        let export: Option<BoxedLayer> = None;
+
+        tracing_subscriber::registry()
+            .with(EnvFilter::from_default_env())
+            .with(
+                [Some(output), export]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>(),
+            )
+            .try_init()?;
+
+        #[cfg(feature = "otlp")]
+        if tracer_provider.is_some() {
+            trace_context::set_propagator(Arc::new(OpenTelemetryPropagator::default()));
+        }
 
         info!(
             "Tracing system initialized for service: {} v{}",
@@ -98,7 +159,8 @@ impl TracingSystem {
 
         Ok(Self {
             config,
-            _guard: Some(guard),
+            #[cfg(feature = "otlp")]
+            tracer_provider,
         })
     }
 
@@ -156,8 +218,73 @@ impl Drop for TracingSystem {
     fn drop(&mut self) {
         if self.config.enabled {
             info!("Shutting down tracing system");
-            // The guard will automatically flush any pending traces
         }
+        // Export the spans still waiting in the batch
+        #[cfg(feature = "otlp")]
+        if let Some(Err(e)) = self
+            .tracer_provider
+            .take()
+            .map(|provider| provider.shutdown())
+        {
+            warn!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// The tracer provider exporting spans over OTLP/gRPC
+#[cfg(feature = "otlp")]
+fn otlp_tracer_provider(config: &TracingConfig) -> anyhow::Result<SdkTracerProvider> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    let mut exporter = opentelemetry_otlp::SpanExporter::builder().with_tonic();
+    if let Some(endpoint) = &config.otlp_endpoint {
+        exporter = exporter.with_endpoint(endpoint.clone());
+    }
+    let resource = Resource::builder()
+        .with_service_name(config.service_name.clone())
+        .with_attribute(KeyValue::new(
+            "service.version",
+            config.service_version.clone(),
+        ))
+        .build();
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter.build()?)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_rate,
+        ))))
+        .with_resource(resource)
+        .build())
+}
+
+/// Carries OpenTelemetry span contexts in request `_meta`, as W3C Trace
+/// Context
+#[cfg(feature = "otlp")]
+#[derive(Debug, Default)]
+pub struct OpenTelemetryPropagator {
+    inner: TraceContextPropagator,
+}
+
+#[cfg(feature = "otlp")]
+impl TracePropagator for OpenTelemetryPropagator {
+    fn current(&self) -> Option<TraceContext> {
+        let mut carrier = HashMap::new();
+        self.inner
+            .inject_context(&tracing::Span::current().context(), &mut carrier);
+        TraceContext::new(
+            carrier.remove(TRACEPARENT_META_KEY)?,
+            carrier.remove(TRACESTATE_META_KEY),
+        )
+    }
+
+    fn set_parent(&self, span: &tracing::Span, context: &TraceContext) {
+        let mut carrier = HashMap::from([(
+            TRACEPARENT_META_KEY.to_string(),
+            context.traceparent().to_string(),
+        )]);
+        if let Some(tracestate) = context.tracestate() {
+            carrier.insert(TRACESTATE_META_KEY.to_string(), tracestate.to_string());
+        }
+        span.set_parent(self.inner.extract(&carrier));
     }
 }
 
@@ -369,6 +496,39 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "otlp")]
+    #[test]
+    fn test_spans_join_the_trace_they_were_called_from() {
+        use opentelemetry::trace::TraceContextExt;
+
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let propagator = OpenTelemetryPropagator::default();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let client = tracing::info_span!("mcp_client_request");
+            let context = client.in_scope(|| propagator.current()).unwrap();
+            assert!(propagator.current().is_none());
+
+            let mut params = None;
+            context.inject(&mut params);
+            let server = tracing::info_span!("mcp_request");
+            propagator.set_parent(&server, &TraceContext::from_meta(params.as_ref()).unwrap());
+
+            let server_context = server.context();
+            let server_span = server_context.span();
+            assert_eq!(
+                server_span.span_context().trace_id().to_string(),
+                context.trace_id()
+            );
+            assert_ne!(
+                server_span.span_context().span_id().to_string(),
+                context.parent_span_id()
+            );
+        });
+    }
+
     #[test]
     fn test_tracing_utils_events() {
         // These should not panic
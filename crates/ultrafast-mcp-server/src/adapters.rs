@@ -0,0 +1,635 @@
+//! Handlers built from plain functions and closures
+//!
+//! [`UltraFastServer::with_tool_handler`] accepts anything implementing
+//! [`IntoToolHandler`], so small servers can pass a function instead of
+//! defining a handler type:
+//!
+//! - an `Arc` of a [`ToolHandler`], as before
+//! - a function or closure taking a [`ToolCall`], which handles every call;
+//!   the tools it lists are the ones registered on the server
+//! - a `(Tool, function)` pair, whose function takes the call's arguments and
+//!   serves that single tool
+//!
+//! [`UltraFastServer::with_resource_handler`] likewise accepts any
+//! [`IntoResourceHandler`]: a [`StaticResources`] map of fixed documents, or
+//! a `(Vec<Resource>, function)` pair whose function reads one of the listed
+//! resources by URI.
+//!
+//! ```rust
+//! use serde_json::Value;
+//! use ultrafast_mcp_core::{
+//!     MCPResult,
+//!     types::tools::{Tool, ToolContent, ToolResult},
+//! };
+//! use ultrafast_mcp_server::{ServerCapabilities, ServerInfo, UltraFastServer};
+//!
+//! async fn echo(arguments: Value) -> MCPResult<ToolResult> {
+//!     Ok(ToolResult {
+//!         content: vec![ToolContent::text(arguments.to_string())],
+//!         is_error: None,
+//!         structured_content: None,
+//!         progress_summary: None,
+//!     })
+//! }
+//!
+//! # let info = ServerInfo {
+//! #     name: "echo".to_string(),
+//! #     version: "1.0.0".to_string(),
+//! #     description: None,
+//! #     authors: None,
+//! #     homepage: None,
+//! #     license: None,
+//! #     repository: None,
+//! # };
+//! let tool = Tool::new(
+//!     "echo".to_string(),
+//!     "Echo the arguments".to_string(),
+//!     serde_json::json!({"type": "object"}),
+//! );
+//! let server = UltraFastServer::new(info, ServerCapabilities::default())
+//!     .with_tool_handler((tool, echo));
+//! ```
+//!
+//! [`UltraFastServer::with_tool_handler`]: crate::UltraFastServer::with_tool_handler
+//! [`UltraFastServer::with_resource_handler`]: crate::UltraFastServer::with_resource_handler
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult, ResourceError},
+    types::{
+        resources::{
+            ByteRange, ListResourceTemplatesRequest, ListResourceTemplatesResponse,
+            ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
+            Resource, ResourceContent,
+        },
+        roots::{Root, RootOperation, RootSecurityValidator},
+        tools::{ListToolsRequest, ListToolsResponse, Tool, ToolCall, ToolResult},
+    },
+};
+
+use crate::handlers::{ResourceHandler, ToolHandler};
+use crate::resource_stream::ResourceByteStream;
+
+/// Conversion into a shared [`ToolHandler`]
+///
+/// `Marker` only tells the implementations apart and is inferred; see the
+/// [module documentation](self) for what can be converted.
+pub trait IntoToolHandler<Marker> {
+    fn into_tool_handler(self) -> Arc<dyn ToolHandler>;
+}
+
+/// Types distinguishing the [`IntoToolHandler`] implementations
+pub mod markers {
+    /// A [`ToolHandler`](crate::ToolHandler) value
+    pub enum Handler {}
+    /// An `Arc` of a [`ToolHandler`](crate::ToolHandler)
+    pub enum SharedHandler {}
+    /// A function taking a [`ToolCall`](ultrafast_mcp_core::types::tools::ToolCall)
+    pub enum ToolCallFn {}
+    /// A tool definition and a function taking its arguments
+    pub enum ArgumentsFn {}
+    /// Resource definitions and a function reading them by URI
+    pub enum ReadFn {}
+}
+
+impl<T: ToolHandler + 'static> IntoToolHandler<markers::Handler> for T {
+    fn into_tool_handler(self) -> Arc<dyn ToolHandler> {
+        Arc::new(self)
+    }
+}
+
+impl<T: ToolHandler + ?Sized + 'static> IntoToolHandler<markers::SharedHandler> for Arc<T> {
+    fn into_tool_handler(self) -> Arc<dyn ToolHandler> {
+        Arc::new(SharedToolHandler(self))
+    }
+}
+
+impl<F, Fut> IntoToolHandler<markers::ToolCallFn> for F
+where
+    F: Fn(ToolCall) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = MCPResult<ToolResult>> + Send + 'static,
+{
+    fn into_tool_handler(self) -> Arc<dyn ToolHandler> {
+        Arc::new(FnToolHandler { handler: self })
+    }
+}
+
+impl<F, Fut> IntoToolHandler<markers::ArgumentsFn> for (Tool, F)
+where
+    F: Fn(Value) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = MCPResult<ToolResult>> + Send + 'static,
+{
+    fn into_tool_handler(self) -> Arc<dyn ToolHandler> {
+        let (tool, handler) = self;
+        Arc::new(SingleToolHandler { tool, handler })
+    }
+}
+
+/// Forwards to a handler that is already shared, without re-wrapping
+/// `Arc<dyn ToolHandler>` values
+struct SharedToolHandler<T: ?Sized>(Arc<T>);
+
+#[async_trait]
+impl<T: ToolHandler + ?Sized + 'static> ToolHandler for SharedToolHandler<T> {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        self.0.handle_tool_call(call).await
+    }
+
+    async fn handle_tool_call_with_context(
+        &self,
+        call: ToolCall,
+        context: crate::Context,
+    ) -> MCPResult<ToolResult> {
+        self.0.handle_tool_call_with_context(call, context).await
+    }
+
+    async fn list_tools(&self, request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        self.0.list_tools(request).await
+    }
+}
+
+/// Handles every call with one function, listing no tools of its own
+struct FnToolHandler<F> {
+    handler: F,
+}
+
+#[async_trait]
+impl<F, Fut> ToolHandler for FnToolHandler<F>
+where
+    F: Fn(ToolCall) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = MCPResult<ToolResult>> + Send + 'static,
+{
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        (self.handler)(call).await
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        // An empty listing makes the server list its registered tools
+        Ok(ListToolsResponse {
+            tools: Vec::new(),
+            next_cursor: None,
+        })
+    }
+}
+
+/// Serves a single tool with a function of its arguments
+struct SingleToolHandler<F> {
+    tool: Tool,
+    handler: F,
+}
+
+#[async_trait]
+impl<F, Fut> ToolHandler for SingleToolHandler<F>
+where
+    F: Fn(Value) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = MCPResult<ToolResult>> + Send + 'static,
+{
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        if call.name != self.tool.name {
+            return Err(MCPError::not_found(format!(
+                "Tool not found: {}",
+                call.name
+            )));
+        }
+        let arguments = call
+            .arguments
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        (self.handler)(arguments).await
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        Ok(ListToolsResponse {
+            tools: vec![self.tool.clone()],
+            next_cursor: None,
+        })
+    }
+}
+
+/// Conversion into a shared [`ResourceHandler`]
+///
+/// `Marker` only tells the implementations apart and is inferred; see the
+/// [module documentation](self) for what can be converted.
+pub trait IntoResourceHandler<Marker> {
+    fn into_resource_handler(self) -> Arc<dyn ResourceHandler>;
+}
+
+impl<T: ResourceHandler + 'static> IntoResourceHandler<markers::Handler> for T {
+    fn into_resource_handler(self) -> Arc<dyn ResourceHandler> {
+        Arc::new(self)
+    }
+}
+
+impl<T: ResourceHandler + ?Sized + 'static> IntoResourceHandler<markers::SharedHandler> for Arc<T> {
+    fn into_resource_handler(self) -> Arc<dyn ResourceHandler> {
+        Arc::new(SharedResourceHandler(self))
+    }
+}
+
+impl<F, Fut> IntoResourceHandler<markers::ReadFn> for (Vec<Resource>, F)
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = MCPResult<ResourceContent>> + Send + 'static,
+{
+    fn into_resource_handler(self) -> Arc<dyn ResourceHandler> {
+        let (resources, read) = self;
+        Arc::new(FnResourceHandler { resources, read })
+    }
+}
+
+/// Check `uri` against the client's roots the way the bundled handlers do:
+/// advisory unless a `file://` root covers it, in which case the root's
+/// security policy applies
+pub(crate) fn validate_against_roots(
+    uri: &str,
+    operation: RootOperation,
+    roots: &[Root],
+) -> MCPResult<()> {
+    let Some(root) = roots.iter().find(|root| uri.starts_with(&root.uri)) else {
+        return Ok(());
+    };
+    if !root.uri.starts_with("file://") {
+        return Ok(());
+    }
+    RootSecurityValidator::default()
+        .validate_access(root, uri, operation)
+        .map_err(|e| {
+            MCPError::Resource(ResourceError::AccessDenied(format!(
+                "Root validation failed: {e}"
+            )))
+        })
+}
+
+fn no_templates() -> ListResourceTemplatesResponse {
+    ListResourceTemplatesResponse {
+        resource_templates: Vec::new(),
+        next_cursor: None,
+    }
+}
+
+/// A resource listing `content` under `uri`, named after the URI's last
+/// segment
+pub(crate) fn describe_content(uri: String, content: &ResourceContent) -> Resource {
+    let name = uri
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .find(|segment| !segment.is_empty())
+        .unwrap_or(&uri)
+        .to_string();
+    let mut resource = Resource::new(uri, name);
+    resource.mime_type = content.mime_type().map(str::to_string);
+    resource
+}
+
+/// Fixed documents served by URI
+///
+/// Resources are listed in URI order. Each is named after the last segment of
+/// its URI unless added with [`with_resource`](Self::with_resource).
+///
+/// ```rust
+/// use ultrafast_mcp_server::StaticResources;
+///
+/// let resources = StaticResources::new()
+///     .with_text("docs://readme", "# Hello")
+///     .with_json("docs://config", &serde_json::json!({"debug": false}));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StaticResources {
+    entries: BTreeMap<String, (Resource, ResourceContent)>,
+}
+
+impl StaticResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve each content under its URI
+    pub fn from_map(contents: HashMap<String, ResourceContent>) -> Self {
+        contents
+            .into_iter()
+            .fold(Self::new(), |resources, (uri, content)| {
+                resources.with_content(uri, content)
+            })
+    }
+
+    /// Serve `text` as `text/plain`
+    pub fn with_text(self, uri: impl Into<String>, text: impl Into<String>) -> Self {
+        let uri = uri.into();
+        let content = ResourceContent::text(uri.clone(), text.into());
+        self.with_content(uri, content)
+    }
+
+    /// Serve `value` as `application/json`
+    pub fn with_json(self, uri: impl Into<String>, value: &Value) -> Self {
+        let uri = uri.into();
+        let content = ResourceContent::json(uri.clone(), value);
+        self.with_content(uri, content)
+    }
+
+    /// Serve `content` under `uri`, listed with a name derived from the URI
+    pub fn with_content(self, uri: impl Into<String>, content: ResourceContent) -> Self {
+        let resource = describe_content(uri.into(), &content);
+        self.with_resource(resource, content)
+    }
+
+    /// Serve `content` under `resource`'s URI, listed as `resource`
+    pub fn with_resource(mut self, resource: Resource, content: ResourceContent) -> Self {
+        self.entries
+            .insert(resource.uri.clone(), (resource, content));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[async_trait]
+impl ResourceHandler for StaticResources {
+    async fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        let (_, content) = self
+            .entries
+            .get(&request.uri)
+            .ok_or_else(|| MCPError::Resource(ResourceError::NotFound(request.uri.clone())))?;
+        Ok(ReadResourceResponse {
+            contents: vec![content.clone()],
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        _request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        Ok(ListResourcesResponse {
+            resources: self
+                .entries
+                .values()
+                .map(|(resource, _)| resource.clone())
+                .collect(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: ListResourceTemplatesRequest,
+    ) -> MCPResult<ListResourceTemplatesResponse> {
+        Ok(no_templates())
+    }
+
+    async fn validate_resource_access(
+        &self,
+        uri: &str,
+        operation: RootOperation,
+        roots: &[Root],
+    ) -> MCPResult<()> {
+        validate_against_roots(uri, operation, roots)
+    }
+}
+
+/// Forwards to a handler that is already shared, without re-wrapping
+/// `Arc<dyn ResourceHandler>` values
+struct SharedResourceHandler<T: ?Sized>(Arc<T>);
+
+#[async_trait]
+impl<T: ResourceHandler + ?Sized + 'static> ResourceHandler for SharedResourceHandler<T> {
+    async fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        self.0.read_resource(request).await
+    }
+
+    async fn list_resources(
+        &self,
+        request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        self.0.list_resources(request).await
+    }
+
+    async fn list_resource_templates(
+        &self,
+        request: ListResourceTemplatesRequest,
+    ) -> MCPResult<ListResourceTemplatesResponse> {
+        self.0.list_resource_templates(request).await
+    }
+
+    async fn validate_resource_access(
+        &self,
+        uri: &str,
+        operation: RootOperation,
+        roots: &[Root],
+    ) -> MCPResult<()> {
+        self.0.validate_resource_access(uri, operation, roots).await
+    }
+
+    async fn open_resource_stream(
+        &self,
+        uri: &str,
+        range: Option<ByteRange>,
+    ) -> MCPResult<Option<ResourceByteStream>> {
+        self.0.open_resource_stream(uri, range).await
+    }
+}
+
+/// Lists fixed resources and reads them with a function of their URI
+struct FnResourceHandler<F> {
+    resources: Vec<Resource>,
+    read: F,
+}
+
+#[async_trait]
+impl<F, Fut> ResourceHandler for FnResourceHandler<F>
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = MCPResult<ResourceContent>> + Send + 'static,
+{
+    async fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        if !self.resources.iter().any(|r| r.uri == request.uri) {
+            return Err(MCPError::Resource(ResourceError::NotFound(request.uri)));
+        }
+        let content = (self.read)(request.uri).await?;
+        Ok(ReadResourceResponse {
+            contents: vec![content],
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        _request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        Ok(ListResourcesResponse {
+            resources: self.resources.clone(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: ListResourceTemplatesRequest,
+    ) -> MCPResult<ListResourceTemplatesResponse> {
+        Ok(no_templates())
+    }
+
+    async fn validate_resource_access(
+        &self,
+        uri: &str,
+        operation: RootOperation,
+        roots: &[Root],
+    ) -> MCPResult<()> {
+        validate_against_roots(uri, operation, roots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use ultrafast_mcp_core::types::tools::ToolContent;
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            name: name.to_string(),
+            arguments: Some(arguments),
+        }
+    }
+
+    fn text(result: &ToolResult) -> &str {
+        match &result.content[0] {
+            ToolContent::Text { text } => text,
+            other => panic!("expected text content, got {other:?}"),
+        }
+    }
+
+    async fn shout(arguments: Value) -> MCPResult<ToolResult> {
+        let message = arguments["message"].as_str().unwrap_or_default();
+        Ok(ToolResult {
+            content: vec![ToolContent::text(message.to_uppercase())],
+            is_error: None,
+            structured_content: None,
+            progress_summary: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_function_of_arguments_serves_one_tool() {
+        let tool = Tool::new(
+            "shout".to_string(),
+            "Upper-case a message".to_string(),
+            json!({"type": "object"}),
+        );
+        let handler = (tool, shout).into_tool_handler();
+
+        let result = handler
+            .handle_tool_call(call("shout", json!({"message": "hi"})))
+            .await
+            .unwrap();
+        assert_eq!(text(&result), "HI");
+        assert!(
+            handler
+                .handle_tool_call(call("whisper", json!({})))
+                .await
+                .is_err()
+        );
+
+        let tools = handler
+            .list_tools(ListToolsRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(tools.tools[0].name, "shout");
+    }
+
+    #[tokio::test]
+    async fn test_closure_of_tool_call_handles_every_call() {
+        let handler = (|call: ToolCall| async move {
+            Ok(ToolResult {
+                content: vec![ToolContent::text(call.name)],
+                is_error: None,
+                structured_content: None,
+                progress_summary: None,
+            })
+        })
+        .into_tool_handler();
+
+        for name in ["first", "second"] {
+            let result = handler
+                .handle_tool_call(call(name, json!({})))
+                .await
+                .unwrap();
+            assert_eq!(text(&result), name);
+        }
+        let tools = handler
+            .list_tools(ListToolsRequest::default())
+            .await
+            .unwrap();
+        assert!(tools.tools.is_empty());
+    }
+
+    fn read(uri: &str) -> ReadResourceRequest {
+        ReadResourceRequest::new(uri)
+    }
+
+    #[tokio::test]
+    async fn test_static_resources_list_and_read() {
+        let resources = StaticResources::from_map(HashMap::from([
+            (
+                "docs://guide/intro".to_string(),
+                ResourceContent::text("docs://guide/intro".to_string(), "Hello".to_string()),
+            ),
+            (
+                "docs://config".to_string(),
+                ResourceContent::json("docs://config".to_string(), &json!({"debug": true})),
+            ),
+        ]))
+        .into_resource_handler();
+
+        let listed = resources
+            .list_resources(ListResourcesRequest::default())
+            .await
+            .unwrap();
+        let names: Vec<_> = listed.resources.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["config", "intro"]);
+        assert_eq!(
+            listed.resources[0].mime_type.as_deref(),
+            Some("application/json")
+        );
+
+        let response = resources
+            .read_resource(read("docs://guide/intro"))
+            .await
+            .unwrap();
+        let ResourceContent::Text { text, .. } = &response.contents[0] else {
+            panic!("expected text content");
+        };
+        assert_eq!(text, "Hello");
+        assert!(matches!(
+            resources.read_resource(read("docs://missing")).await,
+            Err(MCPError::Resource(ResourceError::NotFound(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_function_serves_listed_resources() {
+        let handler = (
+            vec![Resource::new("time://now".to_string(), "now".to_string())],
+            |uri: String| async move { Ok(ResourceContent::text(uri, "noon".to_string())) },
+        )
+            .into_resource_handler();
+
+        let listed = handler
+            .list_resources(ListResourcesRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.resources.len(), 1);
+        let response = handler.read_resource(read("time://now")).await.unwrap();
+        assert_eq!(response.contents.len(), 1);
+        assert!(handler.read_resource(read("time://later")).await.is_err());
+    }
+}
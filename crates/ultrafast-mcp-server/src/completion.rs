@@ -0,0 +1,459 @@
+//! Completion of prompt arguments and resource template variables
+//!
+//! A `completion/complete` request with a `ref/resource` reference names a
+//! resource template and one of its variables, e.g. `path` in
+//! `file:///{path}`. Completers registered with
+//! [`UltraFastServer::with_resource_template_completer`] answer these
+//! requests for their template; requests for other templates still go to the
+//! [`CompletionHandler`](crate::CompletionHandler).
+//!
+//! [`FilePathCompleter`] completes a variable with the files and directories
+//! under a root directory.
+//!
+//! A [`CompletionRouter`] is a `CompletionHandler` built from one provider
+//! per prompt argument or template variable:
+//!
+//! ```rust
+//! use ultrafast_mcp_server::CompletionRouter;
+//!
+//! let router = CompletionRouter::new()
+//!     .prompt_arg("greeting", "style", |_prefix| ["casual", "formal"])
+//!     .resource_var("test://items/{id}", "id", |_prefix| {
+//!         (1..=500).map(|id| id.to_string())
+//!     });
+//! ```
+//!
+//! [`UltraFastServer::with_resource_template_completer`]: crate::UltraFastServer::with_resource_template_completer
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult},
+    types::completion::{
+        CompleteRequest, CompleteResponse, Completion, CompletionKind, CompletionValue,
+    },
+};
+
+use crate::handlers::CompletionHandler;
+
+/// Most values returned in one completion response, as set by the protocol
+pub const MAX_COMPLETION_VALUES: usize = 100;
+
+/// Completes the variables of one resource template
+#[async_trait]
+pub trait ResourceTemplateCompleter: Send + Sync {
+    /// Values for `variable` that start with `prefix`
+    ///
+    /// `resolved` holds the values already chosen for the template's other
+    /// variables.
+    async fn complete(
+        &self,
+        variable: &str,
+        prefix: &str,
+        resolved: &HashMap<String, String>,
+    ) -> MCPResult<Vec<CompletionValue>>;
+}
+
+/// Completes a path variable with the entries under a root directory
+///
+/// Directories are suggested with a trailing `/` so the next request lists
+/// their contents. Paths leaving the root, such as `../etc`, complete to
+/// nothing.
+#[derive(Debug, Clone)]
+pub struct FilePathCompleter {
+    root: PathBuf,
+    variables: Option<Vec<String>>,
+    include_hidden: bool,
+}
+
+impl FilePathCompleter {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            variables: None,
+            include_hidden: false,
+        }
+    }
+
+    /// Only complete the named variable; others complete to nothing
+    pub fn for_variable(mut self, variable: impl Into<String>) -> Self {
+        self.variables
+            .get_or_insert_with(Vec::new)
+            .push(variable.into());
+        self
+    }
+
+    /// Suggest entries whose name starts with `.`
+    pub fn with_hidden_files(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+}
+
+#[async_trait]
+impl ResourceTemplateCompleter for FilePathCompleter {
+    async fn complete(
+        &self,
+        variable: &str,
+        prefix: &str,
+        _resolved: &HashMap<String, String>,
+    ) -> MCPResult<Vec<CompletionValue>> {
+        if let Some(variables) = &self.variables {
+            if !variables.iter().any(|name| name == variable) {
+                return Ok(Vec::new());
+            }
+        }
+
+        let (parent, name_prefix) = match prefix.rfind('/') {
+            Some(slash) => prefix.split_at(slash + 1),
+            None => ("", prefix),
+        };
+        let escapes_root = Path::new(parent)
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes_root {
+            return Ok(Vec::new());
+        }
+
+        let dir = self.root.join(parent);
+        let (parent, name_prefix) = (parent.to_string(), name_prefix.to_string());
+        let include_hidden = self.include_hidden;
+        let mut values = tokio::task::spawn_blocking(move || {
+            list_entries(&dir, &parent, &name_prefix, include_hidden)
+        })
+        .await
+        .map_err(|e| MCPError::internal_error(format!("Path completion failed: {e}")))??;
+        values.sort_by(|a, b| a.value.cmp(&b.value));
+        Ok(values)
+    }
+}
+
+/// Values for one prompt argument or template variable, given the typed
+/// prefix and the values already chosen for the other arguments
+type CompletionProvider =
+    Arc<dyn Fn(&str, &HashMap<String, String>) -> Vec<CompletionValue> + Send + Sync>;
+
+/// Completions served by providers registered per prompt argument and per
+/// resource template variable
+///
+/// The values a provider returns are matched against the typed value
+/// ignoring case: values starting with it come first, then values containing
+/// it, then values containing its characters in order. At most
+/// [`MAX_COMPLETION_VALUES`] are returned. References without a provider
+/// are answered with an invalid params error.
+#[derive(Clone, Default)]
+pub struct CompletionRouter {
+    prompts: HashMap<(String, String), CompletionProvider>,
+    resources: HashMap<(String, String), CompletionProvider>,
+}
+
+impl CompletionRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Complete `argument` of prompt `prompt` with the values of `provider`
+    pub fn prompt_arg<I>(
+        self,
+        prompt: impl Into<String>,
+        argument: impl Into<String>,
+        provider: impl Fn(&str) -> I + Send + Sync + 'static,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<CompletionValue>,
+    {
+        self.prompt_arg_with_context(prompt, argument, move |prefix, _| provider(prefix))
+    }
+
+    /// Complete `argument` of prompt `prompt` with values depending on the
+    /// prompt's other arguments
+    pub fn prompt_arg_with_context<I>(
+        mut self,
+        prompt: impl Into<String>,
+        argument: impl Into<String>,
+        provider: impl Fn(&str, &HashMap<String, String>) -> I + Send + Sync + 'static,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<CompletionValue>,
+    {
+        self.prompts
+            .insert((prompt.into(), argument.into()), boxed(provider));
+        self
+    }
+
+    /// Complete `variable` of resource template `template`, such as
+    /// `file:///{path}`, with the values of `provider`
+    pub fn resource_var<I>(
+        self,
+        template: impl Into<String>,
+        variable: impl Into<String>,
+        provider: impl Fn(&str) -> I + Send + Sync + 'static,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<CompletionValue>,
+    {
+        self.resource_var_with_context(template, variable, move |prefix, _| provider(prefix))
+    }
+
+    /// Complete `variable` of resource template `template` with values
+    /// depending on the template's other variables
+    pub fn resource_var_with_context<I>(
+        mut self,
+        template: impl Into<String>,
+        variable: impl Into<String>,
+        provider: impl Fn(&str, &HashMap<String, String>) -> I + Send + Sync + 'static,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<CompletionValue>,
+    {
+        self.resources
+            .insert((template.into(), variable.into()), boxed(provider));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prompts.is_empty() && self.resources.is_empty()
+    }
+}
+
+fn boxed<I>(
+    provider: impl Fn(&str, &HashMap<String, String>) -> I + Send + Sync + 'static,
+) -> CompletionProvider
+where
+    I: IntoIterator,
+    I::Item: Into<CompletionValue>,
+{
+    Arc::new(move |prefix, resolved| {
+        provider(prefix, resolved)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    })
+}
+
+/// How well `value` matches what the user typed, lower is better: a
+/// prefix, a substring, or the typed characters in order; case is ignored
+fn match_rank(value: &str, typed: &str) -> Option<u8> {
+    let (value, typed) = (value.to_lowercase(), typed.to_lowercase());
+    if value.starts_with(&typed) {
+        Some(0)
+    } else if value.contains(&typed) {
+        Some(1)
+    } else {
+        let mut chars = value.chars();
+        typed
+            .chars()
+            .all(|typed| chars.any(|c| c == typed))
+            .then_some(2)
+    }
+}
+
+#[async_trait]
+impl CompletionHandler for CompletionRouter {
+    async fn complete(&self, request: CompleteRequest) -> MCPResult<CompleteResponse> {
+        let (providers, kind) = match request.reference.ref_type.as_str() {
+            "ref/prompt" => (&self.prompts, "prompt"),
+            "ref/resource" => (&self.resources, "resource template"),
+            other => {
+                return Err(MCPError::invalid_params(format!(
+                    "Unsupported completion reference type '{other}'"
+                )));
+            }
+        };
+        let key = (request.reference.name, request.argument.name);
+        let provider = providers.get(&key).ok_or_else(|| {
+            MCPError::invalid_params(format!(
+                "No completions for argument '{}' of {kind} '{}'",
+                key.1, key.0
+            ))
+        })?;
+        let resolved = request
+            .context
+            .and_then(|context| context.arguments)
+            .unwrap_or_default();
+
+        let typed = &request.argument.value;
+        let mut ranked: Vec<(u8, CompletionValue)> = provider(typed, &resolved)
+            .into_iter()
+            .filter_map(|value| match_rank(&value.value, typed).map(|rank| (rank, value)))
+            .collect();
+        ranked.sort_by_key(|(rank, _)| *rank);
+        let total = ranked.len();
+        let values = ranked
+            .into_iter()
+            .take(MAX_COMPLETION_VALUES)
+            .map(|(_, value)| value)
+            .collect();
+        Ok(CompleteResponse {
+            completion: Completion::with_metadata(
+                values,
+                total as u32,
+                total > MAX_COMPLETION_VALUES,
+            ),
+            metadata: None,
+        })
+    }
+}
+
+/// Entries of `dir` starting with `name_prefix`, as paths below the root
+fn list_entries(
+    dir: &Path,
+    parent: &str,
+    name_prefix: &str,
+    include_hidden: bool,
+) -> MCPResult<Vec<CompletionValue>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut values = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(name_prefix) || (!include_hidden && name.starts_with('.')) {
+            continue;
+        }
+        let value = if entry.file_type()?.is_dir() {
+            CompletionValue::with_kind(format!("{parent}{name}/"), CompletionKind::Folder)
+        } else {
+            CompletionValue::with_kind(format!("{parent}{name}"), CompletionKind::File)
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::types::completion::CompletionContext;
+
+    #[tokio::test]
+    async fn test_file_path_completion_stays_under_root() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/main.rs"), "").unwrap();
+        std::fs::write(root.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.path().join(".env"), "").unwrap();
+        std::fs::write(root.path().join("README.md"), "").unwrap();
+
+        let completer = FilePathCompleter::new(root.path()).for_variable("path");
+        let complete = |prefix: &'static str| {
+            let completer = completer.clone();
+            async move {
+                completer
+                    .complete("path", prefix, &HashMap::new())
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|value| value.value)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(complete("").await, vec!["README.md", "src/"]);
+        assert_eq!(complete("src/m").await, vec!["src/main.rs"]);
+        assert!(complete("../").await.is_empty());
+        assert!(complete("missing/").await.is_empty());
+        assert!(
+            completer
+                .complete("other", "", &HashMap::new())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn prompt_request(prompt: &str, argument: &str, value: &str) -> CompleteRequest {
+        CompleteRequest::with_argument("ref/prompt", prompt, argument, value)
+    }
+
+    fn values(response: CompleteResponse) -> Vec<String> {
+        response
+            .completion
+            .values
+            .into_iter()
+            .map(|value| value.value)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_router_ranks_prefix_then_substring_then_fuzzy_matches() {
+        let router = CompletionRouter::new().prompt_arg("review", "language", |_| {
+            ["typescript", "python", "pytorch", "rust", "javascript"]
+        });
+
+        let response = router
+            .complete(prompt_request("review", "language", "Py"))
+            .await
+            .unwrap();
+        assert_eq!(values(response), ["python", "pytorch"]);
+        let response = router
+            .complete(prompt_request("review", "language", "script"))
+            .await
+            .unwrap();
+        assert_eq!(values(response), ["typescript", "javascript"]);
+        let response = router
+            .complete(prompt_request("review", "language", "rst"))
+            .await
+            .unwrap();
+        assert_eq!(values(response), ["rust"]);
+
+        assert!(
+            router
+                .complete(prompt_request("review", "framework", ""))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_router_passes_context_and_truncates() {
+        let router = CompletionRouter::new()
+            .prompt_arg_with_context("review", "framework", |_, resolved| {
+                match resolved.get("language").map(String::as_str) {
+                    Some("rust") => vec!["axum", "actix"],
+                    _ => vec!["react"],
+                }
+            })
+            .resource_var("test://items/{id}", "id", |_| {
+                (1..=250).map(|id| id.to_string())
+            });
+
+        let mut request = prompt_request("review", "framework", "");
+        request.context = Some(CompletionContext {
+            arguments: Some(HashMap::from([(
+                "language".to_string(),
+                "rust".to_string(),
+            )])),
+            ..CompletionContext::new()
+        });
+        assert_eq!(
+            values(router.complete(request).await.unwrap()),
+            ["axum", "actix"]
+        );
+
+        let response = router
+            .complete(CompleteRequest::with_argument(
+                "ref/resource",
+                "test://items/{id}",
+                "id",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.completion.values.len(), MAX_COMPLETION_VALUES);
+        assert_eq!(response.completion.total, Some(250));
+        assert_eq!(response.completion.has_more, Some(true));
+    }
+}
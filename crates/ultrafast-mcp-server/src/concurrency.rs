@@ -0,0 +1,301 @@
+//! Limiting how many requests are handled at once
+//!
+//! [`UltraFastServer::with_concurrency_limit`] bounds the requests in
+//! progress, protecting handlers that call expensive downstream services:
+//!
+//! - a global limit on requests from all sessions together
+//! - a per-session limit, applied to each session on its own, so one busy
+//!   client cannot take every global slot
+//!
+//! A request over a limit waits in a bounded queue for a slot to free up. A
+//! request that finds the queue full, or waits longer than the queue timeout,
+//! is answered with a
+//! [`SERVER_BUSY`](ultrafast_mcp_core::error::error_codes::SERVER_BUSY) error
+//! whose data carries `maxConcurrent` and the scope that was exceeded.
+//! `initialize` and `ping` are exempt by default.
+//!
+//! [`UltraFastServer::with_concurrency_limit`]: crate::UltraFastServer::with_concurrency_limit
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use ultrafast_mcp_core::error::{MCPError, RateLimitError};
+
+/// At most `max_concurrent` requests in progress, with up to `max_queued`
+/// more waiting for a slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimit {
+    pub max_concurrent: usize,
+    /// Requests that may wait for a slot; zero rejects a request as soon as
+    /// every slot is taken
+    pub max_queued: usize,
+    /// How long a queued request waits before it is rejected; `None` waits
+    /// until a slot frees up
+    pub queue_timeout: Option<Duration>,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            max_queued: 0,
+            queue_timeout: None,
+        }
+    }
+
+    pub fn with_queue(mut self, max_queued: usize) -> Self {
+        self.max_queued = max_queued;
+        self
+    }
+
+    pub fn with_queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = Some(queue_timeout);
+        self
+    }
+}
+
+/// Limits enforced by [`UltraFastServer::with_concurrency_limit`]
+///
+/// [`UltraFastServer::with_concurrency_limit`]: crate::UltraFastServer::with_concurrency_limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrencyConfig {
+    pub global: Option<ConcurrencyLimit>,
+    pub per_session: Option<ConcurrencyLimit>,
+    /// Methods no limit applies to
+    pub exempt_methods: HashSet<String>,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            global: None,
+            per_session: None,
+            exempt_methods: ["initialize", "ping"].map(String::from).into(),
+        }
+    }
+}
+
+impl ConcurrencyConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_global(mut self, limit: ConcurrencyLimit) -> Self {
+        self.global = Some(limit);
+        self
+    }
+
+    pub fn with_per_session(mut self, limit: ConcurrencyLimit) -> Self {
+        self.per_session = Some(limit);
+        self
+    }
+
+    pub fn with_exempt_method(mut self, method: impl Into<String>) -> Self {
+        self.exempt_methods.insert(method.into());
+        self
+    }
+}
+
+/// Which limit a rejected request exceeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConcurrencyScope {
+    Global,
+    Session(String),
+}
+
+/// A request rejected because the server was busy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerBusy {
+    pub scope: ConcurrencyScope,
+    pub limit: ConcurrencyLimit,
+}
+
+impl ServerBusy {
+    pub fn to_error(&self) -> MCPError {
+        MCPError::RateLimit(RateLimitError::ServerBusy {
+            limit: self.limit.max_concurrent as u32,
+        })
+    }
+
+    /// Data of the JSON-RPC error the request is answered with
+    pub fn error_data(&self) -> Value {
+        let mut data = json!({
+            "maxConcurrent": self.limit.max_concurrent,
+            "maxQueued": self.limit.max_queued,
+        });
+        match &self.scope {
+            ConcurrencyScope::Global => data["scope"] = json!("global"),
+            ConcurrencyScope::Session(session_id) => {
+                data["scope"] = json!("session");
+                data["sessionId"] = json!(session_id);
+            }
+        }
+        data
+    }
+}
+
+/// Slots of one limit and the requests waiting for them
+#[derive(Debug)]
+struct Gate {
+    limit: ConcurrencyLimit,
+    slots: Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+/// Counts a request as queued while it lives, including when the waiting
+/// request is dropped
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Gate {
+    fn new(limit: ConcurrencyLimit) -> Self {
+        Self {
+            limit,
+            slots: Arc::new(Semaphore::new(limit.max_concurrent)),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// A slot, unless the queue is full or the wait times out
+    async fn enter(&self) -> Option<OwnedSemaphorePermit> {
+        if let Ok(slot) = self.slots.clone().try_acquire_owned() {
+            return Some(slot);
+        }
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.limit.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        let _queued = QueuedGuard(&self.queued);
+        let slot = self.slots.clone().acquire_owned();
+        match self.limit.queue_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, slot).await.ok()?.ok(),
+            None => slot.await.ok(),
+        }
+    }
+}
+
+/// The slots a request holds while it is handled
+#[derive(Debug, Default)]
+pub(crate) struct ConcurrencyPermit {
+    _session: Option<OwnedSemaphorePermit>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+/// Enforces a [`ConcurrencyConfig`]
+#[derive(Debug)]
+pub(crate) struct ConcurrencyLimiter {
+    config: ConcurrencyConfig,
+    global: Option<Gate>,
+    sessions: Mutex<HashMap<String, Arc<Gate>>>,
+}
+
+impl ConcurrencyLimiter {
+    pub(crate) fn new(config: ConcurrencyConfig) -> Self {
+        Self {
+            global: config.global.map(Gate::new),
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take the slots a request needs, waiting in the queues if allowed
+    ///
+    /// The session slot is taken first, so a session over its own limit
+    /// does not hold a place in the global queue.
+    pub(crate) async fn acquire(
+        &self,
+        session_id: &str,
+        method: &str,
+    ) -> Result<ConcurrencyPermit, ServerBusy> {
+        let mut permit = ConcurrencyPermit::default();
+        if self.config.exempt_methods.contains(method) {
+            return Ok(permit);
+        }
+        if let Some(limit) = self.config.per_session {
+            let gate = self
+                .sessions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(session_id.to_string())
+                .or_insert_with(|| Arc::new(Gate::new(limit)))
+                .clone();
+            permit._session = Some(gate.enter().await.ok_or_else(|| ServerBusy {
+                scope: ConcurrencyScope::Session(session_id.to_string()),
+                limit,
+            })?);
+        }
+        if let Some(gate) = &self.global {
+            permit._global = Some(gate.enter().await.ok_or(ServerBusy {
+                scope: ConcurrencyScope::Global,
+                limit: gate.limit,
+            })?);
+        }
+        Ok(permit)
+    }
+
+    /// Forget the slots of a session that ended
+    pub(crate) fn remove_session(&self, session_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_over_the_limit_queue_or_are_rejected() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(
+            ConcurrencyConfig::new().with_global(ConcurrencyLimit::new(1).with_queue(1)),
+        ));
+        let running = limiter.acquire("a", "tools/call").await.unwrap();
+
+        // The second request waits for the first, the third finds the queue full
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("b", "tools/call").await.map(drop) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!queued.is_finished());
+        let rejected = limiter.acquire("c", "tools/call").await.unwrap_err();
+        assert_eq!(rejected.scope, ConcurrencyScope::Global);
+        assert_eq!(rejected.error_data()["maxConcurrent"], 1);
+        assert!(limiter.acquire("c", "ping").await.is_ok());
+
+        drop(running);
+        assert!(queued.await.unwrap().is_ok());
+        assert!(limiter.acquire("c", "tools/call").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_session_limit_and_queue_timeout() {
+        let limiter = ConcurrencyLimiter::new(
+            ConcurrencyConfig::new().with_per_session(
+                ConcurrencyLimit::new(1)
+                    .with_queue(1)
+                    .with_queue_timeout(Duration::from_millis(10)),
+            ),
+        );
+        let _running = limiter.acquire("a", "tools/call").await.unwrap();
+        assert!(limiter.acquire("b", "tools/call").await.is_ok());
+
+        let rejected = limiter.acquire("a", "tools/call").await.unwrap_err();
+        assert_eq!(rejected.scope, ConcurrencyScope::Session("a".to_string()));
+        assert!(matches!(
+            rejected.to_error(),
+            MCPError::RateLimit(RateLimitError::ServerBusy { limit: 1 })
+        ));
+    }
+}
@@ -3,22 +3,44 @@
 //! This module provides the Context type that allows tools and handlers to interact
 //! with the server for progress tracking, logging, and other operations.
 
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::borrow::Cow;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tracing::{debug, error, info, warn};
 
 use ultrafast_mcp_core::{
-    error::MCPResult,
+    error::{MCPError, MCPResult, ProtocolError},
+    i18n::{LocalizedMessage, MessageCatalog},
     protocol::jsonrpc::{JsonRpcMessage, JsonRpcRequest},
-    types::notifications::{LogLevel, LoggingMessageNotification, ProgressNotification},
+    types::{
+        elicitation::{ElicitationRequest, ElicitationResponse, ElicitationResult},
+        notifications::{
+            LogLevel, LoggingMessageNotification, ProgressNotification, TOOL_CONTENT_CHUNK_METHOD,
+            ToolContentChunk,
+        },
+        roots::Root,
+        sampling::{CreateMessageRequest, CreateMessageResponse},
+        tools::{ProgressCheckpoint, ToolContent, ToolProgressSummary, ToolResult},
+    },
 };
 
+use crate::handlers::ElicitationHandler;
+use crate::peer::ClientPeer;
+use crate::session_state::SessionState;
+use crate::usage::{SamplingUsage, UsageTracker};
+
 /// Simple cancellation manager for tracking cancelled requests
+///
+/// The server marks a request here when the client sends
+/// `notifications/cancelled` for it, which is what [`Context::is_cancelled`]
+/// and [`Context::cancelled`] observe.
 #[derive(Debug, Clone)]
 pub struct CancellationManager {
     cancelled_requests: Arc<tokio::sync::RwLock<std::collections::HashSet<String>>>,
+    cancelled: Arc<tokio::sync::Notify>,
 }
 
 impl CancellationManager {
@@ -27,12 +49,27 @@ impl CancellationManager {
             cancelled_requests: Arc::new(
                 tokio::sync::RwLock::new(std::collections::HashSet::new()),
             ),
+            cancelled: Arc::new(tokio::sync::Notify::new()),
         }
     }
 
     pub async fn cancel_request(&self, request_id: &str) {
         let mut requests = self.cancelled_requests.write().await;
         requests.insert(request_id.to_string());
+        self.cancelled.notify_waiters();
+    }
+
+    /// Wait until `request_id` is cancelled
+    pub async fn wait_cancelled(&self, request_id: &str) {
+        loop {
+            let notified = self.cancelled.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.is_cancelled(request_id).await {
+                return;
+            }
+            notified.await;
+        }
     }
 
     pub async fn is_cancelled(&self, request_id: &str) -> bool {
@@ -44,6 +81,11 @@ impl CancellationManager {
         let mut requests = self.cancelled_requests.write().await;
         requests.remove(request_id);
     }
+
+    /// Number of cancelled requests not yet cleared
+    pub async fn cancelled_count(&self) -> usize {
+        self.cancelled_requests.read().await.len()
+    }
 }
 
 impl Default for CancellationManager {
@@ -52,6 +94,88 @@ impl Default for CancellationManager {
     }
 }
 
+/// Checkpoints kept per tool call; older ones are dropped but still counted
+const MAX_PROGRESS_CHECKPOINTS: usize = 100;
+
+/// Collects the progress a tool call reports, for its result's
+/// [`ToolProgressSummary`], and the content it streams
+#[derive(Debug)]
+pub(crate) struct ProgressRecorder {
+    started: Instant,
+    steps: Mutex<RecordedSteps>,
+    streamed: Mutex<StreamedContent>,
+}
+
+#[derive(Debug, Default)]
+struct RecordedSteps {
+    count: u64,
+    checkpoints: VecDeque<ProgressCheckpoint>,
+}
+
+#[derive(Debug, Default)]
+struct StreamedContent {
+    /// Chunks sent to the client so far
+    sent: u64,
+    /// Chunks kept for the result, as the client did not ask for them to be
+    /// streamed
+    held: Vec<ToolContent>,
+}
+
+impl ProgressRecorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            steps: Mutex::new(RecordedSteps::default()),
+            streamed: Mutex::new(StreamedContent::default()),
+        }
+    }
+
+    /// Index of the next chunk sent to the client
+    fn next_chunk_index(&self) -> u64 {
+        let mut streamed = self.streamed.lock().unwrap();
+        streamed.sent += 1;
+        streamed.sent - 1
+    }
+
+    fn hold_chunk(&self, chunk: ToolContent) {
+        self.streamed.lock().unwrap().held.push(chunk);
+    }
+
+    fn record(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        let checkpoint = ProgressCheckpoint {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            progress,
+            total,
+            message: message.map(str::to_string),
+        };
+        let mut steps = self.steps.lock().unwrap();
+        steps.count += 1;
+        if steps.checkpoints.len() == MAX_PROGRESS_CHECKPOINTS {
+            steps.checkpoints.pop_front();
+        }
+        steps.checkpoints.push_back(checkpoint);
+    }
+
+    /// Fill in `result.progress_summary` if the call reported progress and
+    /// the handler did not set a summary itself
+    ///
+    /// Chunks held back from streaming are put before the content the tool
+    /// returned.
+    pub(crate) fn summarize(&self, mut result: ToolResult) -> ToolResult {
+        let held = std::mem::take(&mut self.streamed.lock().unwrap().held);
+        result.content.splice(0..0, held);
+        let steps = self.steps.lock().unwrap();
+        if steps.count > 0 && result.progress_summary.is_none() {
+            result.progress_summary = Some(ToolProgressSummary {
+                duration_ms: self.started.elapsed().as_millis() as u64,
+                steps_completed: steps.count,
+                checkpoints: steps.checkpoints.iter().cloned().collect(),
+            });
+        }
+        result
+    }
+}
+
 /// Logger configuration for the context
 #[derive(Debug, Clone)]
 pub struct LoggerConfig {
@@ -102,10 +226,19 @@ type NotificationSender = Arc<
 pub struct Context {
     session_id: Option<String>,
     request_id: Option<String>,
+    progress_token: Option<serde_json::Value>,
     metadata: HashMap<String, serde_json::Value>,
     logger_config: LoggerConfig,
     notification_sender: Option<NotificationSender>,
     cancellation_manager: Option<Arc<CancellationManager>>,
+    client_peer: Option<Arc<ClientPeer>>,
+    tool_name: Option<String>,
+    usage_tracker: Option<Arc<UsageTracker>>,
+    emulated_elicitation: Option<Arc<dyn ElicitationHandler>>,
+    progress_recorder: Option<Arc<ProgressRecorder>>,
+    message_catalog: Option<Arc<MessageCatalog>>,
+    locale: Option<String>,
+    session_state: SessionState,
 }
 
 impl std::fmt::Debug for Context {
@@ -113,9 +246,15 @@ impl std::fmt::Debug for Context {
         f.debug_struct("Context")
             .field("session_id", &self.session_id)
             .field("request_id", &self.request_id)
+            .field("progress_token", &self.progress_token)
             .field("metadata", &self.metadata)
             .field("logger_config", &self.logger_config)
             .field("notification_sender", &self.notification_sender.is_some())
+            .field("client_peer", &self.client_peer.is_some())
+            .field("tool_name", &self.tool_name)
+            .field("emulated_elicitation", &self.emulated_elicitation.is_some())
+            .field("locale", &self.locale)
+            .field("session_state", &self.session_state)
             .finish()
     }
 }
@@ -126,10 +265,19 @@ impl Context {
         Self {
             session_id: None,
             request_id: None,
+            progress_token: None,
             metadata: HashMap::new(),
             logger_config: LoggerConfig::default(),
             notification_sender: None,
             cancellation_manager: None,
+            client_peer: None,
+            tool_name: None,
+            usage_tracker: None,
+            emulated_elicitation: None,
+            progress_recorder: None,
+            message_catalog: None,
+            locale: None,
+            session_state: SessionState::new(),
         }
     }
 
@@ -145,6 +293,12 @@ impl Context {
         self
     }
 
+    /// Set the progress token the client sent in the request's `_meta`
+    pub fn with_progress_token(mut self, token: serde_json::Value) -> Self {
+        self.progress_token = Some(token);
+        self
+    }
+
     /// Add metadata to the context
     pub fn with_metadata(mut self, key: String, value: serde_json::Value) -> Self {
         self.metadata.insert(key, value);
@@ -169,16 +323,128 @@ impl Context {
         self
     }
 
+    /// Set the connected client, enabling server-to-client requests
+    pub fn with_client_peer(mut self, peer: Arc<ClientPeer>) -> Self {
+        self.client_peer = Some(peer);
+        self
+    }
+
+    /// Get the connected client, if the request arrived over a connection
+    pub fn client_peer(&self) -> Option<&Arc<ClientPeer>> {
+        self.client_peer.as_ref()
+    }
+
+    /// Set the tool being executed
+    pub fn with_tool_name(mut self, tool_name: String) -> Self {
+        self.tool_name = Some(tool_name);
+        self
+    }
+
+    /// Set the tracker that sampling usage is recorded in
+    pub fn with_usage_tracker(mut self, tracker: Arc<UsageTracker>) -> Self {
+        self.usage_tracker = Some(tracker);
+        self
+    }
+
+    /// Answer elicitations with `handler` when the client cannot elicit
+    pub fn with_emulated_elicitation(mut self, handler: Arc<dyn ElicitationHandler>) -> Self {
+        self.emulated_elicitation = Some(handler);
+        self
+    }
+
+    /// Render messages from `catalog`, see [`localize`](Self::localize)
+    pub fn with_message_catalog(mut self, catalog: Arc<MessageCatalog>) -> Self {
+        self.message_catalog = Some(catalog);
+        self
+    }
+
+    /// Set the locale the client asked for in the request's `_meta`
+    pub fn with_locale(mut self, locale: String) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Record reported progress in `recorder`
+    pub(crate) fn with_progress_recorder(mut self, recorder: Arc<ProgressRecorder>) -> Self {
+        self.progress_recorder = Some(recorder);
+        self
+    }
+
+    /// Get the name of the tool being executed, for `tools/call` requests
+    pub fn tool_name(&self) -> Option<&str> {
+        self.tool_name.as_deref()
+    }
+
+    /// Record a sampling round trip against this context's session and tool
+    ///
+    /// Returns `None` if the context has no usage tracker.
+    pub fn record_sampling_usage(
+        &self,
+        request: &CreateMessageRequest,
+        response: &CreateMessageResponse,
+    ) -> Option<SamplingUsage> {
+        self.usage_tracker.as_ref().map(|tracker| {
+            tracker.record(
+                self.session_id.as_deref(),
+                self.tool_name.as_deref(),
+                request,
+                response,
+            )
+        })
+    }
+
     /// Get the session ID
     pub fn session_id(&self) -> Option<&str> {
         self.session_id.as_deref()
     }
 
+    /// Protocol version negotiated with the client the request came from
+    pub fn protocol_version(&self) -> Option<String> {
+        self.client_peer
+            .as_ref()
+            .and_then(|peer| peer.protocol_version())
+    }
+
+    /// Use `state` as the state of the session the request came from
+    pub fn with_session_state(mut self, state: SessionState) -> Self {
+        self.session_state = state;
+        self
+    }
+
+    /// State kept for the session the request came from, see
+    /// [`session_state`](crate::session_state)
+    pub fn session(&self) -> &SessionState {
+        &self.session_state
+    }
+
     /// Get the request ID
     pub fn request_id(&self) -> Option<&str> {
         self.request_id.as_deref()
     }
 
+    /// Get the progress token of the current request, if the client asked
+    /// for progress updates
+    pub fn progress_token(&self) -> Option<&serde_json::Value> {
+        self.progress_token.as_ref()
+    }
+
+    /// Get the locale the client asked for, if any
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Render `message` in the client's locale
+    ///
+    /// Uses the server's message catalog, or the built-in English messages
+    /// when it has none; useful for elicitation prompts and tool errors
+    /// shown to the user.
+    pub fn localize(&self, message: &LocalizedMessage) -> String {
+        self.message_catalog
+            .as_deref()
+            .unwrap_or_else(|| MessageCatalog::builtin())
+            .render(self.locale.as_deref(), message)
+    }
+
     /// Get metadata value
     pub fn get_metadata(&self, key: &str) -> Option<&serde_json::Value> {
         self.metadata.get(key)
@@ -201,6 +467,80 @@ impl Context {
         level_priority >= min_priority
     }
 
+    /// Send `notifications/progress` for the current request to the client
+    ///
+    /// Progress is only reported when the client asked for it by sending a
+    /// progress token with the request; otherwise this does nothing.
+    /// `progress` must increase with each call, and `total` is omitted when
+    /// unknown. Either way, tool calls include the updates in their result's
+    /// `progress_summary`.
+    pub async fn report_progress(
+        &self,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<&str>,
+    ) -> MCPResult<()> {
+        if let Some(recorder) = &self.progress_recorder {
+            recorder.record(progress, total, message);
+        }
+        let Some(progress_token) = &self.progress_token else {
+            return Ok(());
+        };
+        let mut notification = ProgressNotification::new(progress_token.clone(), progress);
+        if let Some(total) = total {
+            notification = notification.with_total(total);
+        }
+        if let Some(message) = message {
+            notification = notification.with_message(message.to_string());
+        }
+        let params = serde_json::to_value(notification)?;
+        self.notify_client("notifications/progress", params).await
+    }
+
+    /// Send a piece of the tool's result to the client while the tool runs
+    ///
+    /// Useful for tools with large or incremental output, such as proxied
+    /// LLM completions or file dumps. When the client sent a progress token
+    /// with the request, each chunk goes out at once in a
+    /// [`TOOL_CONTENT_CHUNK_METHOD`] notification tied to it, and is left
+    /// out of the final result. Otherwise the chunks are put at the start of
+    /// the final result's content, so a tool streams the same way however
+    /// it is called.
+    pub async fn stream_content(&self, chunk: ToolContent) -> MCPResult<()> {
+        let Some(progress_token) = &self.progress_token else {
+            match &self.progress_recorder {
+                Some(recorder) => recorder.hold_chunk(chunk),
+                None => debug!("Dropping content streamed outside of a tool call"),
+            }
+            return Ok(());
+        };
+        let chunk = ToolContentChunk {
+            progress_token: progress_token.clone(),
+            index: self
+                .progress_recorder
+                .as_ref()
+                .map_or(0, |recorder| recorder.next_chunk_index()),
+            content: chunk,
+        };
+        let params = serde_json::to_value(chunk)?;
+        self.notify_client(TOOL_CONTENT_CHUNK_METHOD, params).await
+    }
+
+    /// Send a notification to the client, if connected to one
+    pub(crate) async fn notify_client(&self, method: &str, params: Value) -> MCPResult<()> {
+        if let Some(peer) = &self.client_peer {
+            peer.send_notification(method, Some(params))
+        } else if let Some(sender) = &self.notification_sender {
+            sender(JsonRpcMessage::Notification(JsonRpcRequest::notification(
+                method.to_string(),
+                Some(params),
+            )))
+            .await
+        } else {
+            Ok(())
+        }
+    }
+
     /// Send a progress update
     ///
     /// # Arguments
@@ -213,6 +553,10 @@ impl Context {
         progress: f64,
         total: Option<f64>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(recorder) = &self.progress_recorder {
+            recorder.record(progress, total, Some(message));
+        }
+
         // Log progress for debugging
         if let Some(total) = total {
             info!(
@@ -228,10 +572,11 @@ impl Context {
 
         // Send progress notification if sender is available
         if let Some(sender) = &self.notification_sender {
-            let progress_token = self
-                .request_id()
-                .map(|id| serde_json::Value::String(id.to_string()))
-                .unwrap_or(serde_json::Value::Null);
+            let progress_token = self.progress_token.clone().unwrap_or_else(|| {
+                self.request_id()
+                    .map(|id| serde_json::Value::String(id.to_string()))
+                    .unwrap_or(serde_json::Value::Null)
+            });
 
             let mut notification = ProgressNotification::new(progress_token, progress)
                 .with_message(message.to_string());
@@ -498,37 +843,200 @@ impl Context {
             LogLevel::Emergency => error!("[{}] EMERGENCY: {}", request_context, truncated_message),
         }
 
-        // Send logging notification to client if configured and sender is available
+        // Send logging notification to the client if configured
         if self.logger_config.send_notifications {
-            if let Some(sender) = &self.notification_sender {
-                let logger_name = self
-                    .logger_config
-                    .logger_name
-                    .as_deref()
-                    .unwrap_or("ultrafast-mcp-server");
-
-                let notification = LoggingMessageNotification::new(level, log_data)
-                    .with_logger(logger_name.to_string());
-
-                let notification_request = JsonRpcRequest {
-                    jsonrpc: Cow::Borrowed("2.0"),
-                    id: None, // Notifications don't have IDs
-                    method: "notifications/message".to_string(),
-                    params: Some(serde_json::to_value(notification)?),
-                    meta: std::collections::HashMap::new(),
-                };
-
-                // Send notification but don't fail if it doesn't work
-                if let Err(e) = sender(JsonRpcMessage::Notification(notification_request)).await {
-                    // Log the error but don't propagate it
-                    error!("Failed to send logging notification: {}", e);
-                }
+            let logger_name = self
+                .logger_config
+                .logger_name
+                .as_deref()
+                .unwrap_or("ultrafast-mcp-server");
+
+            let notification = LoggingMessageNotification::new(level, log_data)
+                .with_logger(logger_name.to_string());
+
+            // Send notification but don't fail if it doesn't work
+            let params = serde_json::to_value(notification)?;
+            if let Err(e) = self.notify_client("notifications/message", params).await {
+                // Log the error but don't propagate it
+                error!("Failed to send logging notification: {}", e);
             }
         }
 
         Ok(())
     }
 
+    /// Ask the client's user for input mid-execution
+    ///
+    /// Sends `elicitation/create` to the connected client and waits for the
+    /// user's answer, up to the peer's default timeout. Fails if the request
+    /// did not arrive over a connection or the client did not declare the
+    /// elicitation capability.
+    pub async fn elicit(&self, request: ElicitationRequest) -> MCPResult<ElicitationResponse> {
+        let timeout = self
+            .client_peer
+            .as_ref()
+            .map(|peer| peer.default_timeout())
+            .unwrap_or_default();
+        self.elicit_with_timeout(request, timeout).await
+    }
+
+    /// Ask the client's user for input and read the answer as `T`
+    ///
+    /// Like [`elicit`](Self::elicit), but an accepted answer is checked
+    /// against the requested schema before it is deserialized. Build the
+    /// request with an [`ElicitationBuilder`]:
+    ///
+    /// ```rust,no_run
+    /// # use ultrafast_mcp_core::types::elicitation::{ElicitationBuilder, ElicitationField};
+    /// # async fn run(ctx: ultrafast_mcp_server::Context) -> ultrafast_mcp_core::error::MCPResult<()> {
+    /// #[derive(serde::Deserialize)]
+    /// struct Confirmation {
+    ///     confirm: bool,
+    /// }
+    ///
+    /// let answer = ctx
+    ///     .elicit_as::<Confirmation>(
+    ///         ElicitationBuilder::new("Delete the project?")
+    ///             .field("confirm", ElicitationField::boolean()),
+    ///     )
+    ///     .await?;
+    /// if answer.accepted().is_some_and(|answer| answer.confirm) {
+    ///     // delete
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ElicitationBuilder`]: ultrafast_mcp_core::types::elicitation::ElicitationBuilder
+    pub async fn elicit_as<T: DeserializeOwned>(
+        &self,
+        request: impl Into<ElicitationRequest>,
+    ) -> MCPResult<ElicitationResult<T>> {
+        let request = request.into();
+        self.elicit(request.clone()).await?.parse(&request)
+    }
+
+    /// Ask the client's user for input, waiting at most `timeout`
+    pub async fn elicit_with_timeout(
+        &self,
+        request: ElicitationRequest,
+        timeout: std::time::Duration,
+    ) -> MCPResult<ElicitationResponse> {
+        let supports_elicitation = self.client_peer.as_ref().is_some_and(|peer| {
+            peer.client_capabilities()
+                .is_some_and(|capabilities| capabilities.elicitation.is_some())
+        });
+        if !supports_elicitation {
+            if let Some(handler) = &self.emulated_elicitation {
+                debug!("Answering elicitation with the emulated client");
+                return handler.handle_elicitation(request).await;
+            }
+        }
+
+        let peer = self.client_peer.as_ref().ok_or_else(|| {
+            MCPError::internal_error("No client connection available for elicitation".to_string())
+        })?;
+        if !supports_elicitation {
+            return Err(MCPError::Protocol(ProtocolError::CapabilityNotSupported(
+                "Client did not declare the elicitation capability".to_string(),
+            )));
+        }
+
+        peer.send_request_as(
+            "elicitation/create",
+            Some(serde_json::to_value(request)?),
+            timeout,
+        )
+        .await
+    }
+
+    /// The client's filesystem roots
+    ///
+    /// Sends `roots/list` to the connected client the first time and reuses
+    /// the answer until the client reports that its roots changed. Fails if
+    /// the request did not arrive over a connection or the client did not
+    /// declare the roots capability.
+    pub async fn list_client_roots(&self) -> MCPResult<Vec<Root>> {
+        let peer = self.client_peer.as_ref().ok_or_else(|| {
+            MCPError::internal_error("No client connection available for roots".to_string())
+        })?;
+        let supports_roots = peer
+            .client_capabilities()
+            .is_some_and(|capabilities| capabilities.roots.is_some());
+        if !supports_roots {
+            return Err(MCPError::Protocol(ProtocolError::CapabilityNotSupported(
+                "Client did not declare the roots capability".to_string(),
+            )));
+        }
+        peer.list_roots(peer.default_timeout()).await
+    }
+
+    /// Ask the client's LLM to generate a message
+    ///
+    /// Sends `sampling/createMessage` to the connected client and waits for
+    /// the completion, up to the peer's default timeout. The round trip is
+    /// recorded with [`record_sampling_usage`](Self::record_sampling_usage).
+    /// Fails if the request did not arrive over a connection or the client
+    /// did not declare the sampling capability.
+    pub async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> MCPResult<CreateMessageResponse> {
+        let timeout = self
+            .client_peer
+            .as_ref()
+            .map(|peer| peer.default_timeout())
+            .unwrap_or_default();
+        self.create_message_with_timeout(request, timeout).await
+    }
+
+    /// Ask the client's LLM to generate a message, waiting at most `timeout`
+    pub async fn create_message_with_timeout(
+        &self,
+        request: CreateMessageRequest,
+        timeout: std::time::Duration,
+    ) -> MCPResult<CreateMessageResponse> {
+        let peer = self.client_peer.as_ref().ok_or_else(|| {
+            MCPError::internal_error("No client connection available for sampling".to_string())
+        })?;
+        let supports_sampling = peer
+            .client_capabilities()
+            .is_some_and(|capabilities| capabilities.sampling.is_some());
+        if !supports_sampling {
+            return Err(MCPError::Protocol(ProtocolError::CapabilityNotSupported(
+                "Client did not declare the sampling capability".to_string(),
+            )));
+        }
+
+        let response: CreateMessageResponse = peer
+            .send_request_as(
+                "sampling/createMessage",
+                Some(serde_json::to_value(&request)?),
+                timeout,
+            )
+            .await?;
+        self.record_sampling_usage(&request, &response);
+        Ok(response)
+    }
+
+    /// Wait until the client cancels the current request
+    ///
+    /// Never completes for contexts without a request id or cancellation
+    /// manager, so it can always be raced against the handler's work:
+    ///
+    /// ```rust,ignore
+    /// tokio::select! {
+    ///     result = do_work() => result,
+    ///     _ = ctx.cancelled() => Err(MCPError::internal_error("Cancelled".to_string())),
+    /// }
+    /// ```
+    pub async fn cancelled(&self) {
+        match (&self.cancellation_manager, &self.request_id) {
+            (Some(manager), Some(request_id)) => manager.wait_cancelled(request_id).await,
+            _ => std::future::pending().await,
+        }
+    }
+
     /// Check if the current request has been cancelled
     pub async fn is_cancelled(&self) -> bool {
         if let Some(cancellation_manager) = &self.cancellation_manager {
@@ -550,7 +1058,7 @@ impl Default for Context {
 }
 
 /// Get numeric priority for log level (higher = more urgent)
-fn log_level_priority(level: &LogLevel) -> u8 {
+pub(crate) fn log_level_priority(level: &LogLevel) -> u8 {
     match level {
         LogLevel::Debug => 0,
         LogLevel::Info => 1,
@@ -632,6 +1140,49 @@ mod tests {
         assert_eq!(ctx.get_metadata("key"), Some(&serde_json::json!("value")));
     }
 
+    struct FixedAnswer(serde_json::Value);
+
+    #[async_trait::async_trait]
+    impl ElicitationHandler for FixedAnswer {
+        async fn handle_elicitation(
+            &self,
+            _request: ElicitationRequest,
+        ) -> MCPResult<ElicitationResponse> {
+            Ok(ElicitationResponse::accept(self.0.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_elicit_as_validates_and_deserializes_answers() {
+        use ultrafast_mcp_core::types::elicitation::{ElicitationBuilder, ElicitationField};
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Confirmation {
+            confirm: bool,
+            reason: Option<String>,
+        }
+        let request = || {
+            ElicitationBuilder::new("Confirm deletion")
+                .field("confirm", ElicitationField::boolean())
+                .field("reason", ElicitationField::string().optional())
+        };
+
+        let ctx = Context::new()
+            .with_emulated_elicitation(Arc::new(FixedAnswer(serde_json::json!({"confirm": true}))));
+        assert_eq!(
+            ctx.elicit_as::<Confirmation>(request()).await.unwrap(),
+            ElicitationResult::Accepted(Confirmation {
+                confirm: true,
+                reason: None,
+            })
+        );
+
+        let ctx = Context::new().with_emulated_elicitation(Arc::new(FixedAnswer(
+            serde_json::json!({"reason": "no confirm"}),
+        )));
+        assert!(ctx.elicit_as::<Confirmation>(request()).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_context_logging() {
         let ctx = Context::new().with_request_id("test-request".to_string());
@@ -0,0 +1,310 @@
+//! Emulated client features for testing
+//!
+//! Sampling and elicitation are answered by the client, so exercising a
+//! server's sampling or elicitation flow normally needs a client that
+//! implements them. The handlers here stand in for such a client:
+//! [`AutoSamplingHandler`] echoes the prompt back and [`AutoElicitationHandler`]
+//! accepts every elicitation with values derived from the requested schema.
+//!
+//! Enable them with [`UltraFastServer::with_client_emulation`]. When
+//! elicitation emulation is on, [`Context::elicit`] falls back to the
+//! emulated responder if the connected client did not declare the
+//! elicitation capability.
+//!
+//! [`UltraFastServer::with_client_emulation`]: crate::UltraFastServer::with_client_emulation
+//! [`Context::elicit`]: crate::Context::elicit
+
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use ultrafast_mcp_core::{
+    error::MCPResult,
+    types::{
+        elicitation::{ElicitationAction, ElicitationRequest, ElicitationResponse},
+        sampling::{
+            CreateMessageRequest, CreateMessageResponse, SamplingContent, SamplingRole, StopReason,
+        },
+    },
+};
+
+use crate::handlers::{ElicitationHandler, SamplingHandler};
+
+/// Model name reported by [`AutoSamplingHandler`]
+pub const ECHO_MODEL: &str = "ultrafast-echo";
+
+/// Which client features the server emulates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientEmulationConfig {
+    /// Answer sampling requests with [`AutoSamplingHandler`]
+    pub sampling: bool,
+    /// Answer elicitation requests with [`AutoElicitationHandler`]
+    pub elicitation: bool,
+}
+
+impl ClientEmulationConfig {
+    /// Emulate every supported client feature
+    pub fn all() -> Self {
+        Self {
+            sampling: true,
+            elicitation: true,
+        }
+    }
+}
+
+/// Sampling handler that replies with the text of the last user message
+#[derive(Debug, Clone)]
+pub struct AutoSamplingHandler {
+    prefix: String,
+}
+
+impl AutoSamplingHandler {
+    pub fn new() -> Self {
+        Self {
+            prefix: String::new(),
+        }
+    }
+
+    /// Prepend `prefix` to every echoed prompt
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+}
+
+impl Default for AutoSamplingHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SamplingHandler for AutoSamplingHandler {
+    async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> MCPResult<CreateMessageResponse> {
+        let prompt = request
+            .messages
+            .iter()
+            .rev()
+            .filter(|message| matches!(message.role, SamplingRole::User))
+            .find_map(|message| match &message.content {
+                SamplingContent::Text { text } => Some(text.as_str()),
+                SamplingContent::Image { .. } => None,
+            })
+            .unwrap_or_default();
+
+        Ok(CreateMessageResponse {
+            role: SamplingRole::Assistant,
+            content: SamplingContent::Text {
+                text: format!("{}{}", self.prefix, prompt),
+            },
+            model: Some(ECHO_MODEL.to_string()),
+            stop_reason: Some(StopReason::EndTurn),
+            approval_status: None,
+            request_id: request.request_id,
+            processing_time_ms: Some(0),
+            cost_info: None,
+            included_context: None,
+            human_feedback: None,
+            warnings: None,
+        })
+    }
+}
+
+/// Elicitation handler that accepts with values derived from the schema
+///
+/// Each property gets, in order of preference: a value set with
+/// [`with_value`](Self::with_value), the schema's `default`, its `const`,
+/// the first `enum` entry, or a placeholder for its type. Optional
+/// properties without an explicit value or default are left out.
+#[derive(Debug, Clone)]
+pub struct AutoElicitationHandler {
+    action: ElicitationAction,
+    values: Map<String, Value>,
+}
+
+impl AutoElicitationHandler {
+    pub fn new() -> Self {
+        Self {
+            action: ElicitationAction::Accept,
+            values: Map::new(),
+        }
+    }
+
+    /// Answer `field` with `value` instead of a derived one
+    pub fn with_value(mut self, field: impl Into<String>, value: Value) -> Self {
+        self.values.insert(field.into(), value);
+        self
+    }
+
+    /// Respond with `action` instead of accepting, e.g. to test declines
+    pub fn with_action(mut self, action: ElicitationAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Build the content an accepting user would submit for `schema`
+    pub fn fill(&self, schema: &Value) -> Map<String, Value> {
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut content = Map::new();
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            return content;
+        };
+        for (name, property) in properties {
+            let value = self
+                .values
+                .get(name)
+                .or_else(|| property.get("default"))
+                .or_else(|| property.get("const"))
+                .cloned()
+                .or_else(|| {
+                    required
+                        .contains(&name.as_str())
+                        .then(|| placeholder(property))
+                });
+            if let Some(value) = value {
+                content.insert(name.clone(), value);
+            }
+        }
+        content
+    }
+}
+
+impl Default for AutoElicitationHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ElicitationHandler for AutoElicitationHandler {
+    async fn handle_elicitation(
+        &self,
+        request: ElicitationRequest,
+    ) -> MCPResult<ElicitationResponse> {
+        let content = matches!(self.action, ElicitationAction::Accept)
+            .then(|| Value::Object(self.fill(&request.requested_schema)));
+        Ok(ElicitationResponse {
+            action: self.action.clone(),
+            content,
+        })
+    }
+}
+
+fn placeholder(property: &Value) -> Value {
+    if let Some(first) = property
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+    {
+        return first.clone();
+    }
+    match property.get("type").and_then(Value::as_str) {
+        Some("boolean") => Value::Bool(false),
+        Some("integer") | Some("number") => property
+            .get("minimum")
+            .cloned()
+            .unwrap_or_else(|| Value::from(0)),
+        Some("string") => {
+            let text = match property.get("format").and_then(Value::as_str) {
+                Some("email") => "user@example.com".to_string(),
+                Some("uri") => "https://example.com".to_string(),
+                Some("date") => "1970-01-01".to_string(),
+                Some("date-time") => "1970-01-01T00:00:00Z".to_string(),
+                _ => {
+                    let min_length = property
+                        .get("minLength")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(0);
+                    "x".repeat(min_length as usize)
+                }
+            };
+            Value::String(text)
+        }
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use ultrafast_mcp_core::{
+        schema::validation::validate_against_schema, types::sampling::SamplingMessage,
+    };
+
+    #[tokio::test]
+    async fn test_auto_sampling_echoes_last_user_prompt() {
+        let request = CreateMessageRequest {
+            messages: vec![
+                SamplingMessage {
+                    role: SamplingRole::User,
+                    content: SamplingContent::Text {
+                        text: "first".to_string(),
+                    },
+                },
+                SamplingMessage {
+                    role: SamplingRole::User,
+                    content: SamplingContent::Text {
+                        text: "second".to_string(),
+                    },
+                },
+            ],
+            ..Default::default()
+        };
+
+        let response = AutoSamplingHandler::new()
+            .with_prefix("echo: ")
+            .create_message(request)
+            .await
+            .unwrap();
+        let SamplingContent::Text { text } = response.content else {
+            panic!("expected text content");
+        };
+        assert_eq!(text, "echo: second");
+        assert_eq!(response.model.as_deref(), Some(ECHO_MODEL));
+    }
+
+    #[tokio::test]
+    async fn test_auto_elicitation_fills_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 2},
+                "email": {"type": "string", "format": "email"},
+                "age": {"type": "integer", "minimum": 18},
+                "color": {"type": "string", "enum": ["red", "blue"]},
+                "subscribe": {"type": "boolean", "default": true},
+                "nickname": {"type": "string"}
+            },
+            "required": ["name", "email", "age", "color"]
+        });
+        let request = ElicitationRequest {
+            message: "Tell us about yourself".to_string(),
+            requested_schema: schema.clone(),
+        };
+
+        let response = AutoElicitationHandler::new()
+            .with_value("email", json!("ada@example.com"))
+            .handle_elicitation(request)
+            .await
+            .unwrap();
+        let content = response.content.unwrap();
+        assert_eq!(
+            content,
+            json!({
+                "name": "xx",
+                "email": "ada@example.com",
+                "age": 18,
+                "color": "red",
+                "subscribe": true
+            })
+        );
+        validate_against_schema(&content, &schema).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! Mapping of handler errors to JSON-RPC errors
+//!
+//! Handlers can fail with rich application errors, wrapped in
+//! [`MCPError::Other`], and leave it to the server to decide what clients
+//! see. An [`ErrorMapper`] registered with
+//! [`UltraFastServer::with_error_mapper`] turns a handler error into the
+//! JSON-RPC error sent to the client; errors no mapper claims get the
+//! server's default code and message.
+//!
+//! [`UltraFastServer::with_domain_error`] covers the common case of mapping
+//! one error type, found anywhere in the error's source chain:
+//!
+//! ```rust
+//! use ultrafast_mcp_core::protocol::jsonrpc::JsonRpcError;
+//! use ultrafast_mcp_server::{ServerCapabilities, ServerInfo, UltraFastServer};
+//!
+//! #[derive(Debug, thiserror::Error)]
+//! #[error("account {0} is frozen")]
+//! struct AccountFrozen(String);
+//!
+//! let info = ServerInfo {
+//!     name: "bank".to_string(),
+//!     version: "1.0.0".to_string(),
+//!     description: None,
+//!     authors: None,
+//!     homepage: None,
+//!     license: None,
+//!     repository: None,
+//! };
+//! let server = UltraFastServer::new(info, ServerCapabilities::default())
+//!     .with_domain_error(|error: &AccountFrozen| {
+//!         JsonRpcError::new(-32010, "Account frozen".to_string())
+//!             .with_data(serde_json::json!({ "account": error.0 }))
+//!     });
+//! ```
+//!
+//! [`UltraFastServer::with_error_mapper`]: crate::UltraFastServer::with_error_mapper
+//! [`UltraFastServer::with_domain_error`]: crate::UltraFastServer::with_domain_error
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use ultrafast_mcp_core::error::MCPError;
+use ultrafast_mcp_core::protocol::jsonrpc::JsonRpcError;
+
+/// Turns handler errors into the JSON-RPC errors clients receive
+pub trait ErrorMapper: Send + Sync {
+    /// The error to send for `error`, or `None` to leave it to the next
+    /// mapper or the server default
+    fn map_error(&self, error: &MCPError) -> Option<JsonRpcError>;
+}
+
+impl<F> ErrorMapper for F
+where
+    F: Fn(&MCPError) -> Option<JsonRpcError> + Send + Sync,
+{
+    fn map_error(&self, error: &MCPError) -> Option<JsonRpcError> {
+        self(error)
+    }
+}
+
+/// Maps errors of type `E` carried by [`MCPError::Other`], directly or as
+/// the source of another error
+pub struct DomainErrorMapper<E, F> {
+    map: F,
+    error: PhantomData<fn(&E)>,
+}
+
+impl<E, F> DomainErrorMapper<E, F>
+where
+    E: std::error::Error + Send + Sync + 'static,
+    F: Fn(&E) -> JsonRpcError + Send + Sync,
+{
+    pub fn new(map: F) -> Self {
+        Self {
+            map,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<E, F> ErrorMapper for DomainErrorMapper<E, F>
+where
+    E: std::error::Error + Send + Sync + 'static,
+    F: Fn(&E) -> JsonRpcError + Send + Sync,
+{
+    fn map_error(&self, error: &MCPError) -> Option<JsonRpcError> {
+        let MCPError::Other(error) = error else {
+            return None;
+        };
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<E>())
+            .map(&self.map)
+    }
+}
+
+/// The error mappers of a server, consulted in registration order
+#[derive(Clone, Default)]
+pub(crate) struct ErrorMappings {
+    mappers: Vec<Arc<dyn ErrorMapper>>,
+}
+
+impl ErrorMappings {
+    pub(crate) fn push(&mut self, mapper: Arc<dyn ErrorMapper>) {
+        self.mappers.push(mapper);
+    }
+
+    /// The error for `error` from the first mapper that claims it, otherwise
+    /// `default`
+    pub(crate) fn map(&self, error: &MCPError, default: JsonRpcError) -> JsonRpcError {
+        self.mappers
+            .iter()
+            .find_map(|mapper| mapper.map_error(error))
+            .unwrap_or(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("quota exceeded for {0}")]
+    struct QuotaExceeded(String);
+
+    #[test]
+    fn test_domain_errors_are_found_in_the_source_chain() {
+        let mapper = DomainErrorMapper::new(|error: &QuotaExceeded| {
+            JsonRpcError::new(-32020, format!("Quota exceeded: {}", error.0))
+        });
+
+        let direct = MCPError::Other(QuotaExceeded("alice".to_string()).into());
+        assert_eq!(mapper.map_error(&direct).unwrap().code, -32020);
+
+        let wrapped = MCPError::Other(
+            anyhow::Error::new(QuotaExceeded("bob".to_string())).context("while searching"),
+        );
+        let mapped = mapper.map_error(&wrapped).unwrap();
+        assert_eq!(mapped.message, "Quota exceeded: bob");
+
+        let other = MCPError::internal_error("boom".to_string());
+        assert!(mapper.map_error(&other).is_none());
+    }
+
+    #[test]
+    fn test_first_matching_mapper_wins() {
+        let mut mappings = ErrorMappings::default();
+        mappings.push(Arc::new(|error: &MCPError| match error {
+            MCPError::Validation(_) => Some(JsonRpcError::new(-32030, "Invalid".to_string())),
+            _ => None,
+        }));
+        mappings.push(Arc::new(|_: &MCPError| {
+            Some(JsonRpcError::new(-32031, "Anything".to_string()))
+        }));
+
+        let default = || JsonRpcError::new(-32603, "Default".to_string());
+        let validation =
+            MCPError::Validation(ultrafast_mcp_core::error::ValidationError::RequiredField {
+                field: "x".to_string(),
+            });
+        assert_eq!(mappings.map(&validation, default()).code, -32030);
+        let other = MCPError::internal_error("boom".to_string());
+        assert_eq!(mappings.map(&other, default()).code, -32031);
+        assert_eq!(ErrorMappings::default().map(&other, default()).code, -32603);
+    }
+}
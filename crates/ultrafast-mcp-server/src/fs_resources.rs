@@ -0,0 +1,726 @@
+//! Resources served from a directory tree
+//!
+//! [`FsResourceHandler`] lists the files under a root directory as `file://`
+//! resources and reads them on request, so a server exposing a project or a
+//! document folder needs no handler of its own:
+//!
+//! - files are listed in path order, a page at a time, with the MIME type
+//!   guessed from their extension
+//! - reading a file sniffs its MIME type from its first bytes, and decodes
+//!   text in encodings other than UTF-8, see [`content_type`]
+//! - glob patterns relative to the root select the files served; `*` matches
+//!   within one path segment, `**` across segments, and a leading `**/` also
+//!   matches files directly under the root
+//! - hidden files and directories are skipped unless asked for, and paths
+//!   leaving the root, including through symbolic links, are refused
+//! - byte ranges and `resources/readStream` read only the requested part of
+//!   a file; a plain read of a file larger than
+//!   [`with_max_file_size`](FsResourceHandler::with_max_file_size) is refused
+//! - [`FsResourceHandler::watch`] polls the tree and sends
+//!   `notifications/resources/updated` for modified files and a list changed
+//!   notification when files appear or disappear
+//!
+//! ```rust,no_run
+//! use ultrafast_mcp_server::{FsResourceHandler, ServerCapabilities, ServerInfo, UltraFastServer};
+//!
+//! # fn info() -> ServerInfo { unimplemented!() }
+//! let docs = FsResourceHandler::new("./docs")
+//!     .with_include("**/*.md")
+//!     .with_exclude("drafts/**")
+//!     .with_template("guide", "guides/{name}.md");
+//! let server = UltraFastServer::new(info(), ServerCapabilities::default())
+//!     .with_resource_handler(docs.clone());
+//! # async fn run(docs: FsResourceHandler, server: UltraFastServer) {
+//! docs.watch(server, std::time::Duration::from_secs(2));
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt},
+    task::JoinHandle,
+};
+use tracing::warn;
+use ultrafast_mcp_core::{
+    content_type::{
+        self, OCTET_STREAM, decode_text, detect_mime_type, is_text_mime_type, looks_like_text,
+    },
+    error::{MCPError, MCPResult, ResourceError},
+    types::{
+        resources::{
+            ByteRange, ListResourceTemplatesRequest, ListResourceTemplatesResponse,
+            ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
+            Resource, ResourceContent, ResourceTemplate,
+        },
+        roots::{Root, RootOperation},
+    },
+};
+
+use crate::{
+    UltraFastServer, adapters::validate_against_roots, handlers::ResourceHandler,
+    resource_stream::ResourceByteStream, subscriptions::glob_matches,
+};
+
+/// Files listed per `resources/list` page unless set otherwise
+pub const DEFAULT_FS_PAGE_SIZE: usize = 100;
+
+/// Largest file a plain `resources/read` loads unless set otherwise, in bytes
+pub const DEFAULT_FS_MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Serves the files under a directory as `file://` resources
+///
+/// See the [module documentation](self) for what is served. Cloning is
+/// cheap enough to keep one copy for the server and one for
+/// [`watch`](Self::watch).
+#[derive(Debug, Clone)]
+pub struct FsResourceHandler {
+    root: PathBuf,
+    base_uri: String,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_hidden: bool,
+    page_size: usize,
+    max_file_size: u64,
+    templates: Vec<ResourceTemplate>,
+}
+
+/// Size and modification time of a file, compared by the watcher
+type FileStamp = (u64, Option<SystemTime>);
+
+impl FsResourceHandler {
+    /// Serve the files under `root`, made absolute against the current
+    /// directory
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let root = std::path::absolute(&root).unwrap_or(root);
+        let mut path = root.to_string_lossy().replace('\\', "/");
+        if !path.starts_with('/') {
+            path.insert(0, '/');
+        }
+        let base_uri = format!("file://{}", encode_path(path.trim_end_matches('/')));
+        Self {
+            root,
+            base_uri,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            include_hidden: false,
+            page_size: DEFAULT_FS_PAGE_SIZE,
+            max_file_size: DEFAULT_FS_MAX_FILE_SIZE,
+            templates: Vec::new(),
+        }
+    }
+
+    /// Only serve files matching `glob`; with several, files matching any
+    pub fn with_include(mut self, glob: impl Into<String>) -> Self {
+        self.include.push(glob.into());
+        self
+    }
+
+    /// Never serve files matching `glob`, even if included
+    pub fn with_exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+
+    /// Serve files and directories whose name starts with `.`
+    pub fn with_hidden_files(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
+    /// Files listed per page, at least one
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Largest file a plain `resources/read` loads into memory, in bytes
+    ///
+    /// Larger files can still be read with `resources/readStream` or a byte
+    /// range. Defaults to [`DEFAULT_FS_MAX_FILE_SIZE`].
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// List a resource template for paths below the root, such as
+    /// `logs/{date}.log`
+    pub fn with_template(mut self, name: impl Into<String>, path_template: &str) -> Self {
+        let uri_template = format!(
+            "{}/{}",
+            self.base_uri,
+            path_template.trim_start_matches('/')
+        );
+        let mut template = ResourceTemplate::new(uri_template, name.into());
+        let has_extension = path_template
+            .rsplit_once('.')
+            .is_some_and(|(_, extension)| !extension.contains(['/', '{', '}']));
+        if has_extension {
+            template.mime_type = Some(mime_type_for(Path::new(path_template)).to_string());
+        }
+        self.templates.push(template);
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// URI of the root directory, without a trailing `/`
+    pub fn base_uri(&self) -> &str {
+        &self.base_uri
+    }
+
+    /// URI of the file at `relative_path` below the root
+    pub fn uri_for(&self, relative_path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_uri,
+            encode_path(relative_path.trim_start_matches('/'))
+        )
+    }
+
+    /// Poll the tree every `interval`, telling `server`'s sessions about
+    /// modified, added and removed files
+    ///
+    /// Modified files are announced with
+    /// [`UltraFastServer::notify_resource_updated`], reaching the sessions
+    /// subscribed to them; added or removed files with a debounced list
+    /// changed notification. Abort the returned task to stop watching.
+    pub fn watch(&self, server: UltraFastServer, interval: Duration) -> JoinHandle<()> {
+        let handler = self.clone();
+        tokio::spawn(async move {
+            let mut known = handler.stamps().await.unwrap_or_default();
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let current = match handler.stamps().await {
+                    Ok(current) => current,
+                    Err(e) => {
+                        warn!("Failed to scan {}: {}", handler.root.display(), e);
+                        continue;
+                    }
+                };
+                let mut list_changed = known.keys().any(|path| !current.contains_key(path));
+                for (path, stamp) in &current {
+                    match known.get(path) {
+                        Some(known_stamp) if known_stamp == stamp => {}
+                        Some(_) => {
+                            let uri = handler.uri_for(path);
+                            let contents = serde_json::json!({"uri": uri, "size": stamp.0});
+                            if let Err(e) = server.notify_resource_updated(uri, contents).await {
+                                warn!("Failed to notify update of {}: {}", path, e);
+                            }
+                        }
+                        None => list_changed = true,
+                    }
+                }
+                let notified = match list_changed {
+                    true => server.notify_resources_changed().await,
+                    false => Ok(()),
+                };
+                if let Err(e) = notified {
+                    warn!("Failed to notify resource list change: {}", e);
+                }
+                known = current;
+            }
+        })
+    }
+
+    /// Whether the file at `relative_path` is served, going by its path
+    fn is_served(&self, relative_path: &str) -> bool {
+        let matches = |glob: &String| {
+            glob_matches(glob.as_bytes(), relative_path.as_bytes())
+                || glob
+                    .strip_prefix("**/")
+                    .is_some_and(|rest| glob_matches(rest.as_bytes(), relative_path.as_bytes()))
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+
+    /// Path of the file `uri` names, if it is one this handler serves
+    fn resolve(&self, uri: &str) -> MCPResult<(String, PathBuf)> {
+        let not_found = || MCPError::Resource(ResourceError::NotFound(uri.to_string()));
+        let relative = uri
+            .strip_prefix(self.base_uri.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .and_then(decode_path)
+            .ok_or_else(not_found)?;
+        let mut components = Path::new(&relative).components().peekable();
+        if components.peek().is_none() {
+            return Err(not_found());
+        }
+        for component in components {
+            let Component::Normal(name) = component else {
+                return Err(leaves_root(uri));
+            };
+            if !self.include_hidden && name.to_string_lossy().starts_with('.') {
+                return Err(not_found());
+            }
+        }
+        if !self.is_served(&relative) {
+            return Err(not_found());
+        }
+        let path = self.root.join(&relative);
+        Ok((relative, path))
+    }
+
+    /// Open `path` if it is a file inside the root once links are followed,
+    /// returning it with its size
+    async fn open_inside_root(&self, uri: &str, path: &Path) -> MCPResult<(tokio::fs::File, u64)> {
+        let checked = self.check_inside_root(uri, path).await?;
+        self.open_checked(uri, checked).await
+    }
+
+    /// Check that `path` is a file inside the root once links are followed
+    async fn check_inside_root(&self, uri: &str, path: &Path) -> MCPResult<CheckedFile> {
+        let (root, path, uri) = (self.root.clone(), path.to_path_buf(), uri.to_string());
+        tokio::task::spawn_blocking(move || {
+            let not_found = || MCPError::Resource(ResourceError::NotFound(uri.clone()));
+            let resolved = path.canonicalize().map_err(|_| not_found())?;
+            if !resolved.starts_with(root.canonicalize()?) {
+                return Err(leaves_root(&uri));
+            }
+            let metadata = std::fs::metadata(&resolved)?;
+            if !metadata.is_file() {
+                return Err(not_found());
+            }
+            Ok(CheckedFile { resolved, metadata })
+        })
+        .await
+        .map_err(|e| MCPError::internal_error(format!("File check failed: {e}")))?
+    }
+
+    /// Open a file that passed [`Self::check_inside_root`]
+    ///
+    /// The path is opened again after the check, so a link swapped in since
+    /// then could lead elsewhere. The opened file is refused unless it is the
+    /// one that was checked.
+    async fn open_checked(
+        &self,
+        uri: &str,
+        checked: CheckedFile,
+    ) -> MCPResult<(tokio::fs::File, u64)> {
+        let uri = uri.to_string();
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&checked.resolved)?;
+            let metadata = file.metadata()?;
+            if !same_file(&metadata, &checked.metadata) {
+                return Err(leaves_root(&uri));
+            }
+            Ok((tokio::fs::File::from_std(file), metadata.len()))
+        })
+        .await
+        .map_err(|e| MCPError::internal_error(format!("File check failed: {e}")))?
+    }
+
+    /// The files served, by path below the root
+    async fn stamps(&self) -> MCPResult<BTreeMap<String, FileStamp>> {
+        let handler = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut files = BTreeMap::new();
+            handler.scan(&handler.root, "", &mut files)?;
+            Ok(files)
+        })
+        .await
+        .map_err(|e| MCPError::internal_error(format!("Directory scan failed: {e}")))?
+    }
+
+    fn scan(
+        &self,
+        dir: &Path,
+        parent: &str,
+        files: &mut BTreeMap<String, FileStamp>,
+    ) -> MCPResult<()> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !self.include_hidden && name.starts_with('.') {
+                continue;
+            }
+            let relative = format!("{parent}{name}");
+            // Linked directories are not descended into, so a link cycle
+            // cannot make the scan loop
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.scan(&entry.path(), &format!("{relative}/"), files)?;
+            } else if self.is_served(&relative) {
+                let Ok(metadata) = std::fs::metadata(entry.path()) else {
+                    continue;
+                };
+                if metadata.is_file() {
+                    files.insert(relative, (metadata.len(), metadata.modified().ok()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceHandler for FsResourceHandler {
+    async fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        let (_, path) = self.resolve(&request.uri)?;
+        let (mut file, size) = self.open_inside_root(&request.uri, &path).await?;
+        if size > self.max_file_size {
+            return Err(MCPError::invalid_request(format!(
+                "'{}' is larger than {} bytes; read it with resources/readStream or a byte range",
+                request.uri, self.max_file_size
+            )));
+        }
+        let mut bytes = Vec::with_capacity(size as usize);
+        file.read_to_end(&mut bytes).await?;
+        let mime_type = match detect_mime_type(Some(&path), &bytes) {
+            // Named like text, but binary
+            mime_type if is_text_mime_type(mime_type) && !looks_like_text(&bytes) => OCTET_STREAM,
+            mime_type => mime_type,
+        };
+        let content = if is_text_mime_type(mime_type) {
+            let text = decode_text(&bytes, None).text;
+            ResourceContent::text_with_mime_type(request.uri, text, mime_type.to_string())
+        } else {
+            ResourceContent::blob_from_bytes(request.uri, &bytes, mime_type.to_string())
+        };
+        Ok(ReadResourceResponse {
+            contents: vec![content],
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        let files = self.stamps().await?;
+        let after = request.cursor.unwrap_or_default();
+        let mut page = files
+            .keys()
+            .filter(|path| path.as_str() > after.as_str())
+            .take(self.page_size + 1);
+        let mut resources = Vec::new();
+        let mut last = None;
+        for path in page.by_ref().take(self.page_size) {
+            let name = path.rsplit('/').next().unwrap_or(path).to_string();
+            let mut resource = Resource::new(self.uri_for(path), name);
+            resource.description = Some(path.clone());
+            resource.mime_type = Some(mime_type_for(Path::new(path)).to_string());
+            resources.push(resource);
+            last = Some(path.clone());
+        }
+        Ok(ListResourcesResponse {
+            resources,
+            next_cursor: page.next().and(last),
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: ListResourceTemplatesRequest,
+    ) -> MCPResult<ListResourceTemplatesResponse> {
+        let files =
+            ResourceTemplate::new(format!("{}/{{path}}", self.base_uri), "file".to_string())
+                .with_description(format!("A file under {}", self.root.display()));
+        Ok(ListResourceTemplatesResponse {
+            resource_templates: std::iter::once(files)
+                .chain(self.templates.iter().cloned())
+                .collect(),
+            next_cursor: None,
+        })
+    }
+
+    async fn validate_resource_access(
+        &self,
+        uri: &str,
+        operation: RootOperation,
+        roots: &[Root],
+    ) -> MCPResult<()> {
+        validate_against_roots(uri, operation, roots)
+    }
+
+    async fn open_resource_stream(
+        &self,
+        uri: &str,
+        range: Option<ByteRange>,
+    ) -> MCPResult<Option<ResourceByteStream>> {
+        let (_, path) = self.resolve(uri)?;
+        let (mut file, size) = self.open_inside_root(uri, &path).await?;
+        if let Some(range) = range {
+            file.seek(std::io::SeekFrom::Start(range.offset.min(size)))
+                .await?;
+        }
+        Ok(Some(
+            ResourceByteStream::new(file)
+                .with_size(size)
+                .with_mime_type(mime_type_for(&path)),
+        ))
+    }
+}
+
+/// A file found inside the root, as it was when checked
+struct CheckedFile {
+    resolved: PathBuf,
+    metadata: std::fs::Metadata,
+}
+
+/// Whether two metadata describe the same file
+#[cfg(unix)]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    (a.dev(), a.ino()) == (b.dev(), b.ino())
+}
+
+/// Whether two metadata describe the same file, as far as size and
+/// modification time tell
+#[cfg(not(unix))]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    a.is_file() && a.len() == b.len() && a.modified().ok() == b.modified().ok()
+}
+
+fn leaves_root(uri: &str) -> MCPError {
+    MCPError::Resource(ResourceError::AccessDenied(format!(
+        "'{uri}' leaves the served directory"
+    )))
+}
+
+/// MIME type of a file, guessed from its extension
+pub fn mime_type_for(path: &Path) -> &'static str {
+    content_type::mime_type_for_path(path).unwrap_or(OCTET_STREAM)
+}
+
+/// Percent-encode the bytes of `path` that may not appear in a URI path
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn decode_path(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let [first, tail @ ..] = rest {
+        if *first == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(*first);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::error::ProtocolError;
+
+    fn tree() -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("docs/guides")).unwrap();
+        std::fs::create_dir(root.path().join(".git")).unwrap();
+        std::fs::write(root.path().join("README.md"), "# Hello").unwrap();
+        std::fs::write(root.path().join("docs/guides/first steps.md"), "Step 1").unwrap();
+        std::fs::write(root.path().join("docs/logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        std::fs::write(root.path().join(".git/config"), "").unwrap();
+        root
+    }
+
+    async fn list_all(handler: &FsResourceHandler) -> Vec<String> {
+        let mut uris = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = handler
+                .list_resources(ListResourcesRequest { cursor })
+                .await
+                .unwrap();
+            uris.extend(page.resources.into_iter().map(|r| r.uri));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                return uris;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lists_in_pages_and_filters_by_glob() {
+        let root = tree();
+        let handler = FsResourceHandler::new(root.path()).with_page_size(1);
+        let uris = list_all(&handler).await;
+        assert_eq!(
+            uris,
+            [
+                handler.uri_for("README.md"),
+                handler.uri_for("docs/guides/first steps.md"),
+                handler.uri_for("docs/logo.png"),
+            ]
+        );
+        assert!(uris[1].ends_with("/docs/guides/first%20steps.md"));
+
+        let markdown = FsResourceHandler::new(root.path())
+            .with_include("**/*.md")
+            .with_exclude("docs/guides/**");
+        assert_eq!(list_all(&markdown).await, [markdown.uri_for("README.md")]);
+    }
+
+    #[tokio::test]
+    async fn test_reads_text_blobs_and_ranges() {
+        let root = tree();
+        let handler = FsResourceHandler::new(root.path());
+
+        let uri = handler.uri_for("docs/guides/first steps.md");
+        let response = handler
+            .read_resource(ReadResourceRequest::new(uri))
+            .await
+            .unwrap();
+        assert!(matches!(
+            &response.contents[0],
+            ResourceContent::Text { text, mime_type, .. }
+                if text == "Step 1" && mime_type.as_deref() == Some("text/markdown")
+        ));
+
+        let logo = handler.uri_for("docs/logo.png");
+        let response = handler
+            .read_resource(ReadResourceRequest::new(logo.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.contents[0].mime_type(), Some("image/png"));
+        assert_eq!(response.contents[0].to_bytes().unwrap(), b"\x89PNG");
+
+        let response = crate::resource_stream::read_range(
+            &handler,
+            ReadResourceRequest::new(logo).with_range(ByteRange::new(1, 2)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.contents[0].to_bytes().unwrap(), b"PN");
+
+        // Files over the limit are only read in parts
+        let handler = handler.with_max_file_size(3);
+        let logo = handler.uri_for("docs/logo.png");
+        assert!(matches!(
+            handler.read_resource(ReadResourceRequest::new(logo.clone())).await,
+            Err(MCPError::Protocol(ProtocolError::InvalidRequest(message)))
+                if message.contains("resources/readStream")
+        ));
+        let response = crate::resource_stream::read_range(
+            &handler,
+            ReadResourceRequest::new(logo).with_range(ByteRange::new(0, 4)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.contents[0].to_bytes().unwrap(), b"\x89PNG");
+    }
+
+    #[tokio::test]
+    async fn test_types_files_by_content_and_decodes_legacy_text() {
+        let root = tree();
+        std::fs::write(root.path().join("menu.md"), b"caf\xe9").unwrap();
+        std::fs::write(root.path().join("photo.txt"), b"\x89PNG\r\n\x1a\n\0\0").unwrap();
+        std::fs::write(root.path().join("LICENSE"), "MIT License\n").unwrap();
+        std::fs::write(root.path().join("notes.txt"), b"\x01\x02\0\x03").unwrap();
+        let handler = FsResourceHandler::new(root.path());
+        let read =
+            |path: &str| handler.read_resource(ReadResourceRequest::new(handler.uri_for(path)));
+
+        let menu = read("menu.md").await.unwrap();
+        assert!(matches!(
+            &menu.contents[0],
+            ResourceContent::Text { text, mime_type, .. }
+                if text == "café" && mime_type.as_deref() == Some("text/markdown")
+        ));
+
+        let photo = read("photo.txt").await.unwrap();
+        assert!(matches!(&photo.contents[0], ResourceContent::Blob { .. }));
+        assert_eq!(photo.contents[0].mime_type(), Some("image/png"));
+
+        let license = read("LICENSE").await.unwrap();
+        assert!(matches!(
+            &license.contents[0],
+            ResourceContent::Text { mime_type, .. } if mime_type.as_deref() == Some("text/plain")
+        ));
+
+        let notes = read("notes.txt").await.unwrap();
+        assert_eq!(notes.contents[0].mime_type(), Some(OCTET_STREAM));
+        assert_eq!(notes.contents[0].to_bytes().unwrap(), b"\x01\x02\0\x03");
+    }
+
+    #[tokio::test]
+    async fn test_refuses_paths_outside_the_served_files() {
+        let root = tree();
+        let handler = FsResourceHandler::new(root.path().join("docs"));
+        for uri in [
+            format!("{}/../README.md", handler.base_uri()),
+            handler.uri_for(".git/config"),
+            handler.uri_for("missing.md"),
+            "file:///etc/passwd".to_string(),
+        ] {
+            assert!(
+                handler
+                    .read_resource(ReadResourceRequest::new(uri.clone()))
+                    .await
+                    .is_err(),
+                "{uri} should not be readable"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_refuses_a_file_swapped_for_a_link_after_the_check() {
+        let root = tree();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret"), "secret").unwrap();
+        let handler = FsResourceHandler::new(root.path());
+        let uri = handler.uri_for("README.md");
+        let (_, path) = handler.resolve(&uri).unwrap();
+
+        let checked = handler.check_inside_root(&uri, &path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret"), &path).unwrap();
+
+        assert!(matches!(
+            handler.open_checked(&uri, checked).await,
+            Err(MCPError::Resource(ResourceError::AccessDenied(_)))
+        ));
+        assert!(matches!(
+            handler.read_resource(ReadResourceRequest::new(uri)).await,
+            Err(MCPError::Resource(ResourceError::AccessDenied(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_templates_are_listed_below_the_root() {
+        let handler = FsResourceHandler::new("/srv/data").with_template("log", "logs/{date}.log");
+        let templates = handler
+            .list_resource_templates(ListResourceTemplatesRequest::default())
+            .await
+            .unwrap()
+            .resource_templates;
+        assert_eq!(templates[0].uri_template, "file:///srv/data/{path}");
+        assert_eq!(
+            templates[1].uri_template,
+            "file:///srv/data/logs/{date}.log"
+        );
+        assert_eq!(templates[1].mime_type.as_deref(), Some("text/plain"));
+    }
+}
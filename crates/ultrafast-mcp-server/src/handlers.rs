@@ -4,6 +4,9 @@
 //! to handle different types of MCP requests.
 
 use async_trait::async_trait;
+
+use crate::context::Context;
+use crate::resource_stream::ResourceByteStream;
 use ultrafast_mcp_core::{
     error::{MCPError, MCPResult},
     types::{
@@ -12,8 +15,8 @@ use ultrafast_mcp_core::{
         elicitation::{ElicitationRequest, ElicitationResponse},
         prompts::{GetPromptRequest, GetPromptResponse, ListPromptsRequest, ListPromptsResponse},
         resources::{
-            ListResourceTemplatesRequest, ListResourceTemplatesResponse, ListResourcesRequest,
-            ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
+            ByteRange, ListResourceTemplatesRequest, ListResourceTemplatesResponse,
+            ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
         },
         sampling::{
             ApprovalStatus, CostInfo, CreateMessageRequest, CreateMessageResponse, HumanFeedback,
@@ -30,6 +33,20 @@ pub trait ToolHandler: Send + Sync {
     /// Handle a tool call request
     async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult>;
 
+    /// Handle a tool call request with access to its [`Context`]
+    ///
+    /// The server always calls this method. Override it instead of
+    /// `handle_tool_call` to interact with the client mid-execution, e.g. via
+    /// [`Context::elicit`]; the default ignores the context.
+    async fn handle_tool_call_with_context(
+        &self,
+        call: ToolCall,
+        context: Context,
+    ) -> MCPResult<ToolResult> {
+        let _ = context;
+        self.handle_tool_call(call).await
+    }
+
     /// List available tools
     async fn list_tools(&self, request: ListToolsRequest) -> MCPResult<ListToolsResponse>;
 }
@@ -61,6 +78,22 @@ pub trait ResourceHandler: Send + Sync {
         operation: ultrafast_mcp_core::types::roots::RootOperation,
         roots: &[ultrafast_mcp_core::types::roots::Root],
     ) -> MCPResult<()>;
+
+    /// Open the bytes of a resource for reading in chunks, positioned at the
+    /// start of `range`
+    ///
+    /// Serves `resources/readStream` and ranged `resources/read` requests
+    /// without loading the whole resource. The default returns `None`, and
+    /// the server reads the resource with `read_resource` instead. See
+    /// [`resource_stream`](crate::resource_stream).
+    async fn open_resource_stream(
+        &self,
+        uri: &str,
+        range: Option<ByteRange>,
+    ) -> MCPResult<Option<ResourceByteStream>> {
+        let _ = (uri, range);
+        Ok(None)
+    }
 }
 
 /// Prompt handler trait for managing prompts
@@ -356,6 +389,8 @@ mod tests {
                     "mock result".to_string(),
                 )],
                 is_error: None,
+                structured_content: None,
+                progress_summary: None,
             })
         }
 
@@ -450,9 +485,7 @@ mod tests {
     #[tokio::test]
     async fn test_resource_handler() {
         let handler = MockResourceHandler;
-        let request = ReadResourceRequest {
-            uri: "test://resource".to_string(),
-        };
+        let request = ReadResourceRequest::new("test://resource");
 
         let result = handler.read_resource(request).await.unwrap();
         assert_eq!(result.contents.len(), 1);
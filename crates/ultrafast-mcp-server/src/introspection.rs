@@ -0,0 +1,66 @@
+//! Build and runtime information for fleet debugging
+//!
+//! Every server answers the vendor method `x-ultrafast/info`, and servers
+//! run over HTTP also serve `GET /x-ultrafast/info`, with a [`BuildInfo`]
+//! describing the running binary: its versions, the crate features it was
+//! compiled with, the protocol versions it accepts and how long it has been
+//! up. `mcp info --server <url>` prints it.
+//!
+//! The git revision is taken from the `ULTRAFAST_MCP_GIT_SHA` environment
+//! variable at compile time, so release pipelines can stamp builds with e.g.
+//! `ULTRAFAST_MCP_GIT_SHA=$(git rev-parse HEAD) cargo build --release`.
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use ultrafast_mcp_core::{protocol::version::SUPPORTED_VERSIONS, types::server::ServerInfo};
+
+/// Method name of the introspection request
+pub const INFO_METHOD: &str = "x-ultrafast/info";
+
+/// What `x-ultrafast/info` returns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    /// Name from the server's [`ServerInfo`]
+    pub server_name: String,
+    /// Version from the server's [`ServerInfo`]
+    pub server_version: String,
+    /// Version of the `ultrafast-mcp-server` crate
+    pub crate_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    /// Optional crate features compiled in
+    pub features: Vec<String>,
+    /// Protocol versions accepted during initialization, newest first
+    pub protocol_versions: Vec<String>,
+    pub uptime_secs: u64,
+}
+
+impl BuildInfo {
+    pub(crate) fn collect(info: &ServerInfo, started_at: Instant) -> Self {
+        Self {
+            server_name: info.name.clone(),
+            server_version: info.version.clone(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: option_env!("ULTRAFAST_MCP_GIT_SHA").map(str::to_string),
+            features: enabled_features().iter().map(|f| f.to_string()).collect(),
+            protocol_versions: SUPPORTED_VERSIONS.iter().map(|v| v.to_string()).collect(),
+            uptime_secs: started_at.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Optional features of this crate enabled in the current build
+pub fn enabled_features() -> Vec<&'static str> {
+    [
+        ("http", cfg!(feature = "http")),
+        ("monitoring", cfg!(feature = "monitoring")),
+        ("cursor-signing", cfg!(feature = "cursor-signing")),
+        ("content-encoding", cfg!(feature = "content-encoding")),
+        ("bare-metal", cfg!(feature = "bare-metal")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
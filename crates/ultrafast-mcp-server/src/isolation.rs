@@ -0,0 +1,196 @@
+//! Running tool handlers away from the connection runtime
+//!
+//! Tool handlers run on the runtime that serves the connections, so a
+//! handler that keeps its thread busy (image processing, parsing) holds up
+//! every session scheduled on that worker. [`UltraFastServer::with_tool_isolation`]
+//! moves the calls of a tool to
+//!
+//! - tokio's blocking thread pool, with [`ToolIsolation::blocking`], running
+//!   at most a given number of calls at once
+//! - a [`DedicatedRuntime`] with its own worker threads, with
+//!   [`ToolIsolation::dedicated`]; one runtime can serve several tools
+//!
+//! The handler future is driven to completion there. A call that is
+//! cancelled or times out is answered right away, but its handler keeps
+//! running until it returns or notices [`Context::is_cancelled`].
+//!
+//! [`UltraFastServer::with_tool_isolation`]: crate::UltraFastServer::with_tool_isolation
+//! [`Context::is_cancelled`]: crate::Context::is_cancelled
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::sync::Semaphore;
+use tokio::task::JoinError;
+use ultrafast_mcp_core::error::{MCPError, MCPResult};
+
+/// Where the calls of an isolated tool run
+#[derive(Debug, Clone)]
+pub struct ToolIsolation {
+    executor: Executor,
+}
+
+#[derive(Debug, Clone)]
+enum Executor {
+    Blocking(Arc<Semaphore>),
+    Dedicated(DedicatedRuntime),
+}
+
+impl ToolIsolation {
+    /// On tokio's blocking thread pool, at most `max_concurrent` calls at
+    /// once; further calls wait for a slot
+    pub fn blocking(max_concurrent: usize) -> Self {
+        Self {
+            executor: Executor::Blocking(Arc::new(Semaphore::new(max_concurrent.max(1)))),
+        }
+    }
+
+    /// On the worker threads of `runtime`
+    pub fn dedicated(runtime: &DedicatedRuntime) -> Self {
+        Self {
+            executor: Executor::Dedicated(runtime.clone()),
+        }
+    }
+
+    /// Run `call` to completion on the isolated executor
+    pub(crate) async fn run<T, F>(&self, call: F) -> MCPResult<T>
+    where
+        T: Send + 'static,
+        F: Future<Output = MCPResult<T>> + Send + 'static,
+    {
+        let joined = match &self.executor {
+            Executor::Blocking(slots) => {
+                let slot = slots
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| MCPError::internal_error(e.to_string()))?;
+                let handle = Handle::current();
+                tokio::task::spawn_blocking(move || {
+                    let _slot = slot;
+                    handle.block_on(call)
+                })
+                .await
+            }
+            Executor::Dedicated(runtime) => runtime.handle()?.spawn(call).await,
+        };
+        joined.map_err(join_error)?
+    }
+}
+
+fn join_error(error: JoinError) -> MCPError {
+    if error.is_panic() {
+        MCPError::internal_error("Tool handler panicked".to_string())
+    } else {
+        MCPError::internal_error("Tool handler was aborted".to_string())
+    }
+}
+
+/// A multi-threaded runtime of its own for CPU-heavy tools
+///
+/// Clones share the runtime, which shuts down without waiting for the
+/// calls still running on it once the last clone is dropped or
+/// [`shutdown`](Self::shutdown) is called.
+#[derive(Debug, Clone)]
+pub struct DedicatedRuntime {
+    runtime: Arc<RuntimeSlot>,
+}
+
+#[derive(Debug)]
+struct RuntimeSlot(Mutex<Option<Runtime>>);
+
+impl RuntimeSlot {
+    fn take(&self) -> Option<Runtime> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+impl Drop for RuntimeSlot {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.take() {
+            // Dropping a runtime blocks, which is not allowed in async code
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl DedicatedRuntime {
+    /// A runtime with `worker_threads` threads named `name`
+    pub fn new(name: impl Into<String>, worker_threads: usize) -> std::io::Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(worker_threads.max(1))
+            .thread_name(name)
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            runtime: Arc::new(RuntimeSlot(Mutex::new(Some(runtime)))),
+        })
+    }
+
+    /// Stop the runtime; calls sent to it afterwards fail
+    pub fn shutdown(&self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+
+    fn handle(&self) -> MCPResult<Handle> {
+        self.runtime
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|runtime| runtime.handle().clone())
+            .ok_or_else(|| MCPError::internal_error("Tool runtime is shut down".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn thread_name() -> Option<String> {
+        std::thread::current().name().map(str::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_blocking_calls_are_limited() {
+        let isolation = ToolIsolation::blocking(1);
+        let running = Arc::new(AtomicUsize::new(0));
+        let calls: Vec<_> = (0..3)
+            .map(|_| {
+                let (isolation, running) = (isolation.clone(), running.clone());
+                tokio::spawn(async move {
+                    isolation
+                        .run(async move {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            std::thread::sleep(Duration::from_millis(20));
+                            running.fetch_sub(1, Ordering::SeqCst);
+                            Ok(now)
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for call in calls {
+            assert_eq!(call.await.unwrap().unwrap(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedicated_runtime_runs_calls_on_its_threads() {
+        let runtime = DedicatedRuntime::new("heavy-tools", 2).unwrap();
+        let isolation = ToolIsolation::dedicated(&runtime);
+        let name = isolation.run(async { Ok(thread_name()) }).await.unwrap();
+        assert_eq!(name.as_deref(), Some("heavy-tools"));
+
+        let panicked = isolation.run::<(), _>(async { panic!("boom") }).await;
+        assert!(panicked.unwrap_err().to_string().contains("panicked"));
+
+        runtime.shutdown();
+        assert!(isolation.run(async { Ok(()) }).await.is_err());
+    }
+}
@@ -64,7 +64,17 @@
 //!
 //! - **[`server`]**: Core server implementation and state management
 //! - **[`handlers`]**: Trait definitions for all handler types
+//! - **[`completion`]**: Completion of resource template variables
 //! - **[`context`]**: Context management for request processing
+//! - **[`emulation`]**: Emulated sampling and elicitation for testing
+//! - **[`fs_resources`]**: Resources served from a directory tree
+//! - **[`peer`]**: Server-to-client requests such as elicitation
+//! - **[`prompt_registry`]**: Prompts declared as templates or loaded from files
+//! - **[`resource_registry`]**: In-memory resources changeable at runtime
+//! - **[`shutdown`]**: Graceful shutdown with draining of running requests
+//! - **[`subscriptions`]**: Resource subscriptions by URI, prefix and glob
+//! - **[`wizard`]**: Multi-step elicitation flows
+//! - **[`usage`]**: Sampling token usage and cost accounting
 //!
 //! ## Usage Examples
 //!
@@ -95,6 +105,8 @@
 //!                 Ok(ToolResult {
 //!                     content: vec![ToolContent::text(message)],
 //!                     is_error: Some(false),
+//!                     structured_content: None,
+//!                     progress_summary: None,
 //!                 })
 //!             }
 //!             _ => Err(MCPError::method_not_found(
@@ -168,6 +180,8 @@
 //!         Ok(ToolResult {
 //!             content: vec![ToolContent::text("Tool executed successfully".to_string())],
 //!             is_error: Some(false),
+//!             structured_content: None,
+//!             progress_summary: None,
 //!         })
 //!     }
 //!     async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
@@ -365,14 +379,66 @@
 //! - HTTP operations server
 //! - Advanced features server
 
+pub mod adapters;
+pub mod completion;
+pub mod concurrency;
 pub mod context;
+pub mod emulation;
+pub mod error_mapping;
+pub mod fs_resources;
 pub mod handlers;
+pub mod introspection;
+pub mod isolation;
+pub mod list_changed;
+pub mod logging;
+pub mod middleware;
+pub mod peer;
+pub mod prompt_registry;
+pub mod rate_limit;
+mod registry;
+pub mod resource_registry;
+pub mod resource_stream;
 pub mod server;
+pub mod session_state;
+pub mod shutdown;
+pub mod subscriptions;
+mod typed_tool;
+pub mod usage;
+pub mod wizard;
 
+pub use adapters::{IntoResourceHandler, IntoToolHandler, StaticResources};
+pub use completion::{CompletionRouter, FilePathCompleter, ResourceTemplateCompleter};
+pub use concurrency::{ConcurrencyConfig, ConcurrencyLimit, ConcurrencyScope, ServerBusy};
 pub use context::{Context, ContextLogger, LoggerConfig};
+pub use emulation::{AutoElicitationHandler, AutoSamplingHandler, ClientEmulationConfig};
+pub use error_mapping::{DomainErrorMapper, ErrorMapper};
+pub use fs_resources::FsResourceHandler;
 pub use handlers::*;
+pub use introspection::{BuildInfo, INFO_METHOD};
+pub use isolation::{DedicatedRuntime, ToolIsolation};
+pub use list_changed::{DEFAULT_LIST_CHANGED_DEBOUNCE, ListKind};
+#[cfg(feature = "tracing-layer")]
+pub use logging::McpLogLayer;
+pub use middleware::{RequestInfo, ServerMiddleware};
+pub use peer::{AckPolicy, ClientPeer};
+pub use prompt_registry::PromptRegistry;
+pub use rate_limit::{
+    RATE_LIMIT_NOTIFICATION_METHOD, RateLimit, RateLimitConfig, RateLimitScope, RateLimited,
+};
+pub use resource_registry::ResourceRegistry;
+pub use resource_stream::ResourceByteStream;
 /// All re-exports for convenience
-pub use server::{ServerLoggingConfig, ServerState, ToolRegistrationError, UltraFastServer};
+#[cfg(feature = "http")]
+pub use server::OPENAI_TOOLS_SESSION;
+pub use server::{
+    ServerLoggingConfig, ServerState, ServerStats, ToolRegistrationError, UltraFastServer,
+    UnsupportedMethodPolicy,
+};
+pub use session_state::SessionState;
+pub use shutdown::{SHUTDOWN_NOTIFICATION_METHOD, ShutdownReport, shutdown_signal};
+pub use subscriptions::{DEFAULT_SUBSCRIPTION_SESSION, SubscriptionPattern, SubscriptionRegistry};
+pub use usage::{ModelPrice, SamplingPricing, SamplingUsage, UsageReport, UsageTracker};
+pub use wizard::{Wizard, WizardAnswers, WizardOutcome, WizardSession, WizardState, WizardStep};
 
 // Re-export transport types for convenience
 pub use ultrafast_mcp_transport::{Transport, TransportConfig, create_transport};
@@ -381,7 +447,7 @@ pub use ultrafast_mcp_transport::{Transport, TransportConfig, create_transport};
 pub use ultrafast_mcp_transport::streamable_http::server::HttpTransportConfig;
 
 #[cfg(feature = "monitoring")]
-pub use ultrafast_mcp_monitoring::metrics::RequestTimer;
+pub use ultrafast_mcp_monitoring::metrics::{RejectReason, RequestTimer};
 #[cfg(feature = "monitoring")]
 pub use ultrafast_mcp_monitoring::{HealthStatus, MonitoringConfig, MonitoringSystem};
 
@@ -0,0 +1,108 @@
+//! Telling clients that the tool, resource or prompt list changed
+//!
+//! [`UltraFastServer::notify_tools_changed`] and its siblings send the
+//! `listChanged` notification of a list to every connected session, and
+//! [`UltraFastServer::notify_list_changed`] to a single one. Changes are
+//! debounced: notifications requested within
+//! [`DEFAULT_LIST_CHANGED_DEBOUNCE`] of each other, or the window set with
+//! [`UltraFastServer::with_list_changed_debounce`], go out once at its end,
+//! so registering a batch of tools does not flood clients with
+//! notifications that each make them fetch the list again.
+//!
+//! [`UltraFastServer::notify_tools_changed`]: crate::UltraFastServer::notify_tools_changed
+//! [`UltraFastServer::notify_list_changed`]: crate::UltraFastServer::notify_list_changed
+//! [`UltraFastServer::with_list_changed_debounce`]: crate::UltraFastServer::with_list_changed_debounce
+
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde_json::Value;
+use ultrafast_mcp_core::types::notifications::{
+    PromptsListChangedNotification, ResourcesListChangedNotification, ToolsListChangedNotification,
+};
+
+/// How long list change notifications are held back to coalesce them
+pub const DEFAULT_LIST_CHANGED_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// A list clients can be told has changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListKind {
+    Tools,
+    Resources,
+    Prompts,
+}
+
+impl ListKind {
+    /// Method of the list's change notification
+    pub fn notification_method(&self) -> &'static str {
+        match self {
+            ListKind::Tools => "notifications/tools/listChanged",
+            ListKind::Resources => "notifications/resources/listChanged",
+            ListKind::Prompts => "notifications/prompts/listChanged",
+        }
+    }
+
+    pub(crate) fn notification_params(&self) -> serde_json::Result<Value> {
+        match self {
+            ListKind::Tools => serde_json::to_value(ToolsListChangedNotification::new()),
+            ListKind::Resources => serde_json::to_value(ResourcesListChangedNotification::new()),
+            ListKind::Prompts => serde_json::to_value(PromptsListChangedNotification::new()),
+        }
+    }
+}
+
+/// Notifications waiting for their debounce window to end, by list and
+/// target session (`None` for all sessions)
+#[derive(Debug)]
+pub(crate) struct ListChangeDebouncer {
+    pub(crate) window: Duration,
+    pending: Mutex<HashSet<(ListKind, Option<String>)>>,
+}
+
+impl Default for ListChangeDebouncer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIST_CHANGED_DEBOUNCE)
+    }
+}
+
+impl ListChangeDebouncer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<(ListKind, Option<String>)>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hold a notification back; false if an equal one is already waiting
+    /// and will cover this change
+    pub(crate) fn hold(&self, list: ListKind, session_id: Option<&str>) -> bool {
+        self.lock().insert((list, session_id.map(str::to_string)))
+    }
+
+    /// Let a held notification go, so later changes are announced again
+    pub(crate) fn release(&self, list: ListKind, session_id: Option<&str>) {
+        self.lock().remove(&(list, session_id.map(str::to_string)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_notifications_are_held_once() {
+        let debouncer = ListChangeDebouncer::default();
+        assert!(debouncer.hold(ListKind::Tools, None));
+        assert!(!debouncer.hold(ListKind::Tools, None));
+        assert!(debouncer.hold(ListKind::Tools, Some("session-1")));
+        assert!(debouncer.hold(ListKind::Prompts, None));
+
+        debouncer.release(ListKind::Tools, None);
+        assert!(debouncer.hold(ListKind::Tools, None));
+    }
+}
@@ -0,0 +1,218 @@
+//! Log messages sent to clients over MCP
+//!
+//! Each session chooses the least severe level it wants to receive with
+//! `logging/setLevel`; until it does, the server's level from
+//! [`UltraFastServer::set_log_level`] applies. Messages reach a session as
+//! `notifications/message` when their level is at least the session's:
+//!
+//! - [`Context::log_info`](crate::Context::log_info) and its siblings log to
+//!   the client that sent the request being handled
+//! - [`UltraFastServer::log_to_sessions`] logs to every connected client
+//! - with the `tracing-layer` feature, [`McpLogLayer`] forwards `tracing`
+//!   events to every connected client
+//!
+//! [`UltraFastServer::set_log_level`]: crate::UltraFastServer::set_log_level
+//! [`UltraFastServer::log_to_sessions`]: crate::UltraFastServer::log_to_sessions
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use ultrafast_mcp_core::types::notifications::LogLevel;
+
+use crate::context::log_level_priority;
+
+/// Whether a message at `level` passes a `min_level` filter
+pub(crate) fn is_enabled(level: &LogLevel, min_level: &LogLevel) -> bool {
+    log_level_priority(level) >= log_level_priority(min_level)
+}
+
+/// The log level each session asked for, and the level of the others
+#[derive(Debug)]
+pub(crate) struct SessionLogLevels {
+    levels: Mutex<Levels>,
+}
+
+#[derive(Debug)]
+struct Levels {
+    default: LogLevel,
+    sessions: HashMap<String, LogLevel>,
+}
+
+impl Default for SessionLogLevels {
+    fn default() -> Self {
+        Self {
+            levels: Mutex::new(Levels {
+                default: LogLevel::Info,
+                sessions: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl SessionLogLevels {
+    fn lock(&self) -> MutexGuard<'_, Levels> {
+        self.levels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The level of sessions that did not set one
+    pub(crate) fn set_default(&self, level: LogLevel) {
+        self.lock().default = level;
+    }
+
+    pub(crate) fn set(&self, session_id: &str, level: LogLevel) {
+        self.lock().sessions.insert(session_id.to_string(), level);
+    }
+
+    /// The level of a session, or the default one
+    pub(crate) fn level(&self, session_id: &str) -> LogLevel {
+        let levels = self.lock();
+        levels
+            .sessions
+            .get(session_id)
+            .unwrap_or(&levels.default)
+            .clone()
+    }
+
+    /// Forget the level of a session that ended
+    pub(crate) fn remove_session(&self, session_id: &str) {
+        self.lock().sessions.remove(session_id);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().sessions.clear();
+    }
+}
+
+#[cfg(feature = "tracing-layer")]
+pub use layer::McpLogLayer;
+
+#[cfg(feature = "tracing-layer")]
+mod layer {
+    use std::cell::Cell;
+
+    use serde_json::{Map, Value};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer};
+    use ultrafast_mcp_core::types::notifications::LogLevel;
+
+    use crate::UltraFastServer;
+
+    thread_local! {
+        /// Set while an event is being forwarded, so events logged on the
+        /// way to the client are not forwarded in turn
+        static FORWARDING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// A `tracing` layer sending events to the server's clients as log
+    /// messages
+    ///
+    /// Each event goes to every connected session whose log level it
+    /// meets, with its target as the logger and its fields, `message`
+    /// included, as the data. Events of this SDK's own crates are not
+    /// forwarded, since sending a message may log them.
+    ///
+    /// ```rust,no_run
+    /// use tracing_subscriber::prelude::*;
+    /// use ultrafast_mcp_server::{McpLogLayer, UltraFastServer};
+    ///
+    /// # fn run(server: UltraFastServer) {
+    /// tracing_subscriber::registry()
+    ///     .with(McpLogLayer::new(server.clone()))
+    ///     .init();
+    /// # }
+    /// ```
+    #[derive(Clone)]
+    pub struct McpLogLayer {
+        server: UltraFastServer,
+    }
+
+    impl std::fmt::Debug for McpLogLayer {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("McpLogLayer").finish_non_exhaustive()
+        }
+    }
+
+    impl McpLogLayer {
+        pub fn new(server: UltraFastServer) -> Self {
+            Self { server }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for McpLogLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let target = event.metadata().target();
+            if target.starts_with("ultrafast_mcp") || FORWARDING.with(|f| f.replace(true)) {
+                return;
+            }
+            let mut fields = FieldsToJson::default();
+            event.record(&mut fields);
+            let level = match *event.metadata().level() {
+                Level::ERROR => LogLevel::Error,
+                Level::WARN => LogLevel::Warning,
+                Level::INFO => LogLevel::Info,
+                Level::DEBUG | Level::TRACE => LogLevel::Debug,
+            };
+            self.server
+                .log_to_sessions(level, Some(target), Value::Object(fields.0));
+            FORWARDING.with(|f| f.set(false));
+        }
+    }
+
+    /// Collects the fields of an event into a JSON object
+    #[derive(Default)]
+    struct FieldsToJson(Map<String, Value>);
+
+    impl Visit for FieldsToJson {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}").into());
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_i64(&mut self, field: &Field, value: i64) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_f64(&mut self, field: &Field, value: f64) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_bool(&mut self, field: &Field, value: bool) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_keep_their_own_level() {
+        let levels = SessionLogLevels::default();
+        levels.set("alice", LogLevel::Error);
+        assert!(matches!(levels.level("alice"), LogLevel::Error));
+        assert!(matches!(levels.level("bob"), LogLevel::Info));
+
+        levels.set_default(LogLevel::Debug);
+        assert!(matches!(levels.level("bob"), LogLevel::Debug));
+        levels.remove_session("alice");
+        assert!(matches!(levels.level("alice"), LogLevel::Debug));
+    }
+
+    #[test]
+    fn test_levels_filter_by_severity() {
+        assert!(is_enabled(&LogLevel::Warning, &LogLevel::Info));
+        assert!(is_enabled(&LogLevel::Info, &LogLevel::Info));
+        assert!(!is_enabled(&LogLevel::Debug, &LogLevel::Info));
+        assert!(is_enabled(&LogLevel::Emergency, &LogLevel::Critical));
+    }
+}
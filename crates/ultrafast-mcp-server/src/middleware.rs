@@ -0,0 +1,86 @@
+//! Middleware around JSON-RPC request dispatch
+//!
+//! A [`ServerMiddleware`] sees every request after it has been decoded and
+//! before the server routes it to a handler, and every response on its way
+//! back, so cross-cutting concerns such as authorization, logging, metrics or
+//! rate limiting can act on MCP method names and parameters rather than raw
+//! transport bytes.
+//!
+//! Middleware registered with [`UltraFastServer::with_middleware`] runs in
+//! registration order on the way in and in reverse order on the way out. A
+//! middleware that rejects a request stops it from reaching the handler or
+//! any later middleware; the middleware before it, and the rejecting one
+//! itself, still see the error.
+//!
+//! ```rust
+//! use async_trait::async_trait;
+//! use ultrafast_mcp_core::protocol::jsonrpc::{JsonRpcError, JsonRpcRequest};
+//! use ultrafast_mcp_server::{RequestInfo, ServerMiddleware};
+//!
+//! /// Refuse every tool call
+//! struct ReadOnly;
+//!
+//! #[async_trait]
+//! impl ServerMiddleware for ReadOnly {
+//!     async fn on_request(&self, request: &mut JsonRpcRequest) -> Result<(), JsonRpcError> {
+//!         if request.method == "tools/call" {
+//!             return Err(JsonRpcError::access_denied("tools".to_string()));
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! [`UltraFastServer::with_middleware`]: crate::UltraFastServer::with_middleware
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use ultrafast_mcp_core::protocol::jsonrpc::{
+    JsonRpcError, JsonRpcRequest, JsonRpcResponse, RequestId,
+};
+
+/// The request a response or error belongs to
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    pub method: String,
+    pub id: Option<RequestId>,
+    pub started_at: Instant,
+}
+
+impl RequestInfo {
+    pub fn new(request: &JsonRpcRequest) -> Self {
+        Self {
+            method: request.method.clone(),
+            id: request.id.clone(),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Time since the request entered the middleware chain
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Hooks around the dispatch of each JSON-RPC request
+///
+/// Notifications and responses to server-initiated requests do not pass
+/// through middleware. All hooks default to doing nothing.
+#[async_trait]
+pub trait ServerMiddleware: Send + Sync {
+    /// Inspect or rewrite a request before it is dispatched
+    ///
+    /// Returning an error rejects the request; the client receives it as the
+    /// error response.
+    async fn on_request(&self, _request: &mut JsonRpcRequest) -> Result<(), JsonRpcError> {
+        Ok(())
+    }
+
+    /// Inspect or rewrite a successful response
+    async fn on_response(&self, _request: &RequestInfo, _response: &mut JsonRpcResponse) {}
+
+    /// Inspect or rewrite an error response, including rejections by
+    /// middleware
+    async fn on_error(&self, _request: &RequestInfo, _error: &mut JsonRpcError) {}
+}
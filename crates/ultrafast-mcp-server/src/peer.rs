@@ -0,0 +1,693 @@
+//! Server-to-client requests for UltraFastServer
+//!
+//! A [`ClientPeer`] represents the client on the other end of one connection.
+//! Handlers use it (through [`Context`](crate::Context)) to send requests such
+//! as `elicitation/create` to the client and await the correlated response
+//! while the server keeps processing other messages.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult, ProtocolError, TransportError},
+    protocol::{
+        capabilities::ClientCapabilities,
+        jsonrpc::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, RequestId},
+    },
+    types::{
+        notifications::{
+            CancelledNotification, NotificationAck, set_notification_ack_id,
+            set_notification_sequence,
+        },
+        roots::{ListRootsResponse, Root},
+    },
+    utils::{PingPolicy, PingStats, PingTracker},
+};
+use ultrafast_mcp_transport::TransportDescription;
+
+/// Retry schedule for [`ClientPeer::send_notification_acked`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AckPolicy {
+    /// How long to wait for an acknowledgement before resending
+    pub retry_interval: Duration,
+    /// How long to keep resending before giving up
+    pub expires_after: Duration,
+}
+
+impl Default for AckPolicy {
+    fn default() -> Self {
+        Self {
+            retry_interval: Duration::from_secs(5),
+            expires_after: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The connected client, as seen by the server
+#[derive(Debug)]
+pub struct ClientPeer {
+    outgoing: mpsc::UnboundedSender<JsonRpcMessage>,
+    pending: Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>,
+    next_request_id: AtomicU64,
+    client_capabilities: RwLock<Option<ClientCapabilities>>,
+    protocol_version: RwLock<Option<String>>,
+    default_timeout: Duration,
+    session_id: Option<String>,
+    transport: TransportDescription,
+    /// Last notification sequence number, when numbering is enabled
+    notification_sequence: Option<Mutex<u64>>,
+    next_ack_id: AtomicU64,
+    awaiting_ack: Mutex<HashMap<String, oneshot::Sender<()>>>,
+    /// Roots listed by the client, until it reports a change
+    roots: Mutex<CachedRoots>,
+    /// Outcomes of the server's pings, when a ping policy watches the peer
+    pings: Option<Arc<PingTracker>>,
+}
+
+#[derive(Debug, Default)]
+struct CachedRoots {
+    // Bumped on every change notification, so that a listing racing with
+    // one is not cached
+    generation: u64,
+    roots: Option<Vec<Root>>,
+}
+
+impl ClientPeer {
+    /// Create a peer whose messages are written to `outgoing` by the
+    /// connection's run loop
+    pub fn new(outgoing: mpsc::UnboundedSender<JsonRpcMessage>, default_timeout: Duration) -> Self {
+        Self {
+            outgoing,
+            pending: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+            client_capabilities: RwLock::new(None),
+            protocol_version: RwLock::new(None),
+            default_timeout,
+            session_id: None,
+            transport: TransportDescription::default(),
+            notification_sequence: None,
+            next_ack_id: AtomicU64::new(1),
+            awaiting_ack: Mutex::new(HashMap::new()),
+            roots: Mutex::new(CachedRoots::default()),
+            pings: None,
+        }
+    }
+
+    /// Number notifications sent through [`send_notification`](Self::send_notification)
+    ///
+    /// Each notification's `_meta` gets a sequence number starting at 1, so
+    /// the client can detect gaps and reordering.
+    pub fn with_notification_sequencing(mut self) -> Self {
+        self.notification_sequence = Some(Mutex::new(0));
+        self
+    }
+
+    /// Ping the client as `policy` says, see
+    /// [`UltraFastServer::with_ping_policy`](crate::UltraFastServer::with_ping_policy)
+    pub fn with_ping_policy(mut self, policy: PingPolicy) -> Self {
+        self.pings = Some(Arc::new(PingTracker::new(policy)));
+        self
+    }
+
+    /// Counts of the pings sent to the client, when a ping policy watches it
+    pub fn ping_stats(&self) -> Option<PingStats> {
+        self.pings.as_ref().map(|tracker| tracker.stats())
+    }
+
+    pub(crate) fn ping_tracker(&self) -> Option<Arc<PingTracker>> {
+        self.pings.clone()
+    }
+
+    /// Associate the peer with a transport session
+    pub fn with_session_id(mut self, session_id: String) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Transport session the peer is connected through, if the transport
+    /// has sessions
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Record what the peer is connected through
+    pub fn with_transport(mut self, transport: TransportDescription) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// What the peer is connected through, used to label its requests in
+    /// metrics and logs
+    ///
+    /// The session id falls back to the one set with
+    /// [`with_session_id`](Self::with_session_id).
+    pub fn transport(&self) -> TransportDescription {
+        let mut transport = self.transport.clone();
+        if transport.session_id.is_none() {
+            transport.session_id = self.session_id.clone();
+        }
+        transport
+    }
+
+    /// Capabilities the client declared in `initialize`, once received
+    pub fn client_capabilities(&self) -> Option<ClientCapabilities> {
+        self.client_capabilities
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn set_client_capabilities(&self, capabilities: ClientCapabilities) {
+        *self
+            .client_capabilities
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(capabilities);
+    }
+
+    /// Protocol version negotiated in `initialize`, once answered
+    pub fn protocol_version(&self) -> Option<String> {
+        self.protocol_version
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn set_protocol_version(&self, version: String) {
+        *self
+            .protocol_version
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(version);
+    }
+
+    /// Timeout applied by [`send_request`](Self::send_request) callers that
+    /// don't specify one
+    pub fn default_timeout(&self) -> Duration {
+        self.default_timeout
+    }
+
+    /// Number of server-to-client requests awaiting a response
+    pub fn pending_requests(&self) -> usize {
+        self.lock_pending().len()
+    }
+
+    /// Queue a message for the client
+    pub fn send(&self, message: JsonRpcMessage) -> MCPResult<()> {
+        self.outgoing
+            .send(message)
+            .map_err(|_| MCPError::Transport(TransportError::ConnectionClosed))
+    }
+
+    /// Send a notification to the client
+    pub fn send_notification(&self, method: &str, params: Option<Value>) -> MCPResult<()> {
+        self.queue_notification(method, params).map(|_| ())
+    }
+
+    /// Send a notification and resend it until the client acknowledges it
+    ///
+    /// The notification's `_meta` carries an ack id which the client echoes
+    /// back in a `notifications/ultrafast/ack` notification. Resends are
+    /// identical to the first send, including any sequence number. Fails
+    /// with [`ProtocolError::RequestTimeout`] if no acknowledgement arrives
+    /// within `policy.expires_after`; spawn the returned future to deliver
+    /// in the background.
+    pub async fn send_notification_acked(
+        &self,
+        method: &str,
+        mut params: Option<Value>,
+        policy: AckPolicy,
+    ) -> MCPResult<()> {
+        let ack_id = self.next_ack_id.fetch_add(1, Ordering::Relaxed).to_string();
+        set_notification_ack_id(&mut params, &ack_id);
+        let (sender, mut receiver) = oneshot::channel();
+        self.lock_awaiting_ack().insert(ack_id.clone(), sender);
+        let _guard = AwaitingAckGuard {
+            peer: self,
+            ack_id: &ack_id,
+        };
+
+        let params = self.queue_notification(method, params)?;
+        let deadline = tokio::time::Instant::now() + policy.expires_after;
+        loop {
+            let wait = policy
+                .retry_interval
+                .min(deadline.saturating_duration_since(tokio::time::Instant::now()));
+            if let Ok(acked) = tokio::time::timeout(wait, &mut receiver).await {
+                return acked.map_err(|_| MCPError::Transport(TransportError::ConnectionClosed));
+            }
+            if tokio::time::Instant::now() >= deadline {
+                warn!(
+                    "Notification {} ({}) expired unacknowledged",
+                    method, ack_id
+                );
+                return Err(MCPError::Protocol(ProtocolError::RequestTimeout));
+            }
+            debug!(
+                "Resending unacknowledged notification {} ({})",
+                method, ack_id
+            );
+            self.send(JsonRpcMessage::Notification(JsonRpcRequest::notification(
+                method.to_string(),
+                params.clone(),
+            )))?;
+        }
+    }
+
+    /// Number of acknowledged notifications still awaiting their ack
+    pub fn unacknowledged_notifications(&self) -> usize {
+        self.lock_awaiting_ack().len()
+    }
+
+    /// Deliver a client's `notifications/ultrafast/ack`
+    ///
+    /// Returns `false` if no notification with the acknowledged id is
+    /// outstanding, e.g. because an earlier ack for a resend arrived first.
+    pub fn handle_notification_ack(&self, params: Option<Value>) -> bool {
+        let Some(ack) =
+            params.and_then(|params| serde_json::from_value::<NotificationAck>(params).ok())
+        else {
+            warn!("Received a notification ack without an ack id");
+            return false;
+        };
+        match self.lock_awaiting_ack().remove(&ack.ack_id) {
+            Some(sender) => {
+                let _ = sender.send(());
+                true
+            }
+            None => {
+                debug!("Ack {} matches no outstanding notification", ack.ack_id);
+                false
+            }
+        }
+    }
+
+    /// Queue a notification, numbering it if sequencing is enabled, and
+    /// return the params as sent
+    fn queue_notification(
+        &self,
+        method: &str,
+        mut params: Option<Value>,
+    ) -> MCPResult<Option<Value>> {
+        let Some(sequence) = &self.notification_sequence else {
+            self.send(JsonRpcMessage::Notification(JsonRpcRequest::notification(
+                method.to_string(),
+                params.clone(),
+            )))?;
+            return Ok(params);
+        };
+        // Numbering and queueing under one lock keeps sequence order equal to
+        // the order notifications reach the outgoing channel
+        let mut sequence = sequence.lock().unwrap_or_else(|e| e.into_inner());
+        *sequence += 1;
+        set_notification_sequence(&mut params, *sequence);
+        self.send(JsonRpcMessage::Notification(JsonRpcRequest::notification(
+            method.to_string(),
+            params.clone(),
+        )))?;
+        Ok(params)
+    }
+
+    /// Send a request to the client and wait for its response
+    ///
+    /// If the response does not arrive within `timeout`, or the returned
+    /// future is dropped first (for example because the server request that
+    /// issued it was cancelled), the client is sent `notifications/cancelled`
+    /// for the outstanding request.
+    pub async fn send_request(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> MCPResult<Value> {
+        let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.lock_pending().insert(id, sender);
+        let mut guard = PendingRequestGuard {
+            peer: self,
+            id,
+            armed: true,
+        };
+
+        debug!("Sending {} request {} to client", method, id);
+        self.send(JsonRpcMessage::Request(JsonRpcRequest::new(
+            method.to_string(),
+            params,
+            Some(RequestId::Number(id as i64)),
+        )))?;
+
+        let response = match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => return Err(MCPError::Transport(TransportError::ConnectionClosed)),
+            Err(_) => return Err(MCPError::Protocol(ProtocolError::RequestTimeout)),
+        };
+        guard.armed = false;
+
+        match (response.error, response.result) {
+            (Some(error), _) => Err(MCPError::from(error)),
+            (None, Some(result)) => Ok(result),
+            (None, None) => Err(MCPError::Protocol(ProtocolError::InvalidResponse(format!(
+                "Response to {method} has no result or error"
+            )))),
+        }
+    }
+
+    /// Send a request to the client and read its result as `T`
+    ///
+    /// See [`send_request`](Self::send_request); a result that cannot be
+    /// read as `T` is an invalid response error.
+    pub async fn send_request_as<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> MCPResult<T> {
+        let result = self.send_request(method, params, timeout).await?;
+        serde_json::from_value(result).map_err(|e| {
+            MCPError::Protocol(ProtocolError::InvalidResponse(format!(
+                "Invalid result for {method}: {e}"
+            )))
+        })
+    }
+
+    /// The client's filesystem roots, as returned by `roots/list`
+    ///
+    /// The answer is cached until the client sends a roots list changed
+    /// notification, see [`invalidate_roots`](Self::invalidate_roots).
+    pub async fn list_roots(&self, timeout: Duration) -> MCPResult<Vec<Root>> {
+        let generation = {
+            let cached = self.lock_roots();
+            if let Some(roots) = &cached.roots {
+                return Ok(roots.clone());
+            }
+            cached.generation
+        };
+
+        let roots = self
+            .send_request_as::<ListRootsResponse>("roots/list", None, timeout)
+            .await?
+            .roots;
+        let mut cached = self.lock_roots();
+        if cached.generation == generation {
+            cached.roots = Some(roots.clone());
+        }
+        Ok(roots)
+    }
+
+    /// Forget the cached roots, after the client reported that they changed
+    pub fn invalidate_roots(&self) {
+        let mut cached = self.lock_roots();
+        cached.generation += 1;
+        cached.roots = None;
+    }
+
+    /// Deliver a response from the client to the request awaiting it
+    ///
+    /// Returns `false` if no request with the response's ID is outstanding.
+    pub fn handle_response(&self, response: JsonRpcResponse) -> bool {
+        let id = match &response.id {
+            Some(RequestId::Number(id)) => u64::try_from(*id).ok(),
+            Some(RequestId::String(id)) => id.parse().ok(),
+            None => None,
+        };
+        match id.and_then(|id| self.lock_pending().remove(&id)) {
+            Some(sender) => {
+                let _ = sender.send(response);
+                true
+            }
+            None => {
+                warn!(
+                    "Received response {:?} matching no outstanding server request",
+                    response.id
+                );
+                false
+            }
+        }
+    }
+
+    fn lock_awaiting_ack(&self) -> std::sync::MutexGuard<'_, HashMap<String, oneshot::Sender<()>>> {
+        self.awaiting_ack.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_roots(&self) -> std::sync::MutexGuard<'_, CachedRoots> {
+        self.roots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_pending(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<u64, oneshot::Sender<JsonRpcResponse>>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Stops waiting for an ack once delivery finishes or is abandoned
+struct AwaitingAckGuard<'a> {
+    peer: &'a ClientPeer,
+    ack_id: &'a str,
+}
+
+impl Drop for AwaitingAckGuard<'_> {
+    fn drop(&mut self) {
+        self.peer.lock_awaiting_ack().remove(self.ack_id);
+    }
+}
+
+/// Removes an abandoned request from the pending table and tells the client
+/// to stop working on it
+struct PendingRequestGuard<'a> {
+    peer: &'a ClientPeer,
+    id: u64,
+    armed: bool,
+}
+
+impl Drop for PendingRequestGuard<'_> {
+    fn drop(&mut self) {
+        if !self.armed || self.peer.lock_pending().remove(&self.id).is_none() {
+            return;
+        }
+
+        let notification = CancelledNotification::new(Value::from(self.id))
+            .with_reason("Request abandoned by the server".to_string());
+        if let Ok(params) = serde_json::to_value(notification) {
+            let _ = self
+                .peer
+                .send_notification("notifications/cancelled", Some(params));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn peer() -> (Arc<ClientPeer>, mpsc::UnboundedReceiver<JsonRpcMessage>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Arc::new(ClientPeer::new(sender, Duration::from_secs(5))),
+            receiver,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_send_request_is_correlated_with_response() {
+        let (peer, mut outgoing) = peer();
+
+        let requester = peer.clone();
+        let call = tokio::spawn(async move {
+            requester
+                .send_request("elicitation/create", None, Duration::from_secs(5))
+                .await
+        });
+
+        let Some(JsonRpcMessage::Request(request)) = outgoing.recv().await else {
+            panic!("expected an outbound request");
+        };
+        assert_eq!(request.method, "elicitation/create");
+        assert!(peer.handle_response(JsonRpcResponse::success(
+            serde_json::json!({"action": "decline"}),
+            request.id,
+        )));
+
+        let result = call.await.unwrap().unwrap();
+        assert_eq!(result["action"], "decline");
+        assert_eq!(peer.pending_requests(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_typed_requests_reject_unexpected_results() {
+        let (peer, mut outgoing) = peer();
+
+        let requester = peer.clone();
+        let call = tokio::spawn(async move {
+            requester
+                .send_request_as::<ListRootsResponse>("roots/list", None, Duration::from_secs(5))
+                .await
+        });
+
+        let Some(JsonRpcMessage::Request(request)) = outgoing.recv().await else {
+            panic!("expected an outbound request");
+        };
+        peer.handle_response(JsonRpcResponse::success(
+            serde_json::json!({"roots": "none"}),
+            request.id,
+        ));
+
+        let error = call.await.unwrap().unwrap_err();
+        assert!(matches!(
+            error,
+            MCPError::Protocol(ProtocolError::InvalidResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_roots_are_cached_until_invalidated() {
+        let (peer, mut outgoing) = peer();
+        let answer = |peer: Arc<ClientPeer>, request: JsonRpcRequest, uri: &str| {
+            assert_eq!(request.method, "roots/list");
+            peer.handle_response(JsonRpcResponse::success(
+                serde_json::json!({"roots": [{"uri": uri}]}),
+                request.id,
+            ));
+        };
+
+        let lister = peer.clone();
+        let first = tokio::spawn(async move { lister.list_roots(Duration::from_secs(5)).await });
+        let Some(JsonRpcMessage::Request(request)) = outgoing.recv().await else {
+            panic!("expected a roots/list request");
+        };
+        answer(peer.clone(), request, "file:///a");
+        assert_eq!(first.await.unwrap().unwrap()[0].uri, "file:///a");
+
+        // Served from the cache without asking the client
+        let cached = peer.list_roots(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(cached[0].uri, "file:///a");
+        assert!(outgoing.try_recv().is_err());
+
+        peer.invalidate_roots();
+        let lister = peer.clone();
+        let second = tokio::spawn(async move { lister.list_roots(Duration::from_secs(5)).await });
+        let Some(JsonRpcMessage::Request(request)) = outgoing.recv().await else {
+            panic!("expected a roots/list request");
+        };
+        answer(peer.clone(), request, "file:///b");
+        assert_eq!(second.await.unwrap().unwrap()[0].uri, "file:///b");
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_request_is_cancelled_on_client() {
+        let (peer, mut outgoing) = peer();
+
+        let result = peer
+            .send_request("elicitation/create", None, Duration::from_millis(10))
+            .await;
+        assert!(matches!(
+            result,
+            Err(MCPError::Protocol(ProtocolError::RequestTimeout))
+        ));
+        assert_eq!(peer.pending_requests(), 0);
+
+        assert!(matches!(
+            outgoing.recv().await,
+            Some(JsonRpcMessage::Request(_))
+        ));
+        let Some(JsonRpcMessage::Notification(cancelled)) = outgoing.recv().await else {
+            panic!("expected a cancellation notification");
+        };
+        assert_eq!(cancelled.method, "notifications/cancelled");
+        assert!(!peer.handle_response(JsonRpcResponse::success(
+            serde_json::json!({}),
+            Some(RequestId::Number(1)),
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_numbered_per_peer() {
+        let (sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = ClientPeer::new(sender, Duration::from_secs(5)).with_notification_sequencing();
+
+        peer.send_notification(
+            "notifications/progress",
+            Some(serde_json::json!({"progress": 1})),
+        )
+        .unwrap();
+        peer.send_notification("notifications/tools/listChanged", None)
+            .unwrap();
+
+        for expected in 1..=2 {
+            let Some(JsonRpcMessage::Notification(notification)) = outgoing.recv().await else {
+                panic!("expected a notification");
+            };
+            assert_eq!(
+                ultrafast_mcp_core::types::notifications::notification_sequence(
+                    notification.params.as_ref()
+                ),
+                Some(expected)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acked_notification_is_resent_until_acknowledged() {
+        let (peer, mut outgoing) = peer();
+
+        let sender = peer.clone();
+        let delivery = tokio::spawn(async move {
+            sender
+                .send_notification_acked(
+                    "notifications/resources/updated",
+                    Some(serde_json::json!({"uri": "file:///a"})),
+                    AckPolicy {
+                        retry_interval: Duration::from_millis(10),
+                        expires_after: Duration::from_secs(5),
+                    },
+                )
+                .await
+        });
+
+        let Some(JsonRpcMessage::Notification(first)) = outgoing.recv().await else {
+            panic!("expected a notification");
+        };
+        let Some(JsonRpcMessage::Notification(resent)) = outgoing.recv().await else {
+            panic!("expected a resent notification");
+        };
+        assert_eq!(first.params, resent.params);
+
+        let ack_id =
+            ultrafast_mcp_core::types::notifications::notification_ack_id(first.params.as_ref())
+                .unwrap()
+                .to_string();
+        assert!(peer.handle_notification_ack(Some(serde_json::json!({"ackId": ack_id}))));
+        delivery.await.unwrap().unwrap();
+        assert_eq!(peer.unacknowledged_notifications(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_notification_expires() {
+        let (peer, _outgoing) = peer();
+
+        let result = peer
+            .send_notification_acked(
+                "notifications/resources/updated",
+                None,
+                AckPolicy {
+                    retry_interval: Duration::from_millis(10),
+                    expires_after: Duration::from_millis(30),
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(MCPError::Protocol(ProtocolError::RequestTimeout))
+        ));
+        assert_eq!(peer.unacknowledged_notifications(), 0);
+    }
+}
@@ -0,0 +1,1042 @@
+//! Prompts declared as data
+//!
+//! [`PromptRegistry`] is a [`PromptHandler`] serving [`PromptTemplate`]s,
+//! built in code or loaded from JSON files (TOML and YAML with the
+//! `prompt-files` feature), instead of a hand-written `match` on prompt
+//! names. Templates use a small syntax:
+//!
+//! - `{{topic}}` is replaced with the value of the argument `topic`, or
+//!   nothing when an optional argument is not given.
+//! - `{{#if topic}}...{{else}}...{{/if}}` keeps its first part when `topic`
+//!   is given and not empty, and the part after `{{else}}`, if any,
+//!   otherwise. `{{#unless topic}}...{{/unless}}` is the reverse.
+//! - In a prompt's `template`, `{{#system}}`, `{{#user}}` and
+//!   `{{#assistant}}` blocks each become a message with that role, with
+//!   surrounding whitespace removed.
+//!
+//! Templates are checked when added: malformed blocks and arguments that are
+//! used but not declared are rejected. `prompts/get` fails with invalid
+//! params when a required argument is missing; optional ones fall back to
+//! their default.
+//!
+//! ```rust
+//! use ultrafast_mcp_server::prompt_registry::{PromptTemplate, PromptTemplateArgument};
+//! use ultrafast_mcp_server::PromptRegistry;
+//!
+//! let registry = PromptRegistry::new();
+//! registry
+//!     .add(
+//!         PromptTemplate::new("review")
+//!             .with_description("Review a change")
+//!             .with_argument(
+//!                 PromptTemplateArgument::new("language")
+//!                     .required(true)
+//!                     .with_suggestions(["rust", "python"]),
+//!             )
+//!             .with_argument(PromptTemplateArgument::new("focus"))
+//!             .with_template(
+//!                 "{{#system}}You review {{language}} code.{{/system}}
+//!                  {{#user}}Review this change{{#if focus}}, focusing on {{focus}}{{/if}}.{{/user}}",
+//!             ),
+//!     )
+//!     .unwrap();
+//! ```
+//!
+//! The same prompt in a JSON file passed to [`PromptRegistry::add_file`]:
+//!
+//! ```json
+//! {
+//!   "prompts": [{
+//!     "name": "review",
+//!     "arguments": [{"name": "language", "required": true, "suggestions": ["rust", "python"]}],
+//!     "messages": [{"role": "user", "text": "Review this {{language}} change."}]
+//!   }]
+//! }
+//! ```
+//!
+//! Registered with [`UltraFastServer::with_prompt_registry`], the registry
+//! also answers `completion/complete` requests for its prompts' arguments
+//! with their suggestions.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+use tracing::warn;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult},
+    types::{
+        completion::{CompleteRequest, CompleteResponse, Completion, CompletionValue},
+        prompts::{
+            GetPromptRequest, GetPromptResponse, ListPromptsRequest, ListPromptsResponse, Prompt,
+            PromptArgument, PromptContent, PromptMessage, PromptRole,
+        },
+    },
+};
+
+use crate::{
+    UltraFastServer,
+    completion::MAX_COMPLETION_VALUES,
+    handlers::{CompletionHandler, PromptHandler},
+};
+
+/// Prompts listed per `prompts/list` page unless set otherwise
+pub const DEFAULT_PROMPT_PAGE_SIZE: usize = 100;
+
+/// Changes kept for a slow publisher; missed ones are merged anyway
+const CHANGE_CAPACITY: usize = 16;
+
+/// Why a prompt could not be added
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PromptTemplateError {
+    #[error("Invalid template for prompt '{prompt}': {message}")]
+    Syntax { prompt: String, message: String },
+
+    #[error("Prompt '{prompt}' uses undeclared argument '{argument}'")]
+    UndeclaredArgument { prompt: String, argument: String },
+
+    #[error("Failed to load prompts from {path}: {message}")]
+    Load { path: String, message: String },
+}
+
+/// An argument of a [`PromptTemplate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptTemplateArgument {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub required: bool,
+
+    /// Value used when the argument is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+
+    /// Values offered by `completion/complete`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+impl PromptTemplateArgument {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_suggestions(mut self, values: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.suggestions = values.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// A message of a [`PromptTemplate`], rendered as written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateMessage {
+    pub role: PromptRole,
+    pub text: String,
+}
+
+/// A prompt whose messages are rendered from its arguments
+///
+/// Messages come from `messages` followed by the role blocks of `template`;
+/// see the [module documentation](self) for the syntax.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<PromptTemplateArgument>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<PromptTemplateMessage>,
+
+    /// Messages written as `{{#user}}`, `{{#assistant}}` and `{{#system}}`
+    /// blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl PromptTemplate {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            arguments: Vec::new(),
+            messages: Vec::new(),
+            template: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_argument(mut self, argument: PromptTemplateArgument) -> Self {
+        self.arguments.push(argument);
+        self
+    }
+
+    /// Add a message with `role`
+    pub fn with_message(mut self, role: PromptRole, text: impl Into<String>) -> Self {
+        self.messages.push(PromptTemplateMessage {
+            role,
+            text: text.into(),
+        });
+        self
+    }
+
+    pub fn system(self, text: impl Into<String>) -> Self {
+        self.with_message(PromptRole::System, text)
+    }
+
+    pub fn user(self, text: impl Into<String>) -> Self {
+        self.with_message(PromptRole::User, text)
+    }
+
+    pub fn assistant(self, text: impl Into<String>) -> Self {
+        self.with_message(PromptRole::Assistant, text)
+    }
+
+    /// Set the messages written as role blocks
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+}
+
+/// Format of a prompt file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptFormat {
+    Json,
+    #[cfg(feature = "prompt-files")]
+    Toml,
+    #[cfg(feature = "prompt-files")]
+    Yaml,
+}
+
+impl PromptFormat {
+    /// The format of a file, by its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(PromptFormat::Json),
+            #[cfg(feature = "prompt-files")]
+            "toml" => Some(PromptFormat::Toml),
+            #[cfg(feature = "prompt-files")]
+            "yaml" | "yml" => Some(PromptFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Contents of a prompt file
+#[derive(Deserialize)]
+struct PromptFile {
+    prompts: Vec<PromptTemplate>,
+}
+
+/// Piece of a parsed template
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Argument(String),
+    Condition {
+        argument: String,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Role(PromptRole, Vec<Node>),
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Argument(&'a str),
+    Open(&'a str, Option<&'a str>),
+    Else,
+    Close(&'a str),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "'{{' is never closed".to_string())?;
+        let tag = after[..end].trim();
+        tokens.push(parse_tag(tag)?);
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(tag: &str) -> Result<Token<'_>, String> {
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    };
+    if let Some(block) = tag.strip_prefix('#') {
+        let mut words = block.split_whitespace();
+        let kind = words.next().unwrap_or_default();
+        let argument = words.next();
+        if words.next().is_some() || argument.is_some_and(|argument| !is_name(argument)) {
+            return Err(format!("malformed tag '{tag}'"));
+        }
+        Ok(Token::Open(kind, argument))
+    } else if let Some(kind) = tag.strip_prefix('/') {
+        Ok(Token::Close(kind.trim()))
+    } else if tag == "else" {
+        Ok(Token::Else)
+    } else if is_name(tag) {
+        Ok(Token::Argument(tag))
+    } else {
+        Err(format!("malformed tag '{tag}'"))
+    }
+}
+
+struct Parser<'a> {
+    tokens: std::vec::IntoIter<Token<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    /// Nodes up to the end of `block`, and whether it ended at `{{else}}`
+    fn parse(&mut self, block: Option<&str>) -> Result<(Vec<Node>, bool), String> {
+        let mut nodes = Vec::new();
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::Text(text) => nodes.push(Node::Text(text.to_string())),
+                Token::Argument(name) => nodes.push(Node::Argument(name.to_string())),
+                Token::Open(kind, argument) => nodes.push(self.parse_block(kind, argument)?),
+                Token::Else => match block {
+                    Some("if" | "unless") => return Ok((nodes, true)),
+                    _ => return Err("'else' outside an '#if' or '#unless' block".to_string()),
+                },
+                Token::Close(kind) => match block {
+                    Some(open) if open == kind => return Ok((nodes, false)),
+                    Some(open) => return Err(format!("'/{kind}' does not close '#{open}'")),
+                    None => return Err(format!("'/{kind}' has no opening block")),
+                },
+            }
+        }
+        match block {
+            Some(open) => Err(format!("'#{open}' block is never closed")),
+            None => Ok((nodes, false)),
+        }
+    }
+
+    fn parse_block(&mut self, kind: &'a str, argument: Option<&str>) -> Result<Node, String> {
+        match (kind, argument) {
+            ("if" | "unless", Some(argument)) => {
+                let (then, has_else) = self.parse(Some(kind))?;
+                let otherwise = match has_else {
+                    true => match self.parse(Some(kind))? {
+                        (_, true) => return Err(format!("'#{kind}' block has two 'else'")),
+                        (otherwise, false) => otherwise,
+                    },
+                    false => Vec::new(),
+                };
+                Ok(Node::Condition {
+                    argument: argument.to_string(),
+                    negated: kind == "unless",
+                    then,
+                    otherwise,
+                })
+            }
+            ("if" | "unless", None) => Err(format!("'#{kind}' needs an argument name")),
+            ("system" | "user" | "assistant", None) => {
+                let role = match kind {
+                    "system" => PromptRole::System,
+                    "user" => PromptRole::User,
+                    _ => PromptRole::Assistant,
+                };
+                let (body, _) = self.parse(Some(kind))?;
+                Ok(Node::Role(role, body))
+            }
+            _ => Err(format!("unknown block '#{kind}'")),
+        }
+    }
+}
+
+fn parse_template(source: &str) -> Result<Vec<Node>, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens: tokens.into_iter(),
+    };
+    parser.parse(None).map(|(nodes, _)| nodes)
+}
+
+/// Check `nodes` use only `declared` arguments and no role blocks
+fn check_nodes(nodes: &[Node], declared: &HashSet<&str>) -> Result<(), (bool, String)> {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Argument(argument) | Node::Condition { argument, .. }
+                if !declared.contains(argument.as_str()) =>
+            {
+                return Err((false, argument.clone()));
+            }
+            Node::Argument(_) => {}
+            Node::Condition {
+                then, otherwise, ..
+            } => {
+                check_nodes(then, declared)?;
+                check_nodes(otherwise, declared)?;
+            }
+            Node::Role(..) => {
+                return Err((
+                    true,
+                    "role blocks can only appear at the top of a template".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn render(nodes: &[Node], values: &HashMap<String, String>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Argument(argument) => {
+                if let Some(value) = values.get(argument) {
+                    out.push_str(value);
+                }
+            }
+            Node::Condition {
+                argument,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let given = values.get(argument).is_some_and(|value| !value.is_empty());
+                match given != *negated {
+                    true => render(then, values, out),
+                    false => render(otherwise, values, out),
+                }
+            }
+            // Rejected when the prompt is added
+            Node::Role(..) => {}
+        }
+    }
+}
+
+/// A prompt ready to render
+#[derive(Debug)]
+struct CompiledPrompt {
+    template: PromptTemplate,
+    /// Messages, and whether their text is trimmed
+    messages: Vec<(PromptRole, Vec<Node>, bool)>,
+}
+
+impl CompiledPrompt {
+    fn compile(template: PromptTemplate) -> Result<Self, PromptTemplateError> {
+        let syntax = |message: String| PromptTemplateError::Syntax {
+            prompt: template.name.clone(),
+            message,
+        };
+        let declared: HashSet<&str> = template
+            .arguments
+            .iter()
+            .map(|argument| argument.name.as_str())
+            .collect();
+        let check = |nodes: &[Node]| {
+            check_nodes(nodes, &declared).map_err(|(syntax_error, message)| match syntax_error {
+                true => syntax(message),
+                false => PromptTemplateError::UndeclaredArgument {
+                    prompt: template.name.clone(),
+                    argument: message,
+                },
+            })
+        };
+
+        let mut messages = Vec::new();
+        for message in &template.messages {
+            let nodes = parse_template(&message.text).map_err(syntax)?;
+            check(&nodes)?;
+            messages.push((message.role.clone(), nodes, false));
+        }
+        if let Some(source) = &template.template {
+            for node in parse_template(source).map_err(syntax)? {
+                match node {
+                    Node::Role(role, body) => {
+                        check(&body)?;
+                        messages.push((role, body, true));
+                    }
+                    Node::Text(text) if text.trim().is_empty() => {}
+                    _ => {
+                        return Err(syntax(
+                            "text outside a '#system', '#user' or '#assistant' block".to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(Self { template, messages })
+    }
+
+    fn describe(&self) -> Prompt {
+        let arguments = self
+            .template
+            .arguments
+            .iter()
+            .map(|argument| PromptArgument {
+                name: argument.name.clone(),
+                description: argument.description.clone(),
+                required: Some(argument.required),
+            })
+            .collect::<Vec<_>>();
+        Prompt {
+            name: self.template.name.clone(),
+            description: self.template.description.clone(),
+            arguments: (!arguments.is_empty()).then_some(arguments),
+        }
+    }
+
+    fn render(&self, arguments: Option<Value>) -> MCPResult<GetPromptResponse> {
+        let name = &self.template.name;
+        let mut given = match arguments {
+            None | Some(Value::Null) => serde_json::Map::new(),
+            Some(Value::Object(arguments)) => arguments,
+            Some(_) => {
+                return Err(MCPError::invalid_params(format!(
+                    "Arguments of prompt '{name}' must be an object"
+                )));
+            }
+        };
+        let mut values = HashMap::new();
+        for argument in &self.template.arguments {
+            let value = match given.remove(&argument.name) {
+                None | Some(Value::Null) => argument.default.clone(),
+                Some(Value::String(value)) => Some(value),
+                Some(value) => Some(value.to_string()),
+            };
+            match value {
+                Some(value) => {
+                    values.insert(argument.name.clone(), value);
+                }
+                None if argument.required => {
+                    return Err(MCPError::invalid_params(format!(
+                        "Missing required argument '{}' for prompt '{name}'",
+                        argument.name
+                    )));
+                }
+                None => {}
+            }
+        }
+
+        let messages = self
+            .messages
+            .iter()
+            .map(|(role, nodes, trim)| {
+                let mut text = String::new();
+                render(nodes, &values, &mut text);
+                if *trim {
+                    text = text.trim().to_string();
+                }
+                PromptMessage {
+                    role: role.clone(),
+                    content: PromptContent::text(text),
+                }
+            })
+            .collect();
+        Ok(GetPromptResponse {
+            description: self.template.description.clone(),
+            messages,
+        })
+    }
+}
+
+/// Prompt templates held in memory, changeable at runtime
+///
+/// Clones share the same prompts. See the [module documentation](self).
+#[derive(Clone)]
+pub struct PromptRegistry {
+    prompts: Arc<RwLock<BTreeMap<String, Arc<CompiledPrompt>>>>,
+    changes: broadcast::Sender<()>,
+    page_size: usize,
+}
+
+impl std::fmt::Debug for PromptRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromptRegistry")
+            .field("prompts", &self.read().keys().collect::<Vec<_>>())
+            .field("page_size", &self.page_size)
+            .finish()
+    }
+}
+
+impl Default for PromptRegistry {
+    fn default() -> Self {
+        Self {
+            prompts: Arc::default(),
+            changes: broadcast::Sender::new(CHANGE_CAPACITY),
+            page_size: DEFAULT_PROMPT_PAGE_SIZE,
+        }
+    }
+}
+
+impl PromptRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prompts listed per page, at least one
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Serve `template`, replacing any prompt with the same name
+    pub fn add(&self, template: PromptTemplate) -> Result<&Self, PromptTemplateError> {
+        let prompt = CompiledPrompt::compile(template)?;
+        self.write()
+            .insert(prompt.template.name.clone(), Arc::new(prompt));
+        self.publish();
+        Ok(self)
+    }
+
+    /// Serve the prompts of a file, in the format given by its extension
+    ///
+    /// No prompt is added unless all of them are valid.
+    pub fn add_file(&self, path: impl AsRef<Path>) -> Result<&Self, PromptTemplateError> {
+        let path = path.as_ref();
+        let load_error = |message: String| PromptTemplateError::Load {
+            path: path.display().to_string(),
+            message,
+        };
+        let format = PromptFormat::from_path(path).ok_or_else(|| {
+            load_error(
+                "unsupported file extension; .json is always supported, .toml and .yaml with the prompt-files feature"
+                    .to_string(),
+            )
+        })?;
+        let source = std::fs::read_to_string(path).map_err(|e| load_error(e.to_string()))?;
+        self.add_source(&source, format).map_err(|e| match e {
+            PromptTemplateError::Load { message, .. } => load_error(message),
+            e => e,
+        })
+    }
+
+    /// Serve the prompts of a file's contents, a `prompts` list of
+    /// [`PromptTemplate`]s
+    ///
+    /// No prompt is added unless all of them are valid.
+    pub fn add_source(
+        &self,
+        source: &str,
+        format: PromptFormat,
+    ) -> Result<&Self, PromptTemplateError> {
+        let load_error = |message: String| PromptTemplateError::Load {
+            path: "source".to_string(),
+            message,
+        };
+        let file: PromptFile = match format {
+            PromptFormat::Json => {
+                serde_json::from_str(source).map_err(|e| load_error(e.to_string()))?
+            }
+            #[cfg(feature = "prompt-files")]
+            PromptFormat::Toml => toml::from_str(source).map_err(|e| load_error(e.to_string()))?,
+            #[cfg(feature = "prompt-files")]
+            PromptFormat::Yaml => {
+                serde_yaml::from_str(source).map_err(|e| load_error(e.to_string()))?
+            }
+        };
+        let prompts = file
+            .prompts
+            .into_iter()
+            .map(CompiledPrompt::compile)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut entries = self.write();
+        for prompt in prompts {
+            entries.insert(prompt.template.name.clone(), Arc::new(prompt));
+        }
+        drop(entries);
+        self.publish();
+        Ok(self)
+    }
+
+    /// Stop serving the prompt `name`; returns whether it was served
+    pub fn remove(&self, name: &str) -> bool {
+        let removed = self.write().remove(name).is_some();
+        if removed {
+            self.publish();
+        }
+        removed
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.read().contains_key(name)
+    }
+
+    /// The template served as `name`
+    pub fn template(&self, name: &str) -> Option<PromptTemplate> {
+        self.read().get(name).map(|prompt| prompt.template.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Tell `server`'s sessions when prompts are added or removed from now on
+    ///
+    /// Changes are announced with a debounced list changed notification.
+    /// The task ends once every clone of the registry is dropped; abort it
+    /// to stop earlier.
+    pub fn publish_changes(&self, server: UltraFastServer) -> JoinHandle<()> {
+        let mut changes = self.changes.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(()) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return,
+                }
+                if let Err(e) = server.notify_prompts_changed().await {
+                    warn!("Failed to notify prompt registry change: {}", e);
+                }
+            }
+        })
+    }
+
+    fn publish(&self) {
+        // No receivers until changes are published
+        let _ = self.changes.send(());
+    }
+
+    fn get(&self, name: &str) -> MCPResult<Arc<CompiledPrompt>> {
+        self.read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| MCPError::invalid_params(format!("Unknown prompt: {name}")))
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<String, Arc<CompiledPrompt>>> {
+        self.prompts.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<String, Arc<CompiledPrompt>>> {
+        self.prompts.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl PromptHandler for PromptRegistry {
+    async fn get_prompt(&self, request: GetPromptRequest) -> MCPResult<GetPromptResponse> {
+        self.get(&request.name)?.render(request.arguments)
+    }
+
+    async fn list_prompts(&self, request: ListPromptsRequest) -> MCPResult<ListPromptsResponse> {
+        let prompts = self.read();
+        let after = request.cursor.unwrap_or_default();
+        let mut page = prompts
+            .iter()
+            .filter(|(name, _)| name.as_str() > after.as_str())
+            .map(|(_, prompt)| prompt.describe());
+        let prompts: Vec<Prompt> = page.by_ref().take(self.page_size).collect();
+        let next_cursor = match page.next() {
+            Some(_) => prompts.last().map(|prompt| prompt.name.clone()),
+            None => None,
+        };
+        Ok(ListPromptsResponse {
+            prompts,
+            next_cursor,
+        })
+    }
+}
+
+/// Completes the arguments of the registry's prompts with their suggestions
+/// starting with the typed value, ignoring case
+#[async_trait]
+impl CompletionHandler for PromptRegistry {
+    async fn complete(&self, request: CompleteRequest) -> MCPResult<CompleteResponse> {
+        if request.reference.ref_type != "ref/prompt" {
+            return Err(MCPError::invalid_params(format!(
+                "Prompt registry cannot complete '{}' references",
+                request.reference.ref_type
+            )));
+        }
+        let prompt = self.get(&request.reference.name)?;
+        let argument = prompt
+            .template
+            .arguments
+            .iter()
+            .find(|argument| argument.name == request.argument.name)
+            .ok_or_else(|| {
+                MCPError::invalid_params(format!(
+                    "Prompt '{}' has no argument '{}'",
+                    request.reference.name, request.argument.name
+                ))
+            })?;
+
+        let prefix = request.argument.value.to_lowercase();
+        let mut values: Vec<CompletionValue> = argument
+            .suggestions
+            .iter()
+            .filter(|value| value.to_lowercase().starts_with(&prefix))
+            .map(CompletionValue::new)
+            .collect();
+        let total = values.len();
+        values.truncate(MAX_COMPLETION_VALUES);
+        Ok(CompleteResponse {
+            completion: Completion::with_metadata(
+                values,
+                total as u32,
+                total > MAX_COMPLETION_VALUES,
+            ),
+            metadata: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::types::completion::{CompletionArgument, CompletionReference};
+
+    fn text(message: &PromptMessage) -> &str {
+        match &message.content {
+            PromptContent::Text { text } => text,
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    fn review() -> PromptTemplate {
+        PromptTemplate::new("review")
+            .with_description("Review a change")
+            .with_argument(
+                PromptTemplateArgument::new("language")
+                    .required(true)
+                    .with_suggestions(["Rust", "Python", "ruby"]),
+            )
+            .with_argument(PromptTemplateArgument::new("focus"))
+            .with_argument(PromptTemplateArgument::new("tone").with_default("kind"))
+            .with_template(
+                "{{#system}}\n  You review {{ language }} code in a {{tone}} tone.\n{{/system}}\n\
+                 {{#user}}Review this{{#if focus}}, focusing on {{focus}}{{else}} in full{{/if}}.{{/user}}",
+            )
+    }
+
+    #[tokio::test]
+    async fn test_templates_render_arguments_conditionals_and_roles() {
+        let registry = PromptRegistry::new();
+        registry.add(review()).unwrap();
+
+        let response = registry
+            .get_prompt(GetPromptRequest {
+                name: "review".to_string(),
+                arguments: Some(serde_json::json!({"language": "Rust"})),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.messages.len(), 2);
+        assert!(matches!(response.messages[0].role, PromptRole::System));
+        assert_eq!(
+            text(&response.messages[0]),
+            "You review Rust code in a kind tone."
+        );
+        assert!(matches!(response.messages[1].role, PromptRole::User));
+        assert_eq!(text(&response.messages[1]), "Review this in full.");
+
+        let response = registry
+            .get_prompt(GetPromptRequest {
+                name: "review".to_string(),
+                arguments: Some(serde_json::json!({"language": "Rust", "focus": "safety"})),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            text(&response.messages[1]),
+            "Review this, focusing on safety."
+        );
+
+        let missing = registry
+            .get_prompt(GetPromptRequest {
+                name: "review".to_string(),
+                arguments: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(missing.to_string().contains("language"), "{missing}");
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        let registry = PromptRegistry::new();
+        let undeclared = registry
+            .add(PromptTemplate::new("p").user("Hi {{name}}"))
+            .unwrap_err();
+        assert_eq!(
+            undeclared,
+            PromptTemplateError::UndeclaredArgument {
+                prompt: "p".to_string(),
+                argument: "name".to_string(),
+            }
+        );
+
+        for source in [
+            "{{#if}}x{{/if}}",
+            "{{#if a}}x",
+            "{{#if a}}x{{/unless}}",
+            "x{{/if}}",
+            "{{else}}",
+            "{{#user}}nested{{/user}}",
+            "{{a",
+            "{{#loop a}}{{/loop}}",
+        ] {
+            let template = PromptTemplate::new("p")
+                .with_argument(PromptTemplateArgument::new("a"))
+                .user(source);
+            assert!(
+                matches!(
+                    registry.add(template),
+                    Err(PromptTemplateError::Syntax { .. })
+                ),
+                "{source}"
+            );
+        }
+        let outside = PromptTemplate::new("p").with_template("Hi {{#user}}x{{/user}}");
+        assert!(registry.add(outside).is_err());
+        assert!(registry.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prompts_load_from_json_and_are_paged() {
+        let registry = PromptRegistry::new().with_page_size(1);
+        registry
+            .add_source(
+                r#"{"prompts": [
+                    {"name": "b", "messages": [{"role": "user", "text": "B"}]},
+                    {"name": "a", "arguments": [{"name": "x", "required": true}],
+                     "messages": [{"role": "assistant", "text": "{{x}}"}]}
+                ]}"#,
+                PromptFormat::Json,
+            )
+            .unwrap();
+        let first = registry
+            .list_prompts(ListPromptsRequest { cursor: None })
+            .await
+            .unwrap();
+        assert_eq!(first.prompts[0].name, "a");
+        assert_eq!(
+            first.prompts[0].arguments.as_ref().unwrap()[0].required,
+            Some(true)
+        );
+        let second = registry
+            .list_prompts(ListPromptsRequest {
+                cursor: first.next_cursor,
+            })
+            .await
+            .unwrap();
+        assert_eq!(second.prompts[0].name, "b");
+        assert!(second.next_cursor.is_none());
+
+        // A broken prompt keeps the whole file out
+        let broken = registry.add_source(
+            r#"{"prompts": [{"name": "c", "messages": []}, {"name": "d", "messages": [{"role": "user", "text": "{{y}}"}]}]}"#,
+            PromptFormat::Json,
+        );
+        assert!(broken.is_err());
+        assert!(!registry.contains("c"));
+    }
+
+    #[cfg(feature = "prompt-files")]
+    #[test]
+    fn test_prompts_load_from_toml_and_yaml_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("prompts.toml");
+        std::fs::write(
+            &toml_path,
+            "[[prompts]]\nname = \"greet\"\ntemplate = \"{{#user}}Hello{{/user}}\"\n",
+        )
+        .unwrap();
+        let yaml_path = dir.path().join("prompts.yml");
+        std::fs::write(
+            &yaml_path,
+            "prompts:\n  - name: bye\n    messages:\n      - role: user\n        text: Bye\n",
+        )
+        .unwrap();
+
+        let registry = PromptRegistry::new();
+        registry
+            .add_file(&toml_path)
+            .unwrap()
+            .add_file(&yaml_path)
+            .unwrap();
+        assert!(registry.contains("greet") && registry.contains("bye"));
+        assert!(matches!(
+            registry.add_file(dir.path().join("prompts.txt")),
+            Err(PromptTemplateError::Load { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_arguments_complete_with_suggestions() {
+        let registry = PromptRegistry::new();
+        registry.add(review()).unwrap();
+        let complete = |argument: &str, value: &str| CompleteRequest {
+            reference: CompletionReference {
+                ref_type: "ref/prompt".to_string(),
+                name: "review".to_string(),
+            },
+            argument: CompletionArgument {
+                name: argument.to_string(),
+                value: value.to_string(),
+            },
+            context: None,
+        };
+
+        let response = registry.complete(complete("language", "r")).await.unwrap();
+        let values: Vec<_> = response
+            .completion
+            .values
+            .iter()
+            .map(|value| value.value.as_str())
+            .collect();
+        assert_eq!(values, ["Rust", "ruby"]);
+        assert!(registry.complete(complete("unknown", "")).await.is_err());
+    }
+}
@@ -0,0 +1,366 @@
+//! Rate limiting of client requests
+//!
+//! [`UltraFastServer::with_rate_limit`] limits how often requests are
+//! handled with token buckets at up to three scopes:
+//!
+//! - a global limit on requests from all sessions together
+//! - a per-session limit, applied to each session on its own
+//! - per-method limits, on calls to one method from all sessions together
+//!
+//! A request has to pass every limit that applies to it, and only counts
+//! against them when it does. A rejected request is answered with a
+//! [`RATE_LIMIT_ERROR`](ultrafast_mcp_core::error::error_codes::RATE_LIMIT_ERROR)
+//! error whose data carries `retryAfterMs` and the scope that was exceeded,
+//! and the client is sent a [`RATE_LIMIT_NOTIFICATION_METHOD`] notification
+//! carrying a [`RateLimitNotification`]. `initialize` and `ping` are exempt by
+//! default, so clients can always connect and check that the server is alive.
+//!
+//! [`UltraFastServer::with_rate_limit`]: crate::UltraFastServer::with_rate_limit
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use ultrafast_mcp_core::{
+    error::{MCPError, RateLimitError},
+    types::notifications::{RateLimitNotification, RateLimitType},
+};
+
+/// Vendor notification sent to a client whose request was rate limited
+///
+/// Its params are a [`RateLimitNotification`].
+pub const RATE_LIMIT_NOTIFICATION_METHOD: &str = "notifications/x-ultrafast/rateLimit";
+
+/// A token bucket: `requests` per `window`, with bursts of up to `burst`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window: Duration,
+    /// Requests allowed at once after a quiet period; defaults to `requests`
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, window: Duration) -> Self {
+        Self {
+            requests,
+            window,
+            burst: requests,
+        }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    pub fn per_hour(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60 * 60))
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Tokens added per second
+    fn rate(&self) -> f64 {
+        f64::from(self.requests) / self.window.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Limits enforced by [`UltraFastServer::with_rate_limit`]
+///
+/// [`UltraFastServer::with_rate_limit`]: crate::UltraFastServer::with_rate_limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub global: Option<RateLimit>,
+    pub per_session: Option<RateLimit>,
+    pub per_method: HashMap<String, RateLimit>,
+    /// Methods no limit applies to
+    pub exempt_methods: HashSet<String>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            global: None,
+            per_session: None,
+            per_method: HashMap::new(),
+            exempt_methods: ["initialize", "ping"].map(String::from).into(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_global(mut self, limit: RateLimit) -> Self {
+        self.global = Some(limit);
+        self
+    }
+
+    pub fn with_per_session(mut self, limit: RateLimit) -> Self {
+        self.per_session = Some(limit);
+        self
+    }
+
+    pub fn with_method_limit(mut self, method: impl Into<String>, limit: RateLimit) -> Self {
+        self.per_method.insert(method.into(), limit);
+        self
+    }
+
+    pub fn with_exempt_method(mut self, method: impl Into<String>) -> Self {
+        self.exempt_methods.insert(method.into());
+        self
+    }
+}
+
+/// Which limit a rejected request exceeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitScope {
+    Global,
+    Session(String),
+    Method(String),
+}
+
+/// A request rejected by the rate limiter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    pub scope: RateLimitScope,
+    pub limit: RateLimit,
+    /// How long until the request would be allowed
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    fn retry_after_ms(&self) -> u64 {
+        (self.retry_after.as_millis() as u64).max(1)
+    }
+
+    pub fn to_error(&self) -> MCPError {
+        MCPError::RateLimit(RateLimitError::TooManyRequests {
+            retry_after: self.retry_after_ms(),
+            limit: Some(self.limit.requests),
+        })
+    }
+
+    /// Data of the JSON-RPC error the request is answered with
+    pub fn error_data(&self) -> Value {
+        let mut data = json!({
+            "retryAfterMs": self.retry_after_ms(),
+            "limit": self.limit.requests,
+            "windowSeconds": self.limit.window.as_secs(),
+        });
+        match &self.scope {
+            RateLimitScope::Global => data["scope"] = json!("global"),
+            RateLimitScope::Session(session_id) => {
+                data["scope"] = json!("session");
+                data["sessionId"] = json!(session_id);
+            }
+            RateLimitScope::Method(method) => {
+                data["scope"] = json!("method");
+                data["method"] = json!(method);
+            }
+        }
+        data
+    }
+
+    pub fn to_notification(&self) -> RateLimitNotification {
+        let window_seconds = self.limit.window.as_secs();
+        let limit_type = match window_seconds {
+            3600 => RateLimitType::RequestsPerHour,
+            86400 => RateLimitType::RequestsPerDay,
+            _ => RateLimitType::RequestsPerMinute,
+        };
+        RateLimitNotification {
+            limit_type,
+            current_count: u64::from(self.limit.requests),
+            max_count: u64::from(self.limit.requests),
+            window_seconds,
+            reset_in_seconds: self.retry_after.as_secs_f64().ceil() as u64,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        }
+    }
+
+    /// Refill the bucket; how long until it holds a token, if it is empty
+    fn wait(&mut self, limit: &RateLimit, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.rate()).min(f64::from(limit.burst));
+        self.updated = now;
+        // A limit of zero requests never refills
+        (self.tokens < 1.0).then(|| {
+            Duration::try_from_secs_f64((1.0 - self.tokens) / limit.rate()).unwrap_or(limit.window)
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    global: Option<TokenBucket>,
+    sessions: HashMap<String, TokenBucket>,
+    methods: HashMap<String, TokenBucket>,
+}
+
+/// Enforces a [`RateLimitConfig`]
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Buckets> {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count a request against the limits that apply to it, unless one of
+    /// them is exceeded
+    pub(crate) fn check(&self, session_id: &str, method: &str) -> Result<(), RateLimited> {
+        self.check_at(session_id, method, Instant::now())
+    }
+
+    fn check_at(&self, session_id: &str, method: &str, now: Instant) -> Result<(), RateLimited> {
+        if self.config.exempt_methods.contains(method) {
+            return Ok(());
+        }
+        let mut buckets = self.lock();
+        let Buckets {
+            global,
+            sessions,
+            methods,
+        } = &mut *buckets;
+
+        let mut applicable = Vec::with_capacity(3);
+        if let Some(limit) = &self.config.global {
+            let bucket = global.get_or_insert_with(|| TokenBucket::full(limit, now));
+            applicable.push((RateLimitScope::Global, limit, bucket));
+        }
+        if let Some(limit) = &self.config.per_session {
+            let bucket = sessions
+                .entry(session_id.to_string())
+                .or_insert_with(|| TokenBucket::full(limit, now));
+            applicable.push((
+                RateLimitScope::Session(session_id.to_string()),
+                limit,
+                bucket,
+            ));
+        }
+        if let Some(limit) = self.config.per_method.get(method) {
+            let bucket = methods
+                .entry(method.to_string())
+                .or_insert_with(|| TokenBucket::full(limit, now));
+            applicable.push((RateLimitScope::Method(method.to_string()), limit, bucket));
+        }
+
+        let mut rejection: Option<RateLimited> = None;
+        for (scope, limit, bucket) in &mut applicable {
+            if let Some(retry_after) = bucket.wait(limit, now) {
+                if rejection
+                    .as_ref()
+                    .is_none_or(|rejection| retry_after > rejection.retry_after)
+                {
+                    rejection = Some(RateLimited {
+                        scope: scope.clone(),
+                        limit: **limit,
+                        retry_after,
+                    });
+                }
+            }
+        }
+        if let Some(rejection) = rejection {
+            return Err(rejection);
+        }
+        for (_, _, bucket) in applicable {
+            bucket.tokens -= 1.0;
+        }
+        Ok(())
+    }
+
+    /// Forget the bucket of a session that ended
+    pub(crate) fn remove_session(&self, session_id: &str) {
+        self.lock().sessions.remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_are_enforced_per_scope() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig::new()
+                .with_per_session(RateLimit::per_second(2))
+                .with_method_limit("tools/call", RateLimit::per_minute(3)),
+        );
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", "tools/list", now).is_ok());
+        assert!(limiter.check_at("a", "tools/call", now).is_ok());
+        let rejected = limiter.check_at("a", "tools/call", now).unwrap_err();
+        assert_eq!(rejected.scope, RateLimitScope::Session("a".to_string()));
+        assert_eq!(rejected.retry_after, Duration::from_millis(500));
+        assert!(limiter.check_at("a", "ping", now).is_ok());
+
+        // The rejected call did not use up a tools/call token
+        assert!(limiter.check_at("b", "tools/call", now).is_ok());
+        assert!(limiter.check_at("b", "tools/call", now).is_ok());
+        let rejected = limiter.check_at("c", "tools/call", now).unwrap_err();
+        assert_eq!(
+            rejected.scope,
+            RateLimitScope::Method("tools/call".to_string())
+        );
+        assert_eq!(rejected.retry_after, Duration::from_secs(20));
+        assert_eq!(rejected.error_data()["retryAfterMs"], 20_000);
+        assert_eq!(rejected.to_notification().reset_in_seconds, 20);
+
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.check_at("a", "tools/list", later).is_ok());
+        assert!(limiter.check_at("a", "tools/list", later).is_ok());
+        assert!(limiter.check_at("a", "tools/list", later).is_err());
+    }
+
+    #[test]
+    fn test_global_limit_is_shared_by_sessions() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig::new().with_global(RateLimit::per_minute(60).with_burst(1)),
+        );
+        let now = Instant::now();
+        assert!(limiter.check_at("a", "tools/list", now).is_ok());
+        let rejected = limiter.check_at("b", "tools/list", now).unwrap_err();
+        assert_eq!(rejected.scope, RateLimitScope::Global);
+        assert!(
+            limiter
+                .check_at("b", "tools/list", now + Duration::from_secs(1))
+                .is_ok()
+        );
+    }
+}
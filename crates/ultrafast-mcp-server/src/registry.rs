@@ -0,0 +1,141 @@
+//! Copy-on-write storage for tool, resource and prompt definitions
+//!
+//! Definitions are immutable once registered and shared as `Arc`s, so listing
+//! or looking up a definition never deep-clones schemas. Writers copy the
+//! name → definition map only while a reader still holds a snapshot of it.
+
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+
+/// Snapshot of all registered definitions, keyed by name
+pub type DefinitionSnapshot<T> = Arc<HashMap<String, Arc<T>>>;
+
+/// Registry of immutable, shared definitions
+#[derive(Debug)]
+pub(crate) struct DefinitionRegistry<T> {
+    entries: RwLock<DefinitionSnapshot<T>>,
+}
+
+impl<T> Default for DefinitionRegistry<T> {
+    fn default() -> Self {
+        Self {
+            entries: RwLock::new(Arc::new(HashMap::new())),
+        }
+    }
+}
+
+impl<T> DefinitionRegistry<T> {
+    /// Current set of definitions; later writes do not affect the snapshot
+    pub async fn snapshot(&self) -> DefinitionSnapshot<T> {
+        self.entries.read().await.clone()
+    }
+
+    pub async fn get(&self, name: &str) -> Option<Arc<T>> {
+        self.entries.read().await.get(name).cloned()
+    }
+
+    pub async fn values(&self) -> Vec<Arc<T>> {
+        self.entries.read().await.values().cloned().collect()
+    }
+
+    pub async fn contains(&self, name: &str) -> bool {
+        self.entries.read().await.contains_key(name)
+    }
+
+    pub async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    /// Insert a definition unless one with the same name exists
+    ///
+    /// Returns `false` without modifying the registry on a name clash.
+    pub async fn insert_new(&self, name: String, definition: T) -> bool {
+        let mut entries = self.entries.write().await;
+        if entries.contains_key(&name) {
+            return false;
+        }
+        Arc::make_mut(&mut entries).insert(name, Arc::new(definition));
+        true
+    }
+
+    /// [`insert_new`](Self::insert_new) through exclusive access, e.g. from
+    /// a builder before the registry is shared
+    pub fn insert_new_mut(&mut self, name: String, definition: T) -> bool {
+        let entries = self.entries.get_mut();
+        if entries.contains_key(&name) {
+            return false;
+        }
+        Arc::make_mut(entries).insert(name, Arc::new(definition));
+        true
+    }
+
+    pub async fn remove(&self, name: &str) -> bool {
+        let mut entries = self.entries.write().await;
+        if !entries.contains_key(name) {
+            return false;
+        }
+        Arc::make_mut(&mut entries).remove(name);
+        true
+    }
+
+    /// Remove every definition, returning how many were removed
+    pub async fn clear(&self) -> usize {
+        let mut entries = self.entries.write().await;
+        let count = entries.len();
+        *entries = Arc::new(HashMap::new());
+        count
+    }
+
+    /// Atomically swap in a whole new set of definitions (hot reload)
+    ///
+    /// Outstanding snapshots keep observing the previous set.
+    pub async fn replace_all(&self, definitions: HashMap<String, Arc<T>>) {
+        *self.entries.write().await = Arc::new(definitions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_is_unaffected_by_later_writes() {
+        let registry = DefinitionRegistry::default();
+        assert!(registry.insert_new("a".to_string(), 1).await);
+
+        let snapshot = registry.snapshot().await;
+        assert!(registry.insert_new("b".to_string(), 2).await);
+        registry.remove("a").await;
+
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(*snapshot["a"], 1);
+        assert_eq!(registry.len().await, 1);
+        assert!(registry.contains("b").await);
+    }
+
+    #[tokio::test]
+    async fn test_insert_new_rejects_duplicates_and_shares_definitions() {
+        let registry = DefinitionRegistry::default();
+        assert!(registry.insert_new("a".to_string(), 1).await);
+        assert!(!registry.insert_new("a".to_string(), 2).await);
+
+        let first = registry.get("a").await.unwrap();
+        let second = registry.get("a").await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, 1);
+    }
+
+    #[tokio::test]
+    async fn test_replace_all_swaps_definitions() {
+        let registry = DefinitionRegistry::default();
+        registry.insert_new("a".to_string(), 1).await;
+
+        let mut next = HashMap::new();
+        next.insert("b".to_string(), Arc::new(2));
+        registry.replace_all(next).await;
+
+        assert!(registry.get("a").await.is_none());
+        assert_eq!(registry.values().await.len(), 1);
+        assert_eq!(registry.clear().await, 1);
+    }
+}
@@ -0,0 +1,462 @@
+//! Resources declared in code and changed while the server runs
+//!
+//! [`ResourceRegistry`] is a [`ResourceHandler`] holding its resources in
+//! memory. Unlike [`StaticResources`](crate::StaticResources) it can be
+//! changed through any clone after it is handed to the server:
+//!
+//! ```rust
+//! use ultrafast_mcp_core::types::resources::{ResourceContent, ResourceTemplate};
+//! use ultrafast_mcp_server::ResourceRegistry;
+//!
+//! let registry = ResourceRegistry::new();
+//! registry
+//!     .add_json("config://app", &serde_json::json!({"debug": false}))
+//!     .add_blob("assets://logo.png", &[0x89, b'P', b'N', b'G'], "image/png")
+//!     .add_template(
+//!         ResourceTemplate::new("users://{id}/profile".to_string(), "profile".to_string()),
+//!         |uri, variables| async move {
+//!             Ok(ResourceContent::text(uri, format!("User {}", variables["id"])))
+//!         },
+//!     );
+//!
+//! // Later, from a clone kept by the application
+//! registry.add_json("config://app", &serde_json::json!({"debug": true}));
+//! ```
+//!
+//! Resources are listed in URI order, a page at a time. Once
+//! [`publish_changes`](ResourceRegistry::publish_changes) is called, replacing
+//! a resource sends `notifications/resources/updated` to the sessions
+//! subscribed to it, and adding or removing one sends a debounced list
+//! changed notification. A server advertising `listChanged` caches resource
+//! lists until such a notification, so it needs the changes published to see
+//! additions and removals.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+use tracing::warn;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult, ResourceError},
+    types::{
+        resources::{
+            ListResourceTemplatesRequest, ListResourceTemplatesResponse, ListResourcesRequest,
+            ListResourcesResponse, ReadResourceRequest, ReadResourceResponse, Resource,
+            ResourceContent, ResourceTemplate,
+        },
+        roots::{Root, RootOperation},
+    },
+};
+
+use crate::{
+    UltraFastServer,
+    adapters::{describe_content, validate_against_roots},
+    handlers::ResourceHandler,
+};
+
+/// Resources listed per `resources/list` page unless set otherwise
+pub const DEFAULT_REGISTRY_PAGE_SIZE: usize = 100;
+
+/// Changes kept for a slow publisher before it falls back to a list change
+const CHANGE_CAPACITY: usize = 256;
+
+type TemplateReader = Arc<
+    dyn Fn(
+            String,
+            HashMap<String, String>,
+        ) -> Pin<Box<dyn Future<Output = MCPResult<ResourceContent>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// A change to the registry, sent to [`ResourceRegistry::publish_changes`]
+#[derive(Debug, Clone)]
+enum Change {
+    /// The resource list changed
+    List,
+    /// The resource at a URI was replaced
+    Updated(String, Value),
+}
+
+#[derive(Default)]
+struct Entries {
+    resources: BTreeMap<String, (Resource, ResourceContent)>,
+    templates: Vec<(ResourceTemplate, TemplateReader)>,
+}
+
+/// Resources held in memory, changeable at runtime
+///
+/// Clones share the same resources. See the [module documentation](self).
+#[derive(Clone)]
+pub struct ResourceRegistry {
+    entries: Arc<RwLock<Entries>>,
+    changes: broadcast::Sender<Change>,
+    page_size: usize,
+}
+
+impl std::fmt::Debug for ResourceRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self.read();
+        f.debug_struct("ResourceRegistry")
+            .field("resources", &entries.resources.keys().collect::<Vec<_>>())
+            .field(
+                "templates",
+                &entries
+                    .templates
+                    .iter()
+                    .map(|(template, _)| &template.uri_template)
+                    .collect::<Vec<_>>(),
+            )
+            .field("page_size", &self.page_size)
+            .finish()
+    }
+}
+
+impl Default for ResourceRegistry {
+    fn default() -> Self {
+        Self {
+            entries: Arc::default(),
+            changes: broadcast::Sender::new(CHANGE_CAPACITY),
+            page_size: DEFAULT_REGISTRY_PAGE_SIZE,
+        }
+    }
+}
+
+impl ResourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resources listed per page, at least one
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Serve `text` as `text/plain` under `uri`
+    pub fn add_text(&self, uri: impl Into<String>, text: impl Into<String>) -> &Self {
+        let uri = uri.into();
+        let content = ResourceContent::text(uri.clone(), text.into());
+        self.add_content(uri, content)
+    }
+
+    /// Serve `value` as `application/json` under `uri`
+    pub fn add_json(&self, uri: impl Into<String>, value: &Value) -> &Self {
+        let uri = uri.into();
+        let content = ResourceContent::json(uri.clone(), value);
+        self.add_content(uri, content)
+    }
+
+    /// Serve `bytes` as a blob of `mime_type` under `uri`
+    pub fn add_blob(
+        &self,
+        uri: impl Into<String>,
+        bytes: &[u8],
+        mime_type: impl Into<String>,
+    ) -> &Self {
+        let uri = uri.into();
+        let content = ResourceContent::blob_from_bytes(uri.clone(), bytes, mime_type.into());
+        self.add_content(uri, content)
+    }
+
+    /// Serve `content` under `uri`, listed with a name derived from the URI
+    pub fn add_content(&self, uri: impl Into<String>, content: ResourceContent) -> &Self {
+        let resource = describe_content(uri.into(), &content);
+        self.add(resource, content)
+    }
+
+    /// Serve `content` under `resource`'s URI, listed as `resource`
+    ///
+    /// Replaces any resource with the same URI.
+    pub fn add(&self, resource: Resource, content: ResourceContent) -> &Self {
+        let uri = resource.uri.clone();
+        let notified = serde_json::to_value(&content).unwrap_or(Value::Null);
+        let replaced = self
+            .write()
+            .resources
+            .insert(uri.clone(), (resource, content))
+            .is_some();
+        self.publish(match replaced {
+            true => Change::Updated(uri, notified),
+            false => Change::List,
+        });
+        self
+    }
+
+    /// List `template` and read the URIs matching it with `read`, given the
+    /// URI and its variables
+    ///
+    /// Resources added by URI take precedence over templates; templates are
+    /// tried in the order added. See [`ResourceTemplate::match_uri`] for how
+    /// URIs are matched.
+    pub fn add_template<F, Fut>(&self, template: ResourceTemplate, read: F) -> &Self
+    where
+        F: Fn(String, HashMap<String, String>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = MCPResult<ResourceContent>> + Send + 'static,
+    {
+        let read: TemplateReader = Arc::new(move |uri, variables| Box::pin(read(uri, variables)));
+        let mut entries = self.write();
+        entries
+            .templates
+            .retain(|(existing, _)| existing.uri_template != template.uri_template);
+        entries.templates.push((template, read));
+        drop(entries);
+        self.publish(Change::List);
+        self
+    }
+
+    /// Stop serving `uri`; returns whether it was served
+    pub fn remove(&self, uri: &str) -> bool {
+        let removed = self.write().resources.remove(uri).is_some();
+        if removed {
+            self.publish(Change::List);
+        }
+        removed
+    }
+
+    /// Stop serving the template with this URI template; returns whether it
+    /// was served
+    pub fn remove_template(&self, uri_template: &str) -> bool {
+        let mut entries = self.write();
+        let before = entries.templates.len();
+        entries
+            .templates
+            .retain(|(template, _)| template.uri_template != uri_template);
+        let removed = entries.templates.len() != before;
+        drop(entries);
+        if removed {
+            self.publish(Change::List);
+        }
+        removed
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.read().resources.contains_key(uri)
+    }
+
+    /// Number of resources added by URI
+    pub fn len(&self) -> usize {
+        self.read().resources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().resources.is_empty()
+    }
+
+    /// Tell `server`'s sessions about changes made from now on
+    ///
+    /// Replaced resources are announced with
+    /// [`UltraFastServer::notify_resource_updated`], reaching the sessions
+    /// subscribed to them; added and removed ones with a debounced list
+    /// changed notification. The task ends once every clone of the registry
+    /// is dropped; abort it to stop earlier.
+    pub fn publish_changes(&self, server: UltraFastServer) -> JoinHandle<()> {
+        let mut changes = self.changes.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let result = match changes.recv().await {
+                    Ok(Change::Updated(uri, contents)) => server
+                        .notify_resource_updated(uri, contents)
+                        .await
+                        .map(|_| ()),
+                    // Changes were missed, so the client lists again
+                    Ok(Change::List) | Err(RecvError::Lagged(_)) => {
+                        server.notify_resources_changed().await
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                if let Err(e) = result {
+                    warn!("Failed to notify resource registry change: {}", e);
+                }
+            }
+        })
+    }
+
+    fn publish(&self, change: Change) {
+        // No receivers until changes are published
+        let _ = self.changes.send(change);
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Entries> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Entries> {
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl ResourceHandler for ResourceRegistry {
+    async fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        let template_read = {
+            let entries = self.read();
+            if let Some((_, content)) = entries.resources.get(&request.uri) {
+                return Ok(ReadResourceResponse {
+                    contents: vec![content.clone()],
+                });
+            }
+            entries.templates.iter().find_map(|(template, read)| {
+                template
+                    .match_uri(&request.uri)
+                    .map(|variables| read(request.uri.clone(), variables))
+            })
+        };
+        match template_read {
+            Some(read) => Ok(ReadResourceResponse {
+                contents: vec![read.await?],
+            }),
+            None => Err(MCPError::Resource(ResourceError::NotFound(request.uri))),
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        let entries = self.read();
+        let after = request.cursor.unwrap_or_default();
+        let mut page = entries
+            .resources
+            .iter()
+            .filter(|(uri, _)| uri.as_str() > after.as_str())
+            .map(|(_, (resource, _))| resource);
+        let resources: Vec<Resource> = page.by_ref().take(self.page_size).cloned().collect();
+        let next_cursor = match page.next() {
+            Some(_) => resources.last().map(|resource| resource.uri.clone()),
+            None => None,
+        };
+        Ok(ListResourcesResponse {
+            resources,
+            next_cursor,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: ListResourceTemplatesRequest,
+    ) -> MCPResult<ListResourceTemplatesResponse> {
+        Ok(ListResourceTemplatesResponse {
+            resource_templates: self
+                .read()
+                .templates
+                .iter()
+                .map(|(template, _)| template.clone())
+                .collect(),
+            next_cursor: None,
+        })
+    }
+
+    async fn validate_resource_access(
+        &self,
+        uri: &str,
+        operation: RootOperation,
+        roots: &[Root],
+    ) -> MCPResult<()> {
+        validate_against_roots(uri, operation, roots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn list_all(registry: &ResourceRegistry) -> Vec<String> {
+        let mut uris = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = registry
+                .list_resources(ListResourcesRequest { cursor })
+                .await
+                .unwrap();
+            uris.extend(page.resources.into_iter().map(|r| r.uri));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                return uris;
+            }
+        }
+    }
+
+    async fn read(registry: &ResourceRegistry, uri: &str) -> MCPResult<ResourceContent> {
+        let mut response = registry
+            .read_resource(ReadResourceRequest::new(uri))
+            .await?;
+        Ok(response.contents.remove(0))
+    }
+
+    #[tokio::test]
+    async fn test_resources_are_paged_and_changed_at_runtime() {
+        let registry = ResourceRegistry::new().with_page_size(2);
+        registry
+            .add_text("docs://b", "B")
+            .add_text("docs://a", "A")
+            .add_blob("docs://c", b"\x00\x01", "application/octet-stream");
+        assert_eq!(
+            list_all(&registry).await,
+            ["docs://a", "docs://b", "docs://c"]
+        );
+
+        let handle = registry.clone();
+        handle.add_text("docs://a", "A2");
+        assert!(handle.remove("docs://b"));
+        assert!(!handle.remove("docs://b"));
+        assert_eq!(list_all(&registry).await, ["docs://a", "docs://c"]);
+        assert!(matches!(
+            read(&registry, "docs://a").await.unwrap(),
+            ResourceContent::Text { text, .. } if text == "A2"
+        ));
+        assert_eq!(
+            read(&registry, "docs://c")
+                .await
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            b"\x00\x01"
+        );
+        assert!(read(&registry, "docs://b").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_templates_read_matching_uris() {
+        let registry = ResourceRegistry::new();
+        registry
+            .add_text("users://admin/profile", "Administrator")
+            .add_template(
+                ResourceTemplate::new("users://{id}/profile".to_string(), "profile".to_string()),
+                |uri, variables| async move {
+                    Ok(ResourceContent::text(
+                        uri,
+                        format!("User {}", variables["id"]),
+                    ))
+                },
+            );
+
+        assert!(matches!(
+            read(&registry, "users://42/profile").await.unwrap(),
+            ResourceContent::Text { text, .. } if text == "User 42"
+        ));
+        assert!(matches!(
+            read(&registry, "users://admin/profile").await.unwrap(),
+            ResourceContent::Text { text, .. } if text == "Administrator"
+        ));
+        assert!(read(&registry, "users://42/settings").await.is_err());
+
+        let templates = registry
+            .list_resource_templates(ListResourceTemplatesRequest::default())
+            .await
+            .unwrap()
+            .resource_templates;
+        assert_eq!(templates.len(), 1);
+        assert!(registry.remove_template("users://{id}/profile"));
+        assert!(read(&registry, "users://42/profile").await.is_err());
+    }
+}
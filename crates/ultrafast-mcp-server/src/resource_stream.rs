@@ -0,0 +1,430 @@
+//! Reading large resources in chunks and by byte range
+//!
+//! A blob returned by `resources/read` is base64 encoded into a single JSON
+//! message, which does not scale to artifacts of hundreds of megabytes. The
+//! [`READ_RESOURCE_STREAM_METHOD`] extension sends the bytes instead as a
+//! series of [`RESOURCE_CHUNK_METHOD`] notifications tied to the request's
+//! progress token, followed by a [`ReadResourceStreamResponse`]. Both it and
+//! `resources/read` accept a [`ByteRange`].
+//!
+//! Handlers serve the bytes without loading them whole by implementing
+//! [`ResourceHandler::open_resource_stream`]. For those that do not, the
+//! server falls back to `read_resource` and cuts the range out of the blob.
+
+use std::pin::Pin;
+
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use ultrafast_mcp_core::content_type::OCTET_STREAM;
+use ultrafast_mcp_core::error::{MCPError, MCPResult};
+use ultrafast_mcp_core::types::notifications::{RESOURCE_CHUNK_METHOD, ResourceChunk};
+use ultrafast_mcp_core::types::resources::{
+    ByteRange, READ_RESOURCE_STREAM_METHOD, ReadResourceRequest, ReadResourceResponse,
+    ReadResourceStreamRequest, ReadResourceStreamResponse, ResourceContent,
+};
+
+use crate::context::Context;
+use crate::handlers::ResourceHandler;
+
+/// Chunk size used when the client asks for none
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest chunk sent, whatever the client asks for
+pub const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The bytes of a resource, read as they are sent
+///
+/// Returned by [`ResourceHandler::open_resource_stream`], positioned at the
+/// start of the requested range. The server stops reading once the range's
+/// length is sent, so the reader may run to the end of the resource.
+pub struct ResourceByteStream {
+    reader: Pin<Box<dyn AsyncRead + Send>>,
+    mime_type: Option<String>,
+    size: Option<u64>,
+}
+
+impl std::fmt::Debug for ResourceByteStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceByteStream")
+            .field("mime_type", &self.mime_type)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResourceByteStream {
+    pub fn new(reader: impl AsyncRead + Send + 'static) -> Self {
+        Self {
+            reader: Box::pin(reader),
+            mime_type: None,
+            size: None,
+        }
+    }
+
+    /// The part of `bytes` in `range`
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>, range: Option<ByteRange>) -> Self {
+        let bytes = bytes.into();
+        let size = bytes.len() as u64;
+        let bytes = match range {
+            Some(range) => range.slice(&bytes).to_vec(),
+            None => bytes,
+        };
+        Self::new(std::io::Cursor::new(bytes)).with_size(size)
+    }
+
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// Size of the whole resource, reported to the client
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Read up to `limit` bytes into `buffer`, which is cleared first
+    async fn read_chunk(&mut self, buffer: &mut Vec<u8>, limit: u64) -> MCPResult<()> {
+        buffer.clear();
+        (&mut self.reader)
+            .take(limit)
+            .read_to_end(buffer)
+            .await
+            .map_err(|e| MCPError::internal_error(format!("Failed to read resource: {e}")))?;
+        Ok(())
+    }
+}
+
+/// Open the bytes of `uri` in `range`, falling back to reading the whole
+/// resource when the handler cannot stream it
+async fn open(
+    handler: &dyn ResourceHandler,
+    uri: &str,
+    range: Option<ByteRange>,
+) -> MCPResult<ResourceByteStream> {
+    if let Some(stream) = handler.open_resource_stream(uri, range).await? {
+        return Ok(stream);
+    }
+    let response = handler.read_resource(ReadResourceRequest::new(uri)).await?;
+    let content = response
+        .contents
+        .first()
+        .ok_or_else(|| MCPError::internal_error(format!("Resource '{uri}' has no contents")))?;
+    let stream = ResourceByteStream::from_bytes(content.to_bytes()?, range);
+    Ok(match content.mime_type() {
+        Some(mime_type) => stream.with_mime_type(mime_type),
+        None => stream,
+    })
+}
+
+/// Answer a `resources/read` request with a range
+///
+/// The handler is asked for a stream of the range first. Otherwise the
+/// whole resource is read, and the range cut out of its blob contents;
+/// text contents are returned whole.
+pub(crate) async fn read_range(
+    handler: &dyn ResourceHandler,
+    mut request: ReadResourceRequest,
+) -> MCPResult<ReadResourceResponse> {
+    let Some(range) = request.range.take() else {
+        return handler.read_resource(request).await;
+    };
+    if let Some(mut stream) = handler
+        .open_resource_stream(&request.uri, Some(range))
+        .await?
+    {
+        let mut bytes = Vec::new();
+        stream
+            .read_chunk(&mut bytes, range.length.unwrap_or(u64::MAX))
+            .await?;
+        let mime_type = stream.mime_type.unwrap_or_else(|| OCTET_STREAM.to_string());
+        return Ok(ReadResourceResponse {
+            contents: vec![ResourceContent::blob_from_bytes(
+                request.uri,
+                &bytes,
+                mime_type,
+            )],
+        });
+    }
+    let mut response = handler.read_resource(request).await?;
+    for content in &mut response.contents {
+        if let ResourceContent::Blob { uri, mime_type, .. } = &*content {
+            let bytes = content.to_bytes()?;
+            *content = ResourceContent::blob_from_bytes(
+                uri.clone(),
+                range.slice(&bytes),
+                mime_type.clone(),
+            );
+        }
+    }
+    Ok(response)
+}
+
+/// Answer a [`READ_RESOURCE_STREAM_METHOD`] request, sending the bytes to
+/// the client in chunks before returning
+pub(crate) async fn stream_resource(
+    handler: &dyn ResourceHandler,
+    request: ReadResourceStreamRequest,
+    context: &Context,
+) -> MCPResult<ReadResourceStreamResponse> {
+    let progress_token = context.progress_token().cloned().ok_or_else(|| {
+        MCPError::invalid_params(format!(
+            "{READ_RESOURCE_STREAM_METHOD} needs a progress token in _meta"
+        ))
+    })?;
+    let chunk_size = request
+        .chunk_size
+        .map_or(DEFAULT_CHUNK_SIZE, |size| size as usize)
+        .clamp(1, MAX_CHUNK_SIZE) as u64;
+    let offset = request.range.map_or(0, |range| range.offset);
+    let mut remaining = request
+        .range
+        .and_then(|range| range.length)
+        .unwrap_or(u64::MAX);
+
+    let mut stream = open(handler, &request.uri, request.range).await?;
+    let mut chunk = Vec::new();
+    let mut sent = 0;
+    while remaining > 0 {
+        if context.is_cancelled().await {
+            return Err(MCPError::request_timeout());
+        }
+        stream
+            .read_chunk(&mut chunk, chunk_size.min(remaining))
+            .await?;
+        if chunk.is_empty() {
+            break;
+        }
+        let params = ResourceChunk::new(progress_token.clone(), offset + sent, &chunk);
+        context
+            .notify_client(RESOURCE_CHUNK_METHOD, serde_json::to_value(params)?)
+            .await?;
+        sent += chunk.len() as u64;
+        remaining -= chunk.len() as u64;
+    }
+    Ok(ReadResourceStreamResponse {
+        uri: request.uri,
+        mime_type: stream.mime_type,
+        offset,
+        length: sent,
+        size: stream.size,
+    })
+}
+
+/// Parameters of a [`READ_RESOURCE_STREAM_METHOD`] request
+pub(crate) fn parse_stream_request(params: Option<Value>) -> MCPResult<ReadResourceStreamRequest> {
+    serde_json::from_value(params.unwrap_or_default()).map_err(|e| {
+        MCPError::invalid_params(format!(
+            "Invalid {READ_RESOURCE_STREAM_METHOD} request: {e}"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+    use ultrafast_mcp_core::protocol::{JsonRpcMessage, JsonRpcRequest};
+    use ultrafast_mcp_core::types::resources::{
+        ListResourceTemplatesRequest, ListResourceTemplatesResponse, ListResourcesRequest,
+        ListResourcesResponse,
+    };
+    use ultrafast_mcp_core::types::roots::{Root, RootOperation};
+
+    /// Serves `blob` only through `read_resource`, base64 encoded
+    struct BlobHandler {
+        blob: Vec<u8>,
+    }
+
+    #[async_trait]
+    impl ResourceHandler for BlobHandler {
+        async fn read_resource(
+            &self,
+            request: ReadResourceRequest,
+        ) -> MCPResult<ReadResourceResponse> {
+            Ok(ReadResourceResponse {
+                contents: vec![ResourceContent::blob_from_bytes(
+                    request.uri,
+                    &self.blob,
+                    "application/zip".to_string(),
+                )],
+            })
+        }
+
+        async fn list_resources(
+            &self,
+            _request: ListResourcesRequest,
+        ) -> MCPResult<ListResourcesResponse> {
+            Ok(ListResourcesResponse {
+                resources: Vec::new(),
+                next_cursor: None,
+            })
+        }
+
+        async fn list_resource_templates(
+            &self,
+            _request: ListResourceTemplatesRequest,
+        ) -> MCPResult<ListResourceTemplatesResponse> {
+            Ok(ListResourceTemplatesResponse {
+                resource_templates: Vec::new(),
+                next_cursor: None,
+            })
+        }
+
+        async fn validate_resource_access(
+            &self,
+            _uri: &str,
+            _operation: RootOperation,
+            _roots: &[Root],
+        ) -> MCPResult<()> {
+            Ok(())
+        }
+    }
+
+    /// Also streams `blob`, counting what it was asked for
+    struct StreamingHandler {
+        inner: BlobHandler,
+        opened: Mutex<Vec<Option<ByteRange>>>,
+    }
+
+    #[async_trait]
+    impl ResourceHandler for StreamingHandler {
+        async fn read_resource(
+            &self,
+            request: ReadResourceRequest,
+        ) -> MCPResult<ReadResourceResponse> {
+            self.inner.read_resource(request).await
+        }
+
+        async fn open_resource_stream(
+            &self,
+            _uri: &str,
+            range: Option<ByteRange>,
+        ) -> MCPResult<Option<ResourceByteStream>> {
+            self.opened.lock().await.push(range);
+            // Positioned at the offset but running to the end, as a file would
+            let offset = range.map_or(0, |range| range.offset);
+            let stream = ResourceByteStream::from_bytes(
+                self.inner.blob.clone(),
+                Some(ByteRange::from_offset(offset)),
+            );
+            Ok(Some(stream.with_mime_type("application/zip")))
+        }
+
+        async fn list_resources(
+            &self,
+            request: ListResourcesRequest,
+        ) -> MCPResult<ListResourcesResponse> {
+            self.inner.list_resources(request).await
+        }
+
+        async fn list_resource_templates(
+            &self,
+            request: ListResourceTemplatesRequest,
+        ) -> MCPResult<ListResourceTemplatesResponse> {
+            self.inner.list_resource_templates(request).await
+        }
+
+        async fn validate_resource_access(
+            &self,
+            uri: &str,
+            operation: RootOperation,
+            roots: &[Root],
+        ) -> MCPResult<()> {
+            self.inner
+                .validate_resource_access(uri, operation, roots)
+                .await
+        }
+    }
+
+    fn blob() -> Vec<u8> {
+        (0..=255).collect()
+    }
+
+    /// A context recording the chunks sent through it
+    fn recording_context(sent: Arc<Mutex<Vec<ResourceChunk>>>) -> Context {
+        Context::new()
+            .with_progress_token(serde_json::json!("read-1"))
+            .with_notification_sender(Arc::new(move |message| {
+                let sent = sent.clone();
+                Box::pin(async move {
+                    let JsonRpcMessage::Notification(JsonRpcRequest { method, params, .. }) =
+                        message
+                    else {
+                        panic!("expected a notification");
+                    };
+                    assert_eq!(method, RESOURCE_CHUNK_METHOD);
+                    sent.lock()
+                        .await
+                        .push(serde_json::from_value(params.unwrap()).unwrap());
+                    Ok(())
+                })
+            }))
+    }
+
+    #[tokio::test]
+    async fn test_stream_sends_the_range_in_chunks() {
+        let handler = BlobHandler { blob: blob() };
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let request = ReadResourceStreamRequest {
+            uri: "file:///archive.zip".to_string(),
+            range: Some(ByteRange::new(10, 100)),
+            chunk_size: Some(32),
+        };
+
+        let response = stream_resource(&handler, request, &recording_context(sent.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.offset, 10);
+        assert_eq!(response.length, 100);
+        assert_eq!(response.size, Some(256));
+        assert_eq!(response.mime_type.as_deref(), Some("application/zip"));
+
+        let sent = sent.lock().await;
+        assert_eq!(
+            sent.iter().map(|chunk| chunk.offset).collect::<Vec<_>>(),
+            [10, 42, 74, 106]
+        );
+        let bytes: Vec<u8> = sent
+            .iter()
+            .flat_map(|chunk| chunk.bytes().unwrap())
+            .collect();
+        assert_eq!(bytes, blob()[10..110]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_needs_a_progress_token() {
+        let handler = BlobHandler { blob: blob() };
+        let request = ReadResourceStreamRequest {
+            uri: "file:///archive.zip".to_string(),
+            ..Default::default()
+        };
+        let error = stream_resource(&handler, request, &Context::new())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("progress token"));
+    }
+
+    #[tokio::test]
+    async fn test_ranged_read_prefers_the_handler_stream() {
+        let request =
+            ReadResourceRequest::new("file:///archive.zip").with_range(ByteRange::new(250, 10));
+
+        let handler = StreamingHandler {
+            inner: BlobHandler { blob: blob() },
+            opened: Mutex::new(Vec::new()),
+        };
+        let response = read_range(&handler, request.clone()).await.unwrap();
+        assert_eq!(response.contents[0].to_bytes().unwrap(), blob()[250..]);
+        assert_eq!(
+            *handler.opened.lock().await,
+            [Some(ByteRange::new(250, 10))]
+        );
+
+        let response = read_range(&handler.inner, request).await.unwrap();
+        assert_eq!(response.contents[0].to_bytes().unwrap(), blob()[250..]);
+        assert_eq!(response.contents[0].mime_type(), Some("application/zip"));
+    }
+}
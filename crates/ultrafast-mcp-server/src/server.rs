@@ -2,34 +2,84 @@
 //!
 //! This module contains the main server implementation with all the core functionality.
 
-use std::{borrow::Cow, collections::HashMap, sync::Arc};
-use tokio::sync::{RwLock, broadcast};
-use tracing::{error, info, warn};
+use schemars::JsonSchema;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::value::RawValue;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+use tokio::sync::{Notify, RwLock, broadcast, mpsc};
+#[cfg(not(feature = "bare-metal"))]
+use tracing::Instrument;
+use tracing::{debug, error, info, warn};
 
+#[cfg(feature = "content-encoding")]
+use ultrafast_mcp_core::content_encoding::{self, DEFAULT_COMPRESSION_THRESHOLD};
 use ultrafast_mcp_core::{
     config::TimeoutConfig,
-    error::{MCPError, MCPResult},
+    error::{MCPError, MCPResult, ProtocolError},
+    i18n::{MessageCatalog, locale_from_params},
     protocol::{
         capabilities::ServerCapabilities,
-        jsonrpc::{JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse},
+        jsonrpc::{JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, RequestId},
+        metadata::ResponseMeta,
     },
-    schema::validation::validate_tool_schema,
+    schema::{SchemaLintConfig, SchemaLintReport, validation::validate_tool_schema},
     types::{
-        notifications::{LogLevel, LogLevelSetRequest, LogLevelSetResponse},
+        notifications::{
+            LogLevel, LogLevelSetRequest, LogLevelSetResponse, LoggingMessageNotification,
+            NOTIFICATION_ACK_METHOD,
+        },
         prompts::Prompt,
-        resources::{Resource, SubscribeResponse},
+        resources::{READ_RESOURCE_STREAM_METHOD, Resource, ResourceTemplate, SubscribeResponse},
         roots::{RootsListChangedNotification, SetRootsRequest, SetRootsResponse},
         server::ServerInfo,
         tools::Tool,
     },
-    utils::{CancellationManager, PingManager},
+    utils::{CancellationManager, PingEvent, PingManager, PingPolicy},
 };
+#[cfg(feature = "signing")]
+use ultrafast_mcp_transport::signing::{MessageSigner, SigningTransport};
 #[cfg(feature = "http")]
-use ultrafast_mcp_transport::streamable_http::server::{HttpTransportConfig, HttpTransportServer};
+use ultrafast_mcp_transport::streamable_http::{
+    HardeningConfig, IdentityResolver, InMemorySessionStore, SessionManager, SessionStore,
+    server::{HttpTransportConfig, HttpTransportServer},
+};
 use ultrafast_mcp_transport::{Transport, TransportConfig, create_transport};
+#[cfg(feature = "http")]
+use ultrafast_mcp_transport::{TransportDescription, TransportKind};
 
-use crate::context::{Context, LoggerConfig};
+use crate::completion::{MAX_COMPLETION_VALUES, ResourceTemplateCompleter};
+use crate::concurrency::{
+    ConcurrencyConfig, ConcurrencyLimit, ConcurrencyLimiter, ConcurrencyPermit,
+};
+use crate::context::{Context, LoggerConfig, ProgressRecorder};
+use crate::emulation::{AutoElicitationHandler, AutoSamplingHandler, ClientEmulationConfig};
+use crate::error_mapping::{DomainErrorMapper, ErrorMapper, ErrorMappings};
 use crate::handlers::*;
+use crate::introspection::{BuildInfo, INFO_METHOD};
+use crate::isolation::ToolIsolation;
+use crate::list_changed::{ListChangeDebouncer, ListKind};
+use crate::logging::{SessionLogLevels, is_enabled};
+#[cfg(not(feature = "bare-metal"))]
+use crate::middleware::{RequestInfo, ServerMiddleware};
+use crate::peer::ClientPeer;
+use crate::prompt_registry::PromptRegistry;
+use crate::rate_limit::{RATE_LIMIT_NOTIFICATION_METHOD, RateLimit, RateLimitConfig, RateLimiter};
+use crate::registry::DefinitionRegistry;
+use crate::resource_stream;
+use crate::session_state::SessionStates;
+use crate::shutdown::{SHUTDOWN_NOTIFICATION_METHOD, ShutdownCoordinator, ShutdownReport};
+use crate::subscriptions::{DEFAULT_SUBSCRIPTION_SESSION, SubscriptionRegistry};
+use crate::typed_tool::{TypedTool, typed_tool_definition};
+use crate::usage::{SamplingPricing, UsageReport, UsageTracker};
 
 /// MCP Server state
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,6 +111,36 @@ impl ServerState {
     }
 }
 
+/// How the server answers requests for features it has no handler for
+///
+/// Either way the error names both the method and the missing capability in
+/// its `data`, and the capability is left out of the `initialize` response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsupportedMethodPolicy {
+    /// JSON-RPC `Method not found` (-32601), as for unknown methods
+    #[default]
+    MethodNotFound,
+    /// MCP `Capability not supported` (-32001)
+    CapabilityNotSupported,
+}
+
+impl UnsupportedMethodPolicy {
+    /// The error for a `method` request belonging to the unsupported
+    /// `capability`
+    pub fn error(&self, method: &str, capability: &str) -> JsonRpcError {
+        let error = match self {
+            Self::MethodNotFound => JsonRpcError::method_not_found(method.to_string()),
+            Self::CapabilityNotSupported => {
+                JsonRpcError::capability_not_supported(capability.to_string())
+            }
+        };
+        error.with_data(serde_json::json!({
+            "method": method,
+            "capability": capability,
+        }))
+    }
+}
+
 /// Tool registration error
 #[derive(Debug, thiserror::Error)]
 pub enum ToolRegistrationError {
@@ -76,6 +156,16 @@ pub enum ToolRegistrationError {
     MissingInputSchema,
     #[error("Tool output schema is required")]
     MissingOutputSchema,
+    #[error("Tool schema fails linting:\n{0}")]
+    SchemaLint(SchemaLintReport),
+    #[error("Tools cannot be added with the builder once the server has been cloned")]
+    ServerShared,
+}
+
+impl From<ToolRegistrationError> for MCPError {
+    fn from(error: ToolRegistrationError) -> Self {
+        MCPError::invalid_request(format!("Cannot register tool: {error}"))
+    }
 }
 
 /// Server logging configuration
@@ -99,29 +189,164 @@ impl Default for ServerLoggingConfig {
     }
 }
 
+/// Notifications a client sends when its roots change, in the spec's
+/// spelling and the camel case used elsewhere in this crate
+const ROOTS_LIST_CHANGED_METHODS: [&str; 2] = [
+    "notifications/roots/list_changed",
+    "notifications/roots/listChanged",
+];
+
+/// Session the calls of the OpenAI-compatible tools endpoints are limited
+/// under, see [`UltraFastServer::with_openai_tools`]
+#[cfg(feature = "http")]
+pub const OPENAI_TOOLS_SESSION: &str = "openai-tools";
+
+/// Serialized `*/list` response shared by every session until the list changes
+#[derive(Debug, Default)]
+struct CachedListResponse {
+    generation: AtomicU64,
+    value: RwLock<Option<Arc<RawValue>>>,
+}
+
+impl CachedListResponse {
+    /// Current generation, captured before building a response so that a
+    /// concurrent invalidation prevents a stale value from being stored
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    async fn get(&self) -> Option<Arc<RawValue>> {
+        self.value.read().await.clone()
+    }
+
+    async fn store(&self, generation: u64, value: &serde_json::Value) {
+        let Ok(raw) = serde_json::value::to_raw_value(value) else {
+            return;
+        };
+        let mut cached = self.value.write().await;
+        if self.generation() == generation {
+            *cached = Some(Arc::from(raw));
+        }
+    }
+
+    async fn is_cached(&self) -> bool {
+        self.value.read().await.is_some()
+    }
+
+    async fn invalidate(&self) {
+        let mut cached = self.value.write().await;
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *cached = None;
+    }
+
+    /// `invalidate` through exclusive access, for builder methods
+    fn invalidate_mut(&mut self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *self.value.get_mut() = None;
+    }
+}
+
+/// Cached `tools/list`, `resources/list` and `prompts/list` responses
+#[derive(Debug, Default)]
+struct ListResponseCache {
+    tools: CachedListResponse,
+    resources: CachedListResponse,
+    prompts: CachedListResponse,
+}
+
+/// Byte counts of a connection's transport already added to the metrics
+#[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+#[derive(Debug, Default)]
+struct TransportTraffic {
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+/// Sizes of the server's per-connection and per-request bookkeeping
+///
+/// Each counter returns to its idle value once clients disconnect and their
+/// requests finish; one that keeps growing under steady load is a leak.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerStats {
+    /// Connected clients
+    pub live_sessions: usize,
+    /// Client requests being handled
+    pub active_requests: usize,
+    /// Server-to-client requests awaiting a response
+    pub pending_client_requests: usize,
+    /// Acknowledged notifications still awaiting their ack
+    pub queued_notifications: usize,
+    /// Cancellations not yet cleared by their finished request
+    pub cancelled_requests: usize,
+    /// Cached `*/list` responses
+    pub cached_list_responses: usize,
+    /// Resource subscription entries across sessions
+    pub subscriptions: usize,
+}
+
 /// MCP Server implementation
 #[derive(Clone)]
 pub struct UltraFastServer {
     info: ServerInfo,
     capabilities: ServerCapabilities,
     state: Arc<RwLock<ServerState>>,
-    tools: Arc<RwLock<HashMap<String, Tool>>>,
-    resources: Arc<RwLock<HashMap<String, Resource>>>,
-    prompts: Arc<RwLock<HashMap<String, Prompt>>>,
+    tools: Arc<DefinitionRegistry<Tool>>,
+    typed_tools: Arc<DefinitionRegistry<TypedTool>>,
+    resources: Arc<DefinitionRegistry<Resource>>,
+    prompts: Arc<DefinitionRegistry<Prompt>>,
+    list_cache: Arc<ListResponseCache>,
+    #[cfg(feature = "cursor-signing")]
+    cursor_codec: Option<ultrafast_mcp_core::utils::CursorCodec>,
+    #[cfg(feature = "signing")]
+    message_signer: Option<Arc<MessageSigner>>,
+    #[cfg(feature = "http")]
+    http_session_store: Option<Arc<dyn SessionStore>>,
+    #[cfg(feature = "http")]
+    http_sessions: SessionManager,
+    #[cfg(feature = "http")]
+    identity_resolver: Option<IdentityResolver>,
+    #[cfg(feature = "http")]
+    http_hardening: Option<HardeningConfig>,
+    #[cfg(feature = "http")]
+    openai_tools: bool,
     tool_handler: Option<Arc<dyn ToolHandler>>,
     resource_handler: Option<Arc<dyn ResourceHandler>>,
     prompt_handler: Option<Arc<dyn PromptHandler>>,
     sampling_handler: Option<Arc<dyn SamplingHandler>>,
     completion_handler: Option<Arc<dyn CompletionHandler>>,
+    template_completers: HashMap<String, Arc<dyn ResourceTemplateCompleter>>,
+    // Completes the arguments of its prompts, see `with_prompt_registry`
+    prompt_registry: Option<PromptRegistry>,
+    // Tools whose calls run off the connection runtime, see
+    // `with_tool_isolation`
+    tool_isolation: HashMap<String, ToolIsolation>,
     roots_handler: Option<Arc<dyn RootsHandler>>,
     elicitation_handler: Option<Arc<dyn ElicitationHandler>>,
     subscription_handler: Option<Arc<dyn ResourceSubscriptionHandler>>,
-    #[allow(dead_code)]
-    resource_subscriptions: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    subscriptions: Arc<SubscriptionRegistry>,
+    session_states: Arc<SessionStates>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    concurrency_limiter: Option<Arc<ConcurrencyLimiter>>,
+    list_changes: Arc<ListChangeDebouncer>,
+    // Size from which content items are sent compressed to clients that
+    // accept it; `None` never compresses
+    #[cfg(feature = "content-encoding")]
+    content_compression: Option<usize>,
+    // What tools are linted for at registration
+    schema_lint: Arc<SchemaLintConfig>,
     cancellation_manager: Arc<CancellationManager>,
+    // What handler contexts observe through `Context::is_cancelled`
+    request_cancellations: Arc<crate::context::CancellationManager>,
     ping_manager: Arc<PingManager>,
+    // Connected clients, for `stats`
+    peers: Arc<Mutex<Vec<Weak<ClientPeer>>>>,
+    // Requests in flight and the closing of transports, see
+    // `shutdown_graceful`
+    shutdown: Arc<ShutdownCoordinator>,
     // Enhanced logging configuration
     logging_config: Arc<RwLock<ServerLoggingConfig>>,
+    // Log levels set by sessions with `logging/setLevel`
+    log_levels: Arc<SessionLogLevels>,
 
     #[cfg(feature = "monitoring")]
     monitoring_system: Option<Arc<crate::MonitoringSystem>>,
@@ -131,6 +356,42 @@ pub struct UltraFastServer {
 
     // Timeout configuration (MCP 2025-06-18 compliance)
     timeout_config: Arc<TimeoutConfig>,
+
+    // Sampling usage accounting
+    usage_tracker: Arc<UsageTracker>,
+    usage_report_enabled: bool,
+
+    // Stand-in for clients without elicitation support, see `with_client_emulation`
+    emulated_elicitation: Option<Arc<dyn ElicitationHandler>>,
+
+    // Notification sequence numbers, see `with_notification_sequencing`
+    notification_sequencing: bool,
+    notification_sequence: Arc<AtomicU64>,
+
+    // Pinging of connected clients, see `with_ping_policy`
+    ping_policy: Option<PingPolicy>,
+    ping_events: broadcast::Sender<(Option<String>, PingEvent)>,
+
+    // Check tool calls against declared schemas, see
+    // `with_strict_schema_validation`
+    strict_schema_validation: bool,
+
+    // Errors for features without a handler
+    unsupported_method_policy: UnsupportedMethodPolicy,
+
+    // What clients see for handler errors, see `with_error_mapper`
+    error_mappings: ErrorMappings,
+
+    // Localized error messages, see `with_message_catalog`
+    message_catalog: Option<Arc<MessageCatalog>>,
+
+    // Execution metadata in results, see `with_response_meta`
+    response_meta: bool,
+
+    // Reported as uptime by `x-ultrafast/info`
+    started_at: Instant,
+    #[cfg(not(feature = "bare-metal"))]
+    middleware: Vec<Arc<dyn ServerMiddleware>>,
     // Authentication middleware (removed oauth feature)
 }
 
@@ -150,21 +411,51 @@ impl UltraFastServer {
             info,
             capabilities,
             state: Arc::new(RwLock::new(ServerState::Uninitialized)),
-            tools: Arc::new(RwLock::new(HashMap::new())),
-            resources: Arc::new(RwLock::new(HashMap::new())),
-            prompts: Arc::new(RwLock::new(HashMap::new())),
+            tools: Arc::new(DefinitionRegistry::default()),
+            typed_tools: Arc::new(DefinitionRegistry::default()),
+            resources: Arc::new(DefinitionRegistry::default()),
+            prompts: Arc::new(DefinitionRegistry::default()),
+            list_cache: Arc::new(ListResponseCache::default()),
+            #[cfg(feature = "cursor-signing")]
+            cursor_codec: None,
+            #[cfg(feature = "signing")]
+            message_signer: None,
+            #[cfg(feature = "http")]
+            http_session_store: None,
+            #[cfg(feature = "http")]
+            http_sessions: SessionManager::new(),
+            #[cfg(feature = "http")]
+            identity_resolver: None,
+            #[cfg(feature = "http")]
+            http_hardening: None,
+            #[cfg(feature = "http")]
+            openai_tools: false,
             tool_handler: None,
             resource_handler: None,
             prompt_handler: None,
             sampling_handler: None,
             completion_handler: None,
+            template_completers: HashMap::new(),
+            prompt_registry: None,
+            tool_isolation: HashMap::new(),
             roots_handler: None,
             elicitation_handler: None,
             subscription_handler: None,
-            resource_subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            subscriptions: Arc::new(SubscriptionRegistry::new()),
+            session_states: Arc::new(SessionStates::default()),
+            rate_limiter: None,
+            concurrency_limiter: None,
+            list_changes: Arc::new(ListChangeDebouncer::default()),
+            #[cfg(feature = "content-encoding")]
+            content_compression: Some(DEFAULT_COMPRESSION_THRESHOLD),
+            schema_lint: Arc::new(SchemaLintConfig::default()),
             cancellation_manager: Arc::new(CancellationManager::new()),
+            request_cancellations: Arc::new(crate::context::CancellationManager::new()),
             ping_manager: Arc::new(PingManager::default()),
+            peers: Arc::new(Mutex::new(Vec::new())),
+            shutdown: Arc::new(ShutdownCoordinator::default()),
             logging_config: Arc::new(RwLock::new(ServerLoggingConfig::default())),
+            log_levels: Arc::new(SessionLogLevels::default()),
 
             #[cfg(feature = "monitoring")]
             monitoring_system: None,
@@ -174,6 +465,31 @@ impl UltraFastServer {
 
             // Timeout configuration (MCP 2025-06-18 compliance)
             timeout_config: Arc::new(TimeoutConfig::default()),
+
+            usage_tracker: Arc::new(UsageTracker::default()),
+            usage_report_enabled: false,
+
+            emulated_elicitation: None,
+
+            notification_sequencing: false,
+            notification_sequence: Arc::new(AtomicU64::new(0)),
+
+            ping_policy: None,
+            ping_events: broadcast::channel(64).0,
+
+            strict_schema_validation: false,
+
+            unsupported_method_policy: UnsupportedMethodPolicy::default(),
+
+            error_mappings: ErrorMappings::default(),
+
+            message_catalog: None,
+
+            response_meta: false,
+
+            started_at: Instant::now(),
+            #[cfg(not(feature = "bare-metal"))]
+            middleware: Vec::new(),
         }
     }
 
@@ -257,6 +573,9 @@ impl UltraFastServer {
     }
 
     /// Set the current log level
+    ///
+    /// This is the level of sessions that did not choose their own with
+    /// `logging/setLevel`.
     pub async fn set_log_level(&self, level: LogLevel) -> MCPResult<()> {
         let mut logging_config = self.logging_config.write().await;
 
@@ -268,6 +587,7 @@ impl UltraFastServer {
 
         logging_config.current_level = level.clone();
         logging_config.default_logger_config.min_level = level.clone();
+        self.log_levels.set_default(level.clone());
 
         info!("Server log level changed to: {:?}", level);
         Ok(())
@@ -278,9 +598,60 @@ impl UltraFastServer {
         self.logging_config.read().await.current_level.clone()
     }
 
+    /// Set the log level of one session, as `logging/setLevel` does
+    async fn set_session_log_level(&self, session_id: &str, level: LogLevel) -> MCPResult<()> {
+        if !self.logging_config.read().await.allow_level_changes {
+            return Err(MCPError::invalid_request(
+                "Log level changes are not allowed on this server".to_string(),
+            ));
+        }
+        info!(
+            "Log level of session {} changed to: {:?}",
+            session_id, level
+        );
+        self.log_levels.set(session_id, level);
+        Ok(())
+    }
+
+    /// Send a log message to every connected session whose log level it
+    /// meets
+    ///
+    /// Returns how many sessions were sent the message.
+    pub fn log_to_sessions(
+        &self,
+        level: LogLevel,
+        logger: Option<&str>,
+        data: serde_json::Value,
+    ) -> usize {
+        let mut notification = LoggingMessageNotification::new(level.clone(), data);
+        if let Some(logger) = logger {
+            notification = notification.with_logger(logger.to_string());
+        }
+        let Ok(params) = serde_json::to_value(notification) else {
+            return 0;
+        };
+        self.live_peers()
+            .iter()
+            .filter(|peer| {
+                is_enabled(
+                    &level,
+                    &self.log_levels.level(subscription_session(Some(peer))),
+                )
+            })
+            .filter(|peer| {
+                peer.send_notification("notifications/message", Some(params.clone()))
+                    .is_ok()
+            })
+            .count()
+    }
+
     // ===== FLUENT BUILDER METHODS =====
 
     /// Enable monitoring with custom configuration
+    ///
+    /// Every request is then timed and counted per method and transport,
+    /// and the bytes the STDIO and HTTP transports move are added to the
+    /// transport metrics; handlers need no instrumentation of their own.
     #[cfg(feature = "monitoring")]
     pub fn with_monitoring_config(mut self, config: crate::MonitoringConfig) -> Self {
         let monitoring = crate::MonitoringSystem::new(config);
@@ -396,10 +767,51 @@ impl UltraFastServer {
         self
     }
 
-    /// Enable middleware support
-    pub fn with_middleware(self) -> Self {
-        // This would integrate with the transport middleware system
-        info!("Middleware support enabled");
+    /// Run `middleware` around the dispatch of every request
+    ///
+    /// Middleware runs in the order it is added on the way in and in reverse
+    /// order on the way out; see [`ServerMiddleware`]. Not available with the
+    /// `bare-metal` feature, which compiles the middleware chain out.
+    #[cfg(not(feature = "bare-metal"))]
+    pub fn with_middleware(mut self, middleware: Arc<dyn ServerMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Decide the JSON-RPC error clients receive when a handler fails
+    ///
+    /// Mappers are consulted in the order they are added; errors none of
+    /// them claims keep the server's default code and message. See
+    /// [`crate::error_mapping`].
+    pub fn with_error_mapper(mut self, mapper: impl ErrorMapper + 'static) -> Self {
+        self.error_mappings.push(Arc::new(mapper));
+        self
+    }
+
+    /// Send handler errors caused by an `E` as the error `map` returns
+    ///
+    /// The error is looked up in the source chain of [`MCPError::Other`],
+    /// so handlers can return `Err(anyhow::Error::new(e).into())` or add
+    /// context around it.
+    pub fn with_domain_error<E, F>(self, map: F) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+        F: Fn(&E) -> JsonRpcError + Send + Sync + 'static,
+    {
+        self.with_error_mapper(DomainErrorMapper::new(map))
+    }
+
+    /// Render user-facing error messages from `catalog`
+    ///
+    /// Authentication, validation, rate limit and not-found errors from
+    /// handlers are sent in the locale the request asks for in
+    /// `_meta["ultrafast/locale"]`, or the catalog's default locale, with
+    /// the message key and arguments in the error's `data`. Error mappers
+    /// still take precedence. Handlers can render their own messages, such
+    /// as elicitation prompts, with [`Context::localize`]. See
+    /// [`ultrafast_mcp_core::i18n`].
+    pub fn with_message_catalog(mut self, catalog: MessageCatalog) -> Self {
+        self.message_catalog = Some(Arc::new(catalog));
         self
     }
 
@@ -441,12 +853,62 @@ impl UltraFastServer {
         self
     }
 
-    /// Enable rate limiting
+    /// Hold list changed notifications back for `window` to coalesce them;
+    /// `Duration::ZERO` sends each one right away
+    pub fn with_list_changed_debounce(mut self, window: std::time::Duration) -> Self {
+        self.list_changes = Arc::new(ListChangeDebouncer::new(window));
+        self
+    }
+
+    /// Send the `text` or `blob` of content items of at least `threshold`
+    /// bytes gzip-compressed to clients that advertise support for it, see
+    /// [`content_encoding`]; `None` always sends them as they are
+    ///
+    /// Defaults to [`DEFAULT_COMPRESSION_THRESHOLD`].
+    #[cfg(feature = "content-encoding")]
+    pub fn with_content_compression(mut self, threshold: Option<usize>) -> Self {
+        self.content_compression = threshold;
+        self
+    }
+
+    /// Lint the schemas of tools registered from now on with `config`
+    ///
+    /// Tools with denied issues are rejected, and warnings are logged; see
+    /// [`schema::lint`](ultrafast_mcp_core::schema::lint). Defaults to
+    /// [`SchemaLintConfig::default`], so set this before adding tools.
+    pub fn with_schema_lint(mut self, config: SchemaLintConfig) -> Self {
+        self.schema_lint = Arc::new(config);
+        self
+    }
+
+    /// Limit each session to `requests_per_minute` requests
     pub fn with_rate_limiting(self, requests_per_minute: u32) -> Self {
-        info!(
-            "Rate limiting enabled: {} requests per minute",
-            requests_per_minute
-        );
+        self.with_rate_limit(
+            RateLimitConfig::new().with_per_session(RateLimit::per_minute(requests_per_minute)),
+        )
+    }
+
+    /// Limit how often client requests are handled, see
+    /// [`rate_limit`](crate::rate_limit)
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        info!("Rate limiting enabled: {:?}", config);
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(config)));
+        self
+    }
+
+    /// Handle at most `max_concurrent_requests` requests at once, answering
+    /// the requests beyond that with a "server busy" error
+    pub fn with_max_concurrent_requests(self, max_concurrent_requests: usize) -> Self {
+        self.with_concurrency_limit(
+            ConcurrencyConfig::new().with_global(ConcurrencyLimit::new(max_concurrent_requests)),
+        )
+    }
+
+    /// Limit how many client requests are handled at once, see
+    /// [`concurrency`](crate::concurrency)
+    pub fn with_concurrency_limit(mut self, config: ConcurrencyConfig) -> Self {
+        info!("Concurrency limiting enabled: {:?}", config);
+        self.concurrency_limiter = Some(Arc::new(ConcurrencyLimiter::new(config)));
         self
     }
 
@@ -500,8 +962,128 @@ impl UltraFastServer {
         context
     }
 
+    /// Create the context for a request received from a connected client
+    async fn create_request_context(
+        &self,
+        request: &JsonRpcRequest,
+        peer: Option<&Arc<ClientPeer>>,
+    ) -> Context {
+        let session_id = peer.and_then(|peer| peer.session_id()).map(str::to_string);
+        let mut context = match &request.id {
+            Some(id) => {
+                self.create_context_with_ids(id.to_string(), session_id)
+                    .await
+            }
+            None => self.create_context().await,
+        }
+        .with_usage_tracker(self.usage_tracker.clone())
+        .with_cancellation_manager(self.request_cancellations.clone())
+        .with_session_state(self.session_states.session(subscription_session(peer)));
+        context.set_log_level(self.log_levels.level(subscription_session(peer)));
+        let progress_token = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("progressToken"));
+        let context = match progress_token {
+            Some(token) => context.with_progress_token(token.clone()),
+            None => context,
+        };
+        let context = match &self.emulated_elicitation {
+            Some(handler) => context.with_emulated_elicitation(handler.clone()),
+            None => context,
+        };
+        let context = match &self.message_catalog {
+            Some(catalog) => context.with_message_catalog(catalog.clone()),
+            None => context,
+        };
+        let context = match locale_from_params(request.params.as_ref()) {
+            Some(locale) => context.with_locale(locale.to_string()),
+            None => context,
+        };
+        match peer {
+            Some(peer) => context.with_client_peer(peer.clone()),
+            None => context,
+        }
+    }
+
     /// Register a tool with validation
     pub async fn register_tool(&self, tool: Tool) -> Result<(), ToolRegistrationError> {
+        self.validate_tool_definition(&tool)?;
+
+        // Check for existing tool and register it
+        let tool_name = tool.name.clone();
+        if !self.tools.insert_new(tool_name.clone(), tool).await {
+            return Err(ToolRegistrationError::ToolAlreadyExists(tool_name));
+        }
+        self.list_cache.tools.invalidate().await;
+        info!("Registered tool: {}", tool_name);
+
+        Ok(())
+    }
+
+    /// Register a tool implemented by a typed closure
+    ///
+    /// The input and output schemas are generated from `I` and `O`, and
+    /// `tools/call` deserializes the arguments into `I` before calling
+    /// `handler`. The output is returned as JSON text content.
+    pub async fn register_typed_tool<I, O, F, Fut>(
+        &self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Result<(), ToolRegistrationError>
+    where
+        I: DeserializeOwned + JsonSchema + Send + 'static,
+        O: Serialize + JsonSchema + Send + 'static,
+        F: Fn(I, Context) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = MCPResult<O>> + Send + 'static,
+    {
+        let tool = typed_tool_definition::<I, O>(name.into(), description.into());
+        let tool_name = tool.name.clone();
+        self.register_tool(tool).await?;
+        self.typed_tools
+            .insert_new(tool_name, TypedTool::new(handler))
+            .await;
+        Ok(())
+    }
+
+    /// Builder form of [`register_typed_tool`](Self::register_typed_tool)
+    ///
+    /// Fails if the tool is invalid or its name is taken, or if the server
+    /// has already been cloned.
+    pub fn tool<I, O, F, Fut>(
+        mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Result<Self, ToolRegistrationError>
+    where
+        I: DeserializeOwned + JsonSchema + Send + 'static,
+        O: Serialize + JsonSchema + Send + 'static,
+        F: Fn(I, Context) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = MCPResult<O>> + Send + 'static,
+    {
+        let tool = typed_tool_definition::<I, O>(name.into(), description.into());
+        let tool_name = tool.name.clone();
+        self.validate_tool_definition(&tool)?;
+
+        let tools = Arc::get_mut(&mut self.tools).ok_or(ToolRegistrationError::ServerShared)?;
+        if !tools.insert_new_mut(tool_name.clone(), tool) {
+            return Err(ToolRegistrationError::ToolAlreadyExists(tool_name));
+        }
+        Arc::get_mut(&mut self.typed_tools)
+            .ok_or(ToolRegistrationError::ServerShared)?
+            .insert_new_mut(tool_name, TypedTool::new(handler));
+        Arc::get_mut(&mut self.list_cache)
+            .ok_or(ToolRegistrationError::ServerShared)?
+            .tools
+            .invalidate_mut();
+        Ok(self)
+    }
+
+    /// Validate a tool definition before it is added to the registry
+    fn validate_tool_definition(&self, tool: &Tool) -> Result<(), ToolRegistrationError> {
         // Validate tool name
         if tool.name.is_empty() {
             return Err(ToolRegistrationError::MissingDescription);
@@ -533,20 +1115,53 @@ impl UltraFastServer {
             return Err(ToolRegistrationError::MissingOutputSchema);
         }
 
-        // Check for existing tool
-        let mut tools = self.tools.write().await;
-        if tools.contains_key(&tool.name) {
-            return Err(ToolRegistrationError::ToolAlreadyExists(tool.name.clone()));
+        let report = self.schema_lint.lint_tool(tool);
+        if report.has_errors() {
+            return Err(ToolRegistrationError::SchemaLint(report));
+        }
+        for issue in report.warnings() {
+            warn!("{}", issue);
         }
-
-        // Register the tool
-        let tool_name = tool.name.clone();
-        tools.insert(tool_name.clone(), tool);
-        info!("Registered tool: {}", tool_name);
 
         Ok(())
     }
 
+    /// Lint the registered tools, and those of the tool handler, with the
+    /// server's [`SchemaLintConfig`]
+    ///
+    /// Registered tools with denied issues were rejected, but the tool
+    /// handler's are only checked here. Fails if the handler cannot list its
+    /// tools, or hands out a cursor it already returned.
+    pub async fn lint_tools(&self) -> MCPResult<SchemaLintReport> {
+        let mut tools: Vec<Tool> = self
+            .list_tools()
+            .await
+            .iter()
+            .map(|tool| (**tool).clone())
+            .collect();
+        if let Some(handler) = &self.tool_handler {
+            let mut cursors = HashSet::new();
+            let mut request = ultrafast_mcp_core::types::tools::ListToolsRequest::default();
+            loop {
+                let response = handler.list_tools(request).await?;
+                tools.extend(response.tools);
+                let Some(cursor) = response.next_cursor else {
+                    break;
+                };
+                if !cursors.insert(cursor.clone()) {
+                    return Err(MCPError::internal_error(format!(
+                        "Tool handler repeated the cursor {cursor}"
+                    )));
+                }
+                request = ultrafast_mcp_core::types::tools::ListToolsRequest {
+                    cursor: Some(cursor),
+                };
+            }
+        }
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(self.schema_lint.lint_tools(&tools))
+    }
+
     /// Register multiple tools
     pub async fn register_tools(&self, tools: Vec<Tool>) -> Result<(), ToolRegistrationError> {
         for tool in tools {
@@ -555,44 +1170,129 @@ impl UltraFastServer {
         Ok(())
     }
 
+    /// Replace every registered tool in one step (hot reload)
+    ///
+    /// All tools are validated first; on error the current set is left
+    /// untouched. In-flight `tools/list` responses keep the previous set.
+    pub async fn replace_tools(&self, tools: Vec<Tool>) -> Result<(), ToolRegistrationError> {
+        let mut next = HashMap::with_capacity(tools.len());
+        for tool in tools {
+            self.validate_tool_definition(&tool)?;
+            if next.contains_key(&tool.name) {
+                return Err(ToolRegistrationError::ToolAlreadyExists(tool.name));
+            }
+            next.insert(tool.name.clone(), Arc::new(tool));
+        }
+
+        let count = next.len();
+        let typed_tools = self
+            .typed_tools
+            .snapshot()
+            .await
+            .iter()
+            .filter(|(name, _)| next.contains_key(*name))
+            .map(|(name, tool)| (name.clone(), tool.clone()))
+            .collect();
+        self.tools.replace_all(next).await;
+        self.typed_tools.replace_all(typed_tools).await;
+        self.list_cache.tools.invalidate().await;
+        info!("Replaced tool set with {} tools", count);
+
+        Ok(())
+    }
+
     /// Unregister a tool by name
     pub async fn unregister_tool(&self, name: &str) -> bool {
-        let mut tools = self.tools.write().await;
-        tools.remove(name).is_some()
+        let removed = self.tools.remove(name).await;
+        self.typed_tools.remove(name).await;
+        if removed {
+            self.list_cache.tools.invalidate().await;
+        }
+        removed
     }
 
     /// Get a tool by name
-    pub async fn get_tool(&self, name: &str) -> Option<Tool> {
-        let tools = self.tools.read().await;
-        tools.get(name).cloned()
+    pub async fn get_tool(&self, name: &str) -> Option<Arc<Tool>> {
+        self.tools.get(name).await
     }
 
     /// List all registered tools
-    pub async fn list_tools(&self) -> Vec<Tool> {
-        let tools = self.tools.read().await;
-        tools.values().cloned().collect()
+    pub async fn list_tools(&self) -> Vec<Arc<Tool>> {
+        self.tools.values().await
+    }
+
+    /// Serialize a `tools/list` result from the registered tools without
+    /// cloning their definitions
+    async fn registered_tools_value(&self) -> serde_json::Result<serde_json::Value> {
+        #[derive(serde::Serialize)]
+        struct RegisteredTools<'a> {
+            tools: Vec<&'a Tool>,
+        }
+
+        let snapshot = self.tools.snapshot().await;
+        serde_json::to_value(RegisteredTools {
+            tools: snapshot.values().map(|tool| tool.as_ref()).collect(),
+        })
+    }
+
+    /// Add typed tools missing from a handler's `tools/list` response
+    async fn append_typed_tools(&self, tools: &mut Vec<Tool>) {
+        let typed_tools = self.typed_tools.snapshot().await;
+        if typed_tools.is_empty() {
+            return;
+        }
+        let listed: HashSet<String> = tools.iter().map(|tool| tool.name.clone()).collect();
+        for name in typed_tools.keys().filter(|name| !listed.contains(*name)) {
+            if let Some(tool) = self.tools.get(name).await {
+                tools.push(tool.as_ref().clone());
+            }
+        }
     }
 
     /// Check if a tool exists
     pub async fn has_tool(&self, name: &str) -> bool {
-        let tools = self.tools.read().await;
-        tools.contains_key(name)
+        self.tools.contains(name).await
     }
 
     /// Get tool count
     pub async fn tool_count(&self) -> usize {
-        let tools = self.tools.read().await;
-        tools.len()
+        self.tools.len().await
     }
 
     /// Clear all tools
     pub async fn clear_tools(&self) {
-        let mut tools = self.tools.write().await;
-        let count = tools.len();
-        tools.clear();
+        let count = self.tools.clear().await;
+        self.typed_tools.clear().await;
+        self.list_cache.tools.invalidate().await;
         info!("Cleared {} tools", count);
     }
 
+    /// Drop every cached `*/list` response
+    ///
+    /// Cached responses are otherwise only invalidated by registry changes and
+    /// the `notify_*_changed` methods; call this after mutating state that a
+    /// list handler reads without sending a list-changed notification.
+    pub async fn invalidate_list_cache(&self) {
+        self.list_cache.tools.invalidate().await;
+        self.list_cache.resources.invalidate().await;
+        self.list_cache.prompts.invalidate().await;
+    }
+
+    /// Whether a handler-provided list response may be cached
+    ///
+    /// Only servers advertising `listChanged` promise to announce changes, so
+    /// only their handler output can be reused until the next notification.
+    fn handler_list_cacheable(&self, method: &str) -> bool {
+        let caps = &self.capabilities;
+        let list_changed = match method {
+            "tools/list" => caps.tools.as_ref().and_then(|c| c.list_changed),
+            "resources/list" => caps.resources.as_ref().and_then(|c| c.list_changed),
+            "prompts/list" => caps.prompts.as_ref().and_then(|c| c.list_changed),
+            _ => None,
+        };
+        list_changed == Some(true)
+    }
+
     /// Check if a name is reserved
     fn is_reserved_name(&self, name: &str) -> bool {
         // MCP reserved method names
@@ -640,6 +1340,29 @@ impl UltraFastServer {
         Ok(())
     }
 
+    /// Definition of `tool_name` from the registry or, failing that, the
+    /// tool handler's listing
+    async fn find_tool_definition(&self, tool_name: &str) -> Option<Arc<Tool>> {
+        if let Some(tool) = self.get_tool(tool_name).await {
+            return Some(tool);
+        }
+        let handler = self.tool_handler.as_ref()?;
+        let mut request = ultrafast_mcp_core::types::tools::ListToolsRequest::default();
+        loop {
+            let response = handler.list_tools(request).await.ok()?;
+            if let Some(tool) = response
+                .tools
+                .into_iter()
+                .find(|tool| tool.name == tool_name)
+            {
+                return Some(Arc::new(tool));
+            }
+            request = ultrafast_mcp_core::types::tools::ListToolsRequest {
+                cursor: Some(response.next_cursor?),
+            };
+        }
+    }
+
     /// Execute a tool call with validation
     pub async fn execute_tool_call(
         &self,
@@ -649,6 +1372,14 @@ impl UltraFastServer {
         // Validate the tool call
         self.validate_tool_call(tool_name, &arguments).await?;
 
+        if let Some(typed_tool) = self.typed_tools.get(tool_name).await {
+            let context = self
+                .create_context()
+                .await
+                .with_tool_name(tool_name.to_string());
+            return typed_tool.call(arguments, context).await;
+        }
+
         // Get the tool handler
         let tool_handler = self
             .tool_handler
@@ -669,14 +1400,32 @@ impl UltraFastServer {
     }
 
     /// Add a tool handler to the server
-    pub fn with_tool_handler(mut self, handler: Arc<dyn ToolHandler>) -> Self {
-        self.tool_handler = Some(handler);
+    ///
+    /// Besides an `Arc` of a [`ToolHandler`], this accepts plain functions
+    /// and closures; see [`IntoToolHandler`](crate::IntoToolHandler).
+    pub fn with_tool_handler<M>(mut self, handler: impl crate::IntoToolHandler<M>) -> Self {
+        self.tool_handler = Some(handler.into_tool_handler());
+        self
+    }
+
+    /// Run the calls of the tool `name` on the blocking thread pool or a
+    /// dedicated runtime, see [`isolation`](crate::isolation)
+    pub fn with_tool_isolation(
+        mut self,
+        name: impl Into<String>,
+        isolation: ToolIsolation,
+    ) -> Self {
+        self.tool_isolation.insert(name.into(), isolation);
         self
     }
 
     /// Add a resource handler to the server
-    pub fn with_resource_handler(mut self, handler: Arc<dyn ResourceHandler>) -> Self {
-        self.resource_handler = Some(handler);
+    ///
+    /// Besides an `Arc` of a [`ResourceHandler`], this accepts
+    /// [`StaticResources`](crate::StaticResources) and read functions; see
+    /// [`IntoResourceHandler`](crate::IntoResourceHandler).
+    pub fn with_resource_handler<M>(mut self, handler: impl crate::IntoResourceHandler<M>) -> Self {
+        self.resource_handler = Some(handler.into_resource_handler());
         self
     }
 
@@ -686,1598 +1435,4267 @@ impl UltraFastServer {
         self
     }
 
+    /// Serve the prompts of `registry`
+    ///
+    /// `completion/complete` requests for the arguments of its prompts are
+    /// answered with their suggestions and no longer reach the completion
+    /// handler.
+    pub fn with_prompt_registry(mut self, registry: PromptRegistry) -> Self {
+        self.prompt_handler = Some(Arc::new(registry.clone()));
+        self.prompt_registry = Some(registry);
+        self
+    }
+
     /// Add a sampling handler to the server
     pub fn with_sampling_handler(mut self, handler: Arc<dyn SamplingHandler>) -> Self {
         self.sampling_handler = Some(handler);
         self
     }
 
+    /// Set the prices used to estimate the cost of sampling requests whose
+    /// responses don't report it
+    pub fn with_sampling_pricing(mut self, pricing: SamplingPricing) -> Self {
+        self.usage_tracker = Arc::new(UsageTracker::new(pricing));
+        self
+    }
+
+    /// Answer the `usage/report` vendor method with the sampling usage
+    /// recorded so far
+    pub fn with_usage_report(mut self) -> Self {
+        self.usage_report_enabled = true;
+        self
+    }
+
+    /// Sampling token usage and cost, by session, tool and model
+    pub fn sampling_usage(&self) -> UsageReport {
+        self.usage_tracker.report()
+    }
+
     /// Add a completion handler to the server
     pub fn with_completion_handler(mut self, handler: Arc<dyn CompletionHandler>) -> Self {
         self.completion_handler = Some(handler);
         self
     }
 
-    /// Add a roots handler to the server
-    pub fn with_roots_handler(mut self, handler: Arc<dyn RootsHandler>) -> Self {
-        self.roots_handler = Some(handler);
-        // Note: Roots is a client capability, not server capability
-        // The server responds to roots requests but doesn't advertise it
+    /// Complete the variables of the resource template `uri_template` with
+    /// `completer`
+    ///
+    /// `completion/complete` requests for this template no longer reach the
+    /// completion handler.
+    pub fn with_resource_template_completer(
+        mut self,
+        uri_template: impl Into<String>,
+        completer: Arc<dyn ResourceTemplateCompleter>,
+    ) -> Self {
+        self.template_completers
+            .insert(uri_template.into(), completer);
         self
     }
 
-    /// Add an elicitation handler to the server
-    pub fn with_elicitation_handler(mut self, handler: Arc<dyn ElicitationHandler>) -> Self {
-        self.elicitation_handler = Some(handler);
-        // Note: Elicitation is a client capability, not server capability
-        // The server responds to elicitation requests but doesn't advertise it
+    /// Sign, and optionally encrypt, the cursors of list responses
+    ///
+    /// Handlers keep producing and consuming plain cursors: `next_cursor`
+    /// values are encoded with `codec` before they leave the server, and
+    /// cursors in `tools/list`, `resources/list`, `resources/templates/list`
+    /// and `prompts/list` requests are decoded before they reach the handler.
+    /// Modified or forged cursors are rejected with an invalid params error.
+    /// To take the key from configuration, build the codec with
+    /// `CursorCodec::from_config`.
+    #[cfg(feature = "cursor-signing")]
+    pub fn with_cursor_codec(mut self, codec: ultrafast_mcp_core::utils::CursorCodec) -> Self {
+        self.cursor_codec = Some(codec);
         self
     }
 
-    /// Add a subscription handler to the server
-    pub fn with_subscription_handler(
-        mut self,
-        handler: Arc<dyn ResourceSubscriptionHandler>,
-    ) -> Self {
-        self.subscription_handler = Some(handler);
+    /// Sign every message sent to clients and verify every message received
+    ///
+    /// Applies to [`run_stdio`](Self::run_stdio),
+    /// [`run_with_transport`](Self::run_with_transport) and
+    /// [`run_http`](Self::run_http). Load the keys with
+    /// [`MessageSigner::from_config`]. A client message that fails
+    /// verification ends a STDIO or custom connection; over HTTP, requests
+    /// are answered with an access denied error and other messages dropped.
+    #[cfg(feature = "signing")]
+    pub fn with_message_signer(mut self, signer: MessageSigner) -> Self {
+        self.message_signer = Some(Arc::new(signer));
         self
     }
 
-    /// Configure logging with a custom configuration
-    pub fn with_logging_config(mut self, config: ServerLoggingConfig) -> Self {
-        let logging_config = Arc::get_mut(&mut self.logging_config)
-            .expect("Cannot modify logging config after server has been cloned");
-        *logging_config.get_mut() = config;
+    /// Keep Streamable HTTP sessions and the events sent on them in `store`
+    ///
+    /// Clients reconnecting with `Last-Event-ID` are sent the events they
+    /// missed. Defaults to an in-memory store.
+    #[cfg(feature = "http")]
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        self.http_session_store = Some(store);
         self
     }
 
-    /// Run the server with stdio transport
-    pub async fn run_stdio(&self) -> MCPResult<()> {
-        let transport = create_transport(TransportConfig::Stdio)
-            .await
-            .map_err(|e| MCPError::internal_error(format!("Transport creation failed: {e}")))?;
-        self.run_with_transport(transport).await
+    /// Record who Streamable HTTP clients authenticated as, from the headers
+    /// of their `initialize` requests, in their session metadata
+    #[cfg(feature = "http")]
+    pub fn with_identity_resolver(mut self, resolver: IdentityResolver) -> Self {
+        self.identity_resolver = Some(resolver);
+        self
     }
 
-    /// Run the server with a custom transport
-    pub async fn run_with_transport(&self, mut transport: Box<dyn Transport>) -> MCPResult<()> {
-        info!("Starting UltraFastServer with transport");
+    /// Check HTTP requests strictly before handling them
+    ///
+    /// Requests with ambiguous framing, too many or too large headers or a
+    /// malformed `Mcp-Session-Id` are rejected. Use this when the server is
+    /// reachable from the internet without a proxy in front of it.
+    #[cfg(feature = "http")]
+    pub fn with_http_hardening(mut self, config: HardeningConfig) -> Self {
+        self.http_hardening = Some(config);
+        self
+    }
 
-        // Initialize the server
-        *self.state.write().await = ServerState::Initializing;
+    /// Also serve the tools over OpenAI-compatible `/v1/tools` endpoints,
+    /// for products that call functions but do not speak MCP
+    ///
+    /// Their calls share the session [`OPENAI_TOOLS_SESSION`] for rate and
+    /// concurrency limits. See [`ultrafast_mcp_transport::streamable_http::openai`].
+    #[cfg(feature = "http")]
+    pub fn with_openai_tools(mut self) -> Self {
+        self.openai_tools = true;
+        self
+    }
 
-        // Start message handling loop
-        loop {
-            match transport.receive_message().await {
-                Ok(message) => {
-                    if let Err(e) = self.handle_message(message, &mut transport).await {
-                        error!("Error handling message: {}", e);
-                    }
-                }
-                Err(e) => {
-                    error!("Transport error: {}", e);
-                    break;
-                }
-            }
+    /// The open Streamable HTTP sessions
+    ///
+    /// Lists the clients connected over HTTP, sends notifications to a
+    /// single one with [`SessionManager::notify_session`] and disconnects
+    /// them with [`SessionManager::evict`].
+    #[cfg(feature = "http")]
+    pub fn http_sessions(&self) -> &SessionManager {
+        &self.http_sessions
+    }
+
+    /// Decode the cursor of a list request in place
+    #[cfg(feature = "cursor-signing")]
+    fn open_cursor(&self, cursor: &mut Option<String>) -> Result<(), JsonRpcError> {
+        if let (Some(codec), Some(token)) = (&self.cursor_codec, cursor.as_mut()) {
+            *token = codec
+                .decode(token)
+                .map_err(|e| JsonRpcError::invalid_params(Some(format!("Invalid cursor: {e}"))))?;
         }
+        Ok(())
+    }
 
+    #[cfg(not(feature = "cursor-signing"))]
+    fn open_cursor(&self, _cursor: &mut Option<String>) -> Result<(), JsonRpcError> {
         Ok(())
     }
 
-    /// Run the server with Streamable HTTP transport
-    #[cfg(feature = "http")]
-    pub async fn run_streamable_http(&self, host: &str, port: u16) -> MCPResult<()> {
-        info!(
-            "Starting UltraFastServer with Streamable HTTP on {}:{}",
-            host, port
-        );
+    /// Encode the next cursor of a list response in place
+    #[cfg(feature = "cursor-signing")]
+    fn seal_cursor(&self, cursor: &mut Option<String>) {
+        if let (Some(codec), Some(value)) = (&self.cursor_codec, cursor.as_mut()) {
+            *value = codec.encode(value);
+        }
+    }
 
-        let config = HttpTransportConfig {
-            host: host.to_string(),
-            port,
-            ..Default::default()
-        };
+    #[cfg(not(feature = "cursor-signing"))]
+    fn seal_cursor(&self, _cursor: &mut Option<String>) {}
 
-        self.run_http(config).await
+    /// Answer a `ref/resource` completion request with a template completer
+    async fn complete_template_variable(
+        completer: &dyn ResourceTemplateCompleter,
+        request: ultrafast_mcp_core::types::completion::CompleteRequest,
+    ) -> MCPResult<ultrafast_mcp_core::types::completion::CompleteResponse> {
+        use ultrafast_mcp_core::types::completion::{CompleteResponse, Completion};
+
+        let template = &request.reference.name;
+        let variables = ResourceTemplate::new(template.clone(), String::new()).parse_variables();
+        if !variables.contains(&request.argument.name) {
+            return Err(MCPError::invalid_params(format!(
+                "Resource template '{template}' has no variable '{}'",
+                request.argument.name
+            )));
+        }
+        let resolved = request
+            .context
+            .and_then(|context| context.arguments)
+            .unwrap_or_default();
+
+        let mut values = completer
+            .complete(&request.argument.name, &request.argument.value, &resolved)
+            .await?;
+        let total = values.len();
+        values.truncate(MAX_COMPLETION_VALUES);
+        Ok(CompleteResponse {
+            completion: Completion::with_metadata(
+                values,
+                total as u32,
+                total > MAX_COMPLETION_VALUES,
+            ),
+            metadata: None,
+        })
     }
 
-    /// Run the server with HTTP transport
-    #[cfg(feature = "http")]
-    pub async fn run_http(&self, config: HttpTransportConfig) -> MCPResult<()> {
-        info!("Starting HTTP transport server with config: {:?}", config);
+    /// Add a roots handler to the server
+    pub fn with_roots_handler(mut self, handler: Arc<dyn RootsHandler>) -> Self {
+        self.roots_handler = Some(handler);
+        // Note: Roots is a client capability, not server capability
+        // The server responds to roots requests but doesn't advertise it
+        self
+    }
 
-        let transport_server = HttpTransportServer::new(config);
-        let message_receiver = transport_server.get_message_receiver();
-        let message_sender = transport_server.get_message_sender();
-        let response_sender = transport_server.get_response_sender();
+    /// Add an elicitation handler to the server
+    pub fn with_elicitation_handler(mut self, handler: Arc<dyn ElicitationHandler>) -> Self {
+        self.elicitation_handler = Some(handler);
+        // Note: Elicitation is a client capability, not server capability
+        // The server responds to elicitation requests but doesn't advertise it
+        self
+    }
 
-        // Start message processing task
-        let server_clone = self.clone();
-        let _message_processor = tokio::spawn(async move {
-            server_clone
-                .process_http_messages(message_receiver, message_sender, response_sender)
-                .await;
-        });
+    /// Emulate client features for testing
+    ///
+    /// Installs [`AutoSamplingHandler`] and/or [`AutoElicitationHandler`] as
+    /// the sampling and elicitation handlers unless handlers were already
+    /// set. With elicitation emulated, [`Context::elicit`] is answered by the
+    /// elicitation handler when the connected client cannot elicit.
+    pub fn with_client_emulation(mut self, config: ClientEmulationConfig) -> Self {
+        if config.sampling && self.sampling_handler.is_none() {
+            self.sampling_handler = Some(Arc::new(AutoSamplingHandler::new()));
+        }
+        if config.elicitation {
+            let handler = self
+                .elicitation_handler
+                .get_or_insert_with(|| Arc::new(AutoElicitationHandler::new()));
+            self.emulated_elicitation = Some(handler.clone());
+        }
+        self
+    }
 
-        // Start the HTTP server
-        transport_server
-            .run()
-            .await
-            .map_err(|e| MCPError::internal_error(format!("HTTP server failed: {e}")))
+    /// Number notifications per session
+    ///
+    /// Every notification carries a sequence number in
+    /// `_meta["ultrafast/seq"]`, starting at 1 for each session, so clients
+    /// can detect notifications lost or reordered by the transport.
+    pub fn with_notification_sequencing(mut self) -> Self {
+        self.notification_sequencing = true;
+        self
     }
 
-    /// Run the server with custom Streamable HTTP transport configuration
-    /// This provides clearer naming for advanced Streamable HTTP configuration
-    #[cfg(feature = "http")]
-    pub async fn run_streamable_http_with_config(
-        &self,
-        config: HttpTransportConfig,
-    ) -> MCPResult<()> {
-        self.run_http(config).await
+    /// Ping connected clients as `policy` says
+    ///
+    /// Clients are pinged once initialized. After `policy.max_missed`
+    /// unanswered pings a client is reported unhealthy through
+    /// [`subscribe_ping_events`](Self::subscribe_ping_events) and
+    /// [`ClientPeer::ping_stats`]; with `policy.disconnect` its connection
+    /// is closed, or its HTTP session evicted. See
+    /// [`ultrafast_mcp_core::utils::ping_policy`].
+    pub fn with_ping_policy(mut self, policy: PingPolicy) -> Self {
+        self.ping_policy = Some(policy);
+        self
     }
 
-    /// Process HTTP messages from the transport layer
-    #[allow(dead_code)]
-    async fn process_http_messages(
-        &self,
-        mut message_receiver: broadcast::Receiver<(String, JsonRpcMessage)>,
-        _message_sender: broadcast::Sender<(String, JsonRpcMessage)>,
-        response_sender: broadcast::Sender<(String, JsonRpcMessage)>,
-    ) {
-        info!("HTTP message processor started");
+    /// Changes in how clients answer the server's pings, with the session
+    /// of the client where it has one
+    pub fn subscribe_ping_events(&self) -> broadcast::Receiver<(Option<String>, PingEvent)> {
+        self.ping_events.subscribe()
+    }
 
-        while let Ok((session_id, message)) = message_receiver.recv().await {
-            let session_id_clone = session_id.clone();
-            match message {
-                JsonRpcMessage::Request(request) => {
-                    info!(
-                        "Processing HTTP request: {} (session: {})",
-                        request.method, session_id
-                    );
+    /// Validate tool calls against the tools' declared schemas
+    ///
+    /// When enabled, `tools/call` arguments that do not match the tool's
+    /// `input_schema` are rejected with `-32602` before the handler runs, and
+    /// successful results that do not match its `output_schema` are replaced
+    /// with an internal error. Tools served by a [`ToolHandler`] are looked up
+    /// through its `list_tools`; calls to tools it does not list are passed
+    /// through unchecked.
+    pub fn with_strict_schema_validation(mut self, enabled: bool) -> Self {
+        self.strict_schema_validation = enabled;
+        self
+    }
 
-                    let response = self.handle_request(request).await;
-                    let response_message = JsonRpcMessage::Response(response);
+    /// Report how each request was executed in its result's `_meta`
+    ///
+    /// Successful results carry a [`ResponseMeta`] under
+    /// [`RESPONSE_META_KEY`](ultrafast_mcp_core::protocol::metadata::RESPONSE_META_KEY)
+    /// with the handling time, the size of the result and whether it was
+    /// served from the list cache. Clients read it back through their
+    /// `*_with_meta` methods.
+    pub fn with_response_meta(mut self) -> Self {
+        self.response_meta = true;
+        self
+    }
 
-                    info!(
-                        "Sending response for session {}: {:?}",
-                        session_id, response_message
-                    );
+    /// Choose the error for requests to features without a handler
+    ///
+    /// Defaults to [`UnsupportedMethodPolicy::MethodNotFound`].
+    pub fn with_unsupported_method_policy(mut self, policy: UnsupportedMethodPolicy) -> Self {
+        self.unsupported_method_policy = policy;
+        self
+    }
 
-                    // Send the response back through the response sender
-                    if let Err(e) = response_sender.send((session_id_clone, response_message)) {
-                        error!("Failed to send response for session {}: {}", session_id, e);
-                    } else {
-                        info!("Successfully sent response for session {}", session_id);
-                    }
-                }
-                JsonRpcMessage::Notification(notification) => {
-                    info!(
-                        "Processing HTTP notification: {} (session: {})",
-                        notification.method, session_id
-                    );
+    /// The error for a `method` request to the handler-less `capability`
+    fn unsupported(&self, method: &str, capability: &str) -> JsonRpcError {
+        self.unsupported_method_policy.error(method, capability)
+    }
 
-                    if let Err(e) = self.handle_notification(notification).await {
-                        error!(
-                            "Failed to handle notification for session {}: {}",
-                            session_id, e
-                        );
-                    }
-                    // Notifications don't have responses, so no need to send anything back
-                }
-                JsonRpcMessage::Response(_) => {
-                    warn!(
-                        "Received unexpected response message for session: {}",
-                        session_id
-                    );
-                }
-            }
-        }
+    /// The capabilities announced to clients
+    ///
+    /// Starts from the configured capabilities, adds those of configured
+    /// handlers, and drops those no handler can serve, so a client never
+    /// sees a capability whose methods would be rejected as unsupported.
+    pub fn advertised_capabilities(&self) -> ServerCapabilities {
+        use ultrafast_mcp_core::protocol::capabilities::{
+            CompletionCapability, PromptsCapability, ResourcesCapability, ToolsCapability,
+        };
 
-        info!("HTTP message processor stopped");
+        let mut capabilities = self.capabilities.clone();
+        if self.tool_handler.is_some() && capabilities.tools.is_none() {
+            capabilities.tools = Some(ToolsCapability { list_changed: None });
+        }
+        if self.resource_handler.is_some() {
+            capabilities
+                .resources
+                .get_or_insert_with(ResourcesCapability::default);
+        } else if !self.supports_resource_subscriptions() {
+            capabilities.resources = None;
+        }
+        if self.prompt_handler.is_some() {
+            capabilities
+                .prompts
+                .get_or_insert(PromptsCapability { list_changed: None });
+        } else {
+            capabilities.prompts = None;
+        }
+        if self.completion_handler.is_some()
+            || !self.template_completers.is_empty()
+            || self.prompt_registry.is_some()
+        {
+            capabilities
+                .completion
+                .get_or_insert(CompletionCapability {});
+        } else {
+            capabilities.completion = None;
+        }
+        capabilities
     }
 
-    /// Get server info
-    pub fn info(&self) -> &ServerInfo {
-        &self.info
+    /// Add a subscription handler to the server
+    pub fn with_subscription_handler(
+        mut self,
+        handler: Arc<dyn ResourceSubscriptionHandler>,
+    ) -> Self {
+        self.subscription_handler = Some(handler);
+        self
     }
 
-    /// Get cancellation manager
-    pub fn cancellation_manager(&self) -> Arc<CancellationManager> {
-        self.cancellation_manager.clone()
+    /// Sessions subscribed to `uri`, directly or through a prefix or glob
+    /// pattern
+    ///
+    /// Connections without a session id are reported as
+    /// [`DEFAULT_SUBSCRIPTION_SESSION`].
+    pub fn resource_subscribers(&self, uri: &str) -> HashSet<String> {
+        self.subscriptions.subscribers(uri)
     }
 
-    /// Get ping manager
-    pub fn ping_manager(&self) -> Arc<PingManager> {
-        self.ping_manager.clone()
+    /// The registry of resource subscriptions made by clients
+    pub fn subscriptions(&self) -> &Arc<SubscriptionRegistry> {
+        &self.subscriptions
     }
 
-    /// Start periodic ping monitoring (optional, for connection health)
-    /// This method should be called after the server is running with a transport
-    pub async fn start_ping_monitoring(&self, ping_interval: std::time::Duration) -> MCPResult<()> {
-        info!(
-            "Starting periodic ping monitoring with interval: {:?}",
-            ping_interval
-        );
+    /// JSON-RPC error for a failed `tools/call`
+    fn tool_call_error(&self, error: &MCPError, locale: Option<&str>) -> JsonRpcError {
+        let code = match error {
+            MCPError::Protocol(ProtocolError::InvalidParams(_))
+            | MCPError::Protocol(ProtocolError::NotFound(_)) => -32602,
+            _ => -32603,
+        };
+        self.handler_error(
+            error,
+            JsonRpcError::new(code, format!("Tool call failed: {error}")),
+            locale,
+        )
+    }
 
-        // Note: This is a placeholder for future implementation
-        // The actual ping monitoring would need to be integrated with the transport layer
-        // For now, we log that ping monitoring is enabled
-        info!("Ping monitoring enabled (interval: {:?})", ping_interval);
+    /// JSON-RPC error for a failed handler, `default` unless an error mapper
+    /// claims the error
+    ///
+    /// With a message catalog, errors it has a message for are sent in
+    /// `locale` instead, keeping the default's code.
+    fn handler_error(
+        &self,
+        error: &MCPError,
+        default: JsonRpcError,
+        locale: Option<&str>,
+    ) -> JsonRpcError {
+        let default = match (&self.message_catalog, error.localized()) {
+            (Some(catalog), Some(message)) => catalog.error(default.code, locale, &message),
+            _ => default,
+        };
+        self.error_mappings.map(error, default)
+    }
 
-        // The PingManager is already configured with default intervals
-        // Future implementation would integrate with the transport layer
-        // to send periodic pings to clients
+    /// Whether clients may subscribe without a subscription handler
+    fn supports_resource_subscriptions(&self) -> bool {
+        self.capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
 
-        Ok(())
+    /// Configure logging with a custom configuration
+    pub fn with_logging_config(mut self, config: ServerLoggingConfig) -> Self {
+        let logging_config = Arc::get_mut(&mut self.logging_config)
+            .expect("Cannot modify logging config after server has been cloned");
+        self.log_levels.set_default(config.current_level.clone());
+        *logging_config.get_mut() = config;
+        self
     }
 
-    /// Stop ping monitoring
-    pub async fn stop_ping_monitoring(&self) -> MCPResult<()> {
-        info!("Stopping periodic ping monitoring");
-        // The ping monitoring task will naturally stop when the transport is closed
-        Ok(())
+    /// Run the server with stdio transport
+    pub async fn run_stdio(&self) -> MCPResult<()> {
+        let transport = create_transport(TransportConfig::Stdio)
+            .await
+            .map_err(|e| MCPError::internal_error(format!("Transport creation failed: {e}")))?;
+        self.run_with_transport(transport).await
     }
 
-    /// Handle MCP initialize request
-    async fn handle_initialize(
+    /// Run the server with stdio transport until `signal` completes
+    ///
+    /// On the signal the server shuts down as
+    /// [`shutdown_graceful`](Self::shutdown_graceful) describes, giving
+    /// running requests the configured `shutdown` timeout, and flushes its
+    /// monitoring metrics. Pass [`shutdown_signal`](crate::shutdown_signal)
+    /// to stop on SIGINT or SIGTERM.
+    pub async fn run_stdio_with_shutdown(
         &self,
-        request: ultrafast_mcp_core::protocol::InitializeRequest,
-    ) -> Result<ultrafast_mcp_core::protocol::InitializeResponse, MCPError> {
-        info!(
-            "Handling initialize request from client: {} (version: {})",
-            request.client_info.name, request.protocol_version
-        );
+        signal: impl Future<Output = ()> + Send,
+    ) -> MCPResult<()> {
+        self.run_until_signal(self.run_stdio(), signal).await
+    }
 
-        // Negotiate protocol version
-        let negotiated_version = match ultrafast_mcp_core::protocol::version::negotiate_version(
-            &request.protocol_version,
-        ) {
-            Ok(version) => {
-                info!(
-                    "Protocol version negotiated: {} -> {}",
-                    request.protocol_version, version
+    /// Drive `run` until it ends or `signal` completes, then shut down
+    async fn run_until_signal(
+        &self,
+        run: impl Future<Output = MCPResult<()>>,
+        signal: impl Future<Output = ()>,
+    ) -> MCPResult<()> {
+        tokio::pin!(run);
+        let result = tokio::select! {
+            result = &mut run => result,
+            _ = signal => {
+                info!("Shutdown signal received");
+                // The run loop keeps going to send the last responses
+                let (shutdown, result) = tokio::join!(
+                    self.shutdown_graceful(self.get_operation_timeout("shutdown")),
+                    &mut run
                 );
-                version
-            }
-            Err(e) => {
-                error!("Protocol version negotiation failed: {}", e);
-                return Err(MCPError::invalid_request(format!(
-                    "Protocol version negotiation failed: {}. Supported versions: {:?}",
-                    e,
-                    ultrafast_mcp_core::protocol::version::SUPPORTED_VERSIONS
-                )));
+                shutdown?;
+                result
             }
         };
 
-        // Validate the initialize request
-        if let Err(e) = request.validate_protocol_version() {
-            warn!("Initialize request validation warning: {}", e);
-            // Continue with warning but don't fail
-        }
-
-        // Validate compatibility
-        if let Err(e) = ultrafast_mcp_core::protocol::capabilities::validate_compatibility(
-            &request.capabilities,
-            &self.capabilities,
-        ) {
-            error!("Capability validation failed: {}", e);
-            return Err(MCPError::Protocol(
-                ultrafast_mcp_core::error::ProtocolError::CapabilityNotSupported(e),
-            ));
+        #[cfg(feature = "monitoring")]
+        if let Some(monitoring) = &self.monitoring_system {
+            if let Err(e) = monitoring.shutdown().await {
+                warn!("Failed to flush metrics: {}", e);
+            }
         }
+        result
+    }
 
-        info!("Capabilities validated successfully");
-
-        // Update server state to Initialized (not Operating yet)
-        // This follows MCP 2025-06-18 specification: server should wait for initialized notification
-        {
-            let mut state = self.state.write().await;
-            *state = ServerState::Initialized;
+    /// Run the server with a custom transport
+    ///
+    /// Requests are handled concurrently so that a handler waiting on the
+    /// client (e.g. [`Context::elicit`]) does not block the connection; all
+    /// outgoing messages are funnelled through a single writer.
+    pub async fn run_with_transport(&self, mut transport: Box<dyn Transport>) -> MCPResult<()> {
+        #[cfg(feature = "signing")]
+        if let Some(signer) = &self.message_signer {
+            transport = Box::new(SigningTransport::new(transport, signer.clone()));
         }
-
+        let description = transport.describe();
         info!(
-            "Server initialized with protocol version: {} (waiting for initialized notification)",
-            negotiated_version
+            "Starting UltraFastServer with {} transport",
+            description.kind
         );
 
-        Ok(ultrafast_mcp_core::protocol::InitializeResponse {
-            protocol_version: negotiated_version,
-            capabilities: self.capabilities.clone(),
-            server_info: self.info.clone(),
-            instructions: None,
-        })
-    }
+        // Initialize the server
+        *self.state.write().await = ServerState::Initializing;
 
-    /// Handle MCP initialized notification
-    async fn handle_initialized(
-        &self,
-        _notification: ultrafast_mcp_core::protocol::InitializedNotification,
-    ) -> MCPResult<()> {
-        info!("Received initialized notification from client");
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            self.create_client_peer(outgoing_sender)
+                .with_transport(description),
+        );
+        self.track_peer(&peer);
+        let unanswered_pings = Arc::new(Notify::new());
+        self.spawn_ping_monitor(&peer, {
+            let unanswered_pings = unanswered_pings.clone();
+            move || unanswered_pings.notify_one()
+        });
+        // Warns if this future is dropped before the connection is closed
+        let close_tracker =
+            ultrafast_mcp_core::utils::CloseTracker::new("UltraFastServer connection");
+        close_tracker.opened();
+        let closed = self.shutdown.closed();
+        tokio::pin!(closed);
+        #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+        let mut traffic = TransportTraffic::default();
 
-        // Ensure server state is operating (it should already be from initialize)
-        {
-            let mut state = self.state.write().await;
-            *state = ServerState::Operating;
+        // Start message handling loop
+        loop {
+            tokio::select! {
+                biased;
+                Some(message) = outgoing.recv() => {
+                    if let Err(e) = transport.send_message(message).await {
+                        error!("Failed to send message: {}", e);
+                        break;
+                    }
+                    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+                    self.record_transport_traffic(transport.as_ref(), &mut traffic).await;
+                }
+                _ = &mut closed => {
+                    // Responses to aborted requests are queued before closing
+                    while let Ok(message) = outgoing.try_recv() {
+                        if let Err(e) = transport.send_message(message).await {
+                            error!("Failed to send message: {}", e);
+                            break;
+                        }
+                    }
+                    info!("Server shut down, closing {} transport", peer.transport().kind);
+                    break;
+                }
+                _ = unanswered_pings.notified() => {
+                    warn!("Client stopped answering pings, closing {} transport", peer.transport().kind);
+                    break;
+                }
+                received = transport.receive_message() => match received {
+                    Ok(message) => {
+                        #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+                        self.record_transport_traffic(transport.as_ref(), &mut traffic).await;
+                        self.dispatch_message(message, &peer).await
+                    }
+                    Err(e) => {
+                        #[cfg(feature = "monitoring")]
+                        self.record_transport_error(&e, &peer).await;
+                        error!("Transport error: {}", e);
+                        break;
+                    }
+                },
+            }
         }
 
-        info!("Server confirmed operating state via initialized notification");
+        if let Err(e) = transport.close().await {
+            warn!("Failed to close transport: {}", e);
+        }
+        close_tracker.closed();
+        self.subscriptions
+            .remove_session(subscription_session(Some(&peer)));
+        self.session_states
+            .remove(subscription_session(Some(&peer)));
+        self.log_levels
+            .remove_session(subscription_session(Some(&peer)));
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.remove_session(subscription_session(Some(&peer)));
+        }
+        if let Some(limiter) = &self.concurrency_limiter {
+            limiter.remove_session(subscription_session(Some(&peer)));
+        }
         Ok(())
     }
 
-    /// Handle MCP shutdown request
-    async fn handle_shutdown(
+    /// Record the error a connection ended with, and a message the transport
+    /// could not decode as a parse reject
+    #[cfg(feature = "monitoring")]
+    async fn record_transport_error(
         &self,
-        request: ultrafast_mcp_core::protocol::ShutdownRequest,
-    ) -> MCPResult<()> {
-        info!("Handling shutdown request: {:?}", request.reason);
+        error: &ultrafast_mcp_transport::TransportError,
+        peer: &ClientPeer,
+    ) {
+        use ultrafast_mcp_transport::TransportError;
 
-        // Update server state
-        {
-            let mut state = self.state.write().await;
-            *state = ServerState::ShuttingDown;
+        let Some(monitoring) = &self.monitoring_system else {
+            return;
+        };
+        match error {
+            // The client hung up
+            TransportError::ConnectionClosed => {}
+            TransportError::SerializationError { .. } => {
+                monitoring
+                    .metrics()
+                    .record_reject(
+                        crate::RejectReason::ParseError,
+                        None,
+                        peer.transport().session_id.as_deref(),
+                    )
+                    .await
+            }
+            _ => {
+                monitoring
+                    .metrics()
+                    .record_transport_error(&format!("{:?}", error.category()))
+                    .await
+            }
         }
+    }
 
-        // Perform cleanup
-        self.perform_shutdown_cleanup().await;
-
-        // Update state to shutdown
-        {
-            let mut state = self.state.write().await;
-            *state = ServerState::Shutdown;
+    /// Add the bytes the transport moved since the last call to the
+    /// transport metrics, for transports that count them
+    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+    async fn record_transport_traffic(
+        &self,
+        transport: &dyn Transport,
+        traffic: &mut TransportTraffic,
+    ) {
+        let Some(monitoring) = &self.monitoring_system else {
+            return;
+        };
+        let health = transport.get_health();
+        let sent = health.bytes_sent.saturating_sub(traffic.bytes_sent);
+        let received = health.bytes_received.saturating_sub(traffic.bytes_received);
+        (traffic.bytes_sent, traffic.bytes_received) = (health.bytes_sent, health.bytes_received);
+        if sent > 0 {
+            monitoring.metrics().record_transport_send(sent).await;
+        }
+        if received > 0 {
+            monitoring
+                .metrics()
+                .record_transport_receive(received)
+                .await;
         }
-
-        info!("Server shutdown completed");
-        Ok(())
     }
 
-    /// Perform shutdown cleanup
-    async fn perform_shutdown_cleanup(&self) {
-        info!("Performing shutdown cleanup");
+    /// Count a connected client in `stats` for as long as it is alive
+    fn track_peer(&self, peer: &Arc<ClientPeer>) {
+        let mut peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        peers.retain(|peer| peer.strong_count() > 0);
+        peers.push(Arc::downgrade(peer));
+    }
 
-        // Clear all tools
-        self.clear_tools().await;
+    /// Clients currently connected
+    fn live_peers(&self) -> Vec<Arc<ClientPeer>> {
+        let mut peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        peers.retain(|peer| peer.strong_count() > 0);
+        peers.iter().filter_map(Weak::upgrade).collect()
+    }
 
-        // Clear all resources
-        {
-            let mut resources = self.resources.write().await;
-            resources.clear();
+    /// Current sizes of the server's connection and request bookkeeping
+    pub async fn stats(&self) -> ServerStats {
+        let peers = self.live_peers();
+        let mut cached_list_responses = 0;
+        for cache in [
+            &self.list_cache.tools,
+            &self.list_cache.resources,
+            &self.list_cache.prompts,
+        ] {
+            if cache.is_cached().await {
+                cached_list_responses += 1;
+            }
         }
-
-        // Clear all prompts
-        {
-            let mut prompts = self.prompts.write().await;
-            prompts.clear();
+        ServerStats {
+            live_sessions: peers.len(),
+            active_requests: self.cancellation_manager.active_requests().await.len(),
+            pending_client_requests: peers.iter().map(|peer| peer.pending_requests()).sum(),
+            queued_notifications: peers
+                .iter()
+                .map(|peer| peer.unacknowledged_notifications())
+                .sum(),
+            cancelled_requests: self.request_cancellations.cancelled_count().await,
+            cached_list_responses,
+            subscriptions: self.subscriptions.len(),
         }
+    }
 
-        // Clear resource subscriptions
-        {
-            let mut subscriptions = self.resource_subscriptions.write().await;
-            subscriptions.clear();
+    fn create_client_peer(&self, outgoing: mpsc::UnboundedSender<JsonRpcMessage>) -> ClientPeer {
+        let mut peer = ClientPeer::new(outgoing, self.get_operation_timeout("elicitation/create"));
+        if self.notification_sequencing {
+            peer = peer.with_notification_sequencing();
         }
-
-        info!("Shutdown cleanup completed");
+        if let Some(policy) = &self.ping_policy {
+            peer = peer.with_ping_policy(policy.clone());
+        }
+        peer
     }
 
-    /// Get current server state
-    pub async fn get_state(&self) -> ServerState {
-        self.state.read().await.clone()
+    /// Ping `peer` as its ping policy says, for as long as it is connected
+    ///
+    /// `disconnect` runs once the policy says to close the connection.
+    fn spawn_ping_monitor(
+        &self,
+        peer: &Arc<ClientPeer>,
+        disconnect: impl FnOnce() + Send + 'static,
+    ) {
+        let Some(tracker) = peer.ping_tracker() else {
+            return;
+        };
+        let peer = Arc::downgrade(peer);
+        let events = self.ping_events.clone();
+        tokio::spawn(async move {
+            let policy = tracker.policy().clone();
+            let mut interval = tokio::time::interval(policy.interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick is immediate, before the client could initialize
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(peer) = peer.upgrade() else {
+                    break;
+                };
+                if peer.protocol_version().is_none() {
+                    continue;
+                }
+                let session_id = peer.transport().session_id;
+                let answered = match peer.send_request("ping", None, policy.timeout).await {
+                    Ok(_) => true,
+                    Err(e) => {
+                        warn!("Client did not answer ping: {}", e);
+                        false
+                    }
+                };
+                drop(peer);
+                if let Some(event) = tracker.record(answered) {
+                    let _ = events.send((session_id.clone(), event));
+                }
+                if tracker.should_disconnect() {
+                    disconnect();
+                    let _ = events.send((session_id, PingEvent::Disconnected));
+                    break;
+                }
+            }
+        });
     }
 
-    /// Check if server can accept operations
-    pub async fn can_operate(&self) -> bool {
-        self.state.read().await.can_operate()
+    /// Route a message received from a client connection
+    ///
+    /// Notifications are handled in order before the next message is read;
+    /// requests run on their own task and reply through the peer.
+    async fn dispatch_message(&self, message: JsonRpcMessage, peer: &Arc<ClientPeer>) {
+        match message {
+            JsonRpcMessage::Request(request) if request.id.is_some() => {
+                if self.shutdown.is_draining() {
+                    let response = JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server is shutting down".to_string()),
+                        request.id,
+                    );
+                    if let Err(e) = peer.send(JsonRpcMessage::Response(response)) {
+                        error!("Failed to queue response: {}", e);
+                    }
+                    return;
+                }
+                let server = self.clone();
+                let peer = peer.clone();
+                let in_flight = self.shutdown.track(request.id.clone(), &peer);
+                let key = in_flight.key();
+                let task = tokio::spawn(async move {
+                    let _in_flight = in_flight;
+                    let response = server.respond(request, &peer).await;
+                    if let Err(e) = peer.send(JsonRpcMessage::Response(response)) {
+                        error!("Failed to queue response: {}", e);
+                    }
+                });
+                self.shutdown.set_abort_handle(key, task.abort_handle());
+            }
+            JsonRpcMessage::Request(notification) | JsonRpcMessage::Notification(notification)
+                if notification.method == NOTIFICATION_ACK_METHOD =>
+            {
+                peer.handle_notification_ack(notification.params);
+            }
+            JsonRpcMessage::Request(notification) | JsonRpcMessage::Notification(notification)
+                if ROOTS_LIST_CHANGED_METHODS.contains(&notification.method.as_str()) =>
+            {
+                debug!("Client roots changed");
+                peer.invalidate_roots();
+            }
+            JsonRpcMessage::Request(notification) | JsonRpcMessage::Notification(notification) => {
+                if let Err(e) = self.handle_notification(notification).await {
+                    error!("Error handling notification: {}", e);
+                }
+            }
+            JsonRpcMessage::Response(response) => {
+                peer.handle_response(response);
+            }
+        }
     }
 
-    /// Helper function to deserialize request parameters with proper defaults
-    fn deserialize_list_tools_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::tools::ListToolsRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
-    }
+    /// Handle a request through the middleware chain and within its
+    /// operation timeout
+    ///
+    /// The request is traced in an `mcp_request` span and, with monitoring
+    /// enabled, recorded in the metrics, both labelled with the transport the
+    /// peer is connected through. A trace context in the request's `_meta`
+    /// makes the span a child of the client span the request was sent from.
+    /// The `bare-metal` feature compiles the span and the metrics out.
+    async fn respond(&self, request: JsonRpcRequest, peer: &Arc<ClientPeer>) -> JsonRpcResponse {
+        #[cfg(not(feature = "bare-metal"))]
+        let transport = peer.transport();
+        #[cfg(not(feature = "bare-metal"))]
+        let span = tracing::info_span!(
+            "mcp_request",
+            method = %request.method,
+            transport = %transport.kind,
+            session_id = transport.session_id.as_deref(),
+            remote_address = transport.remote_address.as_deref(),
+        );
+        #[cfg(not(feature = "bare-metal"))]
+        ultrafast_mcp_core::protocol::trace_context::set_parent_from_meta(
+            &span,
+            request.params.as_ref(),
+        );
+        #[cfg(any(
+            feature = "content-encoding",
+            all(feature = "monitoring", not(feature = "bare-metal"))
+        ))]
+        let method = request.method.clone();
+        #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+        let timer = self.monitoring_system.as_ref().map(|monitoring| {
+            crate::RequestTimer::start(request.method.clone(), monitoring.metrics())
+                .with_transport(transport.kind.as_str())
+        });
 
-    fn deserialize_list_resources_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::resources::ListResourcesRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+        let started = Instant::now();
+        let response = self.with_middleware_chain(request, |request| {
+            self.respond_within_timeout(request, peer)
+        });
+        #[cfg(not(feature = "bare-metal"))]
+        let response = response.instrument(span);
+        let mut response = response.await;
+        #[cfg(feature = "content-encoding")]
+        self.compress_content(&mut response, &method, peer);
+        self.attach_response_meta(&mut response, started);
+
+        #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+        if let (Some(timer), Some(monitoring)) = (timer, &self.monitoring_system) {
+            // Protocol-level rejects are counted apart from handler outcomes
+            match response
+                .error
+                .as_ref()
+                .and_then(|error| crate::RejectReason::from_error_code(error.code))
+            {
+                Some(reason) => {
+                    monitoring
+                        .metrics()
+                        .record_reject(reason, Some(&method), transport.session_id.as_deref())
+                        .await
+                }
+                None => timer.finish(response.error.is_none()).await,
+            }
+        }
+        response
     }
 
-    fn deserialize_list_prompts_request(
+    /// Run a tool call where [`with_tool_isolation`](Self::with_tool_isolation)
+    /// put the tool, or in place
+    async fn run_tool(
         &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::prompts::ListPromptsRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+        tool_name: &str,
+        call: impl Future<Output = MCPResult<ultrafast_mcp_core::types::tools::ToolResult>>
+        + Send
+        + 'static,
+    ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+        match self.tool_isolation.get(tool_name) {
+            Some(isolation) => isolation.run(call).await,
+            None => call.await,
+        }
     }
 
-    fn deserialize_get_prompt_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::prompts::GetPromptRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    /// Compress the large content items of a `tools/call` or
+    /// `resources/read` result, if the client accepts them compressed
+    #[cfg(feature = "content-encoding")]
+    fn compress_content(&self, response: &mut JsonRpcResponse, method: &str, peer: &ClientPeer) {
+        let (Some(threshold), Some(result)) = (self.content_compression, response.result.as_mut())
+        else {
+            return;
+        };
+        if !matches!(method, "tools/call" | "resources/read")
+            || !peer
+                .client_capabilities()
+                .is_some_and(|capabilities| content_encoding::accepts_gzip(&capabilities))
+        {
+            return;
+        }
+        let compressed = content_encoding::encode_content(result, threshold);
+        if compressed > 0 {
+            debug!(
+                "Compressed {} content items of {} response",
+                compressed, method
+            );
+        }
     }
 
-    fn deserialize_read_resource_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::resources::ReadResourceRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    /// Stamp the execution metadata into a successful result, if enabled
+    fn attach_response_meta(&self, response: &mut JsonRpcResponse, started: Instant) {
+        if !self.response_meta {
+            return;
+        }
+        let Some(result) = response.result.as_mut() else {
+            return;
+        };
+        let cache_hit = ResponseMeta::from_result(result).is_some_and(|meta| meta.cache_hit);
+        let meta = ResponseMeta {
+            duration_ms: started.elapsed().as_millis() as u64,
+            bytes_processed: serde_json::to_vec(result).map_or(0, |bytes| bytes.len() as u64),
+            cache_hit,
+        };
+        meta.attach(result);
     }
 
-    fn deserialize_list_resource_templates_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::resources::ListResourceTemplatesRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    /// A `*/list` response served from the list cache
+    ///
+    /// It is sent as serialized, unless response metadata has to be added.
+    fn cached_list_response(&self, raw: Arc<RawValue>, id: Option<RequestId>) -> JsonRpcResponse {
+        if !self.response_meta {
+            return JsonRpcResponse::success_raw(raw, id);
+        }
+        match serde_json::from_str(raw.get()) {
+            Ok(mut value) => {
+                ResponseMeta {
+                    cache_hit: true,
+                    ..ResponseMeta::default()
+                }
+                .attach(&mut value);
+                JsonRpcResponse::success(value, id)
+            }
+            Err(e) => JsonRpcResponse::error(
+                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                id,
+            ),
+        }
     }
 
-    fn deserialize_subscribe_request(
+    /// Handle a request within its operation timeout
+    ///
+    /// On timeout the client receives an error response and a cancellation
+    /// notification for the request.
+    async fn respond_within_timeout(
         &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::resources::SubscribeRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_else(|_| {
-            ultrafast_mcp_core::types::resources::SubscribeRequest { uri: String::new() }
-        })
+        request: JsonRpcRequest,
+        peer: &Arc<ClientPeer>,
+    ) -> JsonRpcResponse {
+        if let Some(response) = self.rate_limited(&request, peer) {
+            return response;
+        }
+        // Held until the request has been handled
+        let _permit = match self.concurrency_permit(&request, peer).await {
+            Ok(permit) => permit,
+            Err(response) => return response,
+        };
+        let operation_timeout = self.get_operation_timeout(&request.method);
+        let request_id = request.id.clone();
+        let tracked_id = request_id
+            .as_ref()
+            .and_then(|id| serde_json::to_value(id).ok());
+        if let Some(id) = &tracked_id {
+            let _ = self
+                .cancellation_manager
+                .register_request(id.clone(), request.method.clone())
+                .await;
+        }
+        let result = tokio::time::timeout(
+            operation_timeout,
+            self.dispatch_request(request, Some(peer)),
+        )
+        .await;
+        if let (Some(id), Some(request_id)) = (&tracked_id, &request_id) {
+            let _ = self.cancellation_manager.complete_request(id).await;
+            self.request_cancellations
+                .clear_cancelled(&request_id.to_string())
+                .await;
+        }
+        match result {
+            Ok(response) => response,
+            Err(_) => {
+                if let Some(request_id) = &request_id {
+                    let notification =
+                        ultrafast_mcp_core::types::notifications::CancelledNotification::new(
+                            serde_json::Value::String(request_id.to_string()),
+                        )
+                        .with_reason("Request timed out".to_string());
+                    if let Ok(params) = serde_json::to_value(notification) {
+                        let _ = peer.send_notification("notifications/cancelled", Some(params));
+                    }
+                }
+                JsonRpcResponse::error(
+                    JsonRpcError::new(-32000, "Request timeout".to_string()),
+                    request_id,
+                )
+            }
+        }
     }
 
-    fn deserialize_unsubscribe_request(
+    /// The error response for a request over its rate limit, if it is
+    fn rate_limited(
         &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::resources::UnsubscribeRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_else(|_| {
-            ultrafast_mcp_core::types::resources::UnsubscribeRequest { uri: String::new() }
-        })
-    }
+        request: &JsonRpcRequest,
+        peer: &Arc<ClientPeer>,
+    ) -> Option<JsonRpcResponse> {
+        let limiter = self.rate_limiter.as_ref()?;
+        let session_id = subscription_session(Some(peer));
+        let rejected = limiter.check(session_id, &request.method).err()?;
+        warn!(
+            "Rate limited {} from session {}: {:?}",
+            request.method, session_id, rejected.scope
+        );
+        if let Ok(params) = serde_json::to_value(rejected.to_notification()) {
+            let _ = peer.send_notification(RATE_LIMIT_NOTIFICATION_METHOD, Some(params));
+        }
 
-    fn deserialize_create_message_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::sampling::CreateMessageRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+        let error = self.limit_error(
+            request,
+            &rejected.to_error(),
+            ultrafast_mcp_core::error::error_codes::RATE_LIMIT_ERROR,
+            rejected.error_data(),
+        );
+        Some(JsonRpcResponse::error(error, request.id.clone()))
     }
 
-    fn deserialize_elicitation_request(
+    /// A slot to handle the request in, or the error response if the server
+    /// is too busy
+    async fn concurrency_permit(
         &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::elicitation::ElicitationRequest {
-        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
-    }
+        request: &JsonRpcRequest,
+        peer: &Arc<ClientPeer>,
+    ) -> Result<Option<ConcurrencyPermit>, JsonRpcResponse> {
+        let Some(limiter) = &self.concurrency_limiter else {
+            return Ok(None);
+        };
+        let session_id = subscription_session(Some(peer));
+        let busy = match limiter.acquire(session_id, &request.method).await {
+            Ok(permit) => return Ok(Some(permit)),
+            Err(busy) => busy,
+        };
+        warn!(
+            "Server busy, rejected {} from session {}: {:?}",
+            request.method, session_id, busy.scope
+        );
 
-    fn deserialize_complete_request(
-        &self,
-        params: Option<serde_json::Value>,
-    ) -> ultrafast_mcp_core::types::completion::CompleteRequest {
-        match params {
-            Some(params) => serde_json::from_value(params).unwrap_or_else(|_| {
-                ultrafast_mcp_core::types::completion::CompleteRequest {
-                    reference: ultrafast_mcp_core::types::completion::CompletionReference {
-                        ref_type: "ref/prompt".to_string(),
-                        name: "".to_string(),
-                    },
-                    argument: ultrafast_mcp_core::types::completion::CompletionArgument {
-                        name: "".to_string(),
-                        value: "".to_string(),
-                    },
-                    context: None,
-                }
-            }),
-            None => ultrafast_mcp_core::types::completion::CompleteRequest {
-                reference: ultrafast_mcp_core::types::completion::CompletionReference {
-                    ref_type: "ref/prompt".to_string(),
-                    name: "".to_string(),
-                },
-                argument: ultrafast_mcp_core::types::completion::CompletionArgument {
-                    name: "".to_string(),
-                    value: "".to_string(),
-                },
-                context: None,
-            },
-        }
+        let error = self.limit_error(
+            request,
+            &busy.to_error(),
+            ultrafast_mcp_core::error::error_codes::SERVER_BUSY,
+            busy.error_data(),
+        );
+        Err(JsonRpcResponse::error(error, request.id.clone()))
     }
 
-    /// Handle incoming messages
-    async fn handle_message(
+    /// The error a limiter turned `request` away with, localized, with the
+    /// limiter's hints in `limit_data` kept next to what localization put in
+    /// the data
+    fn limit_error(
         &self,
-        message: JsonRpcMessage,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        match message {
-            JsonRpcMessage::Request(request) => {
-                // Check if this is actually a notification (no ID)
-                if request.id.is_none() {
-                    // This is a notification, handle it as such
-                    self.handle_notification(request).await?;
-                } else {
-                    // This is a request, handle it with timeout
-                    let operation_timeout = self.get_operation_timeout(&request.method);
-                    let request_id = request.id.clone(); // Clone before moving request
-                    let response =
-                        tokio::time::timeout(operation_timeout, self.handle_request(request)).await;
-
-                    match response {
-                        Ok(response) => {
-                            transport
-                                .send_message(JsonRpcMessage::Response(response))
-                                .await
-                                .map_err(|e| {
-                                    MCPError::internal_error(format!("Failed to send message: {e}"))
-                                })?;
-                        }
-                        Err(_) => {
-                            // Request timed out, send timeout error
-                            let timeout_error = JsonRpcResponse::error(
-                                JsonRpcError::new(-32000, "Request timeout".to_string()),
-                                request_id.clone(),
-                            );
-                            transport
-                                .send_message(JsonRpcMessage::Response(timeout_error))
-                                .await
-                                .map_err(|e| {
-                                    MCPError::internal_error(format!(
-                                        "Failed to send timeout error: {e}"
-                                    ))
-                                })?;
-
-                            // Send cancellation notification
-                            if let Some(request_id) = &request_id {
-                                self.notify_cancelled(
-                                    serde_json::Value::String(request_id.to_string()),
-                                    Some("Request timed out".to_string()),
-                                    transport,
-                                )
-                                .await?;
-                            }
-                        }
-                    }
+        request: &JsonRpcRequest,
+        error: &MCPError,
+        code: i32,
+        limit_data: serde_json::Value,
+    ) -> JsonRpcError {
+        let locale = locale_from_params(request.params.as_ref());
+        let default = JsonRpcError::new(code, error.to_string());
+        let mut error = self.handler_error(error, default, locale);
+        match error
+            .data
+            .as_mut()
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            Some(data) => {
+                if let serde_json::Value::Object(limit) = limit_data {
+                    data.extend(limit);
                 }
             }
-            JsonRpcMessage::Notification(notification) => {
-                self.handle_notification(notification).await?;
-            }
-            JsonRpcMessage::Response(_) => {
-                warn!("Received unexpected response message");
-            }
+            None => error.data = Some(limit_data),
         }
-        Ok(())
+        error
     }
 
-    /// Handle incoming requests
-    async fn handle_request(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+    /// Run the server with Streamable HTTP transport
+    #[cfg(feature = "http")]
+    pub async fn run_streamable_http(&self, host: &str, port: u16) -> MCPResult<()> {
         info!(
-            "Handling request: {} (id: {:?})",
-            request.method, request.id
+            "Starting UltraFastServer with Streamable HTTP on {}:{}",
+            host, port
         );
 
-        match request.method.as_str() {
-            // MCP Lifecycle methods
-            "initialize" => {
-                match serde_json::from_value::<ultrafast_mcp_core::protocol::InitializeRequest>(
-                    request.params.unwrap_or_default(),
-                ) {
-                    Ok(init_request) => match self.handle_initialize(init_request).await {
-                        Ok(response) => match serde_json::to_value(response) {
-                            Ok(value) => JsonRpcResponse::success(value, request.id),
-                            Err(e) => JsonRpcResponse::error(
-                                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
-                                request.id,
-                            ),
-                        },
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, e.to_string()),
-                            request.id,
-                        ),
-                    },
-                    Err(e) => JsonRpcResponse::error(
-                        JsonRpcError::invalid_params(Some(format!(
-                            "Invalid initialize request: {e}"
-                        ))),
-                        request.id,
-                    ),
-                }
-            }
-            "shutdown" => {
-                let shutdown_request = match serde_json::from_value::<
-                    ultrafast_mcp_core::protocol::ShutdownRequest,
-                >(request.params.unwrap_or_default())
-                {
-                    Ok(req) => req,
-                    Err(_) => ultrafast_mcp_core::protocol::ShutdownRequest { reason: None },
-                };
+        let config = HttpTransportConfig {
+            host: host.to_string(),
+            port,
+            ..Default::default()
+        };
 
-                match self.handle_shutdown(shutdown_request).await {
-                    Ok(_) => JsonRpcResponse::success(serde_json::json!({}), request.id),
-                    Err(e) => {
-                        JsonRpcResponse::error(JsonRpcError::new(-32603, e.to_string()), request.id)
-                    }
-                }
-            }
+        self.run_http(config).await
+    }
 
-            // Tools methods
-            "tools/list" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::internal_error(Some("Server not ready".to_string())),
-                        request.id,
-                    );
-                }
+    /// Run the server with HTTP transport
+    #[cfg(feature = "http")]
+    pub async fn run_http(&self, config: HttpTransportConfig) -> MCPResult<()> {
+        info!("Starting HTTP transport server with config: {:?}", config);
 
-                let list_request = self.deserialize_list_tools_request(request.params.clone());
+        let (info, started_at) = (self.info.clone(), self.started_at);
+        let mut transport_server = HttpTransportServer::new(config)
+            .with_session_manager(self.http_sessions.clone())
+            .with_info_provider(Arc::new(move || {
+                serde_json::to_value(BuildInfo::collect(&info, started_at)).unwrap_or_default()
+            }));
+        if let Some(resolver) = &self.identity_resolver {
+            transport_server = transport_server.with_identity_resolver(resolver.clone());
+        }
+        if let Some(hardening) = &self.http_hardening {
+            transport_server = transport_server.with_hardening(hardening.clone());
+        }
+        if self.openai_tools {
+            let server = self.clone();
+            transport_server = transport_server.with_openai_tools(Arc::new(move |request| {
+                let server = server.clone();
+                Box::pin(async move { server.handle_openai_request(request).await })
+            }));
+        }
+        #[cfg(feature = "monitoring")]
+        if let Some(monitoring) = &self.monitoring_system {
+            transport_server = transport_server
+                .with_reject_metrics(monitoring.metrics())
+                .with_traffic_metrics(monitoring.metrics());
+        }
+        // Kept here too, to look up the address each session connected from
+        let session_store = self
+            .http_session_store
+            .clone()
+            .unwrap_or_else(|| Arc::new(InMemorySessionStore::new()));
+        transport_server = transport_server.with_session_store(session_store.clone());
+        let message_receiver = transport_server.get_message_receiver();
+        let message_sender = transport_server.get_message_sender();
+        let response_sender = transport_server.get_response_sender();
 
-                if let Some(handler) = &self.tool_handler {
-                    match handler.list_tools(list_request).await {
-                        Ok(response) => {
-                            // If handler returns empty tools, fallback to registered tools
-                            if response.tools.is_empty() {
-                                let tools = self.list_tools().await;
-                                let response =
-                                    ultrafast_mcp_core::types::tools::ListToolsResponse {
-                                        tools,
-                                        next_cursor: None,
-                                    };
-                                match serde_json::to_value(response) {
-                                    Ok(value) => JsonRpcResponse::success(value, request.id),
-                                    Err(e) => JsonRpcResponse::error(
-                                        JsonRpcError::new(
-                                            -32603,
-                                            format!("Serialization error: {e}"),
-                                        ),
-                                        request.id,
-                                    ),
-                                }
-                            } else {
-                                match serde_json::to_value(response) {
-                                    Ok(value) => JsonRpcResponse::success(value, request.id),
-                                    Err(e) => JsonRpcResponse::error(
-                                        JsonRpcError::new(
-                                            -32603,
-                                            format!("Serialization error: {e}"),
-                                        ),
-                                        request.id,
-                                    ),
-                                }
-                            }
-                        }
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Tools list failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    // Fallback to registered tools
-                    let tools = self.list_tools().await;
-                    let response = ultrafast_mcp_core::types::tools::ListToolsResponse {
-                        tools,
-                        next_cursor: None,
-                    };
-                    match serde_json::to_value(response) {
-                        Ok(value) => JsonRpcResponse::success(value, request.id),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Serialization error: {e}")),
-                            request.id,
-                        ),
-                    }
-                }
-            }
-            "tools/call" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::internal_error(Some("Server not ready".to_string())),
-                        request.id,
-                    );
-                }
+        // Start message processing task
+        let server_clone = self.clone();
+        let message_processor = tokio::spawn(async move {
+            server_clone
+                .process_http_messages(
+                    message_receiver,
+                    message_sender,
+                    response_sender,
+                    session_store,
+                )
+                .await;
+        });
 
-                let params = match &request.params {
-                    Some(params) => params,
-                    None => {
-                        return JsonRpcResponse::error(
-                            JsonRpcError::new(
-                                -32602,
-                                "Tool call failed: Missing parameters".to_string(),
-                            ),
-                            request.id,
-                        );
-                    }
-                };
+        // Start the HTTP server, until `shutdown_graceful` closes it
+        let result = transport_server
+            .run_until(self.shutdown.closed())
+            .await
+            .map_err(|e| MCPError::internal_error(format!("HTTP server failed: {e}")));
+        message_processor.abort();
+        result
+    }
 
-                let tool_name = params.get("name").and_then(|v| v.as_str());
-                let arguments = params
-                    .get("arguments")
-                    .cloned()
-                    .unwrap_or(serde_json::json!({}));
+    /// Run the server with HTTP transport until `signal` completes
+    ///
+    /// Shuts down like [`run_stdio_with_shutdown`](Self::run_stdio_with_shutdown).
+    #[cfg(feature = "http")]
+    pub async fn run_http_with_shutdown(
+        &self,
+        config: HttpTransportConfig,
+        signal: impl Future<Output = ()> + Send,
+    ) -> MCPResult<()> {
+        self.run_until_signal(self.run_http(config), signal).await
+    }
 
-                if let Some(tool_name) = tool_name {
-                    if let Some(handler) = &self.tool_handler {
-                        let tool_call = ultrafast_mcp_core::types::tools::ToolCall {
-                            name: tool_name.to_string(),
-                            arguments: Some(arguments.clone()),
-                        };
-                        // Arguments validation will be handled by the tool handler
-                        match handler.handle_tool_call(tool_call).await {
-                            Ok(result) => match serde_json::to_value(result) {
-                                Ok(value) => JsonRpcResponse::success(value, request.id),
-                                Err(e) => JsonRpcResponse::error(
-                                    JsonRpcError::new(-32603, format!("Serialization error: {e}")),
-                                    request.id,
-                                ),
-                            },
-                            Err(e) => {
-                                use ultrafast_mcp_core::error::{MCPError, ProtocolError};
-                                let (code, msg) = match &e {
-                                    MCPError::Protocol(ProtocolError::InvalidParams(_))
-                                    | MCPError::Protocol(ProtocolError::NotFound(_)) => {
-                                        (-32602, format!("Tool call failed: {e}"))
-                                    }
-                                    _ => (-32603, format!("Tool call failed: {e}")),
-                                };
-                                JsonRpcResponse::error(JsonRpcError::new(code, msg), request.id)
-                            }
-                        }
-                    } else {
-                        // Fallback to registered tools
-                        if !self.has_tool(tool_name).await {
-                            return JsonRpcResponse::error(
-                                JsonRpcError::new(
-                                    -32602,
-                                    format!("Tool call failed: Tool not found: {tool_name}"),
-                                ),
-                                request.id,
-                            );
-                        }
-                        // Arguments validation will be handled by the tool handler
-                        match self.execute_tool_call(tool_name, arguments).await {
-                            Ok(result) => match serde_json::to_value(result) {
-                                Ok(value) => JsonRpcResponse::success(value, request.id),
-                                Err(e) => JsonRpcResponse::error(
-                                    JsonRpcError::new(-32603, format!("Serialization error: {e}")),
-                                    request.id,
-                                ),
-                            },
-                            Err(e) => {
-                                use ultrafast_mcp_core::error::{MCPError, ProtocolError};
-                                let (code, msg) = match &e {
-                                    MCPError::Protocol(ProtocolError::InvalidParams(_))
-                                    | MCPError::Protocol(ProtocolError::NotFound(_)) => {
-                                        (-32602, format!("Tool call failed: {e}"))
-                                    }
-                                    _ => (-32603, format!("Tool call failed: {e}")),
-                                };
-                                JsonRpcResponse::error(JsonRpcError::new(code, msg), request.id)
-                            }
-                        }
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(
-                            -32602,
-                            "Tool call failed: Missing or invalid tool name".to_string(),
-                        ),
-                        request.id,
-                    )
-                }
+    /// Run the server with custom Streamable HTTP transport configuration
+    /// This provides clearer naming for advanced Streamable HTTP configuration
+    #[cfg(feature = "http")]
+    pub async fn run_streamable_http_with_config(
+        &self,
+        config: HttpTransportConfig,
+    ) -> MCPResult<()> {
+        self.run_http(config).await
+    }
+
+    /// Stop the server, letting running requests finish first
+    ///
+    /// New requests are refused from the call on, and connected clients
+    /// receive a [`SHUTDOWN_NOTIFICATION_METHOD`] notification. Requests
+    /// still running after `timeout` are aborted and answered with an error.
+    /// The transports are then closed, so `run_stdio`, `run_with_transport`
+    /// and `run_http` return. Calling it again only closes the transports.
+    pub async fn shutdown_graceful(
+        &self,
+        timeout: std::time::Duration,
+    ) -> MCPResult<ShutdownReport> {
+        if !self.shutdown.start_draining() {
+            self.shutdown.close();
+            return Ok(ShutdownReport::default());
+        }
+        let in_flight = self.shutdown.in_flight();
+        info!(
+            "Shutting down, waiting up to {:?} for {} requests",
+            timeout, in_flight
+        );
+
+        let notice = serde_json::json!({
+            "reason": "Server is shutting down",
+            "timeoutMs": timeout.as_millis() as u64,
+        });
+        for peer in self.live_peers() {
+            if let Err(e) =
+                peer.send_notification(SHUTDOWN_NOTIFICATION_METHOD, Some(notice.clone()))
+            {
+                debug!("Failed to notify client of shutdown: {}", e);
             }
+        }
 
-            // Resources methods
-            "resources/list" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+        let aborted = if self.shutdown.drained(timeout).await {
+            Vec::new()
+        } else {
+            self.shutdown.abort_all()
+        };
+        for request in &aborted {
+            let Some(peer) = request.peer.upgrade() else {
+                continue;
+            };
+            warn!("Aborted request {:?} at shutdown", request.request_id);
+            let response = JsonRpcResponse::error(
+                JsonRpcError::new(
+                    -32000,
+                    "Server shut down before the request completed".to_string(),
+                ),
+                request.request_id.clone(),
+            );
+            let _ = peer.send(JsonRpcMessage::Response(response));
+        }
+
+        // Not before: requests being drained still need an operating server
+        *self.state.write().await = ServerState::ShuttingDown;
+        self.shutdown.close();
+        *self.state.write().await = ServerState::Shutdown;
+        let report = ShutdownReport {
+            drained: in_flight.saturating_sub(aborted.len()),
+            aborted: aborted.len(),
+        };
+        info!(
+            "Server shut down: {} requests drained, {} aborted",
+            report.drained, report.aborted
+        );
+        Ok(report)
+    }
 
-                let list_request = self.deserialize_list_resources_request(request.params.clone());
+    /// Process HTTP messages from the transport layer
+    ///
+    /// Each session gets its own [`ClientPeer`] whose outgoing messages are
+    /// published on the response channel for that session.
+    #[cfg(feature = "http")]
+    async fn process_http_messages(
+        &self,
+        mut message_receiver: broadcast::Receiver<(String, JsonRpcMessage)>,
+        _message_sender: broadcast::Sender<(String, JsonRpcMessage)>,
+        response_sender: broadcast::Sender<(String, JsonRpcMessage)>,
+        session_store: Arc<dyn SessionStore>,
+    ) {
+        info!("HTTP message processor started");
 
-                if let Some(handler) = &self.resource_handler {
-                    // For resources/list, we don't validate against roots since it's a general listing
-                    // Root validation will be done when individual resources are accessed
+        let mut peers: HashMap<String, Arc<ClientPeer>> = HashMap::new();
+        let mut closed_sessions = self.http_sessions.subscribe_closed();
 
-                    match handler.list_resources(list_request).await {
-                        Ok(response) => match serde_json::to_value(response) {
-                            Ok(value) => JsonRpcResponse::success(value, request.id),
-                            Err(e) => JsonRpcResponse::error(
-                                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
-                                request.id,
-                            ),
-                        },
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Resources list failed: {e}")),
-                            request.id,
-                        ),
+        loop {
+            let (session_id, message) = tokio::select! {
+                received = message_receiver.recv() => match received {
+                    Ok(received) => received,
+                    Err(_) => break,
+                },
+                Ok(session_id) = closed_sessions.recv() => {
+                    peers.remove(&session_id);
+                    self.subscriptions.remove_session(&session_id);
+                    self.session_states.remove(&session_id);
+                    self.log_levels.remove_session(&session_id);
+                    if let Some(limiter) = &self.rate_limiter {
+                        limiter.remove_session(&session_id);
                     }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Resources not supported".to_string()),
-                        request.id,
-                    )
+                    if let Some(limiter) = &self.concurrency_limiter {
+                        limiter.remove_session(&session_id);
+                    }
+                    continue;
                 }
-            }
-            "resources/read" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
+            };
+            let mut description = TransportDescription::new(TransportKind::Http);
+            if !peers.contains_key(&session_id) {
+                if let Ok(Some(session)) = session_store.get_session(&session_id).await {
+                    description.remote_address = session.remote_address;
                 }
-
-                let read_request = self.deserialize_read_resource_request(request.params.clone());
-
-                if let Some(handler) = &self.resource_handler {
-                    // Validate against roots if roots handler is available
-                    if let Some(roots_handler) = &self.roots_handler {
-                        match roots_handler.list_roots().await {
-                            Ok(roots) => {
-                                if let Err(e) = handler
-                                    .validate_resource_access(
-                                        &read_request.uri,
-                                        ultrafast_mcp_core::types::roots::RootOperation::Read,
-                                        &roots,
-                                    )
-                                    .await
-                                {
-                                    return JsonRpcResponse::error(
-                                        JsonRpcError::new(
-                                            -32603,
-                                            format!("Root validation failed: {e}"),
-                                        ),
-                                        request.id,
+            }
+            let peer = peers
+                .entry(session_id.clone())
+                .or_insert_with(|| {
+                    let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+                    let response_sender = response_sender.clone();
+                    let session_id = session_id.clone();
+                    let peer = self
+                        .create_client_peer(outgoing_sender)
+                        .with_session_id(session_id.clone())
+                        .with_transport(description);
+                    #[cfg(feature = "signing")]
+                    let signer = self.message_signer.clone();
+                    tokio::spawn(async move {
+                        while let Some(message) = outgoing.recv().await {
+                            #[cfg(feature = "signing")]
+                            let mut message = message;
+                            #[cfg(feature = "signing")]
+                            if let Some(signer) = &signer {
+                                if let Err(e) = signer.sign(&mut message) {
+                                    error!(
+                                        "Failed to sign message for session {}: {}",
+                                        session_id, e
                                     );
+                                    continue;
                                 }
                             }
-                            Err(e) => {
-                                return JsonRpcResponse::error(
-                                    JsonRpcError::new(-32603, format!("Failed to get roots: {e}")),
-                                    request.id,
-                                );
+                            if let Err(e) = response_sender.send((session_id.clone(), message)) {
+                                error!("Failed to send message for session {}: {}", session_id, e);
                             }
                         }
-                    }
-
-                    match handler.read_resource(read_request).await {
-                        Ok(response) => match serde_json::to_value(response) {
-                            Ok(value) => JsonRpcResponse::success(value, request.id),
-                            Err(e) => JsonRpcResponse::error(
-                                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
-                                request.id,
-                            ),
-                        },
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Resource read failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Resources not supported".to_string()),
-                        request.id,
-                    )
+                    });
+                    let peer = Arc::new(peer);
+                    self.track_peer(&peer);
+                    self.spawn_ping_monitor(&peer, {
+                        let sessions = self.http_sessions.clone();
+                        let session_id = peer.session_id().unwrap_or_default().to_string();
+                        move || {
+                            sessions.evict(&session_id);
+                        }
+                    });
+                    peer
+                })
+                .clone();
+
+            #[cfg(feature = "signing")]
+            let Some(message) = self.verify_http_message(message, &peer) else {
+                continue;
+            };
+            #[cfg(not(feature = "bare-metal"))]
+            match &message {
+                JsonRpcMessage::Request(request) if request.id.is_some() => {
+                    info!(
+                        "Processing HTTP request: {} (session: {})",
+                        request.method, session_id
+                    );
                 }
-            }
-            "resources/templates/list" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
+                JsonRpcMessage::Request(notification)
+                | JsonRpcMessage::Notification(notification) => {
+                    info!(
+                        "Processing HTTP notification: {} (session: {})",
+                        notification.method, session_id
                     );
                 }
+                JsonRpcMessage::Response(_) => {}
+            }
 
-                let list_request =
-                    self.deserialize_list_resource_templates_request(request.params.clone());
+            self.dispatch_message(message, &peer).await;
+        }
 
-                if let Some(handler) = &self.resource_handler {
-                    match handler.list_resource_templates(list_request).await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(
-                                -32603,
-                                format!("Resource templates list failed: {e}"),
-                            ),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Resources not supported".to_string()),
-                        request.id,
-                    )
-                }
+        info!("HTTP message processor stopped");
+    }
+
+    /// Check the signature of a message received over HTTP
+    ///
+    /// Requests that fail verification are answered with an access denied
+    /// error; other messages are dropped.
+    #[cfg(all(feature = "http", feature = "signing"))]
+    fn verify_http_message(
+        &self,
+        mut message: JsonRpcMessage,
+        peer: &ClientPeer,
+    ) -> Option<JsonRpcMessage> {
+        let Some(signer) = &self.message_signer else {
+            return Some(message);
+        };
+        let error = match signer.verify(&mut message) {
+            Ok(_) => return Some(message),
+            Err(e) => e,
+        };
+        warn!(
+            "Rejected message in session {}: {}",
+            peer.session_id().unwrap_or_default(),
+            error
+        );
+        if let JsonRpcMessage::Request(request) = message {
+            if request.id.is_some() {
+                let response = JsonRpcResponse::error(
+                    JsonRpcError::new(
+                        ultrafast_mcp_core::protocol::jsonrpc::mcp_error_codes::ACCESS_DENIED,
+                        error.to_string(),
+                    ),
+                    request.id,
+                );
+                let _ = peer.send(JsonRpcMessage::Response(response));
             }
-            "resources/subscribe" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+        }
+        None
+    }
 
-                let subscribe_request = self.deserialize_subscribe_request(request.params.clone());
+    /// Get server info
+    pub fn info(&self) -> &ServerInfo {
+        &self.info
+    }
 
-                // Validate against roots if roots handler is available
-                if let Some(roots_handler) = &self.roots_handler {
-                    if let Some(resource_handler) = &self.resource_handler {
-                        match roots_handler.list_roots().await {
-                            Ok(roots) => {
-                                if let Err(e) = resource_handler
-                                    .validate_resource_access(
-                                        &subscribe_request.uri,
-                                        ultrafast_mcp_core::types::roots::RootOperation::Read,
-                                        &roots,
-                                    )
-                                    .await
-                                {
-                                    return JsonRpcResponse::error(
-                                        JsonRpcError::new(
-                                            -32603,
-                                            format!("Root validation failed: {e}"),
-                                        ),
-                                        request.id,
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                return JsonRpcResponse::error(
-                                    JsonRpcError::new(-32603, format!("Failed to get roots: {e}")),
-                                    request.id,
-                                );
-                            }
-                        }
-                    }
-                }
+    /// Build and runtime information, as returned by `x-ultrafast/info`
+    pub fn build_info(&self) -> BuildInfo {
+        BuildInfo::collect(&self.info, self.started_at)
+    }
 
-                if let Some(handler) = &self.subscription_handler {
-                    match handler.subscribe(subscribe_request.uri.clone()).await {
-                        Ok(_) => {
-                            // Subscription successful - return success response
-                            // Note: The client may timeout if it expects immediate notifications
-                            // This is a limitation of the current MCP architecture
-                            JsonRpcResponse::success(
-                                serde_json::to_value(SubscribeResponse::new()).unwrap(),
-                                request.id,
-                            )
-                        }
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Resource subscribe failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(
-                            -32601,
-                            "Resource subscriptions not supported".to_string(),
-                        ),
-                        request.id,
-                    )
-                }
+    /// Get cancellation manager
+    pub fn cancellation_manager(&self) -> Arc<CancellationManager> {
+        self.cancellation_manager.clone()
+    }
+
+    /// Get ping manager
+    pub fn ping_manager(&self) -> Arc<PingManager> {
+        self.ping_manager.clone()
+    }
+
+    /// Start periodic ping monitoring (optional, for connection health)
+    /// This method should be called after the server is running with a transport
+    pub async fn start_ping_monitoring(&self, ping_interval: std::time::Duration) -> MCPResult<()> {
+        info!(
+            "Starting periodic ping monitoring with interval: {:?}",
+            ping_interval
+        );
+
+        // Note: This is a placeholder for future implementation
+        // The actual ping monitoring would need to be integrated with the transport layer
+        // For now, we log that ping monitoring is enabled
+        info!("Ping monitoring enabled (interval: {:?})", ping_interval);
+
+        // The PingManager is already configured with default intervals
+        // Future implementation would integrate with the transport layer
+        // to send periodic pings to clients
+
+        Ok(())
+    }
+
+    /// Stop ping monitoring
+    pub async fn stop_ping_monitoring(&self) -> MCPResult<()> {
+        info!("Stopping periodic ping monitoring");
+        // The ping monitoring task will naturally stop when the transport is closed
+        Ok(())
+    }
+
+    /// Handle MCP initialize request
+    async fn handle_initialize(
+        &self,
+        request: ultrafast_mcp_core::protocol::InitializeRequest,
+    ) -> Result<ultrafast_mcp_core::protocol::InitializeResponse, MCPError> {
+        info!(
+            "Handling initialize request from client: {} (version: {})",
+            request.client_info.name, request.protocol_version
+        );
+
+        // Negotiate protocol version
+        let negotiated_version = match ultrafast_mcp_core::protocol::version::negotiate_version(
+            &request.protocol_version,
+        ) {
+            Ok(version) => {
+                info!(
+                    "Protocol version negotiated: {} -> {}",
+                    request.protocol_version, version
+                );
+                version
             }
-            "resources/unsubscribe" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+            Err(e) => {
+                error!("Protocol version negotiation failed: {}", e);
+                return Err(MCPError::invalid_request(format!(
+                    "Protocol version negotiation failed: {}. Supported versions: {:?}",
+                    e,
+                    ultrafast_mcp_core::protocol::version::SUPPORTED_VERSIONS
+                )));
+            }
+        };
+
+        // Validate the initialize request
+        if let Err(e) = request.validate_protocol_version() {
+            warn!("Initialize request validation warning: {}", e);
+            // Continue with warning but don't fail
+        }
+
+        // Validate compatibility
+        if let Err(e) = ultrafast_mcp_core::protocol::capabilities::validate_compatibility(
+            &request.capabilities,
+            &self.advertised_capabilities(),
+        ) {
+            error!("Capability validation failed: {}", e);
+            return Err(MCPError::Protocol(
+                ultrafast_mcp_core::error::ProtocolError::CapabilityNotSupported(e),
+            ));
+        }
+
+        info!("Capabilities validated successfully");
+
+        // Update server state to Initialized (not Operating yet)
+        // This follows MCP 2025-06-18 specification: server should wait for initialized notification
+        {
+            let mut state = self.state.write().await;
+            *state = ServerState::Initialized;
+        }
+
+        info!(
+            "Server initialized with protocol version: {} (waiting for initialized notification)",
+            negotiated_version
+        );
+
+        Ok(ultrafast_mcp_core::protocol::InitializeResponse {
+            protocol_version: negotiated_version,
+            capabilities: self.advertised_capabilities(),
+            server_info: self.info.clone(),
+            instructions: None,
+        })
+    }
+
+    /// Handle MCP initialized notification
+    async fn handle_initialized(
+        &self,
+        _notification: ultrafast_mcp_core::protocol::InitializedNotification,
+    ) -> MCPResult<()> {
+        info!("Received initialized notification from client");
+
+        // Ensure server state is operating (it should already be from initialize)
+        {
+            let mut state = self.state.write().await;
+            *state = ServerState::Operating;
+        }
+
+        info!("Server confirmed operating state via initialized notification");
+        Ok(())
+    }
+
+    /// Handle MCP shutdown request
+    async fn handle_shutdown(
+        &self,
+        request: ultrafast_mcp_core::protocol::ShutdownRequest,
+    ) -> MCPResult<()> {
+        info!("Handling shutdown request: {:?}", request.reason);
+
+        // Update server state
+        {
+            let mut state = self.state.write().await;
+            *state = ServerState::ShuttingDown;
+        }
+
+        // Perform cleanup
+        self.perform_shutdown_cleanup().await;
+
+        // Update state to shutdown
+        {
+            let mut state = self.state.write().await;
+            *state = ServerState::Shutdown;
+        }
+
+        info!("Server shutdown completed");
+        Ok(())
+    }
+
+    /// Perform shutdown cleanup
+    async fn perform_shutdown_cleanup(&self) {
+        info!("Performing shutdown cleanup");
+
+        // Clear all tools
+        self.clear_tools().await;
+
+        // Clear all resources
+        self.resources.clear().await;
+
+        // Clear all prompts
+        self.prompts.clear().await;
+
+        // Clear resource subscriptions
+        self.subscriptions.clear();
+
+        self.session_states.clear();
+        self.log_levels.clear();
+
+        self.invalidate_list_cache().await;
+
+        info!("Shutdown cleanup completed");
+    }
+
+    /// Get current server state
+    pub async fn get_state(&self) -> ServerState {
+        self.state.read().await.clone()
+    }
+
+    /// Check if server can accept operations
+    pub async fn can_operate(&self) -> bool {
+        self.state.read().await.can_operate()
+    }
+
+    /// Helper function to deserialize request parameters with proper defaults
+    fn deserialize_list_tools_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::tools::ListToolsRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_list_resources_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::resources::ListResourcesRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_list_prompts_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::prompts::ListPromptsRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_get_prompt_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::prompts::GetPromptRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    /// Check that the client's roots allow reading `uri`, when a roots
+    /// handler is set
+    async fn check_read_roots(
+        &self,
+        handler: &dyn ResourceHandler,
+        uri: &str,
+    ) -> Result<(), JsonRpcError> {
+        let Some(roots_handler) = &self.roots_handler else {
+            return Ok(());
+        };
+        let roots = roots_handler
+            .list_roots()
+            .await
+            .map_err(|e| JsonRpcError::new(-32603, format!("Failed to get roots: {e}")))?;
+        handler
+            .validate_resource_access(
+                uri,
+                ultrafast_mcp_core::types::roots::RootOperation::Read,
+                &roots,
+            )
+            .await
+            .map_err(|e| JsonRpcError::new(-32603, format!("Root validation failed: {e}")))
+    }
+
+    fn deserialize_read_resource_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::resources::ReadResourceRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_list_resource_templates_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::resources::ListResourceTemplatesRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_subscribe_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::resources::SubscribeRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_else(|_| {
+            ultrafast_mcp_core::types::resources::SubscribeRequest { uri: String::new() }
+        })
+    }
+
+    fn deserialize_unsubscribe_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::resources::UnsubscribeRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_else(|_| {
+            ultrafast_mcp_core::types::resources::UnsubscribeRequest { uri: String::new() }
+        })
+    }
+
+    fn deserialize_create_message_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::sampling::CreateMessageRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_elicitation_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::elicitation::ElicitationRequest {
+        serde_json::from_value(params.unwrap_or_default()).unwrap_or_default()
+    }
+
+    fn deserialize_complete_request(
+        &self,
+        params: Option<serde_json::Value>,
+    ) -> ultrafast_mcp_core::types::completion::CompleteRequest {
+        match params {
+            Some(params) => serde_json::from_value(params).unwrap_or_else(|_| {
+                ultrafast_mcp_core::types::completion::CompleteRequest {
+                    reference: ultrafast_mcp_core::types::completion::CompletionReference {
+                        ref_type: "ref/prompt".to_string(),
+                        name: "".to_string(),
+                    },
+                    argument: ultrafast_mcp_core::types::completion::CompletionArgument {
+                        name: "".to_string(),
+                        value: "".to_string(),
+                    },
+                    context: None,
+                }
+            }),
+            None => ultrafast_mcp_core::types::completion::CompleteRequest {
+                reference: ultrafast_mcp_core::types::completion::CompletionReference {
+                    ref_type: "ref/prompt".to_string(),
+                    name: "".to_string(),
+                },
+                argument: ultrafast_mcp_core::types::completion::CompletionArgument {
+                    name: "".to_string(),
+                    value: "".to_string(),
+                },
+                context: None,
+            },
+        }
+    }
+
+    /// Answer a request of the OpenAI-compatible tools endpoints
+    ///
+    /// They act as a client of their own, so the server is initialized on
+    /// their behalf if no MCP client has done so yet.
+    #[cfg(feature = "http")]
+    async fn handle_openai_request(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        if *self.state.read().await == ServerState::Uninitialized {
+            let initialize = JsonRpcRequest::new(
+                "initialize".to_string(),
+                Some(serde_json::json!({
+                    "protocolVersion": ultrafast_mcp_core::protocol::version::PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "openai-tools",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                })),
+                Some(RequestId::number(0)),
+            );
+            if let Some(error) = self.handle_request(initialize).await.error {
+                return JsonRpcResponse::error(error, request.id);
+            }
+        }
+        // A session of their own, so their calls count against the rate and
+        // concurrency limits like any client's; nothing listens to its
+        // notifications
+        let (outgoing, _) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            self.create_client_peer(outgoing)
+                .with_session_id(OPENAI_TOOLS_SESSION.to_string()),
+        );
+        self.with_middleware_chain(request, |request| {
+            self.respond_within_timeout(request, &peer)
+        })
+        .await
+    }
+
+    /// Handle a request that did not arrive through a client connection
+    #[cfg(any(test, feature = "http"))]
+    async fn handle_request(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        self.with_middleware_chain(request, |request| self.dispatch_request(request, None))
+            .await
+    }
+
+    /// Pass a request through the middleware chain around `dispatch`
+    #[cfg(not(feature = "bare-metal"))]
+    async fn with_middleware_chain<'a, F, Fut>(
+        &'a self,
+        mut request: JsonRpcRequest,
+        dispatch: F,
+    ) -> JsonRpcResponse
+    where
+        F: FnOnce(JsonRpcRequest) -> Fut,
+        Fut: Future<Output = JsonRpcResponse> + 'a,
+    {
+        if self.middleware.is_empty() {
+            return dispatch(request).await;
+        }
+
+        let info = RequestInfo::new(&request);
+        let mut entered = 0;
+        let mut response = None;
+        for middleware in &self.middleware {
+            entered += 1;
+            if let Err(error) = middleware.on_request(&mut request).await {
+                response = Some(JsonRpcResponse::error(error, request.id.clone()));
+                break;
+            }
+        }
+        let mut response = match response {
+            Some(rejection) => rejection,
+            None => dispatch(request).await,
+        };
+
+        // Unwind through the middleware that saw the request
+        for middleware in self.middleware[..entered].iter().rev() {
+            match &mut response.error {
+                Some(error) => middleware.on_error(&info, error).await,
+                None => middleware.on_response(&info, &mut response).await,
+            }
+        }
+        response
+    }
+
+    /// Run `dispatch`; the `bare-metal` feature compiles the middleware
+    /// chain out
+    #[cfg(feature = "bare-metal")]
+    async fn with_middleware_chain<'a, F, Fut>(
+        &'a self,
+        request: JsonRpcRequest,
+        dispatch: F,
+    ) -> JsonRpcResponse
+    where
+        F: FnOnce(JsonRpcRequest) -> Fut,
+        Fut: Future<Output = JsonRpcResponse> + 'a,
+    {
+        dispatch(request).await
+    }
+
+    /// Handle a request, giving handlers access to the client connection it
+    /// arrived on
+    async fn dispatch_request(
+        &self,
+        request: JsonRpcRequest,
+        peer: Option<&Arc<ClientPeer>>,
+    ) -> JsonRpcResponse {
+        #[cfg(not(feature = "bare-metal"))]
+        info!(
+            "Handling request: {} (id: {:?})",
+            request.method, request.id
+        );
+        let locale = locale_from_params(request.params.as_ref()).map(str::to_string);
+
+        match request.method.as_str() {
+            // MCP Lifecycle methods
+            "initialize" => {
+                match serde_json::from_value::<ultrafast_mcp_core::protocol::InitializeRequest>(
+                    request.params.unwrap_or_default(),
+                ) {
+                    Ok(init_request) => {
+                        if let Some(peer) = peer {
+                            peer.set_client_capabilities(init_request.capabilities.clone());
+                        }
+                        match self.handle_initialize(init_request).await {
+                            Ok(response) => match serde_json::to_value(&response) {
+                                Ok(value) => {
+                                    if let Some(peer) = peer {
+                                        peer.set_protocol_version(response.protocol_version);
+                                    }
+                                    JsonRpcResponse::success(value, request.id)
+                                }
+                                Err(e) => JsonRpcResponse::error(
+                                    JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                    request.id,
+                                ),
+                            },
+                            Err(e) => JsonRpcResponse::error(
+                                JsonRpcError::new(-32603, e.to_string()),
+                                request.id,
+                            ),
+                        }
+                    }
+                    Err(e) => JsonRpcResponse::error(
+                        JsonRpcError::invalid_params(Some(format!(
+                            "Invalid initialize request: {e}"
+                        ))),
+                        request.id,
+                    ),
+                }
+            }
+            "shutdown" => {
+                let shutdown_request = match serde_json::from_value::<
+                    ultrafast_mcp_core::protocol::ShutdownRequest,
+                >(request.params.unwrap_or_default())
+                {
+                    Ok(req) => req,
+                    Err(_) => ultrafast_mcp_core::protocol::ShutdownRequest { reason: None },
+                };
+
+                match self.handle_shutdown(shutdown_request).await {
+                    Ok(_) => JsonRpcResponse::success(serde_json::json!({}), request.id),
+                    Err(e) => {
+                        JsonRpcResponse::error(JsonRpcError::new(-32603, e.to_string()), request.id)
+                    }
+                }
+            }
+
+            // Tools methods
+            "tools/list" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::internal_error(Some("Server not ready".to_string())),
+                        request.id,
+                    );
+                }
+
+                let mut list_request = self.deserialize_list_tools_request(request.params.clone());
+                if let Err(e) = self.open_cursor(&mut list_request.cursor) {
+                    return JsonRpcResponse::error(e, request.id);
+                }
+                // A handler that does not announce list changes may return
+                // different tools at any time
+                let cacheable = list_request.cursor.is_none()
+                    && (self.tool_handler.is_none() || self.handler_list_cacheable("tools/list"));
+                if cacheable {
+                    if let Some(value) = self.list_cache.tools.get().await {
+                        return self.cached_list_response(value, request.id);
+                    }
+                }
+                let generation = self.list_cache.tools.generation();
+
+                let result = match &self.tool_handler {
+                    Some(handler) => match handler.list_tools(list_request).await {
+                        // If handler returns empty tools, fallback to registered tools
+                        Ok(response) if response.tools.is_empty() => {
+                            self.registered_tools_value().await
+                        }
+                        Ok(mut response) => {
+                            self.append_typed_tools(&mut response.tools).await;
+                            self.seal_cursor(&mut response.next_cursor);
+                            serde_json::to_value(response)
+                        }
+                        Err(e) => {
+                            return JsonRpcResponse::error(
+                                self.handler_error(
+                                    &e,
+                                    JsonRpcError::new(-32603, format!("Tools list failed: {e}")),
+                                    locale.as_deref(),
+                                ),
+                                request.id,
+                            );
+                        }
+                    },
+                    // Fallback to registered tools
+                    None => self.registered_tools_value().await,
+                };
+
+                match result {
+                    Ok(value) => {
+                        if cacheable {
+                            self.list_cache.tools.store(generation, &value).await;
+                        }
+                        JsonRpcResponse::success(value, request.id)
+                    }
+                    Err(e) => JsonRpcResponse::error(
+                        JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                        request.id,
+                    ),
+                }
+            }
+            "tools/call" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::internal_error(Some("Server not ready".to_string())),
+                        request.id,
+                    );
+                }
+
+                let params = match &request.params {
+                    Some(params) => params,
+                    None => {
+                        return JsonRpcResponse::error(
+                            JsonRpcError::new(
+                                -32602,
+                                "Tool call failed: Missing parameters".to_string(),
+                            ),
+                            request.id,
+                        );
+                    }
+                };
+
+                let tool_name = params.get("name").and_then(|v| v.as_str());
+                let arguments = params
+                    .get("arguments")
+                    .cloned()
+                    .unwrap_or(serde_json::json!({}));
+
+                if let Some(tool_name) = tool_name {
+                    let definition = if self.strict_schema_validation {
+                        self.find_tool_definition(tool_name).await
+                    } else {
+                        None
+                    };
+                    if let Some(tool) = &definition {
+                        if let Err(e) = ultrafast_mcp_core::schema::validation::validate_tool_input(
+                            &arguments,
+                            &tool.input_schema,
+                        ) {
+                            return JsonRpcResponse::error(
+                                JsonRpcError::invalid_params(Some(format!(
+                                    "Invalid arguments for tool '{tool_name}': {e}"
+                                ))),
+                                request.id,
+                            );
+                        }
+                    }
+
+                    let recorder = Arc::new(ProgressRecorder::new());
+                    if let Some(typed_tool) = self.typed_tools.get(tool_name).await {
+                        let context = self
+                            .create_request_context(&request, peer)
+                            .await
+                            .with_tool_name(tool_name.to_string())
+                            .with_progress_recorder(recorder.clone());
+                        let result = self
+                            .run_tool(tool_name, async move {
+                                typed_tool.call(arguments, context).await
+                            })
+                            .await;
+                        let result = result
+                            .map(|result| recorder.summarize(result))
+                            .and_then(|result| check_tool_output(definition.as_deref(), result));
+                        match result {
+                            Ok(result) => match serde_json::to_value(result) {
+                                Ok(value) => JsonRpcResponse::success(value, request.id),
+                                Err(e) => JsonRpcResponse::error(
+                                    JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                    request.id,
+                                ),
+                            },
+                            Err(e) => JsonRpcResponse::error(
+                                self.tool_call_error(&e, locale.as_deref()),
+                                request.id,
+                            ),
+                        }
+                    } else if let Some(handler) = &self.tool_handler {
+                        let tool_call = ultrafast_mcp_core::types::tools::ToolCall {
+                            name: tool_name.to_string(),
+                            arguments: Some(arguments.clone()),
+                        };
+                        let context = self
+                            .create_request_context(&request, peer)
+                            .await
+                            .with_tool_name(tool_name.to_string())
+                            .with_progress_recorder(recorder.clone());
+                        // Arguments validation will be handled by the tool handler
+                        let handler = handler.clone();
+                        let result = self
+                            .run_tool(tool_name, async move {
+                                handler
+                                    .handle_tool_call_with_context(tool_call, context)
+                                    .await
+                            })
+                            .await;
+                        let result = result
+                            .map(|result| recorder.summarize(result))
+                            .and_then(|result| check_tool_output(definition.as_deref(), result));
+                        match result {
+                            Ok(result) => match serde_json::to_value(result) {
+                                Ok(value) => JsonRpcResponse::success(value, request.id),
+                                Err(e) => JsonRpcResponse::error(
+                                    JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                    request.id,
+                                ),
+                            },
+                            Err(e) => JsonRpcResponse::error(
+                                self.tool_call_error(&e, locale.as_deref()),
+                                request.id,
+                            ),
+                        }
+                    } else {
+                        // Fallback to registered tools
+                        if !self.has_tool(tool_name).await {
+                            return JsonRpcResponse::error(
+                                JsonRpcError::new(
+                                    -32602,
+                                    format!("Tool call failed: Tool not found: {tool_name}"),
+                                ),
+                                request.id,
+                            );
+                        }
+                        // Arguments validation will be handled by the tool handler
+                        let result = self
+                            .execute_tool_call(tool_name, arguments)
+                            .await
+                            .and_then(|result| check_tool_output(definition.as_deref(), result));
+                        match result {
+                            Ok(result) => match serde_json::to_value(result) {
+                                Ok(value) => JsonRpcResponse::success(value, request.id),
+                                Err(e) => JsonRpcResponse::error(
+                                    JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                    request.id,
+                                ),
+                            },
+                            Err(e) => JsonRpcResponse::error(
+                                self.tool_call_error(&e, locale.as_deref()),
+                                request.id,
+                            ),
+                        }
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        JsonRpcError::new(
+                            -32602,
+                            "Tool call failed: Missing or invalid tool name".to_string(),
+                        ),
+                        request.id,
+                    )
+                }
+            }
+
+            // Resources methods
+            "resources/list" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let mut list_request =
+                    self.deserialize_list_resources_request(request.params.clone());
+                if let Err(e) = self.open_cursor(&mut list_request.cursor) {
+                    return JsonRpcResponse::error(e, request.id);
+                }
+
+                if let Some(handler) = &self.resource_handler {
+                    // For resources/list, we don't validate against roots since it's a general listing
+                    // Root validation will be done when individual resources are accessed
+                    let cacheable = list_request.cursor.is_none()
+                        && self.handler_list_cacheable("resources/list");
+                    if cacheable {
+                        if let Some(value) = self.list_cache.resources.get().await {
+                            return self.cached_list_response(value, request.id);
+                        }
+                    }
+                    let generation = self.list_cache.resources.generation();
+
+                    let result = handler
+                        .list_resources(list_request)
+                        .await
+                        .map(|mut response| {
+                            self.seal_cursor(&mut response.next_cursor);
+                            response
+                        });
+                    match result {
+                        Ok(response) => match serde_json::to_value(response) {
+                            Ok(value) => {
+                                if cacheable {
+                                    self.list_cache.resources.store(generation, &value).await;
+                                }
+                                JsonRpcResponse::success(value, request.id)
+                            }
+                            Err(e) => JsonRpcResponse::error(
+                                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                request.id,
+                            ),
+                        },
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Resources list failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "resources"),
+                        request.id,
+                    )
+                }
+            }
+            "resources/read" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let read_request = self.deserialize_read_resource_request(request.params.clone());
+
+                if let Some(handler) = &self.resource_handler {
+                    if let Err(error) = self
+                        .check_read_roots(handler.as_ref(), &read_request.uri)
+                        .await
+                    {
+                        return JsonRpcResponse::error(error, request.id);
+                    }
+
+                    match resource_stream::read_range(handler.as_ref(), read_request).await {
+                        Ok(response) => match serde_json::to_value(response) {
+                            Ok(value) => JsonRpcResponse::success(value, request.id),
+                            Err(e) => JsonRpcResponse::error(
+                                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                request.id,
+                            ),
+                        },
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Resource read failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "resources"),
+                        request.id,
+                    )
+                }
+            }
+            // Vendor extension sending a resource's bytes in chunks
+            READ_RESOURCE_STREAM_METHOD => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+                let Some(handler) = &self.resource_handler else {
+                    return JsonRpcResponse::error(
+                        self.unsupported(&request.method, "resources"),
+                        request.id,
+                    );
+                };
+                let stream_request =
+                    match resource_stream::parse_stream_request(request.params.clone()) {
+                        Ok(stream_request) => stream_request,
+                        Err(e) => {
+                            return JsonRpcResponse::error(
+                                JsonRpcError::invalid_params(Some(e.to_string())),
+                                request.id,
+                            );
+                        }
+                    };
+                if let Err(error) = self
+                    .check_read_roots(handler.as_ref(), &stream_request.uri)
+                    .await
+                {
+                    return JsonRpcResponse::error(error, request.id);
+                }
+
+                let context = self.create_request_context(&request, peer).await;
+                match resource_stream::stream_resource(handler.as_ref(), stream_request, &context)
+                    .await
+                {
+                    Ok(response) => JsonRpcResponse::success(
+                        serde_json::to_value(response).unwrap(),
+                        request.id,
+                    ),
+                    Err(e) => JsonRpcResponse::error(
+                        self.handler_error(
+                            &e,
+                            JsonRpcError::new(-32603, format!("Resource read failed: {e}")),
+                            locale.as_deref(),
+                        ),
+                        request.id,
+                    ),
+                }
+            }
+            "resources/templates/list" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let mut list_request =
+                    self.deserialize_list_resource_templates_request(request.params.clone());
+                if let Err(e) = self.open_cursor(&mut list_request.cursor) {
+                    return JsonRpcResponse::error(e, request.id);
+                }
+
+                if let Some(handler) = &self.resource_handler {
+                    let result =
+                        handler
+                            .list_resource_templates(list_request)
+                            .await
+                            .map(|mut response| {
+                                self.seal_cursor(&mut response.next_cursor);
+                                response
+                            });
+                    match result {
+                        Ok(response) => JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        ),
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(
+                                    -32603,
+                                    format!("Resource templates list failed: {e}"),
+                                ),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "resources"),
+                        request.id,
+                    )
+                }
+            }
+            "resources/subscribe" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let subscribe_request = self.deserialize_subscribe_request(request.params.clone());
+
+                // Validate against roots if roots handler is available
+                if let Some(roots_handler) = &self.roots_handler {
+                    if let Some(resource_handler) = &self.resource_handler {
+                        match roots_handler.list_roots().await {
+                            Ok(roots) => {
+                                if let Err(e) = resource_handler
+                                    .validate_resource_access(
+                                        &subscribe_request.uri,
+                                        ultrafast_mcp_core::types::roots::RootOperation::Read,
+                                        &roots,
+                                    )
+                                    .await
+                                {
+                                    return JsonRpcResponse::error(
+                                        JsonRpcError::new(
+                                            -32603,
+                                            format!("Root validation failed: {e}"),
+                                        ),
+                                        request.id,
+                                    );
+                                }
+                            }
+                            Err(e) => {
+                                return JsonRpcResponse::error(
+                                    JsonRpcError::new(-32603, format!("Failed to get roots: {e}")),
+                                    request.id,
+                                );
+                            }
+                        }
+                    }
+                }
+
+                let session = subscription_session(peer);
+                if let Some(handler) = &self.subscription_handler {
+                    match handler.subscribe(subscribe_request.uri.clone()).await {
+                        Ok(_) => {
+                            self.subscriptions
+                                .subscribe(session, &subscribe_request.uri);
+                            // Subscription successful - return success response
+                            // Note: The client may timeout if it expects immediate notifications
+                            // This is a limitation of the current MCP architecture
+                            JsonRpcResponse::success(
+                                serde_json::to_value(SubscribeResponse::new()).unwrap(),
+                                request.id,
+                            )
+                        }
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(
+                                    -32603,
+                                    format!("Resource subscribe failed: {e}"),
+                                ),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else if self.supports_resource_subscriptions() {
+                    self.subscriptions
+                        .subscribe(session, &subscribe_request.uri);
+                    JsonRpcResponse::success(
+                        serde_json::to_value(SubscribeResponse::new()).unwrap(),
+                        request.id,
+                    )
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "resources.subscribe"),
+                        request.id,
+                    )
+                }
+            }
+            "resources/unsubscribe" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let unsubscribe_request =
+                    self.deserialize_unsubscribe_request(request.params.clone());
+
+                let session = subscription_session(peer);
+                if let Some(handler) = &self.subscription_handler {
+                    match handler.unsubscribe(unsubscribe_request.uri.clone()).await {
+                        Ok(_) => {
+                            self.subscriptions
+                                .unsubscribe(session, &unsubscribe_request.uri);
+                            JsonRpcResponse::success(serde_json::Value::Null, request.id)
+                        }
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(
+                                    -32603,
+                                    format!("Resource unsubscribe failed: {e}"),
+                                ),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else if self.supports_resource_subscriptions() {
+                    self.subscriptions
+                        .unsubscribe(session, &unsubscribe_request.uri);
+                    JsonRpcResponse::success(serde_json::Value::Null, request.id)
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "resources.subscribe"),
+                        request.id,
+                    )
+                }
+            }
+
+            // Prompts methods
+            "prompts/list" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let mut list_request =
+                    self.deserialize_list_prompts_request(request.params.clone());
+                if let Err(e) = self.open_cursor(&mut list_request.cursor) {
+                    return JsonRpcResponse::error(e, request.id);
+                }
+
+                if let Some(handler) = &self.prompt_handler {
+                    let cacheable = list_request.cursor.is_none()
+                        && self.handler_list_cacheable("prompts/list");
+                    if cacheable {
+                        if let Some(value) = self.list_cache.prompts.get().await {
+                            return self.cached_list_response(value, request.id);
+                        }
+                    }
+                    let generation = self.list_cache.prompts.generation();
+
+                    let result = handler
+                        .list_prompts(list_request)
+                        .await
+                        .map(|mut response| {
+                            self.seal_cursor(&mut response.next_cursor);
+                            response
+                        });
+                    match result {
+                        Ok(response) => match serde_json::to_value(response) {
+                            Ok(value) => {
+                                if cacheable {
+                                    self.list_cache.prompts.store(generation, &value).await;
+                                }
+                                JsonRpcResponse::success(value, request.id)
+                            }
+                            Err(e) => JsonRpcResponse::error(
+                                JsonRpcError::new(-32603, format!("Serialization error: {e}")),
+                                request.id,
+                            ),
+                        },
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Prompts list failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(self.unsupported(&request.method, "prompts"), request.id)
+                }
+            }
+            "prompts/get" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let get_request = self.deserialize_get_prompt_request(request.params.clone());
+
+                if let Some(handler) = &self.prompt_handler {
+                    match handler.get_prompt(get_request).await {
+                        Ok(response) => JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        ),
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Prompt get failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(self.unsupported(&request.method, "prompts"), request.id)
+                }
+            }
+
+            // Completion methods
+            "completion/complete" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let complete_request = self.deserialize_complete_request(request.params.clone());
+
+                let template_completer = (complete_request.reference.ref_type == "ref/resource")
+                    .then(|| {
+                        self.template_completers
+                            .get(&complete_request.reference.name)
+                    })
+                    .flatten();
+                let prompt_registry = (complete_request.reference.ref_type == "ref/prompt")
+                    .then_some(self.prompt_registry.as_ref())
+                    .flatten()
+                    .filter(|registry| registry.contains(&complete_request.reference.name));
+                let completed = if let Some(completer) = template_completer {
+                    Some(
+                        Self::complete_template_variable(
+                            completer.as_ref(),
+                            complete_request.clone(),
+                        )
+                        .await,
+                    )
+                } else if let Some(registry) = prompt_registry {
+                    Some(registry.complete(complete_request.clone()).await)
+                } else {
+                    None
+                };
+                if let Some(completed) = completed {
+                    match completed {
+                        Ok(response) => JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        ),
+                        Err(e @ MCPError::Protocol(ProtocolError::InvalidParams(_))) => {
+                            JsonRpcResponse::error(
+                                JsonRpcError::new(-32602, format!("Completion failed: {e}")),
+                                request.id,
+                            )
+                        }
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Completion failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else if let Some(handler) = &self.completion_handler {
+                    match handler.complete(complete_request).await {
+                        Ok(response) => JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        ),
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Completion failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "completion"),
+                        request.id,
+                    )
+                }
+            }
+
+            // Sampling methods
+            "sampling/createMessage" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let create_request =
+                    self.deserialize_create_message_request(request.params.clone());
+
+                if let Some(handler) = &self.sampling_handler {
+                    match handler.create_message(create_request.clone()).await {
+                        Ok(response) => {
+                            self.usage_tracker.record(
+                                peer.and_then(|peer| peer.session_id()),
+                                None,
+                                &create_request,
+                                &response,
+                            );
+                            JsonRpcResponse::success(
+                                serde_json::to_value(response).unwrap(),
+                                request.id,
+                            )
+                        }
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Message creation failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "sampling"),
+                        request.id,
+                    )
+                }
+            }
+
+            // Roots methods
+            "roots/list" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                if let Some(handler) = &self.roots_handler {
+                    match handler.list_roots().await {
+                        Ok(response) => JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        ),
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Roots list failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(self.unsupported(&request.method, "roots"), request.id)
+                }
+            }
+
+            // Elicitation methods
+            "elicitation/create" => {
+                if !self.can_operate().await {
+                    return JsonRpcResponse::error(
+                        JsonRpcError::new(-32000, "Server not ready".to_string()),
+                        request.id,
+                    );
+                }
+
+                let elicitation_request =
+                    self.deserialize_elicitation_request(request.params.clone());
+
+                if let Some(handler) = &self.elicitation_handler {
+                    match handler.handle_elicitation(elicitation_request).await {
+                        Ok(response) => JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        ),
+                        Err(e) => JsonRpcResponse::error(
+                            self.handler_error(
+                                &e,
+                                JsonRpcError::new(-32603, format!("Elicitation failed: {e}")),
+                                locale.as_deref(),
+                            ),
+                            request.id,
+                        ),
+                    }
+                } else {
+                    JsonRpcResponse::error(
+                        self.unsupported(&request.method, "elicitation"),
+                        request.id,
+                    )
+                }
+            }
+
+            // Logging methods
+            "logging/setLevel" => {
+                let params = match &request.params {
+                    Some(params) => params,
+                    None => {
+                        return JsonRpcResponse::error(
+                            JsonRpcError::new(-32602, "Missing parameters".to_string()),
+                            request.id,
+                        );
+                    }
+                };
+
+                match serde_json::from_value::<LogLevelSetRequest>(params.clone()) {
+                    Ok(set_request) => match self
+                        .set_session_log_level(subscription_session(peer), set_request.level)
+                        .await
+                    {
+                        Ok(()) => {
+                            let response = LogLevelSetResponse::new();
+                            JsonRpcResponse::success(
+                                serde_json::to_value(response).unwrap(),
+                                request.id,
+                            )
+                        }
+                        Err(e) => JsonRpcResponse::error(
+                            JsonRpcError::new(-32603, format!("Failed to set log level: {e}")),
+                            request.id,
+                        ),
+                    },
+                    Err(e) => JsonRpcResponse::error(
+                        JsonRpcError::new(-32602, format!("Invalid log level set request: {e}")),
+                        request.id,
+                    ),
+                }
+            }
+
+            // Ping method for connection health monitoring
+            "ping" => {
+                let ping_request = match serde_json::from_value::<
+                    ultrafast_mcp_core::types::notifications::PingRequest,
+                >(request.params.unwrap_or_default())
+                {
+                    Ok(req) => req,
+                    Err(_) => ultrafast_mcp_core::types::notifications::PingRequest { data: None },
+                };
+
+                match self.ping_manager.handle_ping(ping_request).await {
+                    Ok(response) => JsonRpcResponse::success(
+                        serde_json::to_value(response).unwrap(),
+                        request.id,
+                    ),
+                    Err(e) => JsonRpcResponse::error(
+                        self.handler_error(
+                            &e,
+                            JsonRpcError::new(-32603, format!("Ping failed: {e}")),
+                            locale.as_deref(),
+                        ),
+                        request.id,
+                    ),
+                }
+            }
+
+            // Vendor extension describing the running build, answered in any
+            // state so it works against servers stuck before initialization
+            INFO_METHOD => JsonRpcResponse::success(
+                serde_json::to_value(self.build_info()).unwrap(),
+                request.id,
+            ),
+
+            // Vendor extension reporting sampling usage, enabled with
+            // `with_usage_report`
+            "usage/report" if self.usage_report_enabled => JsonRpcResponse::success(
+                serde_json::to_value(self.usage_tracker.report()).unwrap(),
+                request.id,
+            ),
+
+            // Roots methods
+            "roots/set" => {
+                let params = match &request.params {
+                    Some(params) => params,
+                    None => {
+                        return JsonRpcResponse::error(
+                            JsonRpcError::new(-32602, "Missing parameters".to_string()),
+                            request.id,
+                        );
+                    }
+                };
+
+                match serde_json::from_value::<SetRootsRequest>(params.clone()) {
+                    Ok(set_request) => {
+                        let response = self
+                            .handle_set_roots(
+                                set_request.roots,
+                                &mut Box::new(
+                                    create_transport(TransportConfig::Stdio).await.unwrap(),
+                                ),
+                            )
+                            .await;
+                        JsonRpcResponse::success(
+                            serde_json::to_value(response).unwrap(),
+                            request.id,
+                        )
+                    }
+                    Err(e) => JsonRpcResponse::error(
+                        JsonRpcError::new(-32602, format!("Invalid roots set request: {e}")),
+                        request.id,
+                    ),
+                }
+            }
+
+            // Unknown method
+            _ => JsonRpcResponse::error(
+                JsonRpcError::new(
+                    -32601,
+                    format!("Method not implemented: {}", request.method),
+                ),
+                request.id,
+            ),
+        }
+    }
+
+    /// Handle incoming notifications
+    async fn handle_notification(&self, notification: JsonRpcRequest) -> MCPResult<()> {
+        #[cfg(not(feature = "bare-metal"))]
+        info!("Handling notification: {}", notification.method);
+
+        match notification.method.as_str() {
+            "initialized" => {
+                let notification = ultrafast_mcp_core::protocol::InitializedNotification {};
+                self.handle_initialized(notification).await?;
+                Ok(())
+            }
+            "notifications/cancelled" => {
+                // Handle cancellation notification
+                if let Some(params) = notification.params {
+                    let cancellation_notification: ultrafast_mcp_core::types::notifications::CancelledNotification =
+                        serde_json::from_value(params)?;
+
+                    // Only requests still in flight are marked, so the
+                    // handler's context observes the cancellation
+                    let request_id = serde_json::from_value::<RequestId>(
+                        cancellation_notification.request_id.clone(),
+                    );
+                    let cancelled = self
+                        .cancellation_manager
+                        .handle_cancellation(cancellation_notification)
+                        .await?;
+                    if let (true, Ok(request_id)) = (cancelled, request_id) {
+                        self.request_cancellations
+                            .cancel_request(&request_id.to_string())
+                            .await;
+                    }
+                    info!("Cancellation notification processed");
+                }
+                Ok(())
+            }
+            _ => {
+                warn!("Unknown notification method: {}", notification.method);
+                Ok(())
+            }
+        }
+    }
+
+    // ===== NOTIFICATION METHODS =====
+
+    /// Tell every connected session that the tool list changed
+    ///
+    /// Debounced, see [`list_changed`](crate::list_changed).
+    pub async fn notify_tools_changed(&self) -> MCPResult<()> {
+        self.notify_list_changed(ListKind::Tools, None).await
+    }
+
+    /// Tell every connected session that the resource list changed
+    ///
+    /// Debounced, see [`list_changed`](crate::list_changed).
+    pub async fn notify_resources_changed(&self) -> MCPResult<()> {
+        self.notify_list_changed(ListKind::Resources, None).await
+    }
+
+    /// Tell every connected session that the prompt list changed
+    ///
+    /// Debounced, see [`list_changed`](crate::list_changed).
+    pub async fn notify_prompts_changed(&self) -> MCPResult<()> {
+        self.notify_list_changed(ListKind::Prompts, None).await
+    }
+
+    /// Tell one session, or every connected session with `None`, that
+    /// `list` changed
+    ///
+    /// Cached list responses are dropped right away; the notification is
+    /// sent once the debounce window ends.
+    pub async fn notify_list_changed(
+        &self,
+        list: ListKind,
+        session_id: Option<&str>,
+    ) -> MCPResult<()> {
+        match list {
+            ListKind::Tools => self.list_cache.tools.invalidate().await,
+            ListKind::Resources => self.list_cache.resources.invalidate().await,
+            ListKind::Prompts => self.list_cache.prompts.invalidate().await,
+        }
+        let params = list.notification_params()?;
+        if self.list_changes.window.is_zero() {
+            self.send_list_changed(list, session_id, &params);
+            return Ok(());
+        }
+        if !self.list_changes.hold(list, session_id) {
+            return Ok(());
+        }
+        let server = self.clone();
+        let session_id = session_id.map(str::to_string);
+        tokio::spawn(async move {
+            tokio::time::sleep(server.list_changes.window).await;
+            server.list_changes.release(list, session_id.as_deref());
+            let notified = server.send_list_changed(list, session_id.as_deref(), &params);
+            debug!(
+                "Sent {} to {} sessions",
+                list.notification_method(),
+                notified
+            );
+        });
+        Ok(())
+    }
+
+    /// Send a list changed notification now; returns how many sessions got it
+    fn send_list_changed(
+        &self,
+        list: ListKind,
+        session_id: Option<&str>,
+        params: &serde_json::Value,
+    ) -> usize {
+        self.live_peers()
+            .iter()
+            .filter(|peer| session_id.is_none_or(|id| subscription_session(Some(peer)) == id))
+            .filter(|peer| {
+                peer.send_notification(list.notification_method(), Some(params.clone()))
+                    .is_ok()
+            })
+            .count()
+    }
+
+    /// Tell every connected session subscribed to `uri` that it changed
+    ///
+    /// The [`ResourceSubscriptionHandler`], if any, is given the new
+    /// `contents` first. Returns how many sessions were notified.
+    pub async fn notify_resource_updated(
+        &self,
+        uri: impl Into<String>,
+        contents: serde_json::Value,
+    ) -> MCPResult<usize> {
+        let uri = uri.into();
+        if let Some(handler) = &self.subscription_handler {
+            handler.notify_change(uri.clone(), contents).await?;
+        }
+
+        let subscribers = self.subscriptions.subscribers(&uri);
+        if subscribers.is_empty() {
+            return Ok(0);
+        }
+        let params = serde_json::to_value(
+            ultrafast_mcp_core::types::resources::ResourceUpdatedNotification { uri },
+        )?;
+        let mut notified = 0;
+        for peer in self.live_peers() {
+            if subscribers.contains(subscription_session(Some(&peer)))
+                && peer
+                    .send_notification("notifications/resources/updated", Some(params.clone()))
+                    .is_ok()
+            {
+                notified += 1;
+            }
+        }
+        Ok(notified)
+    }
+
+    /// Tell every connected session that the resource list changed, right
+    /// away rather than debounced
+    ///
+    /// Returns how many sessions were notified.
+    pub async fn notify_resource_list_changed(&self) -> MCPResult<usize> {
+        self.list_cache.resources.invalidate().await;
+        let params = ListKind::Resources.notification_params()?;
+        Ok(self.send_list_changed(ListKind::Resources, None, &params))
+    }
+
+    /// Send progress notification
+    pub async fn notify_progress(
+        &self,
+        progress_token: serde_json::Value,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+        transport: &mut Box<dyn Transport>,
+    ) -> MCPResult<()> {
+        let mut notification = ultrafast_mcp_core::types::notifications::ProgressNotification::new(
+            progress_token,
+            progress,
+        );
+        if let Some(total) = total {
+            notification = notification.with_total(total);
+        }
+        if let Some(message) = message {
+            notification = notification.with_message(message);
+        }
+        self.send_notification(
+            "notifications/progress",
+            Some(serde_json::to_value(notification)?),
+            transport,
+        )
+        .await
+    }
+
+    /// Send logging message notification
+    pub async fn notify_logging_message(
+        &self,
+        level: ultrafast_mcp_core::types::notifications::LogLevel,
+        data: serde_json::Value,
+        logger: Option<String>,
+        transport: &mut Box<dyn Transport>,
+    ) -> MCPResult<()> {
+        let mut notification =
+            ultrafast_mcp_core::types::notifications::LoggingMessageNotification::new(level, data);
+        if let Some(logger) = logger {
+            notification = notification.with_logger(logger);
+        }
+        self.send_notification(
+            "notifications/message",
+            Some(serde_json::to_value(notification)?),
+            transport,
+        )
+        .await
+    }
+
+    /// Send cancellation notification
+    pub async fn notify_cancelled(
+        &self,
+        request_id: serde_json::Value,
+        reason: Option<String>,
+        transport: &mut Box<dyn Transport>,
+    ) -> MCPResult<()> {
+        let mut notification =
+            ultrafast_mcp_core::types::notifications::CancelledNotification::new(request_id);
+        if let Some(reason) = reason {
+            notification = notification.with_reason(reason);
+        }
+        self.send_notification(
+            "notifications/cancelled",
+            Some(serde_json::to_value(notification)?),
+            transport,
+        )
+        .await
+    }
+
+    /// Send roots list changed notification
+    pub async fn notify_roots_changed(&self, transport: &mut Box<dyn Transport>) -> MCPResult<()> {
+        let notification =
+            ultrafast_mcp_core::types::notifications::RootsListChangedNotification::new();
+        self.send_notification(
+            "notifications/roots/listChanged",
+            Some(serde_json::to_value(notification)?),
+            transport,
+        )
+        .await
+    }
+
+    /// Generic method to send notifications
+    async fn send_notification(
+        &self,
+        method: &str,
+        mut params: Option<serde_json::Value>,
+        transport: &mut Box<dyn Transport>,
+    ) -> MCPResult<()> {
+        if self.notification_sequencing {
+            let sequence = self.notification_sequence.fetch_add(1, Ordering::Relaxed) + 1;
+            ultrafast_mcp_core::types::notifications::set_notification_sequence(
+                &mut params,
+                sequence,
+            );
+        }
+        let notification = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: None, // Notifications have no ID
+            method: method.to_string(),
+            params,
+            meta: std::collections::HashMap::new(),
+        };
+
+        transport
+            .send_message(JsonRpcMessage::Request(notification))
+            .await
+            .map_err(|e| MCPError::internal_error(format!("Failed to send notification: {e}")))?;
+
+        #[cfg(not(feature = "bare-metal"))]
+        info!("Sent notification: {}", method);
+        Ok(())
+    }
+
+    /// Set the advanced sampling handler for context collection and human-in-the-loop features
+    pub fn with_advanced_sampling_handler(
+        mut self,
+        handler: Arc<dyn AdvancedSamplingHandler>,
+    ) -> Self {
+        self.advanced_sampling_handler = Some(handler);
+        self
+    }
+
+    /// Set the advanced sampling handler with default implementation
+    pub fn with_default_advanced_sampling(mut self) -> Self {
+        let default_handler = Arc::new(DefaultAdvancedSamplingHandler::new(self.info.clone()));
+        self.advanced_sampling_handler = Some(default_handler);
+        self
+    }
+
+    /// Handle a roots/set request
+    pub async fn handle_set_roots(
+        &self,
+        roots: Vec<ultrafast_mcp_core::types::roots::Root>,
+        transport: &mut Box<dyn Transport>,
+    ) -> SetRootsResponse {
+        if let Some(handler) = &self.roots_handler {
+            match handler.set_roots(roots.clone()).await {
+                Ok(_) => {
+                    // Send notification to all clients (for demo, just send to this transport)
+                    let notification = RootsListChangedNotification { roots };
+                    let params = serde_json::to_value(notification).ok();
+                    let _ = self
+                        .send_notification("roots/listChanged", params, transport)
+                        .await;
+                    SetRootsResponse {
+                        success: true,
+                        error: None,
+                    }
+                }
+                Err(e) => SetRootsResponse {
+                    success: false,
+                    error: Some(e.to_string()),
+                },
+            }
+        } else {
+            SetRootsResponse {
+                success: false,
+                error: Some("Roots handler not available".to_string()),
+            }
+        }
+    }
+}
+
+/// Check a successful tool result against the tool's output schema
+///
+/// The output is the result's structured content or, for results without
+/// any, the JSON value of its first text content. Results without a
+/// definition or output schema and error results pass unchanged.
+fn check_tool_output(
+    tool: Option<&Tool>,
+    result: ultrafast_mcp_core::types::tools::ToolResult,
+) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+    let Some(schema) = tool.and_then(|tool| tool.output_schema.as_ref()) else {
+        return Ok(result);
+    };
+    if result.is_error == Some(true) {
+        return Ok(result);
+    }
+    let output = match &result.structured_content {
+        Some(structured) => structured.clone(),
+        None => result
+            .content
+            .iter()
+            .find_map(|content| match content {
+                ultrafast_mcp_core::types::tools::ToolContent::Text { text } => {
+                    serde_json::from_str::<serde_json::Value>(text).ok()
+                }
+                _ => None,
+            })
+            .ok_or_else(|| MCPError::internal_error("Tool output is not JSON".to_string()))?,
+    };
+    ultrafast_mcp_core::schema::validation::validate_tool_output(&output, schema).map_err(|e| {
+        MCPError::internal_error(format!("Tool output does not match its output schema: {e}"))
+    })?;
+    Ok(result)
+}
+
+/// The session a subscription request is recorded under
+fn subscription_session(peer: Option<&Arc<ClientPeer>>) -> &str {
+    peer.and_then(|peer| peer.session_id())
+        .unwrap_or(DEFAULT_SUBSCRIPTION_SESSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use ultrafast_mcp_core::types::{
+        server::ServerInfo,
+        tools::{Tool, ToolContent},
+    };
+
+    // Mock tool handler for testing
+    struct MockToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for MockToolHandler {
+        async fn handle_tool_call(
+            &self,
+            call: ultrafast_mcp_core::types::tools::ToolCall,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+            // Simulate error for nonexistent tool or invalid arguments
+            if call.name == "nonexistent_tool" {
+                return Err(ultrafast_mcp_core::error::MCPError::not_found(
+                    "Tool not found".to_string(),
+                ));
+            }
+            if let Some(args) = &call.arguments {
+                if args.get("input").is_none() {
+                    return Err(ultrafast_mcp_core::error::MCPError::invalid_params(
+                        "Invalid parameters".to_string(),
+                    ));
+                }
+            } else {
+                return Err(ultrafast_mcp_core::error::MCPError::invalid_params(
+                    "Missing arguments".to_string(),
+                ));
+            }
+            Ok(ultrafast_mcp_core::types::tools::ToolResult {
+                content: vec![ToolContent::text(format!("Mock result for {}", call.name))],
+                is_error: None,
+                structured_content: None,
+                progress_summary: None,
+            })
+        }
+
+        async fn list_tools(
+            &self,
+            _request: ultrafast_mcp_core::types::tools::ListToolsRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ListToolsResponse> {
+            // This will be overridden by the server's fallback to registered tools
+            Ok(ultrafast_mcp_core::types::tools::ListToolsResponse {
+                tools: vec![],
+                next_cursor: None,
+            })
+        }
+    }
+
+    fn create_test_server() -> UltraFastServer {
+        let info = ServerInfo {
+            name: "test-server".to_string(),
+            version: "1.0.0".to_string(),
+            description: Some("Test server".to_string()),
+            homepage: None,
+            repository: None,
+            authors: Some(vec!["test".to_string()]),
+            license: Some("MIT".to_string()),
+        };
+        let capabilities = ServerCapabilities::default();
+        UltraFastServer::new(info, capabilities).with_tool_handler(Arc::new(MockToolHandler))
+    }
+
+    async fn create_initialized_test_server() -> UltraFastServer {
+        let server = create_test_server();
+
+        // Initialize the server to operating state
+        let init_request = ultrafast_mcp_core::protocol::InitializeRequest {
+            protocol_version: "2025-06-18".to_string(),
+            capabilities: ultrafast_mcp_core::protocol::ClientCapabilities::default(),
+            client_info: ultrafast_mcp_core::types::client::ClientInfo {
+                name: "test-client".to_string(),
+                version: "1.0.0".to_string(),
+                description: Some("Test client".to_string()),
+                homepage: None,
+                repository: None,
+                authors: Some(vec!["test".to_string()]),
+                license: Some("MIT".to_string()),
+            },
+        };
+
+        let _response = server.handle_initialize(init_request).await;
+
+        // Send initialized notification
+        let notification = ultrafast_mcp_core::protocol::InitializedNotification {};
+        let _ = server.handle_initialized(notification).await;
+
+        server
+    }
+
+    fn create_valid_tool(name: &str) -> Tool {
+        Tool {
+            name: name.to_string(),
+            description: "A test tool".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "input": {"type": "string"}
+                },
+                "required": ["input"]
+            }),
+            output_schema: Some(json!({
+                "type": "object",
+                "properties": {
+                    "output": {"type": "string"}
+                }
+            })),
+            annotations: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_valid_tool() {
+        let server = create_test_server();
+        let tool = create_valid_tool("test_tool");
+
+        let result = server.register_tool(tool).await;
+        assert!(result.is_ok());
+
+        assert!(server.has_tool("test_tool").await);
+        assert_eq!(server.tool_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_register_duplicate_tool() {
+        let server = create_test_server();
+        let tool1 = create_valid_tool("test_tool");
+        let tool2 = create_valid_tool("test_tool");
+
+        server.register_tool(tool1).await.unwrap();
+        let result = server.register_tool(tool2).await;
+
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::ToolAlreadyExists(_))
+        ));
+        assert_eq!(server.tool_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_register_reserved_name() {
+        let server = create_test_server();
+        let tool = create_valid_tool("initialize");
+
+        let result = server.register_tool(tool).await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::ReservedName(_))
+        ));
+        assert_eq!(server.tool_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_register_tool_without_description() {
+        let server = create_test_server();
+        let mut tool = create_valid_tool("test_tool");
+        tool.description = "".to_string();
+
+        let result = server.register_tool(tool).await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::MissingDescription)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_register_tool_with_invalid_input_schema() {
+        let server = create_test_server();
+        let mut tool = create_valid_tool("test_tool");
+        tool.input_schema = json!("invalid schema");
+
+        let result = server.register_tool(tool).await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::InvalidSchema(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_register_tool_without_output_schema() {
+        let server = create_test_server();
+        let mut tool = create_valid_tool("test_tool");
+        tool.output_schema = None;
+
+        let result = server.register_tool(tool).await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::MissingOutputSchema)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_register_tool_rejects_denied_lint_issues() {
+        use ultrafast_mcp_core::schema::{LintLevel, SchemaLintRule};
+
+        let server = create_test_server().with_schema_lint(
+            SchemaLintConfig::default()
+                .with_max_schema_size(256)
+                .with_level(SchemaLintRule::UnsupportedKeyword, LintLevel::Deny),
+        );
+        let mut large = create_valid_tool("large");
+        large.input_schema["properties"]["input"]["description"] = json!("x".repeat(300));
+        let Err(ToolRegistrationError::SchemaLint(report)) = server.register_tool(large).await
+        else {
+            panic!("expected the oversized schema to be rejected");
+        };
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.issues[0].path, "/inputSchema");
+
+        let mut conditional = create_valid_tool("conditional");
+        conditional.input_schema["if"] = json!({"required": ["input"]});
+        assert!(matches!(
+            server.register_tool(conditional).await,
+            Err(ToolRegistrationError::SchemaLint(_))
+        ));
+
+        // Warnings are reported, but do not keep tools out
+        let mut undescribed = create_valid_tool("undescribed");
+        undescribed.input_schema["properties"]["input"]["description"] = json!("Text");
+        undescribed.input_schema["properties"]["extra"] = json!({"type": "string"});
+        server.register_tool(undescribed).await.unwrap();
+        let report = server.lint_tools().await.unwrap();
+        assert!(!report.has_errors());
+        assert_eq!(
+            report
+                .warnings()
+                .map(|issue| issue.path.as_str())
+                .collect::<Vec<_>>(),
+            ["/inputSchema/properties/extra"]
+        );
+    }
+
+    /// Lists one page of tools after another, each pointing at the same next
+    /// page, or fails
+    struct PagingToolHandler {
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl ToolHandler for PagingToolHandler {
+        async fn handle_tool_call(
+            &self,
+            _call: ultrafast_mcp_core::types::tools::ToolCall,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+            unreachable!("only tools are listed")
+        }
+
+        async fn list_tools(
+            &self,
+            _request: ultrafast_mcp_core::types::tools::ListToolsRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ListToolsResponse> {
+            if self.fail {
+                return Err(MCPError::internal_error("listing failed".to_string()));
+            }
+            Ok(ultrafast_mcp_core::types::tools::ListToolsResponse {
+                tools: vec![create_valid_tool("paged")],
+                next_cursor: Some("page-2".to_string()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lint_tools_reports_handler_listing_failures() {
+        let server =
+            create_test_server().with_tool_handler(Arc::new(PagingToolHandler { fail: false }));
+        let error = server.lint_tools().await.unwrap_err();
+        assert!(error.to_string().contains("repeated the cursor page-2"));
+
+        let server =
+            create_test_server().with_tool_handler(Arc::new(PagingToolHandler { fail: true }));
+        let error = server.lint_tools().await.unwrap_err();
+        assert!(error.to_string().contains("listing failed"));
+    }
+
+    #[tokio::test]
+    async fn test_register_tool_with_invalid_schema() {
+        let server = create_test_server();
+        let mut tool = create_valid_tool("test_tool");
+        tool.input_schema = json!("invalid schema");
+
+        let result = server.register_tool(tool).await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::InvalidSchema(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unregister_tool() {
+        let server = create_test_server();
+        let tool = create_valid_tool("test_tool");
+
+        server.register_tool(tool).await.unwrap();
+        assert!(server.has_tool("test_tool").await);
+
+        let result = server.unregister_tool("test_tool");
+        assert!(result.await);
+        assert!(!server.has_tool("test_tool").await);
+        assert_eq!(server.tool_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unregister_nonexistent_tool() {
+        let server = create_test_server();
+        let result = server.unregister_tool("nonexistent");
+        assert!(!result.await);
+    }
+
+    #[tokio::test]
+    async fn test_register_multiple_tools() {
+        let server = create_test_server();
+        let tools = vec![
+            create_valid_tool("tool1"),
+            create_valid_tool("tool2"),
+            create_valid_tool("tool3"),
+        ];
+
+        let result = server.register_tools(tools).await;
+        assert!(result.is_ok());
+        assert_eq!(server.tool_count().await, 3);
+        assert!(server.has_tool("tool1").await);
+        assert!(server.has_tool("tool2").await);
+        assert!(server.has_tool("tool3").await);
+    }
+
+    #[tokio::test]
+    async fn test_register_multiple_tools_with_duplicate() {
+        let server = create_test_server();
+        let tools = vec![
+            create_valid_tool("tool1"),
+            create_valid_tool("tool1"), // Duplicate
+            create_valid_tool("tool2"),
+        ];
+
+        let result = server.register_tools(tools).await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::ToolAlreadyExists(_))
+        ));
+        assert_eq!(server.tool_count().await, 1); // Only the first one should be registered
+    }
+
+    #[tokio::test]
+    async fn test_get_tool() {
+        let server = create_test_server();
+        let tool = create_valid_tool("test_tool");
+
+        server.register_tool(tool.clone()).await.unwrap();
+
+        let retrieved = server.get_tool("test_tool").await;
+        assert!(retrieved.is_some());
+        assert_eq!(retrieved.unwrap().name, tool.name);
+    }
+
+    #[tokio::test]
+    async fn test_get_nonexistent_tool() {
+        let server = create_test_server();
+        let retrieved = server.get_tool("nonexistent").await;
+        assert!(retrieved.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_tools() {
+        let server = create_test_server();
+        let tools = vec![create_valid_tool("tool1"), create_valid_tool("tool2")];
+
+        server.register_tools(tools).await.unwrap();
+
+        let listed = server.list_tools().await;
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().any(|t| t.name == "tool1"));
+        assert!(listed.iter().any(|t| t.name == "tool2"));
+    }
+
+    #[tokio::test]
+    async fn test_clear_tools() {
+        let server = create_test_server();
+        let tools = vec![create_valid_tool("tool1"), create_valid_tool("tool2")];
+
+        server.register_tools(tools).await.unwrap();
+        assert_eq!(server.tool_count().await, 2);
+
+        server.clear_tools().await;
+        assert_eq!(server.tool_count().await, 0);
+        assert!(!server.has_tool("tool1").await);
+        assert!(!server.has_tool("tool2").await);
+    }
+
+    #[tokio::test]
+    async fn test_replace_tools() {
+        let server = create_test_server();
+        server
+            .register_tools(vec![create_valid_tool("tool1"), create_valid_tool("tool2")])
+            .await
+            .unwrap();
+        let previous = server.get_tool("tool1").await.unwrap();
+
+        // An invalid definition leaves the current set untouched
+        let mut invalid = create_valid_tool("tool3");
+        invalid.output_schema = None;
+        let result = server
+            .replace_tools(vec![create_valid_tool("tool3"), invalid])
+            .await;
+        assert!(matches!(
+            result,
+            Err(ToolRegistrationError::MissingOutputSchema)
+        ));
+        assert!(Arc::ptr_eq(
+            &previous,
+            &server.get_tool("tool1").await.unwrap()
+        ));
+
+        server
+            .replace_tools(vec![create_valid_tool("tool3")])
+            .await
+            .unwrap();
+        assert_eq!(server.tool_count().await, 1);
+        assert!(server.has_tool("tool3").await);
+        assert_eq!(previous.name, "tool1");
+    }
+
+    #[tokio::test]
+    async fn test_validate_tool_call() {
+        let server = create_test_server();
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
+
+        let valid_args = json!({"input": "test input"});
+        let result = server.validate_tool_call("test_tool", &valid_args).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_validate_tool_call_invalid_args() {
+        let server = create_test_server();
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
+
+        let invalid_args = json!({"wrong_field": "test input"});
+        let result = server.validate_tool_call("test_tool", &invalid_args).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_nonexistent_tool_call() {
+        let server = create_test_server();
+        let args = json!({"input": "test input"});
+        let result = server.validate_tool_call("nonexistent", &args).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_call() {
+        let server = create_test_server();
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
+
+        let args = json!({"input": "test input"});
+        let result = server.execute_tool_call("test_tool", args).await;
+        assert!(result.is_ok());
+
+        let tool_result = result.unwrap();
+        assert_eq!(tool_result.content.len(), 1);
+        assert!(!tool_result.is_error.unwrap_or(false));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_call_without_handler() {
+        let server = UltraFastServer::new(
+            ServerInfo {
+                name: "test-server".to_string(),
+                version: "1.0.0".to_string(),
+                description: Some("Test server".to_string()),
+                homepage: None,
+                repository: None,
+                authors: Some(vec!["test".to_string()]),
+                license: Some("MIT".to_string()),
+            },
+            ServerCapabilities::default(),
+        );
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
+
+        let args = json!({"input": "test input"});
+        let result = server.execute_tool_call("test_tool", args).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reserved_names() {
+        let server = create_test_server();
+        let reserved_names = [
+            "initialize",
+            "initialized",
+            "shutdown",
+            "exit",
+            "ping",
+            "tools/list",
+            "tools/call",
+            "resources/list",
+            "resources/read",
+            "resources/subscribe",
+            "resources/unsubscribe",
+            "prompts/list",
+            "prompts/get",
+            "sampling/create",
+            "completion/complete",
+            "roots/list",
+            "elicitation/request",
+        ];
+
+        for name in &reserved_names {
+            let tool = create_valid_tool(name);
+            let result = server.register_tool(tool).await;
+            assert!(matches!(
+                result,
+                Err(ToolRegistrationError::ReservedName(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_jsonrpc() {
+        let server = create_initialized_test_server().await;
+
+        // Register some tools
+        let tools = vec![create_valid_tool("tool1"), create_valid_tool("tool2")];
+        server.register_tools(tools).await.unwrap();
+
+        // Create tools/list request
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/list".to_string(),
+            params: None,
+            meta: std::collections::HashMap::new(),
+        };
 
-                let unsubscribe_request =
-                    self.deserialize_unsubscribe_request(request.params.clone());
+        let response = server.handle_request(request).await;
 
-                if let Some(handler) = &self.subscription_handler {
-                    match handler.unsubscribe(unsubscribe_request.uri).await {
-                        Ok(_) => JsonRpcResponse::success(serde_json::Value::Null, request.id),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Resource unsubscribe failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(
-                            -32601,
-                            "Resource subscriptions not supported".to_string(),
-                        ),
-                        request.id,
-                    )
-                }
-            }
+        // Verify response
+        if let Some(result) = &response.result {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            let tools_array = result.get("tools").and_then(|t| t.as_array()).unwrap();
+            assert_eq!(tools_array.len(), 2);
 
-            // Prompts methods
-            "prompts/list" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+            let tool_names: Vec<&str> = tools_array
+                .iter()
+                .filter_map(|t| t.get("name").and_then(|n| n.as_str()))
+                .collect();
+            assert!(tool_names.contains(&"tool1"));
+            assert!(tool_names.contains(&"tool2"));
+        } else {
+            panic!("Expected success response");
+        }
+    }
 
-                let list_request = self.deserialize_list_prompts_request(request.params.clone());
+    #[tokio::test]
+    async fn test_tools_list_cache_invalidated_on_registry_change() {
+        // Only registered tools, which the server knows about when they change
+        let mut server = create_initialized_test_server().await;
+        server.tool_handler = None;
+        server
+            .register_tool(create_valid_tool("tool1"))
+            .await
+            .unwrap();
+
+        let list_request = || JsonRpcRequest::new("tools/list".to_string(), None, None);
+        let tool_count = |response: JsonRpcResponse| {
+            response.result.unwrap()["tools"]
+                .as_array()
+                .map(|tools| tools.len())
+                .unwrap()
+        };
 
-                if let Some(handler) = &self.prompt_handler {
-                    match handler.list_prompts(list_request).await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Prompts list failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Prompts not supported".to_string()),
-                        request.id,
-                    )
-                }
-            }
-            "prompts/get" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+        assert_eq!(tool_count(server.handle_request(list_request()).await), 1);
+        assert!(server.list_cache.tools.get().await.is_some());
 
-                let get_request = self.deserialize_get_prompt_request(request.params.clone());
+        server
+            .register_tool(create_valid_tool("tool2"))
+            .await
+            .unwrap();
+        assert!(server.list_cache.tools.get().await.is_none());
+        assert_eq!(tool_count(server.handle_request(list_request()).await), 2);
 
-                if let Some(handler) = &self.prompt_handler {
-                    match handler.get_prompt(get_request).await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Prompt get failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Prompts not supported".to_string()),
-                        request.id,
-                    )
-                }
-            }
+        server.unregister_tool("tool1").await;
+        assert_eq!(tool_count(server.handle_request(list_request()).await), 1);
+    }
 
-            // Completion methods
-            "completion/complete" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+    #[tokio::test]
+    async fn test_cached_tools_list_is_sent_as_serialized() {
+        let mut server = create_initialized_test_server().await;
+        server.tool_handler = None;
+        server
+            .register_tool(create_valid_tool("tool1"))
+            .await
+            .unwrap();
 
-                let complete_request = self.deserialize_complete_request(request.params.clone());
+        let list_request = || JsonRpcRequest::new("tools/list".to_string(), None, None);
+        let first = server.handle_request(list_request()).await;
+        let second = server.handle_request(list_request()).await;
 
-                if let Some(handler) = &self.completion_handler {
-                    match handler.complete(complete_request).await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Completion failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Completion not supported".to_string()),
-                        request.id,
-                    )
-                }
-            }
+        let cached = server.list_cache.tools.get().await.unwrap();
+        assert!(Arc::ptr_eq(second.raw_result().unwrap(), &cached));
+        assert_eq!(
+            serde_json::to_string(&second).unwrap(),
+            serde_json::to_string(&first).unwrap()
+        );
+        assert_eq!(second.into_result(), first.result);
+    }
 
-            // Sampling methods
-            "sampling/createMessage" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+    /// Lists nothing on its first call and one tool afterwards, without
+    /// announcing the change
+    #[derive(Default)]
+    struct LateToolHandler {
+        calls: std::sync::atomic::AtomicUsize,
+    }
 
-                let create_request =
-                    self.deserialize_create_message_request(request.params.clone());
+    #[async_trait::async_trait]
+    impl ToolHandler for LateToolHandler {
+        async fn handle_tool_call(
+            &self,
+            _call: ultrafast_mcp_core::types::tools::ToolCall,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+            unreachable!("only tools are listed")
+        }
 
-                if let Some(handler) = &self.sampling_handler {
-                    match handler.create_message(create_request).await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Message creation failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Sampling not supported".to_string()),
-                        request.id,
-                    )
-                }
-            }
+        async fn list_tools(
+            &self,
+            _request: ultrafast_mcp_core::types::tools::ListToolsRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ListToolsResponse> {
+            let tools = match self.calls.fetch_add(1, Ordering::SeqCst) {
+                0 => vec![],
+                _ => vec![create_valid_tool("late")],
+            };
+            Ok(ultrafast_mcp_core::types::tools::ListToolsResponse {
+                tools,
+                next_cursor: None,
+            })
+        }
+    }
 
-            // Roots methods
-            "roots/list" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+    #[tokio::test]
+    async fn test_tools_list_not_cached_for_handler_without_list_changed() {
+        let server = create_initialized_test_server()
+            .await
+            .with_tool_handler(Arc::new(LateToolHandler::default()));
+        server
+            .register_tool(create_valid_tool("registered"))
+            .await
+            .unwrap();
 
-                if let Some(handler) = &self.roots_handler {
-                    match handler.list_roots().await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Roots list failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Roots not supported".to_string()),
-                        request.id,
-                    )
-                }
-            }
+        let tool_names = |response: JsonRpcResponse| {
+            response.result.unwrap()["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tool| tool["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let list_request = || JsonRpcRequest::new("tools/list".to_string(), None, None);
 
-            // Elicitation methods
-            "elicitation/create" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
-                }
+        // The empty first answer falls back to the registry
+        assert_eq!(
+            tool_names(server.handle_request(list_request()).await),
+            ["registered"]
+        );
+        assert!(server.list_cache.tools.get().await.is_none());
+        assert_eq!(
+            tool_names(server.handle_request(list_request()).await),
+            ["late"]
+        );
+    }
 
-                let elicitation_request =
-                    self.deserialize_elicitation_request(request.params.clone());
+    #[tokio::test]
+    async fn test_tools_list_paginated_requests_not_cached() {
+        let server = create_initialized_test_server().await;
+        server
+            .register_tool(create_valid_tool("tool1"))
+            .await
+            .unwrap();
 
-                if let Some(handler) = &self.elicitation_handler {
-                    match handler.handle_elicitation(elicitation_request).await {
-                        Ok(response) => JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        ),
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Elicitation failed: {e}")),
-                            request.id,
-                        ),
-                    }
-                } else {
-                    JsonRpcResponse::error(
-                        JsonRpcError::new(-32601, "Elicitation not supported".to_string()),
-                        request.id,
-                    )
-                }
-            }
+        let request = JsonRpcRequest::new(
+            "tools/list".to_string(),
+            Some(json!({"cursor": "next"})),
+            None,
+        );
+        let response = server.handle_request(request).await;
+        assert!(response.result.is_some());
+        assert!(server.list_cache.tools.get().await.is_none());
+    }
 
-            "elicitation/respond" => {
-                if !self.can_operate().await {
-                    return JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Server not ready".to_string()),
-                        request.id,
-                    );
+    #[tokio::test]
+    async fn test_tools_call_jsonrpc_success() {
+        let server = create_initialized_test_server().await;
+
+        // Register a tool
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
+
+        // Create tools/call request
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "test_tool",
+                "arguments": {
+                    "input": "test input"
                 }
+            })),
+            meta: std::collections::HashMap::new(),
+        };
 
-                let elicitation_response = match serde_json::from_value::<
-                    ultrafast_mcp_core::types::elicitation::ElicitationResponse,
-                >(
-                    request.params.unwrap_or_default()
-                ) {
-                    Ok(response) => response,
-                    Err(e) => {
-                        return JsonRpcResponse::error(
-                            JsonRpcError::new(-32602, format!("Invalid elicitation response: {e}")),
-                            request.id,
-                        );
-                    }
-                };
+        let response = server.handle_request(request).await;
 
-                // Log the elicitation response
-                info!(
-                    "Received elicitation response: {:?}",
-                    elicitation_response.action
-                );
+        // Verify response
+        if let Some(result) = &response.result {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
 
-                // In a real implementation, this would be handled by the server's elicitation flow
-                // For now, we'll just return success
-                JsonRpcResponse::success(serde_json::json!({}), request.id)
-            }
+            // Check that result contains content
+            let content = result.get("content").and_then(|c| c.as_array()).unwrap();
+            assert_eq!(content.len(), 1);
 
-            // Logging methods
-            "logging/setLevel" => {
-                let params = match &request.params {
-                    Some(params) => params,
-                    None => {
-                        return JsonRpcResponse::error(
-                            JsonRpcError::new(-32602, "Missing parameters".to_string()),
-                            request.id,
-                        );
-                    }
-                };
+            // The ToolContent::text creates a structure with "type": "text" and "text" field
+            let text_content = content[0].get("text").and_then(|t| t.as_str()).unwrap();
+            assert!(text_content.contains("Mock result for test_tool"));
+        } else {
+            panic!("Expected success response");
+        }
+    }
 
-                match serde_json::from_value::<LogLevelSetRequest>(params.clone()) {
-                    Ok(set_request) => match self.set_log_level(set_request.level).await {
-                        Ok(()) => {
-                            let response = LogLevelSetResponse::new();
-                            JsonRpcResponse::success(
-                                serde_json::to_value(response).unwrap(),
-                                request.id,
-                            )
-                        }
-                        Err(e) => JsonRpcResponse::error(
-                            JsonRpcError::new(-32603, format!("Failed to set log level: {e}")),
-                            request.id,
-                        ),
-                    },
-                    Err(e) => JsonRpcResponse::error(
-                        JsonRpcError::new(-32602, format!("Invalid log level set request: {e}")),
-                        request.id,
-                    ),
-                }
-            }
+    #[tokio::test]
+    async fn test_tools_call_jsonrpc_missing_params() {
+        let server = create_initialized_test_server().await;
 
-            // Ping method for connection health monitoring
-            "ping" => {
-                let ping_request = match serde_json::from_value::<
-                    ultrafast_mcp_core::types::notifications::PingRequest,
-                >(request.params.unwrap_or_default())
-                {
-                    Ok(req) => req,
-                    Err(_) => ultrafast_mcp_core::types::notifications::PingRequest { data: None },
-                };
+        // Create tools/call request without parameters
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/call".to_string(),
+            params: None,
+            meta: std::collections::HashMap::new(),
+        };
 
-                match self.ping_manager.handle_ping(ping_request).await {
-                    Ok(response) => JsonRpcResponse::success(
-                        serde_json::to_value(response).unwrap(),
-                        request.id,
-                    ),
-                    Err(e) => JsonRpcResponse::error(
-                        JsonRpcError::new(-32603, format!("Ping failed: {e}")),
-                        request.id,
-                    ),
-                }
-            }
+        let response = server.handle_request(request).await;
 
-            // Roots methods
-            "roots/set" => {
-                let params = match &request.params {
-                    Some(params) => params,
-                    None => {
-                        return JsonRpcResponse::error(
-                            JsonRpcError::new(-32602, "Missing parameters".to_string()),
-                            request.id,
-                        );
-                    }
-                };
+        // Verify error response
+        if let Some(error) = &response.error {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            assert_eq!(error.code, -32602); // Invalid params
+            assert!(error.message.contains("Missing parameters"));
+        } else {
+            panic!("Expected error response");
+        }
+    }
 
-                match serde_json::from_value::<SetRootsRequest>(params.clone()) {
-                    Ok(set_request) => {
-                        let response = self
-                            .handle_set_roots(
-                                set_request.roots,
-                                &mut Box::new(
-                                    create_transport(TransportConfig::Stdio).await.unwrap(),
-                                ),
-                            )
-                            .await;
-                        JsonRpcResponse::success(
-                            serde_json::to_value(response).unwrap(),
-                            request.id,
-                        )
-                    }
-                    Err(e) => JsonRpcResponse::error(
-                        JsonRpcError::new(-32602, format!("Invalid roots set request: {e}")),
-                        request.id,
-                    ),
+    #[tokio::test]
+    async fn test_tools_call_jsonrpc_missing_name() {
+        let server = create_initialized_test_server().await;
+
+        // Create tools/call request without tool name
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "arguments": {
+                    "input": "test input"
                 }
-            }
+            })),
+            meta: std::collections::HashMap::new(),
+        };
 
-            // Unknown method
-            _ => JsonRpcResponse::error(
-                JsonRpcError::new(
-                    -32601,
-                    format!("Method not implemented: {}", request.method),
-                ),
-                request.id,
-            ),
+        let response = server.handle_request(request).await;
+
+        // Verify error response
+        if let Some(error) = &response.error {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            assert_eq!(error.code, -32602); // Invalid params
+            assert!(error.message.contains("Missing or invalid tool name"));
+        } else {
+            panic!("Expected error response");
         }
     }
 
-    /// Handle incoming notifications
-    async fn handle_notification(&self, notification: JsonRpcRequest) -> MCPResult<()> {
-        info!("Handling notification: {}", notification.method);
-
-        match notification.method.as_str() {
-            "initialized" => {
-                let notification = ultrafast_mcp_core::protocol::InitializedNotification {};
-                self.handle_initialized(notification).await?;
-                Ok(())
-            }
-            "notifications/cancelled" => {
-                // Handle cancellation notification
-                if let Some(params) = notification.params {
-                    let cancellation_notification: ultrafast_mcp_core::types::notifications::CancelledNotification =
-                        serde_json::from_value(params)?;
+    #[tokio::test]
+    async fn test_tools_call_jsonrpc_nonexistent_tool() {
+        let server = create_initialized_test_server().await;
 
-                    // Use the cancellation manager to handle the cancellation
-                    let _cancelled = self
-                        .cancellation_manager
-                        .handle_cancellation(cancellation_notification)
-                        .await?;
-                    info!("Cancellation notification processed");
+        // Create tools/call request for non-existent tool
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "nonexistent_tool",
+                "arguments": {
+                    "input": "test input"
                 }
-                Ok(())
-            }
-            _ => {
-                warn!("Unknown notification method: {}", notification.method);
-                Ok(())
-            }
+            })),
+            meta: std::collections::HashMap::new(),
+        };
+
+        let response = server.handle_request(request).await;
+
+        // Verify error response
+        if let Some(error) = &response.error {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            assert_eq!(error.code, -32602); // Invalid params
+            assert!(error.message.contains("Tool call failed:"));
+            assert!(error.message.contains("Tool not found"));
+        } else {
+            panic!("Expected error response");
         }
     }
 
-    // ===== NOTIFICATION METHODS =====
+    #[tokio::test]
+    async fn test_tools_call_jsonrpc_invalid_arguments() {
+        let server = create_initialized_test_server().await;
+
+        // Register a tool
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
+
+        // Create tools/call request with invalid arguments
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "test_tool",
+                "arguments": {
+                    "wrong_field": "test input"
+                }
+            })),
+            meta: std::collections::HashMap::new(),
+        };
 
-    /// Send tools list changed notification
-    pub async fn notify_tools_changed(&self, transport: &mut Box<dyn Transport>) -> MCPResult<()> {
-        let notification =
-            ultrafast_mcp_core::types::notifications::ToolsListChangedNotification::new();
-        self.send_notification(
-            "notifications/tools/listChanged",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
-    }
+        let response = server.handle_request(request).await;
 
-    /// Send resources list changed notification
-    pub async fn notify_resources_changed(
-        &self,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let notification =
-            ultrafast_mcp_core::types::notifications::ResourcesListChangedNotification::new();
-        self.send_notification(
-            "notifications/resources/listChanged",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
+        // Verify error response
+        if let Some(error) = &response.error {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            assert_eq!(error.code, -32602); // Invalid params
+            assert!(error.message.contains("Invalid parameters"));
+        } else {
+            panic!("Expected error response");
+        }
     }
 
-    /// Send prompts list changed notification
-    pub async fn notify_prompts_changed(
-        &self,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let notification =
-            ultrafast_mcp_core::types::notifications::PromptsListChangedNotification::new();
-        self.send_notification(
-            "notifications/prompts/listChanged",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
-    }
+    #[tokio::test]
+    async fn test_tools_call_jsonrpc_empty_arguments() {
+        let server = create_initialized_test_server().await;
 
-    /// Send resource updated notification
-    pub async fn notify_resource_updated(
-        &self,
-        uri: String,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let notification =
-            ultrafast_mcp_core::types::resources::ResourceUpdatedNotification { uri };
-        self.send_notification(
-            "notifications/resources/updated",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
-    }
+        // Register a tool
+        let tool = create_valid_tool("test_tool");
+        server.register_tool(tool).await.unwrap();
 
-    /// Send progress notification
-    pub async fn notify_progress(
-        &self,
-        progress_token: serde_json::Value,
-        progress: f64,
-        total: Option<f64>,
-        message: Option<String>,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let mut notification = ultrafast_mcp_core::types::notifications::ProgressNotification::new(
-            progress_token,
-            progress,
-        );
-        if let Some(total) = total {
-            notification = notification.with_total(total);
-        }
-        if let Some(message) = message {
-            notification = notification.with_message(message);
-        }
-        self.send_notification(
-            "notifications/progress",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
-    }
+        // Create tools/call request with empty arguments
+        let request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "test_tool",
+                "arguments": {}
+            })),
+            meta: std::collections::HashMap::new(),
+        };
 
-    /// Send logging message notification
-    pub async fn notify_logging_message(
-        &self,
-        level: ultrafast_mcp_core::types::notifications::LogLevel,
-        data: serde_json::Value,
-        logger: Option<String>,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let mut notification =
-            ultrafast_mcp_core::types::notifications::LoggingMessageNotification::new(level, data);
-        if let Some(logger) = logger {
-            notification = notification.with_logger(logger);
-        }
-        self.send_notification(
-            "notifications/logging/message",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
-    }
+        let response = server.handle_request(request).await;
 
-    /// Send cancellation notification
-    pub async fn notify_cancelled(
-        &self,
-        request_id: serde_json::Value,
-        reason: Option<String>,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let mut notification =
-            ultrafast_mcp_core::types::notifications::CancelledNotification::new(request_id);
-        if let Some(reason) = reason {
-            notification = notification.with_reason(reason);
+        // Verify error response
+        if let Some(error) = &response.error {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            assert_eq!(error.code, -32602); // Invalid params
+            // The actual error message format has changed to include more context
+            assert!(error.message.contains("Invalid parameters"));
+        } else {
+            panic!("Expected error response");
         }
-        self.send_notification(
-            "notifications/cancelled",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
     }
 
-    /// Send roots list changed notification
-    pub async fn notify_roots_changed(&self, transport: &mut Box<dyn Transport>) -> MCPResult<()> {
-        let notification =
-            ultrafast_mcp_core::types::notifications::RootsListChangedNotification::new();
-        self.send_notification(
-            "notifications/roots/listChanged",
-            Some(serde_json::to_value(notification)?),
-            transport,
-        )
-        .await
-    }
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let server = create_test_server();
 
-    /// Generic method to send notifications
-    async fn send_notification(
-        &self,
-        method: &str,
-        params: Option<serde_json::Value>,
-        transport: &mut Box<dyn Transport>,
-    ) -> MCPResult<()> {
-        let notification = JsonRpcRequest {
+        // Create request for unknown method
+        let request = JsonRpcRequest {
             jsonrpc: Cow::Borrowed("2.0"),
-            id: None, // Notifications have no ID
-            method: method.to_string(),
-            params,
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "test-id",
+            )),
+            method: "unknown/method".to_string(),
+            params: None,
             meta: std::collections::HashMap::new(),
         };
 
-        transport
-            .send_message(JsonRpcMessage::Request(notification))
-            .await
-            .map_err(|e| MCPError::internal_error(format!("Failed to send notification: {e}")))?;
+        let response = server.handle_request(request).await;
 
-        info!("Sent notification: {}", method);
-        Ok(())
+        // Verify error response
+        if let Some(error) = &response.error {
+            assert_eq!(
+                response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "test-id"
+                ))
+            );
+            assert_eq!(error.code, -32601); // Method not found
+            assert!(error.message.contains("Method not implemented"));
+        } else {
+            panic!("Expected error response");
+        }
     }
 
-    /// Set the advanced sampling handler for context collection and human-in-the-loop features
-    pub fn with_advanced_sampling_handler(
-        mut self,
-        handler: Arc<dyn AdvancedSamplingHandler>,
-    ) -> Self {
-        self.advanced_sampling_handler = Some(handler);
-        self
-    }
+    #[tokio::test]
+    async fn test_tools_integration_workflow() {
+        let server = create_initialized_test_server().await;
 
-    /// Set the advanced sampling handler with default implementation
-    pub fn with_default_advanced_sampling(mut self) -> Self {
-        let default_handler = Arc::new(DefaultAdvancedSamplingHandler::new(self.info.clone()));
-        self.advanced_sampling_handler = Some(default_handler);
-        self
-    }
+        // Step 1: Register multiple tools
+        let tools = vec![
+            create_valid_tool("calculator"),
+            create_valid_tool("file_reader"),
+        ];
+        server.register_tools(tools).await.unwrap();
+        assert_eq!(server.tool_count().await, 2);
 
-    /// Handle a roots/set request
-    pub async fn handle_set_roots(
-        &self,
-        roots: Vec<ultrafast_mcp_core::types::roots::Root>,
-        transport: &mut Box<dyn Transport>,
-    ) -> SetRootsResponse {
-        if let Some(handler) = &self.roots_handler {
-            match handler.set_roots(roots.clone()).await {
-                Ok(_) => {
-                    // Send notification to all clients (for demo, just send to this transport)
-                    let notification = RootsListChangedNotification { roots };
-                    let params = serde_json::to_value(notification).ok();
-                    let _ = self
-                        .send_notification("roots/listChanged", params, transport)
-                        .await;
-                    SetRootsResponse {
-                        success: true,
-                        error: None,
-                    }
+        // Step 2: List tools via JSON-RPC
+        let list_request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "list-id",
+            )),
+            method: "tools/list".to_string(),
+            params: None,
+            meta: std::collections::HashMap::new(),
+        };
+
+        let list_response = server.handle_request(list_request).await;
+        if let Some(result) = &list_response.result {
+            assert_eq!(
+                list_response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "list-id"
+                ))
+            );
+            let tools_array = result.get("tools").and_then(|t| t.as_array()).unwrap();
+            assert_eq!(tools_array.len(), 2);
+        } else {
+            panic!("Expected success response for tools/list");
+        }
+
+        // Step 3: Call a tool via JSON-RPC
+        let call_request = JsonRpcRequest {
+            jsonrpc: Cow::Borrowed("2.0"),
+            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                "call-id",
+            )),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "calculator",
+                "arguments": {
+                    "input": "2 + 2"
                 }
-                Err(e) => SetRootsResponse {
-                    success: false,
-                    error: Some(e.to_string()),
-                },
-            }
+            })),
+            meta: std::collections::HashMap::new(),
+        };
+
+        let call_response = server.handle_request(call_request).await;
+        if let Some(result) = &call_response.result {
+            assert_eq!(
+                call_response.id,
+                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
+                    "call-id"
+                ))
+            );
+            let content = result
+                .get("content")
+                .and_then(|c| c.as_array())
+                .expect("Expected content array");
+            assert_eq!(content.len(), 1);
         } else {
-            SetRootsResponse {
-                success: false,
-                error: Some("Roots handler not available".to_string()),
-            }
+            panic!("Expected success response for tools/call");
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
-    use ultrafast_mcp_core::types::{
-        server::ServerInfo,
-        tools::{Tool, ToolContent},
-    };
+        // Step 4: Verify tool still exists in registry
+        assert!(server.has_tool("calculator").await);
+        assert!(server.has_tool("file_reader").await);
+    }
 
-    // Mock tool handler for testing
-    struct MockToolHandler;
+    struct ElicitingToolHandler;
 
     #[async_trait::async_trait]
-    impl ToolHandler for MockToolHandler {
+    impl ToolHandler for ElicitingToolHandler {
         async fn handle_tool_call(
             &self,
-            call: ultrafast_mcp_core::types::tools::ToolCall,
+            _call: ultrafast_mcp_core::types::tools::ToolCall,
         ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
-            // Simulate error for nonexistent tool or invalid arguments
-            if call.name == "nonexistent_tool" {
-                return Err(ultrafast_mcp_core::error::MCPError::not_found(
-                    "Tool not found".to_string(),
-                ));
-            }
-            if let Some(args) = &call.arguments {
-                if args.get("input").is_none() {
-                    return Err(ultrafast_mcp_core::error::MCPError::invalid_params(
-                        "Invalid parameters".to_string(),
-                    ));
-                }
-            } else {
-                return Err(ultrafast_mcp_core::error::MCPError::invalid_params(
-                    "Missing arguments".to_string(),
-                ));
-            }
+            unreachable!("tools/call goes through handle_tool_call_with_context")
+        }
+
+        async fn handle_tool_call_with_context(
+            &self,
+            _call: ultrafast_mcp_core::types::tools::ToolCall,
+            context: Context,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+            let response = context
+                .elicit(ultrafast_mcp_core::types::elicitation::ElicitationRequest {
+                    message: "Your name?".to_string(),
+                    requested_schema: json!({
+                        "type": "object",
+                        "properties": {"name": {"type": "string"}}
+                    }),
+                })
+                .await?;
+            let name = response
+                .content
+                .and_then(|content| content["name"].as_str().map(str::to_string))
+                .unwrap_or_default();
             Ok(ultrafast_mcp_core::types::tools::ToolResult {
-                content: vec![ToolContent::text(format!("Mock result for {}", call.name))],
+                content: vec![ToolContent::text(format!("Hello, {name}"))],
                 is_error: None,
+                structured_content: None,
+                progress_summary: None,
             })
         }
 
@@ -2285,7 +5703,6 @@ mod tests {
             &self,
             _request: ultrafast_mcp_core::types::tools::ListToolsRequest,
         ) -> MCPResult<ultrafast_mcp_core::types::tools::ListToolsResponse> {
-            // This will be overridden by the server's fallback to registered tools
             Ok(ultrafast_mcp_core::types::tools::ListToolsResponse {
                 tools: vec![],
                 next_cursor: None,
@@ -2293,743 +5710,2158 @@ mod tests {
         }
     }
 
-    fn create_test_server() -> UltraFastServer {
-        let info = ServerInfo {
-            name: "test-server".to_string(),
-            version: "1.0.0".to_string(),
-            description: Some("Test server".to_string()),
-            homepage: None,
-            repository: None,
-            authors: Some(vec!["test".to_string()]),
-            license: Some("MIT".to_string()),
-        };
-        let capabilities = ServerCapabilities::default();
-        UltraFastServer::new(info, capabilities).with_tool_handler(Arc::new(MockToolHandler))
-    }
+    #[tokio::test]
+    async fn test_tool_handler_elicits_from_client() {
+        let server = create_initialized_test_server()
+            .await
+            .with_tool_handler(Arc::new(ElicitingToolHandler));
+        server
+            .register_tool(create_valid_tool("greet"))
+            .await
+            .unwrap();
 
-    async fn create_initialized_test_server() -> UltraFastServer {
-        let server = create_test_server();
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        peer.set_client_capabilities(ultrafast_mcp_core::protocol::ClientCapabilities {
+            elicitation: Some(ultrafast_mcp_core::protocol::capabilities::ElicitationCapability {}),
+            ..Default::default()
+        });
 
-        // Initialize the server to operating state
-        let init_request = ultrafast_mcp_core::protocol::InitializeRequest {
-            protocol_version: "2025-06-18".to_string(),
-            capabilities: ultrafast_mcp_core::protocol::ClientCapabilities::default(),
-            client_info: ultrafast_mcp_core::types::client::ClientInfo {
-                name: "test-client".to_string(),
-                version: "1.0.0".to_string(),
-                description: Some("Test client".to_string()),
-                homepage: None,
-                repository: None,
-                authors: Some(vec!["test".to_string()]),
-                license: Some("MIT".to_string()),
-            },
+        let request = JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(json!({"name": "greet", "arguments": {"input": "x"}})),
+            Some(RequestId::number(7)),
+        );
+        let responder = {
+            let server = server.clone();
+            let peer = peer.clone();
+            tokio::spawn(async move { server.respond(request, &peer).await })
         };
 
-        let _response = server.handle_initialize(init_request).await;
+        let Some(JsonRpcMessage::Request(elicitation)) = outgoing.recv().await else {
+            panic!("expected an elicitation request");
+        };
+        assert_eq!(elicitation.method, "elicitation/create");
+        assert!(peer.handle_response(JsonRpcResponse::success(
+            json!({"action": "accept", "content": {"name": "Ada"}}),
+            elicitation.id,
+        )));
 
-        // Send initialized notification
-        let notification = ultrafast_mcp_core::protocol::InitializedNotification {};
-        let _ = server.handle_initialized(notification).await;
+        let response = responder.await.unwrap();
+        assert_eq!(response.id, Some(RequestId::number(7)));
+        let result = response.result.expect("Expected success response");
+        assert_eq!(result["content"][0]["text"], "Hello, Ada");
+    }
 
+    #[tokio::test]
+    async fn test_elicit_requires_client_capability() {
+        let server = create_initialized_test_server()
+            .await
+            .with_tool_handler(Arc::new(ElicitingToolHandler));
         server
+            .register_tool(create_valid_tool("greet"))
+            .await
+            .unwrap();
+
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        let request = JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(json!({"name": "greet", "arguments": {"input": "x"}})),
+            Some(RequestId::number(8)),
+        );
+
+        let response = server.respond(request, &peer).await;
+        assert!(response.error.is_some());
+        assert_eq!(peer.pending_requests(), 0);
     }
 
-    fn create_valid_tool(name: &str) -> Tool {
-        Tool {
-            name: name.to_string(),
-            description: "A test tool".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "input": {"type": "string"}
-                },
-                "required": ["input"]
-            }),
-            output_schema: Some(json!({
-                "type": "object",
-                "properties": {
-                    "output": {"type": "string"}
-                }
+    #[tokio::test]
+    async fn test_client_emulation_answers_elicitation() {
+        let server = create_initialized_test_server()
+            .await
+            .with_tool_handler(Arc::new(ElicitingToolHandler))
+            .with_elicitation_handler(Arc::new(
+                AutoElicitationHandler::new().with_value("name", json!("Ada")),
+            ))
+            .with_client_emulation(ClientEmulationConfig::all());
+        server
+            .register_tool(create_valid_tool("greet"))
+            .await
+            .unwrap();
+
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        let request = JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(json!({"name": "greet", "arguments": {"input": "x"}})),
+            Some(RequestId::number(9)),
+        );
+
+        let response = server.respond(request, &peer).await;
+        let result = response.result.expect("Expected success response");
+        assert_eq!(result["content"][0]["text"], "Hello, Ada");
+        assert!(outgoing.try_recv().is_err());
+
+        // Sampling is emulated too
+        let sampling = JsonRpcRequest::new(
+            "sampling/createMessage".to_string(),
+            Some(json!({
+                "messages": [{"role": "user", "content": {"type": "text", "text": "ping"}}]
             })),
-            annotations: None,
+            Some(RequestId::number(10)),
+        );
+        let response = server.respond(sampling, &peer).await;
+        let result = response.result.expect("Expected success response");
+        assert_eq!(result["content"]["text"], "ping");
+    }
+
+    struct FixedSamplingHandler;
+
+    #[async_trait::async_trait]
+    impl SamplingHandler for FixedSamplingHandler {
+        async fn create_message(
+            &self,
+            _request: ultrafast_mcp_core::types::sampling::CreateMessageRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::sampling::CreateMessageResponse> {
+            Ok(serde_json::from_value(json!({
+                "role": "assistant",
+                "content": {"type": "text", "text": "Hello there!"},
+                "model": "test-model"
+            }))
+            .unwrap())
         }
     }
 
     #[tokio::test]
-    async fn test_register_valid_tool() {
-        let server = create_test_server();
-        let tool = create_valid_tool("test_tool");
+    async fn test_sampling_usage_is_recorded_and_reported() {
+        let server = create_initialized_test_server()
+            .await
+            .with_sampling_handler(Arc::new(FixedSamplingHandler))
+            .with_usage_report();
+
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            server
+                .create_client_peer(outgoing_sender)
+                .with_session_id("session-1".to_string()),
+        );
+        let request = JsonRpcRequest::new(
+            "sampling/createMessage".to_string(),
+            Some(json!({
+                "messages": [{"role": "user", "content": {"type": "text", "text": "Hi"}}]
+            })),
+            Some(RequestId::number(1)),
+        );
+        assert!(server.respond(request, &peer).await.result.is_some());
 
-        let result = server.register_tool(tool).await;
-        assert!(result.is_ok());
+        let usage = server.sampling_usage();
+        assert_eq!(usage.total.requests, 1);
+        assert_eq!(usage.sessions["session-1"].output_tokens, 3);
+        assert_eq!(usage.models["test-model"].requests, 1);
+
+        let report = server
+            .handle_request(JsonRpcRequest::new(
+                "usage/report".to_string(),
+                None,
+                Some(RequestId::number(2)),
+            ))
+            .await;
+        assert_eq!(report.result.unwrap()["total"]["requests"], 1);
+    }
+
+    struct AcceptingSubscriptionHandler;
+
+    #[async_trait::async_trait]
+    impl ResourceSubscriptionHandler for AcceptingSubscriptionHandler {
+        async fn subscribe(&self, _uri: String) -> MCPResult<()> {
+            Ok(())
+        }
+
+        async fn unsubscribe(&self, _uri: String) -> MCPResult<()> {
+            Ok(())
+        }
+
+        async fn notify_change(&self, _uri: String, _content: serde_json::Value) -> MCPResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pattern_subscriptions_match_resource_uris() {
+        let server = create_initialized_test_server()
+            .await
+            .with_subscription_handler(Arc::new(AcceptingSubscriptionHandler));
+
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            server
+                .create_client_peer(outgoing_sender)
+                .with_session_id("session-1".to_string()),
+        );
+        let subscribe = JsonRpcRequest::new(
+            "resources/subscribe".to_string(),
+            Some(json!({"uri": "file:///project/**"})),
+            Some(RequestId::number(1)),
+        );
+        assert!(server.respond(subscribe, &peer).await.result.is_some());
+
+        assert_eq!(
+            server.resource_subscribers("file:///project/src/main.rs"),
+            HashSet::from(["session-1".to_string()])
+        );
+        assert!(
+            server
+                .resource_subscribers("file:///elsewhere/main.rs")
+                .is_empty()
+        );
+
+        let unsubscribe = JsonRpcRequest::new(
+            "resources/unsubscribe".to_string(),
+            Some(json!({"uri": "file:///project/**"})),
+            Some(RequestId::number(2)),
+        );
+        assert!(server.respond(unsubscribe, &peer).await.result.is_some());
+        assert!(server.subscriptions().is_empty());
+    }
+
+    #[derive(serde::Deserialize, JsonSchema)]
+    struct AddInput {
+        a: i64,
+        b: i64,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct AddOutput {
+        sum: i64,
+    }
+
+    #[tokio::test]
+    async fn test_typed_tool_is_listed_and_called() {
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap();
+
+        let list = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/list".to_string(),
+                None,
+                Some(RequestId::number(1)),
+            ))
+            .await;
+        let tools = list.result.unwrap()["tools"].clone();
+        assert_eq!(tools[0]["name"], "add");
+        assert_eq!(tools[0]["inputSchema"]["required"], json!(["a", "b"]));
+        assert_eq!(tools[0]["outputSchema"]["type"], "object");
+
+        let call = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": 2, "b": 3}})),
+                Some(RequestId::number(2)),
+            ))
+            .await;
+        let result = call.result.expect("Expected success response");
+        assert_eq!(result["content"][0]["text"], r#"{"sum":5}"#);
+
+        let invalid = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": "two"}})),
+                Some(RequestId::number(3)),
+            ))
+            .await;
+        assert_eq!(invalid.error.unwrap().code, -32602);
+    }
+
+    #[tokio::test]
+    async fn test_tool_builder_reports_what_it_cannot_register() {
+        let add = |input: AddInput, _ctx| async move {
+            Ok(AddOutput {
+                sum: input.a + input.b,
+            })
+        };
+        let server = create_test_server()
+            .tool("add", "Add two numbers", add)
+            .unwrap();
+
+        assert!(matches!(
+            server.clone().tool("add", "Add again", add),
+            Err(ToolRegistrationError::ServerShared)
+        ));
+        assert!(matches!(
+            server.tool("add", "Add again", add),
+            Err(ToolRegistrationError::ToolAlreadyExists(name)) if name == "add"
+        ));
+        assert!(matches!(
+            create_test_server().tool("add", "", add),
+            Err(ToolRegistrationError::MissingDescription)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_isolated_tools_run_off_the_connection_runtime() {
+        #[derive(Serialize, JsonSchema)]
+        struct ThreadOutput {
+            thread: Option<String>,
+        }
+
+        let runtime = crate::DedicatedRuntime::new("isolated-tools", 1).unwrap();
+        let server = create_initialized_test_server()
+            .await
+            .tool("where", "Name the thread", |_: AddInput, _ctx| async {
+                Ok(ThreadOutput {
+                    thread: std::thread::current().name().map(str::to_string),
+                })
+            })
+            .unwrap()
+            .with_tool_isolation("where", ToolIsolation::dedicated(&runtime));
+
+        let call = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "where", "arguments": {"a": 1, "b": 2}})),
+                Some(RequestId::number(1)),
+            ))
+            .await;
+        let result = call.result.expect("Expected success response");
+        assert_eq!(
+            result["content"][0]["text"],
+            r#"{"thread":"isolated-tools"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_result_summarizes_reported_progress() {
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap()
+            .tool(
+                "add_slowly",
+                "Add two numbers, reporting each step",
+                |input: AddInput, ctx: Context| async move {
+                    ctx.report_progress(1.0, Some(2.0), Some("read a")).await?;
+                    ctx.report_progress(2.0, Some(2.0), Some("read b")).await?;
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap();
+
+        let call = |name: &str| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": name, "arguments": {"a": 2, "b": 3}})),
+                Some(RequestId::number(1)),
+            )
+        };
+        let result = server.handle_request(call("add_slowly")).await.result;
+        let result: ultrafast_mcp_core::types::tools::ToolResult =
+            serde_json::from_value(result.unwrap()).unwrap();
+        let summary = result
+            .progress_summary
+            .expect("Expected a progress summary");
+        assert_eq!(summary.steps_completed, 2);
+        assert_eq!(
+            summary
+                .checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.message.as_deref())
+                .collect::<Vec<_>>(),
+            vec![Some("read a"), Some("read b")]
+        );
+        assert!(summary.checkpoints[0].elapsed_ms <= summary.duration_ms);
+
+        // Calls that report no progress have no summary
+        let result = server.handle_request(call("add")).await.result.unwrap();
+        assert!(result.get("progressSummary").is_none());
+    }
+
+    #[cfg(not(feature = "bare-metal"))]
+    #[tokio::test]
+    async fn test_middleware_wraps_dispatch_in_order() {
+        /// Records the hooks it sees and rejects calls to one tool
+        struct Recorder {
+            name: &'static str,
+            forbidden_tool: Option<&'static str>,
+            events: Arc<std::sync::Mutex<Vec<String>>>,
+        }
+
+        #[async_trait::async_trait]
+        impl ServerMiddleware for Recorder {
+            async fn on_request(&self, request: &mut JsonRpcRequest) -> Result<(), JsonRpcError> {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} request {}", self.name, request.method));
+                let tool = request.params.as_ref().and_then(|p| p.get("name"));
+                match self.forbidden_tool {
+                    Some(forbidden) if tool.and_then(|t| t.as_str()) == Some(forbidden) => {
+                        Err(JsonRpcError::access_denied(forbidden.to_string()))
+                    }
+                    _ => Ok(()),
+                }
+            }
+
+            async fn on_response(&self, request: &RequestInfo, response: &mut JsonRpcResponse) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} response {}", self.name, request.method));
+                response.meta.insert(self.name.to_string(), json!(true));
+            }
+
+            async fn on_error(&self, request: &RequestInfo, error: &mut JsonRpcError) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} error {}", self.name, request.method));
+                error.data = Some(json!({"seenBy": self.name}));
+            }
+        }
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap()
+            .with_middleware(Arc::new(Recorder {
+                name: "outer",
+                forbidden_tool: None,
+                events: events.clone(),
+            }))
+            .with_middleware(Arc::new(Recorder {
+                name: "auth",
+                forbidden_tool: Some("add"),
+                events: events.clone(),
+            }));
+
+        let response = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/list".to_string(),
+                None,
+                Some(RequestId::number(1)),
+            ))
+            .await;
+        assert!(response.result.is_some());
+        assert!(response.meta.contains_key("outer") && response.meta.contains_key("auth"));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "outer request tools/list",
+                "auth request tools/list",
+                "auth response tools/list",
+                "outer response tools/list",
+            ]
+        );
 
-        assert!(server.has_tool("test_tool").await);
-        assert_eq!(server.tool_count().await, 1);
+        events.lock().unwrap().clear();
+        let response = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": 2, "b": 3}})),
+                Some(RequestId::number(2)),
+            ))
+            .await;
+        let error = response.error.expect("Expected the call to be rejected");
+        assert_eq!(error.code, JsonRpcError::access_denied(String::new()).code);
+        // The outer middleware rewrote the error last
+        assert_eq!(error.data, Some(json!({"seenBy": "outer"})));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "outer request tools/call",
+                "auth request tools/call",
+                "auth error tools/call",
+                "outer error tools/call",
+            ]
+        );
     }
 
+    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
     #[tokio::test]
-    async fn test_register_duplicate_tool() {
-        let server = create_test_server();
-        let tool1 = create_valid_tool("test_tool");
-        let tool2 = create_valid_tool("test_tool");
+    async fn test_requests_are_labelled_with_their_transport() {
+        use ultrafast_mcp_transport::{TransportDescription, TransportKind};
+
+        let server = create_initialized_test_server().await.with_monitoring();
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            server
+                .create_client_peer(outgoing_sender)
+                .with_session_id("session-1".to_string())
+                .with_transport(
+                    TransportDescription::new(TransportKind::Http)
+                        .with_remote_address("127.0.0.1:4000"),
+                ),
+        );
+        let transport = peer.transport();
+        assert_eq!(transport.session_id.as_deref(), Some("session-1"));
+        assert_eq!(transport.remote_address.as_deref(), Some("127.0.0.1:4000"));
 
-        server.register_tool(tool1).await.unwrap();
-        let result = server.register_tool(tool2).await;
+        let request =
+            JsonRpcRequest::new("tools/list".to_string(), None, Some(RequestId::number(1)));
+        assert!(server.respond(request, &peer).await.result.is_some());
 
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::ToolAlreadyExists(_))
-        ));
-        assert_eq!(server.tool_count().await, 1);
+        let metrics = server.monitoring().unwrap().metrics().get_metrics().await;
+        assert_eq!(metrics.request.by_transport["http"].total_requests, 1);
+        assert_eq!(metrics.request.method_counts["tools/list"], 1);
     }
 
+    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
     #[tokio::test]
-    async fn test_register_reserved_name() {
-        let server = create_test_server();
-        let tool = create_valid_tool("initialize");
+    async fn test_protocol_rejects_are_recorded_apart_from_requests() {
+        let server = create_initialized_test_server().await.with_monitoring();
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            server
+                .create_client_peer(outgoing_sender)
+                .with_session_id("noisy-client".to_string()),
+        );
 
-        let result = server.register_tool(tool).await;
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::ReservedName(_))
-        ));
-        assert_eq!(server.tool_count().await, 0);
+        let request = JsonRpcRequest::new(
+            "tools/explode".to_string(),
+            None,
+            Some(RequestId::number(1)),
+        );
+        let response = server.respond(request, &peer).await;
+        assert_eq!(response.error.unwrap().code, -32601);
+        let request =
+            JsonRpcRequest::new("tools/list".to_string(), None, Some(RequestId::number(2)));
+        assert!(server.respond(request, &peer).await.result.is_some());
+
+        let metrics = server.monitoring().unwrap().metrics().get_metrics().await;
+        assert_eq!(metrics.request.total_requests, 1);
+        assert!(!metrics.request.method_counts.contains_key("tools/explode"));
+        assert_eq!(metrics.rejects.total_rejects, 1);
+        assert_eq!(
+            metrics.rejects.by_reason[&crate::RejectReason::MethodNotFound],
+            1
+        );
+        assert_eq!(metrics.rejects.by_method["tools/explode"], 1);
+        assert_eq!(metrics.rejects.by_session["noisy-client"], 1);
     }
 
     #[tokio::test]
-    async fn test_register_tool_without_description() {
-        let server = create_test_server();
-        let mut tool = create_valid_tool("test_tool");
-        tool.description = "".to_string();
+    async fn test_domain_errors_are_mapped_to_configured_codes() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("overdrawn by {0}")]
+        struct Overdrawn(i64);
 
-        let result = server.register_tool(tool).await;
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::MissingDescription)
-        ));
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    if input.a + input.b < 0 {
+                        return Err(anyhow::Error::new(Overdrawn(input.a + input.b))
+                            .context("balance check")
+                            .into());
+                    }
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap()
+            .with_domain_error(|error: &Overdrawn| {
+                JsonRpcError::new(-32010, "Insufficient funds".to_string())
+                    .with_data(json!({ "shortfall": -error.0 }))
+            });
+        let call = |a: i64| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": a, "b": 1}})),
+                Some(RequestId::number(1)),
+            )
+        };
+
+        let error = server.handle_request(call(-5)).await.error.unwrap();
+        assert_eq!(error.code, -32010);
+        assert_eq!(error.message, "Insufficient funds");
+        assert_eq!(error.data, Some(json!({ "shortfall": 4 })));
+        assert!(server.handle_request(call(1)).await.result.is_some());
+
+        // Errors no mapper claims keep the default code
+        let error = server
+            .handle_request(JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": "x"}})),
+                Some(RequestId::number(2)),
+            ))
+            .await
+            .error
+            .unwrap();
+        assert_ne!(error.code, -32010);
     }
 
     #[tokio::test]
-    async fn test_register_tool_with_invalid_input_schema() {
-        let server = create_test_server();
-        let mut tool = create_valid_tool("test_tool");
-        tool.input_schema = json!("invalid schema");
+    async fn test_handler_errors_are_localized_from_the_catalog() {
+        use ultrafast_mcp_core::error::ValidationError;
+        use ultrafast_mcp_core::i18n::{LOCALE_META_KEY, MessageCatalog, keys};
 
-        let result = server.register_tool(tool).await;
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::InvalidSchema(_))
-        ));
-    }
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    if input.b == 0 {
+                        return Err(MCPError::Validation(ValidationError::RequiredField {
+                            field: "b".to_string(),
+                        }));
+                    }
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap()
+            .with_message_catalog(MessageCatalog::new().with_messages(
+                "de",
+                [(keys::VALIDATION_REQUIRED_FIELD, "Feld '{field}' fehlt")],
+            ));
+        let call = |meta: serde_json::Value| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": 1, "b": 0}, "_meta": meta})),
+                Some(RequestId::number(1)),
+            )
+        };
 
-    #[tokio::test]
-    async fn test_register_tool_without_output_schema() {
-        let server = create_test_server();
-        let mut tool = create_valid_tool("test_tool");
-        tool.output_schema = None;
+        let error = server
+            .handle_request(call(json!({ LOCALE_META_KEY: "de-DE" })))
+            .await
+            .error
+            .unwrap();
+        assert_eq!(error.code, -32603);
+        assert_eq!(error.message, "Feld 'b' fehlt");
+        assert_eq!(
+            error.data,
+            Some(json!({
+                "messageKey": keys::VALIDATION_REQUIRED_FIELD,
+                "messageArgs": {"field": "b"},
+                "locale": "de"
+            }))
+        );
 
-        let result = server.register_tool(tool).await;
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::MissingOutputSchema)
-        ));
+        let error = server.handle_request(call(json!({}))).await.error.unwrap();
+        assert_eq!(error.message, "Required field 'b' is missing");
+        assert_eq!(error.data.unwrap()["locale"], "en");
     }
 
     #[tokio::test]
-    async fn test_register_tool_with_invalid_schema() {
-        let server = create_test_server();
-        let mut tool = create_valid_tool("test_tool");
-        tool.input_schema = json!("invalid schema");
+    async fn test_strict_schema_validation_checks_arguments_and_output() {
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap()
+            .with_strict_schema_validation(true);
+        // Served by `MockToolHandler`, which answers with plain text
+        server
+            .register_tool(
+                Tool::new(
+                    "describe".to_string(),
+                    "Describe the input".to_string(),
+                    json!({"type": "object", "properties": {"input": {"type": "string"}}}),
+                )
+                .with_output_schema(json!({
+                    "type": "object",
+                    "properties": {"description": {"type": "string"}}
+                })),
+            )
+            .await
+            .unwrap();
+
+        let call = |name: &str, arguments: serde_json::Value| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": name, "arguments": arguments})),
+                Some(RequestId::number(1)),
+            )
+        };
 
-        let result = server.register_tool(tool).await;
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::InvalidSchema(_))
-        ));
-    }
+        let response = server
+            .handle_request(call("add", json!({"a": 2, "b": 3})))
+            .await;
+        assert!(response.result.is_some(), "{:?}", response.error);
 
-    #[tokio::test]
-    async fn test_unregister_tool() {
-        let server = create_test_server();
-        let tool = create_valid_tool("test_tool");
+        let error = server
+            .handle_request(call("add", json!({"a": "two", "b": 3})))
+            .await
+            .error
+            .expect("Expected invalid arguments to be rejected");
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("Invalid arguments for tool 'add'"));
 
-        server.register_tool(tool).await.unwrap();
-        assert!(server.has_tool("test_tool").await);
+        let error = server
+            .handle_request(call("describe", json!({"input": "x"})))
+            .await
+            .error
+            .expect("Expected output not matching the schema to be rejected");
+        assert_eq!(error.code, -32603);
 
-        let result = server.unregister_tool("test_tool");
-        assert!(result.await);
-        assert!(!server.has_tool("test_tool").await);
-        assert_eq!(server.tool_count().await, 0);
+        // Without strict validation the handler's output is passed through
+        let server = server.with_strict_schema_validation(false);
+        let response = server
+            .handle_request(call("describe", json!({"input": "x"})))
+            .await;
+        assert!(response.result.is_some());
     }
 
     #[tokio::test]
-    async fn test_unregister_nonexistent_tool() {
-        let server = create_test_server();
-        let result = server.unregister_tool("nonexistent");
-        assert!(!result.await);
-    }
+    async fn test_resource_template_variables_use_registered_completer() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("notes.md"), "").unwrap();
+        std::fs::write(root.path().join("todo.md"), "").unwrap();
+        let server = create_initialized_test_server()
+            .await
+            .with_resource_template_completer(
+                "file:///{path}",
+                Arc::new(crate::FilePathCompleter::new(root.path())),
+            );
 
-    #[tokio::test]
-    async fn test_register_multiple_tools() {
-        let server = create_test_server();
-        let tools = vec![
-            create_valid_tool("tool1"),
-            create_valid_tool("tool2"),
-            create_valid_tool("tool3"),
-        ];
+        let complete = |variable: &str| {
+            JsonRpcRequest::new(
+                "completion/complete".to_string(),
+                Some(json!({
+                    "ref": {"type": "ref/resource", "uri": "file:///{path}"},
+                    "argument": {"name": variable, "value": "no"}
+                })),
+                Some(RequestId::number(1)),
+            )
+        };
+        let response = server.handle_request(complete("path")).await;
+        let result = response.result.expect("Expected success response");
+        assert_eq!(result["completion"]["values"][0]["value"], "notes.md");
+        assert_eq!(result["completion"]["total"], 1);
 
-        let result = server.register_tools(tools).await;
-        assert!(result.is_ok());
-        assert_eq!(server.tool_count().await, 3);
-        assert!(server.has_tool("tool1").await);
-        assert!(server.has_tool("tool2").await);
-        assert!(server.has_tool("tool3").await);
+        let response = server.handle_request(complete("name")).await;
+        assert_eq!(response.error.unwrap().code, -32602);
     }
 
     #[tokio::test]
-    async fn test_register_multiple_tools_with_duplicate() {
-        let server = create_test_server();
-        let tools = vec![
-            create_valid_tool("tool1"),
-            create_valid_tool("tool1"), // Duplicate
-            create_valid_tool("tool2"),
-        ];
+    async fn test_prompt_registry_serves_prompts_and_completes_arguments() {
+        use crate::prompt_registry::{PromptTemplate, PromptTemplateArgument};
+
+        let registry = PromptRegistry::new();
+        registry
+            .add(
+                PromptTemplate::new("greet")
+                    .with_argument(
+                        PromptTemplateArgument::new("name")
+                            .required(true)
+                            .with_suggestions(["Ada", "Alan", "Grace"]),
+                    )
+                    .user("Say hello to {{name}}"),
+            )
+            .unwrap();
+        let server = create_initialized_test_server()
+            .await
+            .with_prompt_registry(registry);
+        assert!(server.advertised_capabilities().completion.is_some());
+
+        let response = server
+            .handle_request(JsonRpcRequest::new(
+                "prompts/get".to_string(),
+                Some(json!({"name": "greet", "arguments": {"name": "Ada"}})),
+                Some(RequestId::number(1)),
+            ))
+            .await;
+        let result = response.result.expect("Expected success response");
+        assert_eq!(result["messages"][0]["content"]["text"], "Say hello to Ada");
+
+        let response = server
+            .handle_request(JsonRpcRequest::new(
+                "completion/complete".to_string(),
+                Some(json!({
+                    "ref": {"type": "ref/prompt", "name": "greet"},
+                    "argument": {"name": "name", "value": "a"}
+                })),
+                Some(RequestId::number(2)),
+            ))
+            .await;
+        let result = response.result.expect("Expected success response");
+        assert_eq!(result["completion"]["values"][1]["value"], "Alan");
+        assert_eq!(result["completion"]["total"], 2);
+    }
+
+    /// Pages through two prompts one at a time with `offset:<n>` cursors
+    #[cfg(feature = "cursor-signing")]
+    struct PagedPromptHandler;
+
+    #[cfg(feature = "cursor-signing")]
+    #[async_trait::async_trait]
+    impl PromptHandler for PagedPromptHandler {
+        async fn get_prompt(
+            &self,
+            request: ultrafast_mcp_core::types::prompts::GetPromptRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::prompts::GetPromptResponse> {
+            Err(MCPError::not_found(request.name))
+        }
 
-        let result = server.register_tools(tools).await;
-        assert!(matches!(
-            result,
-            Err(ToolRegistrationError::ToolAlreadyExists(_))
-        ));
-        assert_eq!(server.tool_count().await, 1); // Only the first one should be registered
+        async fn list_prompts(
+            &self,
+            request: ultrafast_mcp_core::types::prompts::ListPromptsRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::prompts::ListPromptsResponse> {
+            let offset = match request.cursor.as_deref() {
+                None => 0,
+                Some("offset:1") => 1,
+                Some(cursor) => return Err(MCPError::invalid_params(cursor.to_string())),
+            };
+            Ok(ultrafast_mcp_core::types::prompts::ListPromptsResponse {
+                prompts: vec![Prompt::new(format!("prompt{offset}"))],
+                next_cursor: (offset == 0).then(|| "offset:1".to_string()),
+            })
+        }
     }
 
+    #[cfg(feature = "cursor-signing")]
     #[tokio::test]
-    async fn test_get_tool() {
-        let server = create_test_server();
-        let tool = create_valid_tool("test_tool");
+    async fn test_list_cursors_are_signed_and_verified() {
+        let server = create_initialized_test_server()
+            .await
+            .with_prompt_handler(Arc::new(PagedPromptHandler))
+            .with_cursor_codec(
+                ultrafast_mcp_core::utils::CursorCodec::new("secret").with_encryption(),
+            );
+        let list = |cursor: Option<&str>| {
+            JsonRpcRequest::new(
+                "prompts/list".to_string(),
+                cursor.map(|cursor| json!({"cursor": cursor})),
+                Some(RequestId::number(1)),
+            )
+        };
 
-        server.register_tool(tool.clone()).await.unwrap();
+        let first = server.handle_request(list(None)).await.result.unwrap();
+        let cursor = first["nextCursor"].as_str().unwrap().to_string();
+        assert_ne!(cursor, "offset:1");
 
-        let retrieved = server.get_tool("test_tool").await;
-        assert!(retrieved.is_some());
-        assert_eq!(retrieved.unwrap().name, tool.name);
-    }
+        let second = server.handle_request(list(Some(&cursor))).await;
+        let second = second.result.expect("Expected success response");
+        assert_eq!(second["prompts"][0]["name"], "prompt1");
+        assert!(second.get("nextCursor").is_none());
 
-    #[tokio::test]
-    async fn test_get_nonexistent_tool() {
-        let server = create_test_server();
-        let retrieved = server.get_tool("nonexistent").await;
-        assert!(retrieved.is_none());
+        let forged = server.handle_request(list(Some("offset:1"))).await;
+        assert_eq!(forged.error.unwrap().code, -32602);
     }
 
+    #[cfg(all(feature = "http", feature = "signing"))]
     #[tokio::test]
-    async fn test_list_tools() {
-        let server = create_test_server();
-        let tools = vec![create_valid_tool("tool1"), create_valid_tool("tool2")];
+    async fn test_unsigned_http_requests_are_denied() {
+        use ultrafast_mcp_transport::signing::{SigningConfig, SigningKeypair};
 
-        server.register_tools(tools).await.unwrap();
+        let client_key = SigningKeypair::generate();
+        let signer = MessageSigner::from_config(
+            &SigningConfig::new("server").with_trusted_key("client", client_key.public_key.clone()),
+        )
+        .unwrap();
+        let server = create_test_server().with_message_signer(signer);
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = server.create_client_peer(outgoing_sender);
+        let ping = || {
+            JsonRpcMessage::Request(JsonRpcRequest::new(
+                "ping".to_string(),
+                None,
+                Some(RequestId::number(1)),
+            ))
+        };
 
-        let listed = server.list_tools().await;
-        assert_eq!(listed.len(), 2);
-        assert!(listed.iter().any(|t| t.name == "tool1"));
-        assert!(listed.iter().any(|t| t.name == "tool2"));
+        assert!(server.verify_http_message(ping(), &peer).is_none());
+        match outgoing.try_recv().unwrap() {
+            JsonRpcMessage::Response(response) => {
+                assert_eq!(response.error.unwrap().code, -32005);
+                assert_eq!(response.id, Some(RequestId::number(1)));
+            }
+            other => panic!("expected an error response, got {other:?}"),
+        }
+
+        let client = MessageSigner::from_config(
+            &SigningConfig::new("client").with_private_key(client_key.private_key),
+        )
+        .unwrap();
+        let mut signed = ping();
+        client.sign(&mut signed).unwrap();
+        assert!(server.verify_http_message(signed, &peer).is_some());
+        assert!(outgoing.try_recv().is_err());
     }
 
     #[tokio::test]
-    async fn test_clear_tools() {
+    async fn test_build_info_answered_before_initialization() {
         let server = create_test_server();
-        let tools = vec![create_valid_tool("tool1"), create_valid_tool("tool2")];
-
-        server.register_tools(tools).await.unwrap();
-        assert_eq!(server.tool_count().await, 2);
-
-        server.clear_tools().await;
-        assert_eq!(server.tool_count().await, 0);
-        assert!(!server.has_tool("tool1").await);
-        assert!(!server.has_tool("tool2").await);
+        let request =
+            JsonRpcRequest::new(INFO_METHOD.to_string(), None, Some(RequestId::number(1)));
+        let result = server.handle_request(request).await.result.unwrap();
+        let info: BuildInfo = serde_json::from_value(result).unwrap();
+        assert_eq!(info.server_name, "test-server");
+        assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.protocol_versions[0], "2025-06-18");
     }
 
     #[tokio::test]
-    async fn test_validate_tool_call() {
-        let server = create_test_server();
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
+    async fn test_cancelled_notification_reaches_running_tool() {
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "wait",
+                "Wait until cancelled",
+                |input: AddInput, ctx: Context| async move {
+                    ctx.cancelled().await;
+                    assert!(ctx.is_cancelled().await);
+                    Ok(AddOutput { sum: input.a })
+                },
+            )
+            .unwrap();
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+
+        let request = JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(json!({"name": "wait", "arguments": {"a": 1, "b": 0}})),
+            Some(RequestId::number(9)),
+        );
+        let responder = {
+            let server = server.clone();
+            let peer = peer.clone();
+            tokio::spawn(async move { server.respond(request, &peer).await })
+        };
+        while server
+            .cancellation_manager
+            .active_requests()
+            .await
+            .is_empty()
+        {
+            tokio::task::yield_now().await;
+        }
 
-        let valid_args = json!({"input": "test input"});
-        let result = server.validate_tool_call("test_tool", &valid_args).await;
-        assert!(result.is_ok());
+        server
+            .handle_notification(JsonRpcRequest::notification(
+                "notifications/cancelled".to_string(),
+                Some(json!({"requestId": 9, "reason": "user aborted"})),
+            ))
+            .await
+            .unwrap();
+        let response = responder.await.unwrap();
+        assert_eq!(
+            response.result.unwrap()["content"][0]["text"],
+            "{\"sum\":1}"
+        );
+        assert!(
+            server
+                .cancellation_manager
+                .active_requests()
+                .await
+                .is_empty()
+        );
+        assert!(!server.request_cancellations.is_cancelled("9").await);
     }
 
     #[tokio::test]
-    async fn test_validate_tool_call_invalid_args() {
-        let server = create_test_server();
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
-
-        let invalid_args = json!({"wrong_field": "test input"});
-        let result = server.validate_tool_call("test_tool", &invalid_args).await;
-        assert!(result.is_err());
+    async fn test_tool_progress_uses_request_progress_token() {
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "count",
+                "Count to a",
+                |input: AddInput, ctx: Context| async move {
+                    for step in 1..=input.a {
+                        ctx.report_progress(step as f64, Some(input.a as f64), Some("counting"))
+                            .await?;
+                    }
+                    Ok(AddOutput { sum: input.a })
+                },
+            )
+            .unwrap();
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+
+        let request = JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(json!({
+                "name": "count",
+                "arguments": {"a": 2, "b": 0},
+                "_meta": {"progressToken": "count-1"}
+            })),
+            Some(RequestId::number(3)),
+        );
+        let response = server.respond(request, &peer).await;
+        assert!(response.result.is_some());
+
+        for step in 1..=2 {
+            let Some(JsonRpcMessage::Notification(notification)) = outgoing.recv().await else {
+                panic!("expected a progress notification");
+            };
+            assert_eq!(notification.method, "notifications/progress");
+            let params = notification.params.unwrap();
+            assert_eq!(params["progressToken"], "count-1");
+            assert_eq!(params["progress"], step as f64);
+            assert_eq!(params["total"], 2.0);
+        }
     }
 
     #[tokio::test]
-    async fn test_validate_nonexistent_tool_call() {
-        let server = create_test_server();
-        let args = json!({"input": "test input"});
-        let result = server.validate_tool_call("nonexistent", &args).await;
-        assert!(result.is_err());
+    async fn test_stats_return_to_baseline_after_churn() {
+        let server = create_initialized_test_server()
+            .await
+            .with_subscription_handler(Arc::new(AcceptingSubscriptionHandler))
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap();
+        let baseline = server.stats().await;
+        assert_eq!(baseline, ServerStats::default());
+
+        for session in 0..200 {
+            let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+            let peer = Arc::new(
+                server
+                    .create_client_peer(outgoing_sender)
+                    .with_session_id(format!("session-{session}")),
+            );
+            server.track_peer(&peer);
+            assert_eq!(server.stats().await.live_sessions, 1);
+
+            let requests = [
+                ("resources/subscribe", json!({"uri": "file:///project/**"})),
+                (
+                    "tools/call",
+                    json!({"name": "add", "arguments": {"a": 1, "b": 2}}),
+                ),
+                (
+                    "resources/unsubscribe",
+                    json!({"uri": "file:///project/**"}),
+                ),
+            ];
+            for (id, (method, params)) in requests.into_iter().enumerate() {
+                let request = JsonRpcRequest::new(
+                    method.to_string(),
+                    Some(params),
+                    Some(RequestId::number(id as i64)),
+                );
+                assert!(server.respond(request, &peer).await.result.is_some());
+            }
+        }
+
+        assert_eq!(server.stats().await, baseline);
     }
 
     #[tokio::test]
-    async fn test_execute_tool_call() {
-        let server = create_test_server();
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
+    async fn test_tools_keep_state_per_session() {
+        let server = create_initialized_test_server()
+            .await
+            .tool(
+                "add",
+                "Add to the session's running total",
+                |input: AddInput, ctx| async move {
+                    let sum = ctx.session().update::<i64, _>("total", |total| {
+                        let sum = total.copied().unwrap_or(0) + input.a + input.b;
+                        (sum, sum)
+                    });
+                    Ok(AddOutput { sum })
+                },
+            )
+            .unwrap();
+        let connect = |session: &str| {
+            let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+            Arc::new(
+                server
+                    .create_client_peer(outgoing_sender)
+                    .with_session_id(session.to_string()),
+            )
+        };
+        let (alice, bob) = (connect("alice"), connect("bob"));
+        let add = |a: i32| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": a, "b": 0}})),
+                Some(RequestId::number(1)),
+            )
+        };
+        let sum = |response: JsonRpcResponse| {
+            response.result.unwrap()["structuredContent"]["sum"].clone()
+        };
 
-        let args = json!({"input": "test input"});
-        let result = server.execute_tool_call("test_tool", args).await;
-        assert!(result.is_ok());
+        assert_eq!(sum(server.respond(add(1), &alice).await), 1);
+        assert_eq!(sum(server.respond(add(2), &alice).await), 3);
+        assert_eq!(sum(server.respond(add(5), &bob).await), 5);
 
-        let tool_result = result.unwrap();
-        assert_eq!(tool_result.content.len(), 1);
-        assert!(!tool_result.is_error.unwrap_or(false));
+        server.session_states.remove("alice");
+        assert_eq!(sum(server.respond(add(1), &alice).await), 1);
     }
 
+    #[cfg(feature = "content-encoding")]
     #[tokio::test]
-    async fn test_execute_tool_call_without_handler() {
-        let server = UltraFastServer::new(
-            ServerInfo {
-                name: "test-server".to_string(),
-                version: "1.0.0".to_string(),
-                description: Some("Test server".to_string()),
-                homepage: None,
-                repository: None,
-                authors: Some(vec!["test".to_string()]),
-                license: Some("MIT".to_string()),
-            },
-            ServerCapabilities::default(),
+    async fn test_large_content_is_compressed_for_clients_that_accept_it() {
+        #[derive(Serialize, JsonSchema)]
+        struct Report {
+            text: String,
+        }
+        let server = create_initialized_test_server()
+            .await
+            .with_content_compression(Some(1024))
+            .tool(
+                "report",
+                "Produce a long report",
+                |input: AddInput, _ctx| async move {
+                    Ok(Report {
+                        text: "all systems nominal\n".repeat(input.a as usize),
+                    })
+                },
+            )
+            .unwrap();
+        let connect = |compression: bool| {
+            let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+            let peer = Arc::new(server.create_client_peer(outgoing_sender));
+            let mut capabilities = ultrafast_mcp_core::protocol::ClientCapabilities::default();
+            if compression {
+                content_encoding::advertise(&mut capabilities);
+            }
+            peer.set_client_capabilities(capabilities);
+            peer
+        };
+        let report = |lines: i64| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "report", "arguments": {"a": lines, "b": 0}})),
+                Some(RequestId::number(1)),
+            )
+        };
+
+        let plain = server
+            .respond(report(1000), &connect(false))
+            .await
+            .result
+            .unwrap();
+        assert!(plain["content"][0].get("_meta").is_none());
+
+        let mut compressed = server
+            .respond(report(1000), &connect(true))
+            .await
+            .result
+            .unwrap();
+        let item = &compressed["content"][0];
+        assert_eq!(
+            item["_meta"][content_encoding::CONTENT_ENCODING_META_KEY],
+            content_encoding::GZIP_BASE64
         );
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
+        assert!(
+            item["text"].as_str().unwrap().len()
+                < plain["content"][0]["text"].as_str().unwrap().len() / 10
+        );
+        assert_eq!(
+            content_encoding::decode_content(&mut compressed).unwrap(),
+            1
+        );
+        assert_eq!(compressed["content"], plain["content"]);
 
-        let args = json!({"input": "test input"});
-        let result = server.execute_tool_call("test_tool", args).await;
-        assert!(result.is_err());
+        // Small items are sent as they are
+        let small = server
+            .respond(report(10), &connect(true))
+            .await
+            .result
+            .unwrap();
+        assert!(small["content"][0].get("_meta").is_none());
     }
 
     #[tokio::test]
-    async fn test_reserved_names() {
+    async fn test_negotiated_protocol_version_is_kept_on_the_peer() {
         let server = create_test_server();
-        let reserved_names = [
-            "initialize",
-            "initialized",
-            "shutdown",
-            "exit",
-            "ping",
-            "tools/list",
-            "tools/call",
-            "resources/list",
-            "resources/read",
-            "resources/subscribe",
-            "resources/unsubscribe",
-            "prompts/list",
-            "prompts/get",
-            "sampling/create",
-            "completion/complete",
-            "roots/list",
-            "elicitation/request",
-        ];
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        assert_eq!(peer.protocol_version(), None);
+
+        let initialize = JsonRpcRequest::new(
+            "initialize".to_string(),
+            Some(json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": {"name": "test-client", "version": "1.0.0"}
+            })),
+            Some(RequestId::number(0)),
+        );
+        let response = server.respond(initialize, &peer).await;
+        assert_eq!(response.result.unwrap()["protocolVersion"], "2025-03-26");
+        assert_eq!(peer.protocol_version().as_deref(), Some("2025-03-26"));
 
-        for name in &reserved_names {
-            let tool = create_valid_tool(name);
-            let result = server.register_tool(tool).await;
-            assert!(matches!(
-                result,
-                Err(ToolRegistrationError::ReservedName(_))
-            ));
-        }
+        let context = Context::new().with_client_peer(peer);
+        assert_eq!(context.protocol_version().as_deref(), Some("2025-03-26"));
     }
 
+    #[cfg(feature = "http")]
     #[tokio::test]
-    async fn test_tools_list_jsonrpc() {
-        let server = create_initialized_test_server().await;
+    async fn test_openai_tools_requests_initialize_the_server() {
+        let server = create_test_server()
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap();
+        assert!(!server.can_operate().await);
 
-        // Register some tools
-        let tools = vec![create_valid_tool("tool1"), create_valid_tool("tool2")];
-        server.register_tools(tools).await.unwrap();
+        let response = server
+            .handle_openai_request(JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": 2, "b": 3}})),
+                Some(RequestId::number(1)),
+            ))
+            .await;
+        assert_eq!(response.result.unwrap()["structuredContent"]["sum"], 5);
+        assert!(server.can_operate().await);
+    }
 
-        // Create tools/list request
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/list".to_string(),
-            params: None,
-            meta: std::collections::HashMap::new(),
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_openai_tools_requests_are_rate_limited() {
+        let server = create_test_server()
+            .tool(
+                "add",
+                "Add two numbers",
+                |input: AddInput, _ctx| async move {
+                    Ok(AddOutput {
+                        sum: input.a + input.b,
+                    })
+                },
+            )
+            .unwrap()
+            .with_rate_limit(RateLimitConfig::new().with_per_session(RateLimit::per_minute(1)));
+        let call = |id: i64| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "add", "arguments": {"a": 2, "b": 3}})),
+                Some(RequestId::number(id)),
+            )
         };
 
-        let response = server.handle_request(request).await;
+        assert!(server.handle_openai_request(call(1)).await.error.is_none());
+        let error = server.handle_openai_request(call(2)).await.error.unwrap();
+        assert_eq!(
+            error.code,
+            ultrafast_mcp_core::error::error_codes::RATE_LIMIT_ERROR
+        );
+        assert_eq!(error.data.unwrap()["scope"], "session");
+    }
 
-        // Verify response
-        if let Some(result) = &response.result {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
+    #[tokio::test]
+    async fn test_list_changed_notifications_are_debounced() {
+        let server = create_initialized_test_server()
+            .await
+            .with_list_changed_debounce(std::time::Duration::from_millis(20));
+        let connect = |session: &str| {
+            let (outgoing_sender, outgoing) = mpsc::unbounded_channel();
+            let peer = Arc::new(
+                server
+                    .create_client_peer(outgoing_sender)
+                    .with_session_id(session.to_string()),
             );
-            let tools_array = result.get("tools").and_then(|t| t.as_array()).unwrap();
-            assert_eq!(tools_array.len(), 2);
+            server.track_peer(&peer);
+            (peer, outgoing)
+        };
+        let (_a, mut a_outgoing) = connect("a");
+        let (_b, mut b_outgoing) = connect("b");
 
-            let tool_names: Vec<&str> = tools_array
-                .iter()
-                .filter_map(|t| t.get("name").and_then(|n| n.as_str()))
-                .collect();
-            assert!(tool_names.contains(&"tool1"));
-            assert!(tool_names.contains(&"tool2"));
-        } else {
-            panic!("Expected success response");
-        }
+        server.notify_tools_changed().await.unwrap();
+        server.notify_tools_changed().await.unwrap();
+        server
+            .notify_list_changed(ListKind::Prompts, Some("a"))
+            .await
+            .unwrap();
+        assert!(a_outgoing.try_recv().is_err());
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let received = |outgoing: &mut mpsc::UnboundedReceiver<JsonRpcMessage>| {
+            let mut methods = Vec::new();
+            while let Ok(JsonRpcMessage::Notification(notification)) = outgoing.try_recv() {
+                methods.push(notification.method);
+            }
+            methods.sort();
+            methods
+        };
+        assert_eq!(
+            received(&mut a_outgoing),
+            [
+                "notifications/prompts/listChanged",
+                "notifications/tools/listChanged"
+            ]
+        );
+        assert_eq!(
+            received(&mut b_outgoing),
+            ["notifications/tools/listChanged"]
+        );
     }
 
     #[tokio::test]
-    async fn test_tools_call_jsonrpc_success() {
-        let server = create_initialized_test_server().await;
+    async fn test_requests_over_the_rate_limit_are_rejected() {
+        let server = create_initialized_test_server()
+            .await
+            .with_rate_limit(RateLimitConfig::new().with_per_session(RateLimit::per_minute(1)));
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(
+            server
+                .create_client_peer(outgoing_sender)
+                .with_session_id("session-1".to_string()),
+        );
+        let list = |id: i64| {
+            JsonRpcRequest::new("tools/list".to_string(), None, Some(RequestId::number(id)))
+        };
 
-        // Register a tool
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
+        assert!(server.respond(list(1), &peer).await.result.is_some());
+        let error = server.respond(list(2), &peer).await.error.unwrap();
+        assert_eq!(
+            error.code,
+            ultrafast_mcp_core::error::error_codes::RATE_LIMIT_ERROR
+        );
+        let data = error.data.unwrap();
+        assert_eq!(data["scope"], "session");
+        assert!(data["retryAfterMs"].as_u64().unwrap() > 59_000);
+        let Ok(JsonRpcMessage::Notification(notification)) = outgoing.try_recv() else {
+            panic!("expected a rate limit notification");
+        };
+        assert_eq!(notification.method, RATE_LIMIT_NOTIFICATION_METHOD);
+        assert_eq!(notification.params.unwrap()["maxCount"], 1);
 
-        // Create tools/call request
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "name": "test_tool",
-                "arguments": {
-                    "input": "test input"
+        let ping = JsonRpcRequest::new("ping".to_string(), None, Some(RequestId::number(3)));
+        assert!(server.respond(ping, &peer).await.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_the_concurrency_limit_are_rejected() {
+        let started = Arc::new(tokio::sync::Notify::new());
+        let release = Arc::new(tokio::sync::Notify::new());
+        let server = create_initialized_test_server()
+            .await
+            .tool("wait", "Wait to be released", {
+                let (started, release) = (started.clone(), release.clone());
+                move |input: AddInput, _ctx| {
+                    let (started, release) = (started.clone(), release.clone());
+                    async move {
+                        started.notify_one();
+                        release.notified().await;
+                        Ok(AddOutput {
+                            sum: input.a + input.b,
+                        })
+                    }
                 }
-            })),
-            meta: std::collections::HashMap::new(),
+            })
+            .unwrap()
+            .with_max_concurrent_requests(1);
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        let call = |id: i64| {
+            JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": "wait", "arguments": {"a": 1, "b": 2}})),
+                Some(RequestId::number(id)),
+            )
         };
 
-        let response = server.handle_request(request).await;
+        let running = tokio::spawn({
+            let (server, peer, request) = (server.clone(), peer.clone(), call(1));
+            async move { server.respond(request, &peer).await }
+        });
+        started.notified().await;
 
-        // Verify response
-        if let Some(result) = &response.result {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
+        let error = server.respond(call(2), &peer).await.error.unwrap();
+        assert_eq!(
+            error.code,
+            ultrafast_mcp_core::error::error_codes::SERVER_BUSY
+        );
+        assert_eq!(error.data.unwrap()["maxConcurrent"], 1);
+        let ping = JsonRpcRequest::new("ping".to_string(), None, Some(RequestId::number(3)));
+        assert!(server.respond(ping, &peer).await.error.is_none());
+
+        release.notify_one();
+        assert!(running.await.unwrap().result.is_some());
+        release.notify_one();
+        assert!(server.respond(call(4), &peer).await.result.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resource_updates_reach_subscribed_sessions() {
+        let server = create_initialized_test_server()
+            .await
+            .with_subscription_handler(Arc::new(AcceptingSubscriptionHandler));
+        let connect = |session: &str| {
+            let (outgoing_sender, outgoing) = mpsc::unbounded_channel();
+            let peer = Arc::new(
+                server
+                    .create_client_peer(outgoing_sender)
+                    .with_session_id(session.to_string()),
             );
+            server.track_peer(&peer);
+            (peer, outgoing)
+        };
+        let (watcher, mut watcher_outgoing) = connect("watcher");
+        let (_other, mut other_outgoing) = connect("other");
 
-            // Check that result contains content
-            let content = result.get("content").and_then(|c| c.as_array()).unwrap();
-            assert_eq!(content.len(), 1);
+        let subscribe = JsonRpcRequest::new(
+            "resources/subscribe".to_string(),
+            Some(json!({"uri": "file:///project/**"})),
+            Some(RequestId::number(1)),
+        );
+        assert!(server.respond(subscribe, &watcher).await.result.is_some());
 
-            // The ToolContent::text creates a structure with "type": "text" and "text" field
-            let text_content = content[0].get("text").and_then(|t| t.as_str()).unwrap();
-            assert!(text_content.contains("Mock result for test_tool"));
-        } else {
-            panic!("Expected success response");
+        let notified = server
+            .notify_resource_updated("file:///project/main.rs", json!({"text": "fn main() {}"}))
+            .await
+            .unwrap();
+        assert_eq!(notified, 1);
+        let Ok(JsonRpcMessage::Notification(update)) = watcher_outgoing.try_recv() else {
+            panic!("expected a resource updated notification");
+        };
+        assert_eq!(update.method, "notifications/resources/updated");
+        assert_eq!(update.params.unwrap()["uri"], "file:///project/main.rs");
+        assert!(other_outgoing.try_recv().is_err());
+
+        assert_eq!(server.notify_resource_list_changed().await.unwrap(), 2);
+        for outgoing in [&mut watcher_outgoing, &mut other_outgoing] {
+            let Ok(JsonRpcMessage::Notification(changed)) = outgoing.try_recv() else {
+                panic!("expected a list changed notification");
+            };
+            assert_eq!(changed.method, "notifications/resources/listChanged");
         }
     }
 
     #[tokio::test]
-    async fn test_tools_call_jsonrpc_missing_params() {
-        let server = create_initialized_test_server().await;
+    async fn test_fs_resource_watch_notifies_modified_and_added_files() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("notes.md"), "first").unwrap();
+        let files = crate::FsResourceHandler::new(root.path());
+        let server = create_initialized_test_server()
+            .await
+            .with_subscription_handler(Arc::new(AcceptingSubscriptionHandler))
+            .with_list_changed_debounce(std::time::Duration::ZERO);
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        server.track_peer(&peer);
+        let subscribe = JsonRpcRequest::new(
+            "resources/subscribe".to_string(),
+            Some(json!({"uri": format!("{}/**", files.base_uri())})),
+            Some(RequestId::number(1)),
+        );
+        assert!(server.respond(subscribe, &peer).await.result.is_some());
+
+        let watcher = files.watch(server.clone(), std::time::Duration::from_millis(20));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        std::fs::write(root.path().join("notes.md"), "second version").unwrap();
+        std::fs::write(root.path().join("todo.md"), "").unwrap();
+
+        let mut methods = Vec::new();
+        while methods.len() < 2 {
+            let message = tokio::time::timeout(std::time::Duration::from_secs(5), outgoing.recv())
+                .await
+                .expect("watcher should notify")
+                .unwrap();
+            if let JsonRpcMessage::Notification(notification) = message {
+                if notification.method == "notifications/resources/updated" {
+                    assert_eq!(
+                        notification.params.unwrap()["uri"],
+                        files.uri_for("notes.md")
+                    );
+                }
+                methods.push(notification.method);
+            }
+        }
+        methods.sort();
+        assert_eq!(
+            methods,
+            [
+                "notifications/resources/listChanged",
+                "notifications/resources/updated"
+            ]
+        );
+        watcher.abort();
+    }
 
-        // Create tools/call request without parameters
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/call".to_string(),
-            params: None,
-            meta: std::collections::HashMap::new(),
+    #[tokio::test]
+    async fn test_resource_registry_publishes_runtime_changes() {
+        let registry = crate::ResourceRegistry::new();
+        registry.add_text("config://app", "debug = false");
+        let server = create_initialized_test_server()
+            .await
+            .with_subscription_handler(Arc::new(AcceptingSubscriptionHandler))
+            .with_list_changed_debounce(std::time::Duration::ZERO)
+            .with_resource_handler(registry.clone());
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        server.track_peer(&peer);
+        let subscribe = JsonRpcRequest::new(
+            "resources/subscribe".to_string(),
+            Some(json!({"uri": "config://app"})),
+            Some(RequestId::number(1)),
+        );
+        assert!(server.respond(subscribe, &peer).await.result.is_some());
+        let publisher = registry.publish_changes(server.clone());
+
+        let mut next_notification = async || loop {
+            let message = tokio::time::timeout(std::time::Duration::from_secs(5), outgoing.recv())
+                .await
+                .expect("registry change should be notified")
+                .unwrap();
+            if let JsonRpcMessage::Notification(notification) = message {
+                return notification;
+            }
         };
+        registry.add_text("config://app", "debug = true");
+        let updated = next_notification().await;
+        assert_eq!(updated.method, "notifications/resources/updated");
+        assert_eq!(updated.params.unwrap()["uri"], "config://app");
+
+        registry.add_text("config://db", "url = sqlite://");
+        assert_eq!(
+            next_notification().await.method,
+            "notifications/resources/listChanged"
+        );
+        let read = JsonRpcRequest::new(
+            "resources/read".to_string(),
+            Some(json!({"uri": "config://app"})),
+            Some(RequestId::number(2)),
+        );
+        let response = server.respond(read, &peer).await;
+        assert_eq!(
+            response.result.unwrap()["contents"][0]["text"],
+            "debug = true"
+        );
+        publisher.abort();
+    }
 
-        let response = server.handle_request(request).await;
+    #[tokio::test]
+    async fn test_requests_to_features_without_handlers() {
+        let unsupported = [
+            ("resources/list", "resources"),
+            ("resources/read", "resources"),
+            ("resources/templates/list", "resources"),
+            ("resources/subscribe", "resources.subscribe"),
+            ("resources/unsubscribe", "resources.subscribe"),
+            ("prompts/list", "prompts"),
+            ("prompts/get", "prompts"),
+            ("completion/complete", "completion"),
+            ("sampling/createMessage", "sampling"),
+            ("roots/list", "roots"),
+            ("elicitation/create", "elicitation"),
+        ];
+        let policies = [
+            (UnsupportedMethodPolicy::MethodNotFound, -32601),
+            (UnsupportedMethodPolicy::CapabilityNotSupported, -32001),
+        ];
 
-        // Verify error response
-        if let Some(error) = &response.error {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
-            );
-            assert_eq!(error.code, -32602); // Invalid params
-            assert!(error.message.contains("Missing parameters"));
-        } else {
-            panic!("Expected error response");
+        for (policy, code) in policies {
+            let server = create_test_server().with_unsupported_method_policy(policy);
+            *server.state.write().await = ServerState::Operating;
+            for (n, (method, capability)) in unsupported.iter().enumerate() {
+                let params = json!({
+                    "uri": "test://resource",
+                    "name": "prompt",
+                    "ref": {"type": "ref/prompt", "name": "prompt"},
+                    "argument": {"name": "a", "value": ""},
+                    "messages": [],
+                    "maxTokens": 1,
+                    "message": "?",
+                    "requestedSchema": {"type": "object"},
+                });
+                let response = server
+                    .handle_request(JsonRpcRequest::new(
+                        method.to_string(),
+                        Some(params),
+                        Some(RequestId::number(n as i64)),
+                    ))
+                    .await;
+                let error = response
+                    .error
+                    .unwrap_or_else(|| panic!("{method} should be unsupported"));
+                assert_eq!(error.code, code, "{method}");
+                let data = error.data.unwrap();
+                assert_eq!(data["method"], *method);
+                assert_eq!(data["capability"], *capability);
+            }
         }
     }
 
     #[tokio::test]
-    async fn test_tools_call_jsonrpc_missing_name() {
-        let server = create_initialized_test_server().await;
+    async fn test_advertised_capabilities_follow_handlers() {
+        struct NoPrompts;
+
+        #[async_trait::async_trait]
+        impl PromptHandler for NoPrompts {
+            async fn get_prompt(
+                &self,
+                request: ultrafast_mcp_core::types::prompts::GetPromptRequest,
+            ) -> MCPResult<ultrafast_mcp_core::types::prompts::GetPromptResponse> {
+                Err(MCPError::not_found(request.name))
+            }
 
-        // Create tools/call request without tool name
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "arguments": {
-                    "input": "test input"
-                }
-            })),
-            meta: std::collections::HashMap::new(),
+            async fn list_prompts(
+                &self,
+                _request: ultrafast_mcp_core::types::prompts::ListPromptsRequest,
+            ) -> MCPResult<ultrafast_mcp_core::types::prompts::ListPromptsResponse> {
+                Ok(ultrafast_mcp_core::types::prompts::ListPromptsResponse {
+                    prompts: Vec::new(),
+                    next_cursor: None,
+                })
+            }
+        }
+
+        let configured = ServerCapabilities {
+            prompts: Some(
+                ultrafast_mcp_core::protocol::capabilities::PromptsCapability {
+                    list_changed: Some(true),
+                },
+            ),
+            ..Default::default()
         };
+        let server = UltraFastServer::new(create_test_server().info, configured);
+        let advertised = server.advertised_capabilities();
+        assert!(advertised.prompts.is_none());
+        assert!(advertised.tools.is_none());
+
+        let server = server
+            .with_tool_handler(Arc::new(MockToolHandler))
+            .with_prompt_handler(Arc::new(NoPrompts))
+            .with_resource_handler(crate::StaticResources::new());
+        let advertised = server.advertised_capabilities();
+        assert!(advertised.tools.is_some());
+        assert!(advertised.resources.is_some());
+        assert_eq!(advertised.prompts.unwrap().list_changed, Some(true));
+        assert!(advertised.completion.is_none());
+    }
 
-        let response = server.handle_request(request).await;
+    #[tokio::test]
+    async fn test_response_meta_reports_execution() {
+        // Registered tools only, so that the list is cached
+        let mut server = create_test_server().with_response_meta();
+        server.tool_handler = None;
+        *server.state.write().await = ServerState::Operating;
+        let (outgoing_sender, _outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        let list =
+            || JsonRpcRequest::new("tools/list".to_string(), None, Some(RequestId::number(1)));
 
-        // Verify error response
-        if let Some(error) = &response.error {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
-            );
-            assert_eq!(error.code, -32602); // Invalid params
-            assert!(error.message.contains("Missing or invalid tool name"));
-        } else {
-            panic!("Expected error response");
+        let first = server.respond(list(), &peer).await.result.unwrap();
+        let meta = ResponseMeta::from_result(&first).expect("meta attached");
+        assert!(!meta.cache_hit);
+        assert!(meta.bytes_processed > 0);
+
+        let second = server.respond(list(), &peer).await.result.unwrap();
+        assert!(ResponseMeta::from_result(&second).unwrap().cache_hit);
+
+        let plain = create_test_server();
+        *plain.state.write().await = ServerState::Operating;
+        let result = plain.respond(list(), &peer).await.result.unwrap();
+        assert!(ResponseMeta::from_result(&result).is_none());
+    }
+
+    /// Sleeps for the number of milliseconds given as `input`
+    struct SleepyToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for SleepyToolHandler {
+        async fn handle_tool_call(
+            &self,
+            call: ultrafast_mcp_core::types::tools::ToolCall,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ToolResult> {
+            let millis = call
+                .arguments
+                .and_then(|args| args["input"].as_str()?.parse().ok())
+                .unwrap_or(0);
+            tokio::time::sleep(std::time::Duration::from_millis(millis)).await;
+            Ok(ultrafast_mcp_core::types::tools::ToolResult {
+                content: vec![ToolContent::text(format!("slept {millis}ms"))],
+                is_error: None,
+                structured_content: None,
+                progress_summary: None,
+            })
+        }
+
+        async fn list_tools(
+            &self,
+            _request: ultrafast_mcp_core::types::tools::ListToolsRequest,
+        ) -> MCPResult<ultrafast_mcp_core::types::tools::ListToolsResponse> {
+            Ok(ultrafast_mcp_core::types::tools::ListToolsResponse {
+                tools: vec![],
+                next_cursor: None,
+            })
         }
     }
 
+    fn nap_request(id: i64, millis: u64) -> JsonRpcMessage {
+        JsonRpcMessage::Request(JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(json!({"name": "nap", "arguments": {"input": millis.to_string()}})),
+            Some(RequestId::number(id)),
+        ))
+    }
+
     #[tokio::test]
-    async fn test_tools_call_jsonrpc_nonexistent_tool() {
-        let server = create_initialized_test_server().await;
+    async fn test_shutdown_graceful_drains_then_aborts() {
+        let server = create_initialized_test_server()
+            .await
+            .with_tool_handler(Arc::new(SleepyToolHandler));
+        server
+            .register_tool(create_valid_tool("nap"))
+            .await
+            .unwrap();
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        server.track_peer(&peer);
+
+        server.dispatch_message(nap_request(1, 20), &peer).await;
+        server.dispatch_message(nap_request(2, 60_000), &peer).await;
+        let report = server
+            .shutdown_graceful(std::time::Duration::from_millis(500))
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ShutdownReport {
+                drained: 1,
+                aborted: 1
+            }
+        );
+        assert_eq!(server.get_state().await, ServerState::Shutdown);
 
-        // Create tools/call request for non-existent tool
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "name": "nonexistent_tool",
-                "arguments": {
-                    "input": "test input"
-                }
-            })),
-            meta: std::collections::HashMap::new(),
+        let Some(JsonRpcMessage::Notification(notice)) = outgoing.recv().await else {
+            panic!("expected the shutdown notification first");
+        };
+        assert_eq!(notice.method, SHUTDOWN_NOTIFICATION_METHOD);
+        // The quick request finished before the slow one was aborted
+        let Some(JsonRpcMessage::Response(drained)) = outgoing.recv().await else {
+            panic!("expected a response");
         };
+        assert_eq!(drained.id, Some(RequestId::number(1)));
+        assert!(drained.result.is_some());
+        let Some(JsonRpcMessage::Response(aborted)) = outgoing.recv().await else {
+            panic!("expected a response");
+        };
+        assert_eq!(aborted.id, Some(RequestId::number(2)));
+        assert_eq!(aborted.error.unwrap().code, -32000);
 
-        let response = server.handle_request(request).await;
+        // Refused once shutting down
+        server.dispatch_message(nap_request(3, 0), &peer).await;
+        let Some(JsonRpcMessage::Response(refused)) = outgoing.recv().await else {
+            panic!("expected a response");
+        };
+        assert_eq!(refused.error.unwrap().message, "Server is shutting down");
+    }
 
-        // Verify error response
-        if let Some(error) = &response.error {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
-            );
-            assert_eq!(error.code, -32602); // Invalid params
-            assert!(error.message.contains("Tool call failed:"));
-            assert!(error.message.contains("Tool not found"));
-        } else {
-            panic!("Expected error response");
+    /// One end of an in-process connection
+    struct ChannelTransport {
+        sender: mpsc::UnboundedSender<JsonRpcMessage>,
+        receiver: mpsc::UnboundedReceiver<JsonRpcMessage>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for ChannelTransport {
+        async fn send_message(
+            &mut self,
+            message: JsonRpcMessage,
+        ) -> ultrafast_mcp_transport::Result<()> {
+            self.sender
+                .send(message)
+                .map_err(|_| ultrafast_mcp_transport::TransportError::ConnectionClosed)
+        }
+
+        async fn receive_message(&mut self) -> ultrafast_mcp_transport::Result<JsonRpcMessage> {
+            self.receiver
+                .recv()
+                .await
+                .ok_or(ultrafast_mcp_transport::TransportError::ConnectionClosed)
+        }
+
+        async fn close(&mut self) -> ultrafast_mcp_transport::Result<()> {
+            self.receiver.close();
+            Ok(())
         }
     }
 
     #[tokio::test]
-    async fn test_tools_call_jsonrpc_invalid_arguments() {
-        let server = create_initialized_test_server().await;
-
-        // Register a tool
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
+    async fn test_clients_not_answering_pings_are_disconnected() {
+        let server = create_test_server().with_ping_policy(
+            PingPolicy::new(std::time::Duration::from_millis(10))
+                .with_timeout(std::time::Duration::from_millis(10))
+                .with_max_missed(2)
+                .with_disconnect(true),
+        );
+        let mut events = server.subscribe_ping_events();
+        let (client_sender, server_receiver) = mpsc::unbounded_channel();
+        let (server_sender, mut client_receiver) = mpsc::unbounded_channel();
+        let transport = ChannelTransport {
+            sender: server_sender,
+            receiver: server_receiver,
+        };
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move { server.run_with_transport(Box::new(transport)).await }
+        });
 
-        // Create tools/call request with invalid arguments
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "name": "test_tool",
-                "arguments": {
-                    "wrong_field": "test input"
-                }
-            })),
-            meta: std::collections::HashMap::new(),
+        client_sender
+            .send(JsonRpcMessage::Request(JsonRpcRequest::new(
+                "initialize".to_string(),
+                Some(json!({
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": {"name": "test-client", "version": "1.0.0"}
+                })),
+                Some(RequestId::number(0)),
+            )))
+            .unwrap();
+        let Some(JsonRpcMessage::Response(_)) = client_receiver.recv().await else {
+            panic!("expected the initialize response");
+        };
+        // The pings that follow are never answered
+        let Some(JsonRpcMessage::Request(ping)) = client_receiver.recv().await else {
+            panic!("expected a ping");
         };
+        assert_eq!(ping.method, "ping");
+
+        let mut received = Vec::new();
+        while received.last() != Some(&PingEvent::Disconnected) {
+            let (_, event) = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv())
+                .await
+                .expect("the policy should close the connection")
+                .unwrap();
+            received.push(event);
+        }
+        assert_eq!(
+            received,
+            [
+                PingEvent::Missed { consecutive: 1 },
+                PingEvent::Unhealthy { consecutive: 2 },
+                PingEvent::Disconnected
+            ]
+        );
+        tokio::time::timeout(std::time::Duration::from_secs(5), running)
+            .await
+            .expect("the connection should be closed")
+            .unwrap()
+            .unwrap();
+    }
 
-        let response = server.handle_request(request).await;
+    #[tokio::test]
+    async fn test_shutdown_graceful_stops_run_with_transport() {
+        let server = create_initialized_test_server()
+            .await
+            .with_tool_handler(Arc::new(SleepyToolHandler));
+        server
+            .register_tool(create_valid_tool("nap"))
+            .await
+            .unwrap();
+        let (client_sender, server_receiver) = mpsc::unbounded_channel();
+        let (server_sender, mut client_receiver) = mpsc::unbounded_channel();
+        let transport = ChannelTransport {
+            sender: server_sender,
+            receiver: server_receiver,
+        };
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move { server.run_with_transport(Box::new(transport)).await }
+        });
 
-        // Verify error response
-        if let Some(error) = &response.error {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
-            );
-            assert_eq!(error.code, -32602); // Invalid params
-            assert!(error.message.contains("Invalid parameters"));
-        } else {
-            panic!("Expected error response");
+        client_sender
+            .send(JsonRpcMessage::Request(JsonRpcRequest::new(
+                "initialize".to_string(),
+                Some(json!({
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": {"name": "test-client", "version": "1.0.0"}
+                })),
+                Some(RequestId::number(0)),
+            )))
+            .unwrap();
+        let Some(JsonRpcMessage::Response(initialized)) = client_receiver.recv().await else {
+            panic!("expected the initialize response");
+        };
+        assert!(initialized.result.is_some());
+        client_sender
+            .send(JsonRpcMessage::Notification(JsonRpcRequest::notification(
+                "notifications/initialized".to_string(),
+                None,
+            )))
+            .unwrap();
+        client_sender.send(nap_request(1, 200)).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while server.shutdown.in_flight() == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("request should be in flight");
+        let report = server
+            .shutdown_graceful(std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(report.drained, 1);
+        tokio::time::timeout(std::time::Duration::from_secs(5), running)
+            .await
+            .expect("run_with_transport should return after shutdown")
+            .unwrap()
+            .unwrap();
+
+        let mut methods = Vec::new();
+        while let Ok(message) = client_receiver.try_recv() {
+            methods.push(match message {
+                JsonRpcMessage::Notification(n) | JsonRpcMessage::Request(n) => n.method,
+                JsonRpcMessage::Response(response) => format!("response {:?}", response.id),
+            });
         }
+        assert_eq!(
+            methods,
+            [
+                SHUTDOWN_NOTIFICATION_METHOD.to_string(),
+                format!("response {:?}", Some(RequestId::number(1)))
+            ]
+        );
     }
 
-    #[tokio::test]
-    async fn test_tools_call_jsonrpc_empty_arguments() {
-        let server = create_initialized_test_server().await;
+    /// A [`ChannelTransport`] counting the bytes of the messages as JSON
+    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+    struct MeteredTransport {
+        inner: ChannelTransport,
+        health: ultrafast_mcp_transport::TransportHealth,
+    }
 
-        // Register a tool
-        let tool = create_valid_tool("test_tool");
-        server.register_tool(tool).await.unwrap();
+    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
+    #[async_trait::async_trait]
+    impl Transport for MeteredTransport {
+        async fn send_message(
+            &mut self,
+            message: JsonRpcMessage,
+        ) -> ultrafast_mcp_transport::Result<()> {
+            self.health.bytes_sent += serde_json::to_vec(&message).unwrap().len() as u64;
+            self.inner.send_message(message).await
+        }
 
-        // Create tools/call request with empty arguments
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "name": "test_tool",
-                "arguments": {}
-            })),
-            meta: std::collections::HashMap::new(),
-        };
+        async fn receive_message(&mut self) -> ultrafast_mcp_transport::Result<JsonRpcMessage> {
+            let message = self.inner.receive_message().await?;
+            self.health.bytes_received += serde_json::to_vec(&message).unwrap().len() as u64;
+            Ok(message)
+        }
 
-        let response = server.handle_request(request).await;
+        async fn close(&mut self) -> ultrafast_mcp_transport::Result<()> {
+            self.inner.close().await
+        }
 
-        // Verify error response
-        if let Some(error) = &response.error {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
-            );
-            assert_eq!(error.code, -32602); // Invalid params
-            // The actual error message format has changed to include more context
-            assert!(error.message.contains("Invalid parameters"));
-        } else {
-            panic!("Expected error response");
+        fn get_health(&self) -> ultrafast_mcp_transport::TransportHealth {
+            self.health.clone()
         }
     }
 
+    #[cfg(all(feature = "monitoring", not(feature = "bare-metal")))]
     #[tokio::test]
-    async fn test_unknown_method() {
-        let server = create_test_server();
-
-        // Create request for unknown method
-        let request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "test-id",
-            )),
-            method: "unknown/method".to_string(),
-            params: None,
-            meta: std::collections::HashMap::new(),
+    async fn test_run_with_transport_records_requests_and_traffic() {
+        let server = create_initialized_test_server().await.with_monitoring();
+        let (client_sender, server_receiver) = mpsc::unbounded_channel();
+        let (server_sender, mut client_receiver) = mpsc::unbounded_channel();
+        let transport = MeteredTransport {
+            inner: ChannelTransport {
+                sender: server_sender,
+                receiver: server_receiver,
+            },
+            health: Default::default(),
         };
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move { server.run_with_transport(Box::new(transport)).await }
+        });
 
-        let response = server.handle_request(request).await;
+        let request = JsonRpcMessage::Request(JsonRpcRequest::new(
+            "initialize".to_string(),
+            Some(json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": {"name": "test-client", "version": "1.0.0"}
+            })),
+            Some(RequestId::number(0)),
+        ));
+        let request_bytes = serde_json::to_vec(&request).unwrap().len() as u64;
+        client_sender.send(request).unwrap();
+        let response = client_receiver.recv().await.unwrap();
+        let response_bytes = serde_json::to_vec(&response).unwrap().len() as u64;
+        drop(client_sender);
+        tokio::time::timeout(std::time::Duration::from_secs(5), running)
+            .await
+            .expect("run_with_transport should return once the client hangs up")
+            .unwrap()
+            .unwrap();
 
-        // Verify error response
-        if let Some(error) = &response.error {
-            assert_eq!(
-                response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "test-id"
-                ))
-            );
-            assert_eq!(error.code, -32601); // Method not found
-            assert!(error.message.contains("Method not implemented"));
-        } else {
-            panic!("Expected error response");
-        }
+        let metrics = server.monitoring().unwrap().metrics().get_metrics().await;
+        assert_eq!(metrics.request.method_counts["initialize"], 1);
+        assert_eq!(metrics.request.successful_requests, 1);
+        assert_eq!(metrics.transport.bytes_received, request_bytes);
+        assert_eq!(metrics.transport.bytes_sent, response_bytes);
+        // Hanging up is not an error
+        assert_eq!(metrics.transport.error_count, 0);
     }
 
     #[tokio::test]
-    async fn test_tools_integration_workflow() {
+    async fn test_run_until_signal_shuts_down_on_signal() {
         let server = create_initialized_test_server().await;
+        let (_client_sender, server_receiver) = mpsc::unbounded_channel();
+        let (server_sender, mut client_receiver) = mpsc::unbounded_channel();
+        let transport = ChannelTransport {
+            sender: server_sender,
+            receiver: server_receiver,
+        };
+        let (signal, signalled) = tokio::sync::oneshot::channel::<()>();
+        let running = tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .run_until_signal(server.run_with_transport(Box::new(transport)), async {
+                        let _ = signalled.await;
+                    })
+                    .await
+            }
+        });
 
-        // Step 1: Register multiple tools
-        let tools = vec![
-            create_valid_tool("calculator"),
-            create_valid_tool("file_reader"),
-        ];
-        server.register_tools(tools).await.unwrap();
-        assert_eq!(server.tool_count().await, 2);
-
-        // Step 2: List tools via JSON-RPC
-        let list_request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "list-id",
-            )),
-            method: "tools/list".to_string(),
-            params: None,
-            meta: std::collections::HashMap::new(),
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!running.is_finished());
+        signal.send(()).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), running)
+            .await
+            .expect("run should return after the signal")
+            .unwrap()
+            .unwrap();
+        assert_eq!(server.get_state().await, ServerState::Shutdown);
+        let Some(JsonRpcMessage::Notification(notification)) = client_receiver.recv().await else {
+            panic!("expected the shutdown notification");
         };
+        assert_eq!(notification.method, SHUTDOWN_NOTIFICATION_METHOD);
+    }
 
-        let list_response = server.handle_request(list_request).await;
-        if let Some(result) = &list_response.result {
-            assert_eq!(
-                list_response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "list-id"
-                ))
-            );
-            let tools_array = result.get("tools").and_then(|t| t.as_array()).unwrap();
-            assert_eq!(tools_array.len(), 2);
-        } else {
-            panic!("Expected success response for tools/list");
+    /// The levels of the log messages `outgoing` holds
+    fn logged_levels(outgoing: &mut mpsc::UnboundedReceiver<JsonRpcMessage>) -> Vec<String> {
+        let mut levels = Vec::new();
+        while let Ok(message) = outgoing.try_recv() {
+            if let JsonRpcMessage::Notification(notification) = message {
+                assert_eq!(notification.method, "notifications/message");
+                levels.push(notification.params.unwrap()["level"].to_string());
+            }
         }
+        levels
+    }
 
-        // Step 3: Call a tool via JSON-RPC
-        let call_request = JsonRpcRequest {
-            jsonrpc: Cow::Borrowed("2.0"),
-            id: Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                "call-id",
-            )),
-            method: "tools/call".to_string(),
-            params: Some(json!({
-                "name": "calculator",
-                "arguments": {
-                    "input": "2 + 2"
-                }
-            })),
-            meta: std::collections::HashMap::new(),
-        };
-
-        let call_response = server.handle_request(call_request).await;
-        if let Some(result) = &call_response.result {
-            assert_eq!(
-                call_response.id,
-                Some(ultrafast_mcp_core::protocol::jsonrpc::RequestId::string(
-                    "call-id"
-                ))
+    #[tokio::test]
+    async fn test_log_levels_are_set_per_session() {
+        let server = create_initialized_test_server().await;
+        let mut sessions = Vec::new();
+        for session in ["quiet", "chatty"] {
+            let (outgoing_sender, outgoing) = mpsc::unbounded_channel();
+            let peer = Arc::new(
+                server
+                    .create_client_peer(outgoing_sender)
+                    .with_session_id(session.to_string()),
             );
-            let content = result
-                .get("content")
-                .and_then(|c| c.as_array())
-                .expect("Expected content array");
-            assert_eq!(content.len(), 1);
-        } else {
-            panic!("Expected success response for tools/call");
+            server.track_peer(&peer);
+            sessions.push((peer, outgoing));
         }
+        let set_level = |level: &str| {
+            JsonRpcRequest::new(
+                "logging/setLevel".to_string(),
+                Some(json!({"level": level})),
+                Some(RequestId::number(1)),
+            )
+        };
+        let response = server.respond(set_level("error"), &sessions[0].0).await;
+        assert!(response.result.is_some());
+        let response = server.respond(set_level("debug"), &sessions[1].0).await;
+        assert!(response.result.is_some());
+
+        assert_eq!(server.log_to_sessions(LogLevel::Info, None, json!("hi")), 1);
+        assert_eq!(
+            server.log_to_sessions(LogLevel::Error, None, json!("oh")),
+            2
+        );
+        assert_eq!(logged_levels(&mut sessions[0].1), ["\"error\""]);
+        assert_eq!(logged_levels(&mut sessions[1].1), ["\"info\"", "\"error\""]);
 
-        // Step 4: Verify tool still exists in registry
-        assert!(server.has_tool("calculator").await);
-        assert!(server.has_tool("file_reader").await);
+        // Handlers log at the level of the session calling them
+        let quiet = server
+            .create_request_context(&set_level("info"), Some(&sessions[0].0))
+            .await;
+        quiet.log_warn("ignored").await.unwrap();
+        quiet.log_error("sent").await.unwrap();
+        assert_eq!(logged_levels(&mut sessions[0].1), ["\"error\""]);
+
+        // Sessions without a level of their own follow the server's
+        server.set_log_level(LogLevel::Warning).await.unwrap();
+        server.log_levels.remove_session("chatty");
+        assert_eq!(server.log_to_sessions(LogLevel::Info, None, json!("hi")), 0);
+    }
+
+    #[cfg(feature = "tracing-layer")]
+    #[tokio::test]
+    async fn test_tracing_events_are_forwarded_to_sessions() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let server = create_initialized_test_server().await;
+        let (outgoing_sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(server.create_client_peer(outgoing_sender));
+        server.track_peer(&peer);
+
+        let subscriber =
+            tracing_subscriber::registry().with(crate::McpLogLayer::new(server.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "app::storage", free_mb = 12, "disk almost full");
+            tracing::debug!(target: "app::storage", "below the session's level");
+            tracing::error!("from this crate, so not forwarded");
+        });
+
+        let Ok(JsonRpcMessage::Notification(notification)) = outgoing.try_recv() else {
+            panic!("expected a log message");
+        };
+        assert_eq!(
+            notification.params.unwrap(),
+            json!({
+                "level": "warning",
+                "logger": "app::storage",
+                "data": {"message": "disk almost full", "free_mb": 12}
+            })
+        );
+        assert!(outgoing.try_recv().is_err());
     }
 }
@@ -0,0 +1,170 @@
+//! State kept for each client session
+//!
+//! Handlers reach the state of the session a request came from through
+//! [`Context::session`](crate::Context::session). Values are stored by key
+//! and read back by type, so a tool can remember a conversation, a login or
+//! a working directory between calls from the same client:
+//!
+//! ```rust
+//! # use ultrafast_mcp_server::Context;
+//! # fn handle(ctx: &Context) {
+//! let calls = ctx.session().get::<u32>("calls").unwrap_or(0) + 1;
+//! ctx.session().insert("calls", calls);
+//! # }
+//! ```
+//!
+//! The state of a session is dropped when its connection closes, or, over
+//! Streamable HTTP, when the client terminates the session or it is evicted.
+//! Connections without sessions, such as stdio, share the state of
+//! [`DEFAULT_SUBSCRIPTION_SESSION`](crate::DEFAULT_SUBSCRIPTION_SESSION)
+//! while they are open.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Values = HashMap<String, Arc<dyn Any + Send + Sync>>;
+
+/// Typed key/value store of one session
+///
+/// Cloning the store shares it.
+#[derive(Clone, Default)]
+pub struct SessionState {
+    values: Arc<Mutex<Values>>,
+}
+
+impl std::fmt::Debug for SessionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys: Vec<_> = self.lock().keys().cloned().collect();
+        keys.sort();
+        f.debug_struct("SessionState").field("keys", &keys).finish()
+    }
+}
+
+impl SessionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Values> {
+        self.values.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The value stored under `key`, if there is one of type `T`
+    pub fn get<T: Clone + Send + Sync + 'static>(&self, key: &str) -> Option<T> {
+        self.get_shared::<T>(key).map(|value| (*value).clone())
+    }
+
+    /// The value stored under `key` without cloning it, if there is one of
+    /// type `T`
+    pub fn get_shared<T: Send + Sync + 'static>(&self, key: &str) -> Option<Arc<T>> {
+        let value = self.lock().get(key)?.clone();
+        value.downcast().ok()
+    }
+
+    /// Store `value` under `key`, replacing what was stored there before
+    pub fn insert<T: Send + Sync + 'static>(&self, key: impl Into<String>, value: T) {
+        self.lock().insert(key.into(), Arc::new(value));
+    }
+
+    /// Remove the value stored under `key`, returning it if it is of type `T`
+    pub fn remove<T: Send + Sync + 'static>(&self, key: &str) -> Option<Arc<T>> {
+        let value = self.lock().remove(key)?;
+        value.downcast().ok()
+    }
+
+    /// Replace the value of type `T` stored under `key` with what `update`
+    /// makes of it, all while other handlers of the session wait
+    ///
+    /// `update` is given `None` if nothing of type `T` is stored under `key`.
+    pub fn update<T, R>(&self, key: &str, update: impl FnOnce(Option<&T>) -> (T, R)) -> R
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut values = self.lock();
+        let current = values.get(key).and_then(|value| value.downcast_ref::<T>());
+        let (value, result) = update(current);
+        values.insert(key.to_string(), Arc::new(value));
+        result
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.lock().contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+/// The state of every open session
+#[derive(Debug, Default)]
+pub(crate) struct SessionStates {
+    sessions: Mutex<HashMap<String, SessionState>>,
+}
+
+impl SessionStates {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, SessionState>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The state of `session_id`, created empty on first use
+    pub(crate) fn session(&self, session_id: &str) -> SessionState {
+        self.lock()
+            .entry(session_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Drop the state of a session that ended
+    pub(crate) fn remove(&self, session_id: &str) {
+        self.lock().remove(session_id);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_are_read_back_by_type() {
+        let state = SessionState::new();
+        state.insert("user", "alice".to_string());
+        state.insert("calls", 1u32);
+
+        assert_eq!(state.get::<String>("user").as_deref(), Some("alice"));
+        assert_eq!(state.get::<u64>("calls"), None);
+        assert_eq!(state.get::<u32>("missing"), None);
+
+        let calls = state.update::<u32, _>("calls", |calls| {
+            let calls = calls.copied().unwrap_or(0) + 1;
+            (calls, calls)
+        });
+        assert_eq!(calls, 2);
+        assert_eq!(state.remove::<u32>("calls").as_deref(), Some(&2));
+        assert_eq!(state.len(), 1);
+    }
+
+    #[test]
+    fn test_sessions_have_separate_state() {
+        let states = SessionStates::default();
+        states.session("a").insert("key", 1u8);
+        assert_eq!(states.session("a").get::<u8>("key"), Some(1));
+        assert!(states.session("b").is_empty());
+
+        states.remove("a");
+        assert!(states.session("a").is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Graceful shutdown of a running server
+//!
+//! [`UltraFastServer::shutdown_graceful`] stops a server started with
+//! `run_stdio`, `run_with_transport` or `run_http` from application code.
+//! From the call on, new requests are refused. Requests already running get
+//! until the timeout to finish; the rest are aborted and their clients
+//! receive an error response. Connected clients are told about the shutdown
+//! with a [`SHUTDOWN_NOTIFICATION_METHOD`] notification, and the transports
+//! are closed once the last responses are sent.
+//!
+//! `run_stdio_with_shutdown` and `run_http_with_shutdown` do this when a
+//! signal future completes, such as [`shutdown_signal`] for containers that
+//! are stopped with SIGTERM:
+//!
+//! ```rust,no_run
+//! # async fn run(server: ultrafast_mcp_server::UltraFastServer) -> ultrafast_mcp_server::MCPResult<()> {
+//! server
+//!     .run_stdio_with_shutdown(ultrafast_mcp_server::shutdown_signal())
+//!     .await
+//! # }
+//! ```
+//!
+//! [`UltraFastServer::shutdown_graceful`]: crate::UltraFastServer::shutdown_graceful
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+
+use tokio::sync::{Notify, watch};
+use tokio::task::AbortHandle;
+use ultrafast_mcp_core::protocol::jsonrpc::RequestId;
+
+use crate::peer::ClientPeer;
+
+/// Vendor notification sent to connected clients when the server starts
+/// shutting down
+///
+/// Its params carry the `reason` and, as `timeoutMs`, how long running
+/// requests are given to finish.
+pub const SHUTDOWN_NOTIFICATION_METHOD: &str = "notifications/x-ultrafast/shutdown";
+
+/// Outcome of [`UltraFastServer::shutdown_graceful`]
+///
+/// [`UltraFastServer::shutdown_graceful`]: crate::UltraFastServer::shutdown_graceful
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Requests that finished within the timeout
+    pub drained: usize,
+    /// Requests aborted when the timeout ran out
+    pub aborted: usize,
+}
+
+/// A request aborted by the shutdown, still owed a response
+pub(crate) struct AbortedRequest {
+    pub(crate) peer: Weak<ClientPeer>,
+    pub(crate) request_id: Option<RequestId>,
+}
+
+struct InFlightRequest {
+    abort: Option<AbortHandle>,
+    peer: Weak<ClientPeer>,
+    request_id: Option<RequestId>,
+}
+
+/// Requests running on their own task, and whether the server is draining
+/// or closed
+pub(crate) struct ShutdownCoordinator {
+    draining: AtomicBool,
+    closed: watch::Sender<bool>,
+    in_flight: Mutex<HashMap<u64, InFlightRequest>>,
+    next_key: AtomicU64,
+    idle: Notify,
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self {
+            draining: AtomicBool::new(false),
+            closed: watch::Sender::new(false),
+            in_flight: Mutex::new(HashMap::new()),
+            next_key: AtomicU64::new(0),
+            idle: Notify::new(),
+        }
+    }
+}
+
+/// Keeps a request counted as in flight until its task ends or is aborted
+pub(crate) struct InFlightGuard {
+    coordinator: Arc<ShutdownCoordinator>,
+    key: u64,
+}
+
+impl InFlightGuard {
+    pub(crate) fn key(&self) -> u64 {
+        self.key
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut in_flight = self.coordinator.lock_in_flight();
+        in_flight.remove(&self.key);
+        if in_flight.is_empty() {
+            self.coordinator.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownCoordinator {
+    fn lock_in_flight(&self) -> MutexGuard<'_, HashMap<u64, InFlightRequest>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether new requests are refused
+    pub(crate) fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// Refuse new requests from now on; false if already draining
+    pub(crate) fn start_draining(&self) -> bool {
+        !self.draining.swap(true, Ordering::AcqRel)
+    }
+
+    /// Count a request as in flight until the returned guard is dropped
+    pub(crate) fn track(
+        self: &Arc<Self>,
+        request_id: Option<RequestId>,
+        peer: &Arc<ClientPeer>,
+    ) -> InFlightGuard {
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        self.lock_in_flight().insert(
+            key,
+            InFlightRequest {
+                abort: None,
+                peer: Arc::downgrade(peer),
+                request_id,
+            },
+        );
+        InFlightGuard {
+            coordinator: self.clone(),
+            key,
+        }
+    }
+
+    /// Attach the task running a tracked request, so it can be aborted
+    pub(crate) fn set_abort_handle(&self, key: u64, handle: AbortHandle) {
+        // Gone if the task already finished
+        if let Some(request) = self.lock_in_flight().get_mut(&key) {
+            request.abort = Some(handle);
+        }
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.lock_in_flight().len()
+    }
+
+    /// Wait until no request is in flight; false if `timeout` ran out first
+    pub(crate) async fn drained(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let idle = self.idle.notified();
+                tokio::pin!(idle);
+                idle.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Abort every request still in flight
+    pub(crate) fn abort_all(&self) -> Vec<AbortedRequest> {
+        let aborted: Vec<_> = self.lock_in_flight().drain().map(|(_, r)| r).collect();
+        aborted
+            .into_iter()
+            .map(|request| {
+                if let Some(abort) = request.abort {
+                    abort.abort();
+                }
+                AbortedRequest {
+                    peer: request.peer,
+                    request_id: request.request_id,
+                }
+            })
+            .collect()
+    }
+
+    /// Tell the run loops to close their transports
+    pub(crate) fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Completes once [`close`](Self::close) was called
+    pub(crate) fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut closed = self.closed.subscribe();
+        async move {
+            // The sender lives as long as the server
+            let _ = closed.wait_for(|closed| *closed).await;
+        }
+    }
+}
+
+/// Completes on Ctrl-C, or on SIGTERM where there is one
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    fn peer() -> Arc<ClientPeer> {
+        let (outgoing, _) = mpsc::unbounded_channel();
+        Arc::new(ClientPeer::new(outgoing, Duration::from_secs(1)))
+    }
+
+    #[tokio::test]
+    async fn test_drained_waits_for_tracked_requests() {
+        let coordinator = Arc::new(ShutdownCoordinator::default());
+        let peer = peer();
+        assert!(coordinator.drained(Duration::ZERO).await);
+
+        let guard = coordinator.track(Some(RequestId::number(1)), &peer);
+        assert!(!coordinator.drained(Duration::from_millis(20)).await);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+        assert!(coordinator.drained(Duration::from_secs(5)).await);
+        assert!(coordinator.start_draining());
+        assert!(!coordinator.start_draining());
+    }
+
+    #[tokio::test]
+    async fn test_abort_all_stops_running_tasks() {
+        let coordinator = Arc::new(ShutdownCoordinator::default());
+        let peer = peer();
+        let guard = coordinator.track(Some(RequestId::number(2)), &peer);
+        let key = guard.key();
+        let task = tokio::spawn(async move {
+            let _guard = guard;
+            std::future::pending::<()>().await;
+        });
+        coordinator.set_abort_handle(key, task.abort_handle());
+
+        let aborted = coordinator.abort_all();
+        assert_eq!(aborted.len(), 1);
+        assert_eq!(aborted[0].request_id, Some(RequestId::number(2)));
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert_eq!(coordinator.in_flight(), 0);
+    }
+}
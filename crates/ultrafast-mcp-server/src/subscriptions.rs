@@ -0,0 +1,280 @@
+//! Resource subscriptions by URI, prefix and glob pattern
+//!
+//! `resources/subscribe` accepts a pattern in place of a URI so a client can
+//! watch a whole directory tree with one call:
+//!
+//! - a URI without `*` or `?` subscribes to exactly that URI
+//! - a URI ending in `**` with no other wildcards, such as
+//!   `file:///project/**`, subscribes to every URI starting with the part
+//!   before `**`
+//! - any other pattern is a glob, where `*` matches within one path segment,
+//!   `**` matches across segments and `?` matches one character other than
+//!   `/`
+//!
+//! [`SubscriptionRegistry`] indexes exact URIs and prefixes in hash maps, so
+//! finding the subscribers of a changed URI costs one lookup per prefix of
+//! the URI plus one match per distinct glob, independent of how many exact
+//! or prefix subscriptions exist.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// Session that subscriptions from connections without a session id are
+/// recorded under
+pub const DEFAULT_SUBSCRIPTION_SESSION: &str = "default";
+
+/// How a subscription selects URIs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SubscriptionPattern {
+    Exact(String),
+    Prefix(String),
+    Glob(String),
+}
+
+impl SubscriptionPattern {
+    pub fn parse(pattern: &str) -> Self {
+        if !has_wildcards(pattern) {
+            return Self::Exact(pattern.to_string());
+        }
+        match pattern.strip_suffix("**") {
+            Some(prefix) if !has_wildcards(prefix) => Self::Prefix(prefix.to_string()),
+            _ => Self::Glob(pattern.to_string()),
+        }
+    }
+
+    pub fn matches(&self, uri: &str) -> bool {
+        match self {
+            Self::Exact(exact) => exact == uri,
+            Self::Prefix(prefix) => uri.starts_with(prefix.as_str()),
+            Self::Glob(glob) => glob_matches(glob.as_bytes(), uri.as_bytes()),
+        }
+    }
+}
+
+/// Which sessions subscribed to which URIs
+#[derive(Debug, Default)]
+pub struct SubscriptionRegistry {
+    index: RwLock<SubscriptionIndex>,
+}
+
+#[derive(Debug, Default)]
+struct SubscriptionIndex {
+    exact: HashMap<String, HashSet<String>>,
+    prefix: HashMap<String, HashSet<String>>,
+    glob: HashMap<String, HashSet<String>>,
+    /// Patterns by session, as given to `subscribe`
+    sessions: HashMap<String, HashSet<String>>,
+}
+
+impl SubscriptionIndex {
+    fn table(
+        &mut self,
+        pattern: &SubscriptionPattern,
+    ) -> (&mut HashMap<String, HashSet<String>>, String) {
+        match pattern {
+            SubscriptionPattern::Exact(key) => (&mut self.exact, key.clone()),
+            SubscriptionPattern::Prefix(key) => (&mut self.prefix, key.clone()),
+            SubscriptionPattern::Glob(key) => (&mut self.glob, key.clone()),
+        }
+    }
+
+    fn remove(&mut self, session_id: &str, pattern: &str) {
+        let (table, key) = self.table(&SubscriptionPattern::parse(pattern));
+        if let Some(sessions) = table.get_mut(&key) {
+            sessions.remove(session_id);
+            if sessions.is_empty() {
+                table.remove(&key);
+            }
+        }
+    }
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe a session to a URI or pattern
+    ///
+    /// Returns `false` if the session was already subscribed to it.
+    pub fn subscribe(&self, session_id: &str, pattern: &str) -> bool {
+        let mut index = self.write();
+        if !index
+            .sessions
+            .entry(session_id.to_string())
+            .or_default()
+            .insert(pattern.to_string())
+        {
+            return false;
+        }
+        let (table, key) = index.table(&SubscriptionPattern::parse(pattern));
+        table.entry(key).or_default().insert(session_id.to_string());
+        true
+    }
+
+    /// Remove a session's subscription to a URI or pattern
+    ///
+    /// Returns `false` if the session was not subscribed to it.
+    pub fn unsubscribe(&self, session_id: &str, pattern: &str) -> bool {
+        let mut index = self.write();
+        let removed = index
+            .sessions
+            .get_mut(session_id)
+            .is_some_and(|patterns| patterns.remove(pattern));
+        if removed {
+            index.remove(session_id, pattern);
+            if index
+                .sessions
+                .get(session_id)
+                .is_some_and(HashSet::is_empty)
+            {
+                index.sessions.remove(session_id);
+            }
+        }
+        removed
+    }
+
+    /// Drop every subscription of a session, e.g. when it disconnects
+    pub fn remove_session(&self, session_id: &str) {
+        let mut index = self.write();
+        for pattern in index.sessions.remove(session_id).unwrap_or_default() {
+            index.remove(session_id, &pattern);
+        }
+    }
+
+    /// Sessions subscribed to `uri` through any of their patterns
+    pub fn subscribers(&self, uri: &str) -> HashSet<String> {
+        let index = self.read();
+        let mut subscribers = HashSet::new();
+        if let Some(sessions) = index.exact.get(uri) {
+            subscribers.extend(sessions.iter().cloned());
+        }
+        if !index.prefix.is_empty() {
+            for end in uri
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(uri.len()))
+            {
+                if let Some(sessions) = index.prefix.get(&uri[..end]) {
+                    subscribers.extend(sessions.iter().cloned());
+                }
+            }
+        }
+        for (glob, sessions) in &index.glob {
+            if glob_matches(glob.as_bytes(), uri.as_bytes()) {
+                subscribers.extend(sessions.iter().cloned());
+            }
+        }
+        subscribers
+    }
+
+    /// URIs and patterns a session is subscribed to
+    pub fn session_subscriptions(&self, session_id: &str) -> Vec<String> {
+        let mut patterns: Vec<String> = self
+            .read()
+            .sessions
+            .get(session_id)
+            .map(|patterns| patterns.iter().cloned().collect())
+            .unwrap_or_default();
+        patterns.sort();
+        patterns
+    }
+
+    /// Total number of subscriptions across sessions
+    pub fn len(&self) -> usize {
+        self.read().sessions.values().map(HashSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().sessions.is_empty()
+    }
+
+    pub fn clear(&self) {
+        *self.write() = SubscriptionIndex::default();
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, SubscriptionIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, SubscriptionIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+pub(crate) fn glob_matches(pattern: &[u8], uri: &[u8]) -> bool {
+    match pattern {
+        [] => uri.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=uri.len()).any(|skip| glob_matches(rest, &uri[skip..])),
+        [b'*', rest @ ..] => {
+            let segment = uri.iter().position(|&b| b == b'/').unwrap_or(uri.len());
+            (0..=segment).any(|skip| glob_matches(rest, &uri[skip..]))
+        }
+        [b'?', rest @ ..] => {
+            matches!(uri, [first, tail @ ..] if *first != b'/' && glob_matches(rest, tail))
+        }
+        [expected, rest @ ..] => {
+            matches!(uri, [first, tail @ ..] if first == expected && glob_matches(rest, tail))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_kinds_and_matching() {
+        assert_eq!(
+            SubscriptionPattern::parse("file:///a.txt"),
+            SubscriptionPattern::Exact("file:///a.txt".to_string())
+        );
+        assert_eq!(
+            SubscriptionPattern::parse("file:///project/**"),
+            SubscriptionPattern::Prefix("file:///project/".to_string())
+        );
+
+        let glob = SubscriptionPattern::parse("file:///project/*/src/*.rs");
+        assert!(matches!(glob, SubscriptionPattern::Glob(_)));
+        assert!(glob.matches("file:///project/app/src/main.rs"));
+        assert!(!glob.matches("file:///project/app/src/bin/main.rs"));
+        assert!(!glob.matches("file:///project/app/src/main.rsx"));
+
+        let deep = SubscriptionPattern::parse("file:///project/**/*.md");
+        assert!(deep.matches("file:///project/docs/guide/intro.md"));
+        assert!(!deep.matches("file:///other/intro.md"));
+        assert!(SubscriptionPattern::parse("db://table?").matches("db://table1"));
+    }
+
+    #[test]
+    fn test_subscribers_across_pattern_kinds() {
+        let registry = SubscriptionRegistry::new();
+        assert!(registry.subscribe("exact", "file:///project/src/lib.rs"));
+        assert!(registry.subscribe("tree", "file:///project/**"));
+        assert!(registry.subscribe("rust", "file:///project/**/*.rs"));
+        assert!(!registry.subscribe("tree", "file:///project/**"));
+
+        let subscribers = registry.subscribers("file:///project/src/lib.rs");
+        assert_eq!(subscribers.len(), 3);
+        let subscribers = registry.subscribers("file:///project/README.md");
+        assert_eq!(subscribers, HashSet::from(["tree".to_string()]));
+        assert!(registry.subscribers("file:///elsewhere/lib.rs").is_empty());
+
+        assert!(registry.unsubscribe("tree", "file:///project/**"));
+        assert!(!registry.unsubscribe("tree", "file:///project/**"));
+        assert!(registry.subscribers("file:///project/README.md").is_empty());
+
+        registry.remove_session("rust");
+        assert_eq!(
+            registry.subscribers("file:///project/src/lib.rs"),
+            HashSet::from(["exact".to_string()])
+        );
+        assert_eq!(registry.len(), 1);
+    }
+}
@@ -0,0 +1,96 @@
+//! Tools implemented by typed closures
+//!
+//! [`UltraFastServer::tool`] and [`UltraFastServer::register_typed_tool`]
+//! register a single tool from an async closure taking a deserializable
+//! input and returning a serializable output. The tool's input and output
+//! schemas are generated from the two types, so both must be structs (or
+//! other types whose schema is a JSON object).
+//!
+//! ```rust,ignore
+//! #[derive(Deserialize, JsonSchema)]
+//! struct AddInput { a: i64, b: i64 }
+//!
+//! #[derive(Serialize, JsonSchema)]
+//! struct AddOutput { sum: i64 }
+//!
+//! let server = UltraFastServer::new(info, capabilities)
+//!     .tool("add", "Add two numbers", |input: AddInput, _ctx| async move {
+//!         Ok(AddOutput { sum: input.a + input.b })
+//!     })?;
+//! ```
+//!
+//! [`UltraFastServer::tool`]: crate::UltraFastServer::tool
+//! [`UltraFastServer::register_typed_tool`]: crate::UltraFastServer::register_typed_tool
+
+use std::{future::Future, pin::Pin};
+
+use schemars::JsonSchema;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult},
+    schema::generation::generate_schema_for,
+    types::tools::{Tool, ToolResult},
+};
+
+use crate::context::Context;
+
+type ToolFuture = Pin<Box<dyn Future<Output = MCPResult<ToolResult>> + Send>>;
+
+/// A registered closure with its input and output conversions erased
+pub(crate) struct TypedTool {
+    call: Box<dyn Fn(Value, Context) -> ToolFuture + Send + Sync>,
+}
+
+impl std::fmt::Debug for TypedTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedTool").finish_non_exhaustive()
+    }
+}
+
+impl TypedTool {
+    pub(crate) fn new<I, O, F, Fut>(handler: F) -> Self
+    where
+        I: DeserializeOwned + Send + 'static,
+        O: Serialize + Send + 'static,
+        F: Fn(I, Context) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = MCPResult<O>> + Send + 'static,
+    {
+        Self {
+            call: Box::new(move |arguments, context| {
+                let input = serde_json::from_value::<I>(arguments)
+                    .map_err(|e| MCPError::invalid_params(format!("Invalid arguments: {e}")));
+                let output = input.map(|input| handler(input, context));
+                Box::pin(async move { into_tool_result(output?.await?) })
+            }),
+        }
+    }
+
+    /// Deserialize `arguments`, run the closure and serialize its output
+    pub(crate) async fn call(&self, arguments: Value, context: Context) -> MCPResult<ToolResult> {
+        (self.call)(arguments, context).await
+    }
+}
+
+/// Tool definition with schemas generated from the input and output types
+pub(crate) fn typed_tool_definition<I: JsonSchema, O: JsonSchema>(
+    name: String,
+    description: String,
+) -> Tool {
+    Tool::new(name, description, type_schema::<I>()).with_output_schema(type_schema::<O>())
+}
+
+fn type_schema<T: JsonSchema>() -> Value {
+    let mut schema = generate_schema_for::<T>();
+    if let Some(schema) = schema.as_object_mut() {
+        schema.remove("$schema");
+        schema.remove("title");
+    }
+    schema
+}
+
+fn into_tool_result<O: Serialize>(output: O) -> MCPResult<ToolResult> {
+    let value = serde_json::to_value(&output)
+        .map_err(|e| MCPError::serialization_error(format!("Invalid tool output: {e}")))?;
+    Ok(ToolResult::structured(value))
+}
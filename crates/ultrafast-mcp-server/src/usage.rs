@@ -0,0 +1,292 @@
+//! Token usage and cost accounting for sampling
+//!
+//! Every sampling round trip the server takes part in is recorded against
+//! the session it belongs to and, when issued from a tool, the tool's name.
+//! Responses that carry [`CostInfo`] are recorded as reported; otherwise
+//! token counts are estimated from message lengths and priced with
+//! [`SamplingPricing`].
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use serde::{Deserialize, Serialize};
+use ultrafast_mcp_core::types::sampling::{
+    CostInfo, CreateMessageRequest, CreateMessageResponse, SamplingContent,
+};
+
+/// Session key used for requests that did not arrive over a session, such
+/// as the single stdio connection
+pub const DEFAULT_USAGE_SESSION: &str = "default";
+
+/// Price of a model, in cents per 1K tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub input_cost_per_1k_cents: f64,
+    pub output_cost_per_1k_cents: f64,
+}
+
+/// Prices used to estimate cost when a response does not report it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingPricing {
+    /// Price for models without an entry in `models`
+    pub default_price: ModelPrice,
+    /// Prices by model name
+    pub models: HashMap<String, ModelPrice>,
+}
+
+impl Default for SamplingPricing {
+    fn default() -> Self {
+        // Matches the rates behind `SamplingRequest::estimate_cost`
+        Self {
+            default_price: ModelPrice {
+                input_cost_per_1k_cents: 0.2,
+                output_cost_per_1k_cents: 1.2,
+            },
+            models: HashMap::new(),
+        }
+    }
+}
+
+impl SamplingPricing {
+    /// Set the price of a specific model
+    pub fn with_model_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.models.insert(model.into(), price);
+        self
+    }
+
+    fn price_for(&self, model: Option<&str>) -> ModelPrice {
+        model
+            .and_then(|model| self.models.get(model))
+            .copied()
+            .unwrap_or(self.default_price)
+    }
+}
+
+/// Accumulated sampling usage
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingUsage {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_cents: f64,
+    /// Requests whose tokens or cost were estimated rather than reported
+    pub estimated_requests: u64,
+}
+
+impl SamplingUsage {
+    fn add(&mut self, other: &SamplingUsage) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_cents += other.cost_cents;
+        self.estimated_requests += other.estimated_requests;
+    }
+}
+
+/// Sampling usage broken down by session, tool and model
+///
+/// This is the result of the `usage/report` method.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub total: SamplingUsage,
+    pub sessions: HashMap<String, SamplingUsage>,
+    pub tools: HashMap<String, SamplingUsage>,
+    pub models: HashMap<String, SamplingUsage>,
+}
+
+/// Records sampling usage for a server
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    pricing: SamplingPricing,
+    report: Mutex<UsageReport>,
+}
+
+impl UsageTracker {
+    pub fn new(pricing: SamplingPricing) -> Self {
+        Self {
+            pricing,
+            report: Mutex::new(UsageReport::default()),
+        }
+    }
+
+    pub fn pricing(&self) -> &SamplingPricing {
+        &self.pricing
+    }
+
+    /// Record one sampling round trip, returning the usage attributed to it
+    pub fn record(
+        &self,
+        session_id: Option<&str>,
+        tool_name: Option<&str>,
+        request: &CreateMessageRequest,
+        response: &CreateMessageResponse,
+    ) -> SamplingUsage {
+        let usage = match &response.cost_info {
+            Some(cost_info) => Self::reported_usage(cost_info),
+            None => self.estimated_usage(request, response),
+        };
+        let model = response
+            .cost_info
+            .as_ref()
+            .map(|cost_info| cost_info.model.as_str())
+            .or(response.model.as_deref());
+
+        let mut report = self.lock_report();
+        report.total.add(&usage);
+        report
+            .sessions
+            .entry(session_id.unwrap_or(DEFAULT_USAGE_SESSION).to_string())
+            .or_default()
+            .add(&usage);
+        if let Some(tool_name) = tool_name {
+            report
+                .tools
+                .entry(tool_name.to_string())
+                .or_default()
+                .add(&usage);
+        }
+        if let Some(model) = model {
+            report
+                .models
+                .entry(model.to_string())
+                .or_default()
+                .add(&usage);
+        }
+        usage
+    }
+
+    /// Usage recorded so far
+    pub fn report(&self) -> UsageReport {
+        self.lock_report().clone()
+    }
+
+    /// Usage recorded for one session
+    pub fn session_usage(&self, session_id: &str) -> SamplingUsage {
+        self.lock_report()
+            .sessions
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn reset(&self) {
+        *self.lock_report() = UsageReport::default();
+    }
+
+    fn reported_usage(cost_info: &CostInfo) -> SamplingUsage {
+        SamplingUsage {
+            requests: 1,
+            input_tokens: u64::from(cost_info.input_tokens),
+            output_tokens: u64::from(cost_info.output_tokens),
+            cost_cents: cost_info.total_cost_cents,
+            estimated_requests: 0,
+        }
+    }
+
+    fn estimated_usage(
+        &self,
+        request: &CreateMessageRequest,
+        response: &CreateMessageResponse,
+    ) -> SamplingUsage {
+        let input_tokens = u64::from(request.estimate_input_tokens().unwrap_or(0));
+        let output_tokens = match &response.content {
+            SamplingContent::Text { text } => (text.len() as u64).div_ceil(4),
+            SamplingContent::Image { .. } => 85,
+        };
+        let price = self.pricing.price_for(response.model.as_deref());
+        SamplingUsage {
+            requests: 1,
+            input_tokens,
+            output_tokens,
+            cost_cents: input_tokens as f64 / 1000.0 * price.input_cost_per_1k_cents
+                + output_tokens as f64 / 1000.0 * price.output_cost_per_1k_cents,
+            estimated_requests: 1,
+        }
+    }
+
+    fn lock_report(&self) -> MutexGuard<'_, UsageReport> {
+        self.report.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::types::sampling::SamplingRole;
+
+    fn response(text: &str, model: &str, cost_info: Option<CostInfo>) -> CreateMessageResponse {
+        CreateMessageResponse {
+            role: SamplingRole::Assistant,
+            content: SamplingContent::Text {
+                text: text.to_string(),
+            },
+            model: Some(model.to_string()),
+            stop_reason: None,
+            approval_status: None,
+            request_id: None,
+            processing_time_ms: None,
+            cost_info,
+            included_context: None,
+            human_feedback: None,
+            warnings: None,
+        }
+    }
+
+    #[test]
+    fn test_reported_cost_is_attributed_to_session_tool_and_model() {
+        let tracker = UsageTracker::default();
+        let cost_info = CostInfo {
+            total_cost_cents: 3.0,
+            input_cost_cents: 1.0,
+            output_cost_cents: 2.0,
+            input_tokens: 100,
+            output_tokens: 50,
+            model: "large".to_string(),
+        };
+
+        let request = CreateMessageRequest::default();
+        tracker.record(
+            Some("s1"),
+            Some("summarize"),
+            &request,
+            &response("done", "large", Some(cost_info)),
+        );
+        tracker.record(None, None, &request, &response("done", "large", None));
+
+        let report = tracker.report();
+        assert_eq!(report.total.requests, 2);
+        assert_eq!(report.total.estimated_requests, 1);
+        assert_eq!(report.sessions["s1"].input_tokens, 100);
+        assert_eq!(report.sessions[DEFAULT_USAGE_SESSION].requests, 1);
+        assert_eq!(report.tools["summarize"].cost_cents, 3.0);
+        assert_eq!(report.models["large"].requests, 2);
+    }
+
+    #[test]
+    fn test_estimated_cost_uses_model_price() {
+        let pricing = SamplingPricing::default().with_model_price(
+            "cheap",
+            ModelPrice {
+                input_cost_per_1k_cents: 0.0,
+                output_cost_per_1k_cents: 1000.0,
+            },
+        );
+        let tracker = UsageTracker::new(pricing);
+
+        let usage = tracker.record(
+            Some("s1"),
+            None,
+            &CreateMessageRequest::default(),
+            &response("12345678", "cheap", None),
+        );
+        assert_eq!(usage.output_tokens, 2);
+        assert_eq!(usage.cost_cents, 2.0);
+        assert_eq!(tracker.session_usage("s1"), usage);
+    }
+}
@@ -0,0 +1,429 @@
+//! Multi-step elicitation flows
+//!
+//! A [`Wizard`] is an ordered set of [`WizardStep`]s, each of which elicits
+//! one form from the client's user. Running a [`WizardSession`] walks the
+//! steps through [`Context::elicit`], validating every answer against the
+//! step's schema and optional validator. Invalid answers are sent back to
+//! the user together with the error, so the client sees why its input was
+//! rejected.
+//!
+//! A step may pick the next step from the answers collected so far, which
+//! allows branching flows. The progress of a session is captured in a
+//! serializable [`WizardState`]; a session that was declined, cancelled or
+//! interrupted can be stored and later resumed at the step where it stopped.
+
+use std::{fmt, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::debug;
+use ultrafast_mcp_core::{
+    error::{MCPError, MCPResult},
+    i18n::{LocalizedMessage, keys},
+    schema::validation::validate_against_schema,
+    types::elicitation::{ElicitationAction, ElicitationRequest},
+    utils::generate_session_id,
+};
+
+use crate::context::Context;
+
+/// Answers collected so far, keyed by step ID
+pub type WizardAnswers = Map<String, Value>;
+
+/// Checks an accepted answer; the error message is shown to the user
+pub type StepValidator = Arc<dyn Fn(&Value, &WizardAnswers) -> Result<(), String> + Send + Sync>;
+
+/// Chooses the step after this one; `None` completes the wizard
+pub type StepTransition = Arc<dyn Fn(&WizardAnswers) -> Option<String> + Send + Sync>;
+
+/// Default number of times a step is asked before invalid answers fail the
+/// session
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// A single form in a wizard
+#[derive(Clone)]
+pub struct WizardStep {
+    id: String,
+    request: ElicitationRequest,
+    validator: Option<StepValidator>,
+    next: Option<StepTransition>,
+}
+
+impl fmt::Debug for WizardStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WizardStep")
+            .field("id", &self.id)
+            .field("request", &self.request)
+            .field("validator", &self.validator.is_some())
+            .field("next", &self.next.is_some())
+            .finish()
+    }
+}
+
+impl WizardStep {
+    /// Create a step asking `message` with the given flat object schema
+    pub fn new(id: impl Into<String>, message: impl Into<String>, requested_schema: Value) -> Self {
+        Self {
+            id: id.into(),
+            request: ElicitationRequest {
+                message: message.into(),
+                requested_schema,
+            },
+            validator: None,
+            next: None,
+        }
+    }
+
+    /// Validate accepted answers beyond what the schema expresses
+    pub fn with_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&Value, &WizardAnswers) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Choose the next step from the answers so far
+    ///
+    /// Without a transition the wizard continues with the step defined
+    /// after this one.
+    pub fn with_next<F>(mut self, next: F) -> Self
+    where
+        F: Fn(&WizardAnswers) -> Option<String> + Send + Sync + 'static,
+    {
+        self.next = Some(Arc::new(next));
+        self
+    }
+
+    /// Step ID, also the key of its answer
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn validate(&self, content: &Value, answers: &WizardAnswers) -> Result<(), String> {
+        validate_against_schema(content, &self.request.requested_schema)
+            .map_err(|e| e.to_string())?;
+        match &self.validator {
+            Some(validator) => validator(content, answers),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Definition of a multi-step elicitation flow
+#[derive(Debug, Clone)]
+pub struct Wizard {
+    steps: Vec<WizardStep>,
+    max_attempts: u32,
+}
+
+impl Default for Wizard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Wizard {
+    /// Create an empty wizard
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Append a step; the first step added is where sessions start
+    pub fn step(mut self, step: WizardStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// How many times a step is asked before invalid answers fail the session
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Start a new session at the first step
+    pub fn start(self: &Arc<Self>) -> WizardSession {
+        WizardSession {
+            wizard: self.clone(),
+            state: WizardState {
+                session_id: generate_session_id(),
+                current_step: self.steps.first().map(|step| step.id.clone()),
+                answers: WizardAnswers::new(),
+                history: Vec::new(),
+            },
+        }
+    }
+
+    /// Continue a session from a previously saved state
+    pub fn resume(self: &Arc<Self>, state: WizardState) -> MCPResult<WizardSession> {
+        if let Some(step_id) = &state.current_step {
+            self.find_step(step_id)?;
+        }
+        Ok(WizardSession {
+            wizard: self.clone(),
+            state,
+        })
+    }
+
+    fn find_step(&self, id: &str) -> MCPResult<&WizardStep> {
+        self.steps
+            .iter()
+            .find(|step| step.id == id)
+            .ok_or_else(|| MCPError::invalid_request(format!("Unknown wizard step: {id}")))
+    }
+
+    fn step_after(&self, step: &WizardStep, answers: &WizardAnswers) -> Option<String> {
+        match &step.next {
+            Some(next) => next(answers),
+            None => self
+                .steps
+                .iter()
+                .position(|candidate| candidate.id == step.id)
+                .and_then(|index| self.steps.get(index + 1))
+                .map(|next| next.id.clone()),
+        }
+    }
+}
+
+/// Serializable progress of a wizard session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WizardState {
+    pub session_id: String,
+    /// Step to ask next; `None` once the wizard is complete
+    pub current_step: Option<String>,
+    pub answers: WizardAnswers,
+    /// Steps answered so far, in order
+    pub history: Vec<String>,
+}
+
+/// How a wizard session ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum WizardOutcome {
+    /// Every step was answered
+    Completed(WizardAnswers),
+    /// The user declined the given step
+    Declined { step: String },
+    /// The user dismissed the given step
+    Cancelled { step: String },
+}
+
+/// A running instance of a [`Wizard`]
+#[derive(Debug)]
+pub struct WizardSession {
+    wizard: Arc<Wizard>,
+    state: WizardState,
+}
+
+impl WizardSession {
+    pub fn session_id(&self) -> &str {
+        &self.state.session_id
+    }
+
+    /// Step that will be asked next
+    pub fn current_step(&self) -> Option<&str> {
+        self.state.current_step.as_deref()
+    }
+
+    pub fn answers(&self) -> &WizardAnswers {
+        &self.state.answers
+    }
+
+    /// Progress so far, for storing and later [`Wizard::resume`]
+    pub fn state(&self) -> &WizardState {
+        &self.state
+    }
+
+    pub fn into_state(self) -> WizardState {
+        self.state
+    }
+
+    /// Ask the remaining steps until the wizard completes or the user
+    /// declines or cancels
+    ///
+    /// If elicitation fails, the session stays at the current step and can
+    /// be resumed.
+    pub async fn run(&mut self, context: &Context) -> MCPResult<WizardOutcome> {
+        loop {
+            if let Some(outcome) = self.advance(context).await? {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Ask the current step, returning an outcome once the session ends
+    pub async fn advance(&mut self, context: &Context) -> MCPResult<Option<WizardOutcome>> {
+        let wizard = self.wizard.clone();
+        let Some(step_id) = self.state.current_step.clone() else {
+            return Ok(Some(WizardOutcome::Completed(self.state.answers.clone())));
+        };
+        let step = wizard.find_step(&step_id)?;
+
+        let mut request = step.request.clone();
+        for attempt in 1..=wizard.max_attempts {
+            debug!(
+                "Wizard {} asking step {} (attempt {})",
+                self.state.session_id, step.id, attempt
+            );
+            let response = context.elicit(request.clone()).await?;
+            match response.action {
+                ElicitationAction::Decline => {
+                    return Ok(Some(WizardOutcome::Declined { step: step_id }));
+                }
+                ElicitationAction::Cancel => {
+                    return Ok(Some(WizardOutcome::Cancelled { step: step_id }));
+                }
+                ElicitationAction::Accept => {}
+            }
+
+            let content = response
+                .content
+                .unwrap_or_else(|| Value::Object(Map::new()));
+            match step.validate(&content, &self.state.answers) {
+                Ok(()) => {
+                    self.state.answers.insert(step_id.clone(), content);
+                    self.state.history.push(step_id);
+                    self.state.current_step = wizard.step_after(step, &self.state.answers);
+                    if let Some(next) = &self.state.current_step {
+                        wizard.find_step(next)?;
+                        return Ok(None);
+                    }
+                    return Ok(Some(WizardOutcome::Completed(self.state.answers.clone())));
+                }
+                Err(error) => {
+                    request.message = context.localize(
+                        &LocalizedMessage::new(keys::ELICITATION_INVALID_ANSWER)
+                            .with_arg("error", error)
+                            .with_arg("prompt", &step.request.message),
+                    );
+                }
+            }
+        }
+
+        Err(MCPError::invalid_params(format!(
+            "No valid answer for wizard step {} after {} attempts",
+            step.id, wizard.max_attempts
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peer::ClientPeer;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+    use ultrafast_mcp_core::protocol::{
+        capabilities::{ClientCapabilities, ElicitationCapability},
+        jsonrpc::{JsonRpcMessage, JsonRpcResponse},
+    };
+
+    /// Context whose client answers elicitations with `answers` in order,
+    /// recording the messages it was shown
+    fn scripted_client(answers: Vec<Value>) -> (Context, tokio::task::JoinHandle<Vec<String>>) {
+        let (sender, mut outgoing) = mpsc::unbounded_channel();
+        let peer = Arc::new(ClientPeer::new(sender, std::time::Duration::from_secs(5)));
+        peer.set_client_capabilities(ClientCapabilities {
+            elicitation: Some(ElicitationCapability {}),
+            ..Default::default()
+        });
+
+        let client = peer.clone();
+        let handle = tokio::spawn(async move {
+            let mut shown = Vec::new();
+            for answer in answers {
+                let Some(JsonRpcMessage::Request(request)) = outgoing.recv().await else {
+                    break;
+                };
+                let params = request.params.unwrap_or_default();
+                shown.push(params["message"].as_str().unwrap_or_default().to_string());
+                client.handle_response(JsonRpcResponse::success(answer, request.id));
+            }
+            shown
+        });
+
+        (Context::new().with_client_peer(peer), handle)
+    }
+
+    fn wizard() -> Arc<Wizard> {
+        let plan_schema = json!({
+            "type": "object",
+            "properties": {"plan": {"type": "string"}},
+            "required": ["plan"]
+        });
+        Arc::new(
+            Wizard::new()
+                .step(
+                    WizardStep::new("plan", "Choose a plan", plan_schema).with_next(|answers| {
+                        match answers["plan"]["plan"].as_str() {
+                            Some("team") => Some("seats".to_string()),
+                            _ => None,
+                        }
+                    }),
+                )
+                .step(
+                    WizardStep::new("seats", "How many seats?", json!({"type": "object"}))
+                        .with_validator(|content, _| match content["seats"].as_i64() {
+                            Some(seats) if seats > 1 => Ok(()),
+                            _ => Err("A team needs at least two seats".to_string()),
+                        }),
+                ),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_wizard_branches_and_surfaces_validation_errors() {
+        let (context, client) = scripted_client(vec![
+            json!({"action": "accept", "content": {"plan": "team"}}),
+            json!({"action": "accept", "content": {"seats": 1}}),
+            json!({"action": "accept", "content": {"seats": 5}}),
+        ]);
+
+        let mut session = wizard().start();
+        let outcome = session.run(&context).await.unwrap();
+
+        let WizardOutcome::Completed(answers) = outcome else {
+            panic!("expected the wizard to complete");
+        };
+        assert_eq!(answers["seats"]["seats"], 5);
+        assert_eq!(session.state().history, vec!["plan", "seats"]);
+
+        let shown = client.await.unwrap();
+        assert_eq!(shown.len(), 3);
+        assert!(shown[2].starts_with("A team needs at least two seats"));
+    }
+
+    #[tokio::test]
+    async fn test_declined_session_resumes_at_same_step() {
+        let wizard = wizard();
+        let (context, _client) = scripted_client(vec![
+            json!({"action": "accept", "content": {"plan": "team"}}),
+            json!({"action": "decline"}),
+        ]);
+
+        let mut session = wizard.start();
+        let outcome = session.run(&context).await.unwrap();
+        assert_eq!(
+            outcome,
+            WizardOutcome::Declined {
+                step: "seats".to_string()
+            }
+        );
+
+        let saved = serde_json::to_value(session.state()).unwrap();
+        let state: WizardState = serde_json::from_value(saved).unwrap();
+        let (context, _client) =
+            scripted_client(vec![json!({"action": "accept", "content": {"seats": 3}})]);
+
+        let mut resumed = wizard.resume(state).unwrap();
+        assert_eq!(resumed.current_step(), Some("seats"));
+        assert!(matches!(
+            resumed.run(&context).await.unwrap(),
+            WizardOutcome::Completed(_)
+        ));
+        assert_eq!(resumed.answers()["plan"]["plan"], "team");
+    }
+}
@@ -172,6 +172,8 @@ mod handler_tests {
                         text: "test result".to_string(),
                     }],
                     is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
                 })
             }
             async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
@@ -0,0 +1,268 @@
+//! Pathological tool handlers for failure-injection tests
+//!
+//! Each handler serves a single tool that misbehaves in one predictable way,
+//! so server authors can check their middleware, timeout and budget
+//! configurations without writing throwaway handlers:
+//!
+//! - [`SlowToolHandler`] takes a fixed time to answer
+//! - [`FlakyToolHandler`] fails a fixed fraction of calls
+//! - [`HugeOutputToolHandler`] returns a result of a fixed size
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use ultrafast_mcp_test_utils::{SlowToolHandler, create_test_server};
+//!
+//! let server = create_test_server()
+//!     .with_tool_handler(Arc::new(SlowToolHandler::new(Duration::from_secs(5))));
+//! ```
+
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use ultrafast_mcp_core::{
+    MCPError, MCPResult,
+    types::tools::{ListToolsRequest, ListToolsResponse, Tool, ToolCall, ToolContent, ToolResult},
+};
+use ultrafast_mcp_server::ToolHandler;
+
+fn single_tool(name: &str, description: String) -> ListToolsResponse {
+    ListToolsResponse {
+        tools: vec![Tool {
+            name: name.to_string(),
+            description,
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+            output_schema: None,
+            annotations: None,
+        }],
+        next_cursor: None,
+    }
+}
+
+fn check_tool_name(call: &ToolCall, name: &str) -> MCPResult<()> {
+    if call.name == name {
+        Ok(())
+    } else {
+        Err(MCPError::method_not_found(format!(
+            "Unknown tool: {}",
+            call.name
+        )))
+    }
+}
+
+fn text_result(text: String) -> ToolResult {
+    ToolResult {
+        content: vec![ToolContent::text(text)],
+        is_error: Some(false),
+        structured_content: None,
+        progress_summary: None,
+    }
+}
+
+/// Tool handler that sleeps for a fixed duration before answering
+///
+/// Serves the tool `slow`.
+pub struct SlowToolHandler {
+    name: String,
+    duration: Duration,
+}
+
+impl SlowToolHandler {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            name: "slow".to_string(),
+            duration,
+        }
+    }
+
+    /// Serve the tool under `name` instead of `slow`
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for SlowToolHandler {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        check_tool_name(&call, &self.name)?;
+        tokio::time::sleep(self.duration).await;
+        Ok(text_result(format!(
+            "Finished after {}ms",
+            self.duration.as_millis()
+        )))
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        Ok(single_tool(
+            &self.name,
+            format!("Answers after {}ms", self.duration.as_millis()),
+        ))
+    }
+}
+
+/// Tool handler that fails a fixed fraction of its calls
+///
+/// Serves the tool `flaky`. Failures are spread evenly and deterministically:
+/// with an error rate of 0.25, exactly one call in every four returns an
+/// internal error, so tests are reproducible.
+pub struct FlakyToolHandler {
+    name: String,
+    error_rate: f64,
+    calls: AtomicU64,
+}
+
+impl FlakyToolHandler {
+    /// Fail `error_rate` of calls, clamped to `0.0..=1.0`
+    pub fn new(error_rate: f64) -> Self {
+        Self {
+            name: "flaky".to_string(),
+            error_rate: error_rate.clamp(0.0, 1.0),
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// Serve the tool under `name` instead of `flaky`
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Calls handled so far, successful or not
+    pub fn call_count(&self) -> u64 {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    fn should_fail(&self, call: u64) -> bool {
+        // Fail whenever the running total of expected failures ticks over
+        let failures = |calls: u64| (calls as f64 * self.error_rate).floor();
+        failures(call + 1) > failures(call)
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for FlakyToolHandler {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        check_tool_name(&call, &self.name)?;
+        let count = self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.should_fail(count) {
+            return Err(MCPError::internal_error(format!(
+                "Injected failure on call {}",
+                count + 1
+            )));
+        }
+        Ok(text_result(format!("Succeeded on call {}", count + 1)))
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        Ok(single_tool(
+            &self.name,
+            format!("Fails {}% of calls", self.error_rate * 100.0),
+        ))
+    }
+}
+
+/// Tool handler that returns a text result of a fixed size
+///
+/// Serves the tool `huge_output`. The text is `size` bytes of ASCII.
+pub struct HugeOutputToolHandler {
+    name: String,
+    size: usize,
+}
+
+impl HugeOutputToolHandler {
+    pub fn new(size: usize) -> Self {
+        Self {
+            name: "huge_output".to_string(),
+            size,
+        }
+    }
+
+    /// Serve the tool under `name` instead of `huge_output`
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for HugeOutputToolHandler {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        check_tool_name(&call, &self.name)?;
+        Ok(text_result("x".repeat(self.size)))
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        Ok(single_tool(
+            &self.name,
+            format!("Returns {} bytes of text", self.size),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str) -> ToolCall {
+        ToolCall {
+            name: name.to_string(),
+            arguments: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_tool_handler_waits() {
+        let handler = SlowToolHandler::new(Duration::from_millis(50));
+        let started = std::time::Instant::now();
+        handler.handle_tool_call(call("slow")).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        let timed_out = tokio::time::timeout(
+            Duration::from_millis(10),
+            handler.handle_tool_call(call("slow")),
+        )
+        .await;
+        assert!(timed_out.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_flaky_tool_handler_fails_at_error_rate() {
+        let handler = FlakyToolHandler::new(0.25);
+        let mut failures = 0;
+        for _ in 0..100 {
+            if handler.handle_tool_call(call("flaky")).await.is_err() {
+                failures += 1;
+            }
+        }
+        assert_eq!(failures, 25);
+        assert_eq!(handler.call_count(), 100);
+
+        let never = FlakyToolHandler::new(0.0);
+        let always = FlakyToolHandler::new(1.0);
+        for _ in 0..10 {
+            assert!(never.handle_tool_call(call("flaky")).await.is_ok());
+            assert!(always.handle_tool_call(call("flaky")).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_huge_output_tool_handler_size() {
+        let handler = HugeOutputToolHandler::new(1024 * 1024).with_name("dump");
+        let result = handler.handle_tool_call(call("dump")).await.unwrap();
+        let ToolContent::Text { text } = &result.content[0] else {
+            panic!("expected text content");
+        };
+        assert_eq!(text.len(), 1024 * 1024);
+
+        assert!(handler.handle_tool_call(call("huge_output")).await.is_err());
+        let tools = handler
+            .list_tools(ListToolsRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(tools.tools[0].name, "dump");
+    }
+}
@@ -120,15 +120,21 @@ impl ToolHandler for TestEchoToolHandler {
                 Ok(ToolResult {
                     content: vec![ToolContent::text(message.to_string())],
                     is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
                 })
             }
             "error" => Ok(ToolResult {
                 content: vec![ToolContent::text("This is a test error".to_string())],
                 is_error: Some(true),
+                structured_content: None,
+                progress_summary: None,
             }),
             _ => Ok(ToolResult {
                 content: vec![ToolContent::text(format!("Unknown tool: {}", call.name))],
                 is_error: Some(true),
+                structured_content: None,
+                progress_summary: None,
             }),
         }
     }
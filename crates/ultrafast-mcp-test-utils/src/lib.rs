@@ -4,9 +4,15 @@
 //! across test files in the UltraFast MCP ecosystem.
 
 pub mod assertions;
+pub mod faults;
 pub mod fixtures;
 pub mod mocks;
+#[cfg(feature = "oauth")]
+pub mod oauth;
 
 pub use assertions::*;
+pub use faults::*;
 pub use fixtures::*;
 pub use mocks::*;
+#[cfg(feature = "oauth")]
+pub use oauth::*;
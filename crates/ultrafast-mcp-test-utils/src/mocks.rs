@@ -103,9 +103,13 @@ impl Transport for MockTransport {
             last_activity: Some(std::time::SystemTime::now()),
             messages_sent: self.sent_messages.lock().unwrap().len() as u64,
             messages_received: 0, // Mock doesn't track this
+            bytes_sent: 0,
+            bytes_received: 0,
             connection_duration: Some(std::time::Duration::from_secs(10)),
             error_count: 0,
             last_error: None,
+            pool: None,
+            pings: None,
         }
     }
 }
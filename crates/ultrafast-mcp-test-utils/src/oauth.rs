@@ -0,0 +1,636 @@
+//! In-process OAuth 2.1 authorization server for auth tests
+//!
+//! [`MockOAuthServer`] listens on a local port and implements the parts of
+//! an authorization server MCP clients and servers talk to:
+//!
+//! - metadata discovery (RFC 8414) at `/.well-known/oauth-authorization-server`
+//!   and `/.well-known/authorization-server`
+//! - the authorization code grant with PKCE (`S256` only) at `/authorize`,
+//!   which redirects straight back without asking anyone
+//! - token issuance and refresh token rotation at `/token`
+//! - token introspection (RFC 7662) at `/introspect`
+//!
+//! Tokens are opaque random strings. Every endpoint can be told to fail,
+//! once or until cleared, so error handling can be tested without a real
+//! identity provider:
+//!
+//! ```rust,no_run
+//! use ultrafast_mcp_test_utils::{MockOAuthServer, OAuthEndpoint, OAuthFailure};
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let server = MockOAuthServer::start().await?;
+//! server.fail_next(OAuthEndpoint::Token, OAuthFailure::Error("invalid_grant".into()));
+//! let token_endpoint = server.token_endpoint();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Form, Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Digest;
+use tokio::sync::oneshot;
+use ultrafast_mcp_core::utils::generate_secure_random;
+
+/// Client registered with every mock server
+pub const MOCK_CLIENT_ID: &str = "test-client";
+/// Secret of [`MOCK_CLIENT_ID`]
+pub const MOCK_CLIENT_SECRET: &str = "test-secret";
+
+/// An endpoint of the [`MockOAuthServer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OAuthEndpoint {
+    Metadata,
+    Authorize,
+    Token,
+    Introspect,
+}
+
+/// How an endpoint of the [`MockOAuthServer`] misbehaves
+#[derive(Debug, Clone, PartialEq)]
+pub enum OAuthFailure {
+    /// Answer with this OAuth error code, such as `invalid_grant`
+    ///
+    /// `/authorize` redirects back with the error, as for a user who
+    /// denied access; the other endpoints answer 400.
+    Error(String),
+    /// Answer 500
+    ServerError,
+    /// Answer 200 with a body that is not JSON
+    MalformedResponse,
+    /// Wait before handling the request normally
+    Delay(Duration),
+}
+
+#[derive(Debug, Default)]
+struct Failures {
+    always: Option<OAuthFailure>,
+    next: VecDeque<OAuthFailure>,
+}
+
+struct Client {
+    secret: Option<String>,
+}
+
+struct PendingCode {
+    client_id: String,
+    redirect_uri: String,
+    code_challenge: String,
+    scope: Option<String>,
+}
+
+struct AccessToken {
+    client_id: String,
+    scope: Option<String>,
+    expires_at: Instant,
+    expires_at_unix: u64,
+}
+
+struct RefreshToken {
+    client_id: String,
+    scope: Option<String>,
+}
+
+struct MockState {
+    issuer: String,
+    token_lifetime: Duration,
+    clients: HashMap<String, Client>,
+    codes: HashMap<String, PendingCode>,
+    access_tokens: HashMap<String, AccessToken>,
+    refresh_tokens: HashMap<String, RefreshToken>,
+    failures: HashMap<OAuthEndpoint, Failures>,
+    requests: HashMap<OAuthEndpoint, u64>,
+}
+
+type Shared = Arc<Mutex<MockState>>;
+
+fn lock(state: &Shared) -> MutexGuard<'_, MockState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl MockState {
+    fn issue_tokens(&mut self, client_id: &str, scope: Option<String>) -> serde_json::Value {
+        let access_token = generate_secure_random(40);
+        let refresh_token = generate_secure_random(40);
+        let expires_at_unix = SystemTime::now()
+            .checked_add(self.token_lifetime)
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |at| at.as_secs());
+        self.access_tokens.insert(
+            access_token.clone(),
+            AccessToken {
+                client_id: client_id.to_string(),
+                scope: scope.clone(),
+                expires_at: Instant::now() + self.token_lifetime,
+                expires_at_unix,
+            },
+        );
+        self.refresh_tokens.insert(
+            refresh_token.clone(),
+            RefreshToken {
+                client_id: client_id.to_string(),
+                scope: scope.clone(),
+            },
+        );
+        json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+            "expires_in": self.token_lifetime.as_secs(),
+            "refresh_token": refresh_token,
+            "scope": scope,
+        })
+    }
+}
+
+/// A local OAuth 2.1 authorization server, see the [module docs](self)
+///
+/// The server stops when dropped.
+pub struct MockOAuthServer {
+    addr: SocketAddr,
+    state: Shared,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockOAuthServer {
+    /// Start a server on a free local port, with [`MOCK_CLIENT_ID`]
+    /// registered
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let mut clients = HashMap::new();
+        clients.insert(
+            MOCK_CLIENT_ID.to_string(),
+            Client {
+                secret: Some(MOCK_CLIENT_SECRET.to_string()),
+            },
+        );
+        let state = Arc::new(Mutex::new(MockState {
+            issuer: format!("http://{addr}"),
+            token_lifetime: Duration::from_secs(3600),
+            clients,
+            codes: HashMap::new(),
+            access_tokens: HashMap::new(),
+            refresh_tokens: HashMap::new(),
+            failures: HashMap::new(),
+            requests: HashMap::new(),
+        }));
+
+        let router = Router::new()
+            .route("/.well-known/oauth-authorization-server", get(metadata))
+            .route("/.well-known/authorization-server", get(metadata))
+            .route("/authorize", get(authorize))
+            .route("/token", post(token))
+            .route("/introspect", post(introspect))
+            .with_state(state.clone());
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await;
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Base URL, also the issuer
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn authorization_endpoint(&self) -> String {
+        format!("{}/authorize", self.url())
+    }
+
+    pub fn token_endpoint(&self) -> String {
+        format!("{}/token", self.url())
+    }
+
+    pub fn introspection_endpoint(&self) -> String {
+        format!("{}/introspect", self.url())
+    }
+
+    /// Register a client; public clients have no secret
+    pub fn register_client(&self, client_id: impl Into<String>, secret: Option<&str>) {
+        lock(&self.state).clients.insert(
+            client_id.into(),
+            Client {
+                secret: secret.map(str::to_string),
+            },
+        );
+    }
+
+    /// How long tokens issued from now on are valid, one hour by default
+    pub fn set_token_lifetime(&self, lifetime: Duration) {
+        lock(&self.state).token_lifetime = lifetime;
+    }
+
+    /// Make the next request to `endpoint` fail; calls queue up
+    pub fn fail_next(&self, endpoint: OAuthEndpoint, failure: OAuthFailure) {
+        let mut state = lock(&self.state);
+        state
+            .failures
+            .entry(endpoint)
+            .or_default()
+            .next
+            .push_back(failure);
+    }
+
+    /// Make every request to `endpoint` fail until [`clear_failures`](Self::clear_failures)
+    pub fn fail_always(&self, endpoint: OAuthEndpoint, failure: OAuthFailure) {
+        let mut state = lock(&self.state);
+        state.failures.entry(endpoint).or_default().always = Some(failure);
+    }
+
+    pub fn clear_failures(&self) {
+        lock(&self.state).failures.clear();
+    }
+
+    /// Requests `endpoint` received so far, failed or not
+    pub fn request_count(&self, endpoint: OAuthEndpoint) -> u64 {
+        lock(&self.state)
+            .requests
+            .get(&endpoint)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Issue an authorization code without going through `/authorize`
+    pub fn issue_code(
+        &self,
+        client_id: &str,
+        redirect_uri: &str,
+        code_challenge: &str,
+        scope: Option<&str>,
+    ) -> String {
+        let code = generate_secure_random(32);
+        lock(&self.state).codes.insert(
+            code.clone(),
+            PendingCode {
+                client_id: client_id.to_string(),
+                redirect_uri: redirect_uri.to_string(),
+                code_challenge: code_challenge.to_string(),
+                scope: scope.map(str::to_string),
+            },
+        );
+        code
+    }
+
+    /// Issue an access token for [`MOCK_CLIENT_ID`] without any grant
+    pub fn issue_access_token(&self, scope: Option<&str>) -> String {
+        let tokens = lock(&self.state).issue_tokens(MOCK_CLIENT_ID, scope.map(str::to_string));
+        tokens["access_token"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Make `token` inactive, whether access or refresh token
+    pub fn revoke(&self, token: &str) {
+        let mut state = lock(&self.state);
+        state.access_tokens.remove(token);
+        state.refresh_tokens.remove(token);
+    }
+}
+
+impl Drop for MockOAuthServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// Count the request and return the failure to inject, after any delay
+async fn injected_failure(state: &Shared, endpoint: OAuthEndpoint) -> Option<OAuthFailure> {
+    let failure = {
+        let mut state = lock(state);
+        *state.requests.entry(endpoint).or_default() += 1;
+        let failures = state.failures.entry(endpoint).or_default();
+        failures
+            .next
+            .pop_front()
+            .or_else(|| failures.always.clone())
+    };
+    match failure {
+        Some(OAuthFailure::Delay(delay)) => {
+            tokio::time::sleep(delay).await;
+            None
+        }
+        failure => failure,
+    }
+}
+
+fn oauth_error(status: StatusCode, error: &str, description: &str) -> Response {
+    (
+        status,
+        Json(json!({ "error": error, "error_description": description })),
+    )
+        .into_response()
+}
+
+fn failure_response(failure: OAuthFailure) -> Response {
+    match failure {
+        OAuthFailure::Error(error) => {
+            oauth_error(StatusCode::BAD_REQUEST, &error, "Injected failure")
+        }
+        OAuthFailure::ServerError => {
+            (StatusCode::INTERNAL_SERVER_ERROR, "Injected server error").into_response()
+        }
+        OAuthFailure::MalformedResponse => {
+            ([(header::CONTENT_TYPE, "application/json")], "{ not json").into_response()
+        }
+        OAuthFailure::Delay(_) => StatusCode::OK.into_response(),
+    }
+}
+
+async fn metadata(State(state): State<Shared>) -> Response {
+    if let Some(failure) = injected_failure(&state, OAuthEndpoint::Metadata).await {
+        return failure_response(failure);
+    }
+    let issuer = lock(&state).issuer.clone();
+    Json(json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{issuer}/authorize"),
+        "token_endpoint": format!("{issuer}/token"),
+        "introspection_endpoint": format!("{issuer}/introspect"),
+        "response_types_supported": ["code"],
+        "grant_types_supported": ["authorization_code", "refresh_token"],
+        "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post", "none"],
+        "code_challenge_methods_supported": ["S256"],
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct AuthorizeParams {
+    response_type: Option<String>,
+    client_id: Option<String>,
+    redirect_uri: Option<String>,
+    scope: Option<String>,
+    state: Option<String>,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+}
+
+/// Redirect to `redirect_uri` with `params` and the request's `state`
+fn redirect_back(redirect_uri: &str, params: &[(&str, &str)], state: Option<&str>) -> Response {
+    let Ok(mut url) = url::Url::parse(redirect_uri) else {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "redirect_uri is not a URL",
+        );
+    };
+    {
+        let mut query = url.query_pairs_mut();
+        for (key, value) in params {
+            query.append_pair(key, value);
+        }
+        if let Some(state) = state {
+            query.append_pair("state", state);
+        }
+    }
+    Redirect::to(url.as_str()).into_response()
+}
+
+async fn authorize(State(state): State<Shared>, Query(params): Query<AuthorizeParams>) -> Response {
+    let failure = injected_failure(&state, OAuthEndpoint::Authorize).await;
+
+    let Some(client_id) = params.client_id else {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "client_id is required",
+        );
+    };
+    let Some(redirect_uri) = params.redirect_uri else {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "redirect_uri is required",
+        );
+    };
+    if !lock(&state).clients.contains_key(&client_id) {
+        return oauth_error(StatusCode::BAD_REQUEST, "invalid_client", "Unknown client");
+    }
+    // From here on errors go back to the client through the redirect
+    let reject = |error: &str, description: &str| {
+        redirect_back(
+            &redirect_uri,
+            &[("error", error), ("error_description", description)],
+            params.state.as_deref(),
+        )
+    };
+    match failure {
+        Some(OAuthFailure::Error(error)) => return reject(&error, "Injected failure"),
+        Some(failure) => return failure_response(failure),
+        None => {}
+    }
+    if params.response_type.as_deref() != Some("code") {
+        return reject(
+            "unsupported_response_type",
+            "Only the code flow is supported",
+        );
+    }
+    let Some(code_challenge) = params.code_challenge else {
+        return reject("invalid_request", "PKCE code_challenge is required");
+    };
+    if params.code_challenge_method.as_deref() != Some("S256") {
+        return reject("invalid_request", "code_challenge_method must be S256");
+    }
+
+    let code = generate_secure_random(32);
+    lock(&state).codes.insert(
+        code.clone(),
+        PendingCode {
+            client_id,
+            redirect_uri: redirect_uri.clone(),
+            code_challenge,
+            scope: params.scope,
+        },
+    );
+    redirect_back(&redirect_uri, &[("code", &code)], params.state.as_deref())
+}
+
+#[derive(Deserialize)]
+struct TokenParams {
+    grant_type: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    code: Option<String>,
+    redirect_uri: Option<String>,
+    code_verifier: Option<String>,
+    refresh_token: Option<String>,
+}
+
+/// Client ID and secret from the `Authorization` header or the form
+fn client_credentials(
+    headers: &HeaderMap,
+    params: &TokenParams,
+) -> Option<(String, Option<String>)> {
+    let basic = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| {
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .ok()
+        })
+        .and_then(|decoded| String::from_utf8(decoded).ok());
+    match basic {
+        Some(credentials) => {
+            let (id, secret) = credentials.split_once(':')?;
+            Some((id.to_string(), Some(secret.to_string())))
+        }
+        None => Some((params.client_id.clone()?, params.client_secret.clone())),
+    }
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(sha2::Sha256::digest(verifier))
+}
+
+async fn token(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    Form(params): Form<TokenParams>,
+) -> Response {
+    if let Some(failure) = injected_failure(&state, OAuthEndpoint::Token).await {
+        return failure_response(failure);
+    }
+
+    let Some((client_id, secret)) = client_credentials(&headers, &params) else {
+        return oauth_error(
+            StatusCode::UNAUTHORIZED,
+            "invalid_client",
+            "Client authentication failed",
+        );
+    };
+    let mut state = lock(&state);
+    let authenticated = state
+        .clients
+        .get(&client_id)
+        .is_some_and(|client| client.secret.is_none() || client.secret == secret);
+    if !authenticated {
+        return oauth_error(
+            StatusCode::UNAUTHORIZED,
+            "invalid_client",
+            "Client authentication failed",
+        );
+    }
+
+    match params.grant_type.as_str() {
+        "authorization_code" => {
+            // Codes are single use, even when the exchange fails
+            let Some(pending) = params
+                .code
+                .as_deref()
+                .and_then(|code| state.codes.remove(code))
+            else {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_grant",
+                    "Unknown or used code",
+                );
+            };
+            if pending.client_id != client_id {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_grant",
+                    "Code was issued to another client",
+                );
+            }
+            if params.redirect_uri.as_deref() != Some(pending.redirect_uri.as_str()) {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_grant",
+                    "redirect_uri does not match",
+                );
+            }
+            let verified = params
+                .code_verifier
+                .as_deref()
+                .is_some_and(|verifier| pkce_challenge(verifier) == pending.code_challenge);
+            if !verified {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_grant",
+                    "PKCE verification failed",
+                );
+            }
+            Json(state.issue_tokens(&client_id, pending.scope)).into_response()
+        }
+        "refresh_token" => {
+            // Refresh tokens are rotated: the one presented stops working
+            let Some(refresh) = params
+                .refresh_token
+                .as_deref()
+                .and_then(|token| state.refresh_tokens.remove(token))
+            else {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_grant",
+                    "Unknown or used refresh token",
+                );
+            };
+            if refresh.client_id != client_id {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_grant",
+                    "Refresh token was issued to another client",
+                );
+            }
+            Json(state.issue_tokens(&client_id, refresh.scope)).into_response()
+        }
+        other => oauth_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            &format!("Unsupported grant type: {other}"),
+        ),
+    }
+}
+
+#[derive(Deserialize)]
+struct IntrospectParams {
+    token: String,
+}
+
+async fn introspect(State(state): State<Shared>, Form(params): Form<IntrospectParams>) -> Response {
+    if let Some(failure) = injected_failure(&state, OAuthEndpoint::Introspect).await {
+        return failure_response(failure);
+    }
+    let state = lock(&state);
+    match state.access_tokens.get(&params.token) {
+        Some(token) if token.expires_at > Instant::now() => Json(json!({
+            "active": true,
+            "client_id": token.client_id,
+            "scope": token.scope,
+            "token_type": "Bearer",
+            "exp": token.expires_at_unix,
+            "iss": state.issuer,
+        }))
+        .into_response(),
+        _ => Json(json!({ "active": false })).into_response(),
+    }
+}
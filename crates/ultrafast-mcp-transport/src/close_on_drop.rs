@@ -0,0 +1,172 @@
+//! Transports that close themselves when dropped
+//!
+//! [`Transport::close`] is asynchronous and so cannot run in `Drop`. Wrapping
+//! a transport with [`close_on_drop`] closes it on a spawned tokio task if it
+//! is dropped without having been closed, e.g. when the task driving it is
+//! aborted.
+
+use async_trait::async_trait;
+use tracing::warn;
+use ultrafast_mcp_core::protocol::JsonRpcMessage;
+
+use crate::{
+    ConnectionState, Result, ShutdownConfig, Transport, TransportDescription, TransportHealth,
+};
+
+/// Wrap `transport` so that dropping it unclosed closes it in the background
+pub fn close_on_drop<T: Transport + 'static>(transport: T) -> CloseOnDrop<T> {
+    CloseOnDrop {
+        inner: Some(transport),
+        closed: false,
+    }
+}
+
+/// Transport closed on a spawned task when dropped unclosed, see
+/// [`close_on_drop`]
+///
+/// Dropping it outside a tokio runtime drops the inner transport without
+/// closing it.
+pub struct CloseOnDrop<T: Transport + 'static> {
+    // Only taken in `drop`
+    inner: Option<T>,
+    closed: bool,
+}
+
+impl<T: Transport + 'static> CloseOnDrop<T> {
+    fn inner(&self) -> &T {
+        self.inner.as_ref().expect("transport taken before drop")
+    }
+
+    fn inner_mut(&mut self) -> &mut T {
+        self.inner.as_mut().expect("transport taken before drop")
+    }
+
+    /// The wrapped transport
+    pub fn get_ref(&self) -> &T {
+        self.inner()
+    }
+
+    /// Unwrap the transport, which is then no longer closed on drop
+    pub fn into_inner(mut self) -> T {
+        self.inner.take().expect("transport taken before drop")
+    }
+}
+
+impl<T: Transport + 'static> Drop for CloseOnDrop<T> {
+    fn drop(&mut self) {
+        let Some(mut transport) = self.inner.take() else {
+            return;
+        };
+        if self.closed {
+            return;
+        }
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                if let Err(e) = transport.close().await {
+                    warn!("Failed to close dropped transport: {}", e);
+                }
+            });
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport + 'static> Transport for CloseOnDrop<T> {
+    async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        self.inner_mut().send_message(message).await
+    }
+
+    async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+        self.inner_mut().receive_message().await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.closed = true;
+        self.inner_mut().close().await
+    }
+
+    fn get_state(&self) -> ConnectionState {
+        self.inner().get_state()
+    }
+
+    fn get_health(&self) -> TransportHealth {
+        self.inner().get_health()
+    }
+
+    fn describe(&self) -> TransportDescription {
+        self.inner().describe()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner().is_ready()
+    }
+
+    async fn shutdown(&mut self, config: ShutdownConfig) -> Result<()> {
+        self.closed = true;
+        self.inner_mut().shutdown(config).await
+    }
+
+    async fn force_shutdown(&mut self) -> Result<()> {
+        self.closed = true;
+        self.inner_mut().force_shutdown().await
+    }
+
+    async fn reconnect(&mut self) -> Result<()> {
+        let result = self.inner_mut().reconnect().await;
+        if result.is_ok() {
+            self.closed = false;
+        }
+        result
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.inner_mut().reset().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    struct CountingTransport {
+        closes: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Transport for CountingTransport {
+        async fn send_message(&mut self, _message: JsonRpcMessage) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+            Err(crate::TransportError::ConnectionClosed)
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            self.closes.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_transport_is_closed_once() {
+        let closes = Arc::new(AtomicUsize::new(0));
+        drop(close_on_drop(CountingTransport {
+            closes: closes.clone(),
+        }));
+        tokio::task::yield_now().await;
+        assert_eq!(closes.load(Ordering::SeqCst), 1);
+
+        let mut transport = close_on_drop(CountingTransport {
+            closes: closes.clone(),
+        });
+        transport.close().await.unwrap();
+        drop(transport);
+        tokio::task::yield_now().await;
+        assert_eq!(closes.load(Ordering::SeqCst), 2);
+    }
+}
@@ -6,15 +6,37 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{collections::VecDeque, fmt};
 use thiserror::Error;
-use ultrafast_mcp_core::protocol::JsonRpcMessage;
-
+use ultrafast_mcp_core::{
+    error::{BoxError, ErrorCategory, MCPError},
+    protocol::{JsonRpcMessage, JsonRpcRequest, RequestId},
+    utils::PingStats,
+};
+
+pub mod close_on_drop;
+pub mod named_pipe;
+#[cfg(feature = "noise")]
+pub mod noise;
+pub mod recovery;
+#[cfg(feature = "signing")]
+pub mod signing;
 pub mod stdio;
+pub mod stream;
 
-#[cfg(feature = "http")]
+#[cfg(any(feature = "http-client", feature = "http-server"))]
 pub mod streamable_http;
 
+pub use close_on_drop::{CloseOnDrop, close_on_drop};
+#[cfg(windows)]
+pub use named_pipe::NamedPipeListener;
+pub use named_pipe::NamedPipeTransport;
+#[cfg(feature = "monitoring")]
+pub use recovery::RecoveryMetricsRecorder;
+use recovery::{CircuitBreaker, CircuitTransition};
+pub use recovery::{CircuitBreakerConfig, CircuitState, HealthProbeConfig, RecoveryMetrics};
+pub use stream::{ChildProcessTransport, DuplexTransport, StreamTransport, duplex_pair};
+
 /// Result type for transport operations
 pub type Result<T> = std::result::Result<T, TransportError>;
 
@@ -48,6 +70,88 @@ impl fmt::Display for ConnectionState {
     }
 }
 
+/// Kind of connection a transport runs over
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
+pub enum TransportKind {
+    Stdio,
+    Http,
+    WebSocket,
+    /// Any other transport, named by its implementation
+    Custom(String),
+    /// A transport that does not describe itself
+    #[default]
+    Unknown,
+}
+
+impl TransportKind {
+    /// Short label used in metrics and logs: `stdio`, `http`, `ws`, the
+    /// custom name or `unknown`
+    pub fn as_str(&self) -> &str {
+        match self {
+            TransportKind::Stdio => "stdio",
+            TransportKind::Http => "http",
+            TransportKind::WebSocket => "ws",
+            TransportKind::Custom(name) => name,
+            TransportKind::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for TransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<TransportKind> for String {
+    fn from(kind: TransportKind) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
+impl From<String> for TransportKind {
+    fn from(label: String) -> Self {
+        match label.as_str() {
+            "stdio" => TransportKind::Stdio,
+            "http" => TransportKind::Http,
+            "ws" => TransportKind::WebSocket,
+            "unknown" => TransportKind::Unknown,
+            _ => TransportKind::Custom(label),
+        }
+    }
+}
+
+/// What a transport is connected through, for labelling metrics and logs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportDescription {
+    pub kind: TransportKind,
+    /// Session the connection belongs to, for transports with sessions
+    pub session_id: Option<String>,
+    /// Address of the other end, for network transports
+    pub remote_address: Option<String>,
+}
+
+impl TransportDescription {
+    pub fn new(kind: TransportKind) -> Self {
+        Self {
+            kind,
+            session_id: None,
+            remote_address: None,
+        }
+    }
+
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn with_remote_address(mut self, remote_address: impl Into<String>) -> Self {
+        self.remote_address = Some(remote_address.into());
+        self
+    }
+}
+
 /// Transport health information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransportHealth {
@@ -55,9 +159,41 @@ pub struct TransportHealth {
     pub last_activity: Option<std::time::SystemTime>,
     pub messages_sent: u64,
     pub messages_received: u64,
+    /// Bytes written to the connection, for transports that count them
+    #[serde(default)]
+    pub bytes_sent: u64,
+    /// Bytes read from the connection, for transports that count them
+    #[serde(default)]
+    pub bytes_received: u64,
     pub connection_duration: Option<std::time::Duration>,
     pub error_count: u64,
     pub last_error: Option<String>,
+    /// Usage of the HTTP connection pool, for transports that have one
+    #[serde(default)]
+    pub pool: Option<PoolStats>,
+    /// Pings sent over the connection, when a [`PingPolicy`] watches it
+    ///
+    /// Transports leave this empty; the client or server pinging fills it
+    /// in.
+    ///
+    /// [`PingPolicy`]: ultrafast_mcp_core::utils::PingPolicy
+    #[serde(default)]
+    pub pings: Option<PingStats>,
+}
+
+/// Snapshot of an HTTP connection pool's usage
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolStats {
+    pub max_connections: usize,
+    pub max_connections_per_host: usize,
+    /// Requests currently holding a connection
+    pub in_use: usize,
+    /// Highest `in_use` since the pool was created
+    pub peak_in_use: usize,
+    /// Requests sent through the pool
+    pub total_requests: u64,
+    /// Requests that had to wait for a connection to free up
+    pub waited_requests: u64,
 }
 
 impl Default for TransportHealth {
@@ -67,9 +203,13 @@ impl Default for TransportHealth {
             last_activity: None,
             messages_sent: 0,
             messages_received: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
             connection_duration: None,
             error_count: 0,
             last_error: None,
+            pool: None,
+            pings: None,
         }
     }
 }
@@ -79,14 +219,57 @@ impl Default for TransportHealth {
 pub enum TransportEvent {
     Connected,
     Disconnected,
-    Reconnecting,
+    /// A recovery attempt is about to start after `delay`
+    Reconnecting {
+        attempt: u32,
+        delay: std::time::Duration,
+    },
+    /// The peer asked to slow down; the operation is retried on the same
+    /// connection after `delay`
+    Throttled {
+        delay: std::time::Duration,
+    },
     MessageSent,
     MessageReceived,
-    Error(String),
+    Error {
+        category: ErrorCategory,
+        retryable: bool,
+        message: String,
+    },
+    /// Recovery attempt `attempt` failed to reconnect; another may follow
+    RecoveryAttemptFailed {
+        attempt: u32,
+        category: ErrorCategory,
+        message: String,
+    },
+    /// The health probe after a reconnect finished
+    ProbeCompleted {
+        attempt: u32,
+        success: bool,
+        latency: std::time::Duration,
+    },
+    CircuitStateChanged {
+        from: CircuitState,
+        to: CircuitState,
+    },
+    /// Recovery gave up after `attempts` attempts
+    RecoveryFailed {
+        attempts: u32,
+    },
     ShutdownRequested,
     ShutdownComplete,
 }
 
+impl TransportEvent {
+    fn error(err: &TransportError) -> Self {
+        TransportEvent::Error {
+            category: err.category(),
+            retryable: err.is_retryable(),
+            message: err.to_string(),
+        }
+    }
+}
+
 /// Callback trait for transport lifecycle events
 #[async_trait]
 pub trait TransportEventHandler: Send + Sync {
@@ -101,6 +284,10 @@ pub struct RecoveryConfig {
     pub max_delay: std::time::Duration,
     pub backoff_multiplier: f64,
     pub enable_jitter: bool,
+    /// Ping the peer after each reconnect before declaring recovery successful
+    pub health_probe: Option<HealthProbeConfig>,
+    /// Fail fast after repeated recovery failures
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
 }
 
 impl Default for RecoveryConfig {
@@ -111,10 +298,35 @@ impl Default for RecoveryConfig {
             max_delay: std::time::Duration::from_secs(30),
             backoff_multiplier: 2.0,
             enable_jitter: true,
+            health_probe: None,
+            circuit_breaker: None,
         }
     }
 }
 
+impl RecoveryConfig {
+    /// Delay before retry number `attempt`, counting from zero
+    pub fn retry_delay(&self, attempt: u32) -> std::time::Duration {
+        let base_delay = self.initial_delay.as_millis() as f64;
+        let multiplier = self.backoff_multiplier.powi(attempt as i32);
+        let mut delay_ms = base_delay * multiplier;
+
+        // Add jitter if enabled
+        if self.enable_jitter {
+            use rand::Rng;
+            let mut rng = rand::rng();
+            let jitter: f64 = rng.random_range(0.8..1.2);
+            delay_ms *= jitter;
+        }
+
+        // Cap at max delay
+        let max_delay_ms = self.max_delay.as_millis() as f64;
+        delay_ms = delay_ms.min(max_delay_ms);
+
+        std::time::Duration::from_millis(delay_ms as u64)
+    }
+}
+
 /// Transport shutdown configuration
 #[derive(Debug, Clone)]
 pub struct ShutdownConfig {
@@ -149,10 +361,18 @@ pub enum TransportError {
     SerializationError { message: String },
 
     #[error("Network error: {message}")]
-    NetworkError { message: String },
+    NetworkError {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 
     #[error("Authentication error: {message}")]
-    AuthenticationError { message: String },
+    AuthenticationError {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 
     #[error("Protocol error: {message}")]
     ProtocolError { message: String },
@@ -171,6 +391,119 @@ pub enum TransportError {
 
     #[error("Transport not ready: current state is {state}")]
     NotReady { state: ConnectionState },
+
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<std::time::Duration>,
+        /// The peer's limit, when it says what it is
+        limit: Option<u32>,
+    },
+}
+
+impl TransportError {
+    /// Category in the shared [`ErrorCategory`] taxonomy; always equal to
+    /// the category of the [`MCPError`] this error converts into
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            TransportError::ConnectionError { .. }
+            | TransportError::ConnectionClosed
+            | TransportError::NetworkError { .. }
+            | TransportError::InitializationError { .. }
+            | TransportError::RecoveryFailed { .. }
+            | TransportError::ShutdownTimeout { .. }
+            | TransportError::NotReady { .. } => ErrorCategory::Transport,
+            TransportError::ConnectionTimeout => ErrorCategory::Timeout,
+            TransportError::SerializationError { .. } => ErrorCategory::Serialization,
+            TransportError::ProtocolError { .. } => ErrorCategory::Protocol,
+            TransportError::AuthenticationError { .. } => ErrorCategory::Authentication,
+            TransportError::InternalError { .. } => ErrorCategory::Internal,
+            TransportError::RateLimited { .. } => ErrorCategory::RateLimited,
+        }
+    }
+
+    /// Whether retrying the operation, possibly after reconnecting, may succeed
+    ///
+    /// Recovery has already been exhausted for `RecoveryFailed` and
+    /// `ShutdownTimeout`, so those are never retryable.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            TransportError::RecoveryFailed { .. } | TransportError::ShutdownTimeout { .. }
+        ) && self.category().is_retryable()
+    }
+
+    /// Minimum delay before retrying, when the peer asked for one
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            TransportError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Map each transport failure onto the matching [`MCPError`] category so that
+/// callers can use `?` instead of stringifying into a generic connection error
+impl From<TransportError> for MCPError {
+    fn from(err: TransportError) -> Self {
+        use ultrafast_mcp_core::error::{
+            AuthenticationError, ProtocolError, RateLimitError,
+            TransportError as CoreTransportError,
+        };
+
+        match err {
+            TransportError::ConnectionError { message }
+            | TransportError::InitializationError { message } => {
+                MCPError::Transport(CoreTransportError::ConnectionFailed(message))
+            }
+            TransportError::ConnectionClosed => {
+                MCPError::Transport(CoreTransportError::ConnectionClosed)
+            }
+            TransportError::ConnectionTimeout => {
+                MCPError::Transport(CoreTransportError::ConnectionTimeout)
+            }
+            TransportError::NetworkError { message, source } => {
+                MCPError::Transport(CoreTransportError::Network { message, source })
+            }
+            TransportError::NotReady { state } => {
+                MCPError::Transport(CoreTransportError::NotReady(state.to_string()))
+            }
+            TransportError::SerializationError { message } => {
+                MCPError::Protocol(ProtocolError::SerializationError(message))
+            }
+            TransportError::ProtocolError { message } => {
+                MCPError::Protocol(ProtocolError::InvalidRequest(message))
+            }
+            TransportError::AuthenticationError { message, source } => {
+                MCPError::Authentication(AuthenticationError::Failed { message, source })
+            }
+            TransportError::InternalError { message } => {
+                MCPError::Protocol(ProtocolError::InternalError(message))
+            }
+            err @ (TransportError::RecoveryFailed { .. }
+            | TransportError::ShutdownTimeout { .. }) => {
+                MCPError::Transport(CoreTransportError::Exhausted(err.to_string()))
+            }
+            TransportError::RateLimited {
+                retry_after, limit, ..
+            } => MCPError::RateLimit(RateLimitError::TooManyRequests {
+                retry_after: retry_after.map_or(0, |delay| delay.as_millis() as u64),
+                limit,
+            }),
+        }
+    }
+}
+
+/// Keep authentication failures raised while talking HTTP in the
+/// authentication category
+#[cfg(feature = "http-client")]
+impl From<ultrafast_mcp_auth::AuthError> for TransportError {
+    fn from(err: ultrafast_mcp_auth::AuthError) -> Self {
+        TransportError::AuthenticationError {
+            message: err.to_string(),
+            source: Some(Box::new(err)),
+        }
+    }
 }
 
 /// Enhanced transport trait with lifecycle management
@@ -180,6 +513,10 @@ pub trait Transport: Send + Sync {
     async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()>;
 
     /// Receive a message from the transport
+    ///
+    /// Implementations should be cancel-safe: dropping the future before it
+    /// completes must not lose a message, so it can be raced against other
+    /// work in `tokio::select!`.
     async fn receive_message(&mut self) -> Result<JsonRpcMessage>;
 
     /// Close the transport connection gracefully
@@ -198,6 +535,11 @@ pub trait Transport: Send + Sync {
         }
     }
 
+    /// Describe the connection, for labelling metrics and logs
+    fn describe(&self) -> TransportDescription {
+        TransportDescription::default()
+    }
+
     /// Check if transport is ready for operations
     fn is_ready(&self) -> bool {
         matches!(self.get_state(), ConnectionState::Connected)
@@ -243,10 +585,19 @@ pub struct RecoveringTransport {
     event_handler: Option<Box<dyn TransportEventHandler>>,
     retry_count: u32,
     last_error: Option<String>,
+    circuit: Option<CircuitBreaker>,
+    metrics: RecoveryMetrics,
+    /// Messages that arrived while waiting for a health probe response
+    backlog: VecDeque<JsonRpcMessage>,
+    probe_sequence: u64,
 }
 
 impl RecoveringTransport {
     pub fn new(transport: Box<dyn Transport>, recovery_config: RecoveryConfig) -> Self {
+        let circuit = recovery_config
+            .circuit_breaker
+            .clone()
+            .map(CircuitBreaker::new);
         Self {
             inner: transport,
             recovery_config,
@@ -254,6 +605,10 @@ impl RecoveringTransport {
             event_handler: None,
             retry_count: 0,
             last_error: None,
+            circuit,
+            metrics: RecoveryMetrics::default(),
+            backlog: VecDeque::new(),
+            probe_sequence: 0,
         }
     }
 
@@ -262,13 +617,108 @@ impl RecoveringTransport {
         self
     }
 
+    /// Current circuit breaker state; always closed without a circuit breaker
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit
+            .as_ref()
+            .map_or(CircuitState::Closed, CircuitBreaker::state)
+    }
+
+    /// Snapshot of the recovery counters
+    pub fn metrics(&self) -> RecoveryMetrics {
+        RecoveryMetrics {
+            circuit_state: self.circuit_state(),
+            ..self.metrics.clone()
+        }
+    }
+
     async fn emit_event(&self, event: TransportEvent) {
         if let Some(handler) = &self.event_handler {
             handler.handle_event(event).await;
         }
     }
 
-    async fn attempt_recovery(&mut self) -> Result<()> {
+    async fn emit_circuit_transition(&mut self, transition: Option<CircuitTransition>) {
+        if let Some((from, to)) = transition {
+            if to == CircuitState::Open {
+                self.metrics.circuit_trips += 1;
+            }
+            self.emit_event(TransportEvent::CircuitStateChanged { from, to })
+                .await;
+        }
+    }
+
+    /// Reject the operation while the circuit is open
+    async fn check_circuit(&mut self) -> Result<()> {
+        let Some(circuit) = self.circuit.as_mut() else {
+            return Ok(());
+        };
+        let (allowed, transition) = circuit.allow();
+        let remaining = circuit.remaining_open().unwrap_or_default();
+        if transition.is_some() {
+            // A half-open trial starts a fresh round of retries
+            self.retry_count = 0;
+        }
+        self.emit_circuit_transition(transition).await;
+
+        if allowed {
+            Ok(())
+        } else {
+            self.metrics.rejected_calls += 1;
+            Err(TransportError::ConnectionError {
+                message: format!(
+                    "Circuit breaker open; next recovery attempt in {}ms",
+                    remaining.as_millis()
+                ),
+            })
+        }
+    }
+
+    /// Receiving has no caller to fail fast to, so wait out the open period
+    async fn wait_for_circuit(&mut self) {
+        if let Some(remaining) = self
+            .circuit
+            .as_ref()
+            .and_then(CircuitBreaker::remaining_open)
+        {
+            tokio::time::sleep(remaining).await;
+        }
+    }
+
+    /// An operation succeeded on a half-open circuit
+    async fn record_operation_success(&mut self) {
+        if let Some(circuit) = self
+            .circuit
+            .as_mut()
+            .filter(|c| c.state() != CircuitState::Closed)
+        {
+            let transition = circuit.record_success();
+            self.emit_circuit_transition(transition).await;
+        }
+    }
+
+    /// Wait before retrying an operation the peer turned away with `error`
+    ///
+    /// A rate limit says nothing about the connection, so it is neither
+    /// reconnected nor counted by the circuit breaker. After `max_retries`
+    /// waits in a row the operation fails with `error`.
+    async fn wait_out_rate_limit(&mut self, error: TransportError, waits: &mut u32) -> Result<()> {
+        if *waits >= self.recovery_config.max_retries {
+            return Err(error);
+        }
+        let delay = error
+            .retry_after()
+            .unwrap_or_else(|| self.recovery_config.retry_delay(*waits));
+        *waits += 1;
+        self.emit_event(TransportEvent::Throttled { delay }).await;
+        tokio::time::sleep(delay).await;
+        Ok(())
+    }
+
+    /// Reconnect after a failure, waiting at least `retry_after` first
+    async fn attempt_recovery(&mut self, retry_after: Option<std::time::Duration>) -> Result<()> {
+        self.check_circuit().await?;
+
         if self.retry_count >= self.recovery_config.max_retries {
             let error_msg = format!(
                 "Max retries ({}) exceeded. Last error: {}",
@@ -276,82 +726,140 @@ impl RecoveringTransport {
                 self.last_error.as_deref().unwrap_or("unknown")
             );
             self.health.state = ConnectionState::Failed(error_msg.clone());
+            self.emit_event(TransportEvent::RecoveryFailed {
+                attempts: self.retry_count,
+            })
+            .await;
             return Err(TransportError::RecoveryFailed {
                 attempts: self.retry_count,
                 message: error_msg,
             });
         }
 
-        self.health.state = ConnectionState::Reconnecting;
-        self.emit_event(TransportEvent::Reconnecting).await;
-
         // Calculate delay with exponential backoff
-        let delay = self.calculate_retry_delay();
+        let attempt = self.retry_count + 1;
+        let delay = self
+            .calculate_retry_delay()
+            .max(retry_after.unwrap_or_default());
+        self.health.state = ConnectionState::Reconnecting;
+        self.emit_event(TransportEvent::Reconnecting { attempt, delay })
+            .await;
         tokio::time::sleep(delay).await;
 
-        // Attempt reconnection
-        match self.inner.reconnect().await {
+        // Attempt reconnection, then confirm the peer answers
+        self.metrics.recovery_attempts += 1;
+        let result = match self.inner.reconnect().await {
+            Ok(()) => self.probe(attempt).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
             Ok(()) => {
                 self.health.state = ConnectionState::Connected;
                 self.retry_count = 0;
                 self.last_error = None;
+                self.metrics.successful_recoveries += 1;
+                let transition = self
+                    .circuit
+                    .as_mut()
+                    .and_then(CircuitBreaker::record_success);
+                self.emit_circuit_transition(transition).await;
                 self.emit_event(TransportEvent::Connected).await;
                 Ok(())
             }
             Err(e) => {
+                self.emit_event(TransportEvent::RecoveryAttemptFailed {
+                    attempt,
+                    category: e.category(),
+                    message: e.to_string(),
+                })
+                .await;
                 self.retry_count += 1;
                 self.last_error = Some(e.to_string());
                 self.health.error_count += 1;
                 self.health.last_error = Some(e.to_string());
+                self.metrics.failed_recoveries += 1;
+                let transition = self
+                    .circuit
+                    .as_mut()
+                    .and_then(CircuitBreaker::record_failure);
+                self.emit_circuit_transition(transition).await;
                 Err(e)
             }
         }
     }
 
-    fn calculate_retry_delay(&self) -> std::time::Duration {
-        let base_delay = self.recovery_config.initial_delay.as_millis() as f64;
-        let multiplier = self
-            .recovery_config
-            .backoff_multiplier
-            .powi(self.retry_count as i32);
-        let mut delay_ms = base_delay * multiplier;
+    /// Send a `ping` and wait for its response when a health probe is
+    /// configured; anything else received meanwhile is kept for
+    /// `receive_message`
+    async fn probe(&mut self, attempt: u32) -> Result<()> {
+        let Some(probe) = self.recovery_config.health_probe.clone() else {
+            return Ok(());
+        };
+
+        self.probe_sequence += 1;
+        self.metrics.probes_sent += 1;
+        let id = RequestId::string(format!("ultrafast-health-probe-{}", self.probe_sequence));
+        let ping = JsonRpcRequest::new("ping".to_string(), None, Some(id.clone()));
+        let started = std::time::Instant::now();
+
+        let inner = &mut self.inner;
+        let backlog = &mut self.backlog;
+        let result = tokio::time::timeout(probe.timeout, async {
+            inner.send_message(JsonRpcMessage::Request(ping)).await?;
+            loop {
+                match inner.receive_message().await? {
+                    JsonRpcMessage::Response(response) if response.id.as_ref() == Some(&id) => {
+                        return Ok(());
+                    }
+                    message => backlog.push_back(message),
+                }
+            }
+        })
+        .await
+        .unwrap_or(Err(TransportError::ConnectionTimeout));
 
-        // Add jitter if enabled
-        if self.recovery_config.enable_jitter {
-            use rand::Rng;
-            let mut rng = rand::rng();
-            let jitter: f64 = rng.random_range(0.8..1.2);
-            delay_ms *= jitter;
+        if result.is_err() {
+            self.metrics.probes_failed += 1;
         }
+        self.emit_event(TransportEvent::ProbeCompleted {
+            attempt,
+            success: result.is_ok(),
+            latency: started.elapsed(),
+        })
+        .await;
+        result
+    }
 
-        // Cap at max delay
-        let max_delay_ms = self.recovery_config.max_delay.as_millis() as f64;
-        delay_ms = delay_ms.min(max_delay_ms);
-
-        std::time::Duration::from_millis(delay_ms as u64)
+    fn calculate_retry_delay(&self) -> std::time::Duration {
+        self.recovery_config.retry_delay(self.retry_count)
     }
 }
 
 #[async_trait]
 impl Transport for RecoveringTransport {
     async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        self.check_circuit().await?;
+        let mut throttled = 0;
         loop {
             match self.inner.send_message(message.clone()).await {
                 Ok(()) => {
                     self.health.messages_sent += 1;
                     self.health.last_activity = Some(std::time::SystemTime::now());
+                    self.record_operation_success().await;
                     self.emit_event(TransportEvent::MessageSent).await;
                     return Ok(());
                 }
+                Err(e @ TransportError::RateLimited { .. }) => {
+                    self.emit_event(TransportEvent::error(&e)).await;
+                    self.wait_out_rate_limit(e, &mut throttled).await?;
+                }
                 Err(e) => {
-                    self.emit_event(TransportEvent::Error(e.to_string())).await;
-
-                    // Try recovery for connection errors
-                    if matches!(
-                        e,
-                        TransportError::ConnectionClosed | TransportError::ConnectionError { .. }
-                    ) {
-                        match self.attempt_recovery().await {
+                    self.emit_event(TransportEvent::error(&e)).await;
+
+                    // Try recovery for transient connection errors
+                    if e.is_retryable() {
+                        match self.attempt_recovery(e.retry_after()).await {
                             Ok(()) => continue, // Retry the send
                             Err(recovery_err) => return Err(recovery_err),
                         }
@@ -364,23 +872,33 @@ impl Transport for RecoveringTransport {
     }
 
     async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+        if let Some(message) = self.backlog.pop_front() {
+            self.health.messages_received += 1;
+            self.emit_event(TransportEvent::MessageReceived).await;
+            return Ok(message);
+        }
+
+        self.wait_for_circuit().await;
+        let mut throttled = 0;
         loop {
             match self.inner.receive_message().await {
                 Ok(message) => {
                     self.health.messages_received += 1;
                     self.health.last_activity = Some(std::time::SystemTime::now());
+                    self.record_operation_success().await;
                     self.emit_event(TransportEvent::MessageReceived).await;
                     return Ok(message);
                 }
+                Err(e @ TransportError::RateLimited { .. }) => {
+                    self.emit_event(TransportEvent::error(&e)).await;
+                    self.wait_out_rate_limit(e, &mut throttled).await?;
+                }
                 Err(e) => {
-                    self.emit_event(TransportEvent::Error(e.to_string())).await;
-
-                    // Try recovery for connection errors
-                    if matches!(
-                        e,
-                        TransportError::ConnectionClosed | TransportError::ConnectionError { .. }
-                    ) {
-                        match self.attempt_recovery().await {
+                    self.emit_event(TransportEvent::error(&e)).await;
+
+                    // Try recovery for transient connection errors
+                    if e.is_retryable() {
+                        match self.attempt_recovery(e.retry_after()).await {
                             Ok(()) => continue, // Retry the receive
                             Err(recovery_err) => return Err(recovery_err),
                         }
@@ -412,6 +930,10 @@ impl Transport for RecoveringTransport {
         self.health.clone()
     }
 
+    fn describe(&self) -> TransportDescription {
+        self.inner.describe()
+    }
+
     async fn shutdown(&mut self, config: ShutdownConfig) -> Result<()> {
         self.inner.shutdown(config).await
     }
@@ -421,13 +943,18 @@ impl Transport for RecoveringTransport {
     }
 
     async fn reconnect(&mut self) -> Result<()> {
-        self.attempt_recovery().await
+        self.attempt_recovery(None).await
     }
 
     async fn reset(&mut self) -> Result<()> {
         self.health = TransportHealth::default();
         self.retry_count = 0;
         self.last_error = None;
+        self.metrics = RecoveryMetrics::default();
+        self.backlog.clear();
+        if let Some(circuit) = self.circuit.as_mut() {
+            circuit.reset();
+        }
         self.inner.reset().await
     }
 }
@@ -439,7 +966,7 @@ pub enum TransportConfig {
     Stdio,
 
     /// Streamable HTTP transport (PRD recommended)
-    #[cfg(feature = "http")]
+    #[cfg(feature = "http-client")]
     Streamable {
         base_url: String,
         auth_token: Option<String>,
@@ -455,7 +982,7 @@ pub async fn create_transport(config: TransportConfig) -> Result<Box<dyn Transpo
             Ok(Box::new(transport))
         }
 
-        #[cfg(feature = "http")]
+        #[cfg(feature = "http-client")]
         TransportConfig::Streamable {
             base_url,
             auth_token,
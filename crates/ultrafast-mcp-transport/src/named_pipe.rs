@@ -0,0 +1,137 @@
+//! Named pipe transport for local connections on Windows
+//!
+//! Windows has no Unix domain sockets, and a server listening on local HTTP
+//! is often blocked by corporate firewall policy. A named pipe
+//! (`\\.\pipe\<name>`) connects two local processes without either.
+//!
+//! Messages are newline-delimited JSON, exactly as on STDIO, through a
+//! [`StreamTransport`].
+//!
+//! `NamedPipeListener` serves any number of clients from one pipe name, a
+//! new pipe instance being created for the next client as each one connects;
+//! `NamedPipeTransport::connect` is the client side. Both exist on Windows
+//! only.
+
+use crate::stream::StreamTransport;
+#[cfg(windows)]
+use crate::{Result, TransportDescription, TransportError, TransportKind};
+
+/// Transport over one named pipe connection
+pub type NamedPipeTransport<S> = StreamTransport<S>;
+
+/// Full path of the pipe called `name`: `\\.\pipe\<name>`, unless `name`
+/// already is a pipe path
+pub fn pipe_path(name: &str) -> String {
+    if name.starts_with(r"\\") {
+        name.to_string()
+    } else {
+        format!(r"\\.\pipe\{name}")
+    }
+}
+
+#[cfg(windows)]
+fn named_pipe<S>(stream: S, pipe: String) -> NamedPipeTransport<S>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + Sync,
+{
+    let description = TransportDescription::new(TransportKind::Custom("named_pipe".to_string()))
+        .with_remote_address(pipe);
+    StreamTransport::new(stream, description)
+}
+
+#[cfg(windows)]
+impl NamedPipeTransport<tokio::net::windows::named_pipe::NamedPipeClient> {
+    /// Connect to the pipe called `name`, waiting up to `timeout` while all
+    /// of its instances are busy serving other clients
+    pub async fn connect(name: &str, timeout: std::time::Duration) -> Result<Self> {
+        use tokio::net::windows::named_pipe::ClientOptions;
+
+        /// `ERROR_PIPE_BUSY`: every instance of the pipe has a client
+        const ERROR_PIPE_BUSY: i32 = 231;
+
+        let pipe = pipe_path(name);
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match ClientOptions::new().open(&pipe) {
+                Ok(client) => return Ok(named_pipe(client, pipe)),
+                Err(e)
+                    if e.raw_os_error() == Some(ERROR_PIPE_BUSY)
+                        && tokio::time::Instant::now() < deadline =>
+                {
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+                Err(e) => {
+                    return Err(TransportError::ConnectionError {
+                        message: format!("Failed to connect to {pipe}: {e}"),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Accepts clients on a named pipe, one transport per client
+#[cfg(windows)]
+pub struct NamedPipeListener {
+    pipe: String,
+    next: tokio::net::windows::named_pipe::NamedPipeServer,
+}
+
+#[cfg(windows)]
+impl NamedPipeListener {
+    /// Create the pipe called `name`
+    ///
+    /// Fails if another process already serves a pipe with that name, so a
+    /// client cannot end up talking to an impostor.
+    pub fn bind(name: &str) -> Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let pipe = pipe_path(name);
+        let next = ServerOptions::new()
+            .first_pipe_instance(true)
+            .create(&pipe)
+            .map_err(|e| TransportError::InitializationError {
+                message: format!("Failed to create pipe {pipe}: {e}"),
+            })?;
+        Ok(Self { pipe, next })
+    }
+
+    /// Path of the pipe clients connect to
+    pub fn pipe(&self) -> &str {
+        &self.pipe
+    }
+
+    /// Wait for the next client
+    pub async fn accept(
+        &mut self,
+    ) -> Result<NamedPipeTransport<tokio::net::windows::named_pipe::NamedPipeServer>> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        self.next
+            .connect()
+            .await
+            .map_err(|e| TransportError::ConnectionError {
+                message: format!("Failed to accept a client on {}: {e}", self.pipe),
+            })?;
+        // The next client needs an instance of its own before this one is
+        // handed out
+        let next = ServerOptions::new().create(&self.pipe).map_err(|e| {
+            TransportError::InitializationError {
+                message: format!("Failed to create pipe {}: {e}", self.pipe),
+            }
+        })?;
+        let connected = std::mem::replace(&mut self.next, next);
+        Ok(named_pipe(connected, self.pipe.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipe_path() {
+        assert_eq!(pipe_path("mcp-server"), r"\\.\pipe\mcp-server");
+        assert_eq!(pipe_path(r"\\.\pipe\mcp-server"), r"\\.\pipe\mcp-server");
+    }
+}
@@ -0,0 +1,675 @@
+//! Noise-encrypted transport for local byte streams
+//!
+//! STDIO pipes and Unix sockets are not encrypted, which matters when the
+//! two ends run as different OS users. [`NoiseTransport`] runs a
+//! `Noise_XX_25519_ChaChaPoly_BLAKE2s` handshake when the connection starts,
+//! authenticating both sides by their static keys, and then encrypts every
+//! JSON-RPC message.
+//!
+//! Static keys are loaded through a [`SecretProvider`] so they can live
+//! outside of configuration files. Keys are hex encoded; the private key is
+//! read from `NOISE_PRIVATE_KEY` and the comma-separated list of peer public
+//! keys to accept from `NOISE_TRUSTED_PUBLIC_KEYS`. A handshake without
+//! trusted keys fails unless [`NoiseConfig::allow_any_peer`] was called.
+//!
+//! On the wire every Noise message is prefixed with its length as a big
+//! endian `u16`. JSON-RPC messages larger than one Noise message are split
+//! into chunks; the first plaintext byte of each chunk says whether more
+//! chunks follow.
+
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use snow::{Builder, HandshakeState, TransportState};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::debug;
+use ultrafast_mcp_core::protocol::JsonRpcMessage;
+
+use crate::{ConnectionState, Result, Transport, TransportError, TransportHealth};
+
+/// Noise protocol used for every connection
+pub const NOISE_PATTERN: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// Secret holding the hex encoded local private key
+pub const NOISE_PRIVATE_KEY_SECRET: &str = "NOISE_PRIVATE_KEY";
+
+/// Secret holding comma-separated hex encoded public keys of trusted peers
+pub const NOISE_TRUSTED_PUBLIC_KEYS_SECRET: &str = "NOISE_TRUSTED_PUBLIC_KEYS";
+
+/// Largest JSON-RPC message accepted by default, in bytes
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+const MAX_NOISE_MESSAGE: usize = 65535;
+const TAG_LEN: usize = 16;
+const MAX_CHUNK: usize = MAX_NOISE_MESSAGE - TAG_LEN - 1;
+const CHUNK_MORE: u8 = 0;
+const CHUNK_FINAL: u8 = 1;
+
+/// Source of key material
+pub trait SecretProvider: Send + Sync {
+    /// Look up a secret by name
+    fn get_secret(&self, name: &str) -> Option<String>;
+}
+
+/// Reads secrets from environment variables, optionally with a prefix
+#[derive(Debug, Clone, Default)]
+pub struct EnvSecretProvider {
+    prefix: String,
+}
+
+impl EnvSecretProvider {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn get_secret(&self, name: &str) -> Option<String> {
+        std::env::var(format!("{}{}", self.prefix, name)).ok()
+    }
+}
+
+/// Serves secrets from memory
+#[derive(Debug, Clone, Default)]
+pub struct StaticSecretProvider {
+    secrets: HashMap<String, String>,
+}
+
+impl StaticSecretProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_secret(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.secrets.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl SecretProvider for StaticSecretProvider {
+    fn get_secret(&self, name: &str) -> Option<String> {
+        self.secrets.get(name).cloned()
+    }
+}
+
+/// Which side starts the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseRole {
+    /// The side that connects, normally the client
+    Initiator,
+    /// The side that accepts, normally the server
+    Responder,
+}
+
+/// A Curve25519 static key pair
+#[derive(Clone)]
+pub struct NoiseKeypair {
+    pub private: Vec<u8>,
+    pub public: Vec<u8>,
+}
+
+impl std::fmt::Debug for NoiseKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NoiseKeypair")
+            .field("public", &encode_hex(&self.public))
+            .finish_non_exhaustive()
+    }
+}
+
+impl NoiseKeypair {
+    /// Generate a new random key pair
+    pub fn generate() -> Result<Self> {
+        let keypair = Builder::new(noise_params()?)
+            .generate_keypair()
+            .map_err(noise_error)?;
+        Ok(Self {
+            private: keypair.private,
+            public: keypair.public,
+        })
+    }
+}
+
+/// Configuration for a [`NoiseTransport`]
+#[derive(Clone)]
+pub struct NoiseConfig {
+    pub role: NoiseRole,
+    pub local_private_key: Vec<u8>,
+    /// Public keys the peer may authenticate with
+    pub trusted_remote_keys: Vec<Vec<u8>>,
+    /// Accept a peer authenticating with any key, see
+    /// [`allow_any_peer`](Self::allow_any_peer)
+    pub allow_any_peer: bool,
+    pub handshake_timeout: Duration,
+    /// Largest message the peer may send, once its chunks are put back
+    /// together
+    pub max_message_size: usize,
+}
+
+impl std::fmt::Debug for NoiseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NoiseConfig")
+            .field("role", &self.role)
+            .field("trusted_remote_keys", &self.trusted_remote_keys.len())
+            .field("allow_any_peer", &self.allow_any_peer)
+            .field("handshake_timeout", &self.handshake_timeout)
+            .field("max_message_size", &self.max_message_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NoiseConfig {
+    pub fn new(role: NoiseRole, local_private_key: Vec<u8>) -> Self {
+        Self {
+            role,
+            local_private_key,
+            trusted_remote_keys: Vec::new(),
+            allow_any_peer: false,
+            handshake_timeout: Duration::from_secs(10),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Load the static keys from a secret provider
+    pub fn from_secret_provider(role: NoiseRole, provider: &dyn SecretProvider) -> Result<Self> {
+        let private_key = provider
+            .get_secret(NOISE_PRIVATE_KEY_SECRET)
+            .ok_or_else(|| TransportError::InitializationError {
+                message: format!("Secret {NOISE_PRIVATE_KEY_SECRET} is not set"),
+            })?;
+        let mut config = Self::new(role, decode_hex(&private_key)?);
+        if let Some(trusted) = provider.get_secret(NOISE_TRUSTED_PUBLIC_KEYS_SECRET) {
+            for key in trusted
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+            {
+                config.trusted_remote_keys.push(decode_hex(key)?);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Only accept a peer authenticating with this public key
+    pub fn with_trusted_remote_key(mut self, public_key: Vec<u8>) -> Self {
+        self.trusted_remote_keys.push(public_key);
+        self
+    }
+
+    /// Accept a peer authenticating with any static key
+    ///
+    /// The connection is still encrypted, but the peer is not authenticated:
+    /// anyone able to connect is accepted. Without this, a configuration
+    /// with no trusted keys fails the handshake.
+    pub fn allow_any_peer(mut self) -> Self {
+        self.allow_any_peer = true;
+        self
+    }
+
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+}
+
+/// Transport encrypting JSON-RPC messages over a byte stream with Noise
+pub struct NoiseTransport<R, W> {
+    reader: R,
+    writer: W,
+    session: TransportState,
+    remote_public_key: Vec<u8>,
+    /// Received bytes not yet decrypted; kept across calls so a cancelled
+    /// `receive_message` does not lose data
+    read_buffer: Vec<u8>,
+    /// Decrypted chunks of a message whose final chunk has not arrived
+    partial_message: Vec<u8>,
+    max_message_size: usize,
+    health: TransportHealth,
+}
+
+impl NoiseTransport<tokio::io::Stdin, tokio::io::Stdout> {
+    /// Encrypt the process's standard input/output
+    pub async fn stdio(config: NoiseConfig) -> Result<Self> {
+        Self::handshake(tokio::io::stdin(), tokio::io::stdout(), config).await
+    }
+}
+
+#[cfg(unix)]
+impl NoiseTransport<tokio::net::unix::OwnedReadHalf, tokio::net::unix::OwnedWriteHalf> {
+    /// Encrypt a Unix domain socket connection
+    pub async fn unix(stream: tokio::net::UnixStream, config: NoiseConfig) -> Result<Self> {
+        let (reader, writer) = stream.into_split();
+        Self::handshake(reader, writer, config).await
+    }
+}
+
+impl<R, W> NoiseTransport<R, W>
+where
+    R: AsyncRead + Unpin + Send + Sync,
+    W: AsyncWrite + Unpin + Send + Sync,
+{
+    /// Run the Noise handshake over `reader`/`writer`
+    ///
+    /// Fails if no key is trusted and any peer was not allowed, if the
+    /// handshake does not finish within the configured timeout, or if the
+    /// peer's static key is not trusted.
+    pub async fn handshake(reader: R, writer: W, config: NoiseConfig) -> Result<Self> {
+        if config.trusted_remote_keys.is_empty() && !config.allow_any_peer {
+            return Err(TransportError::InitializationError {
+                message: "No trusted Noise peer keys; add one or allow any peer".to_string(),
+            });
+        }
+        let builder = Builder::new(noise_params()?).local_private_key(&config.local_private_key);
+        let state = match config.role {
+            NoiseRole::Initiator => builder.build_initiator(),
+            NoiseRole::Responder => builder.build_responder(),
+        }
+        .map_err(noise_error)?;
+
+        let mut transport = PendingHandshake {
+            reader,
+            writer,
+            read_buffer: Vec::new(),
+        };
+        let state = tokio::time::timeout(config.handshake_timeout, transport.run(state))
+            .await
+            .map_err(|_| TransportError::ConnectionTimeout)??;
+
+        let remote_public_key = state
+            .get_remote_static()
+            .map(<[u8]>::to_vec)
+            .unwrap_or_default();
+        if !config.allow_any_peer && !config.trusted_remote_keys.contains(&remote_public_key) {
+            return Err(TransportError::AuthenticationError {
+                message: format!(
+                    "Peer static key {} is not trusted",
+                    encode_hex(&remote_public_key)
+                ),
+                source: None,
+            });
+        }
+        debug!(
+            "Noise handshake complete with peer {}",
+            encode_hex(&remote_public_key)
+        );
+
+        Ok(Self {
+            reader: transport.reader,
+            writer: transport.writer,
+            session: state.into_transport_mode().map_err(noise_error)?,
+            remote_public_key,
+            read_buffer: transport.read_buffer,
+            partial_message: Vec::new(),
+            max_message_size: config.max_message_size,
+            health: TransportHealth {
+                state: ConnectionState::Connected,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Static public key the peer authenticated with
+    pub fn remote_public_key(&self) -> &[u8] {
+        &self.remote_public_key
+    }
+
+    fn decrypt_buffered(&mut self) -> Result<Option<Vec<u8>>> {
+        while let Some(frame) = take_frame(&mut self.read_buffer) {
+            let mut plaintext = vec![0u8; frame.len()];
+            let len = self
+                .session
+                .read_message(&frame, &mut plaintext)
+                .map_err(noise_error)?;
+            let Some((&flag, chunk)) = plaintext[..len].split_first() else {
+                return Err(TransportError::ProtocolError {
+                    message: "Received empty Noise frame".to_string(),
+                });
+            };
+            if self.partial_message.len() + chunk.len() > self.max_message_size {
+                self.partial_message = Vec::new();
+                return Err(TransportError::ProtocolError {
+                    message: format!(
+                        "Noise message exceeds the limit of {} bytes",
+                        self.max_message_size
+                    ),
+                });
+            }
+            self.partial_message.extend_from_slice(chunk);
+            if flag == CHUNK_FINAL {
+                return Ok(Some(std::mem::take(&mut self.partial_message)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl<R, W> Transport for NoiseTransport<R, W>
+where
+    R: AsyncRead + Unpin + Send + Sync,
+    W: AsyncWrite + Unpin + Send + Sync,
+{
+    async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        let json =
+            serde_json::to_vec(&message).map_err(|e| TransportError::SerializationError {
+                message: format!("Failed to serialize message: {e}"),
+            })?;
+
+        let mut chunks = json.chunks(MAX_CHUNK).peekable();
+        let mut plaintext = Vec::with_capacity(MAX_CHUNK + 1);
+        let mut ciphertext = vec![0u8; MAX_NOISE_MESSAGE];
+        while let Some(chunk) = chunks.next() {
+            plaintext.clear();
+            plaintext.push(if chunks.peek().is_some() {
+                CHUNK_MORE
+            } else {
+                CHUNK_FINAL
+            });
+            plaintext.extend_from_slice(chunk);
+            let len = self
+                .session
+                .write_message(&plaintext, &mut ciphertext)
+                .map_err(noise_error)?;
+            write_frame(&mut self.writer, &ciphertext[..len]).await?;
+            self.health.bytes_sent += 2 + len as u64;
+        }
+        self.writer.flush().await.map_err(io_error)?;
+
+        self.health.messages_sent += 1;
+        self.health.last_activity = Some(std::time::SystemTime::now());
+        Ok(())
+    }
+
+    async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+        loop {
+            if let Some(bytes) = self.decrypt_buffered()? {
+                self.health.messages_received += 1;
+                self.health.last_activity = Some(std::time::SystemTime::now());
+                return serde_json::from_slice(&bytes).map_err(|e| {
+                    TransportError::SerializationError {
+                        message: format!("Failed to parse JSON message: {e}"),
+                    }
+                });
+            }
+
+            let read = self
+                .reader
+                .read_buf(&mut self.read_buffer)
+                .await
+                .map_err(io_error)?;
+            if read == 0 {
+                self.health.state = ConnectionState::Disconnected;
+                return Err(TransportError::ConnectionClosed);
+            }
+            self.health.bytes_received += read as u64;
+        }
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.health.state = ConnectionState::Disconnected;
+        self.writer.shutdown().await.map_err(io_error)
+    }
+
+    fn get_state(&self) -> ConnectionState {
+        self.health.state.clone()
+    }
+
+    fn get_health(&self) -> TransportHealth {
+        self.health.clone()
+    }
+
+    fn describe(&self) -> crate::TransportDescription {
+        crate::TransportDescription::new(crate::TransportKind::Custom("noise".to_string()))
+    }
+}
+
+/// Stream halves while the handshake is in progress
+struct PendingHandshake<R, W> {
+    reader: R,
+    writer: W,
+    read_buffer: Vec<u8>,
+}
+
+impl<R, W> PendingHandshake<R, W>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    async fn run(&mut self, mut state: HandshakeState) -> Result<HandshakeState> {
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE];
+        while !state.is_handshake_finished() {
+            if state.is_my_turn() {
+                let len = state.write_message(&[], &mut buffer).map_err(noise_error)?;
+                write_frame(&mut self.writer, &buffer[..len]).await?;
+                self.writer.flush().await.map_err(io_error)?;
+            } else {
+                let frame = self.read_frame().await?;
+                state
+                    .read_message(&frame, &mut buffer)
+                    .map_err(noise_error)?;
+            }
+        }
+        Ok(state)
+    }
+
+    async fn read_frame(&mut self) -> Result<Vec<u8>> {
+        loop {
+            if let Some(frame) = take_frame(&mut self.read_buffer) {
+                return Ok(frame);
+            }
+            if self
+                .reader
+                .read_buf(&mut self.read_buffer)
+                .await
+                .map_err(io_error)?
+                == 0
+            {
+                return Err(TransportError::ConnectionClosed);
+            }
+        }
+    }
+}
+
+fn noise_params() -> Result<snow::params::NoiseParams> {
+    NOISE_PATTERN.parse().map_err(noise_error)
+}
+
+/// Remove one complete length-prefixed frame from the front of `buffer`
+fn take_frame(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let header: [u8; 2] = buffer.get(..2)?.try_into().ok()?;
+    let len = usize::from(u16::from_be_bytes(header));
+    if buffer.len() < 2 + len {
+        return None;
+    }
+    let frame = buffer[2..2 + len].to_vec();
+    buffer.drain(..2 + len);
+    Some(frame)
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &[u8]) -> Result<()> {
+    let len = u16::try_from(frame.len()).map_err(|_| TransportError::ProtocolError {
+        message: "Noise frame too large".to_string(),
+    })?;
+    writer
+        .write_all(&len.to_be_bytes())
+        .await
+        .map_err(io_error)?;
+    writer.write_all(frame).await.map_err(io_error)
+}
+
+fn noise_error(error: impl std::fmt::Display) -> TransportError {
+    TransportError::ProtocolError {
+        message: format!("Noise error: {error}"),
+    }
+}
+
+fn io_error(error: std::io::Error) -> TransportError {
+    TransportError::NetworkError {
+        message: error.to_string(),
+        source: Some(Box::new(error)),
+    }
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    let value = value.trim();
+    if !value.len().is_multiple_of(2) {
+        return Err(TransportError::InitializationError {
+            message: "Hex encoded key has odd length".to_string(),
+        });
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&value[i..i + 2], 16).map_err(|e| {
+                TransportError::InitializationError {
+                    message: format!("Invalid hex encoded key: {e}"),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Hex encode a key, e.g. to store it with a [`SecretProvider`]
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::protocol::JsonRpcRequest;
+
+    async fn connect(
+        client: NoiseConfig,
+        server: NoiseConfig,
+    ) -> (
+        Result<NoiseTransport<impl AsyncRead, impl AsyncWrite>>,
+        Result<NoiseTransport<impl AsyncRead, impl AsyncWrite>>,
+    ) {
+        let (client_stream, server_stream) = tokio::io::duplex(1024);
+        let (client_reader, client_writer) = tokio::io::split(client_stream);
+        let (server_reader, server_writer) = tokio::io::split(server_stream);
+        tokio::join!(
+            NoiseTransport::handshake(client_reader, client_writer, client),
+            NoiseTransport::handshake(server_reader, server_writer, server),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_round_trip_with_large_message() {
+        let client_keys = NoiseKeypair::generate().unwrap();
+        let server_keys = NoiseKeypair::generate().unwrap();
+        let secrets = StaticSecretProvider::new()
+            .with_secret(NOISE_PRIVATE_KEY_SECRET, encode_hex(&server_keys.private))
+            .with_secret(
+                NOISE_TRUSTED_PUBLIC_KEYS_SECRET,
+                encode_hex(&client_keys.public),
+            );
+
+        let (client, server) = connect(
+            NoiseConfig::new(NoiseRole::Initiator, client_keys.private)
+                .with_trusted_remote_key(server_keys.public.clone()),
+            NoiseConfig::from_secret_provider(NoiseRole::Responder, &secrets).unwrap(),
+        )
+        .await;
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+        assert_eq!(server.remote_public_key(), client_keys.public.as_slice());
+
+        let large = "x".repeat(200_000);
+        let message = JsonRpcMessage::Notification(JsonRpcRequest::notification(
+            "test/large".to_string(),
+            Some(serde_json::json!({ "data": large })),
+        ));
+        let sender = tokio::spawn(async move {
+            client.send_message(message).await.unwrap();
+            client
+        });
+        let (JsonRpcMessage::Request(received) | JsonRpcMessage::Notification(received)) =
+            server.receive_message().await.unwrap()
+        else {
+            panic!("expected a notification");
+        };
+        sender.await.unwrap();
+        assert_eq!(
+            received.params.unwrap()["data"].as_str().unwrap().len(),
+            200_000
+        );
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_peer_is_rejected() {
+        let client_keys = NoiseKeypair::generate().unwrap();
+        let server_keys = NoiseKeypair::generate().unwrap();
+        let other_keys = NoiseKeypair::generate().unwrap();
+
+        let (_, server) = connect(
+            NoiseConfig::new(NoiseRole::Initiator, client_keys.private).allow_any_peer(),
+            NoiseConfig::new(NoiseRole::Responder, server_keys.private)
+                .with_trusted_remote_key(other_keys.public),
+        )
+        .await;
+        assert!(matches!(
+            server,
+            Err(TransportError::AuthenticationError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_peers_must_be_trusted_or_allowed() {
+        let client_keys = NoiseKeypair::generate().unwrap();
+        let server_keys = NoiseKeypair::generate().unwrap();
+
+        let (client, server) = connect(
+            NoiseConfig::new(NoiseRole::Initiator, client_keys.private.clone()).allow_any_peer(),
+            NoiseConfig::new(NoiseRole::Responder, server_keys.private.clone()),
+        )
+        .await;
+        assert!(matches!(
+            server,
+            Err(TransportError::InitializationError { .. })
+        ));
+        assert!(client.is_err());
+
+        let (client, server) = connect(
+            NoiseConfig::new(NoiseRole::Initiator, client_keys.private).allow_any_peer(),
+            NoiseConfig::new(NoiseRole::Responder, server_keys.private).allow_any_peer(),
+        )
+        .await;
+        assert!(client.is_ok());
+        assert_eq!(
+            server.unwrap().remote_public_key(),
+            client_keys.public.as_slice()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_message_is_refused() {
+        let client_keys = NoiseKeypair::generate().unwrap();
+        let server_keys = NoiseKeypair::generate().unwrap();
+
+        let (client, server) = connect(
+            NoiseConfig::new(NoiseRole::Initiator, client_keys.private).allow_any_peer(),
+            NoiseConfig::new(NoiseRole::Responder, server_keys.private)
+                .allow_any_peer()
+                .with_max_message_size(100_000),
+        )
+        .await;
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+
+        let message = JsonRpcMessage::Notification(JsonRpcRequest::notification(
+            "test/large".to_string(),
+            Some(serde_json::json!({ "data": "x".repeat(200_000) })),
+        ));
+        tokio::spawn(async move { client.send_message(message).await });
+        assert!(matches!(
+            server.receive_message().await,
+            Err(TransportError::ProtocolError { .. })
+        ));
+    }
+}
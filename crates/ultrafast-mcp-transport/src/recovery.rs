@@ -0,0 +1,301 @@
+//! Health probing and circuit breaking for [`RecoveringTransport`](crate::RecoveringTransport)
+//!
+//! The circuit breaker stops a transport from hammering a peer that keeps
+//! failing: after `failure_threshold` consecutive failed recoveries the
+//! circuit opens and calls fail fast for `open_duration`. The next call after
+//! that runs a single half-open trial recovery which either closes the circuit
+//! again or re-opens it.
+//!
+//! With the `monitoring` feature, [`RecoveryMetricsRecorder`] turns the
+//! recovery events of a transport into metrics of a
+//! [`MetricsCollector`](ultrafast_mcp_monitoring::MetricsCollector).
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// State of the recovery circuit breaker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitState {
+    /// Operations and recovery attempts proceed normally
+    #[default]
+    Closed,
+    /// Too many consecutive failures; operations fail fast
+    Open,
+    /// Cool-down elapsed; the next recovery attempt decides the state
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// Configuration for the recovery circuit breaker
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed recoveries that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a half-open trial
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Configuration for the active health probe run after each reconnect
+///
+/// The probe sends a JSON-RPC `ping` and waits for the matching response, so
+/// a reconnect only counts as successful once the peer actually answers.
+#[derive(Debug, Clone)]
+pub struct HealthProbeConfig {
+    /// How long to wait for the ping response
+    pub timeout: Duration,
+}
+
+impl Default for HealthProbeConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Counters describing recovery behavior
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryMetrics {
+    pub circuit_state: CircuitState,
+    pub recovery_attempts: u64,
+    pub successful_recoveries: u64,
+    pub failed_recoveries: u64,
+    pub probes_sent: u64,
+    pub probes_failed: u64,
+    /// Number of times the circuit transitioned to open
+    pub circuit_trips: u64,
+    /// Operations rejected without touching the transport while open
+    pub rejected_calls: u64,
+}
+
+/// Circuit breaker state machine; transitions are reported to the caller
+/// so they can be surfaced as events
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// A circuit state change, `(from, to)`
+pub(crate) type CircuitTransition = (CircuitState, CircuitState);
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Time left before an open circuit allows a half-open trial
+    pub fn remaining_open(&self) -> Option<Duration> {
+        match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => Some(
+                self.config
+                    .open_duration
+                    .saturating_sub(opened_at.elapsed()),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Whether an operation may proceed; moves an open circuit whose
+    /// cool-down has elapsed to half-open
+    pub fn allow(&mut self) -> (bool, Option<CircuitTransition>) {
+        match self.state {
+            CircuitState::Closed | CircuitState::HalfOpen => (true, None),
+            CircuitState::Open => {
+                if self.remaining_open().is_some_and(|d| d.is_zero()) {
+                    (true, self.transition(CircuitState::HalfOpen))
+                } else {
+                    (false, None)
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&mut self) -> Option<CircuitTransition> {
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.transition(CircuitState::Closed)
+    }
+
+    pub fn record_failure(&mut self) -> Option<CircuitTransition> {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let trip = match self.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => self.consecutive_failures >= self.config.failure_threshold,
+            CircuitState::Open => false,
+        };
+        if trip {
+            self.opened_at = Some(Instant::now());
+            self.transition(CircuitState::Open)
+        } else {
+            None
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+    }
+
+    fn transition(&mut self, to: CircuitState) -> Option<CircuitTransition> {
+        let from = std::mem::replace(&mut self.state, to);
+        (from != to).then_some((from, to))
+    }
+}
+
+/// Records the recovery events of a [`RecoveringTransport`] into a
+/// [`MetricsCollector`], under a label naming the connection
+///
+/// ```rust,ignore
+/// let transport = RecoveringTransport::new(inner, RecoveryConfig::default())
+///     .with_event_handler(Box::new(RecoveryMetricsRecorder::new(collector, "upstream")));
+/// ```
+///
+/// [`RecoveringTransport`]: crate::RecoveringTransport
+/// [`MetricsCollector`]: ultrafast_mcp_monitoring::MetricsCollector
+#[cfg(feature = "monitoring")]
+pub struct RecoveryMetricsRecorder {
+    collector: std::sync::Arc<ultrafast_mcp_monitoring::MetricsCollector>,
+    connection: String,
+    /// Attempt in progress, reported with its outcome
+    attempt: std::sync::atomic::AtomicU32,
+}
+
+#[cfg(feature = "monitoring")]
+impl RecoveryMetricsRecorder {
+    pub fn new(
+        collector: std::sync::Arc<ultrafast_mcp_monitoring::MetricsCollector>,
+        connection: impl Into<String>,
+    ) -> Self {
+        Self {
+            collector,
+            connection: connection.into(),
+            attempt: std::sync::atomic::AtomicU32::new(0),
+        }
+    }
+}
+
+#[cfg(feature = "monitoring")]
+#[async_trait::async_trait]
+impl crate::TransportEventHandler for RecoveryMetricsRecorder {
+    async fn handle_event(&self, event: crate::TransportEvent) {
+        use crate::TransportEvent;
+        use std::sync::atomic::Ordering;
+
+        let connection = self.connection.as_str();
+        match event {
+            TransportEvent::Reconnecting { attempt, delay } => {
+                self.attempt.store(attempt, Ordering::Relaxed);
+                self.collector
+                    .record_recovery_attempt(connection, attempt, delay)
+                    .await;
+            }
+            // Only sent once a recovery attempt succeeds
+            TransportEvent::Connected => {
+                let attempt = self.attempt.swap(0, Ordering::Relaxed);
+                self.collector
+                    .record_recovery_outcome(connection, attempt, true)
+                    .await;
+            }
+            TransportEvent::RecoveryAttemptFailed { attempt, .. } => {
+                self.collector
+                    .record_recovery_outcome(connection, attempt, false)
+                    .await;
+            }
+            TransportEvent::RecoveryFailed { attempts } => {
+                self.collector
+                    .record_recovery_exhausted(connection, attempts)
+                    .await;
+            }
+            TransportEvent::ProbeCompleted {
+                success, latency, ..
+            } => {
+                self.collector
+                    .record_health_probe(connection, success, latency)
+                    .await;
+            }
+            TransportEvent::CircuitStateChanged { to, .. } => {
+                self.collector
+                    .record_circuit_state(connection, &to.to_string())
+                    .await;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(threshold: u32, open_duration: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: threshold,
+            open_duration,
+        })
+    }
+
+    #[test]
+    fn test_circuit_opens_after_threshold() {
+        let mut breaker = breaker(2, Duration::from_secs(60));
+        assert_eq!(breaker.record_failure(), None);
+        assert_eq!(
+            breaker.record_failure(),
+            Some((CircuitState::Closed, CircuitState::Open))
+        );
+        assert!(!breaker.allow().0);
+        assert!(breaker.remaining_open().is_some());
+    }
+
+    #[test]
+    fn test_half_open_trial_closes_or_reopens() {
+        let mut breaker = breaker(1, Duration::ZERO);
+        breaker.record_failure();
+        assert_eq!(
+            breaker.allow(),
+            (true, Some((CircuitState::Open, CircuitState::HalfOpen)))
+        );
+        assert_eq!(
+            breaker.record_failure(),
+            Some((CircuitState::HalfOpen, CircuitState::Open))
+        );
+
+        breaker.allow();
+        assert_eq!(
+            breaker.record_success(),
+            Some((CircuitState::HalfOpen, CircuitState::Closed))
+        );
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
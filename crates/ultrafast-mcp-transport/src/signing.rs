@@ -0,0 +1,509 @@
+//! Ed25519 message signing
+//!
+//! [`MessageSigner`] attaches a detached Ed25519 signature to every outgoing
+//! JSON-RPC message and checks the signature of every incoming one, so a
+//! receiver can tell which key sent a message and that no proxy in between
+//! modified it. The signature covers a canonical form of the message (object
+//! keys sorted, no whitespace) without the signature member itself, and is
+//! carried in a top-level `signature` member:
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "method": "ping", "id": 1,
+//!  "signature": {"keyId": "server-1", "value": "<base64>"}}
+//! ```
+//!
+//! Keys are loaded from a [`SigningConfig`], which can be part of a
+//! configuration file; the private key can be left out of the file and read
+//! from an environment variable instead, see
+//! [`private_key_env`](SigningConfig::private_key_env). [`SigningTransport`]
+//! signs and verifies the messages of any transport, STDIO included, and
+//! with an HTTP feature enabled [`SigningMiddleware`] plugs the signer into a
+//! `MiddlewareTransport`.
+//!
+//! # Limitations
+//!
+//! - Errors the HTTP transport answers by itself, such as for an unknown
+//!   session, and notifications sent through its `SessionManager` are not
+//!   signed, since no [`MessageSigner`] sees them.
+//! - A signature carries no nonce or timestamp, so it proves who sent a
+//!   message, not when. Anyone who observed a signed message can send it
+//!   again and it still verifies. Do not rely on signing alone to keep
+//!   requests with side effects from being replayed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ultrafast_mcp_core::protocol::JsonRpcMessage;
+
+use crate::{
+    ConnectionState, Result, ShutdownConfig, Transport, TransportDescription, TransportError,
+    TransportHealth,
+};
+
+/// Top-level JSON-RPC member carrying the signature
+pub const SIGNATURE_FIELD: &str = "signature";
+
+/// Key configuration for message signing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningConfig {
+    /// Identifier sent with each signature so receivers can pick the key
+    pub key_id: String,
+    /// Base64 encoded 32-byte Ed25519 secret key; outgoing messages are left
+    /// unsigned when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// Environment variable holding the private key, read when
+    /// `private_key` is not set, so the key need not be written into a
+    /// configuration file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_env: Option<String>,
+    /// Base64 encoded public keys of trusted senders, by key id
+    #[serde(default)]
+    pub trusted_keys: HashMap<String, String>,
+    /// Reject incoming messages that carry no signature
+    #[serde(default = "default_require_signatures")]
+    pub require_signatures: bool,
+}
+
+fn default_require_signatures() -> bool {
+    true
+}
+
+impl SigningConfig {
+    pub fn new(key_id: impl Into<String>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            require_signatures: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_private_key(mut self, private_key: impl Into<String>) -> Self {
+        self.private_key = Some(private_key.into());
+        self
+    }
+
+    pub fn with_private_key_env(mut self, variable: impl Into<String>) -> Self {
+        self.private_key_env = Some(variable.into());
+        self
+    }
+
+    pub fn with_trusted_key(
+        mut self,
+        key_id: impl Into<String>,
+        public_key: impl Into<String>,
+    ) -> Self {
+        self.trusted_keys.insert(key_id.into(), public_key.into());
+        self
+    }
+
+    pub fn with_require_signatures(mut self, require: bool) -> Self {
+        self.require_signatures = require;
+        self
+    }
+}
+
+/// A freshly generated Ed25519 key pair, base64 encoded
+#[derive(Clone)]
+pub struct SigningKeypair {
+    pub private_key: String,
+    pub public_key: String,
+}
+
+impl std::fmt::Debug for SigningKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKeypair")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigningKeypair {
+    pub fn generate() -> Self {
+        let signing_key = SigningKey::from_bytes(&rand::random::<[u8; 32]>());
+        Self {
+            private_key: STANDARD.encode(signing_key.to_bytes()),
+            public_key: STANDARD.encode(signing_key.verifying_key().to_bytes()),
+        }
+    }
+}
+
+/// Signs outgoing and verifies incoming JSON-RPC messages
+pub struct MessageSigner {
+    key_id: String,
+    signing_key: Option<SigningKey>,
+    trusted_keys: HashMap<String, VerifyingKey>,
+    require_signatures: bool,
+}
+
+impl std::fmt::Debug for MessageSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageSigner")
+            .field("key_id", &self.key_id)
+            .field("can_sign", &self.signing_key.is_some())
+            .field("trusted_keys", &self.trusted_keys.keys())
+            .field("require_signatures", &self.require_signatures)
+            .finish()
+    }
+}
+
+impl MessageSigner {
+    pub fn from_config(config: &SigningConfig) -> Result<Self> {
+        let private_key =
+            match (&config.private_key, &config.private_key_env) {
+                (Some(key), _) => Some(key.clone()),
+                (None, Some(variable)) => Some(std::env::var(variable).map_err(|_| {
+                    TransportError::InitializationError {
+                        message: format!("Environment variable {variable} is not set"),
+                    }
+                })?),
+                (None, None) => None,
+            };
+        let signing_key = private_key
+            .as_deref()
+            .map(|key| decode_key(key, "private key"))
+            .transpose()?
+            .map(|bytes| SigningKey::from_bytes(&bytes));
+        let trusted_keys = config
+            .trusted_keys
+            .iter()
+            .map(|(key_id, key)| {
+                let bytes = decode_key(key, "public key")?;
+                let key = VerifyingKey::from_bytes(&bytes).map_err(|e| {
+                    TransportError::InitializationError {
+                        message: format!("Invalid public key for '{key_id}': {e}"),
+                    }
+                })?;
+                Ok((key_id.clone(), key))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            key_id: config.key_id.clone(),
+            signing_key,
+            trusted_keys,
+            require_signatures: config.require_signatures,
+        })
+    }
+
+    /// Attach a signature to `message`, replacing any existing one
+    ///
+    /// Does nothing when no private key is configured.
+    pub fn sign(&self, message: &mut JsonRpcMessage) -> Result<()> {
+        let Some(signing_key) = &self.signing_key else {
+            return Ok(());
+        };
+        meta_mut(message).remove(SIGNATURE_FIELD);
+        let signature = signing_key.sign(canonical_bytes(message)?.as_slice());
+        meta_mut(message).insert(
+            SIGNATURE_FIELD.to_string(),
+            serde_json::json!({
+                "keyId": self.key_id,
+                "value": STANDARD.encode(signature.to_bytes()),
+            }),
+        );
+        Ok(())
+    }
+
+    /// Check and strip the signature of `message`
+    ///
+    /// Returns the id of the key that signed it, or `None` for an unsigned
+    /// message when signatures are not required.
+    pub fn verify(&self, message: &mut JsonRpcMessage) -> Result<Option<String>> {
+        let Some(signature) = meta_mut(message).remove(SIGNATURE_FIELD) else {
+            if self.require_signatures {
+                return Err(auth_error("Message is not signed".to_string()));
+            }
+            return Ok(None);
+        };
+        let key_id = signature
+            .get("keyId")
+            .and_then(Value::as_str)
+            .ok_or_else(|| auth_error("Signature has no keyId".to_string()))?;
+        let key = self
+            .trusted_keys
+            .get(key_id)
+            .ok_or_else(|| auth_error(format!("Signing key '{key_id}' is not trusted")))?;
+        let value = signature
+            .get("value")
+            .and_then(Value::as_str)
+            .and_then(|value| STANDARD.decode(value).ok())
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(|| auth_error("Signature value is malformed".to_string()))?;
+        key.verify(canonical_bytes(message)?.as_slice(), &value)
+            .map_err(|_| auth_error(format!("Invalid signature for key '{key_id}'")))?;
+        Ok(Some(key_id.to_string()))
+    }
+}
+
+/// Transport middleware signing outgoing and verifying incoming messages
+#[derive(Debug)]
+pub struct SigningMiddleware {
+    signer: MessageSigner,
+}
+
+impl SigningMiddleware {
+    pub fn new(config: &SigningConfig) -> Result<Self> {
+        Ok(Self {
+            signer: MessageSigner::from_config(config)?,
+        })
+    }
+
+    pub fn signer(&self) -> &MessageSigner {
+        &self.signer
+    }
+}
+
+#[cfg(any(feature = "http-client", feature = "http-server"))]
+#[async_trait::async_trait]
+impl crate::streamable_http::TransportMiddleware for SigningMiddleware {
+    async fn process_outgoing(&self, message: &mut JsonRpcMessage) -> Result<()> {
+        self.signer.sign(message)
+    }
+
+    async fn process_incoming(&self, message: &mut JsonRpcMessage) -> Result<()> {
+        if let Some(key_id) = self.signer.verify(message)? {
+            tracing::trace!("Verified message signed by '{}'", key_id);
+        }
+        Ok(())
+    }
+}
+
+/// Transport signing the messages it sends and verifying the ones it
+/// receives
+///
+/// A received message that fails verification is returned as an
+/// authentication error.
+pub struct SigningTransport {
+    inner: Box<dyn Transport>,
+    signer: Arc<MessageSigner>,
+}
+
+impl SigningTransport {
+    pub fn new(transport: Box<dyn Transport>, signer: Arc<MessageSigner>) -> Self {
+        Self {
+            inner: transport,
+            signer,
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for SigningTransport {
+    async fn send_message(&mut self, mut message: JsonRpcMessage) -> Result<()> {
+        self.signer.sign(&mut message)?;
+        self.inner.send_message(message).await
+    }
+
+    async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+        let mut message = self.inner.receive_message().await?;
+        if let Some(key_id) = self.signer.verify(&mut message)? {
+            tracing::trace!("Verified message signed by '{}'", key_id);
+        }
+        Ok(message)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+
+    fn get_state(&self) -> ConnectionState {
+        self.inner.get_state()
+    }
+
+    fn get_health(&self) -> TransportHealth {
+        self.inner.get_health()
+    }
+
+    fn describe(&self) -> TransportDescription {
+        self.inner.describe()
+    }
+
+    async fn shutdown(&mut self, config: ShutdownConfig) -> Result<()> {
+        self.inner.shutdown(config).await
+    }
+
+    async fn reconnect(&mut self) -> Result<()> {
+        self.inner.reconnect().await
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        self.inner.reset().await
+    }
+}
+
+fn meta_mut(message: &mut JsonRpcMessage) -> &mut HashMap<String, Value> {
+    match message {
+        JsonRpcMessage::Request(request) | JsonRpcMessage::Notification(request) => {
+            &mut request.meta
+        }
+        JsonRpcMessage::Response(response) => &mut response.meta,
+    }
+}
+
+fn canonical_bytes(message: &JsonRpcMessage) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(message).map_err(|e| TransportError::SerializationError {
+        message: e.to_string(),
+    })?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    Ok(out.into_bytes())
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn decode_key(key: &str, what: &str) -> Result<[u8; 32]> {
+    STANDARD
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| TransportError::InitializationError {
+            message: format!("Signing {what} must be 32 base64 encoded bytes"),
+        })
+}
+
+fn auth_error(message: String) -> TransportError {
+    TransportError::AuthenticationError {
+        message,
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::protocol::{JsonRpcRequest, RequestId};
+
+    fn signer_pair() -> (MessageSigner, MessageSigner) {
+        let keypair = SigningKeypair::generate();
+        let sender = MessageSigner::from_config(
+            &SigningConfig::new("server").with_private_key(keypair.private_key),
+        )
+        .unwrap();
+        let receiver = MessageSigner::from_config(
+            &SigningConfig::new("client").with_trusted_key("server", keypair.public_key),
+        )
+        .unwrap();
+        (sender, receiver)
+    }
+
+    fn request() -> JsonRpcMessage {
+        JsonRpcMessage::Request(JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(serde_json::json!({"name": "echo", "arguments": {"b": 2, "a": 1}})),
+            Some(RequestId::number(1)),
+        ))
+    }
+
+    #[test]
+    fn test_signed_message_verifies_and_tampering_is_detected() {
+        let (sender, receiver) = signer_pair();
+
+        let mut message = request();
+        sender.sign(&mut message).unwrap();
+        // Round trip through JSON, as a proxy would
+        let wire = serde_json::to_string(&message).unwrap();
+        let mut received: JsonRpcMessage = serde_json::from_str(&wire).unwrap();
+        assert_eq!(
+            receiver.verify(&mut received).unwrap().as_deref(),
+            Some("server")
+        );
+        assert!(
+            !serde_json::to_string(&received)
+                .unwrap()
+                .contains(SIGNATURE_FIELD)
+        );
+
+        let mut tampered: JsonRpcMessage =
+            serde_json::from_str(&wire.replace("echo", "exec")).unwrap();
+        assert!(matches!(
+            receiver.verify(&mut tampered),
+            Err(TransportError::AuthenticationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unsigned_and_untrusted_messages_are_rejected() {
+        let (_, receiver) = signer_pair();
+        assert!(receiver.verify(&mut request()).is_err());
+
+        let stranger = MessageSigner::from_config(
+            &SigningConfig::new("server").with_private_key(SigningKeypair::generate().private_key),
+        )
+        .unwrap();
+        let mut message = request();
+        stranger.sign(&mut message).unwrap();
+        assert!(receiver.verify(&mut message).is_err());
+
+        let lenient = MessageSigner::from_config(
+            &SigningConfig::new("client").with_require_signatures(false),
+        )
+        .unwrap();
+        assert_eq!(lenient.verify(&mut request()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_private_key_env_must_be_set() {
+        let config = SigningConfig::new("server")
+            .with_private_key_env("ULTRAFAST_MCP_TEST_UNSET_SIGNING_KEY");
+        assert!(matches!(
+            MessageSigner::from_config(&config),
+            Err(TransportError::InitializationError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_signing_transport_signs_and_verifies() {
+        let (sender, receiver) = signer_pair();
+        let (a, b) = crate::duplex_pair();
+        let mut sending = SigningTransport::new(Box::new(a), Arc::new(sender));
+        let mut receiving = SigningTransport::new(Box::new(b), Arc::new(receiver));
+
+        sending.send_message(request()).await.unwrap();
+        let received = receiving.receive_message().await.unwrap();
+        assert!(
+            !serde_json::to_string(&received)
+                .unwrap()
+                .contains(SIGNATURE_FIELD)
+        );
+
+        // The receiver has no key of its own, so its messages are unsigned
+        receiving.send_message(request()).await.unwrap();
+        assert!(matches!(
+            sending.receive_message().await,
+            Err(TransportError::AuthenticationError { .. })
+        ));
+    }
+}
@@ -2,8 +2,15 @@
 //!
 //! This module provides a transport that communicates over standard input/output,
 //! which is the most common transport for MCP servers.
-
-use crate::{ConnectionState, Result, Transport, TransportError, TransportHealth};
+//!
+//! Lines may end in `\n` or the `\r\n` Windows consoles write. Blank lines
+//! are skipped, and so is a UTF-8 byte order mark at the start of a line,
+//! which some Windows tools (PowerShell among them) put before their output.
+
+use crate::{
+    ConnectionState, Result, Transport, TransportDescription, TransportError, TransportHealth,
+    TransportKind,
+};
 use async_trait::async_trait;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
 use tracing::{debug, trace};
@@ -15,6 +22,9 @@ pub struct StdioTransport {
     stdout: BufWriter<tokio::io::Stdout>,
     health: TransportHealth,
     connected_at: Option<std::time::SystemTime>,
+    /// Bytes of the line being read; kept across calls so a cancelled
+    /// `receive_message` does not lose a partially read message
+    read_buffer: Vec<u8>,
 }
 
 impl StdioTransport {
@@ -35,6 +45,7 @@ impl StdioTransport {
             stdout: BufWriter::new(stdout),
             health,
             connected_at,
+            read_buffer: Vec::new(),
         })
     }
 
@@ -45,6 +56,34 @@ impl StdioTransport {
     }
 }
 
+/// Bytes of a received line without the line ending and a leading UTF-8
+/// byte order mark
+pub(crate) fn message_text(line: &[u8]) -> &[u8] {
+    let line = line.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(line);
+    line.trim_ascii()
+}
+
+/// Parse a line of newline-delimited JSON
+pub(crate) fn decode_line(line: &[u8]) -> Result<JsonRpcMessage> {
+    let text = std::str::from_utf8(message_text(line)).map_err(|e| {
+        TransportError::SerializationError {
+            message: if cfg!(windows) {
+                format!(
+                    "Received message that is not valid UTF-8 (is the console code page 65001?): {e}"
+                )
+            } else {
+                format!("Received message that is not valid UTF-8: {e}")
+            },
+        }
+    })?;
+
+    trace!("Received message: {}", text);
+
+    serde_json::from_str(text).map_err(|e| TransportError::SerializationError {
+        message: format!("Failed to parse JSON message: {e}"),
+    })
+}
+
 #[async_trait]
 impl Transport for StdioTransport {
     async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()> {
@@ -75,6 +114,7 @@ impl Transport for StdioTransport {
                 self.health.state = ConnectionState::Failed(format!("Write failed: {e}"));
                 TransportError::NetworkError {
                     message: format!("Failed to write message: {e}"),
+                    source: Some(Box::new(e)),
                 }
             })?;
 
@@ -85,6 +125,7 @@ impl Transport for StdioTransport {
             self.health.state = ConnectionState::Failed(format!("Write newline failed: {e}"));
             TransportError::NetworkError {
                 message: format!("Failed to write newline: {e}"),
+                source: Some(Box::new(e)),
             }
         })?;
 
@@ -94,11 +135,13 @@ impl Transport for StdioTransport {
             self.health.state = ConnectionState::Failed(format!("Flush failed: {e}"));
             TransportError::NetworkError {
                 message: format!("Failed to flush stdout: {e}"),
+                source: Some(Box::new(e)),
             }
         })?;
 
         // Update health metrics
         self.health.messages_sent += 1;
+        self.health.bytes_sent += json_str.len() as u64 + 1;
         self.health.last_activity = Some(std::time::SystemTime::now());
         self.update_connection_duration();
 
@@ -113,42 +156,37 @@ impl Transport for StdioTransport {
             });
         }
 
-        // Read a line from stdin (newline-delimited JSON)
-        let mut line = String::new();
-        let bytes_read = self.stdin.read_line(&mut line).await.map_err(|e| {
-            self.health.error_count += 1;
-            self.health.last_error = Some(format!("Read error: {e}"));
-            TransportError::NetworkError {
-                message: format!("Failed to read line from stdin: {e}"),
+        // Read a line from stdin (newline-delimited JSON), skipping blank ones
+        let line = loop {
+            let bytes_read = self
+                .stdin
+                .read_until(b'\n', &mut self.read_buffer)
+                .await
+                .map_err(|e| {
+                    self.health.error_count += 1;
+                    self.health.last_error = Some(format!("Read error: {e}"));
+                    TransportError::NetworkError {
+                        message: format!("Failed to read line from stdin: {e}"),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+
+            if bytes_read == 0 && self.read_buffer.is_empty() {
+                // EOF reached
+                self.health.state = ConnectionState::Disconnected;
+                return Err(TransportError::ConnectionClosed);
             }
-        })?;
 
-        if bytes_read == 0 {
-            // EOF reached
-            self.health.state = ConnectionState::Disconnected;
-            return Err(TransportError::ConnectionClosed);
-        }
-
-        // Remove trailing newline
-        let message_str = line.trim_end();
-
-        if message_str.is_empty() {
-            self.health.error_count += 1;
-            self.health.last_error = Some("Empty message received".to_string());
-            return Err(TransportError::SerializationError {
-                message: "Received empty message".to_string(),
-            });
-        }
-
-        trace!("Received message: {}", message_str);
+            let line = std::mem::take(&mut self.read_buffer);
+            self.health.bytes_received += line.len() as u64;
+            if !message_text(&line).is_empty() {
+                break line;
+            }
+        };
 
-        // Parse the JSON message
-        let message: JsonRpcMessage = serde_json::from_str(message_str).map_err(|e| {
+        let message = decode_line(&line).inspect_err(|e| {
             self.health.error_count += 1;
-            self.health.last_error = Some(format!("Parse error: {e}"));
-            TransportError::SerializationError {
-                message: format!("Failed to parse JSON message: {e}"),
-            }
+            self.health.last_error = Some(e.to_string());
         })?;
 
         // Update health metrics
@@ -178,6 +216,10 @@ impl Transport for StdioTransport {
         health
     }
 
+    fn describe(&self) -> TransportDescription {
+        TransportDescription::new(TransportKind::Stdio)
+    }
+
     async fn shutdown(&mut self, _config: crate::ShutdownConfig) -> Result<()> {
         self.health.state = ConnectionState::ShuttingDown;
         debug!("STDIO transport shutting down gracefully");
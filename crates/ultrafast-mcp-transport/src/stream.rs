@@ -0,0 +1,288 @@
+//! Newline-delimited JSON over any byte stream
+//!
+//! [`StreamTransport`] speaks the STDIO wire format, one JSON-RPC message
+//! per line, over a connected stream: a named pipe, the standard
+//! input/output of a child process ([`ChildProcessTransport`]), or an
+//! in-memory duplex ([`duplex_pair`]). Received lines are read as by
+//! [`StdioTransport`](crate::stdio::StdioTransport), tolerating `\r\n`, blank
+//! lines and a UTF-8 byte order mark.
+
+use async_trait::async_trait;
+use tokio::io::{
+    AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream, Join,
+};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tracing::debug;
+use ultrafast_mcp_core::protocol::JsonRpcMessage;
+
+use crate::stdio::{decode_line, message_text};
+use crate::{
+    ConnectionState, Result, Transport, TransportDescription, TransportError, TransportHealth,
+    TransportKind,
+};
+
+/// Transport over the standard input/output of a child process
+pub type ChildProcessTransport = StreamTransport<Join<ChildStdout, ChildStdin>>;
+
+/// One end of an in-process connection made by [`duplex_pair`]
+pub type DuplexTransport = StreamTransport<DuplexStream>;
+
+/// Bytes either end of a [`duplex_pair`] may write ahead of the other's reads
+const DUPLEX_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Two transports connected to each other in memory
+///
+/// Messages are framed as on STDIO, so a client and a server in the same
+/// process talk exactly as they would across processes, without spawning a
+/// child or opening a port.
+pub fn duplex_pair() -> (DuplexTransport, DuplexTransport) {
+    let (a, b) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+    let description = || TransportDescription::new(TransportKind::Custom("duplex".to_string()));
+    (
+        StreamTransport::new(a, description()),
+        StreamTransport::new(b, description()),
+    )
+}
+
+/// Transport over one connected byte stream
+pub struct StreamTransport<S> {
+    stream: BufReader<S>,
+    description: TransportDescription,
+    health: TransportHealth,
+    connected_at: std::time::SystemTime,
+    /// Bytes of the line being read; kept across calls so a cancelled
+    /// `receive_message` does not lose a partially read message
+    read_buffer: Vec<u8>,
+}
+
+impl StreamTransport<Join<ChildStdout, ChildStdin>> {
+    /// Talk to `child` over its standard input/output
+    ///
+    /// The child must have been spawned with both piped; they are taken out
+    /// of `child`, which is left to the caller to wait on.
+    pub fn child(child: &mut Child) -> Result<Self> {
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(TransportError::InitializationError {
+                message: "Child process was not spawned with piped stdin and stdout".to_string(),
+            });
+        };
+        let mut description =
+            TransportDescription::new(TransportKind::Custom("child_process".to_string()));
+        if let Some(pid) = child.id() {
+            description = description.with_remote_address(format!("pid {pid}"));
+        }
+        Ok(Self::new(tokio::io::join(stdout, stdin), description))
+    }
+}
+
+impl<S> StreamTransport<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + Sync,
+{
+    /// Speak MCP over an already connected `stream`, described as
+    /// `description` in metrics and logs
+    pub fn new(stream: S, description: TransportDescription) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+            description,
+            health: TransportHealth {
+                state: ConnectionState::Connected,
+                ..Default::default()
+            },
+            connected_at: std::time::SystemTime::now(),
+            read_buffer: Vec::new(),
+        }
+    }
+
+    /// The other end, for error messages
+    fn peer(&self) -> String {
+        self.description
+            .remote_address
+            .clone()
+            .unwrap_or_else(|| self.description.kind.to_string())
+    }
+
+    fn ensure_connected(&self) -> Result<()> {
+        if matches!(self.health.state, ConnectionState::Connected) {
+            Ok(())
+        } else {
+            Err(TransportError::NotReady {
+                state: self.health.state.clone(),
+            })
+        }
+    }
+
+    fn io_error(&mut self, action: &str, e: std::io::Error) -> TransportError {
+        self.health.error_count += 1;
+        self.health.last_error = Some(format!("{action} error: {e}"));
+        self.health.state = ConnectionState::Failed(format!("{action} failed: {e}"));
+        TransportError::NetworkError {
+            message: format!("Failed to {} {}: {e}", action.to_lowercase(), self.peer()),
+            source: Some(Box::new(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> Transport for StreamTransport<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + Sync,
+{
+    async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        self.ensure_connected()?;
+
+        let mut line =
+            serde_json::to_vec(&message).map_err(|e| TransportError::SerializationError {
+                message: format!("Failed to serialize message: {e}"),
+            })?;
+        line.push(b'\n');
+
+        // One write per message, so a message is never split between writes
+        let written = self.stream.get_mut().write_all(&line).await;
+        if let Err(e) = written {
+            return Err(self.io_error("Write", e));
+        }
+        if let Err(e) = self.stream.get_mut().flush().await {
+            return Err(self.io_error("Flush", e));
+        }
+
+        self.health.messages_sent += 1;
+        self.health.bytes_sent += line.len() as u64;
+        self.health.last_activity = Some(std::time::SystemTime::now());
+        Ok(())
+    }
+
+    async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+        self.ensure_connected()?;
+
+        let line = loop {
+            let read = self.stream.read_until(b'\n', &mut self.read_buffer).await;
+            let bytes_read = match read {
+                Ok(bytes_read) => bytes_read,
+                Err(e) => return Err(self.io_error("Read", e)),
+            };
+            if bytes_read == 0 && self.read_buffer.is_empty() {
+                self.health.state = ConnectionState::Disconnected;
+                return Err(TransportError::ConnectionClosed);
+            }
+
+            let line = std::mem::take(&mut self.read_buffer);
+            self.health.bytes_received += line.len() as u64;
+            if !message_text(&line).is_empty() {
+                break line;
+            }
+        };
+
+        let message = decode_line(&line).inspect_err(|e| {
+            self.health.error_count += 1;
+            self.health.last_error = Some(e.to_string());
+        })?;
+
+        self.health.messages_received += 1;
+        self.health.last_activity = Some(std::time::SystemTime::now());
+        Ok(message)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.health.state = ConnectionState::Disconnected;
+        debug!("Stream transport to {} closed", self.peer());
+        let closed = self.stream.get_mut().shutdown().await;
+        closed.map_err(|e| TransportError::NetworkError {
+            message: format!("Failed to close {}: {e}", self.peer()),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    fn get_state(&self) -> ConnectionState {
+        self.health.state.clone()
+    }
+
+    fn get_health(&self) -> TransportHealth {
+        let mut health = self.health.clone();
+        health.connection_duration = self.connected_at.elapsed().ok();
+        health
+    }
+
+    fn describe(&self) -> TransportDescription {
+        self.description.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::protocol::{JsonRpcRequest, RequestId};
+
+    fn request(id: i64) -> JsonRpcMessage {
+        JsonRpcMessage::Request(JsonRpcRequest::new(
+            "ping".to_string(),
+            None,
+            Some(RequestId::Number(id)),
+        ))
+    }
+
+    fn description() -> TransportDescription {
+        TransportDescription::new(TransportKind::Custom("test".to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_messages_round_trip() {
+        let (a, b) = tokio::io::duplex(1024);
+        let mut client = StreamTransport::new(a, description());
+        let mut server = StreamTransport::new(b, description());
+
+        client.send_message(request(1)).await.unwrap();
+        let JsonRpcMessage::Request(received) = server.receive_message().await.unwrap() else {
+            panic!("expected a request");
+        };
+        assert_eq!(received.id, Some(RequestId::Number(1)));
+        assert_eq!(
+            client.get_health().bytes_sent,
+            server.get_health().bytes_received
+        );
+
+        client.close().await.unwrap();
+        assert!(matches!(
+            server.receive_message().await,
+            Err(TransportError::ConnectionClosed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_duplex_pair_carries_messages_larger_than_its_buffer() {
+        let (mut client, mut server) = duplex_pair();
+        let large = JsonRpcMessage::Request(JsonRpcRequest::new(
+            "tools/call".to_string(),
+            Some(serde_json::json!({ "text": "x".repeat(4 * DUPLEX_BUFFER_SIZE) })),
+            Some(RequestId::Number(1)),
+        ));
+
+        let sent = tokio::spawn(async move { client.send_message(large).await });
+        let JsonRpcMessage::Request(received) = server.receive_message().await.unwrap() else {
+            panic!("expected a request");
+        };
+        sent.await.unwrap().unwrap();
+        assert_eq!(received.method, "tools/call");
+        assert_eq!(server.describe().kind.as_str(), "duplex");
+    }
+
+    #[tokio::test]
+    async fn test_crlf_blank_lines_and_bom_are_tolerated() {
+        let (mut peer, stream) = tokio::io::duplex(1024);
+        let mut transport = StreamTransport::new(stream, description());
+        peer.write_all(b"\xEF\xBB\xBF{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":1}\r\n\r\n")
+            .await
+            .unwrap();
+        peer.write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":2}\r\n")
+            .await
+            .unwrap();
+
+        for id in [1, 2] {
+            let JsonRpcMessage::Request(received) = transport.receive_message().await.unwrap()
+            else {
+                panic!("expected a request");
+            };
+            assert_eq!(received.id, Some(RequestId::Number(id)));
+        }
+    }
+}
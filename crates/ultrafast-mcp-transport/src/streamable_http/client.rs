@@ -2,12 +2,25 @@
 //!
 //! This module implements a MCP-compliant Streamable HTTP transport that follows
 //! the MCP specification for stateless request/response communication.
+//!
+//! With [`StreamableHttpClientConfig::event_stream`] set, the client also
+//! keeps a GET event stream open for server-to-client messages. When that
+//! stream drops it reconnects with backoff, sending the session ID and the
+//! last event ID it saw so the server can replay what was missed.
+//!
+//! Requests go out through an [`HttpConnectionPool`], either the client's own
+//! or one shared with other clients through [`StreamableHttpClient::with_pool`].
+
+use std::sync::{Arc, Mutex};
 
-use crate::{Result, Transport, TransportError};
+use super::pool::{HttpConnectionPool, HttpPoolConfig, host_key};
+use crate::{RecoveryConfig, Result, Transport, TransportError};
 use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
 
 use ultrafast_mcp_core::protocol::JsonRpcMessage;
-use ultrafast_mcp_core::utils::generate_state;
+use ultrafast_mcp_core::utils::{CloseTracker, generate_state};
 
 /// Streamable HTTP client configuration
 #[derive(Debug, Clone)]
@@ -20,6 +33,11 @@ pub struct StreamableHttpClientConfig {
     pub auth_token: Option<String>,
     pub oauth_config: Option<ultrafast_mcp_auth::OAuthConfig>,
     pub auth_method: Option<ultrafast_mcp_auth::AuthMethod>,
+    /// Keep an event stream open for server-to-client messages, reconnecting
+    /// with this backoff when it drops
+    pub event_stream: Option<RecoveryConfig>,
+    /// Connection limits and keep-alive of the client's own pool
+    pub pool: HttpPoolConfig,
 }
 
 impl Default for StreamableHttpClientConfig {
@@ -33,6 +51,8 @@ impl Default for StreamableHttpClientConfig {
             auth_token: None,
             oauth_config: None,
             auth_method: None,
+            event_stream: None,
+            pool: HttpPoolConfig::default(),
         }
     }
 }
@@ -82,11 +102,186 @@ impl StreamableHttpClientConfig {
         self.auth_method = Some(auth_method);
         self
     }
+
+    /// Receive server-to-client messages on an event stream that reconnects
+    /// and resumes after drops
+    ///
+    /// After `recovery.max_retries` failed reconnects in a row, receiving
+    /// fails with a connection error.
+    pub fn with_event_stream(mut self, recovery: RecoveryConfig) -> Self {
+        self.event_stream = Some(recovery);
+        self
+    }
+
+    /// Set the connection limits and keep-alive of the client's own pool
+    pub fn with_pool_config(mut self, pool: HttpPoolConfig) -> Self {
+        self.pool = pool;
+        self
+    }
+}
+
+/// One dispatched event of a `text/event-stream` body
+#[derive(Debug, Clone, PartialEq)]
+struct SseEvent {
+    id: Option<String>,
+    data: String,
+}
+
+/// Incremental parser for `text/event-stream` bodies
+///
+/// Chunks may split lines and UTF-8 sequences anywhere. Comments, such as
+/// keep-alives, and events without data are dropped.
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    id: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                let id = self.id.take();
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        id,
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "data" => self.data.push(value.to_string()),
+                "id" => self.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// What the event stream task needs to open the stream again
+struct EventStreamRequest {
+    client: reqwest::Client,
+    timeout: std::time::Duration,
+    url: String,
+    session_id: String,
+    protocol_version: String,
+    auth_headers: Vec<(String, String)>,
+    last_event_id: Arc<Mutex<Option<String>>>,
+}
+
+impl EventStreamRequest {
+    async fn open(&self) -> Result<reqwest::Response> {
+        let last_event_id = self.last_event_id.lock().unwrap().clone();
+        let mut request_builder = self
+            .client
+            .get(&self.url)
+            .timeout(self.timeout)
+            .header("accept", "text/event-stream")
+            .header("mcp-session-id", &self.session_id)
+            .header("mcp-protocol-version", &self.protocol_version);
+        if let Some(last_event_id) = last_event_id {
+            request_builder = request_builder.header("last-event-id", last_event_id);
+        }
+        for (key, value) in &self.auth_headers {
+            request_builder = request_builder.header(key, value);
+        }
+
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|e| TransportError::NetworkError {
+                message: format!("Failed to open event stream: {e}"),
+                source: Some(Box::new(e)),
+            })?;
+        if !response.status().is_success() {
+            return Err(TransportError::NetworkError {
+                message: format!("Event stream refused: {}", response.status()),
+                source: None,
+            });
+        }
+        Ok(response)
+    }
+
+    /// Forward messages from the event stream to `inbox`, reconnecting when
+    /// the stream ends until `recovery` gives up or the client goes away
+    async fn run(
+        self,
+        recovery: RecoveryConfig,
+        inbox: mpsc::UnboundedSender<Result<JsonRpcMessage>>,
+    ) {
+        let mut attempt = 0;
+        loop {
+            match self.open().await {
+                Ok(mut response) => {
+                    let mut parser = SseParser::default();
+                    loop {
+                        let chunk = match response.chunk().await {
+                            Ok(Some(chunk)) => chunk,
+                            Ok(None) => {
+                                debug!("Event stream closed by server");
+                                break;
+                            }
+                            Err(e) => {
+                                warn!("Event stream dropped: {}", e);
+                                break;
+                            }
+                        };
+                        for event in parser.push(&chunk) {
+                            if let Some(id) = event.id {
+                                *self.last_event_id.lock().unwrap() = Some(id);
+                            }
+                            match serde_json::from_str(&event.data) {
+                                Ok(message) => {
+                                    // Only streams that deliver count as recovered
+                                    attempt = 0;
+                                    if inbox.send(Ok(message)).is_err() {
+                                        return;
+                                    }
+                                }
+                                Err(e) => warn!("Ignoring invalid event stream message: {}", e),
+                            }
+                        }
+                    }
+                }
+                Err(e) => warn!("{}", e),
+            }
+
+            if inbox.is_closed() {
+                return;
+            }
+            if attempt >= recovery.max_retries {
+                let _ = inbox.send(Err(TransportError::ConnectionError {
+                    message: format!("Event stream lost after {attempt} reconnect attempts"),
+                }));
+                return;
+            }
+            tokio::time::sleep(recovery.retry_delay(attempt)).await;
+            attempt += 1;
+        }
+    }
 }
 
 /// Streamable HTTP client - MCP-compliant request/response implementation
 pub struct StreamableHttpClient {
     client: reqwest::Client,
+    pool: HttpConnectionPool,
+    // `host:port` of `base_url`, for the pool's per-host limit
+    host: String,
     config: StreamableHttpClientConfig,
     session_id: Option<String>,
     pending_response: Option<JsonRpcMessage>,
@@ -94,16 +289,33 @@ pub struct StreamableHttpClient {
     access_token: Option<String>,
     token_expiry: Option<std::time::SystemTime>,
     auth_middleware: Option<ultrafast_mcp_auth::ClientAuthMiddleware>,
+    // Messages from the event stream, see `StreamableHttpClientConfig::event_stream`
+    inbox: Option<mpsc::UnboundedReceiver<Result<JsonRpcMessage>>>,
+    event_stream_task: Option<tokio::task::JoinHandle<()>>,
+    last_event_id: Arc<Mutex<Option<String>>>,
+    // Open from `connect` until `close`, which ends the server-side session
+    close_tracker: CloseTracker,
+}
+
+impl Drop for StreamableHttpClient {
+    fn drop(&mut self) {
+        self.stop_event_stream();
+    }
 }
 
 impl StreamableHttpClient {
     pub fn new(config: StreamableHttpClientConfig) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(config.timeout)
-            .build()
-            .map_err(|e| TransportError::InitializationError {
-                message: format!("Failed to create HTTP client: {e}"),
-            })?;
+        let pool = HttpConnectionPool::new(config.pool.clone())?;
+        Self::with_pool(config, pool)
+    }
+
+    /// Create a client sending its requests through `pool`, shared with
+    /// other clients
+    ///
+    /// `config.pool` is ignored; the shared pool's limits apply.
+    pub fn with_pool(config: StreamableHttpClientConfig, pool: HttpConnectionPool) -> Result<Self> {
+        let client = pool.client().clone();
+        let host = host_key(&config.base_url);
 
         let oauth_client = config
             .oauth_config
@@ -119,6 +331,8 @@ impl StreamableHttpClient {
 
         Ok(Self {
             client,
+            pool,
+            host,
             config,
             session_id: None,
             pending_response: None,
@@ -126,9 +340,54 @@ impl StreamableHttpClient {
             access_token,
             token_expiry: None,
             auth_middleware,
+            inbox: None,
+            event_stream_task: None,
+            last_event_id: Arc::new(Mutex::new(None)),
+            close_tracker: CloseTracker::new("StreamableHttpClient"),
         })
     }
 
+    /// The connection pool the client's requests go through
+    pub fn pool(&self) -> &HttpConnectionPool {
+        &self.pool
+    }
+
+    /// ID of the last event received on the event stream
+    pub fn last_event_id(&self) -> Option<String> {
+        self.last_event_id.lock().unwrap().clone()
+    }
+
+    async fn start_event_stream(&mut self, recovery: RecoveryConfig) -> Result<()> {
+        self.stop_event_stream();
+        let session_id =
+            self.session_id
+                .clone()
+                .ok_or_else(|| TransportError::ConnectionError {
+                    message: "Not connected".to_string(),
+                })?;
+        let request = EventStreamRequest {
+            client: self.client.clone(),
+            timeout: self.config.timeout,
+            url: format!("{}/mcp", self.config.base_url),
+            session_id,
+            protocol_version: self.config.protocol_version.clone(),
+            auth_headers: self.get_auth_headers().await?,
+            last_event_id: self.last_event_id.clone(),
+        };
+
+        let (sender, inbox) = mpsc::unbounded_channel();
+        self.inbox = Some(inbox);
+        self.event_stream_task = Some(tokio::spawn(request.run(recovery, sender)));
+        Ok(())
+    }
+
+    fn stop_event_stream(&mut self) {
+        if let Some(task) = self.event_stream_task.take() {
+            task.abort();
+        }
+        self.inbox = None;
+    }
+
     /// Authenticate using OAuth 2.1 if configured
     pub async fn authenticate(&mut self) -> Result<()> {
         if let Some(oauth_client) = &self.oauth_client {
@@ -136,6 +395,7 @@ impl StreamableHttpClient {
             let pkce_params = ultrafast_mcp_auth::generate_pkce_params().map_err(|e| {
                 TransportError::AuthenticationError {
                     message: format!("Failed to generate PKCE: {e}"),
+                    source: Some(Box::new(e)),
                 }
             })?;
 
@@ -148,6 +408,7 @@ impl StreamableHttpClient {
                 .await
                 .map_err(|e| TransportError::AuthenticationError {
                     message: format!("Failed to get auth URL: {e}"),
+                    source: Some(Box::new(e)),
                 })?;
 
             // In a real implementation, you would:
@@ -190,6 +451,7 @@ impl StreamableHttpClient {
             let auth_headers = auth_middleware.get_headers().await.map_err(|e| {
                 TransportError::AuthenticationError {
                     message: format!("Failed to get auth headers: {e}"),
+                    source: Some(Box::new(e)),
                 }
             })?;
 
@@ -224,6 +486,11 @@ impl StreamableHttpClient {
 
         // Store session ID
         self.session_id = Some(session_id.clone());
+        self.close_tracker.opened();
+
+        if let Some(recovery) = self.config.event_stream.clone() {
+            self.start_event_stream(recovery).await?;
+        }
 
         Ok(session_id)
     }
@@ -245,6 +512,7 @@ impl StreamableHttpClient {
         let mut request_builder = self
             .client
             .post(&url)
+            .timeout(self.config.timeout)
             .header("content-type", "application/json")
             .header("accept", "application/json, text/event-stream") // Required Accept header
             .header("mcp-session-id", session_id)
@@ -256,20 +524,39 @@ impl StreamableHttpClient {
             request_builder = request_builder.header(key, value);
         }
 
+        // Held until the response body has been read
+        let _permit = self.pool.acquire(&self.host).await;
         let response = request_builder
             .send()
             .await
             .map_err(|e| TransportError::NetworkError {
                 message: format!("Failed to send message: {e}"),
+                source: Some(Box::new(e)),
             })?;
 
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(&response));
+        }
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(TransportError::NetworkError {
                 message: format!("Send failed: {error_text}"),
+                source: None,
             });
         }
 
+        // Later requests carry the version the server negotiated
+        let is_initialize =
+            matches!(&message, JsonRpcMessage::Request(request) if request.method == "initialize");
+        if let Some(version) = response
+            .headers()
+            .get("mcp-protocol-version")
+            .and_then(|v| v.to_str().ok())
+            .filter(|_| is_initialize)
+        {
+            self.config.protocol_version = version.to_string();
+        }
+
         // Parse the response - it should be a single JSON-RPC message
         let response_message: JsonRpcMessage =
             response
@@ -299,6 +586,7 @@ impl StreamableHttpClient {
         let mut request_builder = self
             .client
             .post(&url)
+            .timeout(self.config.timeout)
             .header("content-type", "application/json")
             .header("accept", "application/json, text/event-stream")
             .header("mcp-session-id", session_id)
@@ -311,6 +599,7 @@ impl StreamableHttpClient {
         }
 
         // Fire and forget: do not block on response
+        let _permit = self.pool.acquire(&self.host).await;
         let _ = request_builder.send().await;
         Ok(())
     }
@@ -326,9 +615,13 @@ impl StreamableHttpClient {
             connection_duration: None,
             messages_sent: 0,
             messages_received: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
             error_count: 0,
             last_activity: None,
             last_error: None,
+            pool: Some(self.pool.stats()),
+            pings: None,
         }
     }
 
@@ -339,6 +632,7 @@ impl StreamableHttpClient {
 
     /// Reconnect to the server
     pub async fn reconnect(&mut self) -> Result<()> {
+        self.stop_event_stream();
         self.session_id = None;
         self.pending_response = None;
         self.connect().await?;
@@ -347,6 +641,8 @@ impl StreamableHttpClient {
 
     /// Reset the client state
     pub async fn reset(&mut self) -> Result<()> {
+        self.stop_event_stream();
+        *self.last_event_id.lock().unwrap() = None;
         self.session_id = None;
         self.pending_response = None;
         self.access_token = None;
@@ -371,6 +667,7 @@ impl StreamableHttpClient {
         let mut request_builder = self
             .client
             .get(&url)
+            .timeout(self.config.timeout)
             .header("accept", "text/event-stream") // SSE-specific Accept header
             .header("mcp-session-id", session_id)
             .header("mcp-protocol-version", &self.config.protocol_version);
@@ -385,12 +682,14 @@ impl StreamableHttpClient {
             .await
             .map_err(|e| TransportError::NetworkError {
                 message: format!("Failed to start SSE stream: {e}"),
+                source: Some(Box::new(e)),
             })?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(TransportError::NetworkError {
                 message: format!("SSE stream failed: {error_text}"),
+                source: None,
             });
         }
 
@@ -414,6 +713,7 @@ impl StreamableHttpClient {
         let mut request_builder = self
             .client
             .get(&url)
+            .timeout(self.config.timeout)
             .header("accept", "text/event-stream")
             .header("mcp-session-id", session_id)
             .header("mcp-protocol-version", &self.config.protocol_version)
@@ -429,12 +729,14 @@ impl StreamableHttpClient {
             .await
             .map_err(|e| TransportError::NetworkError {
                 message: format!("Failed to resume SSE stream: {e}"),
+                source: Some(Box::new(e)),
             })?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
             return Err(TransportError::NetworkError {
                 message: format!("SSE stream resume failed: {error_text}"),
+                source: None,
             });
         }
 
@@ -459,14 +761,22 @@ impl Transport for StreamableHttpClient {
     async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
         // Return the pending response if available
         if let Some(response) = self.pending_response.take() {
-            Ok(response)
-        } else {
-            // No pending response, connection is closed
-            Err(TransportError::ConnectionClosed)
+            return Ok(response);
+        }
+        match &mut self.inbox {
+            Some(inbox) => inbox
+                .recv()
+                .await
+                .unwrap_or(Err(TransportError::ConnectionClosed)),
+            // No pending response and no event stream, connection is closed
+            None => Err(TransportError::ConnectionClosed),
         }
     }
 
     async fn close(&mut self) -> Result<()> {
+        self.stop_event_stream();
+        self.close_tracker.closed();
+
         // Close the session using DELETE method
         if let Some(session_id) = self.session_id.clone() {
             let url = format!("{}/mcp", self.config.base_url);
@@ -477,6 +787,7 @@ impl Transport for StreamableHttpClient {
             let mut request_builder = self
                 .client
                 .delete(&url)
+                .timeout(self.config.timeout)
                 .header("mcp-session-id", session_id)
                 .header("mcp-protocol-version", &self.config.protocol_version);
 
@@ -485,6 +796,7 @@ impl Transport for StreamableHttpClient {
                 request_builder = request_builder.header(key, value);
             }
 
+            let _permit = self.pool.acquire(&self.host).await;
             let _ = request_builder.send().await;
         }
 
@@ -506,9 +818,22 @@ impl Transport for StreamableHttpClient {
             last_activity: None,
             messages_sent: 0,
             messages_received: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
             connection_duration: None,
             error_count: 0,
             last_error: None,
+            pool: Some(self.pool.stats()),
+            pings: None,
+        }
+    }
+
+    fn describe(&self) -> crate::TransportDescription {
+        let description = crate::TransportDescription::new(crate::TransportKind::Http)
+            .with_remote_address(self.config.base_url.clone());
+        match &self.session_id {
+            Some(session_id) => description.with_session_id(session_id.clone()),
+            None => description,
         }
     }
 
@@ -520,3 +845,19 @@ impl Transport for StreamableHttpClient {
         self.reset().await
     }
 }
+
+/// Error for a 429 response, carrying the delay of its `Retry-After` header
+/// when given in seconds
+fn rate_limited(response: &reqwest::Response) -> TransportError {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(std::time::Duration::from_secs);
+    TransportError::RateLimited {
+        message: format!("Server answered {}", response.status()),
+        retry_after,
+        limit: None,
+    }
+}
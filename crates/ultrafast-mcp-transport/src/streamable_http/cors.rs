@@ -0,0 +1,326 @@
+//! Cross-origin access to Streamable HTTP servers
+//!
+//! A [`CorsPolicy`] names the browser origins allowed to call the server and
+//! what they may send. It is used twice: the `Origin` of every request is
+//! checked against it, guarding local servers against DNS rebinding, and,
+//! with [`HttpTransportConfig::cors_enabled`], it decides the CORS headers
+//! browsers see in preflight and actual responses.
+//!
+//! Origins are given as patterns:
+//!
+//! - `https://app.example.com` matches that origin exactly, ignoring case.
+//! - `*` in a pattern matches one or more characters of a host name or port,
+//!   so `https://*.example.com` matches any subdomain and
+//!   `http://localhost:*` any port. A lone `*` matches every origin.
+//! - [`CorsPolicy::with_origin_regex`] takes a regular expression, which has
+//!   to match the whole origin.
+//!
+//! Credentialed requests cannot be allowed from every origin: a policy with
+//! both is rejected when the server starts.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use ultrafast_mcp_transport::streamable_http::CorsPolicy;
+//!
+//! let policy = CorsPolicy::new()
+//!     .with_origin("https://app.example.com")
+//!     .with_origin("https://*.staging.example.com")
+//!     .with_credentials(true)
+//!     .with_max_age(Duration::from_secs(600));
+//! assert!(policy.allows_origin("https://pr-12.staging.example.com"));
+//! assert!(!policy.allows_origin("https://example.com"));
+//! ```
+//!
+//! [`HttpTransportConfig::cors_enabled`]: super::HttpTransportConfig::cors_enabled
+
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use regex::Regex;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{Result, TransportError};
+
+/// An origin, or set of origins, allowed by a [`CorsPolicy`]
+#[derive(Debug, Clone)]
+pub enum OriginPattern {
+    /// Every origin
+    Any,
+    /// One origin, compared ignoring case
+    Exact(String),
+    /// Origins matching a pattern with `*` wildcards, kept as written
+    Wildcard(String, Regex),
+    /// Origins matching a regular expression in full
+    Regex(Regex),
+}
+
+impl OriginPattern {
+    /// Parse a pattern as described in the [module docs](self)
+    pub fn parse(pattern: &str) -> Self {
+        if pattern == "*" {
+            return OriginPattern::Any;
+        }
+        if !pattern.contains('*') {
+            return OriginPattern::Exact(pattern.to_string());
+        }
+        let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+        let regex = format!("(?i)^{}$", parts.join("[A-Za-z0-9.-]+"));
+        // Escaped text and a fixed character class always compile
+        let regex = Regex::new(&regex).expect("wildcard origin pattern");
+        OriginPattern::Wildcard(pattern.to_string(), regex)
+    }
+
+    pub fn matches(&self, origin: &str) -> bool {
+        match self {
+            OriginPattern::Any => true,
+            OriginPattern::Exact(allowed) => allowed.eq_ignore_ascii_case(origin),
+            OriginPattern::Wildcard(_, regex) | OriginPattern::Regex(regex) => {
+                regex.is_match(origin)
+            }
+        }
+    }
+}
+
+/// Which browser origins may call a Streamable HTTP server, and how
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    origins: Vec<OriginPattern>,
+    allowed_methods: Vec<Method>,
+    allowed_headers: Vec<HeaderName>,
+    exposed_headers: Vec<HeaderName>,
+    max_age: Option<Duration>,
+    allow_credentials: bool,
+}
+
+impl Default for CorsPolicy {
+    /// Pages served from this machine, on any port
+    fn default() -> Self {
+        Self::localhost()
+    }
+}
+
+impl CorsPolicy {
+    /// A policy allowing no origins yet, with the methods and headers MCP
+    /// clients use
+    pub fn new() -> Self {
+        Self {
+            origins: Vec::new(),
+            allowed_methods: vec![Method::GET, Method::POST, Method::DELETE, Method::OPTIONS],
+            allowed_headers: vec![
+                header::ACCEPT,
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                HeaderName::from_static("last-event-id"),
+                HeaderName::from_static("mcp-session-id"),
+                HeaderName::from_static("mcp-protocol-version"),
+            ],
+            exposed_headers: vec![
+                HeaderName::from_static("mcp-session-id"),
+                HeaderName::from_static("mcp-protocol-version"),
+            ],
+            max_age: None,
+            allow_credentials: false,
+        }
+    }
+
+    /// Pages served from `localhost` or `127.0.0.1`, on any port
+    pub fn localhost() -> Self {
+        Self::new()
+            .with_origin("http://localhost")
+            .with_origin("http://localhost:*")
+            .with_origin("http://127.0.0.1")
+            .with_origin("http://127.0.0.1:*")
+    }
+
+    /// Every origin, without credentials
+    ///
+    /// Only suitable for development or for servers that need no
+    /// authentication.
+    pub fn permissive() -> Self {
+        Self::new().with_origin("*")
+    }
+
+    /// Allow origins matching `pattern`, see the [module docs](self)
+    pub fn with_origin(mut self, pattern: impl AsRef<str>) -> Self {
+        self.origins.push(OriginPattern::parse(pattern.as_ref()));
+        self
+    }
+
+    /// Allow origins matching the regular expression `pattern` in full
+    pub fn with_origin_regex(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(&format!("^(?:{pattern})$")).map_err(|e| {
+            TransportError::InitializationError {
+                message: format!("Invalid CORS origin pattern '{pattern}': {e}"),
+            }
+        })?;
+        self.origins.push(OriginPattern::Regex(regex));
+        Ok(self)
+    }
+
+    /// Methods allowed in cross-origin requests, replacing the defaults
+    pub fn with_allowed_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.allowed_methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Request headers allowed in cross-origin requests, replacing the
+    /// defaults
+    pub fn with_allowed_headers(mut self, headers: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.allowed_headers = headers.into_iter().collect();
+        self
+    }
+
+    /// Allow `header` in cross-origin requests, besides the allowed headers
+    pub fn with_allowed_header(mut self, header: HeaderName) -> Self {
+        if !self.allowed_headers.contains(&header) {
+            self.allowed_headers.push(header);
+        }
+        self
+    }
+
+    /// Response headers scripts may read, replacing the defaults
+    pub fn with_exposed_headers(mut self, headers: impl IntoIterator<Item = HeaderName>) -> Self {
+        self.exposed_headers = headers.into_iter().collect();
+        self
+    }
+
+    /// How long browsers may cache the answer to a preflight request
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Whether browsers may send cookies and `Authorization` headers
+    pub fn with_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    pub fn origins(&self) -> &[OriginPattern] {
+        &self.origins
+    }
+
+    pub fn allowed_methods(&self) -> &[Method] {
+        &self.allowed_methods
+    }
+
+    pub fn allowed_headers(&self) -> &[HeaderName] {
+        &self.allowed_headers
+    }
+
+    pub fn exposed_headers(&self) -> &[HeaderName] {
+        &self.exposed_headers
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    pub fn allows_credentials(&self) -> bool {
+        self.allow_credentials
+    }
+
+    /// Whether every origin is allowed
+    pub fn allows_any_origin(&self) -> bool {
+        self.origins
+            .iter()
+            .any(|pattern| matches!(pattern, OriginPattern::Any))
+    }
+
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.origins.iter().any(|pattern| pattern.matches(origin))
+    }
+
+    /// Check the policy can be served; credentials cannot be allowed from
+    /// every origin
+    pub fn validate(&self) -> Result<()> {
+        if self.allow_credentials && self.allows_any_origin() {
+            return Err(TransportError::InitializationError {
+                message: "CORS credentials cannot be allowed for every origin ('*'); list the allowed origins instead".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The layer answering preflight requests and adding CORS headers
+    ///
+    /// The policy has to be [valid](Self::validate).
+    pub(super) fn layer(&self) -> CorsLayer {
+        let allow_origin = if self.allows_any_origin() {
+            AllowOrigin::any()
+        } else {
+            let policy = self.clone();
+            AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin
+                    .to_str()
+                    .is_ok_and(|origin| policy.allows_origin(origin))
+            })
+        };
+        let mut layer = CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(self.allowed_methods.clone())
+            .allow_headers(self.allowed_headers.clone())
+            .expose_headers(self.exposed_headers.clone())
+            .allow_credentials(self.allow_credentials);
+        if let Some(max_age) = self.max_age {
+            layer = layer.max_age(max_age);
+        }
+        layer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_patterns() {
+        let policy = CorsPolicy::new()
+            .with_origin("https://App.example.com")
+            .with_origin("https://*.example.org")
+            .with_origin("http://localhost:*")
+            .with_origin_regex(r"https://tenant-\d+\.example\.net")
+            .unwrap();
+
+        assert!(policy.allows_origin("https://app.example.com"));
+        assert!(!policy.allows_origin("https://app.example.com.evil.com"));
+        assert!(policy.allows_origin("https://a.b.example.org"));
+        assert!(!policy.allows_origin("https://example.org"));
+        assert!(!policy.allows_origin("https://evil.com/.example.org"));
+        assert!(policy.allows_origin("http://localhost:3000"));
+        assert!(!policy.allows_origin("http://localhost"));
+        assert!(policy.allows_origin("https://tenant-42.example.net"));
+        assert!(!policy.allows_origin("https://tenant-42.example.net.evil.com"));
+        assert!(!policy.allows_any_origin());
+
+        assert!(CorsPolicy::new().with_origin_regex("(").is_err());
+    }
+
+    #[test]
+    fn test_default_allows_localhost_only() {
+        let policy = CorsPolicy::default();
+        assert!(policy.allows_origin("http://localhost"));
+        assert!(policy.allows_origin("http://127.0.0.1:5173"));
+        assert!(!policy.allows_origin("http://localhost.evil.com"));
+        assert!(!policy.allows_origin("https://example.com"));
+        assert!(!policy.allows_credentials());
+    }
+
+    #[test]
+    fn test_credentials_require_listed_origins() {
+        assert!(CorsPolicy::permissive().validate().is_ok());
+        assert!(
+            CorsPolicy::permissive()
+                .with_credentials(true)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            CorsPolicy::new()
+                .with_origin("https://app.example.com")
+                .with_credentials(true)
+                .validate()
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Strict request checks for Streamable HTTP servers exposed to the internet
+//!
+//! With [`HttpTransportServer::with_hardening`], every request is checked
+//! before it reaches the MCP endpoint:
+//!
+//! - Message framing must be unambiguous: a request may not carry both
+//!   `Content-Length` and `Transfer-Encoding`, more than one of either, a
+//!   `Content-Length` that is not a plain decimal number, or a
+//!   `Transfer-Encoding` other than `chunked`. Proxies and servers that
+//!   disagree on where such a request ends are what request smuggling
+//!   exploits. On HTTP/1.1 connections hyper already drops `Content-Length`
+//!   when `Transfer-Encoding` is present and closes the connection after
+//!   answering, so nothing sent after such a request is handled; the check
+//!   here covers the headers as they reach the application.
+//! - The number and size of headers are capped.
+//! - `Mcp-Session-Id` must appear at most once and, with surrounding
+//!   whitespace removed, consist of 8 to 255 visible ASCII characters.
+//!   Clients also cannot pick the ID of a new session; the server always
+//!   assigns it.
+//!
+//! Rejected requests are answered with `400 Bad Request`, or
+//! `431 Request Header Fields Too Large` for oversized headers.
+//!
+//! [`HttpTransportServer::with_hardening`]: super::HttpTransportServer::with_hardening
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+use ultrafast_mcp_core::{
+    protocol::jsonrpc::{JsonRpcError, JsonRpcResponse},
+    validation::validate_session_id,
+};
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Limits enforced by the strict request checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardeningConfig {
+    /// Headers allowed in one request
+    pub max_header_count: usize,
+    /// Total size of all header names and values
+    pub max_header_bytes: usize,
+    /// Size of a single header value
+    pub max_header_value_bytes: usize,
+}
+
+impl Default for HardeningConfig {
+    fn default() -> Self {
+        Self {
+            max_header_count: 64,
+            max_header_bytes: 16 * 1024,
+            max_header_value_bytes: 8 * 1024,
+        }
+    }
+}
+
+/// Why a request was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HardeningViolation {
+    #[error("Ambiguous message framing: {0}")]
+    AmbiguousFraming(String),
+
+    #[error("Too many headers: {count} (at most {max})")]
+    TooManyHeaders { count: usize, max: usize },
+
+    #[error("Headers too large: {bytes} bytes (at most {max})")]
+    HeadersTooLarge { bytes: usize, max: usize },
+
+    #[error("Header '{name}' too large: {bytes} bytes (at most {max})")]
+    HeaderValueTooLarge {
+        name: String,
+        bytes: usize,
+        max: usize,
+    },
+
+    #[error("Invalid session ID: {0}")]
+    InvalidSessionId(String),
+}
+
+impl HardeningViolation {
+    /// HTTP status the request is rejected with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            HardeningViolation::TooManyHeaders { .. }
+            | HardeningViolation::HeadersTooLarge { .. }
+            | HardeningViolation::HeaderValueTooLarge { .. } => {
+                StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+            }
+            HardeningViolation::AmbiguousFraming(_) | HardeningViolation::InvalidSessionId(_) => {
+                StatusCode::BAD_REQUEST
+            }
+        }
+    }
+}
+
+/// Check the headers of a request, normalizing its `Mcp-Session-Id`
+pub fn check_headers(
+    config: &HardeningConfig,
+    headers: &mut HeaderMap,
+) -> Result<(), HardeningViolation> {
+    check_header_sizes(config, headers)?;
+    check_framing(headers)?;
+    normalize_session_id(headers)
+}
+
+fn check_header_sizes(
+    config: &HardeningConfig,
+    headers: &HeaderMap,
+) -> Result<(), HardeningViolation> {
+    let count = headers.len();
+    if count > config.max_header_count {
+        return Err(HardeningViolation::TooManyHeaders {
+            count,
+            max: config.max_header_count,
+        });
+    }
+    let mut total = 0;
+    for (name, value) in headers {
+        if value.len() > config.max_header_value_bytes {
+            return Err(HardeningViolation::HeaderValueTooLarge {
+                name: name.to_string(),
+                bytes: value.len(),
+                max: config.max_header_value_bytes,
+            });
+        }
+        total += name.as_str().len() + value.len();
+    }
+    if total > config.max_header_bytes {
+        return Err(HardeningViolation::HeadersTooLarge {
+            bytes: total,
+            max: config.max_header_bytes,
+        });
+    }
+    Ok(())
+}
+
+fn check_framing(headers: &HeaderMap) -> Result<(), HardeningViolation> {
+    let lengths: Vec<_> = headers.get_all(header::CONTENT_LENGTH).iter().collect();
+    let encodings: Vec<_> = headers.get_all(header::TRANSFER_ENCODING).iter().collect();
+    let ambiguous = |reason: &str| Err(HardeningViolation::AmbiguousFraming(reason.to_string()));
+
+    if !lengths.is_empty() && !encodings.is_empty() {
+        return ambiguous("both Content-Length and Transfer-Encoding");
+    }
+    match lengths.as_slice() {
+        [] => {}
+        [length] => {
+            let length = length.as_bytes();
+            if length.is_empty() || length.len() > 19 || !length.iter().all(u8::is_ascii_digit) {
+                return ambiguous("Content-Length is not a decimal number");
+            }
+        }
+        _ => return ambiguous("more than one Content-Length"),
+    }
+    match encodings.as_slice() {
+        [] => {}
+        [encoding] if encoding.as_bytes().eq_ignore_ascii_case(b"chunked") => {}
+        [_] => return ambiguous("Transfer-Encoding other than chunked"),
+        _ => return ambiguous("more than one Transfer-Encoding"),
+    }
+    Ok(())
+}
+
+fn normalize_session_id(headers: &mut HeaderMap) -> Result<(), HardeningViolation> {
+    let invalid = |reason: String| Err(HardeningViolation::InvalidSessionId(reason));
+    let normalized = {
+        let mut values = headers.get_all(SESSION_ID_HEADER).iter();
+        let Some(value) = values.next() else {
+            return Ok(());
+        };
+        if values.next().is_some() {
+            return invalid("more than one Mcp-Session-Id header".to_string());
+        }
+        let Ok(value) = value.to_str() else {
+            return invalid("not visible ASCII".to_string());
+        };
+        let value = value.trim_matches([' ', '\t']);
+        if let Err(e) = validate_session_id(value) {
+            return invalid(e.to_string());
+        }
+        HeaderValue::from_str(value).expect("visible ASCII is a valid header value")
+    };
+    headers.insert(SESSION_ID_HEADER, normalized);
+    Ok(())
+}
+
+/// Middleware rejecting requests that fail [`check_headers`]
+pub(crate) async fn enforce_hardening(
+    State(config): State<Arc<HardeningConfig>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Err(violation) = check_headers(&config, request.headers_mut()) {
+        warn!("Rejected HTTP request: {}", violation);
+        return (
+            violation.status(),
+            Json(JsonRpcResponse::error(
+                JsonRpcError::new(-32600, violation.to_string()),
+                None,
+            )),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderName;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers
+    }
+
+    fn check(pairs: &[(&str, &str)]) -> Result<HeaderMap, HardeningViolation> {
+        let mut headers = headers(pairs);
+        check_headers(&HardeningConfig::default(), &mut headers).map(|()| headers)
+    }
+
+    #[test]
+    fn test_ambiguous_framing_is_rejected() {
+        assert!(check(&[("content-length", "12")]).is_ok());
+        assert!(check(&[("transfer-encoding", "Chunked")]).is_ok());
+        for pairs in [
+            &[("content-length", "12"), ("transfer-encoding", "chunked")][..],
+            &[("content-length", "12"), ("content-length", "12")],
+            &[("content-length", "+12")],
+            &[("content-length", "1 2")],
+            &[("content-length", "")],
+            &[("transfer-encoding", "chunked, identity")],
+            &[("transfer-encoding", "xchunked")],
+            &[
+                ("transfer-encoding", "chunked"),
+                ("transfer-encoding", "chunked"),
+            ],
+        ] {
+            let violation = check(pairs).unwrap_err();
+            assert!(
+                matches!(violation, HardeningViolation::AmbiguousFraming(_)),
+                "{pairs:?}: {violation}"
+            );
+            assert_eq!(violation.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_header_limits() {
+        let config = HardeningConfig {
+            max_header_count: 3,
+            max_header_bytes: 60,
+            max_header_value_bytes: 32,
+        };
+        let long = "x".repeat(33);
+        for pairs in [
+            &[("a", "1"), ("b", "1"), ("c", "1"), ("d", "1")][..],
+            &[("a", long.as_str())],
+            &[("a", &long[..30]), ("b", &long[..30])],
+        ] {
+            let violation = check_headers(&config, &mut headers(pairs)).unwrap_err();
+            assert_eq!(
+                violation.status(),
+                StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_ids_are_normalized_and_validated() {
+        let headers = check(&[("mcp-session-id", "  session-1234\t")]).unwrap();
+        assert_eq!(headers[SESSION_ID_HEADER], "session-1234");
+        for pairs in [
+            &[("mcp-session-id", "short")][..],
+            &[("mcp-session-id", "session 1234")],
+            &[
+                ("mcp-session-id", "session-1234"),
+                ("mcp-session-id", "session-5678"),
+            ],
+        ] {
+            assert!(matches!(
+                check(pairs),
+                Err(HardeningViolation::InvalidSessionId(_))
+            ));
+        }
+    }
+
+    /// Random header sets drawn from the values attackers use, checked
+    /// against the rules stated in the module documentation
+    #[test]
+    fn test_fuzzed_headers_are_accepted_only_when_unambiguous() {
+        const NAMES: &[&str] = &[
+            "content-length",
+            "transfer-encoding",
+            "mcp-session-id",
+            "content-type",
+            "x-filler",
+        ];
+        const VALUES: &[&str] = &[
+            "0",
+            "42",
+            "-1",
+            "0x10",
+            "42, 42",
+            "99999999999999999999",
+            "chunked",
+            "CHUNKED",
+            "gzip, chunked",
+            "chunked\t",
+            "identity",
+            "",
+            " ",
+            "session-abcdef",
+            "  session-abcdef ",
+            "sess",
+            "session\u{7f}abcdef",
+            "application/json",
+        ];
+        let config = HardeningConfig {
+            max_header_count: 6,
+            max_header_bytes: 160,
+            max_header_value_bytes: 24,
+        };
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for _ in 0..20_000 {
+            let mut headers = HeaderMap::new();
+            for _ in 0..rng.random_range(0..9) {
+                let name = NAMES[rng.random_range(0..NAMES.len())];
+                let value = VALUES[rng.random_range(0..VALUES.len())];
+                if let Ok(value) = HeaderValue::from_str(value) {
+                    headers.append(name, value);
+                }
+            }
+            let original = headers.clone();
+            if check_headers(&config, &mut headers).is_err() {
+                continue;
+            }
+
+            let lengths = original.get_all(header::CONTENT_LENGTH).iter().count();
+            let encodings: Vec<_> = original.get_all(header::TRANSFER_ENCODING).iter().collect();
+            assert!(lengths + encodings.len() <= 1, "{original:?}");
+            if let Some(length) = original.get(header::CONTENT_LENGTH) {
+                assert!(
+                    length.to_str().unwrap().parse::<u64>().is_ok(),
+                    "{original:?}"
+                );
+            }
+            if let Some(encoding) = encodings.first() {
+                assert!(encoding.as_bytes().eq_ignore_ascii_case(b"chunked"));
+            }
+            assert!(original.len() <= config.max_header_count);
+            if let Some(session_id) = headers.get(SESSION_ID_HEADER) {
+                assert!(validate_session_id(session_id.to_str().unwrap()).is_ok());
+            }
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{Result, Transport, TransportError};
 use async_trait::async_trait;
 use serde_json::Value;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 use ultrafast_mcp_core::protocol::{JsonRpcMessage, RequestId};
 
@@ -145,8 +145,10 @@ impl RateLimitMiddleware {
 
         if last_minute == minute_timestamp {
             if count >= self.max_requests_per_minute {
-                return Err(TransportError::NetworkError {
+                return Err(TransportError::RateLimited {
                     message: "Rate limit exceeded".to_string(),
+                    retry_after: Some(Duration::from_secs(60 - now % 60)),
+                    limit: Some(self.max_requests_per_minute),
                 });
             }
             *count_data = (minute_timestamp, count + 1);
@@ -274,7 +276,10 @@ impl ValidationMiddleware {
     }
 
     pub fn with_allowed_methods(mut self, methods: Vec<String>) -> Self {
-        let leaked: std::collections::HashSet<&'static str> = methods.into_iter().map(|m| Box::leak(m.into_boxed_str()) as &'static str).collect();
+        let leaked: std::collections::HashSet<&'static str> = methods
+            .into_iter()
+            .map(|m| Box::leak(m.into_boxed_str()) as &'static str)
+            .collect();
         self.allowed_methods = leaked;
         self
     }
@@ -320,7 +325,7 @@ impl ValidationMiddleware {
             }
             return Ok(());
         }
-        
+
         // If no custom allowed_methods, check against static methods
         if Self::get_static_method(method).is_none() {
             return Err(TransportError::ProtocolError {
@@ -351,7 +356,10 @@ impl ValidationMiddleware {
     fn sanitize_value(&self, value: &mut Value, depth: usize) -> Result<()> {
         if depth > self.max_params_depth {
             return Err(TransportError::ProtocolError {
-                message: format!("Parameter depth exceeds maximum of {}", self.max_params_depth),
+                message: format!(
+                    "Parameter depth exceeds maximum of {}",
+                    self.max_params_depth
+                ),
             });
         }
         match value {
@@ -403,7 +411,10 @@ impl ValidationMiddleware {
             });
         }
         // Basic URI validation
-        if !uri.starts_with("file://") && !uri.starts_with("http://") && !uri.starts_with("https://") {
+        if !uri.starts_with("file://")
+            && !uri.starts_with("http://")
+            && !uri.starts_with("https://")
+        {
             return Err(TransportError::ProtocolError {
                 message: format!("Unsupported URI scheme: {uri}"),
             });
@@ -443,7 +454,8 @@ impl ValidationMiddleware {
                                 }
                                 if name_str.starts_with('_') {
                                     return Err(TransportError::ProtocolError {
-                                        message: "Tool name cannot start with underscore".to_string(),
+                                        message: "Tool name cannot start with underscore"
+                                            .to_string(),
                                     });
                                 }
                             }
@@ -457,7 +469,7 @@ impl ValidationMiddleware {
                         if let Some(level) = obj.get("level") {
                             if let Some(level_str) = level.as_str() {
                                 match level_str {
-                                    "trace" | "debug" | "info" | "warn" | "error" => {},
+                                    "trace" | "debug" | "info" | "warn" | "error" => {}
                                     _ => {
                                         return Err(TransportError::ProtocolError {
                                             message: "Invalid log level".to_string(),
@@ -475,7 +487,7 @@ impl ValidationMiddleware {
                         if let Some(version) = obj.get("protocolVersion") {
                             if let Some(version_str) = version.as_str() {
                                 match version_str {
-                                    "2025-06-18" | "2025-03-26" | "2024-11-05" => {},
+                                    "2025-06-18" | "2025-03-26" | "2024-11-05" => {}
                                     _ => {
                                         return Err(TransportError::ProtocolError {
                                             message: "Unsupported protocol version".to_string(),
@@ -497,10 +509,18 @@ impl ValidationMiddleware {
         let size = vec.len();
         let buffer = 1024;
         if size > self.max_message_size + buffer {
-            eprintln!("ValidationMiddleware: message size {} exceeds limit {} (buffered limit: {})", size, self.max_message_size, self.max_message_size + buffer);
+            eprintln!(
+                "ValidationMiddleware: message size {} exceeds limit {} (buffered limit: {})",
+                size,
+                self.max_message_size,
+                self.max_message_size + buffer
+            );
             eprintln!("Serialized message: {}", String::from_utf8_lossy(&vec));
             return Err(TransportError::ProtocolError {
-                message: format!("Message size {} exceeds limit {}", size, self.max_message_size),
+                message: format!(
+                    "Message size {} exceeds limit {}",
+                    size, self.max_message_size
+                ),
             });
         }
         Ok(())
@@ -583,6 +603,8 @@ impl<T: Transport> MiddlewareTransport<T> {
         self
     }
 
+    /// Log every message; not available with the `bare-metal` feature
+    #[cfg(not(feature = "bare-metal"))]
     pub fn add_logging(self) -> Self {
         self.with_middleware(Box::new(LoggingMiddleware::new()))
     }
@@ -591,10 +613,14 @@ impl<T: Transport> MiddlewareTransport<T> {
         self.with_middleware(Box::new(RateLimitMiddleware::new(max_requests_per_minute)))
     }
 
+    /// Track progress notifications; not available with the `bare-metal` feature
+    #[cfg(not(feature = "bare-metal"))]
     pub fn add_progress_tracking(self, timeout_seconds: u64) -> Self {
         self.with_middleware(Box::new(ProgressMiddleware::new(timeout_seconds)))
     }
 
+    /// Validate message shape; not available with the `bare-metal` feature
+    #[cfg(not(feature = "bare-metal"))]
     pub fn add_validation(self, strict: bool) -> Self {
         let validation = if strict {
             ValidationMiddleware::strict()
@@ -630,4 +656,8 @@ impl<T: Transport> Transport for MiddlewareTransport<T> {
     async fn close(&mut self) -> Result<()> {
         self.inner.close().await
     }
-} 
\ No newline at end of file
+
+    fn describe(&self) -> crate::TransportDescription {
+        self.inner.describe()
+    }
+}
@@ -2,13 +2,46 @@
 //!
 //! This module provides MCP-compliant Streamable HTTP transport for both client and server.
 //! It follows the MCP specification for stateless request/response communication.
+//!
+//! The client half is enabled by the `http-client` feature and the server half
+//! by `http-server`; `http` enables both.
 
+#[cfg(feature = "http-client")]
 pub mod client;
+#[cfg(feature = "http-server")]
+pub mod cors;
+#[cfg(feature = "http-server")]
+pub mod hardening;
 pub mod middleware;
+#[cfg(feature = "http-server")]
+pub mod openai;
+#[cfg(feature = "http-client")]
+pub mod pool;
+#[cfg(feature = "http-server")]
 pub mod server;
+#[cfg(feature = "http-server")]
+pub mod session_store;
+#[cfg(feature = "http-server")]
+pub mod sessions;
 
+#[cfg(feature = "http-client")]
 pub use client::{StreamableHttpClient, StreamableHttpClientConfig};
+#[cfg(feature = "http-server")]
+pub use cors::{CorsPolicy, OriginPattern};
+#[cfg(feature = "http-server")]
+pub use hardening::{HardeningConfig, HardeningViolation};
+#[cfg(feature = "http-server")]
+pub use openai::RequestDispatcher;
+#[cfg(feature = "http-client")]
+pub use pool::{HttpConnectionPool, HttpPoolConfig, PoolPermit};
+#[cfg(feature = "http-server")]
 pub use server::{HttpTransportConfig, HttpTransportServer, HttpTransportState};
+#[cfg(feature = "session-file")]
+pub use session_store::FileSessionStore;
+#[cfg(feature = "http-server")]
+pub use session_store::{InMemorySessionStore, SessionInfo, SessionStore, StoredEvent};
+#[cfg(feature = "http-server")]
+pub use sessions::{IdentityResolver, SessionManager, SessionMetadata};
 
 // Re-export middleware types for convenience
 pub use middleware::{
@@ -17,6 +50,7 @@ pub use middleware::{
 };
 
 /// Create a Streamable HTTP client with middleware
+#[cfg(feature = "http-client")]
 pub async fn create_streamable_http_client_with_middleware(
     config: StreamableHttpClientConfig,
     middlewares: Vec<Box<dyn TransportMiddleware>>,
@@ -32,6 +66,10 @@ pub async fn create_streamable_http_client_with_middleware(
 }
 
 /// Create a Streamable HTTP client with default middleware stack
+///
+/// Not available with the `bare-metal` feature, which compiles out the
+/// logging, progress and validation middleware of the stack.
+#[cfg(all(feature = "http-client", not(feature = "bare-metal")))]
 pub async fn create_streamable_http_client_default(
     config: StreamableHttpClientConfig,
 ) -> crate::Result<MiddlewareTransport<StreamableHttpClient>> {
@@ -47,6 +85,7 @@ pub async fn create_streamable_http_client_default(
 }
 
 /// Create a Streamable HTTP server with middleware
+#[cfg(feature = "http-server")]
 pub fn create_streamable_http_server_with_middleware(
     config: HttpTransportConfig,
     _middlewares: Vec<Box<dyn TransportMiddleware>>,
@@ -58,13 +97,14 @@ pub fn create_streamable_http_server_with_middleware(
 }
 
 /// Create a Streamable HTTP server with default configuration
+#[cfg(feature = "http-server")]
 pub fn create_streamable_http_server_default(host: &str, port: u16) -> HttpTransportServer {
     let config = HttpTransportConfig {
         host: host.to_string(),
         port,
         cors_enabled: true,
         protocol_version: "2025-06-18".to_string(),
-        allow_origin: Some("*".to_string()), // Allow all origins for development
+        cors: CorsPolicy::permissive(), // Allow all origins for development
         monitoring_enabled: true,
         enable_sse_resumability: true,
     };
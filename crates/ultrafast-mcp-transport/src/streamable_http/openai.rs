@@ -0,0 +1,331 @@
+//! OpenAI-compatible tools endpoints
+//!
+//! Products that integrate with the OpenAI function calling format, but not
+//! with MCP, can list and call the tools of a server over plain HTTP once
+//! [`HttpTransportServer::with_openai_tools`] is set:
+//!
+//! - `GET /v1/tools` lists the tools as function definitions:
+//!   `{"object": "list", "data": [{"type": "function", "function": {...}}]}`
+//! - `POST /v1/tools/invoke` takes a tool call as the model produced it,
+//!   `{"id": "call_1", "type": "function", "function": {"name": "...",
+//!   "arguments": "{...}"}}`, and answers with the `tool` message to add to
+//!   the conversation. A body of `{"tool_calls": [...]}` calls each tool in
+//!   turn and answers with `{"messages": [...]}`; it may hold at most
+//!   [`MAX_TOOL_CALLS`] calls.
+//!
+//! Function names only allow letters, digits, `_` and `-`, so other
+//! characters in tool names are replaced with `_`; invocations may use
+//! either name. Failed calls, including calls of unknown tools, are answered
+//! with a message whose `content` is the error and whose `is_error` is set,
+//! so the model sees what went wrong.
+//!
+//! [`HttpTransportServer::with_openai_tools`]: super::HttpTransportServer::with_openai_tools
+
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::State,
+    http::{StatusCode, header::HeaderMap},
+    response::{IntoResponse, Response},
+};
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+use ultrafast_mcp_core::protocol::jsonrpc::{JsonRpcRequest, JsonRpcResponse, RequestId};
+
+use super::server::{HttpTransportState, validate_origin_header};
+
+/// Answers the `tools/list` and `tools/call` requests the endpoints make
+pub type RequestDispatcher =
+    Arc<dyn Fn(JsonRpcRequest) -> BoxFuture<'static, JsonRpcResponse> + Send + Sync>;
+
+/// Longest function name OpenAI accepts
+const MAX_FUNCTION_NAME_LENGTH: usize = 64;
+
+/// Most tool calls one `POST /v1/tools/invoke` may batch
+pub const MAX_TOOL_CALLS: usize = 32;
+
+/// Most `tools/list` pages fetched to list the tools
+const MAX_LIST_PAGES: usize = 100;
+
+/// The function name a tool is listed under
+pub fn function_name(tool_name: &str) -> String {
+    tool_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_FUNCTION_NAME_LENGTH)
+        .collect()
+}
+
+/// The function definition of an MCP tool
+pub fn function_definition(tool: &Value) -> Value {
+    let name = tool.get("name").and_then(Value::as_str).unwrap_or_default();
+    json!({
+        "type": "function",
+        "function": {
+            "name": function_name(name),
+            "description": tool.get("description").cloned().unwrap_or_else(|| json!("")),
+            "parameters": tool
+                .get("inputSchema")
+                .cloned()
+                .unwrap_or_else(|| json!({"type": "object"})),
+        }
+    })
+}
+
+/// The `content` of the tool message for a `tools/call` result: its text
+/// items, then any other items and structured content as JSON
+pub fn message_content(result: &Value) -> String {
+    let mut parts = Vec::new();
+    for item in result
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match item.get("text").and_then(Value::as_str) {
+            Some(text) if item.get("type").is_none_or(|t| t == "text") => {
+                parts.push(text.to_string())
+            }
+            _ => parts.push(item.to_string()),
+        }
+    }
+    if parts.is_empty() {
+        if let Some(structured) = result.get("structuredContent") {
+            parts.push(structured.to_string());
+        }
+    }
+    parts.join("\n")
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    let body = json!({"error": {"message": message, "type": "invalid_request_error"}});
+    (status, Json(body)).into_response()
+}
+
+/// Every tool of the server, following `nextCursor` until it repeats or
+/// [`MAX_LIST_PAGES`] pages have been fetched
+async fn list_tools(dispatch: &RequestDispatcher) -> Result<Vec<Value>, String> {
+    let mut tools = Vec::new();
+    let mut cursor: Option<Value> = None;
+    let mut seen = HashSet::new();
+    for _ in 0..MAX_LIST_PAGES {
+        let params = cursor.take().map(|cursor| json!({"cursor": cursor}));
+        let request =
+            JsonRpcRequest::new("tools/list".to_string(), params, Some(RequestId::number(1)));
+        let response = dispatch(request).await;
+        if let Some(error) = response.error {
+            return Err(error.message);
+        }
+        let result = response.into_result().unwrap_or_default();
+        tools.extend(
+            result
+                .get("tools")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        match result.get("nextCursor") {
+            Some(next) if !next.is_null() && seen.insert(next.to_string()) => {
+                cursor = Some(next.clone())
+            }
+            _ => return Ok(tools),
+        }
+    }
+    Ok(tools)
+}
+
+pub(super) async fn handle_tools_get(
+    State(state): State<Arc<HttpTransportState>>,
+    headers: HeaderMap,
+) -> Response {
+    if !validate_origin_header(&headers, &state.config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(dispatch) = &state.openai_tools else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match list_tools(dispatch).await {
+        Ok(tools) => {
+            let data: Vec<Value> = tools.iter().map(function_definition).collect();
+            Json(json!({"object": "list", "data": data})).into_response()
+        }
+        Err(message) => error_response(StatusCode::INTERNAL_SERVER_ERROR, message),
+    }
+}
+
+pub(super) async fn handle_tools_invoke(
+    State(state): State<Arc<HttpTransportState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !validate_origin_header(&headers, &state.config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(dispatch) = &state.openai_tools else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let body: Value = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, format!("Invalid JSON: {e}")),
+    };
+    let (calls, batch) = match body.get("tool_calls") {
+        Some(Value::Array(calls)) if calls.len() > MAX_TOOL_CALLS => {
+            return error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("At most {MAX_TOOL_CALLS} tool_calls may be sent at once"),
+            );
+        }
+        Some(Value::Array(calls)) => (calls.clone(), true),
+        Some(_) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "tool_calls must be an array".to_string(),
+            );
+        }
+        None => (vec![body], false),
+    };
+
+    let tools = match list_tools(dispatch).await {
+        Ok(tools) => tools,
+        Err(message) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, message),
+    };
+    let mut messages = Vec::with_capacity(calls.len());
+    for (index, call) in calls.iter().enumerate() {
+        match invoke(dispatch, &tools, call, index).await {
+            Ok(message) => messages.push(message),
+            Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
+        }
+    }
+
+    if batch {
+        Json(json!({"messages": messages})).into_response()
+    } else {
+        Json(messages.remove(0)).into_response()
+    }
+}
+
+/// Call the tool a tool call names, answering with its tool message
+async fn invoke(
+    dispatch: &RequestDispatcher,
+    tools: &[Value],
+    call: &Value,
+    index: usize,
+) -> Result<Value, String> {
+    let function = call
+        .get("function")
+        .ok_or_else(|| "Tool call has no function".to_string())?;
+    let name = function
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "Tool call has no function name".to_string())?;
+    // Models send the arguments as a JSON string; accept an object as well
+    let arguments = match function.get("arguments") {
+        None | Some(Value::Null) => json!({}),
+        Some(Value::String(arguments)) if arguments.trim().is_empty() => json!({}),
+        Some(Value::String(arguments)) => serde_json::from_str(arguments)
+            .map_err(|e| format!("Invalid arguments for '{name}': {e}"))?,
+        Some(arguments) => arguments.clone(),
+    };
+    let id = call
+        .get("id")
+        .and_then(Value::as_str)
+        .map_or_else(|| format!("call_{index}"), str::to_string);
+
+    let tool_name = tools
+        .iter()
+        .filter_map(|tool| tool.get("name").and_then(Value::as_str))
+        .find(|tool| *tool == name)
+        .or_else(|| {
+            tools
+                .iter()
+                .filter_map(|tool| tool.get("name").and_then(Value::as_str))
+                .find(|tool| function_name(tool) == name)
+        });
+    let (content, is_error) = match tool_name {
+        None => (format!("Unknown tool: {name}"), true),
+        Some(tool_name) => {
+            let request = JsonRpcRequest::new(
+                "tools/call".to_string(),
+                Some(json!({"name": tool_name, "arguments": arguments})),
+                Some(RequestId::number(index as i64 + 1)),
+            );
+            let mut response = dispatch(request).await;
+            match response.error.take() {
+                Some(error) => (error.message, true),
+                None => match response.into_result() {
+                    Some(result) => (
+                        message_content(&result),
+                        result.get("isError").and_then(Value::as_bool) == Some(true),
+                    ),
+                    None => (String::new(), false),
+                },
+            }
+        }
+    };
+
+    let mut message = json!({
+        "role": "tool",
+        "tool_call_id": id,
+        "name": name,
+        "content": content,
+    });
+    if is_error {
+        message["is_error"] = json!(true);
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tools_convert_to_functions() {
+        let tool = json!({
+            "name": "files.read",
+            "description": "Read a file",
+            "inputSchema": {"type": "object", "properties": {"path": {"type": "string"}}},
+        });
+        let function = function_definition(&tool);
+        assert_eq!(function["type"], "function");
+        assert_eq!(function["function"]["name"], "files_read");
+        assert_eq!(function["function"]["parameters"], tool["inputSchema"]);
+        assert_eq!(function_name(&"x".repeat(100)).len(), 64);
+
+        let result = json!({
+            "content": [
+                {"type": "text", "text": "line 1"},
+                {"type": "image", "data": "AAAA", "mimeType": "image/png"},
+            ],
+        });
+        assert_eq!(
+            message_content(&result),
+            "line 1\n{\"data\":\"AAAA\",\"mimeType\":\"image/png\",\"type\":\"image\"}"
+        );
+        let structured = json!({"content": [], "structuredContent": {"sum": 3}});
+        assert_eq!(message_content(&structured), "{\"sum\":3}");
+    }
+
+    #[tokio::test]
+    async fn test_listing_stops_when_the_cursor_repeats() {
+        let pages = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = pages.clone();
+        let dispatch: RequestDispatcher = Arc::new(move |request| {
+            counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async move {
+                let result = json!({"tools": [{"name": "echo"}], "nextCursor": "again"});
+                JsonRpcResponse::success(result, request.id)
+            })
+        });
+        assert_eq!(list_tools(&dispatch).await.unwrap().len(), 2);
+        assert_eq!(pages.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+}
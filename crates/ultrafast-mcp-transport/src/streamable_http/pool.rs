@@ -0,0 +1,265 @@
+//! Connection pool for Streamable HTTP clients
+//!
+//! An [`HttpConnectionPool`] owns the HTTP client that requests go out on,
+//! with its keep-alive settings, and caps how many requests may be in flight
+//! at once, overall and per host. Clients created with
+//! [`StreamableHttpClient::with_pool`] share one pool, so a host talking to
+//! many servers, or opening several sessions to one, reuses connections and
+//! stays within the same limits.
+//!
+//! The limits apply to request/response exchanges. Long-lived event streams
+//! hold their own connection and are not counted against them.
+//!
+//! [`StreamableHttpClient::with_pool`]: super::StreamableHttpClient::with_pool
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{PoolStats, Result, TransportError};
+
+/// Limits and keep-alive settings of an [`HttpConnectionPool`]
+#[derive(Debug, Clone)]
+pub struct HttpPoolConfig {
+    /// Requests in flight at once across all hosts
+    pub max_connections: usize,
+    /// Requests in flight at once to a single host
+    pub max_connections_per_host: usize,
+    /// Idle connections kept open per host for reuse
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept open, `None` to keep it until the
+    /// server closes it
+    pub idle_timeout: Option<Duration>,
+    /// Interval of TCP keep-alive probes on open connections
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for HttpPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 100,
+            max_connections_per_host: 32,
+            max_idle_per_host: 32,
+            idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+/// HTTP client and request limits shared by Streamable HTTP clients
+///
+/// Cloning the pool shares it.
+#[derive(Clone)]
+pub struct HttpConnectionPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    client: reqwest::Client,
+    config: HttpPoolConfig,
+    connections: Arc<Semaphore>,
+    host_connections: Mutex<HashMap<String, Arc<Semaphore>>>,
+    in_use: AtomicUsize,
+    peak_in_use: AtomicUsize,
+    total_requests: AtomicU64,
+    waited_requests: AtomicU64,
+}
+
+/// A connection slot held for the duration of one request
+pub struct PoolPermit {
+    pool: Arc<PoolInner>,
+    _connection: OwnedSemaphorePermit,
+    _host_connection: OwnedSemaphorePermit,
+}
+
+impl Drop for PoolPermit {
+    fn drop(&mut self) {
+        self.pool.in_use.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl HttpConnectionPool {
+    pub fn new(config: HttpPoolConfig) -> Result<Self> {
+        if config.max_connections == 0 || config.max_connections_per_host == 0 {
+            return Err(TransportError::InitializationError {
+                message: "Connection pool limits must be at least 1".to_string(),
+            });
+        }
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(config.max_idle_per_host)
+            .pool_idle_timeout(config.idle_timeout)
+            .tcp_keepalive(config.tcp_keepalive)
+            .build()
+            .map_err(|e| TransportError::InitializationError {
+                message: format!("Failed to create HTTP client: {e}"),
+            })?;
+
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                client,
+                connections: Arc::new(Semaphore::new(config.max_connections)),
+                host_connections: Mutex::new(HashMap::new()),
+                config,
+                in_use: AtomicUsize::new(0),
+                peak_in_use: AtomicUsize::new(0),
+                total_requests: AtomicU64::new(0),
+                waited_requests: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    pub fn config(&self) -> &HttpPoolConfig {
+        &self.inner.config
+    }
+
+    /// The HTTP client requests are sent with
+    pub fn client(&self) -> &reqwest::Client {
+        &self.inner.client
+    }
+
+    /// Wait for a free connection slot to `host`, within both the per-host
+    /// and the overall limit
+    pub async fn acquire(&self, host: &str) -> PoolPermit {
+        let inner = &self.inner;
+        let host_connections = {
+            let mut hosts = inner
+                .host_connections
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            hosts
+                .entry(host.to_string())
+                .or_insert_with(|| Arc::new(Semaphore::new(inner.config.max_connections_per_host)))
+                .clone()
+        };
+
+        let mut waited = false;
+        let host_connection = match host_connections.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                waited = true;
+                host_connections
+                    .acquire_owned()
+                    .await
+                    .expect("pool semaphores are never closed")
+            }
+        };
+        let connection = match inner.connections.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                waited = true;
+                inner
+                    .connections
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("pool semaphores are never closed")
+            }
+        };
+
+        inner.total_requests.fetch_add(1, Ordering::Relaxed);
+        if waited {
+            inner.waited_requests.fetch_add(1, Ordering::Relaxed);
+        }
+        let in_use = inner.in_use.fetch_add(1, Ordering::Relaxed) + 1;
+        inner.peak_in_use.fetch_max(in_use, Ordering::Relaxed);
+
+        PoolPermit {
+            pool: inner.clone(),
+            _connection: connection,
+            _host_connection: host_connection,
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let inner = &self.inner;
+        PoolStats {
+            max_connections: inner.config.max_connections,
+            max_connections_per_host: inner.config.max_connections_per_host,
+            in_use: inner.in_use.load(Ordering::Relaxed),
+            peak_in_use: inner.peak_in_use.load(Ordering::Relaxed),
+            total_requests: inner.total_requests.load(Ordering::Relaxed),
+            waited_requests: inner.waited_requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The `host:port` a URL's requests are limited under
+pub(crate) fn host_key(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        ),
+        Err(_) => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(max_connections: usize, max_connections_per_host: usize) -> HttpConnectionPool {
+        HttpConnectionPool::new(HttpPoolConfig {
+            max_connections,
+            max_connections_per_host,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_per_host_limit_does_not_block_other_hosts() {
+        let pool = pool(3, 1);
+        let first = pool.acquire("a:80").await;
+
+        let blocked = tokio::time::timeout(Duration::from_millis(50), pool.acquire("a:80")).await;
+        assert!(blocked.is_err(), "second request to a:80 should wait");
+        let _other = pool.acquire("b:80").await;
+
+        drop(first);
+        let _second = tokio::time::timeout(Duration::from_secs(1), pool.acquire("a:80"))
+            .await
+            .expect("freed slot should be reused");
+
+        let stats = pool.stats();
+        assert_eq!(stats.in_use, 2);
+        assert_eq!(stats.peak_in_use, 2);
+        assert_eq!(stats.total_requests, 3);
+        assert_eq!(stats.waited_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_overall_limit_makes_requests_wait() {
+        let pool = pool(1, 4);
+        let first = pool.acquire("a:80").await;
+
+        let waiting = tokio::spawn({
+            let pool = pool.clone();
+            async move {
+                let _permit = pool.acquire("b:80").await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        waiting.await.unwrap();
+        let stats = pool.stats();
+        assert_eq!((stats.in_use, stats.waited_requests), (0, 1));
+    }
+
+    #[test]
+    fn test_limits_must_be_positive() {
+        let config = HttpPoolConfig {
+            max_connections_per_host: 0,
+            ..Default::default()
+        };
+        assert!(HttpConnectionPool::new(config).is_err());
+        assert_eq!(host_key("http://127.0.0.1:8080/mcp"), "127.0.0.1:8080");
+        assert_eq!(host_key("https://example.com"), "example.com:443");
+    }
+}
@@ -4,30 +4,41 @@
 //! that follows the MCP specification for stateless request/response communication.
 
 use axum::{
-    Json,
-    extract::State,
+    Extension, Json,
+    body::HttpBody,
+    extract::{ConnectInfo, State, rejection::BytesRejection},
     http::{StatusCode, header::HeaderMap},
     response::{IntoResponse, Response, Sse, sse::Event},
     routing::Router,
 };
 use bytes::Bytes;
-use futures::stream::{self, Stream};
+use futures::stream::{self, Stream, StreamExt};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::broadcast;
-use tower_http::cors::CorsLayer;
-use tracing::{error, info};
+use tokio::sync::{broadcast, watch};
+use tracing::{error, info, warn};
 
 use ultrafast_mcp_core::{
     protocol::{
-        jsonrpc::{JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse},
-        version::PROTOCOL_VERSION,
+        jsonrpc::{JsonRpcError, JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, RequestId},
+        version::{
+            DEFAULT_HTTP_PROTOCOL_VERSION, PROTOCOL_VERSION, SUPPORTED_VERSIONS,
+            is_supported_version,
+        },
     },
-    utils::{generate_event_id, generate_session_id},
-    validation::{validate_origin, validate_protocol_version, validate_session_id},
+    utils::generate_session_id,
+    validation::{validate_origin, validate_session_id},
 };
+#[cfg(not(feature = "bare-metal"))]
 use ultrafast_mcp_monitoring::metrics::RequestTimer;
-use ultrafast_mcp_monitoring::{MetricsCollector, MonitoringSystem};
-
+use ultrafast_mcp_monitoring::{MetricsCollector, MonitoringSystem, RejectReason};
+
+use super::cors::CorsPolicy;
+use super::hardening::{HardeningConfig, enforce_hardening};
+use super::openai::{RequestDispatcher, handle_tools_get, handle_tools_invoke};
+pub use super::session_store::SessionInfo;
+use super::session_store::{InMemorySessionStore, SessionStore};
+use super::sessions::{IdentityResolver, SessionManager};
 use crate::{Result, Transport, TransportError};
 use async_trait::async_trait;
 
@@ -36,9 +47,13 @@ use async_trait::async_trait;
 pub struct HttpTransportConfig {
     pub host: String,
     pub port: u16,
+    /// Whether to answer preflight requests and add CORS headers, as set
+    /// out by `cors`
     pub cors_enabled: bool,
     pub protocol_version: String,
-    pub allow_origin: Option<String>,
+    /// Origins allowed to send requests, and what they may send; see
+    /// [`super::cors`]
+    pub cors: CorsPolicy,
     pub monitoring_enabled: bool,
     pub enable_sse_resumability: bool,
 }
@@ -50,13 +65,20 @@ impl Default for HttpTransportConfig {
             port: 8080,
             cors_enabled: true,
             protocol_version: PROTOCOL_VERSION.to_string(),
-            allow_origin: Some("http://localhost:*".to_string()),
+            cors: CorsPolicy::default(),
             monitoring_enabled: true,
             enable_sse_resumability: true,
         }
     }
 }
 
+/// Produces the body of `GET /x-ultrafast/info`
+pub type InfoProvider = Arc<dyn Fn() -> serde_json::Value + Send + Sync>;
+
+/// A message for a session's SSE stream, with its event ID once recorded in
+/// the [`SessionStore`]
+pub type StreamEvent = (String, Option<String>, JsonRpcMessage);
+
 /// Shared state for HTTP transport
 #[derive(Clone)]
 pub struct HttpTransportState {
@@ -65,31 +87,26 @@ pub struct HttpTransportState {
     pub config: HttpTransportConfig,
     pub metrics: Option<Arc<MetricsCollector>>,
     pub monitoring: Option<Arc<MonitoringSystem>>,
-    pub session_store: Arc<tokio::sync::RwLock<std::collections::HashMap<String, SessionInfo>>>,
-}
-
-/// Session information for tracking and resumability
-#[derive(Debug, Clone)]
-pub struct SessionInfo {
-    pub created_at: std::time::SystemTime,
-    pub last_event_id: Option<String>,
-    pub active_streams: std::collections::HashSet<String>,
-}
-
-impl SessionInfo {
-    pub fn new() -> Self {
-        Self {
-            created_at: std::time::SystemTime::now(),
-            last_event_id: None,
-            active_streams: std::collections::HashSet::new(),
-        }
-    }
-}
-
-impl Default for SessionInfo {
-    fn default() -> Self {
-        Self::new()
-    }
+    pub session_store: Arc<dyn SessionStore>,
+    /// Messages from `response_sender` after they were recorded, as sent on
+    /// SSE streams
+    pub event_sender: broadcast::Sender<StreamEvent>,
+    pub info_provider: Option<InfoProvider>,
+    /// Where malformed and oversized messages are recorded; falls back to
+    /// `metrics`
+    pub reject_metrics: Option<Arc<MetricsCollector>>,
+    /// Where the bytes of POST requests and their responses are recorded;
+    /// falls back to `metrics`
+    pub traffic_metrics: Option<Arc<MetricsCollector>>,
+    /// Set once the server shuts down, ending SSE streams
+    pub closing: watch::Sender<bool>,
+    /// Open sessions; `response_sender` is its channel
+    pub sessions: SessionManager,
+    pub identity_resolver: Option<IdentityResolver>,
+    /// Strict request checks, see [`super::hardening`]
+    pub hardening: Option<Arc<HardeningConfig>>,
+    /// Answers the OpenAI-compatible tools endpoints, see [`super::openai`]
+    pub openai_tools: Option<RequestDispatcher>,
 }
 
 /// HTTP transport server implementation
@@ -101,7 +118,9 @@ pub struct HttpTransportServer {
 impl HttpTransportServer {
     pub fn new(config: HttpTransportConfig) -> Self {
         let (message_sender, message_receiver) = broadcast::channel(1000);
-        let (response_sender, _) = broadcast::channel(1000);
+        let sessions = SessionManager::new();
+        let response_sender = sessions.outgoing().clone();
+        let (event_sender, _) = broadcast::channel(1000);
 
         let state = HttpTransportState {
             message_sender,
@@ -109,7 +128,16 @@ impl HttpTransportServer {
             config,
             metrics: None,
             monitoring: None,
-            session_store: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            session_store: Arc::new(InMemorySessionStore::new()),
+            event_sender,
+            info_provider: None,
+            reject_metrics: None,
+            traffic_metrics: None,
+            closing: watch::Sender::new(false),
+            sessions,
+            identity_resolver: None,
+            hardening: None,
+            openai_tools: None,
         };
 
         Self {
@@ -128,6 +156,70 @@ impl HttpTransportServer {
         self
     }
 
+    /// Keep sessions and their events in `store` instead of process memory
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        self.state.session_store = store;
+        self
+    }
+
+    /// Serve `GET /x-ultrafast/info` with the value returned by `provider`
+    pub fn with_info_provider(mut self, provider: InfoProvider) -> Self {
+        self.state.info_provider = Some(provider);
+        self
+    }
+
+    /// Record messages rejected by the transport in `metrics`, without
+    /// counting requests there
+    pub fn with_reject_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.state.reject_metrics = Some(metrics);
+        self
+    }
+
+    /// Record the bytes of POST requests and their responses in `metrics`,
+    /// without counting requests there
+    pub fn with_traffic_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.state.traffic_metrics = Some(metrics);
+        self
+    }
+
+    /// Track sessions in `manager`, created before the server
+    ///
+    /// Messages for sessions are sent on the manager's channel, so this has
+    /// to be called before [`get_response_sender`](Self::get_response_sender).
+    pub fn with_session_manager(mut self, manager: SessionManager) -> Self {
+        self.state.response_sender = manager.outgoing().clone();
+        self.state.sessions = manager;
+        self
+    }
+
+    /// Record who clients authenticated as, from the headers of their
+    /// `initialize` requests, in their [`SessionMetadata`]
+    ///
+    /// [`SessionMetadata`]: super::sessions::SessionMetadata
+    pub fn with_identity_resolver(mut self, resolver: IdentityResolver) -> Self {
+        self.state.identity_resolver = Some(resolver);
+        self
+    }
+
+    /// Reject requests with ambiguous framing, oversized headers or
+    /// malformed session IDs, see [`super::hardening`]
+    pub fn with_hardening(mut self, config: HardeningConfig) -> Self {
+        self.state.hardening = Some(Arc::new(config));
+        self
+    }
+
+    /// Serve the OpenAI-compatible `/v1/tools` endpoints, see
+    /// [`super::openai`], with the tools `dispatcher` lists and calls
+    pub fn with_openai_tools(mut self, dispatcher: RequestDispatcher) -> Self {
+        self.state.openai_tools = Some(dispatcher);
+        self
+    }
+
+    /// The open sessions, to address or evict single clients
+    pub fn sessions(&self) -> SessionManager {
+        self.state.sessions.clone()
+    }
+
     pub fn get_message_receiver(&self) -> broadcast::Receiver<(String, JsonRpcMessage)> {
         self.state.message_sender.subscribe()
     }
@@ -153,11 +245,23 @@ impl HttpTransportServer {
     }
 
     pub async fn run(self) -> Result<()> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Run until `shutdown` completes
+    ///
+    /// Open SSE streams are ended and the server stops once the requests
+    /// being answered are done.
+    pub async fn run_until(
+        self,
+        shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    ) -> Result<()> {
         info!(
             "Starting HTTP transport server on {}:{}",
             self.state.config.host, self.state.config.port
         );
 
+        self.state.config.cors.validate()?;
         let app = self.create_router();
         let addr = (self.state.config.host.as_str(), self.state.config.port);
 
@@ -167,6 +271,9 @@ impl HttpTransportServer {
             }
         })?;
 
+        tokio::spawn(record_stream_events(self.state.clone()));
+        tokio::spawn(remove_evicted_sessions(self.state.clone()));
+
         // Start monitoring HTTP server if enabled
         if let Some(monitoring) = &self.state.monitoring {
             let monitoring_addr =
@@ -184,11 +291,20 @@ impl HttpTransportServer {
             });
         }
 
-        axum::serve(listener, app.into_make_service())
-            .await
-            .map_err(|e| TransportError::InitializationError {
-                message: format!("Server failed: {e}"),
-            })?;
+        let closing = self.state.closing.clone();
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            info!("Shutting down HTTP transport server");
+            closing.send_replace(true);
+        })
+        .await
+        .map_err(|e| TransportError::InitializationError {
+            message: format!("Server failed: {e}"),
+        })?;
 
         Ok(())
     }
@@ -200,8 +316,25 @@ impl HttpTransportServer {
             .route("/mcp", axum::routing::get(handle_mcp_get))
             .route("/mcp", axum::routing::delete(handle_mcp_delete));
 
+        if self.state.info_provider.is_some() {
+            router = router.route("/x-ultrafast/info", axum::routing::get(handle_info_get));
+        }
+
+        if self.state.openai_tools.is_some() {
+            router = router
+                .route("/v1/tools", axum::routing::get(handle_tools_get))
+                .route("/v1/tools/invoke", axum::routing::post(handle_tools_invoke));
+        }
+
+        if let Some(hardening) = &self.state.hardening {
+            router = router.layer(axum::middleware::from_fn_with_state(
+                hardening.clone(),
+                enforce_hardening,
+            ));
+        }
+
         if self.state.config.cors_enabled {
-            router = router.layer(CorsLayer::permissive());
+            router = router.layer(self.state.config.cors.layer());
         }
 
         router.with_state(state)
@@ -226,6 +359,10 @@ impl Transport for HttpTransportServer {
     async fn close(&mut self) -> Result<()> {
         Ok(())
     }
+
+    fn describe(&self) -> crate::TransportDescription {
+        crate::TransportDescription::new(crate::TransportKind::Http)
+    }
 }
 
 /// Extract session ID from headers
@@ -254,16 +391,70 @@ fn extract_last_event_id(headers: &HeaderMap) -> Option<String> {
 
 // Validation functions moved to ultrafast_mcp_core::validation
 
-/// Validate Origin header for security using core validation
-fn validate_origin_header(headers: &HeaderMap, config: &HttpTransportConfig) -> bool {
+/// Validate Origin header for security against the CORS policy
+///
+/// Requests without an `Origin` header, and any origin when the policy lists
+/// none, fall back to the core validation for local servers.
+pub(super) fn validate_origin_header(headers: &HeaderMap, config: &HttpTransportConfig) -> bool {
     let origin = headers.get("origin").and_then(|v| v.to_str().ok());
 
-    validate_origin(origin, config.allow_origin.as_deref(), &config.host)
+    match origin {
+        Some(origin) if !config.cors.origins().is_empty() => config.cors.allows_origin(origin),
+        _ => validate_origin(origin, None, &config.host),
+    }
 }
 
-/// Validate protocol version header using core validation
-fn validate_protocol_version_header(version: &str) -> bool {
-    validate_protocol_version(version).is_ok()
+/// The protocol version a request is made with
+///
+/// An `MCP-Protocol-Version` header has to name a supported version and, in
+/// a session whose version was negotiated, that version. Without the header
+/// the session's version is assumed, or [`DEFAULT_HTTP_PROTOCOL_VERSION`] as
+/// the specification requires when there is none. `session_id` is `None`
+/// for requests that open a session.
+///
+/// A refused version is answered with 400 and counted as a reject.
+async fn resolve_protocol_version(
+    state: &HttpTransportState,
+    headers: &HeaderMap,
+    session_id: Option<&str>,
+) -> std::result::Result<String, Response> {
+    let negotiated = session_id.and_then(|id| state.sessions.protocol_version(id));
+    let Some(requested) = extract_protocol_version(headers) else {
+        return Ok(negotiated.unwrap_or_else(|| DEFAULT_HTTP_PROTOCOL_VERSION.to_string()));
+    };
+
+    let message = if !is_supported_version(&requested) {
+        format!(
+            "Unsupported protocol version: {requested}; supported versions are {}",
+            SUPPORTED_VERSIONS.join(", ")
+        )
+    } else {
+        match &negotiated {
+            Some(negotiated) if *negotiated != requested => format!(
+                "Protocol version {requested} does not match {negotiated}, negotiated for this session; send the version from the initialize result"
+            ),
+            _ => return Ok(requested),
+        }
+    };
+
+    warn!(
+        "Refusing request with protocol version {} in session {}",
+        requested,
+        session_id.unwrap_or("-")
+    );
+    record_reject(state, RejectReason::UnsupportedProtocolVersion, session_id).await;
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(JsonRpcResponse::error(
+            JsonRpcError::new(-32000, message).with_data(serde_json::json!({
+                "requested": requested,
+                "supported": SUPPORTED_VERSIONS,
+                "negotiated": negotiated,
+            })),
+            None,
+        )),
+    )
+        .into_response())
 }
 
 /// Validate session ID format using core validation  
@@ -273,20 +464,67 @@ fn validate_session_id_header(session_id: &str) -> bool {
 
 // Session ID and event ID generation functions moved to ultrafast_mcp_core::utils
 
+async fn handle_info_get(
+    State(state): State<Arc<HttpTransportState>>,
+    headers: HeaderMap,
+) -> Response {
+    if !validate_origin_header(&headers, &state.config) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match &state.info_provider {
+        Some(provider) => Json(provider()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
 async fn handle_mcp_post(
     State(state): State<Arc<HttpTransportState>>,
     headers: HeaderMap,
-    body: Bytes,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    body: std::result::Result<Bytes, BytesRejection>,
 ) -> impl IntoResponse {
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) => {
+            if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+                let session_id = extract_session_id(&headers);
+                record_reject(
+                    &state,
+                    RejectReason::OversizedMessage,
+                    session_id.as_deref(),
+                )
+                .await;
+            }
+            return rejection.into_response();
+        }
+    };
+
     // Start request timer for monitoring
+    #[cfg(not(feature = "bare-metal"))]
     let timer = state
         .metrics
         .as_ref()
         .map(|metrics| RequestTimer::start("mcp_post", metrics.clone()));
 
-    let result = handle_mcp_post_internal(state, headers, body).await;
+    let traffic_metrics = state
+        .traffic_metrics
+        .as_ref()
+        .or(state.metrics.as_ref())
+        .cloned();
+    if let Some(metrics) = &traffic_metrics {
+        metrics.record_transport_receive(body.len() as u64).await;
+    }
+
+    let remote_address = connect_info.map(|Extension(ConnectInfo(address))| address);
+    let result = handle_mcp_post_internal(state, headers, remote_address, body).await;
+
+    // Streamed responses are of unknown length until they are done
+    if let (Some(metrics), Some(bytes)) = (traffic_metrics, result.body().size_hint().exact()) {
+        metrics.record_transport_send(bytes).await;
+    }
 
     // Record metrics
+    #[cfg(not(feature = "bare-metal"))]
     if let Some(timer) = timer {
         let success = result.status() == StatusCode::OK;
         timer.finish(success).await;
@@ -295,9 +533,17 @@ async fn handle_mcp_post(
     result
 }
 
+/// Count a message rejected before it could be forwarded to the server
+async fn record_reject(state: &HttpTransportState, reason: RejectReason, session_id: Option<&str>) {
+    if let Some(metrics) = state.reject_metrics.as_ref().or(state.metrics.as_ref()) {
+        metrics.record_reject(reason, None, session_id).await;
+    }
+}
+
 async fn handle_mcp_post_internal(
     state: Arc<HttpTransportState>,
     headers: HeaderMap,
+    remote_address: Option<SocketAddr>,
     body: Bytes,
 ) -> Response {
     // Validate Origin header
@@ -312,70 +558,12 @@ async fn handle_mcp_post_internal(
             .into_response();
     }
 
-    // Validate protocol version header if present
-    if let Some(protocol_version) = extract_protocol_version(&headers) {
-        if !validate_protocol_version_header(&protocol_version) {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(JsonRpcResponse::error(
-                    JsonRpcError::new(
-                        -32000,
-                        format!("Unsupported protocol version: {protocol_version}"),
-                    ),
-                    None,
-                )),
-            )
-                .into_response();
-        }
-    }
-
-    // Check if this is an initial connection (initialize request or empty body)
-    let is_initial_connection = body.is_empty() || {
-        if let Ok(message) = serde_json::from_slice::<JsonRpcMessage>(&body) {
-            matches!(message, JsonRpcMessage::Request(req) if req.method == "initialize")
-        } else {
-            false
-        }
-    };
-
-    let session_id = if is_initial_connection {
-        extract_session_id(&headers).unwrap_or_else(generate_session_id)
-    } else {
-        match extract_session_id(&headers) {
-            Some(id) => {
-                if !validate_session_id_header(&id) {
-                    return Json(JsonRpcResponse::error(
-                        JsonRpcError::new(-32000, "Invalid session ID format".to_string()),
-                        None,
-                    ))
-                    .into_response();
-                }
-                id
-            }
-            None => {
-                return Json(JsonRpcResponse::error(
-                    JsonRpcError::new(-32000, "Missing session ID".to_string()),
-                    None,
-                ))
-                .into_response();
-            }
-        }
-    };
-
-    // Store session info
-    {
-        let mut sessions = state.session_store.write().await;
-        sessions
-            .entry(session_id.clone())
-            .or_insert_with(SessionInfo::new);
-    }
-
     // Try to parse the body as a JSON-RPC message
-    let message: std::result::Result<JsonRpcMessage, serde_json::Error> =
-        serde_json::from_slice(&body);
-    let message = match message {
+    let message = match serde_json::from_slice::<JsonRpcMessage>(&body) {
         Ok(msg) => msg,
         Err(_) => {
+            let session_id = extract_session_id(&headers);
+            record_reject(&state, RejectReason::ParseError, session_id.as_deref()).await;
             return Json(JsonRpcResponse::error(
                 JsonRpcError::new(-32700, "Parse error: Invalid JSON-RPC message".to_string()),
                 None,
@@ -384,15 +572,84 @@ async fn handle_mcp_post_internal(
         }
     };
 
+    // Only `initialize` opens a session; every other message has to be sent
+    // in one the server opened and has not closed since
+    let is_initial_connection =
+        matches!(&message, JsonRpcMessage::Request(req) if req.method == "initialize");
+    let session_id = if is_initial_connection {
+        let session_id = if state.hardening.is_some() {
+            // Clients may not choose the ID of their session
+            generate_session_id()
+        } else {
+            extract_session_id(&headers).unwrap_or_else(generate_session_id)
+        };
+        if state.sessions.is_evicted(&session_id) {
+            return session_not_found();
+        }
+        session_id
+    } else {
+        match existing_session_id(&state, &headers).await {
+            Ok(id) => id,
+            Err(response) => return response,
+        }
+    };
+
+    let negotiated_in = (!is_initial_connection).then_some(session_id.as_str());
+    let protocol_version = match resolve_protocol_version(&state, &headers, negotiated_in).await {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+    state.sessions.touch(
+        &session_id,
+        remote_address.map(|address| address.to_string()),
+    );
+
+    if is_initial_connection {
+        if let Err(e) = state.session_store.create_session(session_id.clone()).await {
+            error!("Failed to store session {}: {}", session_id, e);
+        }
+        if let Some(address) = remote_address {
+            if let Err(e) = state
+                .session_store
+                .set_remote_address(&session_id, address.to_string())
+                .await
+            {
+                error!("Failed to store address of session {}: {}", session_id, e);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "bare-metal"))]
     info!(
         "Processing POST request for session {}: {:?}",
         session_id, message
     );
+    // Sessions are counted by the version they negotiate, once initialized
+    if !is_initial_connection {
+        if let Some(metrics) = &state.metrics {
+            metrics.record_protocol_version(&protocol_version).await;
+        }
+    }
+    if let JsonRpcMessage::Request(request) = &message {
+        if request.method == "initialize" {
+            let identity = state
+                .identity_resolver
+                .as_ref()
+                .and_then(|resolve| resolve(&headers));
+            state
+                .sessions
+                .record_initialize(&session_id, request.params.as_ref(), identity);
+        }
+    }
     match message {
-        JsonRpcMessage::Request(request) => {
-            handle_jsonrpc_request(state, session_id, request).await
+        // Notifications deserialize as requests without an id; nothing will
+        // answer them, so they must not wait for a response
+        JsonRpcMessage::Request(request) if request.id.is_some() => {
+            handle_jsonrpc_request(state, session_id, protocol_version, request).await
         }
-        JsonRpcMessage::Notification(_) | JsonRpcMessage::Response(_) => {
+        JsonRpcMessage::Request(_)
+        | JsonRpcMessage::Notification(_)
+        | JsonRpcMessage::Response(_) => {
             handle_notification_or_response(state, session_id, message).await
         }
     }
@@ -413,24 +670,13 @@ async fn handle_mcp_get(
             .into_response();
     }
 
-    // Validate protocol version header if present
-    if let Some(protocol_version) = extract_protocol_version(&headers) {
-        if !validate_protocol_version_header(&protocol_version) {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(JsonRpcResponse::error(
-                    JsonRpcError::new(
-                        -32000,
-                        format!("Unsupported protocol version: {protocol_version}"),
-                    ),
-                    None,
-                )),
-            )
-                .into_response();
-        }
+    let session_id = match existing_session_id(&state, &headers).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    if let Err(response) = resolve_protocol_version(&state, &headers, Some(&session_id)).await {
+        return response;
     }
-
-    let session_id = extract_session_id(&headers).unwrap_or_else(generate_session_id);
     let last_event_id = extract_last_event_id(&headers);
 
     info!(
@@ -442,19 +688,47 @@ async fn handle_mcp_get(
             .unwrap_or_default()
     );
 
-    // Store session info
-    {
-        let mut sessions = state.session_store.write().await;
-        let session_info = sessions
-            .entry(session_id.clone())
-            .or_insert_with(SessionInfo::new);
-        if let Some(event_id) = &last_event_id {
-            session_info.last_event_id = Some(event_id.clone());
+    let stream = create_sse_stream(state, session_id, last_event_id).await;
+    Sse::new(stream).into_response()
+}
+
+/// The ID of the session a request other than `initialize` is made in
+///
+/// Only sessions the server opened on `initialize` are accepted: a missing or
+/// malformed `Mcp-Session-Id` header is answered with 400, and an ID the
+/// session store does not know, or that was evicted, with 404.
+async fn existing_session_id(
+    state: &HttpTransportState,
+    headers: &HeaderMap,
+) -> std::result::Result<String, Response> {
+    let Some(session_id) = extract_session_id(headers) else {
+        return Err(bad_session_id("Missing session ID"));
+    };
+    if !validate_session_id_header(&session_id) {
+        return Err(bad_session_id("Invalid session ID format"));
+    }
+    if state.sessions.is_evicted(&session_id) {
+        return Err(session_not_found());
+    }
+    match state.session_store.get_session(&session_id).await {
+        Ok(Some(_)) => Ok(session_id),
+        Ok(None) => Err(session_not_found()),
+        Err(e) => {
+            error!("Failed to look up session {}: {}", session_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
         }
     }
+}
 
-    let stream = create_sse_stream(state, session_id, last_event_id);
-    Sse::new(stream).into_response()
+fn bad_session_id(message: &str) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(JsonRpcResponse::error(
+            JsonRpcError::new(-32000, message.to_string()),
+            None,
+        )),
+    )
+        .into_response()
 }
 
 async fn handle_mcp_delete(
@@ -472,39 +746,31 @@ async fn handle_mcp_delete(
             .into_response();
     }
 
-    // Validate protocol version header if present
-    if let Some(protocol_version) = extract_protocol_version(&headers) {
-        if !validate_protocol_version_header(&protocol_version) {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(JsonRpcResponse::error(
-                    JsonRpcError::new(
-                        -32000,
-                        format!("Unsupported protocol version: {protocol_version}"),
-                    ),
-                    None,
-                )),
-            )
-                .into_response();
-        }
+    let session_id = match existing_session_id(&state, &headers).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    if let Err(response) = resolve_protocol_version(&state, &headers, Some(&session_id)).await {
+        return response;
     }
 
-    let session_id = extract_session_id(&headers).unwrap_or_else(generate_session_id);
-
-    // Remove session from store
-    {
-        let mut sessions = state.session_store.write().await;
-        sessions.remove(&session_id);
+    if let Err(e) = state.session_store.remove_session(&session_id).await {
+        error!("Failed to remove session {}: {}", session_id, e);
     }
+    state.sessions.remove(&session_id);
 
     info!("Terminating session: {}", session_id);
     StatusCode::OK.into_response()
 }
 
 /// Handle JSON-RPC requests
+///
+/// `protocol_version` is the version the request was made with; answers to
+/// `initialize` carry the version negotiated instead.
 async fn handle_jsonrpc_request(
     state: Arc<HttpTransportState>,
     session_id: String,
+    protocol_version: String,
     request: JsonRpcRequest,
 ) -> Response {
     // Create a response receiver for this specific request
@@ -526,51 +792,33 @@ async fn handle_jsonrpc_request(
     // Wait for response from server with timeout
     match tokio::time::timeout(
         std::time::Duration::from_secs(30), // Increased timeout to 30 seconds
-        response_receiver.recv(),
+        wait_for_response(&mut response_receiver, &session_id, request.id.as_ref()),
     )
     .await
     {
-        Ok(Ok((response_session_id, response_message))) => {
-            if response_session_id == session_id || response_session_id == "*" {
-                // Return the actual response from the server
-                match response_message {
-                    JsonRpcMessage::Response(response) => {
-                        info!("Sending response back to client: {:?}", response);
-                        (
-                            StatusCode::OK,
-                            [
-                                ("mcp-session-id", response_session_id),
-                                (
-                                    "mcp-protocol-version",
-                                    state.config.protocol_version.clone(),
-                                ),
-                            ],
-                            Json(response),
-                        )
-                            .into_response()
-                    }
-                    _ => {
-                        // Unexpected message type
-                        error!("Unexpected response type: {:?}", response_message);
-                        Json(JsonRpcResponse::error(
-                            JsonRpcError::new(-32000, "Unexpected response type".to_string()),
-                            request.id,
-                        ))
-                        .into_response()
+        Ok(Ok((response_session_id, response))) => {
+            info!("Sending response back to client: {:?}", response);
+            let mut protocol_version = protocol_version;
+            if request.method == "initialize" {
+                state
+                    .sessions
+                    .record_protocol_version(&session_id, response.result.as_ref());
+                if let Some(negotiated) = state.sessions.protocol_version(&session_id) {
+                    if let Some(metrics) = &state.metrics {
+                        metrics.record_protocol_version(&negotiated).await;
                     }
+                    protocol_version = negotiated;
                 }
-            } else {
-                // Wrong session
-                error!(
-                    "Received response for wrong session: expected {}, got {}",
-                    session_id, response_session_id
-                );
-                Json(JsonRpcResponse::error(
-                    JsonRpcError::new(-32000, "Session mismatch".to_string()),
-                    request.id,
-                ))
-                .into_response()
             }
+            (
+                StatusCode::OK,
+                [
+                    ("mcp-session-id", response_session_id),
+                    ("mcp-protocol-version", protocol_version),
+                ],
+                Json(response),
+            )
+                .into_response()
         }
         Ok(Err(e)) => {
             error!("Failed to receive response: {}", e);
@@ -591,6 +839,32 @@ async fn handle_jsonrpc_request(
     }
 }
 
+/// Wait for the server's response to request `id` in `session_id`
+///
+/// Other messages on the channel, such as requests the server sends to the
+/// client while handling this one, are left for the SSE stream.
+async fn wait_for_response(
+    receiver: &mut broadcast::Receiver<(String, JsonRpcMessage)>,
+    session_id: &str,
+    id: Option<&RequestId>,
+) -> std::result::Result<(String, JsonRpcResponse), broadcast::error::RecvError> {
+    loop {
+        match receiver.recv().await {
+            Ok((response_session_id, JsonRpcMessage::Response(response)))
+                if (response_session_id == session_id || response_session_id == "*")
+                    && response.id.as_ref() == id =>
+            {
+                return Ok((response_session_id, response));
+            }
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Response receiver lagged, skipped {} messages", skipped);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 /// Handle notifications and responses
 async fn handle_notification_or_response(
     state: Arc<HttpTransportState>,
@@ -614,52 +888,192 @@ async fn handle_notification_or_response(
     (StatusCode::ACCEPTED, [("mcp-session-id", session_id)]).into_response()
 }
 
+/// Answer for a session that was evicted
+fn session_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(JsonRpcResponse::error(
+            JsonRpcError::new(-32000, "Session not found".to_string()),
+            None,
+        )),
+    )
+        .into_response()
+}
+
+/// Drop evicted sessions from the session store
+///
+/// The session manager only remembers an eviction until then, so the
+/// evicted IDs do not pile up.
+async fn remove_evicted_sessions(state: HttpTransportState) {
+    let mut evictions = state.sessions.subscribe_evictions();
+    loop {
+        match evictions.recv().await {
+            Ok(session_id) => {
+                info!("Evicted session: {}", session_id);
+                match state.session_store.remove_session(&session_id).await {
+                    Ok(()) => state.sessions.forget_evicted(&session_id),
+                    Err(e) => error!("Failed to remove session {}: {}", session_id, e),
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Eviction listener lagged, skipped {} sessions", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Record messages for sessions in the session store and pass them on to
+/// the SSE streams with their event IDs
+///
+/// Recording happens whether or not the session has a stream open, so a
+/// client that reconnects can be sent what it missed.
+async fn record_stream_events(state: HttpTransportState) {
+    let mut receiver = state.response_sender.subscribe();
+    loop {
+        let (session_id, message) = match receiver.recv().await {
+            Ok(item) => item,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Event recorder lagged, skipped {} messages", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let event_id = if state.config.enable_sse_resumability && session_id != "*" {
+            match state
+                .session_store
+                .append_event(&session_id, &message)
+                .await
+            {
+                Ok(event_id) => Some(event_id),
+                Err(e) => {
+                    error!("Failed to record event for session {}: {}", session_id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let _ = state.event_sender.send((session_id, event_id, message));
+    }
+}
+
+fn sse_event(event_id: Option<String>, message: &JsonRpcMessage) -> Event {
+    let event = Event::default().data(serde_json::to_string(message).unwrap_or_default());
+    match event_id {
+        Some(event_id) => event.id(event_id),
+        None => event,
+    }
+}
+
 /// Create SSE stream for server-to-client communication
-fn create_sse_stream(
+///
+/// When resuming from `last_event_id`, the session's recorded events after
+/// it are sent first; live events up to the last replayed one are skipped.
+async fn create_sse_stream(
     state: Arc<HttpTransportState>,
     session_id: String,
     last_event_id: Option<String>,
 ) -> impl Stream<Item = std::result::Result<Event, axum::Error>> {
-    let response_receiver = state.response_sender.subscribe();
-    let enable_resumability = state.config.enable_sse_resumability;
-
-    stream::unfold(
-        (
-            response_receiver,
-            session_id,
-            last_event_id,
-            enable_resumability,
-        ),
-        |(mut receiver, session_id, last_event_id, enable_resumability)| async move {
-            match receiver.recv().await {
-                Ok((msg_session_id, message)) => {
-                    if msg_session_id == session_id || msg_session_id == "*" {
-                        let event_data = serde_json::to_string(&message).unwrap_or_default();
-                        let mut event = Event::default().data(event_data);
-
-                        // Add event ID for resumability if enabled
-                        if enable_resumability {
-                            let event_id = generate_event_id();
-                            event = event.id(event_id);
-                        }
+    let receiver = state.event_sender.subscribe();
+    let mut evictions = state.sessions.subscribe_evictions();
+
+    let replayed = match &last_event_id {
+        Some(last_event_id) if state.config.enable_sse_resumability => {
+            match state
+                .session_store
+                .events_after(&session_id, last_event_id)
+                .await
+            {
+                Ok(events) => events,
+                Err(e) => {
+                    error!("Failed to load events for session {}: {}", session_id, e);
+                    Vec::new()
+                }
+            }
+        }
+        _ => Vec::new(),
+    };
+    let replayed_up_to = replayed
+        .last()
+        .and_then(|event| event.id.parse::<u64>().ok())
+        .or_else(|| last_event_id.as_deref().and_then(|id| id.parse().ok()))
+        .unwrap_or(0);
+    let replay = stream::iter(
+        replayed
+            .into_iter()
+            .map(|event| Ok(sse_event(Some(event.id), &event.message))),
+    );
 
-                        // Add keep-alive comment
-                        event = event.comment("keep-alive");
+    let evicted = {
+        let state = state.clone();
+        let session_id = session_id.clone();
+        async move {
+            loop {
+                match evictions.recv().await {
+                    Ok(evicted) if evicted == session_id => break,
+                    Ok(_) => {}
+                    // The eviction may have been missed, and forgotten once
+                    // the session store removed the session
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        if state.sessions.is_evicted(&session_id)
+                            || matches!(
+                                state.session_store.get_session(&session_id).await,
+                                Ok(None)
+                            )
+                        {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+                }
+            }
+        }
+    };
 
-                        Some((
-                            Ok(event),
-                            (receiver, session_id, last_event_id, enable_resumability),
-                        ))
-                    } else {
-                        // Skip messages for other sessions, send keep-alive comment
-                        Some((
+    let live = stream::unfold(
+        (receiver, session_id),
+        move |(mut receiver, session_id)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok((msg_session_id, event_id, message))
+                        if msg_session_id == session_id || msg_session_id == "*" =>
+                    {
+                        let already_sent = event_id
+                            .as_deref()
+                            .and_then(|id| id.parse::<u64>().ok())
+                            .is_some_and(|id| id <= replayed_up_to);
+                        if already_sent {
+                            continue;
+                        }
+                        let event = sse_event(event_id, &message).comment("keep-alive");
+                        return Some((Ok(event), (receiver, session_id)));
+                    }
+                    // Skip messages for other sessions, send keep-alive comment
+                    Ok(_) => {
+                        return Some((
                             Ok(Event::default().comment("keep-alive")),
-                            (receiver, session_id, last_event_id, enable_resumability),
-                        ))
+                            (receiver, session_id),
+                        ));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("SSE stream lagged, skipped {} messages", skipped);
                     }
+                    Err(broadcast::error::RecvError::Closed) => return None, // Connection closed
                 }
-                Err(_) => None, // Connection closed
             }
         },
-    )
+    );
+
+    let mut closing = state.closing.subscribe();
+    let closed = async move {
+        let _ = closing.wait_for(|closing| *closing).await;
+    };
+    let ended = async move {
+        tokio::select! {
+            _ = closed => {}
+            _ = evicted => {}
+        }
+    };
+    replay.chain(live).take_until(ended)
 }
@@ -0,0 +1,652 @@
+//! Session persistence for the Streamable HTTP server
+//!
+//! The server records every message it sends to a session in a
+//! [`SessionStore`], numbering them with per-session event IDs. A client that
+//! reconnects its SSE stream with a `Last-Event-ID` header first receives the
+//! recorded messages it missed, then the live stream.
+//!
+//! [`InMemorySessionStore`] is the default and is lost on restart. With the
+//! `session-file` feature, [`FileSessionStore`] keeps each session in a JSON
+//! file so sessions and their events survive a restart.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use ultrafast_mcp_core::protocol::jsonrpc::JsonRpcMessage;
+
+use crate::{Result, TransportError};
+
+/// Events kept per session by default, oldest dropped first
+pub const DEFAULT_MAX_EVENTS: usize = 1000;
+
+/// Session information for tracking and resumability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub created_at: SystemTime,
+    pub last_event_id: Option<String>,
+    pub active_streams: HashSet<String>,
+    /// Address the session was opened from
+    #[serde(default)]
+    pub remote_address: Option<String>,
+}
+
+impl SessionInfo {
+    pub fn new() -> Self {
+        Self {
+            created_at: SystemTime::now(),
+            last_event_id: None,
+            active_streams: HashSet::new(),
+            remote_address: None,
+        }
+    }
+}
+
+impl Default for SessionInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A message recorded on a session's stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredEvent {
+    pub id: String,
+    pub message: JsonRpcMessage,
+}
+
+/// Storage for Streamable HTTP sessions and the events sent on them
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Create a session, keeping it unchanged if it already exists
+    async fn create_session(&self, session_id: String) -> Result<()>;
+
+    async fn get_session(&self, session_id: &str) -> Result<Option<SessionInfo>>;
+
+    async fn remove_session(&self, session_id: &str) -> Result<()>;
+
+    /// Record the address a session was opened from
+    ///
+    /// Fails for sessions that do not exist. The default discards the
+    /// address, for stores that do not track addresses.
+    async fn set_remote_address(&self, _session_id: &str, _remote_address: String) -> Result<()> {
+        Ok(())
+    }
+
+    /// Record a message sent to a session, returning its event ID
+    ///
+    /// Event IDs increase within a session. Fails for sessions that do not
+    /// exist, so a session that was removed is not brought back.
+    async fn append_event(&self, session_id: &str, message: &JsonRpcMessage) -> Result<String>;
+
+    /// Events recorded after `last_event_id`, oldest first
+    ///
+    /// Returns nothing for unknown sessions and IDs this store did not issue.
+    async fn events_after(&self, session_id: &str, last_event_id: &str)
+    -> Result<Vec<StoredEvent>>;
+}
+
+/// A session and its most recent events
+#[derive(Debug, Clone, Default)]
+struct SessionRecord {
+    info: SessionInfo,
+    next_event: u64,
+    events: VecDeque<StoredEvent>,
+}
+
+impl SessionRecord {
+    fn append(&mut self, message: &JsonRpcMessage, max_events: usize) -> String {
+        self.next_event += 1;
+        let id = self.next_event.to_string();
+        self.events.push_back(StoredEvent {
+            id: id.clone(),
+            message: message.clone(),
+        });
+        while self.events.len() > max_events {
+            self.events.pop_front();
+        }
+        self.info.last_event_id = Some(id.clone());
+        id
+    }
+
+    fn events_after(&self, last_event_id: &str) -> Vec<StoredEvent> {
+        let Ok(last) = last_event_id.parse::<u64>() else {
+            return Vec::new();
+        };
+        self.events
+            .iter()
+            .filter(|event| event.id.parse::<u64>().is_ok_and(|id| id > last))
+            .cloned()
+            .collect()
+    }
+}
+
+fn unknown_session(session_id: &str) -> TransportError {
+    TransportError::InternalError {
+        message: format!("Session {session_id} does not exist"),
+    }
+}
+
+/// Sessions held in process memory
+#[derive(Debug)]
+pub struct InMemorySessionStore {
+    sessions: RwLock<HashMap<String, SessionRecord>>,
+    max_events: usize,
+}
+
+impl InMemorySessionStore {
+    pub fn new() -> Self {
+        Self {
+            sessions: RwLock::new(HashMap::new()),
+            max_events: DEFAULT_MAX_EVENTS,
+        }
+    }
+
+    /// Keep at most `max_events` events per session for replay
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = max_events;
+        self
+    }
+}
+
+impl Default for InMemorySessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn create_session(&self, session_id: String) -> Result<()> {
+        self.sessions.write().await.entry(session_id).or_default();
+        Ok(())
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<Option<SessionInfo>> {
+        Ok(self
+            .sessions
+            .read()
+            .await
+            .get(session_id)
+            .map(|record| record.info.clone()))
+    }
+
+    async fn remove_session(&self, session_id: &str) -> Result<()> {
+        self.sessions.write().await.remove(session_id);
+        Ok(())
+    }
+
+    async fn set_remote_address(&self, session_id: &str, remote_address: String) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        let record = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| unknown_session(session_id))?;
+        record.info.remote_address = Some(remote_address);
+        Ok(())
+    }
+
+    async fn append_event(&self, session_id: &str, message: &JsonRpcMessage) -> Result<String> {
+        let mut sessions = self.sessions.write().await;
+        let record = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| unknown_session(session_id))?;
+        Ok(record.append(message, self.max_events))
+    }
+
+    async fn events_after(
+        &self,
+        session_id: &str,
+        last_event_id: &str,
+    ) -> Result<Vec<StoredEvent>> {
+        Ok(self
+            .sessions
+            .read()
+            .await
+            .get(session_id)
+            .map(|record| record.events_after(last_event_id))
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "session-file")]
+pub use file::FileSessionStore;
+
+#[cfg(feature = "session-file")]
+mod file {
+    use std::io::{BufRead, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Sessions kept as files in a directory
+    ///
+    /// Each session has a JSON file holding its [`SessionInfo`] and a log
+    /// its events are appended to, one JSON line each, so recording an
+    /// event writes only that event. The log is rewritten with the most
+    /// recent events once it holds twice as many as are kept. File names
+    /// are the hex-encoded session IDs.
+    ///
+    /// Every change is written through, so the directory can be shared with
+    /// the next process after a restart.
+    #[derive(Debug)]
+    pub struct FileSessionStore {
+        dir: PathBuf,
+        max_events: usize,
+        // One lock per session, so sessions are written concurrently; holds
+        // the state of its event log once read
+        sessions: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Option<EventLog>>>>>,
+    }
+
+    /// Where a session's event log stands
+    #[derive(Debug, Clone, Copy)]
+    struct EventLog {
+        last_event: u64,
+        lines: usize,
+    }
+
+    impl FileSessionStore {
+        /// Store sessions in `dir`, creating it if needed
+        pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+            let dir = dir.into();
+            std::fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+            Ok(Self {
+                dir,
+                max_events: DEFAULT_MAX_EVENTS,
+                sessions: Mutex::new(HashMap::new()),
+            })
+        }
+
+        /// Keep at most `max_events` events per session for replay
+        pub fn with_max_events(mut self, max_events: usize) -> Self {
+            self.max_events = max_events;
+            self
+        }
+
+        fn path(&self, session_id: &str, extension: &str) -> PathBuf {
+            let name: String = session_id
+                .bytes()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            self.dir.join(format!("{name}.{extension}"))
+        }
+
+        fn info_path(&self, session_id: &str) -> PathBuf {
+            self.path(session_id, "json")
+        }
+
+        fn events_path(&self, session_id: &str) -> PathBuf {
+            self.path(session_id, "events")
+        }
+
+        fn session_lock(&self, session_id: &str) -> Arc<tokio::sync::Mutex<Option<EventLog>>> {
+            self.sessions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(session_id.to_string())
+                .or_default()
+                .clone()
+        }
+
+        /// The lock of a session that exists, possibly from before a restart
+        async fn existing_session_lock(
+            &self,
+            session_id: &str,
+        ) -> Result<Arc<tokio::sync::Mutex<Option<EventLog>>>> {
+            let known = self
+                .sessions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(session_id)
+                .cloned();
+            match known {
+                Some(lock) => Ok(lock),
+                None if self.load_info(session_id).await?.is_some() => {
+                    Ok(self.session_lock(session_id))
+                }
+                None => Err(unknown_session(session_id)),
+            }
+        }
+
+        async fn load_info(&self, session_id: &str) -> Result<Option<SessionInfo>> {
+            let path = self.info_path(session_id);
+            blocking(move || match std::fs::read(&path) {
+                Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(|e| {
+                    TransportError::SerializationError {
+                        message: format!("Invalid session file {}: {e}", path.display()),
+                    }
+                }),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(io_error(&path, e)),
+            })
+            .await
+        }
+
+        async fn save_info(&self, session_id: &str, info: &SessionInfo) -> Result<()> {
+            let path = self.info_path(session_id);
+            let bytes =
+                serde_json::to_vec(info).map_err(|e| TransportError::SerializationError {
+                    message: e.to_string(),
+                })?;
+            blocking(move || write_atomically(&path, &bytes)).await
+        }
+
+        async fn load_events(&self, session_id: &str) -> Result<Vec<StoredEvent>> {
+            let path = self.events_path(session_id);
+            blocking(move || read_events(&path)).await
+        }
+
+        /// The state of a session's event log, read on first use
+        ///
+        /// Fails for sessions that do not exist.
+        async fn event_log(
+            &self,
+            session_id: &str,
+            log: &mut Option<EventLog>,
+        ) -> Result<EventLog> {
+            if let Some(log) = log {
+                return Ok(*log);
+            }
+            if self.load_info(session_id).await?.is_none() {
+                return Err(unknown_session(session_id));
+            }
+            let events = self.load_events(session_id).await?;
+            let loaded = EventLog {
+                last_event: events
+                    .last()
+                    .and_then(|event| event.id.parse().ok())
+                    .unwrap_or(0),
+                lines: events.len(),
+            };
+            *log = Some(loaded);
+            Ok(loaded)
+        }
+    }
+
+    async fn blocking<T: Send + 'static>(
+        f: impl FnOnce() -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        tokio::task::spawn_blocking(f).await.map_err(join_error)?
+    }
+
+    /// Write then rename so a crash never leaves a partial file
+    fn write_atomically(path: &Path, bytes: &[u8]) -> Result<()> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        std::fs::write(&temp, bytes).map_err(|e| io_error(&temp, e))?;
+        std::fs::rename(&temp, path).map_err(|e| io_error(path, e))
+    }
+
+    /// The events of a log, skipping a line cut short by a crash
+    fn read_events(path: &Path) -> Result<Vec<StoredEvent>> {
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(path, e)),
+        };
+        let mut events = Vec::new();
+        for line in std::io::BufReader::new(file).lines() {
+            let line = line.map_err(|e| io_error(path, e))?;
+            if let Ok(event) = serde_json::from_str(&line) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn remove_file(path: &Path) -> Result<()> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(path, e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn io_error(path: &Path, e: std::io::Error) -> TransportError {
+        TransportError::InternalError {
+            message: format!("Session file {}: {e}", path.display()),
+        }
+    }
+
+    fn join_error(e: tokio::task::JoinError) -> TransportError {
+        TransportError::InternalError {
+            message: format!("Session file task failed: {e}"),
+        }
+    }
+
+    #[async_trait]
+    impl SessionStore for FileSessionStore {
+        async fn create_session(&self, session_id: String) -> Result<()> {
+            let lock = self.session_lock(&session_id);
+            let _log = lock.lock().await;
+            if self.load_info(&session_id).await?.is_none() {
+                self.save_info(&session_id, &SessionInfo::new()).await?;
+            }
+            Ok(())
+        }
+
+        async fn get_session(&self, session_id: &str) -> Result<Option<SessionInfo>> {
+            // Both files are replaced or appended to, so they can be read
+            // without the session's lock
+            let Some(mut info) = self.load_info(session_id).await? else {
+                return Ok(None);
+            };
+            let events = self.load_events(session_id).await?;
+            info.last_event_id = events.last().map(|event| event.id.clone());
+            Ok(Some(info))
+        }
+
+        async fn remove_session(&self, session_id: &str) -> Result<()> {
+            let lock = self.session_lock(session_id);
+            let mut log = lock.lock().await;
+            // Appends waiting on the lock find the session gone
+            *log = None;
+            let info = self.info_path(session_id);
+            let events = self.events_path(session_id);
+            blocking(move || {
+                remove_file(&events)?;
+                remove_file(&info)
+            })
+            .await?;
+            self.sessions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(session_id);
+            Ok(())
+        }
+
+        async fn set_remote_address(&self, session_id: &str, remote_address: String) -> Result<()> {
+            let lock = self.existing_session_lock(session_id).await?;
+            let _log = lock.lock().await;
+            let mut info = self
+                .load_info(session_id)
+                .await?
+                .ok_or_else(|| unknown_session(session_id))?;
+            info.remote_address = Some(remote_address);
+            self.save_info(session_id, &info).await
+        }
+
+        async fn append_event(&self, session_id: &str, message: &JsonRpcMessage) -> Result<String> {
+            let lock = self.existing_session_lock(session_id).await?;
+            let mut log = lock.lock().await;
+            let mut state = self.event_log(session_id, &mut log).await?;
+            state.last_event += 1;
+            let event = StoredEvent {
+                id: state.last_event.to_string(),
+                message: message.clone(),
+            };
+            let mut line =
+                serde_json::to_vec(&event).map_err(|e| TransportError::SerializationError {
+                    message: e.to_string(),
+                })?;
+            line.push(b'\n');
+
+            let path = self.events_path(session_id);
+            let compact = state.lines >= self.max_events.saturating_mul(2);
+            let max_events = self.max_events;
+            state.lines = blocking(move || {
+                if compact {
+                    // Keep the most recent events, this one included
+                    let events = read_events(&path)?;
+                    let kept = &events[events.len().saturating_sub(max_events.saturating_sub(1))..];
+                    let mut bytes = Vec::new();
+                    for event in kept {
+                        serde_json::to_writer(&mut bytes, event).map_err(|e| {
+                            TransportError::SerializationError {
+                                message: e.to_string(),
+                            }
+                        })?;
+                        bytes.push(b'\n');
+                    }
+                    bytes.extend_from_slice(&line);
+                    write_atomically(&path, &bytes)?;
+                    Ok(kept.len() + 1)
+                } else {
+                    std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&path)
+                        .and_then(|mut file| file.write_all(&line))
+                        .map_err(|e| io_error(&path, e))?;
+                    Ok(state.lines + 1)
+                }
+            })
+            .await?;
+            *log = Some(state);
+            Ok(event.id)
+        }
+
+        async fn events_after(
+            &self,
+            session_id: &str,
+            last_event_id: &str,
+        ) -> Result<Vec<StoredEvent>> {
+            let Ok(last) = last_event_id.parse::<u64>() else {
+                return Ok(Vec::new());
+            };
+            let mut events = self.load_events(session_id).await?;
+            events.retain(|event| event.id.parse::<u64>().is_ok_and(|id| id > last));
+            // The log may hold up to twice as many events as are kept
+            let excess = events.len().saturating_sub(self.max_events);
+            events.drain(..excess);
+            Ok(events)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ultrafast_mcp_core::protocol::jsonrpc::{JsonRpcRequest, RequestId};
+
+    fn message(n: i64) -> JsonRpcMessage {
+        JsonRpcMessage::Request(JsonRpcRequest::new(
+            "ping".to_string(),
+            None,
+            Some(RequestId::number(n)),
+        ))
+    }
+
+    async fn assert_replays_missed_events(store: &dyn SessionStore) {
+        store.create_session("s1".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for n in 0..5 {
+            ids.push(store.append_event("s1", &message(n)).await.unwrap());
+        }
+
+        let missed = store.events_after("s1", &ids[1]).await.unwrap();
+        assert_eq!(
+            missed.iter().map(|event| &event.id).collect::<Vec<_>>(),
+            ids[2..].iter().collect::<Vec<_>>()
+        );
+        assert_eq!(missed[0].message, message(2));
+        // Only the three most recent events are kept
+        assert_eq!(store.events_after("s1", "0").await.unwrap().len(), 3);
+        assert!(
+            store
+                .events_after("s1", "unknown")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(store.events_after("s2", &ids[0]).await.unwrap().is_empty());
+
+        store
+            .set_remote_address("s1", "127.0.0.1:4000".to_string())
+            .await
+            .unwrap();
+        let info = store.get_session("s1").await.unwrap().unwrap();
+        assert_eq!(info.last_event_id.as_ref(), ids.last());
+        assert_eq!(info.remote_address.as_deref(), Some("127.0.0.1:4000"));
+        store.remove_session("s1").await.unwrap();
+        assert!(store.get_session("s1").await.unwrap().is_none());
+
+        // Removed and unknown sessions are not brought back
+        assert!(store.append_event("s1", &message(5)).await.is_err());
+        assert!(
+            store
+                .set_remote_address("s2", "127.0.0.1:4000".to_string())
+                .await
+                .is_err()
+        );
+        assert!(store.get_session("s1").await.unwrap().is_none());
+        assert!(store.get_session("s2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_replays_missed_events() {
+        let store = InMemorySessionStore::new().with_max_events(3);
+        assert_replays_missed_events(&store).await;
+    }
+
+    #[cfg(feature = "session-file")]
+    #[tokio::test]
+    async fn test_file_store_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSessionStore::new(dir.path())
+            .unwrap()
+            .with_max_events(3);
+        assert_replays_missed_events(&store).await;
+
+        store.create_session("a/b".to_string()).await.unwrap();
+        let last = store.append_event("a/b", &message(7)).await.unwrap();
+        drop(store);
+        let restarted = FileSessionStore::new(dir.path()).unwrap();
+        assert!(restarted.get_session("a/b").await.unwrap().is_some());
+        let next = restarted.append_event("a/b", &message(8)).await.unwrap();
+        assert_eq!(
+            restarted.events_after("a/b", &last).await.unwrap(),
+            vec![StoredEvent {
+                id: next,
+                message: message(8),
+            }]
+        );
+    }
+
+    #[cfg(feature = "session-file")]
+    #[tokio::test]
+    async fn test_file_store_log_stays_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSessionStore::new(dir.path())
+            .unwrap()
+            .with_max_events(3);
+        store.create_session("s1".to_string()).await.unwrap();
+        let mut ids = Vec::new();
+        for n in 0..20 {
+            ids.push(store.append_event("s1", &message(n)).await.unwrap());
+        }
+
+        let log = std::fs::read_to_string(dir.path().join("7331.events")).unwrap();
+        assert!(log.lines().count() <= 6, "{log}");
+        let kept = store.events_after("s1", "0").await.unwrap();
+        assert_eq!(
+            kept.iter().map(|event| &event.id).collect::<Vec<_>>(),
+            ids[17..].iter().collect::<Vec<_>>()
+        );
+        assert_eq!(kept[2].message, message(19));
+        let info = store.get_session("s1").await.unwrap().unwrap();
+        assert_eq!(info.last_event_id.as_ref(), ids.last());
+    }
+}
@@ -0,0 +1,325 @@
+//! Addressing individual clients of a Streamable HTTP server
+//!
+//! Every client of an [`HttpTransportServer`] has its own session. The
+//! server's [`SessionManager`] lists the sessions that are currently open,
+//! with what is known about each one: the client's `initialize` info, the
+//! negotiated protocol version, the address it connected from and, with an
+//! [`IdentityResolver`], who it authenticated as. It sends notifications to a
+//! single session and evicts sessions: an evicted session's event streams
+//! are ended and further requests with its session ID are answered with
+//! `404 Not Found`, so the client has to initialize a new session.
+//!
+//! [`HttpTransportServer`]: super::HttpTransportServer
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use axum::http::HeaderMap;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use ultrafast_mcp_core::{
+    protocol::jsonrpc::{JsonRpcMessage, JsonRpcRequest},
+    types::client::ClientInfo,
+};
+
+use crate::{Result, TransportError};
+
+/// Works out who a client authenticated as from the headers of its
+/// `initialize` request, such as the subject of its bearer token
+pub type IdentityResolver = Arc<dyn Fn(&HeaderMap) -> Option<String> + Send + Sync>;
+
+/// What is known about an open session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMetadata {
+    pub session_id: String,
+    pub created_at: SystemTime,
+    /// When the client last sent a message in this session
+    pub last_seen: SystemTime,
+    /// The `clientInfo` of the client's `initialize` request
+    pub client_info: Option<ClientInfo>,
+    /// Protocol version the server answered `initialize` with
+    pub protocol_version: Option<String>,
+    /// Address the session was opened from
+    pub remote_address: Option<String>,
+    /// Who the client authenticated as, see [`IdentityResolver`]
+    pub auth_identity: Option<String>,
+}
+
+impl SessionMetadata {
+    fn new(session_id: String) -> Self {
+        let now = SystemTime::now();
+        Self {
+            session_id,
+            created_at: now,
+            last_seen: now,
+            client_info: None,
+            protocol_version: None,
+            remote_address: None,
+            auth_identity: None,
+        }
+    }
+}
+
+/// Open sessions of a Streamable HTTP server
+///
+/// Cloning the manager shares it.
+#[derive(Clone)]
+pub struct SessionManager {
+    inner: Arc<SessionsInner>,
+}
+
+struct SessionsInner {
+    sessions: Mutex<HashMap<String, SessionMetadata>>,
+    evicted: Mutex<HashSet<String>>,
+    outgoing: broadcast::Sender<(String, JsonRpcMessage)>,
+    evictions: broadcast::Sender<String>,
+    closed: broadcast::Sender<String>,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        let (outgoing, _) = broadcast::channel(1000);
+        let (evictions, _) = broadcast::channel(100);
+        let (closed, _) = broadcast::channel(100);
+        Self {
+            inner: Arc::new(SessionsInner {
+                sessions: Mutex::new(HashMap::new()),
+                evicted: Mutex::new(HashSet::new()),
+                outgoing,
+                evictions,
+                closed,
+            }),
+        }
+    }
+
+    fn lock_sessions(&self) -> MutexGuard<'_, HashMap<String, SessionMetadata>> {
+        self.inner
+            .sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_evicted(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.inner.evicted.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The open sessions, oldest first
+    pub fn sessions(&self) -> Vec<SessionMetadata> {
+        let mut sessions: Vec<_> = self.lock_sessions().values().cloned().collect();
+        sessions.sort_by(|a, b| (a.created_at, &a.session_id).cmp(&(b.created_at, &b.session_id)));
+        sessions
+    }
+
+    pub fn session(&self, session_id: &str) -> Option<SessionMetadata> {
+        self.lock_sessions().get(session_id).cloned()
+    }
+
+    /// The protocol version negotiated in a session, once it is initialized
+    pub fn protocol_version(&self, session_id: &str) -> Option<String> {
+        self.lock_sessions()
+            .get(session_id)
+            .and_then(|session| session.protocol_version.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_sessions().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Send a notification to the client of one session
+    ///
+    /// It goes out on the session's event stream, or is recorded for replay
+    /// until the client opens one.
+    pub fn notify_session(
+        &self,
+        session_id: &str,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<()> {
+        let notification = JsonRpcRequest::notification(method.to_string(), params);
+        self.send_to_session(session_id, JsonRpcMessage::Notification(notification))
+    }
+
+    /// Send any message to the client of one session
+    pub fn send_to_session(&self, session_id: &str, message: JsonRpcMessage) -> Result<()> {
+        if !self.lock_sessions().contains_key(session_id) {
+            return Err(TransportError::ConnectionError {
+                message: format!("Unknown session: {session_id}"),
+            });
+        }
+        self.inner
+            .outgoing
+            .send((session_id.to_string(), message))
+            .map(|_| ())
+            .map_err(|_| TransportError::ConnectionClosed)
+    }
+
+    /// Close a session
+    ///
+    /// Its event streams end, and requests with its session ID are answered
+    /// with `404 Not Found` from now on. Returns false for sessions that are
+    /// not open.
+    pub fn evict(&self, session_id: &str) -> bool {
+        if self.lock_sessions().remove(session_id).is_none() {
+            return false;
+        }
+        self.lock_evicted().insert(session_id.to_string());
+        let _ = self.inner.evictions.send(session_id.to_string());
+        let _ = self.inner.closed.send(session_id.to_string());
+        true
+    }
+
+    /// Whether a session was evicted and the server has not yet removed it
+    /// from its session store
+    ///
+    /// Once removed, the session's ID is simply unknown, which is answered
+    /// with `404 Not Found` just the same, so the eviction is forgotten.
+    pub fn is_evicted(&self, session_id: &str) -> bool {
+        self.lock_evicted().contains(session_id)
+    }
+
+    /// Forget the eviction of a session the session store has removed
+    pub(crate) fn forget_evicted(&self, session_id: &str) {
+        self.lock_evicted().remove(session_id);
+    }
+
+    /// IDs of sessions as they are evicted
+    pub fn subscribe_evictions(&self) -> broadcast::Receiver<String> {
+        self.inner.evictions.subscribe()
+    }
+
+    /// IDs of sessions as they end, because the client terminated them or
+    /// they were evicted
+    pub fn subscribe_closed(&self) -> broadcast::Receiver<String> {
+        self.inner.closed.subscribe()
+    }
+
+    /// Channel carrying the messages the server sends to its sessions
+    pub(crate) fn outgoing(&self) -> &broadcast::Sender<(String, JsonRpcMessage)> {
+        &self.inner.outgoing
+    }
+
+    /// Record a message received in a session, opening it if needed
+    pub(crate) fn touch(&self, session_id: &str, remote_address: Option<String>) {
+        let mut sessions = self.lock_sessions();
+        let session = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionMetadata::new(session_id.to_string()));
+        session.last_seen = SystemTime::now();
+        if session.remote_address.is_none() {
+            session.remote_address = remote_address;
+        }
+    }
+
+    /// Record the client's `initialize` request
+    pub(crate) fn record_initialize(
+        &self,
+        session_id: &str,
+        params: Option<&Value>,
+        auth_identity: Option<String>,
+    ) {
+        let client_info = params
+            .and_then(|params| params.get("clientInfo"))
+            .and_then(|info| serde_json::from_value(info.clone()).ok());
+        if let Some(session) = self.lock_sessions().get_mut(session_id) {
+            session.client_info = client_info;
+            session.auth_identity = auth_identity;
+        }
+    }
+
+    /// Record the protocol version the server answered `initialize` with
+    pub(crate) fn record_protocol_version(&self, session_id: &str, result: Option<&Value>) {
+        let version = result
+            .and_then(|result| result.get("protocolVersion"))
+            .and_then(Value::as_str);
+        if let (Some(session), Some(version)) = (self.lock_sessions().get_mut(session_id), version)
+        {
+            session.protocol_version = Some(version.to_string());
+        }
+    }
+
+    /// Forget a session the client terminated
+    pub(crate) fn remove(&self, session_id: &str) {
+        if self.lock_sessions().remove(session_id).is_some() {
+            let _ = self.inner.closed.send(session_id.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_sessions_are_addressed_and_evicted() {
+        let manager = SessionManager::new();
+        let mut outgoing = manager.outgoing().subscribe();
+        let mut evictions = manager.subscribe_evictions();
+        let mut closed = manager.subscribe_closed();
+
+        manager.touch("a", Some("127.0.0.1:5000".to_string()));
+        manager.record_initialize(
+            "a",
+            Some(&json!({"clientInfo": {"name": "inspector", "version": "1.0"}})),
+            Some("alice".to_string()),
+        );
+        manager.record_protocol_version("a", Some(&json!({"protocolVersion": "2025-06-18"})));
+        manager.touch("b", None);
+
+        let a = manager.session("a").unwrap();
+        assert_eq!(a.client_info.unwrap().name, "inspector");
+        assert_eq!(a.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(manager.protocol_version("a").as_deref(), Some("2025-06-18"));
+        assert_eq!(a.auth_identity.as_deref(), Some("alice"));
+        assert_eq!(a.remote_address.as_deref(), Some("127.0.0.1:5000"));
+        assert_eq!(manager.len(), 2);
+
+        manager
+            .notify_session("b", "notifications/message", None)
+            .unwrap();
+        let (session_id, message) = outgoing.recv().await.unwrap();
+        assert_eq!(session_id, "b");
+        assert!(
+            matches!(message, JsonRpcMessage::Notification(n) if n.method == "notifications/message")
+        );
+        assert!(
+            manager
+                .notify_session("c", "notifications/message", None)
+                .is_err()
+        );
+
+        assert!(manager.evict("a"));
+        assert!(!manager.evict("a"));
+        assert_eq!(evictions.recv().await.unwrap(), "a");
+        assert_eq!(closed.recv().await.unwrap(), "a");
+        assert!(manager.is_evicted("a"));
+        assert!(
+            manager
+                .notify_session("a", "notifications/message", None)
+                .is_err()
+        );
+        let open: Vec<_> = manager
+            .sessions()
+            .into_iter()
+            .map(|s| s.session_id)
+            .collect();
+        assert_eq!(open, ["b"]);
+
+        manager.remove("b");
+        assert_eq!(closed.recv().await.unwrap(), "b");
+        assert!(!manager.is_evicted("b"));
+    }
+}
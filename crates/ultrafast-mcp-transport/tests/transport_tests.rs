@@ -104,6 +104,507 @@ mod middleware_tests {
     }
 }
 
+#[cfg(test)]
+mod error_conversion_tests {
+    use ultrafast_mcp_core::error::{
+        AuthenticationError, MCPError, ProtocolError, TransportError as CoreTransportError,
+    };
+    use ultrafast_mcp_transport::{ConnectionState, TransportError};
+
+    #[test]
+    fn test_transport_error_maps_to_mcp_category() {
+        assert!(matches!(
+            MCPError::from(TransportError::ConnectionClosed),
+            MCPError::Transport(CoreTransportError::ConnectionClosed)
+        ));
+        assert!(matches!(
+            MCPError::from(TransportError::ConnectionTimeout),
+            MCPError::Transport(CoreTransportError::ConnectionTimeout)
+        ));
+        assert!(matches!(
+            MCPError::from(TransportError::SerializationError {
+                message: "bad json".to_string()
+            }),
+            MCPError::Protocol(ProtocolError::SerializationError(message)) if message == "bad json"
+        ));
+        assert!(matches!(
+            MCPError::from(TransportError::AuthenticationError {
+                message: "401".to_string(),
+                source: None,
+            }),
+            MCPError::Authentication(AuthenticationError::Failed { message, .. }) if message == "401"
+        ));
+        assert!(matches!(
+            MCPError::from(TransportError::NotReady {
+                state: ConnectionState::Connecting
+            }),
+            MCPError::Transport(CoreTransportError::NotReady(_))
+        ));
+    }
+
+    #[test]
+    fn test_transport_error_category_matches_conversion() {
+        let errors = vec![
+            TransportError::ConnectionClosed,
+            TransportError::ConnectionTimeout,
+            TransportError::NetworkError {
+                message: "reset".to_string(),
+                source: None,
+            },
+            TransportError::ProtocolError {
+                message: "bad".to_string(),
+            },
+            TransportError::AuthenticationError {
+                message: "401".to_string(),
+                source: None,
+            },
+            TransportError::RecoveryFailed {
+                attempts: 3,
+                message: "gave up".to_string(),
+            },
+            TransportError::ShutdownTimeout {
+                message: "still draining".to_string(),
+            },
+            TransportError::RateLimited {
+                message: "slow down".to_string(),
+                retry_after: None,
+                limit: None,
+            },
+        ];
+
+        for error in errors {
+            let (category, retryable) = (error.category(), error.is_retryable());
+            let converted = MCPError::from(error);
+            assert_eq!(converted.category(), category);
+            assert_eq!(converted.is_retryable(), retryable, "{converted}");
+        }
+
+        assert!(TransportError::ConnectionClosed.is_retryable());
+        assert!(TransportError::ConnectionTimeout.is_retryable());
+        assert!(
+            !TransportError::ProtocolError {
+                message: "bad".to_string()
+            }
+            .is_retryable()
+        );
+        assert!(
+            !TransportError::RecoveryFailed {
+                attempts: 3,
+                message: "gave up".to_string()
+            }
+            .is_retryable()
+        );
+    }
+
+    #[test]
+    fn test_retry_after_survives_conversion() {
+        let error = TransportError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after: Some(std::time::Duration::from_secs(2)),
+            limit: Some(30),
+        };
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), Some(std::time::Duration::from_secs(2)));
+        assert_eq!(
+            MCPError::from(error).retry_after(),
+            Some(std::time::Duration::from_secs(2))
+        );
+
+        let error = MCPError::from(TransportError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after: None,
+            limit: Some(30),
+        });
+        assert!(error.to_string().ends_with("Limit: 30"));
+        let error = MCPError::from(TransportError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after: None,
+            limit: None,
+        });
+        assert!(!error.to_string().contains("Limit"));
+
+        assert_eq!(TransportError::ConnectionClosed.retry_after(), None);
+    }
+
+    #[test]
+    fn test_question_mark_converts_transport_errors() {
+        fn connect() -> Result<(), MCPError> {
+            Err(TransportError::NetworkError {
+                message: "refused".to_string(),
+                source: None,
+            })?
+        }
+
+        assert!(matches!(
+            connect(),
+            Err(MCPError::Transport(CoreTransportError::Network { message, .. })) if message == "refused"
+        ));
+    }
+
+    #[test]
+    fn test_conversions_keep_the_source_error() {
+        use std::error::Error as _;
+
+        let err = MCPError::from(TransportError::NetworkError {
+            message: "reset".to_string(),
+            source: Some(Box::new(std::io::Error::from(
+                std::io::ErrorKind::ConnectionReset,
+            ))),
+        });
+        let MCPError::Transport(transport) = &err else {
+            panic!("unexpected mapping: {err:?}");
+        };
+        let source = transport.source().expect("source kept");
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::ConnectionReset)
+        );
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn test_auth_errors_stay_reachable_through_transport_errors() {
+        use std::error::Error as _;
+        use ultrafast_mcp_auth::AuthError;
+
+        let err = MCPError::from(TransportError::from(AuthError::InvalidToken(
+            "bad signature".to_string(),
+        )));
+        let MCPError::Authentication(auth) = &err else {
+            panic!("unexpected mapping: {err:?}");
+        };
+        let source = auth.source().expect("source kept");
+        assert_eq!(
+            source.downcast_ref::<AuthError>(),
+            Some(&AuthError::InvalidToken("bad signature".to_string()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod recovery_tests {
+    use async_trait::async_trait;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use ultrafast_mcp_core::protocol::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse};
+    use ultrafast_mcp_transport::{
+        CircuitBreakerConfig, CircuitState, HealthProbeConfig, RecoveringTransport, RecoveryConfig,
+        Result, Transport, TransportError, TransportEvent, TransportEventHandler,
+    };
+
+    /// Fails every operation until a scripted reconnect succeeds and answers
+    /// `ping` requests once connected
+    struct FlakyTransport {
+        connected: bool,
+        reconnect_results: VecDeque<bool>,
+        inbox: VecDeque<JsonRpcMessage>,
+    }
+
+    #[async_trait]
+    impl Transport for FlakyTransport {
+        async fn send_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+            if !self.connected {
+                return Err(TransportError::ConnectionClosed);
+            }
+            if let JsonRpcMessage::Request(request) = message {
+                if request.method == "ping" {
+                    self.inbox
+                        .push_back(JsonRpcMessage::Response(JsonRpcResponse::success(
+                            serde_json::json!({}),
+                            request.id,
+                        )));
+                }
+            }
+            Ok(())
+        }
+
+        async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+            match (self.connected, self.inbox.pop_front()) {
+                (true, Some(message)) => Ok(message),
+                _ => Err(TransportError::ConnectionClosed),
+            }
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            self.connected = false;
+            Ok(())
+        }
+
+        async fn reconnect(&mut self) -> Result<()> {
+            if self.reconnect_results.pop_front().unwrap_or(false) {
+                self.connected = true;
+                Ok(())
+            } else {
+                Err(TransportError::ConnectionError {
+                    message: "refused".to_string(),
+                })
+            }
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct EventLog(Arc<Mutex<Vec<TransportEvent>>>);
+
+    #[async_trait]
+    impl TransportEventHandler for EventLog {
+        async fn handle_event(&self, event: TransportEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn recovery_config() -> RecoveryConfig {
+        RecoveryConfig {
+            max_retries: 10,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            enable_jitter: false,
+            ..Default::default()
+        }
+    }
+
+    fn notification() -> JsonRpcMessage {
+        JsonRpcMessage::Notification(JsonRpcRequest::notification(
+            "notifications/progress".to_string(),
+            None,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_and_rejects_calls() {
+        let events = EventLog::default();
+        let inner = FlakyTransport {
+            connected: false,
+            reconnect_results: VecDeque::new(),
+            inbox: VecDeque::new(),
+        };
+        let mut transport = RecoveringTransport::new(
+            Box::new(inner),
+            RecoveryConfig {
+                circuit_breaker: Some(CircuitBreakerConfig {
+                    failure_threshold: 2,
+                    open_duration: Duration::from_secs(60),
+                }),
+                ..recovery_config()
+            },
+        )
+        .with_event_handler(Box::new(events.clone()));
+
+        assert!(transport.send_message(notification()).await.is_err());
+        assert_eq!(transport.circuit_state(), CircuitState::Closed);
+        assert!(transport.send_message(notification()).await.is_err());
+        assert_eq!(transport.circuit_state(), CircuitState::Open);
+
+        // Rejected without touching the inner transport
+        assert!(transport.send_message(notification()).await.is_err());
+
+        let metrics = transport.metrics();
+        assert_eq!(metrics.circuit_state, CircuitState::Open);
+        assert_eq!(metrics.failed_recoveries, 2);
+        assert_eq!(metrics.circuit_trips, 1);
+        assert_eq!(metrics.rejected_calls, 1);
+
+        let events = events.0.lock().unwrap();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            TransportEvent::CircuitStateChanged {
+                from: CircuitState::Closed,
+                to: CircuitState::Open
+            }
+        )));
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, TransportEvent::Reconnecting { attempt: 2, .. }))
+        );
+        assert!(events.iter().any(|e| matches!(
+            e,
+            TransportEvent::Error {
+                retryable: true,
+                ..
+            }
+        )));
+    }
+
+    /// Turns away the first `throttled` messages, asking for a pause before
+    /// the next
+    struct ThrottledTransport {
+        throttled: u32,
+        reconnects: Arc<Mutex<u32>>,
+    }
+
+    #[async_trait]
+    impl Transport for ThrottledTransport {
+        async fn send_message(&mut self, _message: JsonRpcMessage) -> Result<()> {
+            if self.throttled > 0 {
+                self.throttled -= 1;
+                return Err(TransportError::RateLimited {
+                    message: "slow down".to_string(),
+                    retry_after: Some(Duration::from_millis(50)),
+                    limit: None,
+                });
+            }
+            Ok(())
+        }
+
+        async fn receive_message(&mut self) -> Result<JsonRpcMessage> {
+            Err(TransportError::ConnectionClosed)
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn reconnect(&mut self) -> Result<()> {
+            *self.reconnects.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recovery_waits_as_long_as_the_peer_asks() {
+        let events = EventLog::default();
+        let reconnects = Arc::new(Mutex::new(0));
+        let mut transport = RecoveringTransport::new(
+            Box::new(ThrottledTransport {
+                throttled: 2,
+                reconnects: reconnects.clone(),
+            }),
+            RecoveryConfig {
+                circuit_breaker: Some(CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    open_duration: Duration::from_secs(60),
+                }),
+                ..recovery_config()
+            },
+        )
+        .with_event_handler(Box::new(events.clone()));
+
+        transport.send_message(notification()).await.unwrap();
+
+        // Waited twice on the same connection, without reconnecting or
+        // counting the rate limit as a failure
+        let events = events.0.lock().unwrap();
+        let waits: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                TransportEvent::Throttled { delay } => Some(*delay),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(waits, [Duration::from_millis(50); 2]);
+        assert!(
+            !events
+                .iter()
+                .any(|e| matches!(e, TransportEvent::Reconnecting { .. }))
+        );
+        assert_eq!(*reconnects.lock().unwrap(), 0);
+        assert_eq!(transport.circuit_state(), CircuitState::Closed);
+        assert_eq!(transport.metrics().recovery_attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_fail_after_max_retries() {
+        let mut transport = RecoveringTransport::new(
+            Box::new(ThrottledTransport {
+                throttled: u32::MAX,
+                reconnects: Arc::default(),
+            }),
+            RecoveryConfig {
+                max_retries: 1,
+                ..recovery_config()
+            },
+        );
+
+        let error = transport.send_message(notification()).await.unwrap_err();
+        assert!(matches!(error, TransportError::RateLimited { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_health_probe_confirms_reconnect_and_keeps_messages() {
+        let events = EventLog::default();
+        let inner = FlakyTransport {
+            connected: false,
+            reconnect_results: VecDeque::from([true]),
+            inbox: VecDeque::from([notification()]),
+        };
+        let mut transport = RecoveringTransport::new(
+            Box::new(inner),
+            RecoveryConfig {
+                health_probe: Some(HealthProbeConfig::default()),
+                ..recovery_config()
+            },
+        )
+        .with_event_handler(Box::new(events.clone()));
+
+        transport.send_message(notification()).await.unwrap();
+
+        // The notification received while probing is still delivered
+        assert!(matches!(
+            transport.receive_message().await.unwrap(),
+            JsonRpcMessage::Notification(_)
+        ));
+
+        let metrics = transport.metrics();
+        assert_eq!(metrics.probes_sent, 1);
+        assert_eq!(metrics.probes_failed, 0);
+        assert_eq!(metrics.successful_recoveries, 1);
+        assert!(events.0.lock().unwrap().iter().any(|e| matches!(
+            e,
+            TransportEvent::ProbeCompleted {
+                attempt: 1,
+                success: true,
+                ..
+            }
+        )));
+    }
+
+    #[cfg(feature = "monitoring")]
+    #[tokio::test]
+    async fn test_recovery_is_recorded_into_metrics() {
+        use ultrafast_mcp_monitoring::MetricsCollector;
+        use ultrafast_mcp_transport::RecoveryMetricsRecorder;
+
+        let collector = Arc::new(MetricsCollector::new());
+        let inner = FlakyTransport {
+            connected: false,
+            reconnect_results: VecDeque::from([false, true]),
+            inbox: VecDeque::new(),
+        };
+        let mut transport = RecoveringTransport::new(
+            Box::new(inner),
+            RecoveryConfig {
+                health_probe: Some(HealthProbeConfig::default()),
+                circuit_breaker: Some(CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    open_duration: Duration::ZERO,
+                }),
+                ..recovery_config()
+            },
+        )
+        .with_event_handler(Box::new(RecoveryMetricsRecorder::new(
+            collector.clone(),
+            "upstream",
+        )));
+
+        // The failed attempt opens the circuit; the next call is its
+        // half-open trial
+        assert!(transport.send_message(notification()).await.is_err());
+        transport.send_message(notification()).await.unwrap();
+
+        let metrics = collector.get_metrics().await;
+        let upstream = &metrics.recovery["upstream"];
+        assert_eq!(upstream.attempts, 2);
+        assert_eq!(upstream.failures, 1);
+        assert_eq!(upstream.successes, 1);
+        assert_eq!(upstream.backoff.count, 2);
+        assert_eq!(upstream.probes, 1);
+        assert_eq!(upstream.circuit_state, "closed");
+        assert_eq!(upstream.circuit_transitions["open"], 1);
+        assert_eq!(upstream.circuit_transitions["half-open"], 1);
+    }
+}
+
 #[cfg(test)]
 mod transport_abstraction_tests {
     use serde_json::json;
@@ -170,7 +671,9 @@ mod validation_middleware_tests {
     use std::borrow::Cow;
 
     use ultrafast_mcp_core::protocol::{JsonRpcMessage, JsonRpcRequest, RequestId};
-    use ultrafast_mcp_transport::streamable_http::middleware::{TransportMiddleware, ValidationMiddleware};
+    use ultrafast_mcp_transport::streamable_http::middleware::{
+        TransportMiddleware, ValidationMiddleware,
+    };
 
     #[tokio::test]
     async fn test_validation_middleware_basic() {
@@ -682,6 +1185,7 @@ mod transport_compliance_tests {
     use serde_json::json;
     use std::sync::Arc;
     use ultrafast_mcp_core::protocol::{JsonRpcError, JsonRpcResponse};
+    use ultrafast_mcp_transport::streamable_http::CorsPolicy;
     use ultrafast_mcp_transport::streamable_http::server::{
         HttpTransportConfig, HttpTransportServer, HttpTransportState,
     };
@@ -689,7 +1193,7 @@ mod transport_compliance_tests {
     #[tokio::test]
     async fn test_protocol_version_header_validation() {
         let config = HttpTransportConfig {
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -723,7 +1227,7 @@ mod transport_compliance_tests {
     #[tokio::test]
     async fn test_session_id_validation() {
         let config = HttpTransportConfig {
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -760,7 +1264,7 @@ mod transport_compliance_tests {
     #[tokio::test]
     async fn test_accept_header_requirement() {
         let config = HttpTransportConfig {
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -780,7 +1284,7 @@ mod transport_compliance_tests {
     async fn test_sse_event_id_generation() {
         let config = HttpTransportConfig {
             enable_sse_resumability: true,
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -799,7 +1303,7 @@ mod transport_compliance_tests {
     async fn test_last_event_id_header() {
         let config = HttpTransportConfig {
             enable_sse_resumability: true,
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -818,7 +1322,7 @@ mod transport_compliance_tests {
     #[tokio::test]
     async fn test_origin_validation() {
         let config = HttpTransportConfig {
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -850,7 +1354,7 @@ mod transport_compliance_tests {
     #[tokio::test]
     async fn test_session_management() {
         let config = HttpTransportConfig {
-            allow_origin: Some("http://localhost:3000".to_string()),
+            cors: CorsPolicy::new().with_origin("http://localhost:3000"),
             ..Default::default()
         };
         let server = HttpTransportServer::new(config);
@@ -870,10 +1374,14 @@ mod transport_compliance_tests {
         assert_eq!(response.status(), StatusCode::OK);
 
         // Verify session exists
-        {
-            let sessions = state.session_store.read().await;
-            assert!(sessions.contains_key(session_id));
-        } // Explicitly drop the read lock here
+        assert!(
+            state
+                .session_store
+                .get_session(session_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
 
         // Delete the session
         let mut headers = HeaderMap::new();
@@ -884,10 +1392,14 @@ mod transport_compliance_tests {
         assert_eq!(response.into_response().status(), StatusCode::OK);
 
         // Verify session is removed
-        {
-            let sessions = state.session_store.read().await;
-            assert!(!sessions.contains_key(session_id));
-        } // Explicitly drop the read lock here
+        assert!(
+            state
+                .session_store
+                .get_session(session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
     }
 
     // Helper function to simulate the server's POST handler
@@ -936,9 +1448,11 @@ mod transport_compliance_tests {
             .unwrap_or_else(|| "default-session".to_string());
         if let Ok(message) = serde_json::from_slice::<serde_json::Value>(&body) {
             if message.get("method") == Some(&serde_json::Value::String("initialize".to_string())) {
-                use ultrafast_mcp_transport::streamable_http::server::SessionInfo;
-                let mut sessions = state.session_store.write().await;
-                sessions.entry(session_id).or_insert_with(SessionInfo::new);
+                state
+                    .session_store
+                    .create_session(session_id)
+                    .await
+                    .unwrap();
             }
         }
 
@@ -984,8 +1498,11 @@ mod transport_compliance_tests {
             .and_then(|v| v.to_str().ok())
             .map(|s| s.to_string())
             .unwrap_or_else(|| "default-session".to_string());
-        let mut sessions = state.session_store.write().await;
-        sessions.remove(&session_id);
+        state
+            .session_store
+            .remove_session(&session_id)
+            .await
+            .unwrap();
 
         // For this test, just return success
         StatusCode::OK.into_response()
@@ -995,8 +1512,8 @@ mod transport_compliance_tests {
     fn validate_origin(headers: &HeaderMap, config: &HttpTransportConfig) -> bool {
         if let Some(origin) = headers.get("origin") {
             if let Ok(origin_str) = origin.to_str() {
-                if let Some(allowed_origin) = &config.allow_origin {
-                    return origin_str == allowed_origin;
+                if !config.cors.origins().is_empty() {
+                    return config.cors.allows_origin(origin_str);
                 }
                 return origin_str.contains("localhost") || origin_str.contains("127.0.0.1");
             }
@@ -1016,3 +1533,1085 @@ mod transport_compliance_tests {
         ["2025-06-18", "2025-03-26", "2024-11-05"].contains(&version)
     }
 }
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod event_stream_tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use axum::{Router, extract::State, http::HeaderMap, routing::get};
+    use ultrafast_mcp_core::protocol::JsonRpcMessage;
+    use ultrafast_mcp_transport::streamable_http::client::{
+        StreamableHttpClient, StreamableHttpClientConfig,
+    };
+    use ultrafast_mcp_transport::{RecoveryConfig, Transport, TransportError};
+
+    /// `Last-Event-ID` of every GET, in order
+    type SeenIds = Arc<Mutex<Vec<Option<String>>>>;
+
+    /// Serves one event per connection and then drops the stream
+    async fn flaky_stream(State(seen): State<SeenIds>, headers: HeaderMap) -> String {
+        let last_event_id = headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut seen = seen.lock().unwrap();
+        seen.push(last_event_id);
+        match seen.len() {
+            1 => ": keep-alive\n\nid: 1\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/one\"}\n\n"
+                .to_string(),
+            // Data split over two lines
+            2 => "id: 2\ndata: {\"jsonrpc\":\"2.0\",\ndata: \"method\":\"notifications/two\"}\n\n"
+                .to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn method(message: JsonRpcMessage) -> String {
+        match message {
+            JsonRpcMessage::Request(request) | JsonRpcMessage::Notification(request) => {
+                request.method
+            }
+            other => panic!("expected a notification, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_reconnects_with_last_event_id() {
+        let seen = SeenIds::default();
+        let app = Router::new()
+            .route("/mcp", get(flaky_stream))
+            .with_state(seen.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = StreamableHttpClientConfig {
+            base_url,
+            ..Default::default()
+        }
+        .with_event_stream(RecoveryConfig {
+            max_retries: 2,
+            initial_delay: Duration::from_millis(10),
+            enable_jitter: false,
+            ..Default::default()
+        });
+        let mut client = StreamableHttpClient::new(config).unwrap();
+        client.connect().await.unwrap();
+
+        assert_eq!(
+            method(client.receive_message().await.unwrap()),
+            "notifications/one"
+        );
+        assert_eq!(
+            method(client.receive_message().await.unwrap()),
+            "notifications/two"
+        );
+        assert_eq!(client.last_event_id().as_deref(), Some("2"));
+
+        // Empty streams count as failed reconnects until the client gives up
+        let error = client.receive_message().await.unwrap_err();
+        assert!(matches!(error, TransportError::ConnectionError { .. }));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                None,
+                Some("1".to_string()),
+                Some("2".to_string()),
+                Some("2".to_string())
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod notification_tests {
+    use std::time::Duration;
+
+    use ultrafast_mcp_core::protocol::{JsonRpcMessage, JsonRpcRequest};
+    use ultrafast_mcp_transport::Transport;
+    use ultrafast_mcp_transport::streamable_http::{
+        HttpTransportConfig, HttpTransportServer, StreamableHttpClient, StreamableHttpClientConfig,
+    };
+
+    #[tokio::test]
+    async fn test_notifications_are_accepted_without_waiting() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        });
+        let state = server.get_state();
+        let mut received = server.get_message_receiver();
+        tokio::spawn(server.run());
+
+        let mut client = StreamableHttpClient::new(StreamableHttpClientConfig {
+            base_url: format!("http://127.0.0.1:{port}"),
+            ..Default::default()
+        })
+        .unwrap();
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let session_id = client.connect().await.unwrap();
+        // Nothing here answers `initialize`, so open the session directly
+        state
+            .session_store
+            .create_session(session_id)
+            .await
+            .unwrap();
+
+        // Nothing answers a notification, so the POST must not wait for a
+        // response
+        let notification =
+            JsonRpcRequest::notification("notifications/initialized".to_string(), None);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_message(JsonRpcMessage::Notification(notification)),
+        )
+        .await
+        .expect("notification should be accepted immediately")
+        .unwrap();
+
+        let (_, message) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .expect("notification should reach the server")
+            .unwrap();
+        match message {
+            JsonRpcMessage::Request(request) | JsonRpcMessage::Notification(request) => {
+                assert_eq!(request.method, "notifications/initialized")
+            }
+            other => panic!("expected a notification, got {other:?}"),
+        }
+        client.close().await.unwrap();
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod reject_metrics_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ultrafast_mcp_monitoring::{MetricsCollector, RejectReason};
+    use ultrafast_mcp_transport::streamable_http::{HttpTransportConfig, HttpTransportServer};
+
+    #[tokio::test]
+    async fn test_malformed_posts_are_recorded_as_rejects() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let metrics = Arc::new(MetricsCollector::new());
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_reject_metrics(metrics.clone());
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(format!("http://127.0.0.1:{port}/mcp"))
+            .header("mcp-session-id", "noisy-client")
+            .header("content-type", "application/json")
+            .body("{not json")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], -32700);
+
+        let rejects = metrics.get_metrics().await.rejects;
+        assert_eq!(rejects.by_reason[&RejectReason::ParseError], 1);
+        assert_eq!(rejects.by_session["noisy-client"], 1);
+        assert_eq!(metrics.get_metrics().await.request.total_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_post_bytes_are_recorded_as_traffic() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let metrics = Arc::new(MetricsCollector::new());
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_traffic_metrics(metrics.clone());
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let body = "{not json";
+        let response = reqwest::Client::new()
+            .post(format!("http://127.0.0.1:{port}/mcp"))
+            .header("content-type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        let transport = metrics.get_metrics().await.transport;
+        assert_eq!(transport.bytes_received, body.len() as u64);
+        assert_eq!(transport.bytes_sent, response.len() as u64);
+        assert_eq!(metrics.get_metrics().await.request.total_requests, 0);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod pool_tests {
+    use std::time::Duration;
+
+    use ultrafast_mcp_core::protocol::{JsonRpcMessage, JsonRpcRequest};
+    use ultrafast_mcp_transport::Transport;
+    use ultrafast_mcp_transport::streamable_http::{
+        HttpConnectionPool, HttpPoolConfig, HttpTransportConfig, HttpTransportServer,
+        StreamableHttpClient, StreamableHttpClientConfig,
+    };
+
+    #[tokio::test]
+    async fn test_clients_share_a_connection_pool() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        });
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let pool = HttpConnectionPool::new(HttpPoolConfig {
+            max_connections_per_host: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let config = StreamableHttpClientConfig {
+            base_url: format!("http://127.0.0.1:{port}"),
+            ..Default::default()
+        };
+        let mut first = StreamableHttpClient::with_pool(config.clone(), pool.clone()).unwrap();
+        let mut second = StreamableHttpClient::with_pool(config, pool.clone()).unwrap();
+        first.connect().await.unwrap();
+        second.connect().await.unwrap();
+
+        let notification = || {
+            JsonRpcMessage::Notification(JsonRpcRequest::notification(
+                "notifications/initialized".to_string(),
+                None,
+            ))
+        };
+        let (sent, other) = tokio::join!(
+            first.send_message(notification()),
+            second.send_message(notification())
+        );
+        sent.unwrap();
+        other.unwrap();
+
+        let stats = Transport::get_health(&first).pool.unwrap();
+        assert_eq!(stats, pool.stats());
+        assert_eq!(stats.total_requests, 2);
+        assert_eq!(stats.in_use, 0);
+        // One connection per host, so the requests took turns
+        assert_eq!(stats.peak_in_use, 1);
+        assert_eq!(stats.max_connections_per_host, 1);
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod graceful_shutdown_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ultrafast_mcp_transport::streamable_http::{
+        HttpTransportConfig, HttpTransportServer, InMemorySessionStore, SessionStore,
+    };
+
+    #[tokio::test]
+    async fn test_run_until_ends_open_event_streams() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let store = InMemorySessionStore::new();
+        store
+            .create_session("open-session".to_string())
+            .await
+            .unwrap();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_session_store(Arc::new(store));
+        let (shutdown, stop) = tokio::sync::oneshot::channel::<()>();
+        let running = tokio::spawn(server.run_until(async move {
+            let _ = stop.await;
+        }));
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let stream = reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{port}/mcp"))
+            .header("Accept", "text/event-stream")
+            .header("mcp-session-id", "open-session")
+            .send()
+            .await
+            .unwrap();
+        assert!(stream.status().is_success());
+
+        shutdown.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), running)
+            .await
+            .expect("server should stop despite the open stream")
+            .unwrap()
+            .unwrap();
+        // The stream ended rather than being cut off
+        let body = tokio::time::timeout(Duration::from_secs(5), stream.text())
+            .await
+            .unwrap();
+        assert!(body.is_ok());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod session_manager_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde_json::json;
+    use ultrafast_mcp_core::protocol::jsonrpc::{JsonRpcMessage, JsonRpcResponse};
+    use ultrafast_mcp_transport::streamable_http::{HttpTransportConfig, HttpTransportServer};
+
+    #[tokio::test]
+    async fn test_sessions_can_be_listed_notified_and_evicted() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_identity_resolver(Arc::new(|headers| {
+            headers
+                .get("x-user")
+                .and_then(|user| user.to_str().ok())
+                .map(str::to_string)
+        }));
+        let sessions = server.sessions();
+        let mut requests = server.get_message_receiver();
+        let responses = server.get_response_sender();
+        tokio::spawn(async move {
+            while let Ok((session_id, message)) = requests.recv().await {
+                if let JsonRpcMessage::Request(request) = message {
+                    let result = json!({"protocolVersion": "2025-06-18"});
+                    let response = JsonRpcResponse::success(result, request.id);
+                    let _ = responses.send((session_id, JsonRpcMessage::Response(response)));
+                }
+            }
+        });
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let client = reqwest::Client::new();
+        let url = format!("http://127.0.0.1:{port}/mcp");
+        let initialized = client
+            .post(&url)
+            .header("x-user", "alice")
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": {"name": "inspector", "version": "1.0.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = initialized.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let session = sessions.session(&session_id).unwrap();
+        assert_eq!(session.client_info.unwrap().name, "inspector");
+        assert_eq!(session.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(session.auth_identity.as_deref(), Some("alice"));
+        assert!(session.remote_address.is_some());
+        assert_eq!(sessions.len(), 1);
+
+        let mut stream = client
+            .get(&url)
+            .header("Accept", "text/event-stream")
+            .header("mcp-session-id", &session_id)
+            .send()
+            .await
+            .unwrap();
+        sessions
+            .notify_session(&session_id, "notifications/custom", None)
+            .unwrap();
+        let mut body = String::new();
+        while !body.contains("notifications/custom") {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), stream.chunk())
+                .await
+                .expect("notification should arrive")
+                .unwrap()
+                .expect("stream should stay open");
+            body.push_str(&String::from_utf8_lossy(&chunk));
+        }
+
+        assert!(sessions.evict(&session_id));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while stream.chunk().await.unwrap().is_some() {}
+        })
+        .await
+        .expect("evicting should end the session's stream");
+        let rejected = client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rejected.status(), reqwest::StatusCode::NOT_FOUND);
+        assert!(sessions.is_empty());
+
+        // The eviction is forgotten once the session store has dropped the
+        // session, and its ID stays unknown
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while sessions.is_evicted(&session_id) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the evicted session should be removed from the store");
+        let rejected = client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .json(&json!({"jsonrpc": "2.0", "id": 3, "method": "ping"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rejected.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_streams_and_deletes_need_an_open_session() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        });
+        let state = server.get_state();
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let client = reqwest::Client::new();
+        let url = format!("http://127.0.0.1:{port}/mcp");
+        for (session_id, status) in [
+            (None, reqwest::StatusCode::BAD_REQUEST),
+            (Some("not a valid id"), reqwest::StatusCode::BAD_REQUEST),
+            (Some("chosen-by-client"), reqwest::StatusCode::NOT_FOUND),
+        ] {
+            for method in [reqwest::Method::GET, reqwest::Method::DELETE] {
+                let mut request = client.request(method.clone(), &url);
+                if let Some(session_id) = session_id {
+                    request = request.header("mcp-session-id", session_id);
+                }
+                let response = request.send().await.unwrap();
+                assert_eq!(response.status(), status, "{method} with {session_id:?}");
+            }
+        }
+        // Streams are not a way to open sessions
+        assert!(
+            state
+                .session_store
+                .get_session("chosen-by-client")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_posts_need_an_open_session() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        });
+        let state = server.get_state();
+        let mut requests = server.get_message_receiver();
+        let responses = server.get_response_sender();
+        tokio::spawn(async move {
+            while let Ok((session_id, message)) = requests.recv().await {
+                if let JsonRpcMessage::Request(request) = message {
+                    let result = json!({"protocolVersion": "2025-06-18"});
+                    let response = JsonRpcResponse::success(result, request.id);
+                    let _ = responses.send((session_id, JsonRpcMessage::Response(response)));
+                }
+            }
+        });
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let client = reqwest::Client::new();
+        let url = format!("http://127.0.0.1:{port}/mcp");
+        let ping = json!({"jsonrpc": "2.0", "id": 2, "method": "ping"});
+        for (session_id, status) in [
+            (None, reqwest::StatusCode::BAD_REQUEST),
+            (Some("not a valid id"), reqwest::StatusCode::BAD_REQUEST),
+            (Some("made-up-session"), reqwest::StatusCode::NOT_FOUND),
+        ] {
+            let mut request = client.post(&url).json(&ping);
+            if let Some(session_id) = session_id {
+                request = request.header("mcp-session-id", session_id);
+            }
+            let response = request.send().await.unwrap();
+            assert_eq!(response.status(), status, "POST with {session_id:?}");
+        }
+        // Requests are not a way to open sessions
+        assert!(
+            state
+                .session_store
+                .get_session("made-up-session")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let initialized = client
+            .post(&url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": {"name": "test", "version": "1.0.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session_id = initialized.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let pinged = client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .header("mcp-protocol-version", "2025-06-18")
+            .json(&ping)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(pinged.status(), reqwest::StatusCode::OK);
+
+        let deleted = client
+            .delete(&url)
+            .header("mcp-session-id", &session_id)
+            .header("mcp-protocol-version", "2025-06-18")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(deleted.status(), reqwest::StatusCode::OK);
+        let after_delete = client
+            .post(&url)
+            .header("mcp-session-id", &session_id)
+            .header("mcp-protocol-version", "2025-06-18")
+            .json(&ping)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(after_delete.status(), reqwest::StatusCode::NOT_FOUND);
+        assert!(
+            state
+                .session_store
+                .get_session(&session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "http-server")]
+mod hardening_tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use ultrafast_mcp_transport::streamable_http::{
+        HardeningConfig, HttpTransportConfig, HttpTransportServer,
+    };
+
+    /// Send raw bytes and read until the server closes the connection
+    async fn send_raw(port: u16, request: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_hardened_server_rejects_malformed_requests() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_hardening(HardeningConfig::default());
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // The body is framed twice; a proxy going by Content-Length would
+        // forward the GET as part of it
+        let smuggled = send_raw(
+            port,
+            "POST /mcp HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: 37\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n\
+             GET /mcp HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        )
+        .await;
+        assert_eq!(smuggled.matches("HTTP/1.1 ").count(), 1, "{smuggled}");
+        assert!(
+            smuggled.starts_with("HTTP/1.1 4") || smuggled.contains("connection: close"),
+            "{smuggled}"
+        );
+
+        let duplicate_session = send_raw(
+            port,
+            "GET /mcp HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Accept: text/event-stream\r\nMcp-Session-Id: session-1234\r\nMcp-Session-Id: session-5678\r\n\r\n",
+        )
+        .await;
+        assert!(
+            duplicate_session.starts_with("HTTP/1.1 400"),
+            "{duplicate_session}"
+        );
+
+        let filler = "x".repeat(9 * 1024);
+        let oversized = send_raw(
+            port,
+            &format!(
+                "GET /mcp HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+                 X-Filler: {filler}\r\n\r\n"
+            ),
+        )
+        .await;
+        assert!(oversized.starts_with("HTTP/1.1 431"), "{oversized}");
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod openai_tools_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde_json::{Value, json};
+    use ultrafast_mcp_core::protocol::{JsonRpcError, JsonRpcResponse};
+    use ultrafast_mcp_transport::streamable_http::openai::MAX_TOOL_CALLS;
+    use ultrafast_mcp_transport::streamable_http::{HttpTransportConfig, HttpTransportServer};
+
+    #[tokio::test]
+    async fn test_tools_are_listed_and_invoked_as_functions() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        // Two pages of tools, and a tool that adds its arguments
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_openai_tools(Arc::new(|request| {
+            Box::pin(async move {
+                let params = request.params.unwrap_or_default();
+                let result = match (request.method.as_str(), params.get("cursor")) {
+                    ("tools/list", None) => json!({
+                        "tools": [{"name": "math.add", "description": "Add", "inputSchema": {"type": "object"}}],
+                        "nextCursor": "2",
+                    }),
+                    ("tools/list", Some(_)) => json!({
+                        "tools": [{"name": "fail", "inputSchema": {"type": "object"}}],
+                    }),
+                    ("tools/call", _) if params["name"] == "math.add" => {
+                        let sum = params["arguments"]["a"].as_i64().unwrap()
+                            + params["arguments"]["b"].as_i64().unwrap();
+                        json!({"content": [{"type": "text", "text": sum.to_string()}]})
+                    }
+                    _ => {
+                        return JsonRpcResponse::error(
+                            JsonRpcError::new(-32603, "Tool failed".to_string()),
+                            request.id,
+                        );
+                    }
+                };
+                JsonRpcResponse::success(result, request.id)
+            })
+        }));
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let client = reqwest::Client::new();
+
+        let list: Value = client
+            .get(format!("http://127.0.0.1:{port}/v1/tools"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let names: Vec<_> = list["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|function| function["function"]["name"].clone())
+            .collect();
+        assert_eq!(names, [json!("math_add"), json!("fail")]);
+
+        let message: Value = client
+            .post(format!("http://127.0.0.1:{port}/v1/tools/invoke"))
+            .json(&json!({
+                "id": "call_abc",
+                "type": "function",
+                "function": {"name": "math_add", "arguments": "{\"a\": 2, \"b\": 3}"},
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            message,
+            json!({"role": "tool", "tool_call_id": "call_abc", "name": "math_add", "content": "5"})
+        );
+
+        let batch: Value = client
+            .post(format!("http://127.0.0.1:{port}/v1/tools/invoke"))
+            .json(&json!({"tool_calls": [
+                {"function": {"name": "fail", "arguments": "{}"}},
+                {"function": {"name": "missing"}},
+            ]}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(batch["messages"][0]["content"], "Tool failed");
+        assert_eq!(batch["messages"][0]["is_error"], true);
+        assert_eq!(batch["messages"][1]["tool_call_id"], "call_1");
+        assert_eq!(batch["messages"][1]["content"], "Unknown tool: missing");
+
+        let invalid = client
+            .post(format!("http://127.0.0.1:{port}/v1/tools/invoke"))
+            .json(&json!({"function": {"name": "math_add", "arguments": "{not json"}}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(invalid.status(), 400);
+
+        let call = json!({"function": {"name": "math_add", "arguments": "{\"a\": 1, \"b\": 1}"}});
+        let too_many = client
+            .post(format!("http://127.0.0.1:{port}/v1/tools/invoke"))
+            .json(&json!({"tool_calls": vec![call; MAX_TOOL_CALLS + 1]}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(too_many.status(), 413);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "http-server")]
+mod cors_tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use ultrafast_mcp_transport::streamable_http::{
+        CorsPolicy, HttpTransportConfig, HttpTransportServer,
+    };
+
+    async fn preflight(port: u16, origin: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        let request = format!(
+            "OPTIONS /mcp HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Origin: {origin}\r\nAccess-Control-Request-Method: POST\r\n\
+             Access-Control-Request-Headers: content-type, mcp-session-id\r\n\r\n"
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        String::from_utf8_lossy(&response).to_lowercase()
+    }
+
+    #[tokio::test]
+    async fn test_preflight_follows_the_policy() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let cors = CorsPolicy::new()
+            .with_origin("https://*.example.com")
+            .with_credentials(true)
+            .with_max_age(Duration::from_secs(600));
+        tokio::spawn(
+            HttpTransportServer::new(HttpTransportConfig {
+                port,
+                cors,
+                ..Default::default()
+            })
+            .run(),
+        );
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let allowed = preflight(port, "https://app.example.com").await;
+        assert!(
+            allowed.contains("access-control-allow-origin: https://app.example.com"),
+            "{allowed}"
+        );
+        assert!(
+            allowed.contains("access-control-allow-credentials: true"),
+            "{allowed}"
+        );
+        assert!(allowed.contains("access-control-max-age: 600"), "{allowed}");
+        assert!(allowed.contains("mcp-session-id"), "{allowed}");
+
+        let denied = preflight(port, "https://evil.com").await;
+        assert!(!denied.contains("access-control-allow-origin"), "{denied}");
+    }
+
+    #[tokio::test]
+    async fn test_credentials_for_every_origin_are_refused() {
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port: 0,
+            cors: CorsPolicy::permissive().with_credentials(true),
+            ..Default::default()
+        });
+        assert!(server.run().await.is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "http-client", feature = "http-server"))]
+mod protocol_version_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde_json::json;
+    use ultrafast_mcp_core::protocol::jsonrpc::{JsonRpcMessage, JsonRpcResponse};
+    use ultrafast_mcp_monitoring::{MetricsCollector, RejectReason};
+    use ultrafast_mcp_transport::streamable_http::{HttpTransportConfig, HttpTransportServer};
+
+    #[tokio::test]
+    async fn test_requests_must_use_the_negotiated_version() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let metrics = Arc::new(MetricsCollector::new());
+        let server = HttpTransportServer::new(HttpTransportConfig {
+            port,
+            ..Default::default()
+        })
+        .with_metrics(metrics.clone());
+        let mut requests = server.get_message_receiver();
+        let responses = server.get_response_sender();
+        tokio::spawn(async move {
+            while let Ok((session_id, message)) = requests.recv().await {
+                if let JsonRpcMessage::Request(request) = message {
+                    let result = json!({"protocolVersion": "2025-03-26"});
+                    let response = JsonRpcResponse::success(result, request.id);
+                    let _ = responses.send((session_id, JsonRpcMessage::Response(response)));
+                }
+            }
+        });
+        tokio::spawn(server.run());
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let client = reqwest::Client::new();
+        let url = format!("http://127.0.0.1:{port}/mcp");
+        let initialized = client
+            .post(&url)
+            .header("mcp-protocol-version", "2025-06-18")
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-03-26",
+                    "capabilities": {},
+                    "clientInfo": {"name": "inspector", "version": "1.0.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(initialized.headers()["mcp-protocol-version"], "2025-03-26");
+        let session_id = initialized.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let ping = |version: Option<&str>| {
+            let mut request = client
+                .post(&url)
+                .header("mcp-session-id", &session_id)
+                .json(&json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}));
+            if let Some(version) = version {
+                request = request.header("mcp-protocol-version", version);
+            }
+            request.send()
+        };
+
+        let matching = ping(Some("2025-03-26")).await.unwrap();
+        assert_eq!(matching.status(), reqwest::StatusCode::OK);
+        let assumed = ping(None).await.unwrap();
+        assert_eq!(assumed.status(), reqwest::StatusCode::OK);
+        assert_eq!(assumed.headers()["mcp-protocol-version"], "2025-03-26");
+
+        let mismatched = ping(Some("2025-06-18")).await.unwrap();
+        assert_eq!(mismatched.status(), reqwest::StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = mismatched.json().await.unwrap();
+        assert_eq!(error["error"]["data"]["requested"], "2025-06-18");
+        assert_eq!(error["error"]["data"]["negotiated"], "2025-03-26");
+
+        let unknown = ping(Some("1999-01-01")).await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = unknown.json().await.unwrap();
+        let message = error["error"]["message"].as_str().unwrap();
+        assert!(
+            message.contains("supported versions are 2025-06-18"),
+            "{message}"
+        );
+
+        let stream = client
+            .get(&url)
+            .header("Accept", "text/event-stream")
+            .header("mcp-session-id", &session_id)
+            .header("mcp-protocol-version", "2024-11-05")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stream.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let metrics = metrics.get_metrics().await;
+        assert_eq!(metrics.request.by_protocol_version["2025-03-26"], 3);
+        assert_eq!(
+            metrics.rejects.by_reason[&RejectReason::UnsupportedProtocolVersion],
+            3
+        );
+    }
+}
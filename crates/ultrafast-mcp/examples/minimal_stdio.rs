@@ -0,0 +1,48 @@
+//! Echo server built with only the `minimal` feature profile
+//!
+//! The binary-size budget in `tests/minimal_dependency_budget.rs` measures a
+//! release build of this example.
+//!
+//! Usage:
+//!   cargo run -p ultrafast-mcp --example minimal_stdio --no-default-features --features minimal
+
+use serde::{Deserialize, Serialize};
+use ultrafast_mcp::{MCPResult, ServerCapabilities, ServerInfo, ToolsCapability, UltraFastServer};
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct EchoInput {
+    text: String,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct EchoOutput {
+    text: String,
+}
+
+async fn echo(input: EchoInput, _ctx: ultrafast_mcp::Context) -> MCPResult<EchoOutput> {
+    Ok(EchoOutput { text: input.text })
+}
+
+#[tokio::main]
+async fn main() -> MCPResult<()> {
+    UltraFastServer::new(
+        ServerInfo {
+            name: "minimal-echo".to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            authors: None,
+            homepage: None,
+            license: None,
+            repository: None,
+        },
+        ServerCapabilities {
+            tools: Some(ToolsCapability {
+                list_changed: Some(false),
+            }),
+            ..Default::default()
+        },
+    )
+    .tool("echo", "Echo the text back", echo)?
+    .run_stdio()
+    .await
+}
@@ -0,0 +1,188 @@
+//! Synchronous wrappers for applications that do not run on tokio
+//!
+//! [`Client`] and [`Server`] each own a small tokio runtime and drive the
+//! async [`UltraFastClient`] and [`UltraFastServer`] on it, so CLI tools and
+//! GUI applications can embed MCP without becoming async themselves.
+//!
+//! ```rust,no_run
+//! use ultrafast_mcp::blocking;
+//! use ultrafast_mcp::{ClientCapabilities, ClientInfo, ToolCall, UltraFastClient};
+//!
+//! fn main() -> ultrafast_mcp::MCPResult<()> {
+//!     let info = ClientInfo {
+//!         name: "gui-app".to_string(),
+//!         version: "1.0.0".to_string(),
+//!         authors: None,
+//!         description: None,
+//!         homepage: None,
+//!         repository: None,
+//!         license: None,
+//!     };
+//!     let client = blocking::Client::new(UltraFastClient::new(info, ClientCapabilities::default()))?;
+//!     client.connect_stdio()?;
+//!
+//!     let tools = client.list_tools()?;
+//!     println!("{} tools", tools.tools.len());
+//!     let result = client.call_tool(ToolCall {
+//!         name: "echo".to_string(),
+//!         arguments: Some(serde_json::json!({"message": "hi"})),
+//!     })?;
+//!     println!("{result:?}");
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The methods block the calling thread, so they must not be called from
+//! inside an async runtime; tokio panics if they are. Both types are `Send`
+//! and `Sync` and can be shared between threads, for example in an `Arc`.
+
+use std::future::Future;
+
+use tokio::runtime::Runtime;
+use ultrafast_mcp_client::UltraFastClient;
+use ultrafast_mcp_core::types::{
+    prompts::{GetPromptRequest, GetPromptResponse, ListPromptsRequest, ListPromptsResponse},
+    resources::{
+        ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
+    },
+    tools::{ListToolsRequest, ListToolsResponse, ToolCall, ToolResult},
+};
+use ultrafast_mcp_core::{MCPError, MCPResult};
+use ultrafast_mcp_server::UltraFastServer;
+
+#[cfg(feature = "stdio")]
+use ultrafast_mcp_transport::Transport;
+
+/// Runtime for one blocking wrapper
+///
+/// Background tasks, such as the client's message receiver, run on the
+/// calling thread while a call is in progress.
+fn runtime() -> MCPResult<Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| MCPError::internal_error(format!("Failed to start runtime: {e}")))
+}
+
+/// Synchronous [`UltraFastClient`]
+pub struct Client {
+    inner: UltraFastClient,
+    runtime: Runtime,
+}
+
+impl Client {
+    pub fn new(client: UltraFastClient) -> MCPResult<Self> {
+        Ok(Self {
+            inner: client,
+            runtime: runtime()?,
+        })
+    }
+
+    /// The wrapped async client
+    pub fn inner(&self) -> &UltraFastClient {
+        &self.inner
+    }
+
+    /// Run any other client operation to completion
+    ///
+    /// ```rust,no_run
+    /// # fn example(client: &ultrafast_mcp::blocking::Client) -> ultrafast_mcp::MCPResult<()> {
+    /// let response = client.block_on(|client| client.list_tools_default())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn block_on<'a, F, Fut>(&'a self, operation: F) -> Fut::Output
+    where
+        F: FnOnce(&'a UltraFastClient) -> Fut,
+        Fut: Future,
+    {
+        self.runtime.block_on(operation(&self.inner))
+    }
+
+    /// Connect over `transport` and initialize the session
+    #[cfg(feature = "stdio")]
+    pub fn connect(&self, transport: Box<dyn Transport>) -> MCPResult<()> {
+        self.block_on(|client| client.connect(transport))
+    }
+
+    /// Connect over STDIO and initialize the session
+    pub fn connect_stdio(&self) -> MCPResult<()> {
+        self.block_on(|client| client.connect_stdio())
+    }
+
+    /// Connect to a Streamable HTTP server and initialize the session
+    #[cfg(feature = "http-client")]
+    pub fn connect_streamable_http(&self, url: &str) -> MCPResult<()> {
+        self.block_on(|client| client.connect_streamable_http(url))
+    }
+
+    /// List tools, following no cursor
+    pub fn list_tools(&self) -> MCPResult<ListToolsResponse> {
+        self.block_on(|client| client.list_tools(ListToolsRequest::default()))
+    }
+
+    pub fn call_tool(&self, tool_call: ToolCall) -> MCPResult<ToolResult> {
+        self.block_on(|client| client.call_tool(tool_call))
+    }
+
+    pub fn list_resources(
+        &self,
+        request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        self.block_on(|client| client.list_resources(request))
+    }
+
+    pub fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        self.block_on(|client| client.read_resource(request))
+    }
+
+    pub fn list_prompts(&self, request: ListPromptsRequest) -> MCPResult<ListPromptsResponse> {
+        self.block_on(|client| client.list_prompts(request))
+    }
+
+    pub fn get_prompt(&self, request: GetPromptRequest) -> MCPResult<GetPromptResponse> {
+        self.block_on(|client| client.get_prompt(request))
+    }
+
+    pub fn shutdown(&self, reason: Option<String>) -> MCPResult<()> {
+        self.block_on(|client| client.shutdown(reason))
+    }
+}
+
+/// Synchronous [`UltraFastServer`]
+pub struct Server {
+    inner: UltraFastServer,
+    runtime: Runtime,
+}
+
+impl Server {
+    pub fn new(server: UltraFastServer) -> MCPResult<Self> {
+        Ok(Self {
+            inner: server,
+            runtime: runtime()?,
+        })
+    }
+
+    /// The wrapped async server
+    pub fn inner(&self) -> &UltraFastServer {
+        &self.inner
+    }
+
+    /// Serve over STDIO until the client disconnects
+    pub fn run(&self) -> MCPResult<()> {
+        self.runtime.block_on(self.inner.run_stdio())
+    }
+
+    /// Serve over `transport` until it closes
+    #[cfg(feature = "stdio")]
+    pub fn run_with_transport(&self, transport: Box<dyn Transport>) -> MCPResult<()> {
+        self.runtime
+            .block_on(self.inner.run_with_transport(transport))
+    }
+
+    #[cfg(feature = "http-server")]
+    pub fn run_streamable_http(&self, host: &str, port: u16) -> MCPResult<()> {
+        self.runtime
+            .block_on(self.inner.run_streamable_http(host, port))
+    }
+}
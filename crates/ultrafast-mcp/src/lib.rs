@@ -31,6 +31,10 @@
 //! - **`Transport`**: Flexible transport layer with HTTP, STDIO, and custom options
 //! - **`ResourceSubscriptionHandler`**: Handle resource updates and notifications
 //!
+//! ### Synchronous Applications
+//! - **`blocking::Client`** and **`blocking::Server`**: The same APIs with
+//!   blocking methods, for programs that do not run on tokio
+//!
 //! ## Quick Start
 //!
 //! ### Creating an MCP Server
@@ -79,6 +83,8 @@
 //!                 Ok(ToolResult {
 //!                     content: vec![ToolContent::text(message)],
 //!                     is_error: Some(false),
+//!                     structured_content: None,
+//!                     progress_summary: None,
 //!                 })
 //!             }
 //!             _ => Err(MCPError::method_not_found(
@@ -412,6 +418,10 @@ pub use ultrafast_mcp_core::utils::identifiers::{generate_session_id, generate_s
 // Prelude module for convenient imports
 pub mod prelude;
 
+// Synchronous client and server wrappers
+#[cfg(feature = "core")]
+pub mod blocking;
+
 // Re-export commonly used types directly for convenience
 #[cfg(feature = "core")]
 pub use ultrafast_mcp_core::types::{
@@ -420,7 +430,10 @@ pub use ultrafast_mcp_core::types::{
     // Completion types
     completion::{CompleteRequest, CompleteResponse, Completion, CompletionValue},
     // Elicitation types
-    elicitation::{ElicitationRequest, ElicitationResponse},
+    elicitation::{
+        ElicitationAction, ElicitationBuilder, ElicitationField, ElicitationRequest,
+        ElicitationResponse, ElicitationResult,
+    },
     // Notification types
     notifications::{LogLevel, PingResponse},
     // Prompt types
@@ -430,8 +443,9 @@ pub use ultrafast_mcp_core::types::{
     },
     // Resource types
     resources::{
-        ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
-        Resource, ResourceContent, ResourceTemplate,
+        ByteRange, ListResourcesRequest, ListResourcesResponse, ReadResourceRequest,
+        ReadResourceResponse, ReadResourceStreamRequest, ReadResourceStreamResponse, Resource,
+        ResourceContent, ResourceTemplate,
     },
     // Roots types
     roots::Root,
@@ -445,7 +459,7 @@ pub use ultrafast_mcp_core::types::{
     // Tool types
     tools::{
         ListToolsRequest, ListToolsResponse, Tool, ToolAnnotations, ToolCall, ToolContent,
-        ToolResult,
+        ToolProgressSummary, ToolResult,
     },
 };
 
@@ -456,6 +470,28 @@ pub use ultrafast_mcp_core::protocol::capabilities::{
     ToolsCapability,
 };
 
+// Re-export the execution metadata servers can attach to results
+#[cfg(feature = "core")]
+pub use ultrafast_mcp_core::protocol::metadata::ResponseMeta;
+
+// Re-export localized user-facing messages
+#[cfg(feature = "core")]
+pub use ultrafast_mcp_core::i18n::{self, LocalizedMessage, MessageCatalog};
+
+// Re-export compression of large content items
+#[cfg(feature = "content-encoding")]
+pub use ultrafast_mcp_core::content_encoding::{self, DEFAULT_COMPRESSION_THRESHOLD};
+
+// Re-export MIME type detection and charset decoding of resource content
+#[cfg(feature = "core")]
+pub use ultrafast_mcp_core::content_type::{self, decode_text, detect_mime_type};
+
+// Re-export linting of tool schemas at registration
+#[cfg(feature = "core")]
+pub use ultrafast_mcp_core::schema::lint::{
+    LintLevel, SchemaLintConfig, SchemaLintIssue, SchemaLintReport, SchemaLintRule,
+};
+
 // =========================
 // Server API
 // =========================
@@ -463,45 +499,94 @@ pub use ultrafast_mcp_core::protocol::capabilities::{
 #[cfg(feature = "core")]
 #[cfg(not(doc))]
 pub use ultrafast_mcp_server::{
-    CompletionHandler, Context, ContextLogger, ElicitationHandler, LoggerConfig, PromptHandler,
-    ResourceHandler, ResourceSubscriptionHandler, RootsHandler, SamplingHandler,
-    ServerLoggingConfig, ServerState, ToolHandler, ToolRegistrationError, UltraFastServer,
+    AckPolicy, AutoElicitationHandler, AutoSamplingHandler, BuildInfo, ClientEmulationConfig,
+    ClientPeer, CompletionHandler, CompletionRouter, Context, ContextLogger,
+    DEFAULT_LIST_CHANGED_DEBOUNCE, DomainErrorMapper, ElicitationHandler, ErrorMapper,
+    FilePathCompleter, FsResourceHandler, IntoResourceHandler, IntoToolHandler, ListKind,
+    LoggerConfig, ModelPrice, PromptHandler, PromptRegistry, RATE_LIMIT_NOTIFICATION_METHOD,
+    RateLimit, RateLimitConfig, RateLimitScope, RateLimited, RequestInfo, ResourceByteStream,
+    ResourceHandler, ResourceRegistry, ResourceSubscriptionHandler, ResourceTemplateCompleter,
+    RootsHandler, SHUTDOWN_NOTIFICATION_METHOD, SamplingHandler, SamplingPricing, SamplingUsage,
+    ServerLoggingConfig, ServerMiddleware, ServerState, ServerStats, SessionState, ShutdownReport,
+    StaticResources, SubscriptionPattern, SubscriptionRegistry, ToolHandler, ToolRegistrationError,
+    UltraFastServer, UnsupportedMethodPolicy, UsageReport, Wizard, WizardAnswers, WizardOutcome,
+    WizardSession, WizardState, WizardStep, shutdown_signal,
 };
 
+// Forward tracing events to clients as MCP log messages
+#[cfg(feature = "tracing-layer")]
+pub use ultrafast_mcp_server::McpLogLayer;
+
 // =========================
 // Client API
 // =========================
 #[cfg(feature = "core")]
-pub use ultrafast_mcp_client::{ClientElicitationHandler, UltraFastClient};
+pub use ultrafast_mcp_client::{
+    AdaptiveTimeouts, ClientElicitationHandler, ClientLateResponseHandler,
+    ClientNotificationOrderHandler, ClientSamplingHandler, ClientStats, ConnectOptions,
+    DecodeImages, LateResponse, LatencyEstimate, ModelDecision, ModelPolicy, NormalizeMarkdown,
+    NotificationOrderMetrics, NotificationSubscription, OutputValidation, RejectedModel,
+    ResourceChangeHandler, SelectionReason, SequenceAnomaly, ServerNotification, ToolAvailability,
+    ToolCallStream, ToolResultTransform, TruncateText, UltraFastClient, UnmatchedResponseKind,
+    WithMeta,
+};
+// Renamed so it does not clash with the monitoring `RequestMetrics`
+#[cfg(feature = "core")]
+pub use ultrafast_mcp_client::RequestMetrics as ClientRequestMetrics;
 
 // =========================
 // Transport Layer
 // =========================
 #[cfg(feature = "stdio")]
 pub use ultrafast_mcp_transport::{
+    CloseOnDrop,
     Transport,
     TransportConfig,
+    TransportDescription,
+    TransportKind,
+    close_on_drop,
     create_recovering_transport,
     create_transport,
-    // Middleware (moved to streamable_http module)
-    streamable_http::middleware::{
-        LoggingMiddleware, MiddlewareTransport, ProgressMiddleware, RateLimitMiddleware,
-        TransportMiddleware, ValidationMiddleware,
-    },
     // STDIO
     stdio::StdioTransport,
+    // Any byte stream, e.g. a child process or an in-memory duplex
+    stream::{ChildProcessTransport, DuplexTransport, StreamTransport, duplex_pair},
+};
+
+// Message signing (feature = "signing")
+#[cfg(feature = "signing")]
+pub use ultrafast_mcp_transport::signing::{
+    MessageSigner, SigningConfig, SigningKeypair, SigningMiddleware, SigningTransport,
 };
 
-// Streamable HTTP (feature = "http")
-#[cfg(feature = "http")]
+// Middleware (moved to streamable_http module)
+#[cfg(any(feature = "http-client", feature = "http-server"))]
+pub use ultrafast_mcp_transport::streamable_http::middleware::{
+    LoggingMiddleware, MiddlewareTransport, ProgressMiddleware, RateLimitMiddleware,
+    TransportMiddleware, ValidationMiddleware,
+};
+
+// Streamable HTTP (feature = "http-client" / "http-server")
+#[cfg(any(feature = "http-client", feature = "http-server"))]
 pub use ultrafast_mcp_transport::streamable_http;
 
-// Streamable HTTP (feature = "http")
-#[cfg(feature = "http")]
+// Streamable HTTP client half (feature = "http-client")
+#[cfg(all(feature = "http-client", not(feature = "bare-metal")))]
+pub use ultrafast_mcp_transport::streamable_http::create_streamable_http_client_default;
+#[cfg(feature = "http-client")]
+pub use ultrafast_mcp_transport::streamable_http::{
+    HttpConnectionPool, HttpPoolConfig, StreamableHttpClient, StreamableHttpClientConfig,
+    create_streamable_http_client_with_middleware,
+};
+
+// Streamable HTTP server half (feature = "http-server")
+#[cfg(feature = "session-file")]
+pub use ultrafast_mcp_transport::streamable_http::FileSessionStore;
+#[cfg(feature = "http-server")]
 pub use ultrafast_mcp_transport::streamable_http::{
-    HttpTransportConfig, HttpTransportServer, HttpTransportState, StreamableHttpClient,
-    StreamableHttpClientConfig, create_streamable_http_client_default,
-    create_streamable_http_client_with_middleware, create_streamable_http_server_default,
+    CorsPolicy, HardeningConfig, HardeningViolation, HttpTransportConfig, HttpTransportServer,
+    HttpTransportState, IdentityResolver, InMemorySessionStore, RequestDispatcher, SessionManager,
+    SessionMetadata, SessionStore, create_streamable_http_server_default,
     create_streamable_http_server_with_middleware,
 };
 
@@ -553,7 +638,10 @@ pub use ultrafast_mcp_monitoring::{
     exporters,
     // Re-export monitoring types explicitly for better discoverability
     health::{HealthCheck, HealthCheckResult, HealthChecker, HealthStatus},
-    metrics::{MetricsCollector, RequestMetrics, RequestTimer, SystemMetrics, TransportMetrics},
+    metrics::{
+        LatencyHistogram, LatencyPercentiles, MetricsCollector, RequestMetrics, RequestTimer,
+        SystemMetrics, TransportMetrics,
+    },
     middleware,
     tracing,
 };
@@ -44,9 +44,11 @@ pub use ultrafast_mcp_core::types::{ElicitationCapability, RootsCapability, Samp
 #[cfg(feature = "stdio")]
 pub use crate::{Transport, TransportConfig};
 
-// HTTP-specific types (available with http feature)
-#[cfg(feature = "http")]
-pub use crate::{HttpTransportConfig, StreamableHttpClient, StreamableHttpClientConfig};
+// HTTP-specific types (available with the http-client / http-server features)
+#[cfg(feature = "http-server")]
+pub use crate::HttpTransportConfig;
+#[cfg(feature = "http-client")]
+pub use crate::{StreamableHttpClient, StreamableHttpClientConfig};
 
 // AuthConfig only if oauth is enabled
 #[cfg(feature = "oauth")]
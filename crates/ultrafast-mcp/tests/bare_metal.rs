@@ -0,0 +1,154 @@
+//! The same requests get the same responses with and without `bare-metal`
+//!
+//! CI runs this file in both builds, so each is checked against the same
+//! expected responses.
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use ultrafast_mcp::protocol::JsonRpcMessage;
+use ultrafast_mcp::protocol::jsonrpc::{JsonRpcRequest, RequestId};
+use ultrafast_mcp::{MCPResult, Transport};
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct AddInput {
+    a: i64,
+    b: i64,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct AddOutput {
+    sum: i64,
+}
+
+async fn add(input: AddInput, _ctx: ultrafast_mcp::Context) -> MCPResult<AddOutput> {
+    Ok(AddOutput {
+        sum: input.a + input.b,
+    })
+}
+
+/// Send each request in turn, returning the responses as JSON
+async fn exchange(requests: Vec<(&str, Value)>) -> Vec<Value> {
+    let server = common::server("parity-server", common::tool_capabilities())
+        .tool("add", "Add two numbers", add)
+        .unwrap();
+    let mut client = common::serve(&server);
+
+    let mut responses = Vec::new();
+    for (id, (method, params)) in requests.into_iter().enumerate() {
+        let request = JsonRpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(RequestId::number(id as i64)),
+        );
+        client
+            .send_message(JsonRpcMessage::Request(request))
+            .await
+            .unwrap();
+        if method == "initialize" {
+            let initialized =
+                JsonRpcRequest::notification("notifications/initialized".to_string(), None);
+            client
+                .send_message(JsonRpcMessage::Notification(initialized))
+                .await
+                .unwrap();
+        }
+        loop {
+            if let JsonRpcMessage::Response(response) = client.receive_message().await.unwrap() {
+                responses.push(serde_json::to_value(response).unwrap());
+                break;
+            }
+        }
+    }
+    responses
+}
+
+fn int64() -> Value {
+    json!({"format": "int64", "type": "integer"})
+}
+
+#[tokio::test]
+async fn test_responses_do_not_depend_on_bookkeeping() {
+    let exchanges = vec![
+        (
+            "initialize",
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": {"name": "parity", "version": "1.0.0"}
+            }),
+            json!({"result": {
+                "capabilities": {"tools": {"listChanged": false}},
+                "protocolVersion": "2025-06-18",
+                "serverInfo": {"name": "parity-server", "version": "1.0.0"}
+            }}),
+        ),
+        ("ping", json!({}), json!({"result": {}})),
+        (
+            "tools/list",
+            json!({}),
+            json!({"result": {"tools": [{
+                "name": "add",
+                "description": "Add two numbers",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"a": int64(), "b": int64()},
+                    "required": ["a", "b"]
+                },
+                "outputSchema": {
+                    "type": "object",
+                    "properties": {"sum": int64()},
+                    "required": ["sum"]
+                }
+            }]}}),
+        ),
+        (
+            "tools/call",
+            json!({"name": "add", "arguments": {"a": 2, "b": 3}}),
+            json!({"result": {
+                "content": [{"type": "text", "text": "{\"sum\":5}"}],
+                "structuredContent": {"sum": 5}
+            }}),
+        ),
+        (
+            "tools/call",
+            json!({"name": "add", "arguments": {"a": "two"}}),
+            json!({"error": {
+                "code": -32602,
+                "message": "Tool call failed: Protocol error: Invalid parameters: Invalid arguments: invalid type: string \"two\", expected i64"
+            }}),
+        ),
+        (
+            "tools/call",
+            json!({"name": "missing", "arguments": {}}),
+            json!({"error": {
+                "code": -32602,
+                "message": "Tool call failed: Tool not found: missing"
+            }}),
+        ),
+        (
+            "tools/explode",
+            json!({}),
+            json!({"error": {
+                "code": -32601,
+                "message": "Method not implemented: tools/explode"
+            }}),
+        ),
+    ];
+
+    let requests = exchanges
+        .iter()
+        .map(|(method, params, _)| (*method, params.clone()))
+        .collect();
+    let responses = exchange(requests).await;
+    for (id, ((method, _, mut expected), response)) in
+        exchanges.into_iter().zip(responses).enumerate()
+    {
+        expected["jsonrpc"] = json!("2.0");
+        expected["id"] = json!(id);
+        assert_eq!(response, expected, "response to {method}");
+    }
+}
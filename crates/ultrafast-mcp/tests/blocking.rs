@@ -0,0 +1,58 @@
+//! The blocking wrappers, driven from plain threads without a tokio runtime
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use common::channel_pair;
+use serde::{Deserialize, Serialize};
+use ultrafast_mcp::{MCPResult, ToolCall, ToolContent, blocking};
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct AddInput {
+    a: i64,
+    b: i64,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct AddOutput {
+    sum: i64,
+}
+
+async fn add(input: AddInput, _ctx: ultrafast_mcp::Context) -> MCPResult<AddOutput> {
+    Ok(AddOutput {
+        sum: input.a + input.b,
+    })
+}
+
+#[test]
+fn test_blocking_client_calls_blocking_server() {
+    let (client_end, server_end) = channel_pair();
+
+    let server = common::server("blocking-server", common::tool_capabilities())
+        .tool("add", "Add two numbers", add)
+        .unwrap();
+    let server = blocking::Server::new(server).unwrap();
+    std::thread::spawn(move || server.run_with_transport(Box::new(server_end)));
+
+    let client = blocking::Client::new(common::client()).unwrap();
+    client.connect(Box::new(client_end)).unwrap();
+
+    let tools = client.list_tools().unwrap();
+    assert_eq!(tools.tools.len(), 1);
+    assert_eq!(tools.tools[0].name, "add");
+
+    let result = client
+        .call_tool(ToolCall {
+            name: "add".to_string(),
+            arguments: Some(serde_json::json!({"a": 2, "b": 3})),
+        })
+        .unwrap();
+    let ToolContent::Text { text } = &result.content[0] else {
+        panic!("expected text content, got {:?}", result.content);
+    };
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(text).unwrap()["sum"],
+        5
+    );
+}
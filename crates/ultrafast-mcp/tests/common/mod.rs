@@ -0,0 +1,124 @@
+//! Fixtures shared by the integration tests
+//!
+//! Each test file is its own crate and uses only some of these.
+
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use ultrafast_mcp::protocol::JsonRpcMessage;
+use ultrafast_mcp::{
+    ClientCapabilities, ClientInfo, DuplexTransport, MCPResult, ServerCapabilities, ServerInfo,
+    ToolsCapability, Transport, UltraFastClient, UltraFastServer, duplex_pair,
+};
+
+/// Capabilities of a server that offers a fixed set of tools
+pub fn tool_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        tools: Some(ToolsCapability {
+            list_changed: Some(false),
+        }),
+        ..Default::default()
+    }
+}
+
+/// A server called `name`, without tools or handlers yet
+pub fn server(name: &str, capabilities: ServerCapabilities) -> UltraFastServer {
+    UltraFastServer::new(
+        ServerInfo {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            authors: None,
+            homepage: None,
+            license: None,
+            repository: None,
+        },
+        capabilities,
+    )
+}
+
+/// A client with default info and capabilities
+pub fn client() -> UltraFastClient {
+    UltraFastClient::new(ClientInfo::default(), ClientCapabilities::default())
+}
+
+/// Run `server` on `transport` in the background
+pub fn run(server: &UltraFastServer, transport: impl Transport + 'static) {
+    let server = server.clone();
+    tokio::spawn(async move { server.run_with_transport(Box::new(transport)).await });
+}
+
+/// Run `server` over an in-memory connection, returning the client's end
+pub fn serve(server: &UltraFastServer) -> DuplexTransport {
+    let (client_end, server_end) = duplex_pair();
+    run(server, server_end);
+    client_end
+}
+
+/// Run `server` over an in-memory connection and connect `client` to it
+pub async fn connect(server: &UltraFastServer, client: UltraFastClient) -> UltraFastClient {
+    client.connect(Box::new(serve(server))).await.unwrap();
+    client
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub struct EchoInput {
+    pub text: String,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+pub struct EchoOutput {
+    pub text: String,
+}
+
+/// Tool answering with the text it was given
+pub async fn echo(input: EchoInput, _ctx: ultrafast_mcp::Context) -> MCPResult<EchoOutput> {
+    Ok(EchoOutput { text: input.text })
+}
+
+/// One end of an in-process connection passing messages without framing
+pub struct ChannelTransport {
+    sender: mpsc::UnboundedSender<JsonRpcMessage>,
+    receiver: mpsc::UnboundedReceiver<JsonRpcMessage>,
+}
+
+pub fn channel_pair() -> (ChannelTransport, ChannelTransport) {
+    let (client_tx, server_rx) = mpsc::unbounded_channel();
+    let (server_tx, client_rx) = mpsc::unbounded_channel();
+    (
+        ChannelTransport {
+            sender: client_tx,
+            receiver: client_rx,
+        },
+        ChannelTransport {
+            sender: server_tx,
+            receiver: server_rx,
+        },
+    )
+}
+
+#[async_trait]
+impl Transport for ChannelTransport {
+    async fn send_message(
+        &mut self,
+        message: JsonRpcMessage,
+    ) -> ultrafast_mcp_transport::Result<()> {
+        self.sender
+            .send(message)
+            .map_err(|_| ultrafast_mcp_transport::TransportError::ConnectionClosed)
+    }
+
+    async fn receive_message(&mut self) -> ultrafast_mcp_transport::Result<JsonRpcMessage> {
+        self.receiver
+            .recv()
+            .await
+            .ok_or(ultrafast_mcp_transport::TransportError::ConnectionClosed)
+    }
+
+    async fn close(&mut self) -> ultrafast_mcp_transport::Result<()> {
+        self.receiver.close();
+        Ok(())
+    }
+}
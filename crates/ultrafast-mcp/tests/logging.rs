@@ -0,0 +1,114 @@
+//! Log messages sent to clients, filtered by the level each one set
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use ultrafast_mcp::{
+    LogLevel, LoggingCapability, MCPError, MCPResult, ServerCapabilities, ServerNotification,
+    ToolCall, UltraFastClient, UltraFastServer,
+};
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct ChatterInput {}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct ChatterOutput {}
+
+async fn chatter(_input: ChatterInput, ctx: ultrafast_mcp::Context) -> MCPResult<ChatterOutput> {
+    let logged = async {
+        ctx.log_debug("debug").await?;
+        ctx.log_info("info").await?;
+        ctx.log_warn("warning").await?;
+        ctx.log_error("error").await
+    };
+    logged
+        .await
+        .map_err(|e| MCPError::internal_error(e.to_string()))?;
+    Ok(ChatterOutput {})
+}
+
+async fn connect() -> (UltraFastServer, UltraFastClient) {
+    let capabilities = ServerCapabilities {
+        logging: Some(LoggingCapability {}),
+        ..common::tool_capabilities()
+    };
+    let server = common::server("logging-server", capabilities)
+        .tool("chatter", "Log at every level", chatter)
+        .unwrap();
+    let client = common::connect(&server, common::client()).await;
+    (server, client)
+}
+
+/// The messages of the log notifications received, until one saying `last`
+async fn messages_until(
+    notifications: &mut broadcast::Receiver<ServerNotification>,
+    last: &str,
+) -> Vec<String> {
+    let mut messages = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let ServerNotification::LogMessage(log) = notifications.recv().await.unwrap() else {
+                continue;
+            };
+            let message = match log.data.get("message") {
+                Some(message) => message.as_str().unwrap_or_default().to_string(),
+                None => log.data.as_str().unwrap_or_default().to_string(),
+            };
+            messages.push(message);
+            if messages.last().is_some_and(|message| message == last) {
+                return;
+            }
+        }
+    })
+    .await
+    .unwrap();
+    messages
+}
+
+fn chatter_call() -> ToolCall {
+    ToolCall {
+        name: "chatter".to_string(),
+        arguments: Some(serde_json::json!({})),
+    }
+}
+
+#[tokio::test]
+async fn test_context_logs_reach_the_client_at_its_level() {
+    let (_server, client) = connect().await;
+    let mut notifications = client.subscribe_notifications();
+
+    client.call_tool(chatter_call()).await.unwrap();
+    assert_eq!(
+        messages_until(&mut notifications, "error").await,
+        ["info", "warning", "error"]
+    );
+
+    client.set_log_level(LogLevel::Error).await.unwrap();
+    client.call_tool(chatter_call()).await.unwrap();
+    assert_eq!(messages_until(&mut notifications, "error").await, ["error"]);
+}
+
+#[tokio::test]
+async fn test_server_logs_reach_sessions_whose_level_they_meet() {
+    let (server, client) = connect().await;
+    let mut notifications = client.subscribe_notifications();
+    client.set_log_level(LogLevel::Warning).await.unwrap();
+
+    let info = serde_json::json!({ "message": "cache warmed" });
+    assert_eq!(server.log_to_sessions(LogLevel::Info, None, info), 0);
+    let warning = serde_json::json!({ "message": "disk almost full" });
+    assert_eq!(
+        server.log_to_sessions(LogLevel::Warning, Some("storage"), warning),
+        1
+    );
+
+    assert_eq!(
+        messages_until(&mut notifications, "disk almost full").await,
+        ["disk almost full"]
+    );
+}
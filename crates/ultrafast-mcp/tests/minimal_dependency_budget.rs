@@ -0,0 +1,147 @@
+//! Dependency and binary-size budgets for the `minimal` (core + stdio)
+//! feature profile
+//!
+//! The `minimal` profile is meant for small CLI tools, so these tests fail
+//! when it starts pulling in HTTP, auth or observability stacks, when its
+//! dependency graph grows past the budget below, or when a release build of
+//! the `minimal_stdio` example grows past the size budget. Raise a budget only
+//! deliberately, together with the change that needs it.
+//!
+//! The checks run `cargo tree --offline` and `cargo build --offline`. Where
+//! cargo cannot run, such as in a vendored checkout without a populated
+//! registry, they are skipped with a message, except under CI (the `CI`
+//! variable is set), where that is a failure. The size budget needs a release
+//! build, so it is ignored by default; CI runs it with `--include-ignored`.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Maximum number of distinct packages (including this workspace's own crates)
+/// in the normal dependency graph of `ultrafast-mcp --features minimal`
+const MINIMAL_DEPENDENCY_BUDGET: usize = 80;
+
+/// Maximum size in bytes of the release build of the `minimal_stdio` example
+const MINIMAL_BINARY_BUDGET: u64 = 7 * 1024 * 1024;
+
+/// Crates that must never be compiled into the minimal profile
+const FORBIDDEN_CRATES: &[&str] = &[
+    "axum",
+    "hyper",
+    "reqwest",
+    "tower-http",
+    "oauth2",
+    "jsonwebtoken",
+    "opentelemetry",
+    "sysinfo",
+    "ultrafast-mcp-auth",
+    "ultrafast-mcp-monitoring",
+];
+
+/// Run cargo with `args` in this crate, or `None` when it cannot run outside
+/// CI
+fn cargo(args: &[&str]) -> Option<Output> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output();
+    let failure = match output {
+        Ok(output) if output.status.success() => return Some(output),
+        Ok(output) => format!(
+            "cargo {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => format!("cannot run cargo {}: {e}", args[0]),
+    };
+    if std::env::var_os("CI").is_some() {
+        panic!("{failure}");
+    }
+    eprintln!("skipping budget: {failure}");
+    None
+}
+
+/// Packages in the minimal profile
+fn minimal_profile_packages() -> Option<BTreeSet<String>> {
+    let output = cargo(&[
+        "tree",
+        "--offline",
+        "-p",
+        "ultrafast-mcp",
+        "--no-default-features",
+        "--features",
+        "minimal",
+        "-e",
+        "normal",
+        "--prefix",
+        "none",
+    ])?;
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+#[test]
+fn test_minimal_profile_dependency_budget() {
+    let Some(packages) = minimal_profile_packages() else {
+        return;
+    };
+
+    let forbidden: Vec<&str> = FORBIDDEN_CRATES
+        .iter()
+        .copied()
+        .filter(|name| packages.contains(*name))
+        .collect();
+    assert!(
+        forbidden.is_empty(),
+        "minimal profile pulls in {forbidden:?}"
+    );
+
+    assert!(
+        packages.len() <= MINIMAL_DEPENDENCY_BUDGET,
+        "minimal profile has {} packages, budget is {}: {:?}",
+        packages.len(),
+        MINIMAL_DEPENDENCY_BUDGET,
+        packages
+    );
+}
+
+#[test]
+#[ignore = "needs a release build; CI runs it with --include-ignored"]
+fn test_minimal_profile_binary_size_budget() {
+    // A separate target directory, so the build does not wait on the lock
+    // held by the `cargo test` running this test
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("minimal-size-budget");
+    let Some(_) = cargo(&[
+        "build",
+        "--offline",
+        "--release",
+        "-p",
+        "ultrafast-mcp",
+        "--no-default-features",
+        "--features",
+        "minimal",
+        "--example",
+        "minimal_stdio",
+        "--target-dir",
+        target_dir.to_str().unwrap(),
+    ]) else {
+        return;
+    };
+
+    let binary = target_dir
+        .join("release")
+        .join("examples")
+        .join(format!("minimal_stdio{}", std::env::consts::EXE_SUFFIX));
+    let size = std::fs::metadata(&binary).unwrap().len();
+    assert!(
+        size <= MINIMAL_BINARY_BUDGET,
+        "minimal_stdio is {size} bytes, budget is {MINIMAL_BINARY_BUDGET}"
+    );
+}
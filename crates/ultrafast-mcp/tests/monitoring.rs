@@ -0,0 +1,31 @@
+//! Requests instrumented by the server when the `monitoring` feature is on
+//! (and `bare-metal`, which compiles the instrumentation out, is off)
+
+#![cfg(all(feature = "stdio", feature = "monitoring", not(feature = "bare-metal")))]
+
+mod common;
+
+use common::echo;
+use ultrafast_mcp::ToolCall;
+
+#[tokio::test]
+async fn test_requests_are_counted_without_instrumenting_handlers() {
+    let server = common::server("monitored-server", common::tool_capabilities())
+        .tool("echo", "Echo the text back", echo)
+        .unwrap()
+        .with_monitoring();
+    let monitoring = server.monitoring().unwrap();
+    let client = common::connect(&server, common::client()).await;
+    client
+        .call_tool(ToolCall {
+            name: "echo".to_string(),
+            arguments: Some(serde_json::json!({"text": "counted"})),
+        })
+        .await
+        .unwrap();
+
+    let metrics = monitoring.metrics().get_metrics().await;
+    assert_eq!(metrics.request.method_counts["tools/call"], 1);
+    assert_eq!(metrics.request.method_counts["initialize"], 1);
+    assert_eq!(metrics.request.by_transport["duplex"].total_requests, 2);
+}
@@ -0,0 +1,110 @@
+//! Server notifications delivered to client subscribers
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use ultrafast_mcp::{
+    MCPResult, ServerCapabilities, ServerNotification, ToolCall, ToolsCapability, UltraFastClient,
+    UltraFastServer,
+};
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct WorkInput {
+    steps: u32,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct WorkOutput {
+    done: u32,
+}
+
+async fn work(input: WorkInput, ctx: ultrafast_mcp::Context) -> MCPResult<WorkOutput> {
+    for step in 1..=input.steps {
+        ctx.report_progress(step as f64, Some(input.steps as f64), None)
+            .await?;
+    }
+    Ok(WorkOutput { done: input.steps })
+}
+
+async fn connect() -> (UltraFastServer, UltraFastClient) {
+    let capabilities = ServerCapabilities {
+        tools: Some(ToolsCapability {
+            list_changed: Some(true),
+        }),
+        ..Default::default()
+    };
+    let server = common::server("notifying-server", capabilities)
+        .tool("work", "Work through some steps", work)
+        .unwrap();
+    let client = common::connect(&server, common::client()).await;
+    (server, client)
+}
+
+#[tokio::test]
+async fn test_progress_is_delivered_to_the_callback_for_its_token() {
+    let (_server, client) = connect().await;
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let recorded = reported.clone();
+    let _subscription = client.on_progress("work-1", move |progress| {
+        recorded.lock().unwrap().push(progress.progress);
+    });
+    let others = Arc::new(Mutex::new(0));
+    let counted = others.clone();
+    let _other = client.on_progress("work-2", move |_| *counted.lock().unwrap() += 1);
+
+    client
+        .call_tool_with_progress(
+            ToolCall {
+                name: "work".to_string(),
+                arguments: Some(serde_json::json!({ "steps": 3 })),
+            },
+            "work-1",
+        )
+        .await
+        .unwrap();
+
+    // The callbacks run on tasks of their own
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while reported.lock().unwrap().len() < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(*reported.lock().unwrap(), [1.0, 2.0, 3.0]);
+    assert_eq!(*others.lock().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_list_changes_reach_subscribers_and_callbacks() {
+    let (server, client) = connect().await;
+    let mut notifications = client.subscribe_notifications();
+    let (changed, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let subscription = client.on_tools_changed(move || {
+        let _ = changed.send(());
+    });
+
+    server.notify_tools_changed().await.unwrap();
+
+    let notification = tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(notification, ServerNotification::ToolsListChanged));
+    tokio::time::timeout(Duration::from_secs(5), changes.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Unsubscribing drops the callback, closing its channel
+    subscription.unsubscribe();
+    tokio::time::timeout(Duration::from_secs(5), changes.recv())
+        .await
+        .unwrap();
+}
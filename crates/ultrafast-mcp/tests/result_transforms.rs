@@ -0,0 +1,92 @@
+//! Client-side transforms applied to tool results
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use common::echo;
+use ultrafast_mcp::{
+    MCPError, MCPResult, NormalizeMarkdown, ToolCall, ToolContent, ToolResult, TruncateText,
+    UltraFastClient,
+};
+
+fn call(name: &str, text: &str) -> ToolCall {
+    ToolCall {
+        name: name.to_string(),
+        arguments: Some(serde_json::json!({ "text": text })),
+    }
+}
+
+fn first_text(result: &ToolResult) -> &str {
+    match &result.content[0] {
+        ToolContent::Text { text } => text,
+        other => panic!("expected text content, got {other:?}"),
+    }
+}
+
+/// Replace the JSON the typed tools answer with its `text` field
+fn unwrap_text(_tool: &str, mut result: ToolResult) -> MCPResult<ToolResult> {
+    let output: serde_json::Value = serde_json::from_str(first_text(&result))?;
+    result.content[0] = ToolContent::text(output["text"].as_str().unwrap_or_default().to_string());
+    Ok(result)
+}
+
+async fn connect(client: UltraFastClient) -> UltraFastClient {
+    let server = common::server("echo-server", common::tool_capabilities())
+        .tool("echo", "Echo the text", echo)
+        .unwrap()
+        .tool("loud_echo", "Echo the text, loudly", echo)
+        .unwrap();
+    common::connect(&server, client).await
+}
+
+#[tokio::test]
+async fn test_transforms_rewrite_results_of_their_tools() {
+    let client = connect(
+        common::client()
+            .with_result_transform(unwrap_text)
+            .with_result_transform(NormalizeMarkdown)
+            .with_tool_result_transform("loud_echo", |_: &str, mut result: ToolResult| {
+                if let Some(ToolContent::Text { text }) = result.content.first_mut() {
+                    *text = text.to_uppercase();
+                }
+                Ok(result)
+            })
+            .with_result_transform(TruncateText::new(20)),
+    )
+    .await;
+
+    let result = client
+        .call_tool(call("echo", "hello  \r\n\r\n\r\nworld\n"))
+        .await
+        .unwrap();
+    assert_eq!(first_text(&result), "hello\n\nworld");
+
+    let result = client.call_tool(call("loud_echo", "hello")).await.unwrap();
+    assert_eq!(first_text(&result), "HELLO");
+
+    let result = client
+        .call_tool(call("echo", &"long ".repeat(100)))
+        .await
+        .unwrap();
+    assert_eq!(first_text(&result), "long long long long ");
+    assert!(matches!(
+        result.content.last(),
+        Some(ToolContent::Text { text }) if text == "[truncated 479 characters]"
+    ));
+}
+
+#[tokio::test]
+async fn test_a_failing_transform_fails_the_call() {
+    let client = connect(common::client().with_tool_result_transform(
+        "echo",
+        |tool: &str, _: ToolResult| {
+            Err(MCPError::invalid_request(format!("{tool} is not allowed")))
+        },
+    ))
+    .await;
+
+    let error = client.call_tool(call("echo", "hi")).await.unwrap_err();
+    assert!(error.to_string().contains("echo is not allowed"));
+    assert!(client.call_tool(call("loud_echo", "hi")).await.is_ok());
+}
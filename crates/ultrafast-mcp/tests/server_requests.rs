@@ -0,0 +1,104 @@
+//! Requests from the server to the client, answered as JSON-RPC responses
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::channel_pair;
+use serde::{Deserialize, Serialize};
+use ultrafast_mcp::protocol::capabilities::ElicitationCapability;
+use ultrafast_mcp::{
+    ClientCapabilities, ClientElicitationHandler, ClientInfo, ElicitationBuilder, ElicitationField,
+    ElicitationRequest, ElicitationResponse, ElicitationResult, MCPResult, ToolCall, ToolContent,
+    UltraFastClient,
+};
+
+/// Answers every elicitation with the message it was shown
+struct EchoingUser;
+
+#[async_trait]
+impl ClientElicitationHandler for EchoingUser {
+    async fn handle_elicitation_request(
+        &self,
+        request: ElicitationRequest,
+    ) -> MCPResult<ElicitationResponse> {
+        Ok(ElicitationResponse::accept(
+            serde_json::json!({ "answer": request.message }),
+        ))
+    }
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct AskInput {
+    question: String,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct AskOutput {
+    answer: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Answer {
+    answer: String,
+}
+
+async fn ask(input: AskInput, ctx: ultrafast_mcp::Context) -> MCPResult<AskOutput> {
+    let answer = ctx
+        .elicit_as::<Answer>(
+            ElicitationBuilder::new(input.question).field("answer", ElicitationField::string()),
+        )
+        .await?;
+    match answer {
+        ElicitationResult::Accepted(Answer { answer }) => Ok(AskOutput { answer }),
+        other => panic!("expected an accepted answer, got {other:?}"),
+    }
+}
+
+fn text(content: &[ToolContent]) -> serde_json::Value {
+    let ToolContent::Text { text } = &content[0] else {
+        panic!("expected text content, got {content:?}");
+    };
+    serde_json::from_str(text).unwrap()
+}
+
+#[tokio::test]
+async fn test_concurrent_elicitations_are_matched_to_their_requests() {
+    let (client_end, server_end) = channel_pair();
+
+    let server = common::server("asking-server", common::tool_capabilities())
+        .tool("ask", "Ask the user a question", ask)
+        .unwrap();
+    common::run(&server, server_end);
+
+    let client = Arc::new(
+        UltraFastClient::new(
+            ClientInfo::default(),
+            ClientCapabilities {
+                elicitation: Some(ElicitationCapability {}),
+                ..Default::default()
+            },
+        )
+        .with_elicitation_handler(Arc::new(EchoingUser)),
+    );
+    client.connect(Box::new(client_end)).await.unwrap();
+
+    let calls = (0..5).map(|i| {
+        let client = client.clone();
+        tokio::spawn(async move {
+            client
+                .call_tool(ToolCall {
+                    name: "ask".to_string(),
+                    arguments: Some(serde_json::json!({ "question": format!("question {i}") })),
+                })
+                .await
+        })
+    });
+    for (i, call) in calls.collect::<Vec<_>>().into_iter().enumerate() {
+        let result = call.await.unwrap().unwrap();
+        assert_eq!(text(&result.content)["answer"], format!("question {i}"));
+    }
+}
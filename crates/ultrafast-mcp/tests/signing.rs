@@ -0,0 +1,69 @@
+//! Ed25519 signatures on the messages between a client and a server
+
+#![cfg(all(feature = "stdio", feature = "signing"))]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::echo;
+use ultrafast_mcp::{MessageSigner, SigningConfig, SigningKeypair, SigningTransport, ToolCall};
+
+/// Start a server that signs with `server_key` and only trusts `client_key`,
+/// and return the client's end of the connection
+fn serve(
+    server_key: &SigningKeypair,
+    client_key: &SigningKeypair,
+) -> ultrafast_mcp::DuplexTransport {
+    let signer = MessageSigner::from_config(
+        &SigningConfig::new("server")
+            .with_private_key(server_key.private_key.clone())
+            .with_trusted_key("client", client_key.public_key.clone()),
+    )
+    .unwrap();
+    let server = common::server("signing-server", common::tool_capabilities())
+        .tool("echo", "Echo the text back", echo)
+        .unwrap()
+        .with_message_signer(signer);
+    common::serve(&server)
+}
+
+#[tokio::test]
+async fn test_signed_client_talks_to_signed_server() {
+    let (server_key, client_key) = (SigningKeypair::generate(), SigningKeypair::generate());
+    let client_end = serve(&server_key, &client_key);
+    let signer = MessageSigner::from_config(
+        &SigningConfig::new("client")
+            .with_private_key(client_key.private_key)
+            .with_trusted_key("server", server_key.public_key),
+    )
+    .unwrap();
+
+    let client = common::client();
+    client
+        .connect(Box::new(SigningTransport::new(
+            Box::new(client_end),
+            Arc::new(signer),
+        )))
+        .await
+        .unwrap();
+    let result = client
+        .call_tool(ToolCall {
+            name: "echo".to_string(),
+            arguments: Some(serde_json::json!({"text": "signed"})),
+        })
+        .await
+        .unwrap();
+    assert_eq!(result.structured_content.unwrap()["text"], "signed");
+}
+
+#[tokio::test]
+async fn test_server_refuses_unsigned_client() {
+    let (server_key, client_key) = (SigningKeypair::generate(), SigningKeypair::generate());
+    let client_end = serve(&server_key, &client_key);
+
+    let client = common::client().with_timeout(Duration::from_secs(5));
+    let connected = client.connect(Box::new(client_end)).await;
+    assert!(connected.is_err());
+}
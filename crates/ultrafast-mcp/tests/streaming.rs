@@ -0,0 +1,192 @@
+//! Tool results and resource bytes streamed in chunks
+
+#![cfg(feature = "stdio")]
+
+mod common;
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncReadExt;
+use ultrafast_mcp::{
+    ByteRange, MCPResult, ReadResourceRequest, ReadResourceStreamRequest, ResourceContent,
+    StaticResources, StreamTransport, ToolCall, ToolContent, TransportDescription, TransportKind,
+    UltraFastClient,
+};
+use ultrafast_mcp_core::config::TimeoutConfig;
+
+const ARCHIVE_URI: &str = "file:///archive.bin";
+
+/// Larger than a few chunks, and than the duplex buffer
+fn archive() -> Vec<u8> {
+    (0..300_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct DumpInput {
+    rows: u32,
+}
+
+#[derive(Serialize, schemars::JsonSchema)]
+struct DumpOutput {
+    rows: u32,
+}
+
+async fn dump(input: DumpInput, ctx: ultrafast_mcp::Context) -> MCPResult<DumpOutput> {
+    for row in 0..input.rows {
+        ctx.stream_content(ToolContent::text(format!("row {row}")))
+            .await?;
+    }
+    Ok(DumpOutput { rows: input.rows })
+}
+
+async fn connect() -> UltraFastClient {
+    connect_with(common::client()).await
+}
+
+async fn connect_with(client: UltraFastClient) -> UltraFastClient {
+    let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+    let description = || TransportDescription::new(TransportKind::Custom("test".to_string()));
+
+    let server = common::server("streaming-server", common::tool_capabilities())
+        .tool("dump", "Dump rows one at a time", dump)
+        .unwrap()
+        .with_resource_handler(StaticResources::new().with_content(
+            ARCHIVE_URI,
+            ResourceContent::blob_from_bytes(
+                ARCHIVE_URI.to_string(),
+                &archive(),
+                "application/octet-stream".to_string(),
+            ),
+        ));
+    common::run(&server, StreamTransport::new(server_end, description()));
+
+    client
+        .connect(Box::new(StreamTransport::new(client_end, description())))
+        .await
+        .unwrap();
+    client
+}
+
+fn dump_call(rows: u32) -> ToolCall {
+    ToolCall {
+        name: "dump".to_string(),
+        arguments: Some(serde_json::json!({ "rows": rows })),
+    }
+}
+
+fn texts(content: &[ToolContent]) -> Vec<&str> {
+    content
+        .iter()
+        .map(|content| match content {
+            ToolContent::Text { text } => text.as_str(),
+            other => panic!("expected text content, got {other:?}"),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_chunks_are_streamed_before_the_result() {
+    let client = connect().await;
+
+    let mut stream = client.call_tool_streaming(dump_call(3));
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        chunks.push(chunk);
+    }
+    assert_eq!(texts(&chunks), ["row 0", "row 1", "row 2"]);
+
+    let result = stream.result().await.unwrap();
+    assert_eq!(result.content.len(), 1);
+    assert!(texts(&result.content)[0].contains("\"rows\""));
+}
+
+#[tokio::test]
+async fn test_chunks_are_collected_without_streaming() {
+    let client = connect().await;
+
+    let result = client.call_tool(dump_call(2)).await.unwrap();
+    let texts = texts(&result.content);
+    assert_eq!(texts[..2], ["row 0", "row 1"]);
+    assert!(texts[2].contains("\"rows\""));
+}
+
+#[tokio::test]
+async fn test_resource_bytes_are_streamed_to_a_writer() {
+    let client = connect().await;
+
+    let mut bytes = Vec::new();
+    let response = client
+        .read_resource_to_writer(ARCHIVE_URI, &mut bytes)
+        .await
+        .unwrap();
+    assert_eq!(response.length, 300_000);
+    assert_eq!(response.size, Some(300_000));
+    assert_eq!(bytes, archive());
+
+    let mut bytes = Vec::new();
+    let request = ReadResourceStreamRequest {
+        uri: ARCHIVE_URI.to_string(),
+        range: Some(ByteRange::new(1_000, 70_000)),
+        chunk_size: Some(16 * 1024),
+    };
+    let response = client
+        .read_resource_stream(request, &mut bytes)
+        .await
+        .unwrap();
+    assert_eq!((response.offset, response.length), (1_000, 70_000));
+    assert_eq!(bytes, archive()[1_000..71_000]);
+}
+
+#[tokio::test]
+async fn test_resource_read_with_a_range() {
+    let client = connect().await;
+
+    let request = ReadResourceRequest::new(ARCHIVE_URI).with_range(ByteRange::from_offset(299_990));
+    let response = client.read_resource(request).await.unwrap();
+    assert_eq!(
+        response.contents[0].to_bytes().unwrap(),
+        archive()[299_990..]
+    );
+}
+
+#[tokio::test]
+async fn test_slow_resource_stream_does_not_time_out() {
+    // Far less than the whole read takes, but more than any one chunk
+    let client = connect_with(common::client().with_timeout_config(TimeoutConfig {
+        resource_read_timeout: Duration::from_millis(500),
+        ..TimeoutConfig::default()
+    }))
+    .await;
+
+    // Takes the bytes a chunk at a time, pausing after each
+    let (mut writer, mut reader) = tokio::io::duplex(4 * 1024);
+    let drain = tokio::spawn(async move {
+        let mut bytes = Vec::new();
+        let mut chunk = vec![0; 4 * 1024];
+        loop {
+            match reader.read(&mut chunk).await.unwrap() {
+                0 => return bytes,
+                read => bytes.extend_from_slice(&chunk[..read]),
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let request = ReadResourceStreamRequest {
+        uri: ARCHIVE_URI.to_string(),
+        range: None,
+        chunk_size: Some(4 * 1024),
+    };
+    let started = std::time::Instant::now();
+    let response = client
+        .read_resource_stream(request, &mut writer)
+        .await
+        .unwrap();
+    // Reading waited for the writer
+    assert!(started.elapsed() > Duration::from_millis(500));
+    drop(writer);
+    assert_eq!(response.length, 300_000);
+    assert_eq!(drain.await.unwrap(), archive());
+}
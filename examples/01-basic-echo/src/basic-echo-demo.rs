@@ -14,8 +14,8 @@ use std::process::Stdio;
 use tokio::process::Command;
 use tracing::{info, warn};
 use ultrafast_mcp::{
-    ClientCapabilities, ClientInfo, HttpTransportConfig, ListToolsRequest, MCPError, MCPResult,
-    ServerCapabilities, ServerInfo, Tool, ToolCall, ToolContent, ToolHandler,
+    ClientCapabilities, ClientInfo, CorsPolicy, HttpTransportConfig, ListToolsRequest, MCPError,
+    MCPResult, ServerCapabilities, ServerInfo, Tool, ToolCall, ToolContent, ToolHandler,
     ToolResult as ServerToolResult, ToolsCapability, UltraFastClient, UltraFastServer,
 };
 
@@ -88,6 +88,8 @@ impl ToolHandler for DemoEchoHandler {
         Ok(ServerToolResult {
             content: vec![ultrafast_mcp::ToolContent::text(response.to_string())],
             is_error: None,
+            structured_content: None,
+            progress_summary: None,
         })
     }
 
@@ -295,7 +297,7 @@ async fn run_integrated_demo(host: &str, port: u16) -> anyhow::Result<()> {
             port,
             cors_enabled: true,
             protocol_version: "2025-06-18".to_string(),
-            allow_origin: Some("*".to_string()),
+            cors: CorsPolicy::permissive(),
             monitoring_enabled: true,
             enable_sse_resumability: true,
         };
@@ -0,0 +1,248 @@
+//! Basic echo server and its self-test
+//!
+//! [`server`] builds the echo server run by the `basic-echo-server` binary
+//! over STDIO or Streamable HTTP. [`self_test`] runs the same server and a
+//! client in one process over [`duplex_pair`], which is how the example is
+//! exercised in CI.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info};
+use ultrafast_mcp::{
+    ClientCapabilities, ClientInfo, ListToolsRequest, ListToolsResponse, MCPError, MCPResult,
+    ServerCapabilities, ServerInfo, Tool, ToolCall, ToolContent, ToolHandler, ToolResult,
+    ToolsCapability, UltraFastClient, UltraFastServer, duplex_pair,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EchoRequest {
+    #[serde(default = "default_message")]
+    message: String,
+}
+
+fn default_message() -> String {
+    "Hello, World!".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EchoResponse {
+    message: String,
+    timestamp: String,
+    echo_count: u32,
+    server_id: String,
+    transport: String,
+}
+
+/// Serves the `echo` tool
+pub struct EchoToolHandler {
+    echo_count: std::sync::atomic::AtomicU32,
+    transport_type: String,
+}
+
+impl EchoToolHandler {
+    /// Handler naming `transport_type` in its responses
+    pub fn new(transport_type: &str) -> Self {
+        Self {
+            echo_count: std::sync::atomic::AtomicU32::new(0),
+            transport_type: transport_type.to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for EchoToolHandler {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        info!(
+            "Handling tool call: {} (transport: {})",
+            call.name, self.transport_type
+        );
+
+        // Validate tool name
+        if call.name != "echo" {
+            return Err(MCPError::method_not_found(format!(
+                "Unknown tool: {}",
+                call.name
+            )));
+        }
+
+        // Parse and validate request
+        let arguments = call
+            .arguments
+            .ok_or_else(|| MCPError::invalid_params("Missing arguments".to_string()))?;
+
+        let request: EchoRequest = serde_json::from_value(arguments).map_err(|e| {
+            error!("Failed to parse echo request: {}", e);
+            MCPError::invalid_params(format!("Invalid request format: {e}"))
+        })?;
+
+        // Validate input
+        if request.message.is_empty() {
+            return Err(MCPError::invalid_params(
+                "Message cannot be empty".to_string(),
+            ));
+        }
+
+        if request.message.len() > 1000 {
+            return Err(MCPError::invalid_params(
+                "Message too long (max 1000 characters)".to_string(),
+            ));
+        }
+
+        // Increment echo counter
+        let echo_count = self
+            .echo_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            + 1;
+
+        // Process the request
+        let response = EchoResponse {
+            message: request.message,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            echo_count,
+            server_id: format!("echo-server-{}", std::process::id()),
+            transport: self.transport_type.clone(),
+        };
+
+        let response_text = serde_json::to_string_pretty(&response).map_err(|e| {
+            error!("Failed to serialize echo response: {}", e);
+            MCPError::serialization_error(e.to_string())
+        })?;
+
+        info!(
+            "Echo tool completed successfully (count: {}, transport: {})",
+            echo_count, self.transport_type
+        );
+        Ok(ToolResult {
+            content: vec![ToolContent::text(response_text)],
+            is_error: None,
+            structured_content: None,
+            progress_summary: None,
+        })
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        info!(
+            "Listing available tools (transport: {})",
+            self.transport_type
+        );
+        Ok(ListToolsResponse {
+            tools: vec![Tool {
+                name: "echo".to_string(),
+                description: format!(
+                    "Echo back a message with timestamp and metadata (transport: {})",
+                    self.transport_type
+                ),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "message": {
+                            "type": "string",
+                            "description": "Message to echo back (max 1000 characters, optional - defaults to 'Hello, World!')",
+                            "maxLength": 1000,
+                            "default": "Hello, World!"
+                        }
+                    }
+                }),
+                output_schema: None,
+                annotations: None,
+            }],
+            next_cursor: None,
+        })
+    }
+}
+
+/// The echo server, describing itself as running over `transport`
+pub fn server(transport: &str) -> UltraFastServer {
+    // Create server capabilities
+    let capabilities = ServerCapabilities {
+        tools: Some(ToolsCapability {
+            list_changed: Some(true),
+        }),
+        ..Default::default()
+    };
+
+    // Create server info
+    let server_info = ServerInfo {
+        name: "basic-echo-server".to_string(),
+        version: "1.0.0".to_string(),
+        description: Some(format!(
+            "A simple echo server for MCP with {transport} transport"
+        )),
+        authors: Some(vec!["ULTRAFAST_MCP Team".to_string()]),
+        homepage: Some("https://github.com/ultrafast-mcp/ultrafast-mcp".to_string()),
+        license: Some("MIT OR Apache-2.0".to_string()),
+        repository: Some("https://github.com/ultrafast-mcp/ultrafast-mcp".to_string()),
+    };
+
+    // Create server with tool handler
+    UltraFastServer::new(server_info, capabilities)
+        .with_tool_handler(Arc::new(EchoToolHandler::new(transport)))
+}
+
+/// Run the echo server and a client against each other in memory
+///
+/// Lists the tools, echoes two messages and checks that invalid input is
+/// rejected, failing on the first unexpected answer.
+pub async fn self_test() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = duplex_pair();
+    let server = server("Duplex");
+    let server =
+        tokio::spawn(async move { server.run_with_transport(Box::new(server_transport)).await });
+
+    let client = UltraFastClient::new(
+        ClientInfo {
+            name: "basic-echo-self-test".to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            authors: None,
+            homepage: None,
+            license: None,
+            repository: None,
+        },
+        ClientCapabilities::default(),
+    );
+    client.connect(Box::new(client_transport)).await?;
+
+    let tools = client.list_tools_default().await?.tools;
+    anyhow::ensure!(
+        tools.iter().map(|tool| tool.name.as_str()).eq(["echo"]),
+        "expected only the echo tool, got {tools:?}"
+    );
+
+    for (count, message) in [(1, "Hello, duplex!"), (2, "Hello again!")] {
+        let result = client
+            .call_tool(ToolCall {
+                name: "echo".to_string(),
+                arguments: Some(serde_json::json!({ "message": message })),
+            })
+            .await?;
+        let Some(ToolContent::Text { text }) = result.content.first() else {
+            anyhow::bail!("expected text content, got {:?}", result.content);
+        };
+        let response: EchoResponse = serde_json::from_str(text)?;
+        anyhow::ensure!(response.message == message, "echoed {}", response.message);
+        anyhow::ensure!(
+            response.echo_count == count,
+            "echo count {}",
+            response.echo_count
+        );
+        anyhow::ensure!(
+            response.transport == "Duplex",
+            "transport {}",
+            response.transport
+        );
+    }
+
+    let empty = client
+        .call_tool(ToolCall {
+            name: "echo".to_string(),
+            arguments: Some(serde_json::json!({ "message": "" })),
+        })
+        .await;
+    anyhow::ensure!(empty.is_err(), "an empty message was echoed");
+
+    // The server stops once the client hangs up
+    client.disconnect().await?;
+    server.await??;
+    Ok(())
+}
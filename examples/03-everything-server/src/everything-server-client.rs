@@ -3,9 +3,11 @@
 //! This example showcases the new convenience methods for different authentication types.
 
 use std::sync::Arc;
+#[cfg(all(feature = "http", feature = "oauth"))]
+use ultrafast_mcp::AuthMethod;
 use ultrafast_mcp::types::ElicitationAction;
 use ultrafast_mcp::{
-    ClientCapabilities, ClientElicitationHandler, ClientInfo, ElicitationRequest,
+    ClientCapabilities, ClientElicitationHandler, ClientInfo, ConnectOptions, ElicitationRequest,
     ElicitationResponse, ListPromptsRequest, ListResourcesRequest, ListToolsRequest,
     StreamableHttpClientConfig, ToolCall, ToolContent, UltraFastClient,
 };
@@ -76,17 +78,18 @@ async fn main() -> anyhow::Result<()> {
     client.disconnect().await?;
 
     println!("\n🔗 Connection Method 2: With Bearer Token Authentication");
-    println!("   Using: client.connect_streamable_http_with_bearer(url, token).await?");
+    println!(
+        "   Using: client.connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::bearer(token))).await?"
+    );
 
     // Method 2: Bearer token authentication
     #[cfg(all(feature = "http", feature = "oauth"))]
     {
         let client2 = UltraFastClient::new(client_info.clone(), capabilities.clone());
         let mock_token = "demo-bearer-token-12345";
-        match client2
-            .connect_streamable_http_with_bearer(url, mock_token.to_string())
-            .await
-        {
+        let options = ConnectOptions::streamable_http(url)
+            .with_auth(AuthMethod::bearer(mock_token.to_string()));
+        match client2.connect_with(options).await {
             Ok(_) => {
                 println!("✅ Connected with Bearer token (server may not require auth)");
                 test_connection(&client2, "Bearer Token").await?;
@@ -105,17 +108,18 @@ async fn main() -> anyhow::Result<()> {
     }
 
     println!("\n🔗 Connection Method 3: With API Key Authentication");
-    println!("   Using: client.connect_streamable_http_with_api_key(url, api_key).await?");
+    println!(
+        "   Using: client.connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::api_key(key))).await?"
+    );
 
     // Method 3: API key authentication (new convenience method)
     #[cfg(all(feature = "http", feature = "oauth"))]
     {
         let client3 = UltraFastClient::new(client_info.clone(), capabilities.clone());
         let mock_api_key = "demo-api-key-67890";
-        match client3
-            .connect_streamable_http_with_api_key(url, mock_api_key.to_string())
-            .await
-        {
+        let options = ConnectOptions::streamable_http(url)
+            .with_auth(AuthMethod::api_key(mock_api_key.to_string()));
+        match client3.connect_with(options).await {
             Ok(_) => {
                 println!("✅ Connected with API key (server may not require auth)");
                 test_connection(&client3, "API Key").await?;
@@ -134,20 +138,19 @@ async fn main() -> anyhow::Result<()> {
     }
 
     println!("\n🔗 Connection Method 4: With Basic Authentication");
-    println!("   Using: client.connect_streamable_http_with_basic(url, username, password).await?");
+    println!(
+        "   Using: client.connect_with(ConnectOptions::streamable_http(url).with_auth(AuthMethod::basic(username, password))).await?"
+    );
 
     // Method 4: Basic authentication (new convenience method)
     #[cfg(all(feature = "http", feature = "oauth"))]
     {
         let client4 = UltraFastClient::new(client_info.clone(), capabilities.clone());
-        match client4
-            .connect_streamable_http_with_basic(
-                url,
-                "demo_user".to_string(),
-                "demo_pass".to_string(),
-            )
-            .await
-        {
+        let options = ConnectOptions::streamable_http(url).with_auth(AuthMethod::basic(
+            "demo_user".to_string(),
+            "demo_pass".to_string(),
+        ));
+        match client4.connect_with(options).await {
             Ok(_) => {
                 println!("✅ Connected with Basic auth (server may not require auth)");
                 test_connection(&client4, "Basic Auth").await?;
@@ -191,7 +194,9 @@ async fn main() -> anyhow::Result<()> {
     }
 
     println!("\n🔗 Connection Method 6: Custom Configuration (advanced)");
-    println!("   Using: client.connect_streamable_http_with_config(custom_config).await?");
+    println!(
+        "   Using: client.connect_with(ConnectOptions::streamable_http_config(custom_config)).await?"
+    );
 
     // Method 6: Custom configuration (for advanced use cases)
     let client6 = UltraFastClient::new(client_info.clone(), capabilities.clone())
@@ -206,10 +211,12 @@ async fn main() -> anyhow::Result<()> {
         auth_token: None,
         oauth_config: None,
         auth_method: None,
+        event_stream: None,
+        pool: Default::default(),
     };
 
     client6
-        .connect_streamable_http_with_config(custom_config)
+        .connect_with(ConnectOptions::streamable_http_config(custom_config))
         .await?;
     println!("✅ Connected with custom configuration");
 
@@ -219,9 +226,9 @@ async fn main() -> anyhow::Result<()> {
     println!("\n🎉 All connection methods demonstrated successfully!");
     println!("💡 Key takeaways:");
     println!("   • Use connect_streamable_http() for simple connections");
-    println!("   • Use connect_streamable_http_with_*() for specific auth types");
+    println!("   • Use connect_with(ConnectOptions::...) for per-connection auth and timeouts");
     println!("   • Use client.with_*_auth() for client-level auth integration");
-    println!("   • Use connect_streamable_http_with_config() for advanced scenarios");
+    println!("   • Use ConnectOptions::streamable_http_config() for advanced scenarios");
 
     Ok(())
 }
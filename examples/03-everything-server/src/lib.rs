@@ -0,0 +1,1073 @@
+//! Everything MCP Server Example (Streamable HTTP)
+//! Comprehensive implementation matching the official MCP everything example
+//!
+//! The `everything-server` binary serves [`server`] over Streamable HTTP;
+//! [`self_test`] runs it against a client in-process over [`duplex_pair`].
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use ultrafast_mcp::McpCoreError::ResourceError;
+use ultrafast_mcp::types::resources::{
+    ListResourceTemplatesRequest, ListResourceTemplatesResponse,
+};
+use ultrafast_mcp::types::roots::RootSecurityValidator;
+use ultrafast_mcp::{
+    ClientCapabilities, ClientInfo, CompleteRequest, CompletionRouter, Context, ElicitationHandler,
+    ListResourcesRequest, ListResourcesResponse, ListToolsRequest, ListToolsResponse, MCPError,
+    MCPResult, PromptHandler, ReadResourceRequest, ReadResourceResponse, Resource, ResourceContent,
+    ResourceHandler, ResourceSubscriptionHandler, ResourceTemplateCompleter, RootsHandler,
+    SamplingHandler, ServerCapabilities, ServerInfo, Tool, ToolCall, ToolContent, ToolHandler,
+    ToolResult, UltraFastClient, UltraFastServer, duplex_pair,
+    types::{completion, elicitation, prompts, resources, roots, sampling},
+};
+
+// Tiny test image (base64 encoded PNG)
+const MCP_TINY_IMAGE: &str = "iVBORw0KGgoAAAANSUhEUgAAABQAAAAUCAYAAACNiR0NAAAKsGlDQ1BJQ0MgUHJvZmlsZQAASImVlwdUU+kSgOfe9JDQEiIgJfQmSCeAlBBaAAXpYCMkAUKJMRBU7MriClZURLCs6KqIgo0idizYFsWC3QVZBNR1sWDDlXeBQ9jdd9575805c+a7c+efmf+e/z9nLgCdKZDJMlF1gCxpjjwyyI8dn5DIJvUABRiY0kBdIMyWcSMiwgCTUft3+dgGyJC9YzuU69/f/1fREImzhQBIBMbJomxhFsbHMe0TyuQ5ALg9mN9kbo5siK9gzJRjDWL8ZIhTR7hviJOHGY8fjomO5GGsDUCmCQTyVACaKeZn5wpTsTw0f4ztpSKJFGPsGbyzsmaLMMbqgiUWI8N4KD8n+S95Uv+WM1mZUyBIVfLIXoaF7C/JlmUK5v+fn+N/S1amYrSGOaa0NHlwJGaxvpAHGbNDlSxNnhI+yhLRcPwwpymCY0ZZmM1LHGWRwD9UuTZzStgop0gC+co8OfzoURZnB0SNsnx2pLJWipzHHWWBfKyuIiNG6U8T85X589Ki40Y5VxI7ZZSzM6JCx2J4Sr9cEansXywN8hurG6jce1b2X/Yr4SvX5qRFByv3LhjrXyzljuXMjlf2JhL7B4zFxCjjZTl+ylqyzAhlvDgzSOnPzo1Srs3BDuTY2gjlN0wXhESMMoRBELAhBjIhB+QggECQgBTEOeJ5Q2cUeLNl8+WS1LQcNhe7ZWI2Xyq0m8B2tHd0Bhi6syNH4j1r+C4irGtjvhWVAF4nBgcHT475Qm4BHEkCoNaO+SxnAKh3A1w5JVTIc0d8Q9cJCEAFNWCCDhiACViCLTiCK3iCLwRACIRDNCTATBBCGmRhnc+FhbAMCqAI1sNmKIOdsBv2wyE4CvVwCs7DZbgOt+AePIZ26IJX0AcfYQBBEBJCRxiIDmKImCE2iCPCQbyRACQMiUQSkCQkFZEiCmQhsgIpQoqRMmQXUokcQU4g55GrSCvyEOlAepF3yFcUh9JQJqqPmqMTUQ7KRUPRaHQGmorOQfPQfHQtWopWoAfROvQ8eh29h7ajr9B+HOBUcCycEc4Wx8HxcOG4RFwKTo5bjCvEleAqcNW4Rlwz7g6uHfca9wVPxDPwbLwt3hMfjI/BC/Fz8Ivxq/Fl+P34OvxF/B18B74P/51AJ+gRbAgeBD4hnpBKmEsoIJQQ9hJqCZcI9whdhI9EIpFFtCC6EYOJCcR04gLiauJ2Yg3xHLGV2EnsJ5FIOiQbkhcpnCQg5ZAKSFtJB0lnSbdJXaTPZBWyIdmRHEhOJEvJy8kl5APkM+Tb5G7yAEWdYkbxoIRTRJT5lHWUPZRGyk1KF2WAqkG1oHpRo6np1GXUUmo19RL1CfW9ioqKsYq7ylQVicpSlVKVwypXVDpUvtA0adY0Hm06TUFbS9tHO0d7SHtPp9PN6b70RHoOfS29kn6B/oz+WZWhaqfKVxWpLlEtV61Tva36Ro2iZqbGVZuplqdWonZM7abaa3WKurl6T12gvli9XP2E+n31fg2GhoNGuEaWxmqNAxpXNXo0SZrmmgGaIs18zd2aFzQ7GTiGCYPHEDJWMPYwLjG6mESmBZPPTGcWMQ8xW5h9WppazlqxWvO0yrVOa7WzcCxzFp+VyVrHOspqY30dpz+OO048btW46nG3x33SHq/tqy3WLtSu0b6n/VWHrROgk6GzQade56kuXtdad6ruXN0dupd0X49njvccLxxfOP7o+Ed6qJ61XqTeAr3dejf0+vUN9IP0Zfpb9S/ovzZgGfgapBtsMjhj0GvIMPQ2lBhuMjxr+JKtxeayM9ml7IvsPiM9o2AjhdEuoxajAWML4xjj5cY1xk9NqCYckxSTTSZNJn2mhqaTTReaVpk+MqOYcczSzLaYNZt9MrcwjzNfaV5v3mOhbcG3yLOosnhiSbf0sZxjWWF514poxbHKsNpudcsatXaxTrMut75pg9q42khsttu0TiBMcJ8gnVAx4b4tzZZrm2tbZdthx7ILs1tuV2/3ZqLpxMSJGyY2T/xu72Kfab/H/rGDpkOIw3KHRod3jtaOQsdyx7tOdKdApyVODU5vnW2cxc47nB+4MFwmu6x0aXL509XNVe5a7drrZuqW5LbN7T6HyYngrOZccSe4+7kvcT/l/sXD1SPH46jHH562nhmeBzx7JllMEk/aM6nTy9hL4LXLq92b7Z3k/ZN3u4+Rj8Cnwue5r4mvyHevbzfXipvOPch942fvJ/er9fvE8+At4p3zx/kH+Rf6twRoBsQElAU8CzQOTA2sCuwLcglaEHQumBAcGrwh+D5fny/kV/L7QtxCFoVcDKWFRoWWhT4Psw6ThzVORieHTN44+ckUsynSKfXhEM4P3xj+NMIiYk7EyanEqRFTy6e+iHSIXBjZHMWImhV1IOpjtF/0uujHMZYxipimWLXY6bGVsZ/i/OOK49rjJ8Yvin+eoJsgSWhIJCXGJu5N7J8WMG3ztK7pLtMLprfNsJgxb8bVmbozM2eenqU2SzDrWBIhKS7pQNI3QbigQtCfzE/eltwn5Am3CF+JfEWbRL1iL3GxuDvFK6U4pSfVK3Vjam+aT1pJ2msJT1ImeZsenL4z/VNGeMa+jMHMuMyaLHJWUtYJqaY0Q3pxtsHsebNbZTayAln7HI85m+f0yUPle7OR7BnZDTlMbDi6obBU/KDoyPXOLc/9PDd27rF5GvOk827Mt56/an53XmDezwvwC4QLmhYaLVy2sGMRd9Guxcji5MVNS0yW5C/pWhq0dP8y6rKMZb8st19evPzDirgVjfn6+UvzO38I+qGqQLVAXnB/pefKnT/if5T82LLKadXWVd8LRYXXiuyLSoq+rRaurrbGYU3pmsG1KWtb1rmu27GeuF66vm2Dz4b9xRrFecWdGydvrNvE3lS46cPmWZuvljiX7NxC3aLY0l4aVtqw1XTr+q3fytLK7pX7ldds09u2atun7aLtt3f47qjeqb+zaOfXnyQ/PdgVtKuuwryiZDdxd+7uF3ti9zT/zPm5cq/u3qK9f+6T7mvfH7n/YqVbZeUBvQPrqtAqRVXvwekHbx3yP9RQbVu9q4ZVU3QYDisOvzySdKTtaOjRpmOcY9XHzY5vq2XUFtYhdfPr+urT6tsbEhpaT4ScaGr0bKw9aXdy3ymjU+WntU6vO0M9k39m8Gze2f5zsnOvz6ee72ya1fT4QvyFuxenXmy5FHrpyuXAyxeauc1nr3hdOXXV4+qJa5xr9dddr9fdcLlR+4vLL7Utri11N91uNtzyv9XYOqn1zG2f2+fv+N+5fJd/9/q9Kfda22LaHtyffr/9gehBz8PMh28f5T4aeLz0CeFJ4VP1pyXP9J5V/Gr1a027a/vpDv+OG8+jnj/uFHa++i37t29d+S/oL0q6Dbsrexx7TvUG9t56Oe1l1yvZq4HXBb9r/L7tjeWb43/4/nGjL76v66387eC71e913u/74PyhqT+i/9nHrI8Dnwo/63ze/4Xzpflr3NfugbnfSN9K/7T6s/F76Pcng1mDgzKBXDA8CuAwRVNSAN7tA6AnADCwGYI6bWSmHhZk5D9gmOA/8cjcPSyuANWYGRqNeOcADmNqvhRAzRdgaCyK9gXUyUmpo/Pv8Kw+JAbYv8K0HECi2x6tebQU/iEjc/xf+v6nBWXWv9l/AV0EC6JTIblRAAAAeGVYSWZNTQAqAAAACAAFARIAAwAAAAEAAQAAARoABQAAAAEAAABKARsABQAAAAEAAABSASgAAwAAAAEAAgAAh2kABAAAAAEAAABaAAAAAAAAAJAAAAABAAAAkAAAAAEAAqACAAQAAAABAAAAFKADAAQAAAABAAAAFAAAAAAXNii1AAAACXBIWXMAABYlAAAWJQFJUiTwAAAB82lUWHRYTUw6Y29tLmFkb2JlLnhtcAAAAAAAPHg6eG1wbWV0YSB4bWxuczp4PSJhZG9iZTpuczptZXRhLyIgeDp4bXB0az0iWE1QIENvcmUgNi4wLjAiPgogICA8cmRmOlJERiB4bWxuczpypZGY9Imh0dHA6Ly93d3cudzMub3JnLzE5OTkvMDIvMjItcmRmLXN5bnRheC1ucyMiPgogICAgICA8cmRmOkRlc2NyaXB0aW9uIHJkZjphYm91dD0iIgogICAgICAgICAgICB4bWxuczp0aWZmPSJodHRwOi8vbnMuYWRvYmUuY29tL3RpZmYvMS4wLyI+CiAgICAgICAgIDx0aWZmOllSZXNvbHV0aW9uPjE0NDwvdGlmZjpZUmVzb2x1dGlvbj4KICAgICAgICAgPHRpZmY6T3JpZW50YXRpb24+MTwvdGlmZjpPcmllbnRhdGlvbj4KICAgICAgICAgPHRpZmY6WFJlc29sdXRpb24+MTQ0PC90aWZmOlhSZXNvbHV0aW9uPgogICAgICAgICA8dGlmZjpSZXNvbHV0aW9uVW5pdD4yPC90aWZmOlJlc29sdXRpb25Vbml0PgogICAgICA8L3JkZjpEZXNjcmlwdGlvbj4KICAgPC9yZGY6UkRGPgo8L3g6eG1wbWV0YT4KReh49gAAAjRJREFUOBGFlD2vMUEUx2clvoNCcW8hCqFAo1dKhEQpvsF9KrWEBh/ALbQ0KkInBI3SWyGPCCJEQliXgsTLefaca/bBWjvJzs6c+f/fnDkzOQJIjWm06/XKBEGgD8c6nU5VIWgBtQDPZPWtJE8O63a7LBgMMo/Hw0ql0jPjcY4RvmqXy4XMjUYDUwLtdhtmsxnYbDbI5/O0djqdFFKmsEiGZ9jP9gem0yn0ej2Yz+fg9XpfycimAD7DttstQTDKfr8Po9GIIg6Hw1Cr1RTgB+A72GAwgMPhQLBMJgNSXsFqtUI2myUo18pA6QJogefsPrLBX4QdCVatViklw+EQRFGEj88P2O12pEUGATmsXq9TaLPZ0AXgMRF2vMEqlQoJTSYTpNNpApvNZliv1/+BHDaZTAi2Wq1A3Ig0xmMej7+RcZjdbodUKkWAaDQK+GHjHPnImB88JrZIJAKFQgH2+z2BOczhcMiwRCIBgUAA+NN5BP6mj2DYff35gk6nA61WCzBn2JxO5wPM7/fLz4vD0E+OECfn8xl/0Gw2KbLxeAyLxQIsFgt8p75pDSO7h/HbpUWpewCike9WLpfB7XaDy+WCYrFI/slk8i0MnRRAUt46hPMI4vE4+Hw+ec7t9/44VgWigEeby+UgFArJWjUYOqhWG6x50rpcSfR6PVUfNOgEVRlTX0HhrZBKz4MZjUYWi8VoA+lc9H/VaRZYjBKrtXR8tlwumcFgeMWRbZpA9ORQWfVm8A/FsrLaxebd5wAAAABJRU5ErkJggg==";
+
+struct EverythingToolHandler;
+
+/// Sleep through `steps` steps, reporting progress to the client after each
+async fn long_running_operation(call: ToolCall, ctx: &Context) -> MCPResult<ToolResult> {
+    let args = call.arguments.unwrap_or_default();
+    let duration = args
+        .get("duration")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(10.0);
+    let steps = args.get("steps").and_then(|v| v.as_u64()).unwrap_or(5);
+    let step_duration = duration / steps as f64;
+
+    for i in 1..=steps {
+        tokio::time::sleep(tokio::time::Duration::from_secs_f64(step_duration)).await;
+        ctx.report_progress(
+            i as f64,
+            Some(steps as f64),
+            Some(&format!("Step {i}/{steps} completed")),
+        )
+        .await?;
+    }
+
+    Ok(ToolResult {
+        content: vec![ToolContent::text(format!(
+            "Long running operation completed. Duration: {duration} seconds, Steps: {steps}. Progress was tracked through {steps} steps."
+        ))],
+        is_error: Some(false),
+        structured_content: None,
+        progress_summary: None,
+    })
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for EverythingToolHandler {
+    async fn handle_tool_call_with_context(
+        &self,
+        call: ToolCall,
+        context: Context,
+    ) -> MCPResult<ToolResult> {
+        match call.name.as_str() {
+            "longRunningOperation" => long_running_operation(call, &context).await,
+            _ => self.handle_tool_call(call).await,
+        }
+    }
+
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        match call.name.as_str() {
+            "echo" => {
+                let message = call
+                    .arguments
+                    .and_then(|args| args.get("message").cloned())
+                    .and_then(|v| v.as_str().map(|s| s.to_string()))
+                    .unwrap_or_else(|| "Hello, World!".to_string());
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(format!("Echo: {message}"))],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "add" => {
+                let args = call.arguments.unwrap_or_default();
+                let a = args.get("a").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                let b = args.get("b").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                let sum = a + b;
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(format!(
+                        "The sum of {a} and {b} is {sum}."
+                    ))],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "longRunningOperation" => long_running_operation(call, &Context::new()).await,
+            "printEnv" => {
+                let env_vars: HashMap<String, String> = std::env::vars().collect();
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(
+                        serde_json::to_string_pretty(&env_vars).unwrap(),
+                    )],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "sampleLLM" => {
+                let args = call.arguments.unwrap_or_default();
+                let prompt = args
+                    .get("prompt")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Hello");
+                let max_tokens = args
+                    .get("maxTokens")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(100);
+
+                // Simulate LLM sampling
+                let response = format!(
+                    "LLM sampling result for '{prompt}' (max tokens: {max_tokens}): This is a simulated response."
+                );
+
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(response)],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "getTinyImage" => Ok(ToolResult {
+                content: vec![
+                    ToolContent::text("This is a tiny image:".to_string()),
+                    ToolContent::image(MCP_TINY_IMAGE.to_string(), "image/png".to_string()),
+                    ToolContent::text("The image above is the MCP tiny image.".to_string()),
+                ],
+                is_error: Some(false),
+                structured_content: None,
+                progress_summary: None,
+            }),
+            "annotatedMessage" => {
+                let args = call.arguments.unwrap_or_default();
+                let message_type = args
+                    .get("messageType")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("success");
+                let include_image = args
+                    .get("includeImage")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                let mut content = vec![];
+
+                match message_type {
+                    "error" => {
+                        content.push(ToolContent::text("Error: Operation failed".to_string()));
+                    }
+                    "success" => {
+                        content.push(ToolContent::text(
+                            "Operation completed successfully".to_string(),
+                        ));
+                    }
+                    "debug" => {
+                        content.push(ToolContent::text(
+                            "Debug: Cache hit ratio 0.95, latency 150ms".to_string(),
+                        ));
+                    }
+                    _ => {
+                        content.push(ToolContent::text("Unknown message type".to_string()));
+                    }
+                }
+
+                if include_image {
+                    content.push(ToolContent::image(
+                        MCP_TINY_IMAGE.to_string(),
+                        "image/png".to_string(),
+                    ));
+                }
+
+                Ok(ToolResult {
+                    content,
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "getResourceReference" => {
+                let args = call.arguments.unwrap_or_default();
+                let resource_id = args.get("resourceId").and_then(|v| v.as_u64()).unwrap_or(1);
+
+                let resource_uri = format!("test://static/resource/{resource_id}");
+
+                Ok(ToolResult {
+                    content: vec![
+                        ToolContent::text(format!(
+                            "Returning resource reference for Resource {resource_id}:"
+                        )),
+                        ToolContent::resource(resource_uri),
+                        ToolContent::text(format!(
+                            "You can access this resource using the URI: test://static/resource/{resource_id}"
+                        )),
+                    ],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "cancellableOperation" => {
+                let args = call.arguments.unwrap_or_default();
+                let duration = args
+                    .get("duration")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(30.0);
+                let check_interval = args
+                    .get("checkInterval")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(2.0);
+
+                let mut elapsed = 0.0;
+                let mut check_count = 0;
+
+                while elapsed < duration {
+                    tokio::time::sleep(tokio::time::Duration::from_secs_f64(check_interval)).await;
+                    elapsed += check_interval;
+                    check_count += 1;
+
+                    // In a real implementation, you would check for cancellation requests here
+                    // For now, we'll just simulate periodic checking
+                    println!(
+                        "Cancellable operation check #{check_count}: {elapsed:.1}/{duration:.1} seconds"
+                    );
+                }
+
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(format!(
+                        "Cancellable operation completed after {elapsed:.1} seconds with {check_count} checks. This operation could be cancelled by sending a cancellation notification."
+                    ))],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "notificationDemo" => {
+                let args = call.arguments.unwrap_or_default();
+                let notification_type = args.get("type").and_then(|v| v.as_str()).unwrap_or("info");
+
+                let message = match notification_type {
+                    "resource_list_changed" => {
+                        "This would trigger a resource list changed notification"
+                    }
+                    "resource_updated" => "This would trigger a resource updated notification",
+                    "tool_list_changed" => "This would trigger a tool list changed notification",
+                    "prompt_list_changed" => {
+                        "This would trigger a prompt list changed notification"
+                    }
+                    "log_message" => "This would trigger a log message notification",
+                    _ => "This would trigger a general notification",
+                };
+
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(format!(
+                        "Notification demo: {message}. In a real implementation, this would send a '{notification_type}' notification to connected clients."
+                    ))],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "getResourceLinks" => {
+                let args = call.arguments.unwrap_or_default();
+                let count = args.get("count").and_then(|v| v.as_u64()).unwrap_or(3);
+
+                let mut content = vec![ToolContent::text(format!(
+                    "Here are {count} resource links to resources available in this server:"
+                ))];
+
+                for i in 1..=count.min(100) {
+                    content.push(ToolContent::resource_with_description(
+                        format!("test://static/resource/{i}"),
+                        format!("Resource {i}: test resource"),
+                    ));
+                }
+
+                Ok(ToolResult {
+                    content,
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            "startElicitation" => {
+                let args = call.arguments.unwrap_or_default();
+                let color = args.get("color").and_then(|v| v.as_str()).unwrap_or("blue");
+                let number = args.get("number").and_then(|v| v.as_u64()).unwrap_or(42);
+                let pets = args.get("pets").and_then(|v| v.as_str()).unwrap_or("dogs");
+
+                Ok(ToolResult {
+                    content: vec![ToolContent::text(format!(
+                        "Elicitation demo completed! Your selections:\n- Favorite color: {color}\n- Favorite number: {number}\n- Favorite pets: {pets}"
+                    ))],
+                    is_error: Some(false),
+                    structured_content: None,
+                    progress_summary: None,
+                })
+            }
+            _ => Ok(ToolResult {
+                content: vec![ToolContent::text(format!("Unknown tool: {}", call.name))],
+                is_error: Some(true),
+                structured_content: None,
+                progress_summary: None,
+            }),
+        }
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        println!("DEBUG: Entered list_tools handler");
+        let response = ListToolsResponse {
+            tools: vec![
+                Tool::new(
+                    "echo".to_string(),
+                    "Echoes back the input".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "message": {
+                                "type": "string",
+                                "description": "Message to echo"
+                            }
+                        },
+                        "required": ["message"]
+                    })
+                ),
+                Tool::new(
+                    "add".to_string(),
+                    "Adds two numbers".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "a": {
+                                "type": "number",
+                                "description": "First number"
+                            },
+                            "b": {
+                                "type": "number",
+                                "description": "Second number"
+                            }
+                        },
+                        "required": ["a", "b"]
+                    })
+                ),
+                Tool::new(
+                    "longRunningOperation".to_string(),
+                    "Demonstrates a long running operation with progress updates".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "duration": {
+                                "type": "number",
+                                "default": 10,
+                                "description": "Duration of the operation in seconds"
+                            },
+                            "steps": {
+                                "type": "number",
+                                "default": 5,
+                                "description": "Number of steps in the operation"
+                            }
+                        }
+                    })
+                ),
+                Tool::new(
+                    "printEnv".to_string(),
+                    "Prints all environment variables, helpful for debugging MCP server configuration".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {}
+                    })
+                ),
+                Tool::new(
+                    "sampleLLM".to_string(),
+                    "Samples from an LLM using MCP's sampling feature".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "prompt": {
+                                "type": "string",
+                                "description": "The prompt to send to the LLM"
+                            },
+                            "maxTokens": {
+                                "type": "number",
+                                "default": 100,
+                                "description": "Maximum number of tokens to generate"
+                            }
+                        },
+                        "required": ["prompt"]
+                    })
+                ),
+                Tool::new(
+                    "getTinyImage".to_string(),
+                    "Returns the MCP_TINY_IMAGE".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {}
+                    })
+                ),
+                Tool::new(
+                    "annotatedMessage".to_string(),
+                    "Demonstrates how annotations can be used to provide metadata about content".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "messageType": {
+                                "type": "string",
+                                "enum": ["error", "success", "debug"],
+                                "description": "Type of message to demonstrate different annotation patterns"
+                            },
+                            "includeImage": {
+                                "type": "boolean",
+                                "default": false,
+                                "description": "Whether to include an example image"
+                            }
+                        },
+                        "required": ["messageType"]
+                    })
+                ),
+                Tool::new(
+                    "getResourceReference".to_string(),
+                    "Returns a resource reference that can be used by MCP clients".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "resourceId": {
+                                "type": "number",
+                                "minimum": 1,
+                                "maximum": 100,
+                                "description": "ID of the resource to reference (1-100)"
+                            }
+                        },
+                        "required": ["resourceId"]
+                    })
+                ),
+                Tool::new(
+                    "getResourceLinks".to_string(),
+                    "Returns multiple resource links that reference different types of resources".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "count": {
+                                "type": "number",
+                                "minimum": 1,
+                                "maximum": 10,
+                                "default": 3,
+                                "description": "Number of resource links to return (1-10)"
+                            }
+                        }
+                    })
+                ),
+                Tool::new(
+                    "startElicitation".to_string(),
+                    "Initiates an elicitation (interaction) within the MCP client".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "color": {
+                                "type": "string",
+                                "description": "Favorite color"
+                            },
+                            "number": {
+                                "type": "number",
+                                "minimum": 1,
+                                "maximum": 100,
+                                "description": "Favorite number (1-100)"
+                            },
+                            "pets": {
+                                "type": "string",
+                                "enum": ["dogs", "cats", "birds", "fish", "other"],
+                                "description": "Favorite pet"
+                            }
+                        }
+                    })
+                ),
+                Tool::new(
+                    "cancellableOperation".to_string(),
+                    "Demonstrates a cancellable long-running operation that can be interrupted".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "duration": {
+                                "type": "number",
+                                "default": 30,
+                                "description": "Duration of the operation in seconds"
+                            },
+                            "checkInterval": {
+                                "type": "number",
+                                "default": 2,
+                                "description": "Interval between cancellation checks in seconds"
+                            }
+                        }
+                    })
+                ),
+                Tool::new(
+                    "notificationDemo".to_string(),
+                    "Demonstrates various MCP notification types that can be sent to clients".to_string(),
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "type": {
+                                "type": "string",
+                                "enum": ["resource_list_changed", "resource_updated", "tool_list_changed", "prompt_list_changed", "log_message"],
+                                "default": "info",
+                                "description": "Type of notification to demonstrate"
+                            }
+                        }
+                    })
+                ),
+            ],
+            next_cursor: None,
+        };
+        println!("DEBUG: Returning from list_tools handler");
+        Ok(response)
+    }
+}
+
+struct EverythingResourceHandler;
+#[async_trait::async_trait]
+impl ResourceHandler for EverythingResourceHandler {
+    async fn read_resource(&self, request: ReadResourceRequest) -> MCPResult<ReadResourceResponse> {
+        let uri = request.uri;
+
+        if uri.starts_with("test://static/resource/") {
+            let id = uri.split("/").last().unwrap_or("1");
+            let resource_id = id.parse::<u64>().unwrap_or(1);
+
+            let resource = if resource_id % 2 == 0 {
+                ResourceContent::text(
+                    uri.clone(),
+                    format!("Resource {resource_id}: This is a plaintext resource"),
+                )
+            } else {
+                let data = format!("Resource {resource_id}: This is a base64 blob");
+                ResourceContent::blob(
+                    uri.clone(),
+                    BASE64.encode(data.as_bytes()),
+                    "application/octet-stream".to_string(),
+                )
+            };
+
+            Ok(ReadResourceResponse {
+                contents: vec![resource],
+            })
+        } else {
+            Err(anyhow::anyhow!("Unknown resource: {}", uri).into())
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        request: ListResourcesRequest,
+    ) -> MCPResult<ListResourcesResponse> {
+        let cursor = request.cursor;
+        let page_size = 10;
+        let mut start_index = 0;
+
+        if let Some(cursor_str) = cursor {
+            if let Ok(decoded) = BASE64.decode(cursor_str) {
+                if let Ok(decoded_str) = String::from_utf8(decoded) {
+                    if let Ok(index) = decoded_str.parse::<usize>() {
+                        start_index = index;
+                    }
+                }
+            }
+        }
+
+        let mut resources = vec![];
+        for i in start_index..(start_index + page_size).min(100) {
+            let resource_id = i + 1;
+            let uri = format!("test://static/resource/{resource_id}");
+            let name = format!("Resource {resource_id}");
+
+            let resource = Resource::new(uri, name);
+            resources.push(resource);
+        }
+
+        let next_cursor = if start_index + page_size < 100 {
+            Some(BASE64.encode((start_index + page_size).to_string()))
+        } else {
+            None
+        };
+
+        Ok(ListResourcesResponse {
+            resources,
+            next_cursor,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: ListResourceTemplatesRequest,
+    ) -> MCPResult<ListResourceTemplatesResponse> {
+        Ok(ListResourceTemplatesResponse {
+            resource_templates: vec![resources::ResourceTemplate::new(
+                "test://static/resource/{id}".to_string(),
+                "Static Resource".to_string(),
+            )],
+            next_cursor: None,
+        })
+    }
+
+    async fn validate_resource_access(
+        &self,
+        uri: &str,
+        operation: roots::RootOperation,
+        roots: &[roots::Root],
+    ) -> MCPResult<()> {
+        // If no roots are configured, allow all access (informational nature of roots)
+        if roots.is_empty() {
+            return Ok(());
+        }
+
+        // Find matching root for the URI
+        for root in roots {
+            if uri.starts_with(&root.uri) {
+                // Only use the security validator for file:// roots and file:// URIs
+                // This prevents validation errors for non-file URIs like test://, http://, etc.
+                if root.uri.starts_with("file://") && uri.starts_with("file://") {
+                    let validator = RootSecurityValidator::default();
+                    return validator
+                        .validate_access(root, uri, operation)
+                        .map_err(|e| {
+                            MCPError::Resource(ResourceError::AccessDenied(format!(
+                                "Root validation failed: {e}"
+                            )))
+                        });
+                } else {
+                    // For non-file roots or non-file URIs, allow access (roots are informational)
+                    // This aligns with MCP specification that roots provide guidance, not enforcement
+                    return Ok(());
+                }
+            }
+        }
+
+        // If no matching root is found, allow access (roots are informational)
+        // This aligns with MCP specification that roots provide guidance, not enforcement
+        Ok(())
+    }
+}
+
+struct EverythingPromptHandler;
+#[async_trait::async_trait]
+impl PromptHandler for EverythingPromptHandler {
+    async fn get_prompt(
+        &self,
+        request: prompts::GetPromptRequest,
+    ) -> MCPResult<prompts::GetPromptResponse> {
+        let name = request.name;
+        let args = request.arguments;
+
+        match name.as_str() {
+            "simple_prompt" => Ok(prompts::GetPromptResponse {
+                description: Some("A prompt without arguments".to_string()),
+                messages: vec![prompts::PromptMessage::user(prompts::PromptContent::text(
+                    "This is a simple prompt without arguments.".to_string(),
+                ))],
+            }),
+            "complex_prompt" => {
+                let temperature = args
+                    .as_ref()
+                    .and_then(|a| a.get("temperature").and_then(|v| v.as_str()))
+                    .unwrap_or("0.7");
+                let style = args
+                    .as_ref()
+                    .and_then(|a| a.get("style").and_then(|v| v.as_str()))
+                    .unwrap_or("default");
+
+                Ok(prompts::GetPromptResponse {
+                    description: Some("A prompt with arguments".to_string()),
+                    messages: vec![
+                        prompts::PromptMessage::user(prompts::PromptContent::text(format!("This is a complex prompt with arguments: temperature={temperature}, style={style}"))),
+                        prompts::PromptMessage::assistant(prompts::PromptContent::text("I understand. You've provided a complex prompt with temperature and style arguments. How would you like me to proceed?".to_string())),
+                        prompts::PromptMessage::user(prompts::PromptContent::image(MCP_TINY_IMAGE.to_string(), "image/png".to_string())),
+                    ],
+                })
+            }
+            "resource_prompt" => {
+                let resource_id = args
+                    .as_ref()
+                    .and_then(|a| a.get("resourceId").and_then(|v| v.as_str()))
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or(1);
+
+                // Allow 0 as a valid resource ID, or use 1 as default if invalid
+                let valid_resource_id = if resource_id == 0 {
+                    1 // Use 1 as default for 0
+                } else if !(1..=100).contains(&resource_id) {
+                    return Err(anyhow::anyhow!(
+                        "Invalid resourceId: {}. Must be a number between 1 and 100.",
+                        resource_id
+                    )
+                    .into());
+                } else {
+                    resource_id
+                };
+
+                let resource_uri = format!("test://static/resource/{valid_resource_id}");
+
+                Ok(prompts::GetPromptResponse {
+                    description: Some(
+                        "A prompt that includes an embedded resource reference".to_string(),
+                    ),
+                    messages: vec![
+                        prompts::PromptMessage::user(prompts::PromptContent::text(format!(
+                            "This prompt includes Resource {valid_resource_id}. Please analyze the following resource:"
+                        ))),
+                        prompts::PromptMessage::user(prompts::PromptContent::resource_link(
+                            format!("Resource {valid_resource_id}"),
+                            resource_uri,
+                        )),
+                    ],
+                })
+            }
+            _ => Err(anyhow::anyhow!("Unknown prompt: {}", name).into()),
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: prompts::ListPromptsRequest,
+    ) -> MCPResult<prompts::ListPromptsResponse> {
+        Ok(prompts::ListPromptsResponse {
+            prompts: vec![
+                prompts::Prompt::new("simple_prompt".to_string())
+                    .with_description("A prompt without arguments".to_string()),
+                prompts::Prompt::new("complex_prompt".to_string())
+                    .with_description("A prompt with arguments".to_string())
+                    .with_arguments(vec![
+                        prompts::PromptArgument::new("temperature".to_string()),
+                        prompts::PromptArgument::new("style".to_string()),
+                    ]),
+                prompts::Prompt::new("resource_prompt".to_string())
+                    .with_description(
+                        "A prompt that includes an embedded resource reference".to_string(),
+                    )
+                    .with_arguments(vec![prompts::PromptArgument::new("resourceId".to_string())]),
+            ],
+            next_cursor: None,
+        })
+    }
+}
+
+struct EverythingSamplingHandler;
+#[async_trait::async_trait]
+impl SamplingHandler for EverythingSamplingHandler {
+    async fn create_message(
+        &self,
+        request: sampling::CreateMessageRequest,
+    ) -> MCPResult<sampling::CreateMessageResponse> {
+        let messages = request.messages;
+        let system_prompt = request
+            .system_prompt
+            .unwrap_or_else(|| "You are a helpful test server.".to_string());
+        let max_tokens = request.max_tokens.unwrap_or(100);
+        let temperature = request.temperature.unwrap_or(0.7);
+
+        // Simulate LLM response
+        let response_text = format!(
+            "Simulated LLM response based on {} messages, system prompt: '{}', max tokens: {}, temperature: {}",
+            messages.len(),
+            system_prompt,
+            max_tokens,
+            temperature
+        );
+
+        Ok(sampling::CreateMessageResponse {
+            role: sampling::SamplingRole::Assistant,
+            content: sampling::SamplingContent::text(response_text),
+            model: Some("simulated-model".to_string()),
+            stop_reason: Some(sampling::StopReason::MaxTokens),
+            approval_status: None,
+            request_id: None,
+            processing_time_ms: None,
+            cost_info: None,
+            included_context: None,
+            human_feedback: None,
+            warnings: None,
+        })
+    }
+}
+
+/// Completions for the arguments of the `code_review` and `greeting` prompts
+fn completion_router() -> CompletionRouter {
+    CompletionRouter::new()
+        .prompt_arg("code_review", "language", |_| {
+            [
+                "python",
+                "pytorch",
+                "pyside",
+                "rust",
+                "javascript",
+                "typescript",
+            ]
+        })
+        // Framework suggestions depend on the language already chosen
+        .prompt_arg_with_context("code_review", "framework", |_, arguments| {
+            match arguments.get("language").map(String::as_str) {
+                Some("python") => vec!["flask", "django", "fastapi", "pytorch", "tensorflow"],
+                Some("javascript") => vec!["react", "vue", "angular", "express", "next"],
+                Some("rust") => vec!["actix", "rocket", "axum", "tokio", "serde"],
+                _ => vec!["flask", "django", "react", "vue", "actix"],
+            }
+        })
+        .prompt_arg("greeting", "style", |_| {
+            ["casual", "formal", "technical", "friendly"]
+        })
+        .prompt_arg("greeting", "temperature", |_| ["0", "0.5", "0.7", "1.0"])
+}
+
+/// Completes the `{id}` variable of `test://static/resource/{id}`
+struct ResourceIdCompleter;
+
+#[async_trait::async_trait]
+impl ResourceTemplateCompleter for ResourceIdCompleter {
+    async fn complete(
+        &self,
+        _variable: &str,
+        prefix: &str,
+        _resolved: &HashMap<String, String>,
+    ) -> MCPResult<Vec<completion::CompletionValue>> {
+        Ok((1..=100)
+            .map(|id| id.to_string())
+            .filter(|id| id.starts_with(prefix))
+            .map(completion::CompletionValue::new)
+            .collect())
+    }
+}
+
+struct EverythingRootsHandler {
+    roots: Arc<Mutex<Vec<roots::Root>>>,
+}
+
+#[async_trait::async_trait]
+impl RootsHandler for EverythingRootsHandler {
+    async fn list_roots(&self) -> MCPResult<Vec<roots::Root>> {
+        Ok(self
+            .roots
+            .lock()
+            .expect("Failed to acquire roots lock")
+            .clone())
+    }
+    async fn set_roots(&self, roots: Vec<roots::Root>) -> MCPResult<()> {
+        *self.roots.lock().expect("Failed to acquire roots lock") = roots;
+        Ok(())
+    }
+}
+
+struct EverythingElicitationHandler;
+#[async_trait::async_trait]
+impl ElicitationHandler for EverythingElicitationHandler {
+    async fn handle_elicitation(
+        &self,
+        request: elicitation::ElicitationRequest,
+    ) -> MCPResult<elicitation::ElicitationResponse> {
+        // Log the elicitation request
+        println!("Elicitation request: {}", request.message);
+        println!("Requested schema: {:?}", request.requested_schema);
+
+        // In a real implementation, this would present the request to the user
+        // For demonstration, we'll simulate different responses based on the message content
+
+        if request.message.contains("username") {
+            // Simulate user providing a username
+            Ok(elicitation::ElicitationResponse {
+                action: elicitation::ElicitationAction::Accept,
+                content: Some(serde_json::json!({
+                    "username": "demo_user"
+                })),
+            })
+        } else if request.message.contains("confirm") {
+            // Simulate user declining a confirmation
+            Ok(elicitation::ElicitationResponse {
+                action: elicitation::ElicitationAction::Decline,
+                content: None,
+            })
+        } else {
+            // Simulate user cancelling for other requests
+            Ok(elicitation::ElicitationResponse {
+                action: elicitation::ElicitationAction::Cancel,
+                content: None,
+            })
+        }
+    }
+}
+
+struct EverythingSubscriptionHandler;
+#[async_trait::async_trait]
+impl ResourceSubscriptionHandler for EverythingSubscriptionHandler {
+    async fn subscribe(&self, uri: String) -> MCPResult<()> {
+        println!("Subscribed to resource: {}", uri);
+        Ok(())
+    }
+
+    async fn unsubscribe(&self, uri: String) -> MCPResult<()> {
+        println!("Unsubscribed from resource: {}", uri);
+        Ok(())
+    }
+
+    async fn notify_change(&self, uri: String, content: Value) -> MCPResult<()> {
+        println!("Resource changed: {} -> {:?}", uri, content);
+        Ok(())
+    }
+}
+
+/// The everything server, with every handler installed
+pub fn server() -> UltraFastServer {
+    let server_info = ServerInfo {
+        name: "example-servers/everything".to_string(),
+        version: "1.0.0".to_string(),
+        description: Some(
+            "Everything MCP Server - Comprehensive example implementing all MCP features. This server attempts to exercise all the features of the MCP protocol and is intended as a test server for builders of MCP clients."
+                .to_string(),
+        ),
+        authors: Some(vec!["ULTRAFAST_MCP Team".to_string()]),
+        homepage: Some("https://github.com/ultrafast-mcp/ultrafast-mcp".to_string()),
+        license: Some("MIT OR Apache-2.0".to_string()),
+        repository: Some("https://github.com/ultrafast-mcp/ultrafast-mcp".to_string()),
+    };
+
+    let capabilities = ServerCapabilities {
+        tools: Some(ultrafast_mcp::ToolsCapability {
+            list_changed: Some(true),
+        }),
+        resources: Some(ultrafast_mcp::ResourcesCapability {
+            subscribe: Some(true),
+            list_changed: Some(true),
+        }),
+        prompts: Some(ultrafast_mcp::PromptsCapability {
+            list_changed: Some(true),
+        }),
+        logging: Some(ultrafast_mcp::LoggingCapability {}),
+        completion: Some(ultrafast_mcp::CompletionCapability {}),
+    };
+
+    let roots = Arc::new(Mutex::new(vec![roots::Root {
+        uri: "test://static/".to_string(),
+        name: Some("Static Resources".to_string()),
+        security: None,
+    }]));
+
+    UltraFastServer::new(server_info, capabilities)
+        .with_tool_handler(Arc::new(EverythingToolHandler))
+        .with_resource_handler(Arc::new(EverythingResourceHandler))
+        .with_prompt_handler(Arc::new(EverythingPromptHandler))
+        .with_sampling_handler(Arc::new(EverythingSamplingHandler))
+        .with_completion_handler(Arc::new(completion_router()))
+        .with_resource_template_completer(
+            "test://static/resource/{id}",
+            Arc::new(ResourceIdCompleter),
+        )
+        .with_roots_handler(Arc::new(EverythingRootsHandler {
+            roots: roots.clone(),
+        }))
+        .with_elicitation_handler(Arc::new(EverythingElicitationHandler))
+        .with_subscription_handler(Arc::new(EverythingSubscriptionHandler))
+}
+
+/// Run the everything server and a client against each other in memory
+///
+/// Touches each kind of handler once: tools, including one reporting
+/// progress, resources, prompts and completions.
+pub async fn self_test() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = duplex_pair();
+    let server = server();
+    let server =
+        tokio::spawn(async move { server.run_with_transport(Box::new(server_transport)).await });
+
+    let client = UltraFastClient::new(
+        ClientInfo {
+            name: "everything-self-test".to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            authors: None,
+            homepage: None,
+            license: None,
+            repository: None,
+        },
+        ClientCapabilities::default(),
+    );
+    client.connect(Box::new(client_transport)).await?;
+
+    let call = |name: &str, arguments: Value| {
+        client.call_tool(ToolCall {
+            name: name.to_string(),
+            arguments: Some(arguments),
+        })
+    };
+    let text = |result: &ToolResult| match result.content.first() {
+        Some(ToolContent::Text { text }) => text.clone(),
+        other => format!("{other:?}"),
+    };
+
+    let tools = client.list_tools_default().await?.tools;
+    for name in ["echo", "add", "longRunningOperation", "getTinyImage"] {
+        anyhow::ensure!(
+            tools.iter().any(|tool| tool.name == name),
+            "{name} is not listed"
+        );
+    }
+
+    let echoed = call("echo", serde_json::json!({ "message": "duplex" })).await?;
+    anyhow::ensure!(text(&echoed) == "Echo: duplex", "echoed {}", text(&echoed));
+    let sum = call("add", serde_json::json!({ "a": 2, "b": 3 })).await?;
+    anyhow::ensure!(
+        text(&sum) == "The sum of 2 and 3 is 5.",
+        "added {}",
+        text(&sum)
+    );
+    let image = call("getTinyImage", serde_json::json!({})).await?;
+    anyhow::ensure!(
+        matches!(image.content.get(1), Some(ToolContent::Image { .. })),
+        "no image in {:?}",
+        image.content
+    );
+    call(
+        "longRunningOperation",
+        serde_json::json!({ "duration": 0.1, "steps": 2 }),
+    )
+    .await?;
+
+    let resource = client
+        .read_resource(ReadResourceRequest::new("test://static/resource/2"))
+        .await?;
+    anyhow::ensure!(
+        matches!(
+            resource.contents.first(),
+            Some(ResourceContent::Text { text, .. }) if text.starts_with("Resource 2")
+        ),
+        "read {:?}",
+        resource.contents
+    );
+
+    let prompt = client
+        .get_prompt(prompts::GetPromptRequest {
+            name: "simple_prompt".to_string(),
+            arguments: None,
+        })
+        .await?;
+    anyhow::ensure!(prompt.messages.len() == 1, "prompt {:?}", prompt.messages);
+
+    let completion = client
+        .complete(CompleteRequest::with_argument(
+            "ref/prompt",
+            "code_review",
+            "language",
+            "ru",
+        ))
+        .await?
+        .completion;
+    anyhow::ensure!(
+        completion.values.first().map(|value| value.value.as_str()) == Some("rust"),
+        "completed {:?}",
+        completion.values
+    );
+
+    // The server stops once the client hangs up
+    client.disconnect().await?;
+    server.await??;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! Agent orchestrator demo
+//!
+//! Starts three demo upstreams (weather with a backup instance, calculator
+//! and notes), runs a small agent plan through the orchestrator, takes the
+//! primary weather server down to show failover, and prints the collected
+//! metrics. With `--serve` the orchestrator is then kept running as a single
+//! aggregated MCP server.
+//!
+//! Usage:
+//!   cargo run --bin agent-orchestrator
+//!   cargo run --bin agent-orchestrator -- --serve --port 8090
+//!   cargo run --bin agent-orchestrator -- --self-test
+
+use std::sync::Arc;
+
+use agent_orchestrator_example::{
+    DemoServer, DemoService, FailoverPolicy, Orchestrator, UpstreamConfig, namespaced, self_test,
+};
+use clap::Parser;
+use serde_json::json;
+use tracing::info;
+use ultrafast_mcp::{ServerCapabilities, ServerInfo, ToolResult, ToolsCapability, UltraFastServer};
+
+#[derive(Parser)]
+#[command(name = "agent-orchestrator")]
+#[command(about = "One MCP client orchestrating several MCP servers with failover")]
+struct Args {
+    /// Keep serving the orchestrator as an aggregated MCP server afterwards
+    #[arg(long)]
+    serve: bool,
+
+    /// Check each demo upstream in-process and exit
+    #[arg(long)]
+    self_test: bool,
+
+    /// Host for the aggregated server
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Port for the aggregated server
+    #[arg(long, default_value = "8090")]
+    port: u16,
+}
+
+fn summary(result: &ToolResult) -> String {
+    result
+        .structured_content
+        .as_ref()
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_env_filter("warn,agent_orchestrator_example=info")
+        .init();
+
+    if args.self_test {
+        self_test().await?;
+        println!("Self-test passed");
+        return Ok(());
+    }
+
+    let mut weather = DemoServer::start(DemoService::Weather, "weather-primary")?;
+    let weather_backup = DemoServer::start(DemoService::Weather, "weather-backup")?;
+    let calculator = DemoServer::start(DemoService::Calculator, "calculator")?;
+    let notes = DemoServer::start(DemoService::Notes, "notes")?;
+
+    let orchestrator = Arc::new(
+        Orchestrator::new(vec![
+            UpstreamConfig::new("weather", weather.endpoint())
+                .with_backup(weather_backup.endpoint()),
+            UpstreamConfig::new("calculator", calculator.endpoint()),
+            UpstreamConfig::new("notes", notes.endpoint()).with_bearer_token("demo-notes-token"),
+        ])
+        .with_policy(FailoverPolicy::default()),
+    );
+    orchestrator.connect().await?;
+
+    println!("🔧 Tools available to the agent:");
+    for tool in orchestrator.list_tools().await? {
+        let hints = tool
+            .annotations
+            .as_ref()
+            .map(|annotations| annotations.hint_labels().join(", "))
+            .unwrap_or_default();
+        println!("   {} [{}]", tool.name, hints);
+    }
+
+    println!("\n🤖 Running the agent plan:");
+    let forecast = orchestrator
+        .call_tool(
+            &namespaced("weather", "forecast"),
+            Some(json!({ "city": "Lisbon" })),
+        )
+        .await?;
+    println!("   forecast  -> {}", summary(&forecast));
+
+    let sum = orchestrator
+        .call_tool(
+            &namespaced("calculator", "add"),
+            Some(json!({ "a": 19, "b": 23 })),
+        )
+        .await?;
+    println!("   add       -> {}", summary(&sum));
+
+    let note = orchestrator
+        .call_tool(
+            &namespaced("notes", "append_note"),
+            Some(json!({ "text": format!("Lisbon: {}", summary(&forecast)) })),
+        )
+        .await?;
+    println!("   note      -> {}", summary(&note));
+
+    println!("\n💥 Stopping the primary weather server...");
+    weather.stop();
+    let forecast = orchestrator
+        .call_tool(
+            &namespaced("weather", "forecast"),
+            Some(json!({ "city": "Porto" })),
+        )
+        .await?;
+    println!("   forecast  -> {}", summary(&forecast));
+    println!(
+        "   weather upstream now at {}",
+        orchestrator
+            .active_endpoint("weather")
+            .await
+            .unwrap_or_default()
+    );
+
+    println!("\n📊 Metrics:");
+    print!("{}", orchestrator.metrics().export_prometheus().await);
+
+    if args.serve {
+        info!(
+            "Serving the orchestrator on http://{}:{}/mcp",
+            args.host, args.port
+        );
+        UltraFastServer::new(
+            ServerInfo {
+                name: "agent-orchestrator".to_string(),
+                version: "1.0.0".to_string(),
+                description: Some("Aggregated view of the demo upstreams".to_string()),
+                authors: None,
+                homepage: None,
+                license: None,
+                repository: None,
+            },
+            ServerCapabilities {
+                tools: Some(ToolsCapability {
+                    list_changed: Some(false),
+                }),
+                ..Default::default()
+            },
+        )
+        .with_tool_handler(orchestrator.clone())
+        .run_streamable_http(&args.host, args.port)
+        .await?;
+    }
+
+    orchestrator.shutdown().await;
+    Ok(())
+}
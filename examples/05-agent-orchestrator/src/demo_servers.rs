@@ -0,0 +1,412 @@
+//! Demo upstream servers for the orchestrator
+//!
+//! Each [`DemoService`] is a tiny MCP server exposing a couple of tools over
+//! Streamable HTTP. [`DemoServer::start`] runs one on its own thread and
+//! tokio runtime so that [`DemoServer::stop`] can take it down completely,
+//! open keep-alive connections included, the way a crashed process would.
+//! [`self_test`] instead serves each in-process over an in-memory transport.
+
+use std::{
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::oneshot;
+use tracing::{error, info};
+use ultrafast_mcp::{
+    ClientCapabilities, ClientInfo, ListToolsRequest, ListToolsResponse, MCPError, MCPResult,
+    ServerCapabilities, ServerInfo, Tool, ToolAnnotations, ToolCall, ToolHandler, ToolResult,
+    ToolsCapability, UltraFastClient, UltraFastServer, duplex_pair,
+};
+
+/// The services the demo upstreams provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoService {
+    /// `forecast`: read-only lookups against an external weather service
+    Weather,
+    /// `add` and `multiply`: pure functions
+    Calculator,
+    /// `append_note` and `list_notes`: a per-instance notebook
+    Notes,
+}
+
+impl DemoService {
+    /// Name of the service, used as its namespace by the orchestrator
+    pub fn name(self) -> &'static str {
+        match self {
+            DemoService::Weather => "weather",
+            DemoService::Calculator => "calculator",
+            DemoService::Notes => "notes",
+        }
+    }
+
+    fn tools(self) -> Vec<Tool> {
+        match self {
+            DemoService::Weather => vec![
+                Tool::new(
+                    "forecast".to_string(),
+                    "Weather forecast for a city".to_string(),
+                    json!({
+                        "type": "object",
+                        "properties": { "city": { "type": "string" } },
+                        "required": ["city"]
+                    }),
+                )
+                .read_only()
+                .open_world(),
+            ],
+            DemoService::Calculator => ["add", "multiply"]
+                .into_iter()
+                .map(|operation| {
+                    Tool::new(
+                        operation.to_string(),
+                        format!("{operation} two numbers"),
+                        json!({
+                            "type": "object",
+                            "properties": {
+                                "a": { "type": "number" },
+                                "b": { "type": "number" }
+                            },
+                            "required": ["a", "b"]
+                        }),
+                    )
+                    .read_only()
+                    .closed_world()
+                })
+                .collect(),
+            DemoService::Notes => vec![
+                Tool::new(
+                    "append_note".to_string(),
+                    "Append a note to the notebook".to_string(),
+                    json!({
+                        "type": "object",
+                        "properties": { "text": { "type": "string" } },
+                        "required": ["text"]
+                    }),
+                )
+                .with_annotations(
+                    ToolAnnotations::default()
+                        .with_read_only_hint(false)
+                        .with_destructive_hint(false),
+                )
+                .closed_world(),
+                Tool::new(
+                    "list_notes".to_string(),
+                    "List the notes in the notebook".to_string(),
+                    json!({ "type": "object" }),
+                )
+                .read_only()
+                .closed_world(),
+            ],
+        }
+    }
+}
+
+/// Tool handler of a demo upstream
+///
+/// Every result names the instance that produced it, so failover is visible
+/// to the caller.
+pub struct DemoToolHandler {
+    service: DemoService,
+    instance: String,
+    calls: AtomicU64,
+    notes: Mutex<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct ForecastInput {
+    city: String,
+}
+
+#[derive(Deserialize)]
+struct OperandsInput {
+    a: f64,
+    b: f64,
+}
+
+#[derive(Deserialize)]
+struct NoteInput {
+    text: String,
+}
+
+impl DemoToolHandler {
+    pub fn new(service: DemoService, instance: impl Into<String>) -> Self {
+        Self {
+            service,
+            instance: instance.into(),
+            calls: AtomicU64::new(0),
+            notes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of tool calls this instance has handled
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    fn output(&self, value: Value) -> ToolResult {
+        let mut value = value;
+        value["servedBy"] = json!(self.instance);
+        ToolResult::structured(value)
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(arguments: Option<Value>) -> MCPResult<T> {
+    serde_json::from_value(arguments.unwrap_or_else(|| json!({})))
+        .map_err(|e| MCPError::invalid_params(format!("Invalid arguments: {e}")))
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for DemoToolHandler {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        info!("{} handling {}", self.instance, call.name);
+
+        match (self.service, call.name.as_str()) {
+            (DemoService::Weather, "forecast") => {
+                let input: ForecastInput = parse(call.arguments)?;
+                // Deterministic "forecast" so tests can assert on it
+                let temperature = 10 + input.city.len() as i64;
+                Ok(self.output(json!({
+                    "city": input.city,
+                    "temperatureC": temperature,
+                    "conditions": if temperature % 2 == 0 { "sunny" } else { "cloudy" }
+                })))
+            }
+            (DemoService::Calculator, operation @ ("add" | "multiply")) => {
+                let input: OperandsInput = parse(call.arguments)?;
+                let result = if operation == "add" {
+                    input.a + input.b
+                } else {
+                    input.a * input.b
+                };
+                Ok(self.output(json!({ "result": result })))
+            }
+            (DemoService::Notes, "append_note") => {
+                let input: NoteInput = parse(call.arguments)?;
+                let mut notes = self.notes.lock().unwrap_or_else(|e| e.into_inner());
+                notes.push(input.text);
+                Ok(self.output(json!({ "count": notes.len() })))
+            }
+            (DemoService::Notes, "list_notes") => {
+                let notes = self.notes.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                Ok(self.output(json!({ "notes": notes })))
+            }
+            _ => Err(MCPError::method_not_found(format!(
+                "Unknown tool: {}",
+                call.name
+            ))),
+        }
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        Ok(ListToolsResponse {
+            tools: self.service.tools(),
+            next_cursor: None,
+        })
+    }
+}
+
+fn demo_server(
+    service: DemoService,
+    instance: &str,
+    handler: Arc<DemoToolHandler>,
+) -> UltraFastServer {
+    UltraFastServer::new(
+        ServerInfo {
+            name: format!("{}-server", service.name()),
+            version: "1.0.0".to_string(),
+            description: Some(format!("Demo {} upstream ({instance})", service.name())),
+            authors: None,
+            homepage: None,
+            license: None,
+            repository: None,
+        },
+        ServerCapabilities {
+            tools: Some(ToolsCapability {
+                list_changed: Some(false),
+            }),
+            ..Default::default()
+        },
+    )
+    .with_tool_handler(handler)
+}
+
+/// A demo upstream running on its own thread and runtime
+pub struct DemoServer {
+    service: DemoService,
+    address: SocketAddr,
+    handler: Arc<DemoToolHandler>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DemoServer {
+    /// Start `service` on a free local port, labelling results with
+    /// `instance`, and wait until it accepts connections
+    pub fn start(service: DemoService, instance: &str) -> anyhow::Result<Self> {
+        let address = TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let handler = Arc::new(DemoToolHandler::new(service, instance));
+        let server = demo_server(service, instance, handler.clone());
+
+        let (shutdown, stopped) = oneshot::channel();
+        let thread = std::thread::Builder::new()
+            .name(format!("demo-{instance}"))
+            .spawn(move || {
+                let runtime = match tokio::runtime::Runtime::new() {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        error!("Failed to start runtime: {}", e);
+                        return;
+                    }
+                };
+                runtime.block_on(async move {
+                    tokio::select! {
+                        result = server.run_streamable_http("127.0.0.1", address.port()) => {
+                            if let Err(e) = result {
+                                error!("Demo server stopped: {}", e);
+                            }
+                        }
+                        _ = stopped => {}
+                    }
+                });
+                // Dropping the runtime aborts every connection task as well
+                runtime.shutdown_background();
+            })?;
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while TcpStream::connect(address).is_err() {
+            if Instant::now() > deadline {
+                anyhow::bail!("{instance} did not start listening on {address}");
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        Ok(Self {
+            service,
+            address,
+            handler,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+
+    pub fn service(&self) -> DemoService {
+        self.service
+    }
+
+    /// Base URL of the server, under which it serves `/mcp`
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Number of tool calls the server has handled
+    pub fn calls(&self) -> u64 {
+        self.handler.calls()
+    }
+
+    /// Take the server down, dropping all of its connections
+    pub fn stop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for DemoServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Serve each demo service in-process and call its tools through a client
+///
+/// Covers the upstreams only: pooling and failover between endpoints need
+/// real connections and are exercised over HTTP by the integration tests.
+pub async fn self_test() -> anyhow::Result<()> {
+    let plans = [
+        (
+            DemoService::Weather,
+            vec![(
+                "forecast",
+                json!({ "city": "Oslo" }),
+                json!(14),
+                "temperatureC",
+            )],
+        ),
+        (
+            DemoService::Calculator,
+            vec![
+                ("add", json!({ "a": 2, "b": 3 }), json!(5.0), "result"),
+                ("multiply", json!({ "a": 2, "b": 3 }), json!(6.0), "result"),
+            ],
+        ),
+        (
+            DemoService::Notes,
+            vec![
+                ("append_note", json!({ "text": "hello" }), json!(1), "count"),
+                ("list_notes", json!({}), json!(["hello"]), "notes"),
+            ],
+        ),
+    ];
+
+    for (service, calls) in plans {
+        let instance = format!("{}-self-test", service.name());
+        let handler = Arc::new(DemoToolHandler::new(service, instance.as_str()));
+        let server = demo_server(service, &instance, handler.clone());
+        let (client_transport, server_transport) = duplex_pair();
+        let server =
+            tokio::spawn(
+                async move { server.run_with_transport(Box::new(server_transport)).await },
+            );
+
+        let client = UltraFastClient::new(ClientInfo::default(), ClientCapabilities::default());
+        client.connect(Box::new(client_transport)).await?;
+
+        let listed = client.list_tools_default().await?.tools;
+        let expected = service.tools();
+        anyhow::ensure!(
+            listed
+                .iter()
+                .map(|tool| &tool.name)
+                .eq(expected.iter().map(|tool| &tool.name)),
+            "{} lists {listed:?}",
+            instance
+        );
+
+        let call_count = calls.len() as u64;
+        for (tool, arguments, expected, field) in calls {
+            let result = client
+                .call_tool(ToolCall {
+                    name: tool.to_string(),
+                    arguments: Some(arguments),
+                })
+                .await?;
+            let output = result.structured_content.unwrap_or_default();
+            anyhow::ensure!(
+                output[field] == expected && output["servedBy"] == instance.as_str(),
+                "{instance} answered {tool} with {output}"
+            );
+        }
+        anyhow::ensure!(
+            handler.calls() == call_count,
+            "{instance} counted {} calls",
+            handler.calls()
+        );
+
+        // The server stops once the client hangs up
+        client.disconnect().await?;
+        server.await??;
+    }
+    Ok(())
+}
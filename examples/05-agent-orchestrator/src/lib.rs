@@ -0,0 +1,25 @@
+//! Multi-server agent orchestration with UltraFast MCP
+//!
+//! An agent talks to one [`Orchestrator`], which fans its requests out to
+//! several upstream MCP servers over Streamable HTTP:
+//!
+//! - **Pooling**: one [`ultrafast_mcp::UltraFastClient`] per upstream,
+//!   connected on first use and replaced after transport failures
+//! - **Namespacing**: upstream tools are exposed as `<upstream>__<tool>`
+//! - **Failover and retries**: each upstream may list backup endpoints;
+//!   transient failures are retried with backoff, replaying calls only for
+//!   read-only or idempotent tools
+//! - **Auth**: a bearer token per upstream
+//! - **Monitoring**: every call is recorded in a
+//!   [`ultrafast_mcp::MetricsCollector`]
+//!
+//! [`demo_servers`] provides the upstreams used by the demo binary and the
+//! integration tests, and [`self_test`] checks each of them in-process.
+
+pub mod demo_servers;
+pub mod orchestrator;
+
+pub use demo_servers::{DemoServer, DemoService, DemoToolHandler, self_test};
+pub use orchestrator::{
+    FailoverPolicy, NAMESPACE_SEPARATOR, Orchestrator, UpstreamConfig, namespaced, split_namespaced,
+};
@@ -0,0 +1,414 @@
+//! One client front end for several MCP servers
+//!
+//! An [`Orchestrator`] keeps a pool of [`UltraFastClient`]s, one per
+//! upstream server, and presents their tools under a single namespace:
+//! tool `forecast` of upstream `weather` becomes `weather__forecast`.
+//!
+//! Each upstream may list several endpoints. Connection failures are retried
+//! across them with backoff; a call that fails in transit is only replayed,
+//! possibly on another endpoint, when the tool is annotated as read-only or
+//! idempotent, since the first attempt may already have run. Every call is
+//! recorded in a [`MetricsCollector`].
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+use ultrafast_mcp::{
+    AuthMethod, ClientCapabilities, ClientInfo, ConnectOptions, ListToolsRequest,
+    ListToolsResponse, MCPError, MCPResult, McpCoreError::ToolError, MetricsCollector, Tool,
+    ToolCall, ToolHandler, ToolResult, UltraFastClient,
+};
+
+/// Separator between the upstream name and the tool name
+///
+/// Tool names may only contain alphanumerics, `-` and `_`, so a double
+/// underscore is the least likely to clash with upstream tool names.
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+/// Name of `tool` of upstream `upstream` as exposed by the orchestrator
+pub fn namespaced(upstream: &str, tool: &str) -> String {
+    format!("{upstream}{NAMESPACE_SEPARATOR}{tool}")
+}
+
+/// Split a namespaced tool name into upstream and tool names
+pub fn split_namespaced(name: &str) -> Option<(&str, &str)> {
+    name.split_once(NAMESPACE_SEPARATOR)
+        .filter(|(upstream, tool)| !upstream.is_empty() && !tool.is_empty())
+}
+
+/// An upstream server and the endpoints it can be reached at
+#[derive(Debug, Clone)]
+pub struct UpstreamConfig {
+    /// Namespace of the upstream's tools
+    pub name: String,
+    /// Base URLs of Streamable HTTP servers, in order of preference
+    pub endpoints: Vec<String>,
+    /// Bearer token sent to every endpoint of the upstream
+    pub bearer_token: Option<String>,
+}
+
+impl UpstreamConfig {
+    pub fn new(name: impl Into<String>, endpoint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            endpoints: vec![endpoint.into()],
+            bearer_token: None,
+        }
+    }
+
+    /// Add an endpoint to fail over to
+    pub fn with_backup(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoints.push(endpoint.into());
+        self
+    }
+
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+}
+
+/// Retry and failover behaviour of an [`Orchestrator`]
+#[derive(Debug, Clone)]
+pub struct FailoverPolicy {
+    /// Attempts per call, including the first
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled for each further one
+    pub backoff: Duration,
+    /// Timeout of each request to an upstream
+    pub request_timeout: Duration,
+}
+
+impl Default for FailoverPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(100),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl FailoverPolicy {
+    fn delay(&self, attempt: u32, error: &MCPError) -> Duration {
+        error
+            .retry_after()
+            .unwrap_or(self.backoff * 2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+struct Connection {
+    client: Arc<UltraFastClient>,
+    endpoint: usize,
+}
+
+struct Upstream {
+    config: UpstreamConfig,
+    connection: Mutex<Option<Connection>>,
+    // Endpoint to try first on the next connect
+    next_endpoint: Mutex<usize>,
+    tools: RwLock<HashMap<String, Tool>>,
+}
+
+impl Upstream {
+    fn new(config: UpstreamConfig) -> Self {
+        Self {
+            config,
+            connection: Mutex::new(None),
+            next_endpoint: Mutex::new(0),
+            tools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The connected client, connecting to the first reachable endpoint if
+    /// there is none
+    async fn client(&self, policy: &FailoverPolicy) -> MCPResult<Arc<UltraFastClient>> {
+        let mut connection = self.connection.lock().await;
+        if let Some(connection) = connection.as_ref() {
+            return Ok(connection.client.clone());
+        }
+
+        let endpoints = &self.config.endpoints;
+        let first = *self.next_endpoint.lock().await;
+        let mut last_error = None;
+        for offset in 0..endpoints.len() {
+            let endpoint = (first + offset) % endpoints.len();
+            match self.connect(&endpoints[endpoint], policy).await {
+                Ok(client) => {
+                    info!(
+                        "Upstream {} connected to {}",
+                        self.config.name, endpoints[endpoint]
+                    );
+                    *connection = Some(Connection {
+                        client: client.clone(),
+                        endpoint,
+                    });
+                    return Ok(client);
+                }
+                Err(e) => {
+                    warn!(
+                        "Upstream {} unreachable at {}: {}",
+                        self.config.name, endpoints[endpoint], e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            MCPError::transport_error(format!("Upstream {} has no endpoints", self.config.name))
+        }))
+    }
+
+    async fn connect(
+        &self,
+        endpoint: &str,
+        policy: &FailoverPolicy,
+    ) -> MCPResult<Arc<UltraFastClient>> {
+        let client = UltraFastClient::new(
+            ClientInfo {
+                name: "agent-orchestrator".to_string(),
+                version: "1.0.0".to_string(),
+                description: Some(format!("Orchestrator client for {}", self.config.name)),
+                authors: None,
+                homepage: None,
+                license: None,
+                repository: None,
+            },
+            ClientCapabilities::default(),
+        )
+        .with_timeout(policy.request_timeout);
+
+        let options = match &self.config.bearer_token {
+            Some(token) => ConnectOptions::streamable_http(endpoint)
+                .with_auth(AuthMethod::bearer(token.clone())),
+            None => ConnectOptions::streamable_http(endpoint),
+        };
+        client.connect_with(options).await?;
+        Ok(Arc::new(client))
+    }
+
+    /// Drop the connection of `client` after it failed, so that the next
+    /// call connects again starting from the following endpoint
+    async fn fail(&self, client: &Arc<UltraFastClient>) {
+        let mut connection = self.connection.lock().await;
+        let Some(current) = connection.take_if(|c| Arc::ptr_eq(&c.client, client)) else {
+            return;
+        };
+        *self.next_endpoint.lock().await = (current.endpoint + 1) % self.config.endpoints.len();
+        tokio::spawn(async move {
+            let _ = current.client.disconnect().await;
+        });
+    }
+
+    async fn active_endpoint(&self) -> Option<String> {
+        self.connection
+            .lock()
+            .await
+            .as_ref()
+            .map(|connection| self.config.endpoints[connection.endpoint].clone())
+    }
+
+    /// Whether replaying a call of `tool` is harmless
+    async fn is_retry_safe(&self, tool: &str) -> bool {
+        self.tools
+            .read()
+            .await
+            .get(tool)
+            .and_then(|tool| tool.annotations.as_ref())
+            .is_some_and(|annotations| annotations.is_idempotent())
+    }
+
+    async fn list_tools(&self, policy: &FailoverPolicy) -> MCPResult<Vec<Tool>> {
+        let tools = self
+            .with_retries(policy, true, |client| async move {
+                client.list_tools_default().await
+            })
+            .await?
+            .tools;
+        *self.tools.write().await = tools
+            .iter()
+            .map(|tool| (tool.name.clone(), tool.clone()))
+            .collect();
+        Ok(tools)
+    }
+
+    /// Run `operation` against the upstream, reconnecting and retrying on
+    /// transient failures
+    ///
+    /// Failures to connect are always retried; failures of `operation` only
+    /// when `replayable`.
+    async fn with_retries<T, F, Fut>(
+        &self,
+        policy: &FailoverPolicy,
+        replayable: bool,
+        operation: F,
+    ) -> MCPResult<T>
+    where
+        F: Fn(Arc<UltraFastClient>) -> Fut,
+        Fut: Future<Output = MCPResult<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let (error, sent) = match self.client(policy).await {
+                Ok(client) => match operation(client.clone()).await {
+                    Ok(value) => return Ok(value),
+                    Err(e) => {
+                        if e.is_retryable() {
+                            self.fail(&client).await;
+                        }
+                        (e, true)
+                    }
+                },
+                Err(e) => (e, false),
+            };
+            if attempt >= policy.max_attempts || !error.is_retryable() || (sent && !replayable) {
+                return Err(error);
+            }
+            let delay = policy.delay(attempt, &error);
+            warn!(
+                "Upstream {} attempt {} failed ({}), retrying in {:?}",
+                self.config.name, attempt, error, delay
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Client front end for several upstream MCP servers
+pub struct Orchestrator {
+    upstreams: HashMap<String, Arc<Upstream>>,
+    // Upstream names in configuration order, for stable listings
+    order: Vec<String>,
+    policy: FailoverPolicy,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl Orchestrator {
+    pub fn new(upstreams: Vec<UpstreamConfig>) -> Self {
+        let order = upstreams.iter().map(|u| u.name.clone()).collect();
+        Self {
+            upstreams: upstreams
+                .into_iter()
+                .map(|config| (config.name.clone(), Arc::new(Upstream::new(config))))
+                .collect(),
+            order,
+            policy: FailoverPolicy::default(),
+            metrics: Arc::new(MetricsCollector::new()),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: FailoverPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Metrics of the calls made through the orchestrator, keyed by
+    /// namespaced method such as `tools/call:weather__forecast`
+    pub fn metrics(&self) -> Arc<MetricsCollector> {
+        self.metrics.clone()
+    }
+
+    /// Connect to every upstream and learn its tools
+    ///
+    /// Fails if any upstream is unreachable at all of its endpoints.
+    pub async fn connect(&self) -> MCPResult<()> {
+        self.list_tools().await.map(|_| ())
+    }
+
+    /// Tools of all upstreams under their namespaced names
+    pub async fn list_tools(&self) -> MCPResult<Vec<Tool>> {
+        let started = Instant::now();
+        let mut tools = Vec::new();
+        let mut result = Ok(());
+        for name in &self.order {
+            match self.upstreams[name].list_tools(&self.policy).await {
+                Ok(upstream_tools) => tools.extend(upstream_tools.into_iter().map(|mut tool| {
+                    tool.name = namespaced(name, &tool.name);
+                    tool
+                })),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.metrics
+            .record_request("tools/list", started.elapsed(), result.is_ok())
+            .await;
+        result.map(|()| tools)
+    }
+
+    /// Call a tool by its namespaced name
+    pub async fn call_tool(&self, name: &str, arguments: Option<Value>) -> MCPResult<ToolResult> {
+        let (upstream_name, tool) = split_namespaced(name)
+            .ok_or_else(|| MCPError::ToolExecution(ToolError::NotFound(name.to_string())))?;
+        let upstream = self
+            .upstreams
+            .get(upstream_name)
+            .ok_or_else(|| MCPError::ToolExecution(ToolError::NotFound(name.to_string())))?;
+
+        let started = Instant::now();
+        let replayable = upstream.is_retry_safe(tool).await;
+        let result = upstream
+            .with_retries(&self.policy, replayable, |client| {
+                let call = ToolCall {
+                    name: tool.to_string(),
+                    arguments: arguments.clone(),
+                };
+                async move { client.call_tool(call).await }
+            })
+            .await;
+        self.metrics
+            .record_request(
+                &format!("tools/call:{name}"),
+                started.elapsed(),
+                result.is_ok(),
+            )
+            .await;
+        result
+    }
+
+    /// Endpoint an upstream is currently connected to
+    pub async fn active_endpoint(&self, upstream: &str) -> Option<String> {
+        self.upstreams.get(upstream)?.active_endpoint().await
+    }
+
+    /// Disconnect from every upstream
+    pub async fn shutdown(&self) {
+        for upstream in self.upstreams.values() {
+            if let Some(connection) = upstream.connection.lock().await.take() {
+                if let Err(e) = connection.client.disconnect().await {
+                    warn!("Failed to disconnect from {}: {}", upstream.config.name, e);
+                }
+            }
+        }
+    }
+}
+
+/// The orchestrator is itself a tool handler, so it can be served as a
+/// single MCP server aggregating its upstreams
+#[async_trait::async_trait]
+impl ToolHandler for Orchestrator {
+    async fn handle_tool_call(&self, call: ToolCall) -> MCPResult<ToolResult> {
+        self.call_tool(&call.name, call.arguments).await
+    }
+
+    async fn list_tools(&self, _request: ListToolsRequest) -> MCPResult<ListToolsResponse> {
+        Ok(ListToolsResponse {
+            tools: Orchestrator::list_tools(self).await?,
+            next_cursor: None,
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use agent_orchestrator_example::{
+    DemoServer, DemoService, FailoverPolicy, Orchestrator, UpstreamConfig, namespaced,
+};
+use serde_json::{Value, json};
+use ultrafast_mcp::{ListToolsRequest, ToolCall, ToolHandler, ToolResult};
+
+fn policy() -> FailoverPolicy {
+    FailoverPolicy {
+        max_attempts: 3,
+        backoff: Duration::from_millis(20),
+        request_timeout: Duration::from_secs(2),
+    }
+}
+
+fn output(result: &ToolResult) -> &Value {
+    result
+        .structured_content
+        .as_ref()
+        .expect("demo tools return structured content")
+}
+
+/// An endpoint nothing listens on
+fn dead_endpoint() -> String {
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    format!("http://{address}")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tools_are_namespaced_and_routed() {
+    let weather = DemoServer::start(DemoService::Weather, "weather").unwrap();
+    let calculator = DemoServer::start(DemoService::Calculator, "calculator").unwrap();
+    let notes = DemoServer::start(DemoService::Notes, "notes").unwrap();
+    let orchestrator = Orchestrator::new(vec![
+        UpstreamConfig::new("weather", weather.endpoint()),
+        UpstreamConfig::new("calculator", calculator.endpoint()),
+        UpstreamConfig::new("notes", notes.endpoint()).with_bearer_token("notes-token"),
+    ])
+    .with_policy(policy());
+    orchestrator.connect().await.unwrap();
+
+    let tools = ToolHandler::list_tools(&orchestrator, ListToolsRequest::default())
+        .await
+        .unwrap()
+        .tools;
+    let names: Vec<_> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "weather__forecast",
+            "calculator__add",
+            "calculator__multiply",
+            "notes__append_note",
+            "notes__list_notes"
+        ]
+    );
+    let forecast = &tools[0].annotations.as_ref().unwrap();
+    assert!(forecast.is_read_only());
+    assert!(forecast.is_open_world());
+
+    let product = orchestrator
+        .handle_tool_call(ToolCall {
+            name: namespaced("calculator", "multiply"),
+            arguments: Some(json!({ "a": 6, "b": 7 })),
+        })
+        .await
+        .unwrap();
+    assert_eq!(output(&product)["result"], json!(42.0));
+    assert_eq!(output(&product)["servedBy"], "calculator");
+
+    let note = orchestrator
+        .call_tool(
+            &namespaced("notes", "append_note"),
+            Some(json!({ "text": "hello" })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(output(&note)["count"], 1);
+    assert_eq!(calculator.calls(), 1);
+    assert_eq!(notes.calls(), 1);
+    assert_eq!(weather.calls(), 0);
+
+    for unknown in ["weather_forecast", "ghost__forecast", "__forecast"] {
+        let error = orchestrator.call_tool(unknown, None).await.unwrap_err();
+        assert!(!error.is_retryable(), "{unknown}: {error}");
+    }
+
+    let metrics = orchestrator.metrics().get_metrics().await;
+    assert_eq!(metrics.request.total_requests, 4);
+    assert_eq!(metrics.request.successful_requests, 4);
+
+    orchestrator.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_only_calls_fail_over_to_backup() {
+    let mut primary = DemoServer::start(DemoService::Weather, "primary").unwrap();
+    let backup = DemoServer::start(DemoService::Weather, "backup").unwrap();
+    let orchestrator = Orchestrator::new(vec![
+        UpstreamConfig::new("weather", primary.endpoint()).with_backup(backup.endpoint()),
+    ])
+    .with_policy(policy());
+    orchestrator.connect().await.unwrap();
+
+    let forecast = namespaced("weather", "forecast");
+    let arguments = Some(json!({ "city": "Oslo" }));
+    let result = orchestrator
+        .call_tool(&forecast, arguments.clone())
+        .await
+        .unwrap();
+    assert_eq!(output(&result)["servedBy"], "primary");
+
+    primary.stop();
+    let result = orchestrator.call_tool(&forecast, arguments).await.unwrap();
+    assert_eq!(output(&result)["servedBy"], "backup");
+    assert_eq!(output(&result)["city"], "Oslo");
+    assert_eq!(
+        orchestrator.active_endpoint("weather").await,
+        Some(backup.endpoint())
+    );
+
+    orchestrator.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unreachable_endpoints_are_skipped_on_connect() {
+    let backup = DemoServer::start(DemoService::Calculator, "backup").unwrap();
+    let orchestrator = Orchestrator::new(vec![
+        UpstreamConfig::new("calculator", dead_endpoint()).with_backup(backup.endpoint()),
+    ])
+    .with_policy(policy());
+    orchestrator.connect().await.unwrap();
+    assert_eq!(
+        orchestrator.active_endpoint("calculator").await,
+        Some(backup.endpoint())
+    );
+
+    let unreachable = Orchestrator::new(vec![UpstreamConfig::new("calculator", dead_endpoint())])
+        .with_policy(policy());
+    let error = unreachable.connect().await.unwrap_err();
+    assert!(error.is_transport(), "{error}");
+
+    orchestrator.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_non_idempotent_calls_are_not_replayed() {
+    let mut primary = DemoServer::start(DemoService::Notes, "primary").unwrap();
+    let backup = DemoServer::start(DemoService::Notes, "backup").unwrap();
+    let orchestrator = Orchestrator::new(vec![
+        UpstreamConfig::new("notes", primary.endpoint()).with_backup(backup.endpoint()),
+    ])
+    .with_policy(policy());
+    orchestrator.connect().await.unwrap();
+
+    let append = namespaced("notes", "append_note");
+    orchestrator
+        .call_tool(&append, Some(json!({ "text": "first" })))
+        .await
+        .unwrap();
+
+    primary.stop();
+    let error = orchestrator
+        .call_tool(&append, Some(json!({ "text": "second" })))
+        .await
+        .unwrap_err();
+    assert!(error.is_retryable(), "{error}");
+    assert_eq!(backup.calls(), 0);
+
+    // The failed connection was dropped, so the next call uses the backup
+    let notes = orchestrator
+        .call_tool(&namespaced("notes", "list_notes"), None)
+        .await
+        .unwrap();
+    assert_eq!(output(&notes)["servedBy"], "backup");
+    assert_eq!(output(&notes)["notes"], json!([]));
+
+    orchestrator.shutdown().await;
+}
@@ -0,0 +1,6 @@
+//! Examples as integration tests
+//!
+//! Every example under `examples/` exposes a self-test that serves it over
+//! an in-memory duplex transport and drives it with a real client, without
+//! child processes or ports. The tests in this crate run each of them, so a
+//! change that breaks an example fails `cargo test --workspace`.
@@ -0,0 +1,26 @@
+//! One test per example, each running its in-process self-test
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basic_echo() -> anyhow::Result<()> {
+    basic_echo_example::self_test().await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_file_operations() -> anyhow::Result<()> {
+    file_operations_example::self_test().await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_everything_server() -> anyhow::Result<()> {
+    everything_server_streamable_http::self_test().await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_authentication() -> anyhow::Result<()> {
+    authentication_example::run_examples().await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_agent_orchestrator_upstreams() -> anyhow::Result<()> {
+    agent_orchestrator_example::self_test().await
+}
@@ -72,6 +72,8 @@ mod tests {
                             start.elapsed().as_secs_f64()
                         ))],
                         is_error: Some(false),
+                        structured_content: None,
+                        progress_summary: None,
                     })
                 }
                 "echo" => {
@@ -84,6 +86,8 @@ mod tests {
                     Ok(ToolResult {
                         content: vec![ToolContent::text(message)],
                         is_error: Some(false),
+                        structured_content: None,
+                        progress_summary: None,
                     })
                 }
                 _ => Err(MCPError::method_not_found(format!(
@@ -31,6 +31,8 @@ mod tests {
                     Ok(ultrafast_mcp_core::types::tools::ToolResult {
                         content: vec![ultrafast_mcp_core::types::tools::ToolContent::text(message)],
                         is_error: Some(false),
+                        structured_content: None,
+                        progress_summary: None,
                     })
                 }
                 "calculator" => {
@@ -57,6 +59,8 @@ mod tests {
                     Ok(ultrafast_mcp_core::types::tools::ToolResult {
                         content: vec![ultrafast_mcp_core::types::tools::ToolContent::text(result)],
                         is_error: Some(false),
+                        structured_content: None,
+                        progress_summary: None,
                     })
                 }
                 _ => Err(MCPError::method_not_found(format!(
@@ -8,4 +8,5 @@ pub mod completion_tests;
 pub mod http_integration_tests;
 pub mod integration_tests;
 pub mod mcp_compliance_tests;
+pub mod oauth_mock_tests;
 pub mod test_ergonomic_api;
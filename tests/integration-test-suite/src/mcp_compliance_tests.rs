@@ -44,6 +44,8 @@ mod tests {
                     Ok(ToolResult {
                         content: vec![ToolContent::text(message)],
                         is_error: Some(false),
+                        structured_content: None,
+                        progress_summary: None,
                     })
                 }
                 _ => Err(MCPError::method_not_found(format!(
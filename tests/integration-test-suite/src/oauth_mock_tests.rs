@@ -0,0 +1,286 @@
+//! OAuth client tests against the mock authorization server
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ultrafast_mcp_auth::{
+        OAuthClient, generate_pkce_params, oauth::AuthorizationUrlParams, types::PkceParams,
+    };
+    use ultrafast_mcp_test_utils::{
+        MOCK_CLIENT_ID, MOCK_CLIENT_SECRET, MockOAuthServer, OAuthEndpoint, OAuthFailure,
+    };
+
+    const REDIRECT_URI: &str = "http://127.0.0.1:9/callback";
+
+    fn client(server: &MockOAuthServer) -> OAuthClient {
+        OAuthClient::new(
+            MOCK_CLIENT_ID.to_string(),
+            MOCK_CLIENT_SECRET.to_string(),
+            server.authorization_endpoint(),
+        )
+    }
+
+    /// Query parameters of the redirect `/authorize` answers with
+    async fn authorize(
+        server: &MockOAuthServer,
+        pkce: &PkceParams,
+        state: &str,
+    ) -> HashMap<String, String> {
+        let url = client(server)
+            .build_authorization_url(&AuthorizationUrlParams {
+                authorization_endpoint: server.authorization_endpoint(),
+                client_id: MOCK_CLIENT_ID.to_string(),
+                redirect_uri: REDIRECT_URI.to_string(),
+                scopes: vec!["tools:read".to_string()],
+                state: state.to_string(),
+                code_challenge: pkce.code_challenge.clone(),
+                code_challenge_method: pkce.code_challenge_method.clone(),
+                audience: None,
+            })
+            .unwrap();
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        let response = http.get(url).send().await.unwrap();
+        assert!(response.status().is_redirection());
+        let location = response.headers()["location"].to_str().unwrap();
+        let location = url::Url::parse(location).unwrap();
+        assert!(location.as_str().starts_with(REDIRECT_URI));
+        location.query_pairs().into_owned().collect()
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_flow_with_refresh_and_introspection() {
+        let server = MockOAuthServer::start().await.unwrap();
+        let client = client(&server);
+
+        let metadata = client
+            .discover_server_metadata(&server.url())
+            .await
+            .unwrap();
+        assert_eq!(metadata.issuer, server.url());
+        assert_eq!(metadata.token_endpoint, server.token_endpoint());
+        assert_eq!(
+            metadata.code_challenge_methods_supported,
+            Some(vec!["S256".to_string()])
+        );
+
+        let pkce = generate_pkce_params().unwrap();
+        let redirect = authorize(&server, &pkce, "state-1").await;
+        assert_eq!(redirect["state"], "state-1");
+        let code = &redirect["code"];
+
+        let tokens = client
+            .exchange_code_for_token(
+                &metadata.token_endpoint,
+                MOCK_CLIENT_ID,
+                Some(MOCK_CLIENT_SECRET),
+                REDIRECT_URI,
+                code,
+                &pkce.code_verifier,
+            )
+            .await
+            .unwrap();
+        assert_eq!(tokens.token_type, "Bearer");
+        assert_eq!(tokens.scope.as_deref(), Some("tools:read"));
+
+        // Codes are single use
+        assert!(
+            client
+                .exchange_code_for_token(
+                    &metadata.token_endpoint,
+                    MOCK_CLIENT_ID,
+                    Some(MOCK_CLIENT_SECRET),
+                    REDIRECT_URI,
+                    code,
+                    &pkce.code_verifier,
+                )
+                .await
+                .is_err()
+        );
+
+        let introspection = client
+            .introspect_token(
+                &server.introspection_endpoint(),
+                &tokens.access_token,
+                MOCK_CLIENT_ID,
+                Some(MOCK_CLIENT_SECRET),
+            )
+            .await
+            .unwrap();
+        assert_eq!(introspection["active"], true);
+        assert_eq!(introspection["client_id"], MOCK_CLIENT_ID);
+
+        let refresh_token = tokens.refresh_token.unwrap();
+        let refreshed = client
+            .refresh_token(
+                &metadata.token_endpoint,
+                MOCK_CLIENT_ID,
+                Some(MOCK_CLIENT_SECRET),
+                &refresh_token,
+            )
+            .await
+            .unwrap();
+        assert_ne!(refreshed.access_token, tokens.access_token);
+        // Refresh tokens are rotated
+        assert!(
+            client
+                .refresh_token(
+                    &metadata.token_endpoint,
+                    MOCK_CLIENT_ID,
+                    Some(MOCK_CLIENT_SECRET),
+                    &refresh_token,
+                )
+                .await
+                .is_err()
+        );
+
+        server.revoke(&refreshed.access_token);
+        let introspection = client
+            .introspect_token(
+                &server.introspection_endpoint(),
+                &refreshed.access_token,
+                MOCK_CLIENT_ID,
+                Some(MOCK_CLIENT_SECRET),
+            )
+            .await
+            .unwrap();
+        assert_eq!(introspection["active"], false);
+    }
+
+    #[tokio::test]
+    async fn test_pkce_and_client_authentication_are_enforced() {
+        let server = MockOAuthServer::start().await.unwrap();
+        let client = client(&server);
+        let pkce = generate_pkce_params().unwrap();
+
+        let code = server.issue_code(MOCK_CLIENT_ID, REDIRECT_URI, &pkce.code_challenge, None);
+        let other = generate_pkce_params().unwrap();
+        assert!(
+            client
+                .exchange_code_for_token(
+                    &server.token_endpoint(),
+                    MOCK_CLIENT_ID,
+                    Some(MOCK_CLIENT_SECRET),
+                    REDIRECT_URI,
+                    &code,
+                    &other.code_verifier,
+                )
+                .await
+                .is_err()
+        );
+
+        let code = server.issue_code(MOCK_CLIENT_ID, REDIRECT_URI, &pkce.code_challenge, None);
+        assert!(
+            client
+                .exchange_code_for_token(
+                    &server.token_endpoint(),
+                    MOCK_CLIENT_ID,
+                    Some("wrong-secret"),
+                    REDIRECT_URI,
+                    &code,
+                    &pkce.code_verifier,
+                )
+                .await
+                .is_err()
+        );
+
+        // Public clients authenticate with PKCE alone
+        server.register_client("public-client", None);
+        let code = server.issue_code("public-client", REDIRECT_URI, &pkce.code_challenge, None);
+        assert!(
+            client
+                .exchange_code_for_token(
+                    &server.token_endpoint(),
+                    "public-client",
+                    None,
+                    REDIRECT_URI,
+                    &code,
+                    &pkce.code_verifier,
+                )
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_injected_failures() {
+        let server = MockOAuthServer::start().await.unwrap();
+        let client = client(&server);
+        let pkce = generate_pkce_params().unwrap();
+
+        server.fail_always(OAuthEndpoint::Metadata, OAuthFailure::ServerError);
+        assert!(
+            client
+                .discover_server_metadata(&server.url())
+                .await
+                .is_err()
+        );
+        assert!(
+            client
+                .discover_server_metadata(&server.url())
+                .await
+                .is_err()
+        );
+        server.clear_failures();
+        assert!(client.discover_server_metadata(&server.url()).await.is_ok());
+        assert_eq!(server.request_count(OAuthEndpoint::Metadata), 3);
+
+        server.fail_next(
+            OAuthEndpoint::Authorize,
+            OAuthFailure::Error("access_denied".to_string()),
+        );
+        let redirect = authorize(&server, &pkce, "state-2").await;
+        assert_eq!(redirect["error"], "access_denied");
+        assert_eq!(redirect["state"], "state-2");
+        assert!(!redirect.contains_key("code"));
+
+        // A failed exchange leaves the code usable
+        let code = authorize(&server, &pkce, "state-3").await["code"].clone();
+        server.fail_next(
+            OAuthEndpoint::Token,
+            OAuthFailure::Error("temporarily_unavailable".to_string()),
+        );
+        let token_endpoint = server.token_endpoint();
+        let exchange = || {
+            client.exchange_code_for_token(
+                &token_endpoint,
+                MOCK_CLIENT_ID,
+                Some(MOCK_CLIENT_SECRET),
+                REDIRECT_URI,
+                &code,
+                &pkce.code_verifier,
+            )
+        };
+        assert!(exchange().await.is_err());
+        let tokens = exchange().await.unwrap();
+
+        server.fail_next(OAuthEndpoint::Introspect, OAuthFailure::MalformedResponse);
+        assert!(
+            client
+                .introspect_token(
+                    &server.introspection_endpoint(),
+                    &tokens.access_token,
+                    MOCK_CLIENT_ID,
+                    Some(MOCK_CLIENT_SECRET),
+                )
+                .await
+                .is_err()
+        );
+
+        server.set_token_lifetime(std::time::Duration::ZERO);
+        let expired = server.issue_access_token(None);
+        let introspection = client
+            .introspect_token(
+                &server.introspection_endpoint(),
+                &expired,
+                MOCK_CLIENT_ID,
+                Some(MOCK_CLIENT_SECRET),
+            )
+            .await
+            .unwrap();
+        assert_eq!(introspection["active"], false);
+    }
+}
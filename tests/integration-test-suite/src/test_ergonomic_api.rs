@@ -64,6 +64,8 @@ mod tests {
                     Ok(ToolResult {
                         content: vec![ToolContent::text(response_text)],
                         is_error: None,
+                        structured_content: None,
+                        progress_summary: None,
                     })
                 }
                 _ => Err(MCPError::method_not_found(format!(
@@ -291,9 +293,7 @@ mod tests {
         assert_eq!(list_response.resources[0].uri, "test://status");
 
         // Test resource reading
-        let read_request = ReadResourceRequest {
-            uri: "test://status".to_string(),
-        };
+        let read_request = ReadResourceRequest::new("test://status");
         let read_response = handler.read_resource(read_request).await.unwrap();
         assert_eq!(read_response.contents.len(), 1);
         if let ResourceContent::Text {
@@ -310,9 +310,7 @@ mod tests {
         }
 
         // Test unknown resource
-        let unknown_request = ReadResourceRequest {
-            uri: "test://unknown".to_string(),
-        };
+        let unknown_request = ReadResourceRequest::new("test://unknown");
         let error = handler.read_resource(unknown_request).await.unwrap_err();
         assert!(error.to_string().contains("Resource not found"));
 